@@ -0,0 +1,274 @@
+//! Database Backups
+//!
+//! Copying the live SQLite file can catch it in the middle of a write.
+//! [`create_backup`] has SQLite write a consistent copy with `VACUUM INTO`
+//! instead, to a timestamped file in `BACKUP_DIR`, and then deletes the
+//! backups older than `BACKUP_RETENTION_DAYS`.
+//!
+//! Runs through `POST /api/admin/backup` and the `backup_db` binary;
+//! `GET /api/admin/backups` lists the backups and downloads one.
+
+use crate::db::DbPool;
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::fmt;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+
+const PREFIX: &str = "backup-";
+const EXTENSION: &str = ".db";
+
+/// A backup file in the backup directory.
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupFile {
+    pub name: String,
+    pub size_bytes: u64,
+    pub created_at: String,
+}
+
+/// Outcome of one [`create_backup`] run.
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupReport {
+    #[serde(flatten)]
+    pub backup: BackupFile,
+    /// Where the backup was written.
+    pub path: String,
+    /// Backups deleted for being older than the retention period.
+    pub pruned: Vec<String>,
+}
+
+/// Why a backup failed.
+#[derive(Debug)]
+pub enum BackupError {
+    Database(sqlx::Error),
+    Io(std::io::Error),
+}
+
+impl fmt::Display for BackupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BackupError::Database(err) => write!(f, "database error: {err}"),
+            BackupError::Io(err) => write!(f, "backup I/O error: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for BackupError {}
+
+impl From<sqlx::Error> for BackupError {
+    fn from(err: sqlx::Error) -> Self {
+        BackupError::Database(err)
+    }
+}
+
+impl From<std::io::Error> for BackupError {
+    fn from(err: std::io::Error) -> Self {
+        BackupError::Io(err)
+    }
+}
+
+/// Whether `name` is a file name [`create_backup`] writes:
+/// `backup-<digits and dashes>.db`. Nothing else in the backup directory
+/// is listed, served or pruned.
+pub fn is_backup_name(name: &str) -> bool {
+    name.strip_prefix(PREFIX)
+        .and_then(|rest| rest.strip_suffix(EXTENSION))
+        .is_some_and(|stamp| {
+            !stamp.is_empty()
+                && stamp
+                    .bytes()
+                    .all(|byte| byte.is_ascii_digit() || byte == b'-')
+        })
+}
+
+/// Writes a backup of the database to `dir`, which is created if needed,
+/// then deletes the backups there older than `retention_days`.
+pub async fn create_backup(
+    pool: &DbPool,
+    dir: &Path,
+    retention_days: u32,
+) -> Result<BackupReport, BackupError> {
+    fs::create_dir_all(dir).await?;
+
+    let stamp = Utc::now().format("%Y%m%d-%H%M%S").to_string();
+    let mut name = format!("{PREFIX}{stamp}{EXTENSION}");
+    let mut suffix = 1;
+    // VACUUM INTO refuses to overwrite; a second backup in the same second
+    // gets a counter
+    while fs::try_exists(dir.join(&name)).await? {
+        suffix += 1;
+        name = format!("{PREFIX}{stamp}-{suffix}{EXTENSION}");
+    }
+    let path = dir.join(&name);
+
+    sqlx::query("VACUUM INTO $1")
+        .bind(path.to_string_lossy().into_owned())
+        .execute(pool)
+        .await?;
+
+    let metadata = fs::metadata(&path).await?;
+    let pruned = prune_backups(dir, retention_days).await?;
+    Ok(BackupReport {
+        backup: BackupFile {
+            name,
+            size_bytes: metadata.len(),
+            created_at: modified_at(&metadata)?.to_rfc3339(),
+        },
+        path: path.display().to_string(),
+        pruned,
+    })
+}
+
+/// The backups in `dir`, newest first. A missing directory has none.
+pub async fn list_backups(dir: &Path) -> Result<Vec<BackupFile>, std::io::Error> {
+    let mut entries = match fs::read_dir(dir).await {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err),
+    };
+    let mut backups = Vec::new();
+    while let Some(entry) = entries.next_entry().await? {
+        let Ok(name) = entry.file_name().into_string() else {
+            continue;
+        };
+        let metadata = fs::symlink_metadata(entry.path()).await?;
+        if !metadata.is_file() || !is_backup_name(&name) {
+            continue;
+        }
+        backups.push((modified_at(&metadata)?, name, metadata.len()));
+    }
+    backups.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| b.1.cmp(&a.1)));
+    Ok(backups
+        .into_iter()
+        .map(|(modified, name, size_bytes)| BackupFile {
+            name,
+            size_bytes,
+            created_at: modified.to_rfc3339(),
+        })
+        .collect())
+}
+
+/// Path of the backup `name` in `dir`, if `name` is a backup file name and
+/// there is such a file.
+pub async fn backup_path(dir: &Path, name: &str) -> Result<Option<PathBuf>, std::io::Error> {
+    if !is_backup_name(name) {
+        return Ok(None);
+    }
+    let path = dir.join(name);
+    match fs::symlink_metadata(&path).await {
+        Ok(metadata) if metadata.is_file() => Ok(Some(path)),
+        Ok(_) => Ok(None),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// Deletes the backups in `dir` older than `retention_days` and returns
+/// their names.
+pub async fn prune_backups(dir: &Path, retention_days: u32) -> Result<Vec<String>, std::io::Error> {
+    let cutoff = Utc::now() - Duration::days(i64::from(retention_days));
+    let mut pruned = Vec::new();
+    for backup in list_backups(dir).await? {
+        let expired =
+            DateTime::parse_from_rfc3339(&backup.created_at).is_ok_and(|created| created < cutoff);
+        if !expired {
+            continue;
+        }
+        match fs::remove_file(dir.join(&backup.name)).await {
+            Ok(()) => pruned.push(backup.name),
+            Err(err) if err.kind() == ErrorKind::NotFound => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(pruned)
+}
+
+fn modified_at(metadata: &std::fs::Metadata) -> Result<DateTime<Utc>, std::io::Error> {
+    Ok(metadata.modified()?.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+
+    #[test]
+    fn only_generated_names_are_backups() {
+        assert!(is_backup_name("backup-20261016-181538.db"));
+        assert!(is_backup_name("backup-20261016-181538-2.db"));
+        assert!(!is_backup_name("backup-.db"));
+        assert!(!is_backup_name("backup-../database.db"));
+        assert!(!is_backup_name("database.db"));
+        assert!(!is_backup_name("backup-20261016.db-wal"));
+    }
+
+    #[tokio::test]
+    async fn backups_are_readable_copies_and_expire() {
+        // VACUUM INTO writes nothing for an in-memory database
+        let dir = std::env::temp_dir().join(format!("minos-backup-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(
+                SqliteConnectOptions::new()
+                    .filename(dir.join("live.sqlite"))
+                    .create_if_missing(true),
+            )
+            .await
+            .expect("create sqlite pool");
+        sqlx::query("CREATE TABLE notes (body TEXT NOT NULL)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO notes (body) VALUES ('kept')")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let first = create_backup(&pool, &dir, 30).await.expect("first backup");
+        let second = create_backup(&pool, &dir, 30).await.expect("second backup");
+        assert_ne!(first.backup.name, second.backup.name);
+        assert!(first.backup.size_bytes > 0);
+        assert!(second.pruned.is_empty());
+
+        let copy = SqlitePoolOptions::new()
+            .connect(&format!("sqlite:{}?mode=ro", second.path))
+            .await
+            .expect("open backup");
+        let body: String = sqlx::query_scalar("SELECT body FROM notes")
+            .fetch_one(&copy)
+            .await
+            .expect("read backup");
+        assert_eq!(body, "kept");
+        copy.close().await;
+
+        let listed: Vec<String> = list_backups(&dir)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|backup| backup.name)
+            .collect();
+        assert_eq!(listed.len(), 2);
+        assert!(backup_path(&dir, &first.backup.name)
+            .await
+            .unwrap()
+            .is_some());
+        assert!(backup_path(&dir, "../database.db").await.unwrap().is_none());
+
+        // Backdate the first backup past a one-day retention period
+        let old = std::time::SystemTime::now() - std::time::Duration::from_secs(3 * 24 * 3600);
+        std::fs::File::options()
+            .write(true)
+            .open(dir.join(&first.backup.name))
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+        let pruned = prune_backups(&dir, 1).await.unwrap();
+        assert_eq!(pruned, [first.backup.name]);
+        assert_eq!(list_backups(&dir).await.unwrap().len(), 1);
+
+        pool.close().await;
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,45 @@
+/**
+ * Database Backup Utility
+ *
+ * Writes a consistent copy of the database to `BACKUP_DIR` with SQLite's
+ * `VACUUM INTO`, which is safe while the server is running, and deletes
+ * the backups older than `BACKUP_RETENTION_DAYS`.
+ *
+ * Usage:
+ * ```bash
+ * cargo run --bin backup_db
+ * ```
+ *
+ * The work is done by `minos_backend::backup`, which also serves
+ * `POST /api/admin/backup`.
+ */
+use anyhow::{Context, Result};
+
+use minos_backend::{backup, config, db};
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenvy::dotenv().ok();
+
+    let pool = db::create_pool()
+        .await
+        .context("Failed to connect to database. Is DATABASE_URL set correctly?")?;
+    let config = config::get();
+
+    let report = backup::create_backup(&pool, &config.backup_dir, config.backup_retention_days)
+        .await
+        .context("Failed to back up the database")?;
+
+    for name in &report.pruned {
+        println!("  pruned {name}");
+    }
+    println!(
+        "Backed up the database to {} ({} bytes); {} backups older than {} days pruned",
+        report.path,
+        report.backup.size_bytes,
+        report.pruned.len(),
+        config.backup_retention_days
+    );
+
+    Ok(())
+}
@@ -0,0 +1,70 @@
+/**
+ * Orphaned Upload Cleanup Utility
+ *
+ * Lists the uploads and stored files that no tutorial, post, page or site
+ * content references any more, and deletes them with `--delete`. Files and
+ * uploads younger than 24 hours are always left alone.
+ *
+ * Usage:
+ * ```bash
+ * cargo run --bin cleanup_uploads             # report only
+ * cargo run --bin cleanup_uploads -- --delete
+ * ```
+ *
+ * The work is done by `minos_backend::upload_cleanup`, which also serves
+ * `POST /api/admin/uploads/cleanup`. Uploads are looked up in the store
+ * configured by `STORAGE_BACKEND` (or `UPLOAD_DIR`), as the server does.
+ */
+use std::env;
+
+use anyhow::{anyhow, Context, Result};
+
+use minos_backend::{config, db, storage, upload_cleanup};
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenvy::dotenv().ok();
+
+    let delete = match env::args().nth(1).as_deref() {
+        None => false,
+        Some("--delete") => true,
+        Some(other) => return Err(anyhow!("Unknown argument '{other}'; use --delete")),
+    };
+
+    let pool = db::create_pool()
+        .await
+        .context("Failed to connect to database. Is DATABASE_URL set correctly?")?;
+    let store = storage::from_config(config::get());
+
+    let report = upload_cleanup::clean_up(&pool, store.as_ref(), !delete)
+        .await
+        .context("Failed to clean up uploads")?;
+
+    for upload in &report.uploads {
+        println!(
+            "  upload {} ({}, {} bytes, {})",
+            upload.filename, upload.original_name, upload.size_bytes, upload.created_at
+        );
+    }
+    for file in &report.files {
+        println!(
+            "  file {} ({} bytes, {})",
+            file.key, file.size_bytes, file.modified
+        );
+    }
+    println!(
+        "{}: {} uploads, {} unrecorded files, {} bytes; {} younger than {} hours skipped",
+        if delete {
+            "Deleted"
+        } else {
+            "Unreferenced (dry run, pass --delete to remove)"
+        },
+        report.uploads.len(),
+        report.files.len(),
+        report.bytes,
+        report.skipped_recent,
+        upload_cleanup::GRACE_PERIOD_HOURS
+    );
+
+    Ok(())
+}
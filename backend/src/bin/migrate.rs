@@ -0,0 +1,70 @@
+/**
+ * Database Migration Utility
+ *
+ * Applies the pending schema migrations without starting the server, or
+ * lists them with `--check`, which exits with status 1 if any are pending.
+ * Site content, the admin user and the default tutorials are still seeded
+ * by the server on startup.
+ *
+ * Usage:
+ * ```bash
+ * cargo run --bin migrate             # apply pending migrations
+ * cargo run --bin migrate -- --check
+ * ```
+ *
+ * The migrations are `minos_backend::db::migrations::MIGRATIONS`; the ones
+ * applied are recorded in the `schema_migrations` table.
+ */
+use std::{env, process};
+
+use anyhow::{anyhow, Context, Result};
+
+use minos_backend::db::{self, migrations};
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenvy::dotenv().ok();
+
+    let check = match env::args().nth(1).as_deref() {
+        None => false,
+        Some("--check") => true,
+        Some(other) => return Err(anyhow!("Unknown argument '{other}'; use --check")),
+    };
+
+    let pool = db::connect()
+        .await
+        .context("Failed to connect to database. Is DATABASE_URL set correctly?")?;
+
+    if check {
+        let applied = migrations::applied_migrations(&pool)
+            .await
+            .context("Failed to read the migration history")?;
+        let pending = migrations::pending_migrations(&pool)
+            .await
+            .context("Failed to read the migration history")?;
+        for migration in &applied {
+            println!(
+                "  applied {:>3} {} ({})",
+                migration.version, migration.name, migration.applied_at
+            );
+        }
+        for migration in &pending {
+            println!("  pending {:>3} {}", migration.version, migration.name);
+        }
+        println!("{} applied, {} pending", applied.len(), pending.len());
+        if !pending.is_empty() {
+            process::exit(1);
+        }
+        return Ok(());
+    }
+
+    let ran = migrations::apply_pending_migrations(&pool)
+        .await
+        .context("Migration failed; it was rolled back and the ones before it kept")?;
+    for migration in &ran {
+        println!("  applied {:>3} {}", migration.version, migration.name);
+    }
+    println!("{} migrations applied", ran.len());
+
+    Ok(())
+}
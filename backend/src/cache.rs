@@ -0,0 +1,129 @@
+//! In-process Caches for Hot Public Reads
+//!
+//! Navigation, site content and published page bundles change rarely but
+//! are fetched on every page view. They are kept in small [`TtlCache`]s:
+//! repository writes invalidate them immediately, and the TTL bounds
+//! staleness for changes made by other processes (e.g. the import binary).
+//!
+//! Responses served through a cache carry a `Cache-Status: hit|miss`
+//! header, so a stale read can be told apart from a slow query.
+
+use crate::models::SitePageWithPostsResponse;
+use axum::{
+    http::{HeaderName, HeaderValue},
+    response::Response,
+};
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::{Arc, LazyLock, RwLock};
+use std::time::{Duration, Instant};
+
+/// How long an entry of the public read caches may be served.
+pub const PUBLIC_CACHE_TTL: Duration = Duration::from_secs(30);
+
+/// Response header reporting whether a cache answered the request.
+pub const CACHE_STATUS_HEADER: HeaderName = HeaderName::from_static("cache-status");
+
+/// Published page bundles of `GET /api/public/pages/{slug}`, by slug.
+/// Cleared by every page and post write, since a post can appear in its
+/// page's bundle and a slug change moves a page to another key.
+pub static PAGE_BUNDLES: LazyLock<TtlCache<String, Arc<SitePageWithPostsResponse>>> =
+    LazyLock::new(|| TtlCache::new(PUBLIC_CACHE_TTL));
+
+/// Whether a value came from a cache.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheStatus {
+    Hit,
+    Miss,
+}
+
+impl CacheStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CacheStatus::Hit => "hit",
+            CacheStatus::Miss => "miss",
+        }
+    }
+}
+
+/// Adds the `Cache-Status` header to `response`.
+pub fn with_cache_status(mut response: Response, status: CacheStatus) -> Response {
+    response.headers_mut().insert(
+        CACHE_STATUS_HEADER,
+        HeaderValue::from_static(status.as_str()),
+    );
+    response
+}
+
+/// A map whose entries expire `ttl` after they were inserted. A poisoned
+/// lock only disables caching; reads then fall through to the database.
+#[derive(Debug)]
+pub struct TtlCache<K, V> {
+    ttl: Duration,
+    entries: RwLock<HashMap<K, (Instant, V)>>,
+}
+
+impl<K: Eq + Hash, V: Clone> TtlCache<K, V> {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// The value cached for `key`, unless it has expired.
+    pub fn get<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        let entries = self.entries.read().ok()?;
+        let (cached_at, value) = entries.get(key)?;
+        (cached_at.elapsed() < self.ttl).then(|| value.clone())
+    }
+
+    pub fn insert(&self, key: K, value: V) {
+        if let Ok(mut entries) = self.entries.write() {
+            // Expired entries are only dropped here, which keeps the map
+            // bounded by the keys requested within one TTL
+            entries.retain(|_, (cached_at, _)| cached_at.elapsed() < self.ttl);
+            entries.insert(key, (Instant::now(), value));
+        }
+    }
+
+    pub fn remove<Q>(&self, key: &Q)
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        if let Ok(mut entries) = self.entries.write() {
+            entries.remove(key);
+        }
+    }
+
+    pub fn clear(&self) {
+        if let Ok(mut entries) = self.entries.write() {
+            entries.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_expire_after_the_ttl() {
+        let cache = TtlCache::new(Duration::from_millis(20));
+        cache.insert("nav".to_string(), 1);
+        assert_eq!(cache.get("nav"), Some(1));
+
+        std::thread::sleep(Duration::from_millis(30));
+        assert_eq!(cache.get("nav"), None);
+
+        cache.insert("nav".to_string(), 2);
+        cache.remove("nav");
+        assert_eq!(cache.get("nav"), None);
+    }
+}
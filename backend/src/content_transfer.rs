@@ -0,0 +1,758 @@
+//! Content Bundle Export and Import
+//!
+//! The bundle format moves site content, pages, posts and tutorials between
+//! instances as one JSON document. It is written and read here, so the
+//! `export_content`/`import_content` binaries and the admin endpoints under
+//! `/api/admin/content` produce and accept exactly the same structure.
+//!
+//! Imports run against a caller-provided transaction: either every item of
+//! a bundle is applied or none is. Original IDs and timestamps are kept.
+
+use crate::db::DbPool;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{FromRow, Sqlite, Transaction};
+use std::collections::HashMap;
+use std::fmt;
+
+/// A stored content section.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SiteContentEntry {
+    pub section: String,
+    pub content: Value,
+    #[serde(default)]
+    pub updated_at: Option<String>,
+}
+
+/// A site page with its hero and layout blobs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SitePageEntry {
+    pub id: String,
+    pub slug: String,
+    pub title: String,
+    pub description: String,
+    pub nav_label: Option<String>,
+    pub show_in_nav: bool,
+    pub order_index: i64,
+    pub is_published: bool,
+    pub hero: Value,
+    pub layout: Value,
+    #[serde(default)]
+    pub created_at: Option<String>,
+    #[serde(default)]
+    pub updated_at: Option<String>,
+}
+
+/// A post below a site page, with its tags.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SitePostEntry {
+    pub id: String,
+    pub page_id: String,
+    pub title: String,
+    pub slug: String,
+    pub excerpt: String,
+    pub content_markdown: String,
+    pub is_published: bool,
+    pub published_at: Option<String>,
+    pub order_index: i64,
+    #[serde(default)]
+    pub cover_image_url: Option<String>,
+    #[serde(default)]
+    pub meta_description: Option<String>,
+    #[serde(default)]
+    pub canonical_url: Option<String>,
+    #[serde(default)]
+    pub created_at: Option<String>,
+    #[serde(default)]
+    pub updated_at: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// A tutorial. `version` is informational; imports bump the local one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TutorialEntry {
+    pub id: String,
+    pub title: String,
+    pub description: String,
+    pub icon: String,
+    pub color: String,
+    pub topics: Vec<String>,
+    pub content: String,
+    #[serde(default)]
+    pub version: i64,
+    #[serde(default = "default_allow_comments")]
+    pub allow_comments: bool,
+    #[serde(default)]
+    pub created_by: Option<String>,
+    #[serde(default)]
+    pub updated_by: Option<String>,
+    #[serde(default)]
+    pub created_at: Option<String>,
+    #[serde(default)]
+    pub updated_at: Option<String>,
+}
+
+fn default_allow_comments() -> bool {
+    true
+}
+
+/// A row of the tutorial topic index. Exported for reference only; imports
+/// rebuild the index from each tutorial's `topics`.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct TutorialTopicEntry {
+    pub tutorial_id: String,
+    pub topic: String,
+}
+
+/// The document written by an export and read by an import. Every list may
+/// be missing, so a filtered export can be imported as is.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ContentBundle {
+    #[serde(default)]
+    pub site_content: Vec<SiteContentEntry>,
+    #[serde(default)]
+    pub pages: Vec<SitePageEntry>,
+    #[serde(default)]
+    pub posts: Vec<SitePostEntry>,
+    #[serde(default)]
+    pub tutorials: Vec<TutorialEntry>,
+    #[serde(default)]
+    pub tutorial_topics: Vec<TutorialTopicEntry>,
+}
+
+/// What an export includes.
+#[derive(Debug, Clone, Default)]
+pub struct ExportFilter {
+    /// Content sections to export; `None` exports all of them.
+    pub sections: Option<Vec<String>>,
+    /// Export site pages and their posts.
+    pub include_pages: bool,
+    /// Export tutorials and their topic index.
+    pub include_tutorials: bool,
+}
+
+impl ExportFilter {
+    /// Everything in the database, as the `export_content` binary writes it.
+    pub fn everything() -> Self {
+        Self {
+            sections: None,
+            include_pages: true,
+            include_tutorials: true,
+        }
+    }
+}
+
+/// How an import treats items whose ID (or section name) already exists.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportMode {
+    /// Leave existing items untouched.
+    #[default]
+    SkipExisting,
+    /// Replace existing items with the imported ones.
+    Overwrite,
+}
+
+/// What happened to one imported item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportOutcome {
+    Created,
+    Overwritten,
+    Skipped,
+}
+
+/// The outcome of one item of an import.
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportItemResult {
+    /// `site_content`, `page`, `post` or `tutorial`.
+    pub kind: &'static str,
+    /// Section name for site content, the ID otherwise.
+    pub key: String,
+    pub outcome: ImportOutcome,
+}
+
+/// Why an export or import failed.
+#[derive(Debug)]
+pub enum TransferError {
+    Database {
+        context: String,
+        source: sqlx::Error,
+    },
+    /// A stored JSON column could not be parsed, or a value not serialized.
+    Json {
+        context: String,
+        source: serde_json::Error,
+    },
+}
+
+impl fmt::Display for TransferError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransferError::Database { context, .. } | TransferError::Json { context, .. } => {
+                f.write_str(context)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TransferError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TransferError::Database { source, .. } => Some(source),
+            TransferError::Json { source, .. } => Some(source),
+        }
+    }
+}
+
+fn db_error(context: impl Into<String>) -> impl FnOnce(sqlx::Error) -> TransferError {
+    let context = context.into();
+    move |source| TransferError::Database { context, source }
+}
+
+fn json_error(context: impl Into<String>) -> impl FnOnce(serde_json::Error) -> TransferError {
+    let context = context.into();
+    move |source| TransferError::Json { context, source }
+}
+
+#[derive(Debug, FromRow)]
+struct SiteContentRow {
+    section: String,
+    content_json: String,
+    updated_at: String,
+}
+
+#[derive(Debug, FromRow)]
+struct SitePageRow {
+    id: String,
+    slug: String,
+    title: String,
+    description: String,
+    nav_label: Option<String>,
+    show_in_nav: bool,
+    order_index: i64,
+    is_published: bool,
+    hero_json: String,
+    layout_json: String,
+    created_at: String,
+    updated_at: String,
+}
+
+#[derive(Debug, FromRow)]
+struct SitePostRow {
+    id: String,
+    page_id: String,
+    title: String,
+    slug: String,
+    excerpt: String,
+    content_markdown: String,
+    is_published: bool,
+    published_at: Option<String>,
+    order_index: i64,
+    cover_image_url: Option<String>,
+    meta_description: Option<String>,
+    canonical_url: Option<String>,
+    created_at: String,
+    updated_at: String,
+}
+
+#[derive(Debug, FromRow)]
+struct TutorialRow {
+    id: String,
+    title: String,
+    description: String,
+    icon: String,
+    color: String,
+    topics: String,
+    content: String,
+    version: i64,
+    allow_comments: bool,
+    created_by: Option<String>,
+    updated_by: Option<String>,
+    created_at: String,
+    updated_at: String,
+}
+
+/// Reads the parts of the database selected by `filter` into a bundle.
+pub async fn export_bundle(
+    pool: &DbPool,
+    filter: &ExportFilter,
+) -> Result<ContentBundle, TransferError> {
+    let mut bundle = ContentBundle {
+        site_content: export_site_content(pool, filter.sections.as_deref()).await?,
+        ..Default::default()
+    };
+    if filter.include_pages {
+        bundle.pages = export_site_pages(pool).await?;
+        bundle.posts = export_site_posts(pool).await?;
+    }
+    if filter.include_tutorials {
+        bundle.tutorials = export_tutorials(pool).await?;
+        bundle.tutorial_topics = sqlx::query_as::<_, TutorialTopicEntry>(
+            "SELECT tutorial_id, topic FROM tutorial_topics ORDER BY tutorial_id, topic",
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(db_error("Failed to load tutorial_topics entries"))?;
+    }
+    Ok(bundle)
+}
+
+async fn export_site_content(
+    pool: &DbPool,
+    sections: Option<&[String]>,
+) -> Result<Vec<SiteContentEntry>, TransferError> {
+    let rows = sqlx::query_as::<_, SiteContentRow>(
+        "SELECT section, content_json, updated_at FROM site_content ORDER BY section",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(db_error("Failed to load site_content entries"))?;
+
+    rows.into_iter()
+        .filter(|row| sections.is_none_or(|sections| sections.contains(&row.section)))
+        .map(|row| {
+            let content: Value = serde_json::from_str(&row.content_json).map_err(json_error(
+                format!("Failed to parse JSON for section '{}'", row.section),
+            ))?;
+            Ok(SiteContentEntry {
+                section: row.section,
+                content,
+                updated_at: Some(row.updated_at),
+            })
+        })
+        .collect()
+}
+
+async fn export_site_pages(pool: &DbPool) -> Result<Vec<SitePageEntry>, TransferError> {
+    let rows = sqlx::query_as::<_, SitePageRow>(
+        r#"SELECT id, slug, title, description, nav_label, show_in_nav, order_index,
+                  is_published, hero_json, layout_json, created_at, updated_at
+           FROM site_pages
+           ORDER BY order_index, title"#,
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(db_error("Failed to load site_pages entries"))?;
+
+    rows.into_iter()
+        .map(|row| {
+            let hero: Value = serde_json::from_str(&row.hero_json).map_err(json_error(format!(
+                "Failed to parse hero JSON for page '{}'",
+                row.slug
+            )))?;
+            let layout: Value = serde_json::from_str(&row.layout_json).map_err(json_error(
+                format!("Failed to parse layout JSON for page '{}'", row.slug),
+            ))?;
+            Ok(SitePageEntry {
+                id: row.id,
+                slug: row.slug,
+                title: row.title,
+                description: row.description,
+                nav_label: row.nav_label,
+                show_in_nav: row.show_in_nav,
+                order_index: row.order_index,
+                is_published: row.is_published,
+                hero,
+                layout,
+                created_at: Some(row.created_at),
+                updated_at: Some(row.updated_at),
+            })
+        })
+        .collect()
+}
+
+async fn export_site_posts(pool: &DbPool) -> Result<Vec<SitePostEntry>, TransferError> {
+    let rows = sqlx::query_as::<_, SitePostRow>(
+        r#"SELECT id, page_id, title, slug, excerpt, content_markdown, is_published,
+                  published_at, order_index, cover_image_url, meta_description,
+                  canonical_url, created_at, updated_at
+           FROM site_posts
+           ORDER BY page_id, order_index, created_at"#,
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(db_error("Failed to load site_posts entries"))?;
+
+    let tag_rows = sqlx::query_as::<_, (String, String)>(
+        "SELECT post_id, tag FROM post_tags ORDER BY post_id, tag",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(db_error("Failed to load post_tags entries"))?;
+    let mut tags_by_post: HashMap<String, Vec<String>> = HashMap::new();
+    for (post_id, tag) in tag_rows {
+        tags_by_post.entry(post_id).or_default().push(tag);
+    }
+
+    Ok(rows
+        .into_iter()
+        .map(|row| SitePostEntry {
+            tags: tags_by_post.remove(&row.id).unwrap_or_default(),
+            id: row.id,
+            page_id: row.page_id,
+            title: row.title,
+            slug: row.slug,
+            excerpt: row.excerpt,
+            content_markdown: row.content_markdown,
+            is_published: row.is_published,
+            published_at: row.published_at,
+            order_index: row.order_index,
+            cover_image_url: row.cover_image_url,
+            meta_description: row.meta_description,
+            canonical_url: row.canonical_url,
+            created_at: Some(row.created_at),
+            updated_at: Some(row.updated_at),
+        })
+        .collect())
+}
+
+async fn export_tutorials(pool: &DbPool) -> Result<Vec<TutorialEntry>, TransferError> {
+    let rows = sqlx::query_as::<_, TutorialRow>(
+        r#"SELECT id, title, description, icon, color, topics, content, version,
+                  allow_comments, created_by, updated_by, created_at, updated_at
+           FROM tutorials
+           ORDER BY created_at"#,
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(db_error("Failed to load tutorials entries"))?;
+
+    rows.into_iter()
+        .map(|row| {
+            let topics: Vec<String> = serde_json::from_str(&row.topics).map_err(json_error(
+                format!("Failed to parse topics JSON for tutorial '{}'", row.id),
+            ))?;
+            Ok(TutorialEntry {
+                id: row.id,
+                title: row.title,
+                description: row.description,
+                icon: row.icon,
+                color: row.color,
+                topics,
+                content: row.content,
+                version: row.version,
+                allow_comments: row.allow_comments,
+                created_by: row.created_by,
+                updated_by: row.updated_by,
+                created_at: Some(row.created_at),
+                updated_at: Some(row.updated_at),
+            })
+        })
+        .collect()
+}
+
+/// Writes `bundle` within `tx`, reporting one result per item in bundle
+/// order. Pages are written before their posts.
+pub async fn import_bundle(
+    tx: &mut Transaction<'_, Sqlite>,
+    bundle: &ContentBundle,
+    mode: ImportMode,
+) -> Result<Vec<ImportItemResult>, TransferError> {
+    let mut results = Vec::new();
+    import_site_content(tx, &bundle.site_content, mode, &mut results).await?;
+    import_site_pages(tx, &bundle.pages, mode, &mut results).await?;
+    import_site_posts(tx, &bundle.posts, mode, &mut results).await?;
+    import_tutorials(tx, &bundle.tutorials, mode, &mut results).await?;
+    Ok(results)
+}
+
+/// Decides what to do with an item from whether its key already exists.
+/// `exists_sql` takes the key as its only parameter.
+async fn plan(
+    tx: &mut Transaction<'_, Sqlite>,
+    exists_sql: &str,
+    key: &str,
+    mode: ImportMode,
+) -> Result<ImportOutcome, TransferError> {
+    let exists: bool = sqlx::query_scalar(exists_sql)
+        .bind(key)
+        .fetch_one(&mut **tx)
+        .await
+        .map_err(db_error(format!("Failed to look up '{key}'")))?;
+    Ok(match (exists, mode) {
+        (false, _) => ImportOutcome::Created,
+        (true, ImportMode::Overwrite) => ImportOutcome::Overwritten,
+        (true, ImportMode::SkipExisting) => ImportOutcome::Skipped,
+    })
+}
+
+async fn import_site_content(
+    tx: &mut Transaction<'_, Sqlite>,
+    items: &[SiteContentEntry],
+    mode: ImportMode,
+    results: &mut Vec<ImportItemResult>,
+) -> Result<(), TransferError> {
+    for item in items {
+        let outcome = plan(
+            tx,
+            "SELECT EXISTS(SELECT 1 FROM site_content WHERE section = ?)",
+            &item.section,
+            mode,
+        )
+        .await?;
+        results.push(ImportItemResult {
+            kind: "site_content",
+            key: item.section.clone(),
+            outcome,
+        });
+        if outcome == ImportOutcome::Skipped {
+            continue;
+        }
+
+        let serialized = serde_json::to_string(&item.content)
+            .map_err(json_error("Failed to serialize site_content entry"))?;
+
+        sqlx::query(
+            r#"INSERT INTO site_content (section, content_json, updated_at)
+               VALUES (?, ?, COALESCE(?, CURRENT_TIMESTAMP))
+               ON CONFLICT(section) DO UPDATE SET
+                   content_json = excluded.content_json,
+                   updated_at = COALESCE(excluded.updated_at, CURRENT_TIMESTAMP)"#,
+        )
+        .bind(&item.section)
+        .bind(&serialized)
+        .bind(&item.updated_at)
+        .execute(&mut **tx)
+        .await
+        .map_err(db_error(format!(
+            "Failed to upsert site_content section '{}'",
+            item.section
+        )))?;
+    }
+
+    Ok(())
+}
+
+async fn import_site_pages(
+    tx: &mut Transaction<'_, Sqlite>,
+    items: &[SitePageEntry],
+    mode: ImportMode,
+    results: &mut Vec<ImportItemResult>,
+) -> Result<(), TransferError> {
+    for item in items {
+        let outcome = plan(
+            tx,
+            "SELECT EXISTS(SELECT 1 FROM site_pages WHERE id = ?)",
+            &item.id,
+            mode,
+        )
+        .await?;
+        results.push(ImportItemResult {
+            kind: "page",
+            key: item.id.clone(),
+            outcome,
+        });
+        if outcome == ImportOutcome::Skipped {
+            continue;
+        }
+
+        let hero_serialized = serde_json::to_string(&item.hero)
+            .map_err(json_error("Failed to serialize page hero JSON"))?;
+        let layout_serialized = serde_json::to_string(&item.layout)
+            .map_err(json_error("Failed to serialize page layout JSON"))?;
+
+        sqlx::query(
+            r#"INSERT INTO site_pages (
+                   id, slug, title, description, nav_label, show_in_nav, order_index,
+                   is_published, hero_json, layout_json, created_at, updated_at
+               ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?,
+                   COALESCE(?, CURRENT_TIMESTAMP), COALESCE(?, CURRENT_TIMESTAMP))
+               ON CONFLICT(id) DO UPDATE SET
+                   slug = excluded.slug, title = excluded.title,
+                   description = excluded.description, nav_label = excluded.nav_label,
+                   show_in_nav = excluded.show_in_nav, order_index = excluded.order_index,
+                   is_published = excluded.is_published, hero_json = excluded.hero_json,
+                   layout_json = excluded.layout_json,
+                   updated_at = COALESCE(excluded.updated_at, CURRENT_TIMESTAMP)"#,
+        )
+        .bind(&item.id)
+        .bind(&item.slug)
+        .bind(&item.title)
+        .bind(&item.description)
+        .bind(&item.nav_label)
+        .bind(if item.show_in_nav { 1 } else { 0 })
+        .bind(item.order_index)
+        .bind(if item.is_published { 1 } else { 0 })
+        .bind(&hero_serialized)
+        .bind(&layout_serialized)
+        .bind(&item.created_at)
+        .bind(&item.updated_at)
+        .execute(&mut **tx)
+        .await
+        .map_err(db_error(format!(
+            "Failed to upsert site_page '{}'",
+            item.slug
+        )))?;
+    }
+
+    Ok(())
+}
+
+async fn import_site_posts(
+    tx: &mut Transaction<'_, Sqlite>,
+    items: &[SitePostEntry],
+    mode: ImportMode,
+    results: &mut Vec<ImportItemResult>,
+) -> Result<(), TransferError> {
+    for item in items {
+        let outcome = plan(
+            tx,
+            "SELECT EXISTS(SELECT 1 FROM site_posts WHERE id = ?)",
+            &item.id,
+            mode,
+        )
+        .await?;
+        results.push(ImportItemResult {
+            kind: "post",
+            key: item.id.clone(),
+            outcome,
+        });
+        if outcome == ImportOutcome::Skipped {
+            continue;
+        }
+
+        sqlx::query(
+            r#"INSERT INTO site_posts (
+                   id, page_id, title, slug, excerpt, content_markdown, is_published,
+                   published_at, order_index, cover_image_url, meta_description,
+                   canonical_url, created_at, updated_at
+               ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?,
+                   COALESCE(?, CURRENT_TIMESTAMP), COALESCE(?, CURRENT_TIMESTAMP))
+               ON CONFLICT(id) DO UPDATE SET
+                   page_id = excluded.page_id, title = excluded.title, slug = excluded.slug,
+                   excerpt = excluded.excerpt, content_markdown = excluded.content_markdown,
+                   is_published = excluded.is_published, published_at = excluded.published_at,
+                   order_index = excluded.order_index,
+                   cover_image_url = excluded.cover_image_url,
+                   meta_description = excluded.meta_description,
+                   canonical_url = excluded.canonical_url,
+                   updated_at = COALESCE(excluded.updated_at, CURRENT_TIMESTAMP)"#,
+        )
+        .bind(&item.id)
+        .bind(&item.page_id)
+        .bind(&item.title)
+        .bind(&item.slug)
+        .bind(&item.excerpt)
+        .bind(&item.content_markdown)
+        .bind(if item.is_published { 1 } else { 0 })
+        .bind(&item.published_at)
+        .bind(item.order_index)
+        .bind(&item.cover_image_url)
+        .bind(&item.meta_description)
+        .bind(&item.canonical_url)
+        .bind(&item.created_at)
+        .bind(&item.updated_at)
+        .execute(&mut **tx)
+        .await
+        .map_err(db_error(format!(
+            "Failed to upsert site_post '{}'",
+            item.slug
+        )))?;
+
+        sqlx::query("DELETE FROM post_tags WHERE post_id = ?")
+            .bind(&item.id)
+            .execute(&mut **tx)
+            .await
+            .map_err(db_error(format!(
+                "Failed to clear tags of site_post '{}'",
+                item.slug
+            )))?;
+        for tag in &item.tags {
+            let tag = tag.trim().to_lowercase();
+            if tag.is_empty() {
+                continue;
+            }
+            sqlx::query("INSERT OR IGNORE INTO post_tags (post_id, tag) VALUES (?, ?)")
+                .bind(&item.id)
+                .bind(&tag)
+                .execute(&mut **tx)
+                .await
+                .map_err(db_error(format!("Failed to tag site_post '{}'", item.slug)))?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn import_tutorials(
+    tx: &mut Transaction<'_, Sqlite>,
+    items: &[TutorialEntry],
+    mode: ImportMode,
+    results: &mut Vec<ImportItemResult>,
+) -> Result<(), TransferError> {
+    for item in items {
+        let outcome = plan(
+            tx,
+            "SELECT EXISTS(SELECT 1 FROM tutorials WHERE id = ?)",
+            &item.id,
+            mode,
+        )
+        .await?;
+        results.push(ImportItemResult {
+            kind: "tutorial",
+            key: item.id.clone(),
+            outcome,
+        });
+        if outcome == ImportOutcome::Skipped {
+            continue;
+        }
+
+        let topics_json = serde_json::to_string(&item.topics)
+            .map_err(json_error("Failed to serialize tutorial topics"))?;
+
+        sqlx::query(
+            r#"INSERT INTO tutorials (
+                   id, title, description, icon, color, topics, content, allow_comments,
+                   created_by, updated_by, order_index, created_at, updated_at
+               ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?,
+                   (SELECT COALESCE(MAX(order_index) + 1, 0) FROM tutorials),
+                   COALESCE(?, CURRENT_TIMESTAMP), COALESCE(?, CURRENT_TIMESTAMP))
+               ON CONFLICT(id) DO UPDATE SET
+                   title = excluded.title, description = excluded.description,
+                   icon = excluded.icon, color = excluded.color, topics = excluded.topics,
+                   content = excluded.content, allow_comments = excluded.allow_comments,
+                   created_by = excluded.created_by, updated_by = excluded.updated_by,
+                   version = version + 1,
+                   updated_at = COALESCE(excluded.updated_at, CURRENT_TIMESTAMP)"#,
+        )
+        .bind(&item.id)
+        .bind(&item.title)
+        .bind(&item.description)
+        .bind(&item.icon)
+        .bind(&item.color)
+        .bind(&topics_json)
+        .bind(&item.content)
+        .bind(item.allow_comments)
+        .bind(&item.created_by)
+        .bind(&item.updated_by)
+        .bind(&item.created_at)
+        .bind(&item.updated_at)
+        .execute(&mut **tx)
+        .await
+        .map_err(db_error(format!("Failed to upsert tutorial '{}'", item.id)))?;
+
+        sqlx::query("DELETE FROM tutorial_topics WHERE tutorial_id = ?")
+            .bind(&item.id)
+            .execute(&mut **tx)
+            .await
+            .map_err(db_error(format!(
+                "Failed to reset topics of tutorial '{}'",
+                item.id
+            )))?;
+        for topic in &item.topics {
+            sqlx::query("INSERT OR IGNORE INTO tutorial_topics (tutorial_id, topic) VALUES (?, ?)")
+                .bind(&item.id)
+                .bind(topic)
+                .execute(&mut **tx)
+                .await
+                .map_err(db_error(format!(
+                    "Failed to store topics of tutorial '{}'",
+                    item.id
+                )))?;
+        }
+    }
+
+    Ok(())
+}
@@ -5,12 +5,12 @@ use std::env;
 
 /// Runs all database migrations and initial data seeding.
 /// # Migration Steps
-/// 1. **Core Schema**: Create core tables (users, tutorials, comments, login_attempts)
-/// 2. **Site Schema**: Create site-related tables (pages, posts, content)
-/// 3. **FTS Index**: Create and populate full-text search index
-/// 4. **Default Content**: Seed default site content (hero, footer, etc.)
-/// 5. **Admin User**: Create admin account from environment variables
-/// 6. **Default Tutorials**: Optionally seed sample tutorials
+/// 1. **Schema**: Apply the pending versioned migrations (see [`MIGRATIONS`]);
+///    a failing one aborts startup
+/// 2. **FTS Index**: Warn when the search index tokenizer differs from `FTS_TOKENIZER`
+/// 3. **Default Content**: Seed default site content (hero, footer, etc.)
+/// 4. **Admin User**: Create admin account from environment variables
+/// 5. **Default Tutorials**: Optionally seed sample tutorials
 ///
 /// # Admin User Creation
 /// If `ADMIN_USERNAME` and `ADMIN_PASSWORD` are set:
@@ -33,7 +33,7 @@ use std::env;
 /// - `Err(sqlx::Error)` if any migration fails
 ///
 /// # Errors
-/// - Schema creation failure
+/// - Migration failure
 /// - Admin password too weak (< 12 characters)
 /// - bcrypt hashing failure
 /// - Transaction rollback on any error
@@ -43,76 +43,8 @@ use std::env;
 /// - `ADMIN_PASSWORD`: Admin account password (optional, min 12 chars)
 /// - `ENABLE_DEFAULT_TUTORIALS`: "false" to disable tutorial seeding (default: true)
 pub async fn run_migrations(pool: &DbPool) -> Result<(), sqlx::Error> {
-    let mut tx = pool.begin().await?;
-
-    // Apply core schema migrations (users, tutorials, comments, etc.)
-    if let Err(err) = apply_core_migrations(&mut tx).await {
-        tx.rollback().await?;
-        return Err(err);
-    }
-
-    tx.commit().await?;
-
-    // Apply login attempt schema migrations (add last_attempt_at for cleanup)
-    {
-        let mut tx = pool.begin().await?;
-        apply_login_attempt_migrations(&mut tx).await?;
-        tx.commit().await?;
-    }
-
-    // Apply comment schema migrations (add post_id and rate_limit_key)
-    {
-        let mut tx = pool.begin().await?;
-        apply_comment_migrations(&mut tx).await?;
-        tx.commit().await?;
-    }
-
-    // Apply vote tracking schema migration
-    {
-        let mut tx = pool.begin().await?;
-        apply_vote_migration(&mut tx).await?;
-        tx.commit().await?;
-    }
-
-    // Fix comment schema (make tutorial_id nullable)
-    {
-        let mut tx = pool.begin().await?;
-        fix_comment_schema(&mut tx).await?;
-        tx.commit().await?;
-    }
-
-    // Apply comment author identity migration (author_username / is_guest for ownership checks)
-    {
-        let mut tx = pool.begin().await?;
-        apply_comment_author_identity_migration(&mut tx).await?;
-        tx.commit().await?;
-    }
-
-    // Rehash legacy plaintext rows in token_blacklist (raw JWTs -> SHA-256)
-    {
-        let mut tx = pool.begin().await?;
-        apply_token_blacklist_hash_migration(&mut tx).await?;
-        tx.commit().await?;
-    }
-
-    // Create site-related schema (pages, posts, content)
-    ensure_site_page_schema(pool).await?;
-
-    // Migrate persisted CMS content to the current project branding.
-    {
-        let mut tx = pool.begin().await?;
-        apply_site_content_branding_migration(&mut tx).await?;
-        tx.commit().await?;
-    }
-
-    // Apply site post schema migrations (add allow_comments)
-    {
-        let mut tx = pool.begin().await?;
-        if let Err(err) = apply_site_post_migrations(&mut tx).await {
-            tracing::error!("Failed to apply site post migrations: {}", err);
-        }
-        tx.commit().await?;
-    }
+    apply_pending_migrations(pool).await?;
+    check_search_tokenizer(pool).await?;
 
     // Seed default site content (hero, footer, etc.)
     {
@@ -173,11 +105,11 @@ pub async fn run_migrations(pool: &DbPool) -> Result<(), sqlx::Error> {
                     }
                 },
                 None => {
-                    let password_hash =
-                        bcrypt::hash(&password, bcrypt::DEFAULT_COST).map_err(|e| {
-                            tracing::error!("Failed to hash admin password: {}", e);
-                            sqlx::Error::Protocol("Failed to hash admin password".into())
-                        })?;
+                    let password_hash = bcrypt::hash(&password, crate::config::get().bcrypt_cost)
+                        .map_err(|e| {
+                        tracing::error!("Failed to hash admin password: {}", e);
+                        sqlx::Error::Protocol("Failed to hash admin password".into())
+                    })?;
                     sqlx::query(
                         "INSERT INTO users (username, password_hash, role) VALUES (?, ?, ?)",
                     )
@@ -235,6 +167,12 @@ pub async fn run_migrations(pool: &DbPool) -> Result<(), sqlx::Error> {
 
     tx.commit().await?;
 
+    // Seeded tutorials and rows from older releases have no cached stats yet.
+    let backfilled = crate::repositories::stats::backfill_content_stats(pool).await?;
+    if backfilled > 0 {
+        tracing::info!("Computed content statistics for {} documents", backfilled);
+    }
+
     Ok(())
 }
 
@@ -368,36 +306,35 @@ async fn apply_core_migrations(tx: &mut Transaction<'_, Sqlite>) -> Result<(), s
         .execute(&mut **tx)
         .await?;
 
-    sqlx::query("DROP TRIGGER IF EXISTS tutorials_ai")
-        .execute(&mut **tx)
-        .await?;
-    sqlx::query("DROP TRIGGER IF EXISTS tutorials_ad")
-        .execute(&mut **tx)
-        .await?;
-    sqlx::query("DROP TRIGGER IF EXISTS tutorials_au")
-        .execute(&mut **tx)
-        .await?;
-    sqlx::query("DROP TABLE IF EXISTS tutorials_fts")
-        .execute(&mut **tx)
-        .await?;
+    // The search index is only built when it is missing; a desynced index
+    // is repaired with `POST /api/admin/search/reindex` instead of on every
+    // start. Later migrations replace the triggers created here.
+    let has_fts: bool = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'tutorials_fts'",
+    )
+    .fetch_one(&mut **tx)
+    .await
+    .map(|count: i64| count > 0)?;
+
+    if !has_fts {
+        let tokenizer = &crate::config::get().fts_tokenizer;
+        sqlx::query(&create_tutorials_fts_sql(tokenizer))
+            .execute(&mut **tx)
+            .await?;
 
-    sqlx::query(
-        r#"
-        CREATE VIRTUAL TABLE tutorials_fts USING fts5(
-            tutorial_id UNINDEXED,
-            title,
-            description,
-            content,
-            topics
-        )
+        sqlx::query(
+            r#"
+        INSERT INTO tutorials_fts(tutorial_id, title, description, content, topics)
+        SELECT id, title, description, content, topics FROM tutorials
         "#,
-    )
-    .execute(&mut **tx)
-    .await?;
+        )
+        .execute(&mut **tx)
+        .await?;
+    }
 
     sqlx::query(
         r#"
-        CREATE TRIGGER tutorials_ai AFTER INSERT ON tutorials BEGIN
+        CREATE TRIGGER IF NOT EXISTS tutorials_ai AFTER INSERT ON tutorials BEGIN
             INSERT INTO tutorials_fts(tutorial_id, title, description, content, topics)
             VALUES (new.id, new.title, new.description, new.content, new.topics);
         END
@@ -408,7 +345,7 @@ async fn apply_core_migrations(tx: &mut Transaction<'_, Sqlite>) -> Result<(), s
 
     sqlx::query(
         r#"
-        CREATE TRIGGER tutorials_ad AFTER DELETE ON tutorials BEGIN
+        CREATE TRIGGER IF NOT EXISTS tutorials_ad AFTER DELETE ON tutorials BEGIN
             DELETE FROM tutorials_fts WHERE tutorial_id = old.id;
         END
         "#,
@@ -418,7 +355,7 @@ async fn apply_core_migrations(tx: &mut Transaction<'_, Sqlite>) -> Result<(), s
 
     sqlx::query(
         r#"
-        CREATE TRIGGER tutorials_au AFTER UPDATE ON tutorials BEGIN
+        CREATE TRIGGER IF NOT EXISTS tutorials_au AFTER UPDATE ON tutorials BEGIN
             DELETE FROM tutorials_fts WHERE tutorial_id = old.id;
             INSERT INTO tutorials_fts(tutorial_id, title, description, content, topics)
             VALUES (new.id, new.title, new.description, new.content, new.topics);
@@ -428,18 +365,58 @@ async fn apply_core_migrations(tx: &mut Transaction<'_, Sqlite>) -> Result<(), s
     .execute(&mut **tx)
     .await?;
 
-    sqlx::query(
-        r#"
-        INSERT INTO tutorials_fts(tutorial_id, title, description, content, topics)
-        SELECT id, title, description, content, topics FROM tutorials
-        "#,
+    Ok(())
+}
+
+/// Warns when the search index was built with another tokenizer than
+/// `FTS_TOKENIZER` names; it is only rebuilt on request.
+async fn check_search_tokenizer(pool: &DbPool) -> Result<(), sqlx::Error> {
+    let create_sql: Option<String> = sqlx::query_scalar(
+        "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = 'tutorials_fts'",
     )
-    .execute(&mut **tx)
+    .fetch_optional(pool)
     .await?;
-
+    let Some(create_sql) = create_sql else {
+        return Ok(());
+    };
+    let tokenizer = &crate::config::get().fts_tokenizer;
+    let current = fts_tokenizer_in(&create_sql);
+    if &current != tokenizer {
+        tracing::warn!(
+            "Search index uses tokenizer '{}' but FTS_TOKENIZER is '{}'; rebuild it via POST /api/admin/search/reindex",
+            current,
+            tokenizer
+        );
+    }
     Ok(())
 }
 
+/// `CREATE VIRTUAL TABLE` statement for `tutorials_fts` with the given
+/// tokenizer, which must come from [`crate::config::parse_fts_tokenizer`].
+pub(crate) fn create_tutorials_fts_sql(tokenizer: &str) -> String {
+    format!(
+        "CREATE VIRTUAL TABLE tutorials_fts USING fts5(\
+         tutorial_id UNINDEXED, title, description, content, topics, \
+         tokenize = '{tokenizer}')"
+    )
+}
+
+/// Tokenizer an FTS5 table was created with, read from its `CREATE` statement.
+pub(crate) fn fts_tokenizer_in(create_sql: &str) -> String {
+    create_sql
+        .split_once("tokenize")
+        .and_then(|(_, rest)| rest.split('\'').nth(1))
+        .map(str::to_string)
+        // What FTS5 uses when no tokenizer is named
+        .unwrap_or_else(|| "unicode61".to_string())
+}
+
+mod runner;
+pub use runner::{
+    applied_migrations, apply_pending_migrations, pending_migrations, AppliedMigration, Migration,
+    BASELINE_VERSION, MIGRATIONS,
+};
+
 mod site_pages;
 use site_pages::*;
 
@@ -447,6 +424,7 @@ mod comments;
 use comments::*;
 
 mod maintenance;
+pub(crate) use maintenance::indexed_tutorial_content;
 use maintenance::*;
 
 #[cfg(test)]
@@ -47,6 +47,28 @@ pub(super) async fn apply_comment_migrations(
     Ok(())
 }
 
+/// Composite indexes backing keyset (cursor) pagination of comment lists.
+///
+/// The list queries order by `created_at DESC, id DESC` within one tutorial or
+/// post, so these let SQLite seek straight to the cursor position instead of
+/// scanning every earlier comment.
+pub(super) async fn apply_comment_keyset_indexes(
+    tx: &mut Transaction<'_, Sqlite>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_comments_tutorial_created ON comments(tutorial_id, created_at, id)",
+    )
+    .execute(&mut **tx)
+    .await?;
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_comments_post_created ON comments(post_id, created_at, id)",
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
 pub(super) async fn apply_vote_migration(
     tx: &mut Transaction<'_, Sqlite>,
 ) -> Result<(), sqlx::Error> {
@@ -167,3 +189,49 @@ pub(super) async fn fix_comment_schema(
 
     Ok(())
 }
+
+/// Rebuilds `comment_votes` when its foreign key still points at
+/// `comments_old`.
+///
+/// SQLite rewrites references in other tables when a table is renamed, so
+/// the rename in [`fix_comment_schema`] retargeted the votes foreign key at
+/// the temporary table it then dropped. With foreign keys enforced, every
+/// vote insert on such a database fails with "no such table".
+pub(super) async fn repair_comment_votes_foreign_key(
+    tx: &mut Transaction<'_, Sqlite>,
+) -> Result<(), sqlx::Error> {
+    let stale: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM pragma_foreign_key_list('comment_votes') WHERE \"table\" = 'comments_old'",
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    if stale == 0 {
+        return Ok(());
+    }
+
+    tracing::info!("Repairing comment_votes foreign key left pointing at comments_old");
+
+    sqlx::query("ALTER TABLE comment_votes RENAME TO comment_votes_old")
+        .execute(&mut **tx)
+        .await?;
+    sqlx::query(include_str!(
+        "../../../migrations/20241119_create_comment_votes.sql"
+    ))
+    .execute(&mut **tx)
+    .await?;
+    // Votes for comments that no longer exist could not cascade away; drop
+    // them rather than carry dangling rows into the rebuilt table.
+    sqlx::query(concat!(
+        "INSERT INTO comment_votes (comment_id, voter_id, created_at) ",
+        "SELECT v.comment_id, v.voter_id, v.created_at FROM comment_votes_old v ",
+        "WHERE EXISTS (SELECT 1 FROM comments c WHERE c.id = v.comment_id)"
+    ))
+    .execute(&mut **tx)
+    .await?;
+    sqlx::query("DROP TABLE comment_votes_old")
+        .execute(&mut **tx)
+        .await?;
+
+    Ok(())
+}
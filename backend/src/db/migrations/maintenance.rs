@@ -19,12 +19,20 @@ fn replace_legacy_branding(value: &mut serde_json::Value) -> bool {
                 true
             }
         }
-        serde_json::Value::Array(items) => items.iter_mut().fold(false, |changed, item| {
-            replace_legacy_branding(item) || changed
-        }),
-        serde_json::Value::Object(fields) => fields.values_mut().fold(false, |changed, field| {
-            replace_legacy_branding(field) || changed
-        }),
+        serde_json::Value::Array(items) => {
+            let mut changed = false;
+            for item in items {
+                changed |= replace_legacy_branding(item);
+            }
+            changed
+        }
+        serde_json::Value::Object(fields) => {
+            let mut changed = false;
+            for field in fields.values_mut() {
+                changed |= replace_legacy_branding(field);
+            }
+            changed
+        }
         serde_json::Value::Null | serde_json::Value::Bool(_) | serde_json::Value::Number(_) => {
             false
         }
@@ -59,7 +67,7 @@ pub(super) async fn apply_site_content_branding_migration(
         }
 
         let serialized = serde_json::to_string(&content)
-            .map_err(|error| sqlx::Error::Protocol(error.to_string().into()))?;
+            .map_err(|error| sqlx::Error::Protocol(error.to_string()))?;
         sqlx::query(
             "UPDATE site_content SET content_json = ?, updated_at = CURRENT_TIMESTAMP WHERE section = ?",
         )
@@ -136,7 +144,63 @@ pub(super) async fn apply_comment_author_identity_migration(
     Ok(())
 }
 
-/// Adds the `last_attempt_at` column to `login_attempts`.
+/// Adds `comments.user_id`, the `users.id` of an authenticated commenter.
+///
+/// Ownership checks compare this instead of names, so renaming an account
+/// keeps its comments and a new account that reuses an old name does not
+/// inherit them. Guests stay NULL. Existing rows are backfilled once, when
+/// the column is added: from `author_username` where it is known, and for
+/// pre-identity rows of unknown origin (see
+/// [`apply_comment_author_identity_migration`]) from an `author` that
+/// matches a registered username -- the same match the old legacy fallback
+/// in `delete_comment` allowed.
+pub(super) async fn apply_comment_user_id_migration(
+    tx: &mut Transaction<'_, Sqlite>,
+) -> Result<(), sqlx::Error> {
+    let has_user_id: bool = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM pragma_table_info('comments') WHERE name='user_id'",
+    )
+    .fetch_one(&mut **tx)
+    .await
+    .map(|count: i64| count > 0)?;
+
+    if has_user_id {
+        return Ok(());
+    }
+
+    tracing::info!("Adding user_id column to comments table");
+    add_column_if_missing_race_safe(
+        tx,
+        concat!(
+            "ALTER TABLE comments ADD COLUMN user_id INTEGER DEFAULT NULL ",
+            "REFERENCES users(id) ON DELETE SET NULL"
+        ),
+    )
+    .await?;
+
+    sqlx::query(concat!(
+        "UPDATE comments SET user_id = ",
+        "(SELECT id FROM users WHERE users.username = comments.author_username) ",
+        "WHERE user_id IS NULL AND author_username IS NOT NULL"
+    ))
+    .execute(&mut **tx)
+    .await?;
+    sqlx::query(concat!(
+        "UPDATE comments SET user_id = ",
+        "(SELECT id FROM users WHERE users.username = comments.author) ",
+        "WHERE user_id IS NULL AND author_username IS NULL AND is_guest IS NULL ",
+        "AND is_admin = FALSE"
+    ))
+    .execute(&mut **tx)
+    .await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_comments_user_id ON comments(user_id)")
+        .execute(&mut **tx)
+        .await?;
+
+    Ok(())
+}
+
+/// Adds the `last_attempt_at` and `key_type` columns to `login_attempts`.
 ///
 /// Without a timestamp, rows with fewer than 3 failures (blocked_until NULL)
 /// could never be aged out, so the table grew unbounded under attack traffic
@@ -170,6 +234,25 @@ pub(super) async fn apply_login_attempt_migrations(
             .await?;
     }
 
+    // `key_type` tells the (IP + username) rows from the IP-wide ones, which
+    // are counted with different thresholds. Rows from before the column
+    // existed read as `pair`; they age out within a day either way.
+    let has_key_type: bool = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM pragma_table_info('login_attempts') WHERE name='key_type'",
+    )
+    .fetch_one(&mut **tx)
+    .await
+    .map(|count: i64| count > 0)?;
+
+    if !has_key_type {
+        tracing::info!("Adding key_type column to login_attempts table");
+        add_column_if_missing_race_safe(
+            tx,
+            "ALTER TABLE login_attempts ADD COLUMN key_type TEXT NOT NULL DEFAULT 'pair'",
+        )
+        .await?;
+    }
+
     Ok(())
 }
 
@@ -301,3 +384,1227 @@ pub(super) async fn apply_site_post_migrations(
 
     Ok(())
 }
+
+/// Adds the cached `content_stats` column to tutorials and site posts.
+///
+/// The column is left NULL here; `repositories::stats::backfill_content_stats`
+/// fills it in after the schema steps so the dashboard never has to count
+/// words at read time.
+pub(super) async fn apply_content_stats_migration(
+    tx: &mut Transaction<'_, Sqlite>,
+) -> Result<(), sqlx::Error> {
+    for table in ["tutorials", "site_posts"] {
+        let has_content_stats: bool = sqlx::query_scalar(&format!(
+            "SELECT COUNT(*) FROM pragma_table_info('{table}') WHERE name='content_stats'"
+        ))
+        .fetch_one(&mut **tx)
+        .await
+        .map(|count: i64| count > 0)?;
+
+        if !has_content_stats {
+            tracing::info!("Adding content_stats column to {} table", table);
+            add_column_if_missing_race_safe(
+                tx,
+                &format!("ALTER TABLE {table} ADD COLUMN content_stats TEXT DEFAULT NULL"),
+            )
+            .await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Adds per-page crawler and response header controls to `site_pages`.
+///
+/// `meta_robots` stays NULL for pages that should follow the site default;
+/// `custom_headers_json` holds an allowlisted header map (see
+/// `models::PAGE_CUSTOM_HEADER_ALLOWLIST`).
+pub(super) async fn apply_site_page_seo_migration(
+    tx: &mut Transaction<'_, Sqlite>,
+) -> Result<(), sqlx::Error> {
+    for (column, definition) in [
+        ("meta_robots", "TEXT DEFAULT NULL"),
+        ("custom_headers_json", "TEXT NOT NULL DEFAULT '{}'"),
+    ] {
+        let exists: bool = sqlx::query_scalar(&format!(
+            "SELECT COUNT(*) FROM pragma_table_info('site_pages') WHERE name='{column}'"
+        ))
+        .fetch_one(&mut **tx)
+        .await
+        .map(|count: i64| count > 0)?;
+
+        if !exists {
+            tracing::info!("Adding {} column to site_pages table", column);
+            add_column_if_missing_race_safe(
+                tx,
+                &format!("ALTER TABLE site_pages ADD COLUMN {column} {definition}"),
+            )
+            .await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Creates the `content_events` table behind the public changelog.
+///
+/// Rows are append-only; deleting a tutorial or post drops its events via
+/// triggers so a later document reusing the same ID starts with a clean
+/// history. Unpublished content is filtered at read time instead.
+pub(super) async fn apply_content_events_migration(
+    tx: &mut Transaction<'_, Sqlite>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS content_events (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            entity_type TEXT NOT NULL CHECK (entity_type IN ('tutorial', 'post')),
+            entity_id TEXT NOT NULL,
+            slug TEXT NOT NULL,
+            title TEXT NOT NULL,
+            kind TEXT NOT NULL CHECK (kind IN ('published', 'updated')),
+            created_at TEXT NOT NULL DEFAULT (datetime('now'))
+        )
+        "#,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_content_events_created ON content_events(created_at DESC, id DESC)",
+    )
+    .execute(&mut **tx)
+    .await?;
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_content_events_entity ON content_events(entity_type, entity_id)",
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TRIGGER IF NOT EXISTS tutorials_events_ad AFTER DELETE ON tutorials BEGIN
+            DELETE FROM content_events WHERE entity_type = 'tutorial' AND entity_id = old.id;
+        END
+        "#,
+    )
+    .execute(&mut **tx)
+    .await?;
+    sqlx::query(
+        r#"
+        CREATE TRIGGER IF NOT EXISTS site_posts_events_ad AFTER DELETE ON site_posts BEGIN
+            DELETE FROM content_events WHERE entity_type = 'post' AND entity_id = old.id;
+        END
+        "#,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Creates the `security_counters` table: one row per day, rejection source
+/// and reason, incremented in place.
+pub(super) async fn apply_security_counters_migration(
+    tx: &mut Transaction<'_, Sqlite>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS security_counters (
+            day TEXT NOT NULL,
+            source TEXT NOT NULL,
+            reason TEXT NOT NULL,
+            count INTEGER NOT NULL DEFAULT 0,
+            PRIMARY KEY (day, source, reason)
+        )
+        "#,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Creates the `deletion_log` table: a snapshot of every hard-deleted
+/// tutorial, page, post or comment, kept for the retention period.
+///
+/// Entries are immutable once written; an update trigger rejects any change.
+/// The only way out is the retention purge deleting whole rows.
+pub(super) async fn apply_deletion_log_migration(
+    tx: &mut Transaction<'_, Sqlite>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS deletion_log (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            entity_type TEXT NOT NULL CHECK (entity_type IN ('tutorial', 'page', 'post', 'comment')),
+            entity_id TEXT NOT NULL,
+            snapshot_json TEXT NOT NULL,
+            deleted_by TEXT NOT NULL,
+            deleted_at TEXT NOT NULL DEFAULT (datetime('now'))
+        )
+        "#,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_deletion_log_deleted_at ON deletion_log(deleted_at DESC, id DESC)",
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TRIGGER IF NOT EXISTS deletion_log_immutable BEFORE UPDATE ON deletion_log BEGIN
+            SELECT RAISE(ABORT, 'deletion_log entries are immutable');
+        END
+        "#,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Two-factor login: the encrypted TOTP secret on `users`, hashed recovery
+/// codes and the short-lived challenges that bridge the password step and
+/// the code step of a login.
+///
+/// `totp_secret` is set as soon as enrollment starts; `totp_enabled` only
+/// flips once a code from the new secret has been confirmed.
+/// `totp_last_step` is the last accepted time step, so a code cannot be
+/// replayed.
+pub(super) async fn apply_two_factor_migration(
+    tx: &mut Transaction<'_, Sqlite>,
+) -> Result<(), sqlx::Error> {
+    for (column, definition) in [
+        ("totp_secret", "TEXT"),
+        ("totp_enabled", "BOOLEAN NOT NULL DEFAULT FALSE"),
+        ("totp_last_step", "INTEGER"),
+    ] {
+        let exists: bool =
+            sqlx::query_scalar("SELECT COUNT(*) FROM pragma_table_info('users') WHERE name = ?")
+                .bind(column)
+                .fetch_one(&mut **tx)
+                .await
+                .map(|count: i64| count > 0)?;
+
+        if !exists {
+            tracing::info!("Adding {} column to users table", column);
+            sqlx::query(&format!(
+                "ALTER TABLE users ADD COLUMN {column} {definition}"
+            ))
+            .execute(&mut **tx)
+            .await?;
+        }
+    }
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS user_recovery_codes (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            user_id INTEGER NOT NULL,
+            code_hash TEXT NOT NULL,
+            used_at TEXT,
+            FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+        )
+        "#,
+    )
+    .execute(&mut **tx)
+    .await?;
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_user_recovery_codes_user ON user_recovery_codes(user_id, code_hash)",
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS two_factor_challenges (
+            token_hash TEXT PRIMARY KEY,
+            user_id INTEGER NOT NULL,
+            expires_at TEXT NOT NULL,
+            attempts INTEGER NOT NULL DEFAULT 0,
+            remember_me BOOLEAN NOT NULL DEFAULT FALSE,
+            FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+        )
+        "#,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    // Carries the login form's "remember me" choice to the second step
+    let has_remember_me: bool = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM pragma_table_info('two_factor_challenges') WHERE name='remember_me'",
+    )
+    .fetch_one(&mut **tx)
+    .await
+    .map(|count: i64| count > 0)?;
+    if !has_remember_me {
+        add_column_if_missing_race_safe(
+            tx,
+            "ALTER TABLE two_factor_challenges ADD COLUMN remember_me BOOLEAN NOT NULL DEFAULT FALSE",
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+pub(super) async fn apply_api_keys_migration(
+    tx: &mut Transaction<'_, Sqlite>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS api_keys (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            user_id INTEGER NOT NULL,
+            name TEXT NOT NULL,
+            key_hash TEXT NOT NULL UNIQUE,
+            prefix TEXT NOT NULL,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            last_used_at TEXT,
+            revoked_at TEXT,
+            FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+        )
+        "#,
+    )
+    .execute(&mut **tx)
+    .await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_api_keys_user ON api_keys(user_id)")
+        .execute(&mut **tx)
+        .await?;
+
+    Ok(())
+}
+
+pub(super) async fn apply_sessions_migration(
+    tx: &mut Transaction<'_, Sqlite>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS sessions (
+            jti TEXT PRIMARY KEY,
+            username TEXT NOT NULL,
+            issued_at TEXT NOT NULL DEFAULT (datetime('now')),
+            expires_at INTEGER NOT NULL,
+            user_agent TEXT,
+            ip_hash TEXT,
+            revoked_at TEXT
+        )
+        "#,
+    )
+    .execute(&mut **tx)
+    .await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_sessions_username ON sessions(username)")
+        .execute(&mut **tx)
+        .await?;
+
+    Ok(())
+}
+
+pub(super) async fn apply_audit_log_migration(
+    tx: &mut Transaction<'_, Sqlite>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS audit_log (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            username TEXT NOT NULL,
+            action TEXT NOT NULL,
+            entity_type TEXT NOT NULL,
+            entity_id TEXT,
+            details TEXT NOT NULL DEFAULT '{}'
+        )
+        "#,
+    )
+    .execute(&mut **tx)
+    .await?;
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_audit_log_entity_type ON audit_log(entity_type, id DESC)",
+    )
+    .execute(&mut **tx)
+    .await?;
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_audit_log_username ON audit_log(username, id DESC)",
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Adds the draft/published workflow to `tutorials`. Tutorials that predate
+/// it were public from creation, so they become published as of their
+/// `created_at`.
+pub(super) async fn apply_tutorial_status_migration(
+    tx: &mut Transaction<'_, Sqlite>,
+) -> Result<(), sqlx::Error> {
+    let has_status: bool = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM pragma_table_info('tutorials') WHERE name='status'",
+    )
+    .fetch_one(&mut **tx)
+    .await
+    .map(|count: i64| count > 0)?;
+
+    if !has_status {
+        tracing::info!("Adding status and published_at columns to tutorials table");
+        add_column_if_missing_race_safe(
+            tx,
+            "ALTER TABLE tutorials ADD COLUMN status TEXT NOT NULL DEFAULT 'published'",
+        )
+        .await?;
+        add_column_if_missing_race_safe(
+            tx,
+            "ALTER TABLE tutorials ADD COLUMN published_at TEXT DEFAULT NULL",
+        )
+        .await?;
+        sqlx::query("UPDATE tutorials SET published_at = created_at WHERE published_at IS NULL")
+            .execute(&mut **tx)
+            .await?;
+    }
+
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_tutorials_status_created ON tutorials(status, created_at)",
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Adds `deleted_at` to `tutorials` for the trash, and keeps trashed
+/// tutorials out of the search index.
+///
+/// The core migrations create `tutorials_fts` and its triggers before this
+/// column is guaranteed to exist, so the update trigger is replaced here and
+/// rows of already trashed tutorials are dropped from the index.
+pub(super) async fn apply_tutorial_trash_migration(
+    tx: &mut Transaction<'_, Sqlite>,
+) -> Result<(), sqlx::Error> {
+    let has_deleted_at: bool = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM pragma_table_info('tutorials') WHERE name='deleted_at'",
+    )
+    .fetch_one(&mut **tx)
+    .await
+    .map(|count: i64| count > 0)?;
+
+    if !has_deleted_at {
+        tracing::info!("Adding deleted_at column to tutorials table");
+        add_column_if_missing_race_safe(
+            tx,
+            "ALTER TABLE tutorials ADD COLUMN deleted_at TEXT DEFAULT NULL",
+        )
+        .await?;
+    }
+
+    sqlx::query("DROP TRIGGER IF EXISTS tutorials_au")
+        .execute(&mut **tx)
+        .await?;
+    sqlx::query(
+        r#"
+        CREATE TRIGGER tutorials_au AFTER UPDATE ON tutorials BEGIN
+            DELETE FROM tutorials_fts WHERE tutorial_id = old.id;
+            INSERT INTO tutorials_fts(tutorial_id, title, description, content, topics)
+            SELECT new.id, new.title, new.description, new.content, new.topics
+            WHERE new.deleted_at IS NULL;
+        END
+        "#,
+    )
+    .execute(&mut **tx)
+    .await?;
+    sqlx::query(
+        "DELETE FROM tutorials_fts WHERE tutorial_id IN \
+         (SELECT id FROM tutorials WHERE deleted_at IS NOT NULL)",
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Creates `tutorial_revisions`: the state of a tutorial before each update,
+/// keyed by the version it had.
+pub(super) async fn apply_tutorial_revisions_migration(
+    tx: &mut Transaction<'_, Sqlite>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS tutorial_revisions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            tutorial_id TEXT NOT NULL,
+            version INTEGER NOT NULL,
+            title TEXT NOT NULL,
+            description TEXT NOT NULL,
+            content TEXT NOT NULL,
+            topics TEXT NOT NULL,
+            editor TEXT NOT NULL,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            UNIQUE (tutorial_id, version),
+            FOREIGN KEY (tutorial_id) REFERENCES tutorials(id) ON DELETE CASCADE
+        )
+        "#,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Adds `order_index` to `tutorials` for manual ordering. Existing
+/// tutorials are numbered in creation order, which is the order they were
+/// listed in before.
+pub(super) async fn apply_tutorial_order_migration(
+    tx: &mut Transaction<'_, Sqlite>,
+) -> Result<(), sqlx::Error> {
+    let has_order_index: bool = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM pragma_table_info('tutorials') WHERE name='order_index'",
+    )
+    .fetch_one(&mut **tx)
+    .await
+    .map(|count: i64| count > 0)?;
+
+    if !has_order_index {
+        tracing::info!("Adding order_index column to tutorials table");
+        add_column_if_missing_race_safe(
+            tx,
+            "ALTER TABLE tutorials ADD COLUMN order_index INTEGER NOT NULL DEFAULT 0",
+        )
+        .await?;
+        sqlx::query(concat!(
+            "UPDATE tutorials SET order_index = (",
+            "SELECT COUNT(*) FROM tutorials AS earlier ",
+            "WHERE earlier.created_at < tutorials.created_at ",
+            "OR (earlier.created_at = tutorials.created_at AND earlier.id < tutorials.id))"
+        ))
+        .execute(&mut **tx)
+        .await?;
+    }
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_tutorials_order ON tutorials(order_index)")
+        .execute(&mut **tx)
+        .await?;
+
+    Ok(())
+}
+
+/// Creates `tutorial_redirects`, which maps the former IDs of renamed
+/// tutorials to their current one so old links keep resolving.
+pub(super) async fn apply_tutorial_redirects_migration(
+    tx: &mut Transaction<'_, Sqlite>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS tutorial_redirects (
+            old_id TEXT PRIMARY KEY,
+            tutorial_id TEXT NOT NULL,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            FOREIGN KEY (tutorial_id) REFERENCES tutorials(id) ON DELETE CASCADE
+        )
+        "#,
+    )
+    .execute(&mut **tx)
+    .await?;
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_tutorial_redirects_tutorial ON tutorial_redirects(tutorial_id)",
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Creates `content_views`: daily read counts of tutorials and posts.
+pub(super) async fn apply_content_views_migration(
+    tx: &mut Transaction<'_, Sqlite>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS content_views (
+            entity_type TEXT NOT NULL CHECK (entity_type IN ('tutorial', 'post')),
+            entity_id TEXT NOT NULL,
+            day TEXT NOT NULL,
+            count INTEGER NOT NULL DEFAULT 0,
+            PRIMARY KEY (entity_type, entity_id, day)
+        )
+        "#,
+    )
+    .execute(&mut **tx)
+    .await?;
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_content_views_day ON content_views(entity_type, day)",
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Icons tutorials may use, seeded with the names that used to be hardcoded.
+pub(super) async fn apply_allowed_icons_migration(
+    tx: &mut Transaction<'_, Sqlite>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS allowed_icons (
+            name TEXT PRIMARY KEY,
+            created_at TEXT NOT NULL DEFAULT (datetime('now'))
+        )
+        "#,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    let seeded: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM app_metadata WHERE key = 'allowed_icons_seeded'")
+            .fetch_optional(&mut **tx)
+            .await?;
+    if seeded.is_none() {
+        for name in [
+            "Terminal",
+            "FolderTree",
+            "FileText",
+            "Settings",
+            "Shield",
+            "Network",
+            "Database",
+            "Server",
+        ] {
+            sqlx::query("INSERT OR IGNORE INTO allowed_icons (name) VALUES (?)")
+                .bind(name)
+                .execute(&mut **tx)
+                .await?;
+        }
+        sqlx::query(
+            "INSERT INTO app_metadata (key, value) VALUES ('allowed_icons_seeded', datetime('now'))",
+        )
+        .execute(&mut **tx)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Adds `allow_comments` to `tutorials`, like `site_posts` already has.
+/// Existing tutorials stay open for comments.
+pub(super) async fn apply_tutorial_allow_comments_migration(
+    tx: &mut Transaction<'_, Sqlite>,
+) -> Result<(), sqlx::Error> {
+    let has_allow_comments: bool = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM pragma_table_info('tutorials') WHERE name='allow_comments'",
+    )
+    .fetch_one(&mut **tx)
+    .await
+    .map(|count: i64| count > 0)?;
+
+    if !has_allow_comments {
+        tracing::info!("Adding allow_comments column to tutorials table");
+        add_column_if_missing_race_safe(
+            tx,
+            "ALTER TABLE tutorials ADD COLUMN allow_comments BOOLEAN NOT NULL DEFAULT 1",
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Adds `created_by` and `updated_by` (usernames) to `tutorials`. Tutorials
+/// written before have no recorded author.
+pub(super) async fn apply_tutorial_authorship_migration(
+    tx: &mut Transaction<'_, Sqlite>,
+) -> Result<(), sqlx::Error> {
+    for column in ["created_by", "updated_by"] {
+        let exists: bool = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM pragma_table_info('tutorials') WHERE name = ?",
+        )
+        .bind(column)
+        .fetch_one(&mut **tx)
+        .await
+        .map(|count: i64| count > 0)?;
+
+        if !exists {
+            tracing::info!("Adding {} column to tutorials table", column);
+            add_column_if_missing_race_safe(
+                tx,
+                &format!("ALTER TABLE tutorials ADD COLUMN {column} TEXT DEFAULT NULL"),
+            )
+            .await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Text indexed for a tutorial: its own `content` followed by the titles
+/// and content of its sections in order. `{id}` is the tutorial ID
+/// expression (`new.id`, `t.id`, ...).
+pub(crate) fn indexed_tutorial_content(content: &str, id: &str) -> String {
+    format!(
+        "{content} || COALESCE((SELECT char(10) || group_concat(title || char(10) || content, char(10)) \
+         FROM (SELECT title, content FROM tutorial_sections \
+               WHERE tutorial_id = {id} ORDER BY order_index, section_id)), '')"
+    )
+}
+
+/// Creates `tutorial_sections`, optional chapters of a tutorial, and makes
+/// `tutorials_fts` index their text as part of the tutorial's `content`.
+///
+/// Like [`apply_tutorial_trash_migration`], this replaces triggers the core
+/// migrations create, and refreshes the index rows of tutorials that have
+/// sections.
+pub(super) async fn apply_tutorial_sections_migration(
+    tx: &mut Transaction<'_, Sqlite>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS tutorial_sections (
+            tutorial_id TEXT NOT NULL,
+            section_id TEXT NOT NULL,
+            title TEXT NOT NULL,
+            content TEXT NOT NULL,
+            order_index INTEGER NOT NULL DEFAULT 0,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+            PRIMARY KEY (tutorial_id, section_id),
+            FOREIGN KEY (tutorial_id) REFERENCES tutorials(id)
+                ON DELETE CASCADE ON UPDATE CASCADE
+        )
+        "#,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    for trigger in [
+        "tutorials_ai",
+        "tutorials_au",
+        "tutorial_sections_ai",
+        "tutorial_sections_au",
+        "tutorial_sections_ad",
+    ] {
+        sqlx::query(&format!("DROP TRIGGER IF EXISTS {trigger}"))
+            .execute(&mut **tx)
+            .await?;
+    }
+
+    let new_content = indexed_tutorial_content("new.content", "new.id");
+    sqlx::query(&format!(
+        r#"
+        CREATE TRIGGER tutorials_ai AFTER INSERT ON tutorials BEGIN
+            INSERT INTO tutorials_fts(tutorial_id, title, description, content, topics)
+            VALUES (new.id, new.title, new.description, {new_content}, new.topics);
+        END
+        "#
+    ))
+    .execute(&mut **tx)
+    .await?;
+    sqlx::query(&format!(
+        r#"
+        CREATE TRIGGER tutorials_au AFTER UPDATE ON tutorials BEGIN
+            DELETE FROM tutorials_fts WHERE tutorial_id = old.id;
+            INSERT INTO tutorials_fts(tutorial_id, title, description, content, topics)
+            SELECT new.id, new.title, new.description, {new_content}, new.topics
+            WHERE new.deleted_at IS NULL;
+        END
+        "#
+    ))
+    .execute(&mut **tx)
+    .await?;
+
+    // A section change rebuilds the index row of the tutorial it belongs to
+    let reindex = |id: &str| {
+        format!(
+            "DELETE FROM tutorials_fts WHERE tutorial_id = {id}; \
+             INSERT INTO tutorials_fts(tutorial_id, title, description, content, topics) \
+             SELECT t.id, t.title, t.description, {}, t.topics FROM tutorials AS t \
+             WHERE t.id = {id} AND t.deleted_at IS NULL;",
+            indexed_tutorial_content("t.content", "t.id")
+        )
+    };
+    for (trigger, event, body) in [
+        ("tutorial_sections_ai", "INSERT", reindex("new.tutorial_id")),
+        (
+            "tutorial_sections_au",
+            "UPDATE",
+            format!(
+                "{} {}",
+                reindex("old.tutorial_id"),
+                reindex("new.tutorial_id")
+            ),
+        ),
+        ("tutorial_sections_ad", "DELETE", reindex("old.tutorial_id")),
+    ] {
+        sqlx::query(&format!(
+            "CREATE TRIGGER {trigger} AFTER {event} ON tutorial_sections BEGIN {body} END"
+        ))
+        .execute(&mut **tx)
+        .await?;
+    }
+
+    // A freshly created index holds `content` alone
+    sqlx::query(&format!(
+        "DELETE FROM tutorials_fts WHERE tutorial_id IN \
+         (SELECT DISTINCT tutorial_id FROM tutorial_sections); \
+         INSERT INTO tutorials_fts(tutorial_id, title, description, content, topics) \
+         SELECT t.id, t.title, t.description, {}, t.topics FROM tutorials AS t \
+         WHERE t.deleted_at IS NULL \
+         AND t.id IN (SELECT DISTINCT tutorial_id FROM tutorial_sections)",
+        indexed_tutorial_content("t.content", "t.id")
+    ))
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Adds `comments.parent_id` for threaded replies. Deleting a comment removes
+/// its replies with it; existing comments become top-level threads.
+pub(super) async fn apply_comment_threads_migration(
+    tx: &mut Transaction<'_, Sqlite>,
+) -> Result<(), sqlx::Error> {
+    let has_parent_id: bool = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM pragma_table_info('comments') WHERE name='parent_id'",
+    )
+    .fetch_one(&mut **tx)
+    .await
+    .map(|count: i64| count > 0)?;
+
+    if !has_parent_id {
+        tracing::info!("Adding parent_id column to comments table");
+        add_column_if_missing_race_safe(
+            tx,
+            concat!(
+                "ALTER TABLE comments ADD COLUMN parent_id TEXT DEFAULT NULL ",
+                "REFERENCES comments(id) ON DELETE CASCADE"
+            ),
+        )
+        .await?;
+    }
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_comments_parent_id ON comments(parent_id)")
+        .execute(&mut **tx)
+        .await?;
+
+    Ok(())
+}
+
+/// Adds `comments.avatar_hash`, the Gravatar hash of the commenter's email.
+pub(super) async fn apply_comment_avatar_hash_migration(
+    tx: &mut Transaction<'_, Sqlite>,
+) -> Result<(), sqlx::Error> {
+    let has_avatar_hash: bool = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM pragma_table_info('comments') WHERE name='avatar_hash'",
+    )
+    .fetch_one(&mut **tx)
+    .await
+    .map(|count: i64| count > 0)?;
+
+    if !has_avatar_hash {
+        tracing::info!("Adding avatar_hash column to comments table");
+        add_column_if_missing_race_safe(
+            tx,
+            "ALTER TABLE comments ADD COLUMN avatar_hash TEXT DEFAULT NULL",
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Creates `comment_blocklist` and adds `comments.status`, which holds back
+/// comments that tripped a blocked word until a moderator sees them.
+pub(super) async fn apply_comment_blocklist_migration(
+    tx: &mut Transaction<'_, Sqlite>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS comment_blocklist (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            kind TEXT NOT NULL CHECK (kind IN ('word', 'ip_hash', 'author_name')),
+            value TEXT NOT NULL,
+            created_by TEXT NOT NULL,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            UNIQUE (kind, value)
+        )
+        "#,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    let has_status: bool = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM pragma_table_info('comments') WHERE name='status'",
+    )
+    .fetch_one(&mut **tx)
+    .await
+    .map(|count: i64| count > 0)?;
+
+    if !has_status {
+        tracing::info!("Adding status column to comments table");
+        add_column_if_missing_race_safe(
+            tx,
+            "ALTER TABLE comments ADD COLUMN status TEXT NOT NULL DEFAULT 'published'",
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Adds `comments.edited_at`, set when a comment's content is changed after
+/// posting.
+pub(super) async fn apply_comment_edited_at_migration(
+    tx: &mut Transaction<'_, Sqlite>,
+) -> Result<(), sqlx::Error> {
+    let has_edited_at: bool = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM pragma_table_info('comments') WHERE name='edited_at'",
+    )
+    .fetch_one(&mut **tx)
+    .await
+    .map(|count: i64| count > 0)?;
+
+    if !has_edited_at {
+        tracing::info!("Adding edited_at column to comments table");
+        add_column_if_missing_race_safe(
+            tx,
+            "ALTER TABLE comments ADD COLUMN edited_at TEXT DEFAULT NULL",
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Creates `search_log`: one row per public search, with the normalized
+/// query and how many tutorials it found. Rows are purged after
+/// `SEARCH_LOG_RETENTION_DAYS`.
+pub(super) async fn apply_search_log_migration(
+    tx: &mut Transaction<'_, Sqlite>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS search_log (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            query TEXT NOT NULL,
+            result_count INTEGER NOT NULL,
+            ip_hash TEXT,
+            searched_at TEXT NOT NULL DEFAULT (datetime('now'))
+        )
+        "#,
+    )
+    .execute(&mut **tx)
+    .await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_search_log_searched_at ON search_log(searched_at)")
+        .execute(&mut **tx)
+        .await?;
+
+    Ok(())
+}
+
+/// Adds `preview_nonce` to pages and posts. Preview links are signed over
+/// it, so clearing it revokes every link handed out for that page or post.
+pub(super) async fn apply_preview_nonce_migration(
+    tx: &mut Transaction<'_, Sqlite>,
+) -> Result<(), sqlx::Error> {
+    for table in ["site_pages", "site_posts"] {
+        let exists: bool = sqlx::query_scalar(&format!(
+            "SELECT COUNT(*) FROM pragma_table_info('{table}') WHERE name='preview_nonce'"
+        ))
+        .fetch_one(&mut **tx)
+        .await
+        .map(|count: i64| count > 0)?;
+
+        if !exists {
+            tracing::info!("Adding preview_nonce column to {} table", table);
+            add_column_if_missing_race_safe(
+                tx,
+                &format!("ALTER TABLE {table} ADD COLUMN preview_nonce TEXT DEFAULT NULL"),
+            )
+            .await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Creates `post_tags`: the lowercase tags of each site post, removed with
+/// their post.
+pub(super) async fn apply_post_tags_migration(
+    tx: &mut Transaction<'_, Sqlite>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS post_tags (
+            post_id TEXT NOT NULL,
+            tag TEXT NOT NULL,
+            PRIMARY KEY (post_id, tag),
+            CONSTRAINT fk_post_tags_post
+                FOREIGN KEY (post_id) REFERENCES site_posts(id)
+                ON DELETE CASCADE ON UPDATE CASCADE
+        )
+        "#,
+    )
+    .execute(&mut **tx)
+    .await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_post_tags_tag ON post_tags(tag)")
+        .execute(&mut **tx)
+        .await?;
+
+    Ok(())
+}
+
+/// Creates `site_post_revisions`: the state of a site post before each
+/// update, numbered per post.
+pub(super) async fn apply_post_revisions_migration(
+    tx: &mut Transaction<'_, Sqlite>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS site_post_revisions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            post_id TEXT NOT NULL,
+            revision INTEGER NOT NULL,
+            title TEXT NOT NULL,
+            slug TEXT NOT NULL,
+            excerpt TEXT NOT NULL,
+            content_markdown TEXT NOT NULL,
+            is_published INTEGER NOT NULL,
+            published_at TEXT,
+            editor TEXT NOT NULL,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            UNIQUE (post_id, revision),
+            FOREIGN KEY (post_id) REFERENCES site_posts(id) ON DELETE CASCADE
+        )
+        "#,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Adds the cover image and SEO columns to `site_posts`. All stay NULL
+/// until an editor sets them.
+pub(super) async fn apply_post_seo_migration(
+    tx: &mut Transaction<'_, Sqlite>,
+) -> Result<(), sqlx::Error> {
+    for column in ["cover_image_url", "meta_description", "canonical_url"] {
+        let exists: bool = sqlx::query_scalar(&format!(
+            "SELECT COUNT(*) FROM pragma_table_info('site_posts') WHERE name='{column}'"
+        ))
+        .fetch_one(&mut **tx)
+        .await
+        .map(|count: i64| count > 0)?;
+
+        if !exists {
+            tracing::info!("Adding {} column to site_posts table", column);
+            add_column_if_missing_race_safe(
+                tx,
+                &format!("ALTER TABLE site_posts ADD COLUMN {column} TEXT DEFAULT NULL"),
+            )
+            .await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Creates `post_slug_history`: slugs a site post was reachable under
+/// before it was renamed, so old links can be resolved. A slug is unique
+/// per page, like the live ones, and its entry goes with the post.
+pub(super) async fn apply_post_slug_history_migration(
+    tx: &mut Transaction<'_, Sqlite>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS post_slug_history (
+            page_id TEXT NOT NULL,
+            slug TEXT NOT NULL,
+            post_id TEXT NOT NULL,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            PRIMARY KEY (page_id, slug),
+            FOREIGN KEY (post_id) REFERENCES site_posts(id) ON DELETE CASCADE
+        )
+        "#,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_post_slug_history_post ON post_slug_history(post_id)",
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Adds `deleted_at` to `site_pages` and `site_posts` for the trash. A
+/// trashed page hides its posts without marking them.
+pub(super) async fn apply_site_trash_migration(
+    tx: &mut Transaction<'_, Sqlite>,
+) -> Result<(), sqlx::Error> {
+    for table in ["site_pages", "site_posts"] {
+        let has_deleted_at: bool = sqlx::query_scalar(&format!(
+            "SELECT COUNT(*) FROM pragma_table_info('{table}') WHERE name='deleted_at'"
+        ))
+        .fetch_one(&mut **tx)
+        .await
+        .map(|count: i64| count > 0)?;
+
+        if !has_deleted_at {
+            tracing::info!("Adding deleted_at column to {} table", table);
+            add_column_if_missing_race_safe(
+                tx,
+                &format!("ALTER TABLE {table} ADD COLUMN deleted_at TEXT DEFAULT NULL"),
+            )
+            .await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Creates `site_content_revisions`: every version saved of a site content
+/// section, the current one included. `updated_by` is NULL for content that
+/// predates the table.
+pub(super) async fn apply_site_content_revisions_migration(
+    tx: &mut Transaction<'_, Sqlite>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS site_content_revisions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            section TEXT NOT NULL,
+            content_json TEXT NOT NULL,
+            updated_by TEXT,
+            created_at TEXT NOT NULL DEFAULT (datetime('now'))
+        )
+        "#,
+    )
+    .execute(&mut **tx)
+    .await?;
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_site_content_revisions_section ON site_content_revisions(section, id DESC)",
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Site content sections the API manages, seeded with the names that used to
+/// be hardcoded. Like `allowed_icons`, the seed runs once, so a removed
+/// section stays removed.
+pub(super) async fn apply_content_sections_migration(
+    tx: &mut Transaction<'_, Sqlite>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS content_sections (
+            name TEXT PRIMARY KEY,
+            created_at TEXT NOT NULL DEFAULT (datetime('now'))
+        )
+        "#,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    let seeded: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM app_metadata WHERE key = 'content_sections_seeded'")
+            .fetch_optional(&mut **tx)
+            .await?;
+    if seeded.is_none() {
+        for name in crate::models::BUILT_IN_CONTENT_SECTIONS {
+            sqlx::query("INSERT OR IGNORE INTO content_sections (name) VALUES (?)")
+                .bind(name)
+                .execute(&mut **tx)
+                .await?;
+        }
+        sqlx::query(
+            "INSERT INTO app_metadata (key, value) VALUES ('content_sections_seeded', datetime('now'))",
+        )
+        .execute(&mut **tx)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Uploaded files with their metadata. Files uploaded before this table
+/// existed stay untracked.
+pub(super) async fn apply_uploads_migration(
+    tx: &mut Transaction<'_, Sqlite>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS uploads (
+            id TEXT PRIMARY KEY,
+            filename TEXT NOT NULL UNIQUE,
+            original_name TEXT NOT NULL,
+            mime TEXT NOT NULL,
+            size_bytes INTEGER NOT NULL,
+            width INTEGER,
+            height INTEGER,
+            uploaded_by TEXT,
+            created_at TEXT NOT NULL DEFAULT (datetime('now'))
+        )
+        "#,
+    )
+    .execute(&mut **tx)
+    .await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_uploads_created_at ON uploads(created_at DESC)")
+        .execute(&mut **tx)
+        .await?;
+
+    Ok(())
+}
+
+/// Resized copies of uploaded images, one row per width; removed with
+/// their upload.
+pub(super) async fn apply_upload_variants_migration(
+    tx: &mut Transaction<'_, Sqlite>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS upload_variants (
+            upload_id TEXT NOT NULL REFERENCES uploads(id) ON DELETE CASCADE,
+            width INTEGER NOT NULL,
+            height INTEGER NOT NULL,
+            filename TEXT NOT NULL UNIQUE,
+            size_bytes INTEGER NOT NULL,
+            PRIMARY KEY (upload_id, width)
+        )
+        "#,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Adds the optional `alt` text and `caption` of uploaded images.
+pub(super) async fn apply_upload_descriptions_migration(
+    tx: &mut Transaction<'_, Sqlite>,
+) -> Result<(), sqlx::Error> {
+    for column in ["alt", "caption"] {
+        let has_column: bool = sqlx::query_scalar(&format!(
+            "SELECT COUNT(*) FROM pragma_table_info('uploads') WHERE name='{column}'"
+        ))
+        .fetch_one(&mut **tx)
+        .await
+        .map(|count: i64| count > 0)?;
+
+        if !has_column {
+            tracing::info!("Adding {} column to uploads table", column);
+            add_column_if_missing_race_safe(
+                tx,
+                &format!("ALTER TABLE uploads ADD COLUMN {column} TEXT DEFAULT NULL"),
+            )
+            .await?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,206 @@
+//! Versioned schema migrations.
+//!
+//! Every schema change is a numbered [`Migration`] in [`MIGRATIONS`]. The
+//! versions that have run are recorded in `schema_migrations`, so each
+//! migration runs exactly once, in its own transaction together with that
+//! record. New migrations are appended with the next version; released ones
+//! are never renumbered or edited.
+
+use super::*;
+use std::future::Future;
+use std::pin::Pin;
+
+type Step = for<'a> fn(
+    &'a mut Transaction<'static, Sqlite>,
+) -> Pin<Box<dyn Future<Output = Result<(), sqlx::Error>> + Send + 'a>>;
+
+/// One numbered schema change.
+pub struct Migration {
+    pub version: i64,
+    pub name: &'static str,
+    run: Step,
+}
+
+/// A migration recorded in `schema_migrations`.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct AppliedMigration {
+    pub version: i64,
+    pub name: String,
+    pub applied_at: String,
+}
+
+/// Migrations up to this version were the startup steps that ran on every
+/// start before migrations were versioned. Each checks for itself whether
+/// it is needed, so on a database created before `schema_migrations` they
+/// run once more and are recorded.
+pub const BASELINE_VERSION: i64 = 48;
+
+macro_rules! migrations {
+    ($($version:literal => $name:literal: $step:path,)*) => {
+        /// All migrations, oldest first.
+        pub static MIGRATIONS: &[Migration] = &[
+            $(Migration {
+                version: $version,
+                name: $name,
+                run: |tx| Box::pin($step(tx)),
+            },)*
+        ];
+    };
+}
+
+migrations! {
+    1 => "core_schema": apply_core_migrations,
+    2 => "login_attempt_keys": apply_login_attempt_migrations,
+    3 => "comment_post_ids": apply_comment_migrations,
+    4 => "comment_votes": apply_vote_migration,
+    5 => "nullable_comment_tutorial": fix_comment_schema,
+    6 => "comment_votes_foreign_key": repair_comment_votes_foreign_key,
+    7 => "comment_author_identity": apply_comment_author_identity_migration,
+    8 => "comment_user_ids": apply_comment_user_id_migration,
+    9 => "comment_keyset_indexes": apply_comment_keyset_indexes,
+    10 => "hashed_token_blacklist": apply_token_blacklist_hash_migration,
+    11 => "site_pages": ensure_site_page_schema,
+    12 => "site_content_branding": apply_site_content_branding_migration,
+    13 => "site_post_comments": apply_site_post_migrations,
+    14 => "site_page_seo": apply_site_page_seo_migration,
+    15 => "content_stats": apply_content_stats_migration,
+    16 => "content_events": apply_content_events_migration,
+    17 => "security_counters": apply_security_counters_migration,
+    18 => "deletion_log": apply_deletion_log_migration,
+    19 => "two_factor": apply_two_factor_migration,
+    20 => "api_keys": apply_api_keys_migration,
+    21 => "sessions": apply_sessions_migration,
+    22 => "audit_log": apply_audit_log_migration,
+    23 => "tutorial_status": apply_tutorial_status_migration,
+    24 => "tutorial_trash": apply_tutorial_trash_migration,
+    25 => "tutorial_revisions": apply_tutorial_revisions_migration,
+    26 => "tutorial_order": apply_tutorial_order_migration,
+    27 => "tutorial_redirects": apply_tutorial_redirects_migration,
+    28 => "content_views": apply_content_views_migration,
+    29 => "allowed_icons": apply_allowed_icons_migration,
+    30 => "tutorial_allow_comments": apply_tutorial_allow_comments_migration,
+    31 => "tutorial_authorship": apply_tutorial_authorship_migration,
+    32 => "tutorial_sections": apply_tutorial_sections_migration,
+    33 => "comment_threads": apply_comment_threads_migration,
+    34 => "comment_edited_at": apply_comment_edited_at_migration,
+    35 => "comment_avatars": apply_comment_avatar_hash_migration,
+    36 => "comment_blocklist": apply_comment_blocklist_migration,
+    37 => "search_log": apply_search_log_migration,
+    38 => "preview_nonces": apply_preview_nonce_migration,
+    39 => "post_tags": apply_post_tags_migration,
+    40 => "post_revisions": apply_post_revisions_migration,
+    41 => "post_seo": apply_post_seo_migration,
+    42 => "post_slug_history": apply_post_slug_history_migration,
+    43 => "site_trash": apply_site_trash_migration,
+    44 => "site_content_revisions": apply_site_content_revisions_migration,
+    45 => "content_sections": apply_content_sections_migration,
+    46 => "uploads": apply_uploads_migration,
+    47 => "upload_variants": apply_upload_variants_migration,
+    48 => "upload_descriptions": apply_upload_descriptions_migration,
+}
+
+async fn ensure_history_table(pool: &DbPool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS schema_migrations (
+            version INTEGER PRIMARY KEY,
+            name TEXT NOT NULL,
+            applied_at TEXT NOT NULL DEFAULT (datetime('now'))
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// The migrations recorded as applied, oldest first.
+pub async fn applied_migrations(pool: &DbPool) -> Result<Vec<AppliedMigration>, sqlx::Error> {
+    ensure_history_table(pool).await?;
+    sqlx::query_as("SELECT version, name, applied_at FROM schema_migrations ORDER BY version")
+        .fetch_all(pool)
+        .await
+}
+
+/// The migrations not applied yet, oldest first.
+pub async fn pending_migrations(pool: &DbPool) -> Result<Vec<&'static Migration>, sqlx::Error> {
+    let applied = applied_migrations(pool).await?;
+    Ok(MIGRATIONS
+        .iter()
+        .filter(|migration| {
+            !applied
+                .iter()
+                .any(|applied| applied.version == migration.version)
+        })
+        .collect())
+}
+
+/// Applies the pending migrations in order and returns them. Stops at the
+/// first failure, whose migration is rolled back and stays pending.
+pub async fn apply_pending_migrations(
+    pool: &DbPool,
+) -> Result<Vec<&'static Migration>, sqlx::Error> {
+    let applied = applied_migrations(pool).await?;
+    if let Some(latest) = applied.last() {
+        if MIGRATIONS.iter().all(|m| m.version != latest.version) {
+            tracing::warn!(
+                "Database has migration {} ({}) which this build does not know; it is newer than this build",
+                latest.version,
+                latest.name
+            );
+        }
+    } else if has_legacy_schema(pool).await? {
+        tracing::info!(
+            "Database predates schema_migrations; checking migrations 1-{} against it once",
+            BASELINE_VERSION
+        );
+    }
+
+    let mut ran = Vec::new();
+    for migration in pending_migrations(pool).await? {
+        // Take the write lock up front, so that of two processes starting
+        // at once only one runs the migration; the other sees it recorded.
+        let mut tx = pool.begin_with("BEGIN IMMEDIATE").await?;
+        let recorded: Option<i64> =
+            sqlx::query_scalar("SELECT version FROM schema_migrations WHERE version = $1")
+                .bind(migration.version)
+                .fetch_optional(&mut *tx)
+                .await?;
+        if recorded.is_some() {
+            continue;
+        }
+
+        if let Err(err) = (migration.run)(&mut tx).await {
+            tracing::error!(
+                "Migration {} ({}) failed: {}",
+                migration.version,
+                migration.name,
+                err
+            );
+            return Err(err);
+        }
+        sqlx::query("INSERT INTO schema_migrations (version, name) VALUES ($1, $2)")
+            .bind(migration.version)
+            .bind(migration.name)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        tracing::info!(
+            "Applied migration {} ({})",
+            migration.version,
+            migration.name
+        );
+        ran.push(migration);
+    }
+    Ok(ran)
+}
+
+/// Whether the database has tables from before `schema_migrations`.
+async fn has_legacy_schema(pool: &DbPool) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'users')",
+    )
+    .fetch_one(pool)
+    .await
+}
@@ -1,8 +1,8 @@
 use super::*;
 
-pub(super) async fn ensure_site_page_schema(pool: &DbPool) -> Result<(), sqlx::Error> {
-    let mut tx = pool.begin().await?;
-
+pub(super) async fn ensure_site_page_schema(
+    tx: &mut Transaction<'_, Sqlite>,
+) -> Result<(), sqlx::Error> {
     sqlx::query(
         "CREATE TABLE IF NOT EXISTS site_content (
             section TEXT PRIMARY KEY,
@@ -10,7 +10,7 @@ pub(super) async fn ensure_site_page_schema(pool: &DbPool) -> Result<(), sqlx::E
             updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
         )",
     )
-    .execute(&mut *tx)
+    .execute(&mut **tx)
     .await?;
 
     sqlx::query(
@@ -29,13 +29,13 @@ pub(super) async fn ensure_site_page_schema(pool: &DbPool) -> Result<(), sqlx::E
             updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
         )",
     )
-    .execute(&mut *tx)
+    .execute(&mut **tx)
     .await?;
 
     sqlx::query(
         "CREATE INDEX IF NOT EXISTS idx_site_pages_nav ON site_pages(show_in_nav, order_index)",
     )
-    .execute(&mut *tx)
+    .execute(&mut **tx)
     .await?;
 
     sqlx::query(
@@ -55,22 +55,20 @@ pub(super) async fn ensure_site_page_schema(pool: &DbPool) -> Result<(), sqlx::E
             FOREIGN KEY(page_id) REFERENCES site_pages(id) ON DELETE CASCADE
         )",
     )
-    .execute(&mut *tx)
+    .execute(&mut **tx)
     .await?;
 
     sqlx::query(
         "CREATE UNIQUE INDEX IF NOT EXISTS idx_site_posts_unique_slug ON site_posts(page_id, slug)",
     )
-    .execute(&mut *tx)
+    .execute(&mut **tx)
     .await?;
 
     sqlx::query(
         "CREATE INDEX IF NOT EXISTS idx_site_posts_page_published ON site_posts(page_id, is_published, published_at)",
     )
-    .execute(&mut *tx)
+    .execute(&mut **tx)
     .await?;
 
-    tx.commit().await?;
-
     Ok(())
 }
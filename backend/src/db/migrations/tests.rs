@@ -1,4 +1,4 @@
-use super::run_migrations;
+use super::{apply_pending_migrations, pending_migrations, run_migrations, MIGRATIONS};
 use sqlx::sqlite::SqlitePoolOptions;
 
 #[tokio::test]
@@ -267,6 +267,11 @@ async fn run_migrations_updates_persisted_site_branding() {
         .execute(&pool)
         .await
         .expect("insert stale site branding");
+    // A database from before the migration history replays the baseline
+    sqlx::query("DROP TABLE schema_migrations")
+        .execute(&pool)
+        .await
+        .expect("forget migration history");
 
     run_migrations(&pool)
         .await
@@ -282,3 +287,394 @@ async fn run_migrations_updates_persisted_site_branding() {
     assert_eq!(content["brand"]["name"], "minos");
     assert_eq!(content["nested"][0], "minos archive");
 }
+
+#[tokio::test]
+async fn comment_list_queries_use_keyset_indexes() {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .expect("create sqlite pool");
+
+    run_migrations(&pool).await.expect("create current schema");
+
+    for (scope, index) in [
+        ("tutorial_id", "idx_comments_tutorial_created"),
+        ("post_id", "idx_comments_post_created"),
+    ] {
+        let plan: Vec<(i64, i64, i64, String)> = sqlx::query_as(&format!(
+            "EXPLAIN QUERY PLAN SELECT id FROM comments WHERE {scope} = 'x' \
+             AND (created_at < '2024' OR (created_at = '2024' AND id < 'c')) \
+             ORDER BY created_at DESC, id DESC LIMIT 10"
+        ))
+        .fetch_all(&pool)
+        .await
+        .expect("explain comment list query");
+
+        assert!(
+            plan.iter().any(|(_, _, _, detail)| detail.contains(index)),
+            "expected {index} in plan: {plan:?}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn comment_votes_reference_the_rebuilt_comments_table() {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .expect("create sqlite pool");
+
+    run_migrations(&pool).await.expect("create current schema");
+
+    let targets: Vec<String> =
+        sqlx::query_scalar("SELECT \"table\" FROM pragma_foreign_key_list('comment_votes')")
+            .fetch_all(&pool)
+            .await
+            .expect("read comment_votes foreign keys");
+    assert_eq!(targets, ["comments"]);
+
+    sqlx::query("INSERT INTO comments (id, author, content) VALUES ('c1', 'Guest', 'Hi')")
+        .execute(&pool)
+        .await
+        .expect("insert comment");
+    sqlx::query("INSERT INTO comment_votes (comment_id, voter_id) VALUES ('c1', 'voter')")
+        .execute(&pool)
+        .await
+        .expect("vote is accepted");
+    sqlx::query("DELETE FROM comments WHERE id = 'c1'")
+        .execute(&pool)
+        .await
+        .expect("delete comment");
+    let remaining: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM comment_votes")
+        .fetch_one(&pool)
+        .await
+        .expect("count votes");
+    assert_eq!(remaining, 0);
+}
+
+#[tokio::test]
+async fn run_migrations_links_legacy_comments_to_matching_accounts() {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .expect("create sqlite pool");
+
+    sqlx::query(
+        r#"
+            CREATE TABLE users (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                username TEXT NOT NULL UNIQUE,
+                password_hash TEXT NOT NULL,
+                role TEXT NOT NULL DEFAULT 'user',
+                created_at TEXT NOT NULL DEFAULT (datetime('now'))
+            )
+            "#,
+    )
+    .execute(&pool)
+    .await
+    .expect("create legacy users table");
+    sqlx::query("INSERT INTO users (id, username, password_hash) VALUES (7, 'carol', 'x')")
+        .execute(&pool)
+        .await
+        .expect("insert legacy user");
+
+    sqlx::query(
+        r#"
+            CREATE TABLE tutorials (
+                id TEXT PRIMARY KEY,
+                title TEXT NOT NULL,
+                description TEXT NOT NULL,
+                icon TEXT NOT NULL,
+                color TEXT NOT NULL,
+                topics TEXT NOT NULL,
+                content TEXT NOT NULL DEFAULT '',
+                version INTEGER NOT NULL DEFAULT 1,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+            )
+            "#,
+    )
+    .execute(&pool)
+    .await
+    .expect("create legacy tutorials table");
+    sqlx::query(
+        r#"
+            INSERT INTO tutorials (id, title, description, icon, color, topics)
+            VALUES ('t1', 'Legacy', 'Legacy', 'book', '#000000', 'legacy')
+            "#,
+    )
+    .execute(&pool)
+    .await
+    .expect("insert legacy tutorial");
+
+    sqlx::query(
+        r#"
+            CREATE TABLE comments (
+                id TEXT PRIMARY KEY,
+                tutorial_id TEXT NOT NULL,
+                author TEXT NOT NULL,
+                content TEXT NOT NULL,
+                created_at TEXT NOT NULL DEFAULT (datetime('now'))
+            )
+            "#,
+    )
+    .execute(&pool)
+    .await
+    .expect("create legacy comments table");
+    sqlx::query(
+        r#"
+            INSERT INTO comments (id, tutorial_id, author, content)
+            VALUES ('by-carol', 't1', 'carol', 'Mine'), ('by-guest', 't1', 'Dave', 'Hi')
+            "#,
+    )
+    .execute(&pool)
+    .await
+    .expect("insert legacy comments");
+
+    run_migrations(&pool)
+        .await
+        .expect("migrate legacy comments table");
+
+    let owners: Vec<(String, Option<i64>)> =
+        sqlx::query_as("SELECT id, user_id FROM comments ORDER BY id")
+            .fetch_all(&pool)
+            .await
+            .expect("read comment owners");
+    assert_eq!(
+        owners,
+        [
+            ("by-carol".to_string(), Some(7)),
+            ("by-guest".to_string(), None)
+        ]
+    );
+}
+
+#[tokio::test]
+async fn run_migrations_orders_existing_tutorials_by_creation() {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .expect("create sqlite pool");
+
+    sqlx::query(
+        r#"
+            CREATE TABLE tutorials (
+                id TEXT PRIMARY KEY,
+                title TEXT NOT NULL,
+                description TEXT NOT NULL,
+                icon TEXT NOT NULL,
+                color TEXT NOT NULL,
+                topics TEXT NOT NULL,
+                content TEXT NOT NULL DEFAULT '',
+                version INTEGER NOT NULL DEFAULT 1,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+            )
+            "#,
+    )
+    .execute(&pool)
+    .await
+    .expect("create legacy tutorials table");
+
+    for (id, created_at) in [
+        ("newest", "2024-03-01 00:00:00"),
+        ("oldest", "2024-01-01 00:00:00"),
+        ("middle", "2024-02-01 00:00:00"),
+    ] {
+        sqlx::query(
+            "INSERT INTO tutorials (id, title, description, icon, color, topics, created_at) \
+             VALUES (?, 'Legacy', 'Legacy description', 'book', '#000000', '[]', ?)",
+        )
+        .bind(id)
+        .bind(created_at)
+        .execute(&pool)
+        .await
+        .expect("insert legacy tutorial");
+    }
+
+    run_migrations(&pool)
+        .await
+        .expect("migrate legacy tutorials table");
+
+    let ordered: Vec<String> = sqlx::query_scalar("SELECT id FROM tutorials ORDER BY order_index")
+        .fetch_all(&pool)
+        .await
+        .expect("read tutorial order");
+    assert_eq!(ordered, ["oldest", "middle", "newest"]);
+}
+
+#[tokio::test]
+async fn rerunning_migrations_keeps_the_search_index_and_reindex_repairs_it() {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .expect("create sqlite pool");
+    run_migrations(&pool).await.expect("first migration run");
+
+    let status = crate::repositories::search_index::tutorials_status(&pool)
+        .await
+        .expect("index status");
+    assert_eq!((status.missing, status.stale), (0, 0));
+
+    // Desync the index: drop a real row and add one for no tutorial
+    sqlx::query("DELETE FROM tutorials_fts WHERE tutorial_id = (SELECT id FROM tutorials LIMIT 1)")
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::query(
+        "INSERT INTO tutorials_fts(tutorial_id, title, description, content, topics) \
+         VALUES ('ghost', 'Ghost', '', '', '[]')",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    // A restart no longer rebuilds the index, so the drift stays visible
+    run_migrations(&pool).await.expect("second migration run");
+    let status = crate::repositories::search_index::tutorials_status(&pool)
+        .await
+        .expect("index status");
+    assert_eq!((status.missing, status.stale), (1, 1));
+
+    let indexed = crate::repositories::search_index::rebuild_tutorials(
+        &pool,
+        crate::config::DEFAULT_FTS_TOKENIZER,
+    )
+    .await
+    .expect("rebuild index");
+    let status = crate::repositories::search_index::tutorials_status(&pool)
+        .await
+        .expect("index status");
+    assert_eq!(indexed as i64, status.source_rows);
+    assert_eq!(status.indexed_rows, status.source_rows);
+    assert_eq!((status.missing, status.stale), (0, 0));
+}
+
+#[tokio::test]
+async fn search_index_tokenizer_folds_diacritics_and_can_switch_to_stemming() {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .expect("create sqlite pool");
+    run_migrations(&pool).await.expect("run migrations");
+
+    for (id, title) in [
+        ("uebung", "Übungen zur Shell"),
+        ("editing", "Editor basics"),
+    ] {
+        crate::repositories::tutorials::create_tutorial(
+            &pool,
+            id,
+            title,
+            "Tokenizer",
+            "Body",
+            "Terminal",
+            "from-blue-500 to-indigo-600",
+            "[]",
+            &[],
+            crate::models::TUTORIAL_STATUS_PUBLISHED,
+            true,
+            None,
+        )
+        .await
+        .expect("seed tutorial");
+    }
+    let hits = |raw: &'static str| {
+        let pool = pool.clone();
+        async move {
+            let query = crate::handlers::search::sanitize_fts_query(raw).unwrap();
+            sqlx::query_scalar::<_, String>(
+                "SELECT tutorial_id FROM tutorials_fts WHERE tutorials_fts MATCH ? \
+                 AND tutorial_id IN ('uebung', 'editing')",
+            )
+            .bind(query)
+            .fetch_all(&pool)
+            .await
+            .unwrap()
+        }
+    };
+
+    assert_eq!(hits("ubung").await, ["uebung"]);
+    assert_eq!(hits("Übung").await, ["uebung"]);
+    assert!(hits("editors basics").await.is_empty());
+
+    crate::repositories::search_index::rebuild_tutorials(
+        &pool,
+        "porter unicode61 remove_diacritics 2",
+    )
+    .await
+    .expect("rebuild with porter");
+    let status = crate::repositories::search_index::tutorials_status(&pool)
+        .await
+        .expect("index status");
+    assert_eq!(status.tokenizer, "porter unicode61 remove_diacritics 2");
+    assert_eq!(hits("editors basics").await, ["editing"]);
+    assert_eq!(hits("ubung").await, ["uebung"]);
+
+    // The triggers keep maintaining the rebuilt index
+    sqlx::query("UPDATE tutorials SET title = 'Editors at work' WHERE id = 'uebung'")
+        .execute(&pool)
+        .await
+        .unwrap();
+    assert!(hits("ubung").await.is_empty());
+}
+
+#[tokio::test]
+async fn migrations_run_once_and_are_recorded() {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .expect("create sqlite pool");
+
+    assert!(MIGRATIONS
+        .windows(2)
+        .all(|pair| pair[0].version < pair[1].version));
+
+    run_migrations(&pool).await.expect("first migration run");
+    let recorded: Vec<i64> =
+        sqlx::query_scalar("SELECT version FROM schema_migrations ORDER BY version")
+            .fetch_all(&pool)
+            .await
+            .expect("read migration history");
+    let expected: Vec<i64> = MIGRATIONS.iter().map(|m| m.version).collect();
+    assert_eq!(recorded, expected);
+    assert!(pending_migrations(&pool).await.unwrap().is_empty());
+
+    run_migrations(&pool).await.expect("second migration run");
+    assert!(apply_pending_migrations(&pool).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn failing_migration_is_rolled_back_and_aborts() {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .expect("create sqlite pool");
+
+    // Taken name: the core migration cannot create the search index
+    sqlx::query("CREATE VIEW tutorials_fts AS SELECT 1")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    assert!(run_migrations(&pool).await.is_err());
+    let users_table: bool = sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'users')",
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert!(!users_table);
+    assert_eq!(
+        pending_migrations(&pool).await.unwrap().len(),
+        MIGRATIONS.len()
+    );
+}
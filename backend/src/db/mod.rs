@@ -8,4 +8,4 @@ pub mod migrations; // SQL schema versioning
 pub mod pool; // Connection lifecycle management
 pub mod seed; // Initial data (Default User, etc.)
 
-pub use pool::{create_pool, DbPool};
+pub use pool::{connect, create_pool, DbPool};
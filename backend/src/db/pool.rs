@@ -3,9 +3,12 @@ use sqlx::{
     sqlite::{SqliteConnectOptions, SqlitePoolOptions},
     SqlitePool,
 };
-use std::env;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::time::Duration;
+
+/// SQLite page cache per connection, in KiB (the default is 2 MiB).
+const CACHE_SIZE_KIB: u32 = 16 * 1024;
 
 /// Type alias for the SQLite connection pool.
 /// Used throughout the application for database access.
@@ -13,72 +16,96 @@ pub type DbPool = SqlitePool;
 
 /// Creates and initializes the database connection pool.
 ///
-/// This is the main entry point for database initialization. It:
-/// 1. Loads database URL from environment (defaults to ./database.db)
-/// 2. Ensures the database directory exists
-/// 3. Configures SQLite connection options
-/// 4. Creates connection pool (1-5 connections)
-/// 5. Runs all migrations
+/// This is the main entry point for database initialization. It opens the
+/// pool with [`connect`] and runs all migrations on it.
 ///
-/// # Database Configuration
-/// - **WAL Mode**: Write-Ahead Logging for better concurrency
-/// - **Foreign Keys**: Enabled for referential integrity
-/// - **Synchronous**: Normal mode (balanced safety/performance)
-/// - **Busy Timeout**: 60 seconds to handle lock contention
-/// - **Auto-create**: Database file created if missing
+/// # Returns
+/// - `Ok(DbPool)` on success
+/// - `Err(sqlx::Error)` if initialization fails
+///
+/// # Errors
+/// - Any error of [`connect`]
+/// - Migration failure
+pub async fn create_pool() -> Result<DbPool, sqlx::Error> {
+    let pool = connect().await?;
+
+    // Run all database migrations
+    run_migrations(&pool).await?;
+
+    tracing::info!("Database pool created successfully");
+    Ok(pool)
+}
+
+/// Opens the connection pool without migrating the database.
+///
+/// It:
+/// 1. Reads the database URL from [`crate::config`] (defaults to ./database.db)
+/// 2. Ensures the database directory exists
+/// 3. Configures SQLite connection options (see [`connect_options`])
+/// 4. Creates connection pool
 ///
 /// # Connection Pool
 /// - Min connections: 1 (always ready)
-/// - Max connections: 5 (prevents resource exhaustion)
-/// - Acquire timeout: 30 seconds
+/// - Max connections: `DB_MAX_CONNECTIONS`, 5 by default (prevents resource exhaustion)
+/// - Acquire timeout: `DB_ACQUIRE_TIMEOUT_SECS`, 30 seconds by default
 /// - No idle timeout (connections persist)
 /// - No max lifetime (connections don't expire)
 ///
-/// # Returns
-/// - `Ok(DbPool)` on success
-/// - `Err(sqlx::Error)` if initialization fails
-///
 /// # Errors
 /// - Invalid DATABASE_URL format
 /// - Database directory creation failure
 /// - Connection establishment failure
-/// - Migration failure
 ///
 /// # Environment Variables
-/// - `DATABASE_URL`: SQLite database path (default: "sqlite:./database.db")
-pub async fn create_pool() -> Result<DbPool, sqlx::Error> {
-    // Load database URL from environment or use default
-    let database_url = env::var("DATABASE_URL").unwrap_or_else(|_| {
-        tracing::warn!("DATABASE_URL not set, defaulting to sqlite:./database.db");
-        "sqlite:./database.db".to_string()
-    });
+/// - `DATABASE_URL`: SQLite database path (default: "sqlite:./database.db"),
+///   validated by [`crate::config::Config::from_env`]
+pub async fn connect() -> Result<DbPool, sqlx::Error> {
+    let config = crate::config::get();
+    let database_url = config.database_url.as_str();
 
     // Ensure parent directory exists
-    ensure_sqlite_directory(&database_url)?;
+    ensure_sqlite_directory(database_url)?;
 
-    // Configure SQLite connection options
-    let connect_options = SqliteConnectOptions::from_str(&database_url)?
-        .create_if_missing(true)
-        .journal_mode(sqlx::sqlite::SqliteJournalMode::Wal)
-        .synchronous(sqlx::sqlite::SqliteSynchronous::Normal)
-        .foreign_keys(true)
-        .busy_timeout(std::time::Duration::from_secs(60));
+    let connect_options = connect_options(
+        database_url,
+        Duration::from_secs(u64::from(config.db_busy_timeout_secs)),
+    )?;
 
     // Create connection pool
-    let pool = SqlitePoolOptions::new()
-        .max_connections(5)
+    SqlitePoolOptions::new()
+        .max_connections(config.db_max_connections)
         .min_connections(1)
-        .acquire_timeout(std::time::Duration::from_secs(30))
+        .acquire_timeout(Duration::from_secs(u64::from(
+            config.db_acquire_timeout_secs,
+        )))
         .idle_timeout(None)
         .max_lifetime(None)
         .connect_with(connect_options)
-        .await?;
-
-    // Run all database migrations
-    run_migrations(&pool).await?;
+        .await
+}
 
-    tracing::info!("Database pool created successfully");
-    Ok(pool)
+/// Options every connection to `database_url` is opened with.
+///
+/// - **WAL Mode**: Write-Ahead Logging, so readers don't block the writer
+/// - **Synchronous**: Normal mode (balanced safety/performance; safe in WAL mode)
+/// - **Foreign Keys**: Enforced; deletes rely on `ON DELETE CASCADE`, e.g.
+///   a tutorial's comments
+/// - **Busy Timeout**: `busy_timeout`; a writer waits this long for the
+///   lock before failing with "database is locked"
+/// - **Cache Size**: 16 MiB of page cache per connection
+/// - **Auto-create**: Database file created if missing
+pub fn connect_options(
+    database_url: &str,
+    busy_timeout: Duration,
+) -> Result<SqliteConnectOptions, sqlx::Error> {
+    Ok(SqliteConnectOptions::from_str(database_url)?
+        .create_if_missing(true)
+        .journal_mode(sqlx::sqlite::SqliteJournalMode::Wal)
+        .synchronous(sqlx::sqlite::SqliteSynchronous::Normal)
+        .foreign_keys(true)
+        .busy_timeout(busy_timeout)
+        // Negative sizes are in KiB rather than pages
+        .pragma("cache_size", format!("-{CACHE_SIZE_KIB}")))
 }
 
 fn ensure_sqlite_directory(database_url: &str) -> Result<(), sqlx::Error> {
@@ -136,3 +163,56 @@ fn sqlite_file_path(database_url: &str) -> Option<PathBuf> {
 
     Some(PathBuf::from(normalized))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn connections_enforce_foreign_keys() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(connect_options("sqlite::memory:", Duration::from_secs(5)).unwrap())
+            .await
+            .expect("create sqlite pool");
+        run_migrations(&pool).await.expect("run migrations");
+
+        let (foreign_keys, cache_size): (i64, i64) = sqlx::query_as(
+            "SELECT foreign_keys, cache_size FROM pragma_foreign_keys, pragma_cache_size",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(foreign_keys, 1);
+        assert_eq!(cache_size, -i64::from(CACHE_SIZE_KIB));
+
+        sqlx::query(
+            "INSERT INTO tutorials (id, title, description, icon, color, topics, content) \
+             VALUES ('cascade', 'Cascade', 'Description', 'book', '#000000', '[]', 'Content')",
+        )
+        .execute(&pool)
+        .await
+        .expect("insert tutorial");
+        for id in ["comment-1", "comment-2"] {
+            sqlx::query(
+                "INSERT INTO comments (id, tutorial_id, author, content) \
+                 VALUES (?, 'cascade', 'Reader', 'Nice')",
+            )
+            .bind(id)
+            .execute(&pool)
+            .await
+            .expect("insert comment");
+        }
+
+        let deleted = crate::repositories::tutorials::delete_tutorial(&pool, "cascade", "root")
+            .await
+            .expect("delete tutorial");
+        assert!(deleted);
+        let comments: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM comments WHERE tutorial_id = 'cascade'")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(comments, 0);
+    }
+}
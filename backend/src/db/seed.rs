@@ -234,11 +234,16 @@ pub async fn insert_default_tutorials_tx(
             continue;
         }
 
-        if let Err(err) = crate::handlers::tutorials::validate_icon(icon) {
+        let icon_allowed: Option<(String,)> =
+            sqlx::query_as("SELECT name FROM allowed_icons WHERE name = ?")
+                .bind(icon)
+                .fetch_optional(&mut **tx)
+                .await?;
+        if icon_allowed.is_none() {
             tracing::warn!(
-                "Skipping default tutorial '{}' due to invalid icon: {}",
+                "Skipping default tutorial '{}' due to icon '{}' not being allowed",
                 id,
-                err
+                icon
             );
             continue;
         }
@@ -261,8 +266,10 @@ pub async fn insert_default_tutorials_tx(
 
         sqlx::query(concat!(
             "INSERT INTO tutorials ",
-            "(id, title, description, icon, color, topics, content, version) ",
-            "VALUES (?, ?, ?, ?, ?, ?, ?, 1)"
+            "(id, title, description, icon, color, topics, content, version, published_at, ",
+            "order_index) ",
+            "VALUES (?, ?, ?, ?, ?, ?, ?, 1, datetime('now'), ",
+            "(SELECT COALESCE(MAX(order_index) + 1, 0) FROM tutorials))"
         ))
         .bind(id)
         .bind(title)
@@ -0,0 +1,111 @@
+//! Admin API Key Handlers
+//!
+//! API keys let scripts and integrations call the admin API without a
+//! browser session. A key acts with its owner's current role, is sent in
+//! the `X-Api-Key` header and skips the CSRF check; the admin rate limiter
+//! still applies. The plaintext key is returned once, at creation, and only
+//! its hash is stored.
+
+use crate::{
+    db::DbPool,
+    handlers::common::{map_sqlx_error, require_permission},
+    models::*,
+    repositories,
+    security::auth::{self, Permission},
+};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+
+const MAX_NAME_LENGTH: usize = 100;
+
+/// Handler for `GET /api/admin/api-keys`, including revoked keys.
+/// Admin-only.
+pub async fn list_api_keys(
+    claims: auth::Claims,
+    State(pool): State<DbPool>,
+) -> Result<Json<ApiKeyListResponse>, AppError> {
+    require_permission(&claims, Permission::ManageUsers)?;
+
+    let items = repositories::api_keys::list_keys(&pool)
+        .await
+        .map_err(|err| map_sqlx_error(err, "API key"))?;
+    Ok(Json(ApiKeyListResponse { items }))
+}
+
+/// Handler for `POST /api/admin/api-keys`.
+/// Admin-only, protected by CSRF.
+pub async fn create_api_key(
+    claims: auth::Claims,
+    State(pool): State<DbPool>,
+    Json(payload): Json<CreateApiKeyRequest>,
+) -> Result<(StatusCode, Json<CreatedApiKeyResponse>), AppError> {
+    require_permission(&claims, Permission::ManageUsers)?;
+
+    let name = payload.name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_LENGTH {
+        return Err(bad_request(format!(
+            "Name must be between 1 and {MAX_NAME_LENGTH} characters"
+        )));
+    }
+
+    let owner = match payload.user_id {
+        Some(id) => repositories::users::get_user_by_id(&pool, id).await,
+        None => repositories::users::get_user_by_username(&pool, &claims.sub).await,
+    }
+    .map_err(|err| map_sqlx_error(err, "User"))?
+    .ok_or_else(|| not_found("User not found"))?;
+
+    let key = auth::generate_api_key();
+    let api_key = repositories::api_keys::create_key(
+        &pool,
+        owner.id,
+        name,
+        &auth::hash_api_key(&key),
+        &auth::api_key_display_prefix(&key),
+    )
+    .await
+    .map_err(|err| map_sqlx_error(err, "API key"))?;
+
+    tracing::info!(
+        action = "create_api_key",
+        user = %claims.sub,
+        key_id = api_key.id,
+        owner = %owner.username,
+        "Admin created API key"
+    );
+
+    Ok((
+        StatusCode::CREATED,
+        Json(CreatedApiKeyResponse { api_key, key }),
+    ))
+}
+
+/// Handler for `DELETE /api/admin/api-keys/{id}`. The key stops working
+/// immediately but stays listed as revoked.
+/// Admin-only, protected by CSRF.
+pub async fn revoke_api_key(
+    claims: auth::Claims,
+    State(pool): State<DbPool>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, AppError> {
+    require_permission(&claims, Permission::ManageUsers)?;
+
+    let revoked = repositories::api_keys::revoke_key(&pool, id)
+        .await
+        .map_err(|err| map_sqlx_error(err, "API key"))?;
+    if !revoked {
+        return Err(not_found("API key not found"));
+    }
+
+    tracing::info!(
+        action = "revoke_api_key",
+        user = %claims.sub,
+        key_id = id,
+        "Admin revoked API key"
+    );
+
+    Ok(StatusCode::NO_CONTENT)
+}
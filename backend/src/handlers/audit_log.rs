@@ -0,0 +1,144 @@
+//! Audit Log Handlers
+//!
+//! Read access to the audit log that the content handlers append to on
+//! every create, update and delete (see [`repositories::audit`]).
+
+use crate::{
+    db::DbPool,
+    handlers::common::ensure_admin,
+    models::*,
+    repositories::{self, audit::AuditFilter},
+    security::auth,
+};
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use serde::Deserialize;
+
+const DEFAULT_LIST_LIMIT: i64 = 50;
+const MAX_LIST_LIMIT: i64 = 200;
+
+/// Query parameters for `GET /api/admin/audit-log`.
+#[derive(Debug, Deserialize)]
+pub struct AuditLogQuery {
+    /// Only entries about this kind of entity, e.g. `tutorial`.
+    #[serde(default)]
+    pub entity_type: Option<String>,
+    /// Only entries by this account.
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub limit: Option<i64>,
+    #[serde(default)]
+    pub offset: Option<i64>,
+}
+
+/// Handler for `GET /api/admin/audit-log`: newest entries first.
+/// Admin-only.
+pub async fn list_audit_log(
+    claims: auth::Claims,
+    State(pool): State<DbPool>,
+    Query(params): Query<AuditLogQuery>,
+) -> Result<Json<AuditLogListResponse>, AppError> {
+    ensure_admin(&claims)?;
+
+    let filter = AuditFilter {
+        entity_type: params
+            .entity_type
+            .as_deref()
+            .map(str::trim)
+            .filter(|value| !value.is_empty()),
+        username: params
+            .username
+            .as_deref()
+            .map(str::trim)
+            .filter(|value| !value.is_empty()),
+    };
+    let limit = params
+        .limit
+        .unwrap_or(DEFAULT_LIST_LIMIT)
+        .clamp(1, MAX_LIST_LIMIT);
+    let offset = params.offset.unwrap_or(0).max(0);
+
+    let records = repositories::audit::list_entries(&pool, &filter, limit, offset)
+        .await
+        .map_err(internal_error("Failed to load audit log"))?;
+    let total = repositories::audit::count_entries(&pool, &filter)
+        .await
+        .map_err(internal_error("Failed to load audit log"))?;
+
+    Ok(Json(AuditLogListResponse {
+        items: records.into_iter().map(AuditLogEntry::from).collect(),
+        total,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(entity_type: Option<&str>, username: Option<&str>, limit: i64) -> AuditLogQuery {
+        AuditLogQuery {
+            entity_type: entity_type.map(str::to_string),
+            username: username.map(str::to_string),
+            limit: Some(limit),
+            offset: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn lists_filtered_entries_newest_first() {
+        let pool = crate::test_support::migrated_pool().await;
+        let admin = crate::test_support::claims_for("root", "admin");
+
+        for (username, action, entity_type, entity_id) in [
+            ("root", "create", "tutorial", "t1"),
+            ("editor", "update", "tutorial", "t1"),
+            ("editor", "create", "post", "p1"),
+            ("root", "delete", "tutorial", "t1"),
+        ] {
+            repositories::audit::append_entry(
+                &pool,
+                username,
+                action,
+                entity_type,
+                Some(entity_id),
+                serde_json::json!({ "n": action }),
+            )
+            .await;
+        }
+
+        let Json(all) = list_audit_log(
+            admin.clone(),
+            State(pool.clone()),
+            Query(query(None, None, 2)),
+        )
+        .await
+        .unwrap();
+        assert_eq!(all.total, 4);
+        let actions: Vec<_> = all.items.iter().map(|e| e.action.as_str()).collect();
+        assert_eq!(actions, ["delete", "create"]);
+        assert_eq!(all.items[0].details["n"], "delete");
+
+        let Json(filtered) = list_audit_log(
+            admin.clone(),
+            State(pool.clone()),
+            Query(query(Some("tutorial"), Some("editor"), 50)),
+        )
+        .await
+        .unwrap();
+        assert_eq!(filtered.total, 1);
+        assert_eq!(filtered.items[0].action, "update");
+
+        let editor = auth::Claims {
+            role: "editor".to_string(),
+            ..admin
+        };
+        let status = list_audit_log(editor, State(pool), Query(query(None, None, 50)))
+            .await
+            .expect_err("admin only")
+            .status();
+        assert_eq!(status, axum::http::StatusCode::FORBIDDEN);
+    }
+}
@@ -15,24 +15,37 @@
 //! # Endpoints
 //! - POST /api/auth/login: Authenticate user and issue tokens
 //! - GET /api/auth/me: Get current user information
+//! - GET /api/auth/csrf: Issue a fresh CSRF token (cookie and body)
+//! - GET /api/auth/jwks: Public token verification keys (JWKS)
+//! - POST /api/auth/refresh: Exchange a valid token for a fresh one
 //! - POST /api/auth/logout: Invalidate session
+//! - POST /api/auth/logout-all: Revoke every session of the caller
+//! - GET /api/auth/sessions, DELETE /api/auth/sessions/{jti}: List and revoke sessions
+//! - POST /api/auth/change-password: Rotate the caller's password
+//! - POST /api/auth/sudo: Confirm the password before destructive actions
+//! - POST /api/auth/login/2fa: Finish a login with a TOTP or recovery code
+//! - POST /api/auth/2fa/setup, /verify, /disable: Manage two-factor login
 //!
 //! # Rate Limiting
 //! Failed login attempts trigger progressive lockout on two keys:
 //! - Per (IP + username): 3 failures → 10s lockout, 5+ failures → 60s lockout
 //! - Per IP across all usernames (anti password-spraying):
-//!   10 failures → 60s lockout, 20+ failures → 300s lockout
+//!   10 failures → 60s lockout, 20+ failures → 300s lockout; the count
+//!   restarts once an address has been quiet for 15 minutes
+//!
+//! Either key being over its limit blocks the attempt.
 
 use crate::{
     db::DbPool,
     middleware::security as security_middleware,
     models::*,
     repositories,
-    security::{auth, csrf},
+    security::{auth, csrf, rejections},
 };
 use axum::{
     extract::{ConnectInfo, State},
-    http::{HeaderMap, StatusCode},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
     Json,
 };
 use chrono::{DateTime, Duration as ChronoDuration, Utc};
@@ -40,11 +53,19 @@ use chrono::{DateTime, Duration as ChronoDuration, Utc};
 use axum_extra::extract::cookie::CookieJar;
 use rand::RngExt;
 use std::net::SocketAddr;
-use std::{env, sync::OnceLock, time::Duration};
+use std::{sync::OnceLock, time::Duration};
 
+mod sessions;
+mod sudo;
 mod support;
-pub use support::init_login_attempt_salt;
+mod two_factor;
+pub use sessions::{list_sessions, logout_all, revoke_session};
+pub use sudo::enter_sudo_mode;
 use support::*;
+pub(crate) use support::{hash_comment_ip, hash_search_ip, validate_username};
+pub use support::{init_login_attempt_salt, validate_login_attempt_salt, validate_password};
+use two_factor::issue_challenge;
+pub use two_factor::{disable_two_factor, login_two_factor, setup_two_factor, verify_two_factor};
 
 /// HTTP handler for user login.
 ///
@@ -69,6 +90,10 @@ use support::*;
 /// - Sets CSRF cookie (ltcms_csrf)
 /// - Returns LoginResponse with user info
 ///
+/// If the account has two-factor login enabled, the password step answers
+/// 202 Accepted with a [`TwoFactorChallengeResponse`] and no cookies; the
+/// session is issued by [`login_two_factor`].
+///
 /// # Errors
 /// - 400 Bad Request: Invalid username/password format
 /// - 401 Unauthorized: Invalid credentials
@@ -86,40 +111,20 @@ use support::*;
 /// # Rate Limiting
 /// After failed attempts:
 /// - Per (IP + username): 3 failures → 10s lockout, 5+ → 60s lockout
-/// - Per IP across all usernames: 10 failures → 60s, 20+ → 300s lockout
+/// - Per IP across all usernames: 10 failures → 60s, 20+ → 300s lockout,
+///   counted over failures no more than 15 minutes apart
 /// - Lockout countdown shown to user
 pub async fn login(
     State(pool): State<DbPool>,
     headers: HeaderMap,
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
     Json(payload): Json<LoginRequest>,
-) -> Result<(HeaderMap, Json<LoginResponse>), ApiError> {
+) -> Result<Response, AppError> {
     let username = payload.username.trim().to_string();
 
     validate_username(&username).map_err(bad_request)?;
     validate_login_password(&payload.password).map_err(bad_request)?;
 
-    // Probabilistic cleanup (1% of requests) of tables that otherwise grow
-    // unbounded. Runs before the auth outcome is known on purpose: attack
-    // traffic is almost entirely *failed* logins, so tying cleanup to
-    // successful logins would never fire under the load that fills these
-    // tables. Spawned so it never adds latency to the login itself.
-    let should_cleanup = {
-        let mut rng = rand::rng();
-        rng.random_bool(0.01)
-    };
-    if should_cleanup {
-        let pool_clone = pool.clone();
-        tokio::spawn(async move {
-            if let Err(e) = repositories::token_blacklist::cleanup_expired(&pool_clone).await {
-                tracing::error!("Failed to cleanup expired blacklist tokens: {}", e);
-            }
-            if let Err(e) = repositories::users::cleanup_stale_login_attempts(&pool_clone).await {
-                tracing::error!("Failed to cleanup stale login attempts: {}", e);
-            }
-        });
-    }
-
     // Rate limit on two independent keys:
     //
     // 1. (IP + Username): tight thresholds. Using the pair (not username
@@ -131,36 +136,8 @@ pub async fn login(
     //    fresh pair key on every attempt and would otherwise never hit any
     //    limit (password spraying).
     let client_ip = security_middleware::extract_client_ip(&headers, addr.ip());
-    let attempt_key = hash_login_identifier(&format!("{}:{}", client_ip, username));
-    let ip_attempt_key = hash_login_identifier(&format!("ip-wide:{}", client_ip));
-
-    let attempt_record = repositories::users::get_login_attempt(&pool, &attempt_key)
-        .await
-        .map_err(internal_error("Failed to load login attempts"))?;
-    let ip_attempt_record = repositories::users::get_login_attempt(&pool, &ip_attempt_key)
-        .await
-        .map_err(internal_error("Failed to load login attempts"))?;
-
-    let blocked_until = [&attempt_record, &ip_attempt_record]
-        .into_iter()
-        .flatten()
-        .filter_map(|record| parse_rfc3339_opt(&record.blocked_until))
-        .max();
-    if let Some(blocked_until) = blocked_until {
-        let now = Utc::now();
-        if blocked_until > now {
-            let remaining = (blocked_until - now).num_seconds().max(0);
-            // Do not sleep here to avoid holding connections (DoS prevention)
-            return Err(api_error(
-                StatusCode::TOO_MANY_REQUESTS,
-                format!(
-                    "Too many failed attempts. Please wait {} second{}.",
-                    remaining,
-                    if remaining == 1 { "" } else { "s" }
-                ),
-            ));
-        }
-    }
+    let keys = AttemptKeys::new(client_ip, &username);
+    let has_attempt_record = enforce_lockout(&pool, &keys).await?;
 
     let user = repositories::users::get_user_by_username(&pool, &username)
         .await
@@ -190,44 +167,35 @@ pub async fn login(
     tokio::time::sleep(Duration::from_millis(jitter)).await;
 
     if !password_valid {
-        let now = Utc::now();
-        let long_block = (now + ChronoDuration::seconds(60)).to_rfc3339();
-        let short_block = (now + ChronoDuration::seconds(10)).to_rfc3339();
-
-        repositories::users::record_failed_login(
-            &pool,
-            &attempt_key,
-            &long_block,
-            &short_block,
-            PAIR_LONG_THRESHOLD,
-            PAIR_SHORT_THRESHOLD,
-        )
-        .await
-        .map_err(internal_error("Failed to record login attempt"))?;
-
-        let ip_long_block =
-            (now + ChronoDuration::seconds(IP_WIDE_LONG_BLOCK_SECONDS)).to_rfc3339();
-        let ip_short_block =
-            (now + ChronoDuration::seconds(IP_WIDE_SHORT_BLOCK_SECONDS)).to_rfc3339();
-        repositories::users::record_failed_login(
-            &pool,
-            &ip_attempt_key,
-            &ip_long_block,
-            &ip_short_block,
-            IP_WIDE_LONG_THRESHOLD,
-            IP_WIDE_SHORT_THRESHOLD,
-        )
-        .await
-        .map_err(internal_error("Failed to record login attempt"))?;
+        record_password_failure(&pool, &keys).await?;
+        return Err(unauthorized("Invalid credentials"));
+    }
 
-        return Err(api_error(StatusCode::UNAUTHORIZED, "Invalid credentials"));
+    let user_record = user_record.expect("Successful login must have user record");
+    upgrade_password_hash(
+        &pool,
+        &user_record.username,
+        &payload.password,
+        &user_record.password_hash,
+    )
+    .await;
+
+    // With two-factor login the password alone proves nothing yet, so the
+    // attempt counters stay in place until the code step succeeds.
+    let two_factor_enabled = repositories::two_factor::get_state_by_id(&pool, user_record.id)
+        .await
+        .map_err(internal_error("Failed to load user"))?
+        .is_some_and(|state| state.totp_enabled);
+    if two_factor_enabled {
+        let challenge = issue_challenge(&pool, user_record.id, payload.remember_me).await?;
+        return Ok((StatusCode::ACCEPTED, Json(challenge)).into_response());
     }
 
     // Only the pair key is cleared on success. The IP-wide counter must
     // survive: an attacker who knows one valid credential could otherwise
     // reset the spray counter at will by logging into that account.
-    if attempt_record.is_some() {
-        if let Err(e) = repositories::users::clear_login_attempts(&pool, &attempt_key).await {
+    if has_attempt_record {
+        if let Err(e) = repositories::users::clear_login_attempts(&pool, &keys.pair).await {
             tracing::warn!(
                 "Failed to clear login attempts for hashed key after successful login: {}",
                 e
@@ -235,32 +203,17 @@ pub async fn login(
         }
     }
 
-    let user_record = user_record.expect("Successful login must have user record");
-    let token = auth::create_jwt(user_record.username.clone(), user_record.role.clone())
-        .map_err(internal_error("Failed to create token"))?;
-
-    let mut headers = HeaderMap::new();
-    auth::append_auth_cookie(&mut headers, auth::build_auth_cookie(&token));
-
-    if let Ok(csrf_token) = csrf::issue_csrf_token(&user_record.username) {
-        csrf::append_csrf_cookie(&mut headers, &csrf_token);
-    } else {
-        tracing::error!(
-            "Failed to issue CSRF token for user {}",
-            user_record.username
-        );
-        return Err(internal_error_plain("Failed to create token"));
-    }
-
-    Ok((
-        headers,
-        Json(LoginResponse {
-            user: UserResponse {
-                username: user_record.username,
-                role: user_record.role,
-            },
-        }),
-    ))
+    let client = SessionClient::new(&headers, client_ip);
+    Ok(start_session(
+        &pool,
+        &client,
+        user_record.id,
+        user_record.username,
+        user_record.role,
+        payload.remember_me,
+    )
+    .await?
+    .into_response())
 }
 
 /// HTTP handler for retrieving current user information.
@@ -291,13 +244,12 @@ pub async fn login(
 /// # Security
 /// User identity is extracted from the validated JWT token,
 /// not from request parameters, preventing impersonation.
-pub async fn me(claims: auth::Claims) -> Result<(HeaderMap, Json<UserResponse>), ApiError> {
+pub async fn me(claims: auth::Claims) -> Result<(HeaderMap, Json<UserResponse>), AppError> {
     let mut headers = HeaderMap::new();
 
-    // Refresh CSRF token to ensure active sessions always have a valid one
-    if let Ok(csrf_token) = csrf::issue_csrf_token(&claims.sub) {
-        csrf::append_csrf_cookie(&mut headers, &csrf_token);
-    } else {
+    // Refresh CSRF token to ensure active sessions always have a valid one,
+    // lasting as long as the session itself
+    if append_fresh_csrf_cookie(&mut headers, &claims).is_err() {
         tracing::error!("Failed to refresh CSRF token for user {}", claims.sub);
         // We don't fail the request here, as the user is authenticated,
         // but subsequent state-changing requests might fail.
@@ -312,6 +264,184 @@ pub async fn me(claims: auth::Claims) -> Result<(HeaderMap, Json<UserResponse>),
     ))
 }
 
+/// HTTP handler issuing a fresh CSRF token.
+///
+/// # Endpoint
+/// GET /api/auth/csrf
+///
+/// # Response
+/// 200 OK with a new CSRF cookie and a [`CsrfTokenResponse`] carrying the
+/// same token, for clients that cannot read the cookie (e.g. a frontend on
+/// another subdomain). The token lasts as long as the session.
+///
+/// # Errors
+/// - 401 Unauthorized: Missing or invalid token
+pub async fn csrf_token(
+    claims: auth::Claims,
+) -> Result<(HeaderMap, Json<CsrfTokenResponse>), AppError> {
+    let mut headers = HeaderMap::new();
+    let (csrf_token, ttl) = append_fresh_csrf_cookie(&mut headers, &claims).map_err(|err| {
+        tracing::error!(
+            "Failed to issue CSRF token for user {}: {}",
+            claims.sub,
+            err
+        );
+        internal_error_plain("Failed to issue CSRF token")
+    })?;
+    headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+
+    Ok((
+        headers,
+        Json(CsrfTokenResponse {
+            csrf_token,
+            expires_in: ttl.num_seconds(),
+        }),
+    ))
+}
+
+/// Issues a CSRF token that expires with the caller's session token (or
+/// sooner, see [`csrf::token_ttl`]) and appends it as a cookie. Returns the
+/// token and its lifetime.
+fn append_fresh_csrf_cookie(
+    headers: &mut HeaderMap,
+    claims: &auth::Claims,
+) -> Result<(String, ChronoDuration), String> {
+    let ttl = csrf::token_ttl(claims.remaining_lifetime(Utc::now()));
+    let csrf_token = csrf::issue_csrf_token(&claims.sub, ttl)?;
+    csrf::append_csrf_cookie(headers, &csrf_token, ttl);
+    Ok((csrf_token, ttl))
+}
+
+/// Serves the public key tokens are signed with, as a JSON Web Key Set, so
+/// other services can verify them. The set is empty while tokens are
+/// signed with the shared `JWT_SECRET`, which must never be published.
+pub async fn jwks() -> impl IntoResponse {
+    (
+        [(header::CACHE_CONTROL, "public, max-age=3600")],
+        Json(auth::jwks()),
+    )
+}
+
+/// HTTP handler for refreshing a session token.
+///
+/// Exchanges the current, still valid JWT (cookie or Authorization header)
+/// for a new one and rotates the CSRF cookie, so an active SPA session does
+/// not end when its token expires. The old token is blacklisted, which also makes
+/// it single-use: a second refresh with the same token is rejected.
+///
+/// # Endpoint
+/// POST /api/auth/refresh
+///
+/// # Response
+/// On success (200 OK): new auth and CSRF cookies plus a LoginResponse.
+///
+/// # Errors
+/// - 401 Unauthorized: Missing, invalid, expired or revoked token, unknown
+///   user, or the session reached `MAX_SESSION_HOURS`
+/// - 403 Forbidden: Missing or invalid CSRF token
+///
+/// # Security
+/// - Expiry is checked without the clock-skew leeway `verify_jwt` grants
+/// - The role is re-read from the database, so demotions take effect
+/// - The new expiry never exceeds login time + `MAX_SESSION_HOURS`
+pub async fn refresh(
+    State(pool): State<DbPool>,
+    headers: HeaderMap,
+    _csrf: csrf::CsrfGuard,
+) -> Result<(HeaderMap, Json<LoginResponse>), AppError> {
+    let unauthorized = |message: &str| unauthorized(message);
+
+    let token = auth::extract_token(&headers)
+        .ok_or_else(|| unauthorized("Missing authentication token"))?;
+    let claims = auth::verify_jwt(&token).map_err(|_| unauthorized("Invalid token"))?;
+
+    let now = Utc::now();
+    if claims.exp as i64 <= now.timestamp() {
+        return Err(unauthorized("Token has expired"));
+    }
+
+    let revoked = auth::is_token_revoked(&pool, &token, &claims)
+        .await
+        .map_err(internal_error("Failed to check token blacklist"))?;
+    if revoked {
+        return Err(unauthorized("Token has been revoked"));
+    }
+
+    let user = repositories::users::get_user_by_username(&pool, &claims.sub)
+        .await
+        .map_err(internal_error("Failed to load user"))?
+        .ok_or_else(|| unauthorized("Invalid token"))?;
+
+    // A remembered login may outlive MAX_SESSION_HOURS by design, so its cap
+    // is at least the remember-me lifetime.
+    let ttl = auth::session_ttl(claims.remember);
+    let max_session = ChronoDuration::hours(i64::from(crate::config::get().max_session_hours));
+    let max_session = if claims.remember {
+        max_session.max(ttl)
+    } else {
+        max_session
+    };
+    let mut refreshed = claims.refreshed(now, ttl, max_session).ok_or_else(|| {
+        unauthorized("Session has reached its maximum lifetime. Please log in again.")
+    })?;
+    refreshed.role = user.role.clone();
+
+    // Revoke first: the primary key on the blacklist turns a concurrent
+    // second refresh of the same token into a conflict instead of a second
+    // valid session.
+    if let Err(err) =
+        repositories::token_blacklist::blacklist_token(&pool, &token, claims.exp as i64).await
+    {
+        if err
+            .as_database_error()
+            .is_some_and(|db_err| db_err.is_unique_violation())
+        {
+            return Err(unauthorized("Token has been revoked"));
+        }
+        tracing::error!("Failed to blacklist token on refresh: {}", err);
+        return Err(internal_error_plain("Failed to refresh session"));
+    }
+
+    let new_token =
+        auth::encode_claims(&refreshed).map_err(internal_error("Failed to create token"))?;
+    if !refreshed.jti.is_empty() {
+        if let Err(err) =
+            repositories::sessions::extend_session(&pool, &refreshed.jti, refreshed.exp as i64)
+                .await
+        {
+            tracing::warn!("Failed to extend session on refresh: {}", err);
+        }
+    }
+
+    let mut response_headers = HeaderMap::new();
+    let ttl = refreshed.remaining_lifetime(now);
+    auth::append_auth_cookie(
+        &mut response_headers,
+        auth::build_auth_cookie(&new_token, ttl),
+    );
+    let csrf_token = csrf::issue_csrf_token(&user.username, ttl).map_err(|err| {
+        tracing::error!(
+            "Failed to issue CSRF token for user {}: {}",
+            user.username,
+            err
+        );
+        internal_error_plain("Failed to create token")
+    })?;
+    csrf::append_csrf_cookie(&mut response_headers, &csrf_token, ttl);
+
+    tracing::info!(user = %user.username, "Session refreshed");
+
+    Ok((
+        response_headers,
+        Json(LoginResponse {
+            user: UserResponse {
+                username: user.username,
+                role: user.role,
+            },
+        }),
+    ))
+}
+
 /// HTTP handler for user logout.
 ///
 /// Invalidates the user's session by removing auth and CSRF cookies.
@@ -346,36 +476,20 @@ pub async fn logout(
     _csrf: csrf::CsrfGuard,
     claims: auth::Claims,
 ) -> (StatusCode, HeaderMap) {
-    let mut tokens_to_blacklist = Vec::new();
-
-    // 1. Extract from Header
-    if let Some(auth_header) = headers.get(axum::http::header::AUTHORIZATION) {
-        if let Ok(value) = auth_header.to_str() {
-            if let Some(token) = value.strip_prefix("Bearer ").map(|t| t.trim()) {
-                if !token.is_empty() {
-                    tokens_to_blacklist.push(token.to_string());
-                }
-            }
-        }
-    }
-
-    // 2. Extract from Cookie
-    if let Some(cookie) = jar.get(auth::AUTH_COOKIE_NAME) {
-        let token = cookie.value();
-        // Avoid duplicates
-        if !tokens_to_blacklist.contains(&token.to_string()) {
-            tokens_to_blacklist.push(token.to_string());
-        }
-    }
-
-    // 3. Blacklist all found tokens
-    for token in tokens_to_blacklist {
+    for token in presented_tokens(&headers, &jar) {
         if let Err(e) =
             repositories::token_blacklist::blacklist_token(&pool, &token, claims.exp as i64).await
         {
             tracing::error!("Failed to blacklist token on logout: {}", e);
         }
     }
+    if !claims.jti.is_empty() {
+        if let Err(e) =
+            repositories::sessions::revoke_session(&pool, &claims.sub, &claims.jti).await
+        {
+            tracing::error!("Failed to revoke session on logout: {}", e);
+        }
+    }
 
     let mut headers = HeaderMap::new();
     auth::append_auth_cookie(&mut headers, auth::build_cookie_removal());
@@ -384,5 +498,104 @@ pub async fn logout(
     (StatusCode::NO_CONTENT, headers)
 }
 
+/// HTTP handler for changing the logged-in user's own password.
+///
+/// # Endpoint
+/// POST /api/auth/change-password
+///
+/// # Request
+/// JSON body with ChangePasswordRequest:
+/// ```json
+/// {
+///   "current_password": "old secret",
+///   "new_password": "a new, longer secret"
+/// }
+/// ```
+///
+/// # Response
+/// On success (204 No Content) the presented token is blacklisted and the
+/// auth and CSRF cookies are cleared, so the user has to log in again.
+///
+/// # Errors
+/// - 400 Bad Request: New password fails the password rules or equals the
+///   current one
+/// - 401 Unauthorized: Missing or invalid JWT, or wrong current password
+/// - 403 Forbidden: Missing or invalid CSRF token
+/// - 429 Too Many Requests: Locked out by failed attempts
+///
+/// # Security
+/// A wrong current password counts toward the same lockout as failed
+/// logins (keyed on client IP and the token's username), so a stolen
+/// session cannot be used to brute-force the password. Other tokens the
+/// user holds are not revoked and stay valid until they expire.
+pub async fn change_password(
+    State(pool): State<DbPool>,
+    headers: HeaderMap,
+    jar: CookieJar,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    _csrf: csrf::CsrfGuard,
+    claims: auth::Claims,
+    Json(payload): Json<ChangePasswordRequest>,
+) -> Result<(StatusCode, HeaderMap), AppError> {
+    validate_login_password(&payload.current_password).map_err(bad_request)?;
+    validate_password(&payload.new_password).map_err(bad_request)?;
+    if payload.new_password == payload.current_password {
+        return Err(bad_request(
+            "New password must differ from the current password",
+        ));
+    }
+
+    let client_ip = security_middleware::extract_client_ip(&headers, addr.ip());
+    let keys = AttemptKeys::new(client_ip, &claims.sub);
+    let has_attempt_record = enforce_lockout(&pool, &keys).await?;
+
+    let user = repositories::users::get_user_by_username(&pool, &claims.sub)
+        .await
+        .map_err(internal_error("Failed to load user"))?
+        .ok_or_else(|| unauthorized("Invalid token"))?;
+
+    let password_valid = bcrypt::verify(&payload.current_password, &user.password_hash)
+        .unwrap_or_else(|e| {
+            tracing::error!("Password verification error: {}", e);
+            false
+        });
+    if !password_valid {
+        record_password_failure(&pool, &keys).await?;
+        return Err(unauthorized("Current password is incorrect"));
+    }
+
+    let password_hash = bcrypt::hash(&payload.new_password, crate::config::get().bcrypt_cost)
+        .map_err(internal_error("Failed to hash password"))?;
+    let updated = repositories::users::update_password_hash(&pool, &user.username, &password_hash)
+        .await
+        .map_err(internal_error("Failed to update password"))?;
+    if !updated {
+        return Err(unauthorized("Invalid token"));
+    }
+
+    if has_attempt_record {
+        if let Err(e) = repositories::users::clear_login_attempts(&pool, &keys.pair).await {
+            tracing::warn!(
+                "Failed to clear login attempts after password change: {}",
+                e
+            );
+        }
+    }
+
+    for token in presented_tokens(&headers, &jar) {
+        if let Err(e) =
+            repositories::token_blacklist::blacklist_token(&pool, &token, claims.exp as i64).await
+        {
+            tracing::error!("Failed to blacklist token on password change: {}", e);
+        }
+    }
+
+    let mut response_headers = HeaderMap::new();
+    auth::append_auth_cookie(&mut response_headers, auth::build_cookie_removal());
+    csrf::append_csrf_removal(&mut response_headers);
+    tracing::info!(user = %user.username, "Password changed");
+    Ok((StatusCode::NO_CONTENT, response_headers))
+}
+
 #[cfg(test)]
 mod tests;
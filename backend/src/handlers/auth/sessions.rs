@@ -0,0 +1,101 @@
+//! Listing and revoking the caller's login sessions.
+//!
+//! Every login records a session keyed by the token's `jti`; refreshes keep
+//! the `jti`, so one login stays one session. Revoking a session rejects
+//! all of its tokens, wherever they are held.
+
+use super::*;
+use axum::extract::Path;
+
+/// HTTP handler listing the caller's active sessions, newest first.
+///
+/// # Endpoint
+/// GET /api/auth/sessions
+///
+/// # Response
+/// 200 OK with a [`SessionListResponse`]; the session the request was made
+/// with has `current: true`.
+///
+/// # Errors
+/// - 401 Unauthorized: Missing or invalid JWT
+pub async fn list_sessions(
+    State(pool): State<DbPool>,
+    claims: auth::Claims,
+) -> Result<Json<SessionListResponse>, AppError> {
+    let mut items = repositories::sessions::list_active_sessions(&pool, &claims.sub)
+        .await
+        .map_err(internal_error("Failed to load sessions"))?;
+    for session in &mut items {
+        session.current = !claims.jti.is_empty() && session.jti == claims.jti;
+    }
+    Ok(Json(SessionListResponse { items }))
+}
+
+/// HTTP handler revoking one of the caller's sessions.
+///
+/// # Endpoint
+/// DELETE /api/auth/sessions/{jti}
+///
+/// # Response
+/// 204 No Content. Revoking the current session works like a logout that
+/// leaves the cookies in place; they are rejected from then on.
+///
+/// # Errors
+/// - 401 Unauthorized: Missing or invalid JWT
+/// - 403 Forbidden: Missing or invalid CSRF token
+/// - 404 Not Found: No active session with that ID belongs to the caller
+pub async fn revoke_session(
+    State(pool): State<DbPool>,
+    _csrf: csrf::CsrfGuard,
+    claims: auth::Claims,
+    Path(jti): Path<String>,
+) -> Result<StatusCode, AppError> {
+    let revoked = repositories::sessions::revoke_session(&pool, &claims.sub, &jti)
+        .await
+        .map_err(internal_error("Failed to revoke session"))?;
+    if !revoked {
+        return Err(not_found("Session not found"));
+    }
+
+    tracing::info!(user = %claims.sub, "Session revoked");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// HTTP handler ending every session of the caller, including the current
+/// one.
+///
+/// # Endpoint
+/// POST /api/auth/logout-all
+///
+/// # Response
+/// 204 No Content with the auth and CSRF cookies cleared.
+///
+/// # Errors
+/// - 401 Unauthorized: Missing or invalid JWT
+/// - 403 Forbidden: Missing or invalid CSRF token
+pub async fn logout_all(
+    State(pool): State<DbPool>,
+    headers: HeaderMap,
+    jar: CookieJar,
+    _csrf: csrf::CsrfGuard,
+    claims: auth::Claims,
+) -> Result<(StatusCode, HeaderMap), AppError> {
+    let revoked = repositories::sessions::revoke_all_sessions(&pool, &claims.sub)
+        .await
+        .map_err(internal_error("Failed to revoke sessions"))?;
+
+    // The presented tokens go on the blacklist too, as on a plain logout.
+    for token in presented_tokens(&headers, &jar) {
+        if let Err(e) =
+            repositories::token_blacklist::blacklist_token(&pool, &token, claims.exp as i64).await
+        {
+            tracing::error!("Failed to blacklist token on logout: {}", e);
+        }
+    }
+
+    let mut headers = HeaderMap::new();
+    auth::append_auth_cookie(&mut headers, auth::build_cookie_removal());
+    csrf::append_csrf_removal(&mut headers);
+    tracing::info!(user = %claims.sub, sessions = revoked, "User logged out everywhere");
+    Ok((StatusCode::NO_CONTENT, headers))
+}
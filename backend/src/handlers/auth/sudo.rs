@@ -0,0 +1,88 @@
+//! Entering sudo mode.
+//!
+//! Deleting content and managing accounts require
+//! [`RequireSudo`](crate::security::auth::RequireSudo): the session must
+//! have confirmed its password within the last few minutes. This endpoint
+//! does the confirming.
+
+use super::*;
+
+/// HTTP handler re-checking the caller's password to enter sudo mode.
+///
+/// # Endpoint
+/// POST /api/auth/sudo
+///
+/// # Response
+/// 200 OK with a [`SudoResponse`] and a new auth cookie whose token is in
+/// sudo mode for [`auth::SUDO_MODE_TTL_SECONDS`]. The token keeps the
+/// session and expiry of the one it replaces.
+///
+/// # Errors
+/// - 400 Bad Request: Missing password
+/// - 401 Unauthorized: Missing or invalid JWT, or wrong password
+/// - 403 Forbidden: Missing or invalid CSRF token, or an API key request
+/// - 429 Too Many Requests: Too many failed attempts
+///
+/// # Security
+/// Wrong passwords count toward the login lockout, as in
+/// [`change_password`], so a stolen session cannot guess its way in.
+pub async fn enter_sudo_mode(
+    State(pool): State<DbPool>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    _csrf: csrf::CsrfGuard,
+    claims: auth::Claims,
+    Json(payload): Json<SudoRequest>,
+) -> Result<(HeaderMap, Json<SudoResponse>), AppError> {
+    if auth::extract_api_key(&headers).is_some() {
+        return Err(forbidden("Sudo mode requires a login session"));
+    }
+    validate_login_password(&payload.password).map_err(bad_request)?;
+
+    let client_ip = security_middleware::extract_client_ip(&headers, addr.ip());
+    let keys = AttemptKeys::new(client_ip, &claims.sub);
+    let has_attempt_record = enforce_lockout(&pool, &keys).await?;
+
+    let user = repositories::users::get_user_by_username(&pool, &claims.sub)
+        .await
+        .map_err(internal_error("Failed to load user"))?
+        .ok_or_else(|| unauthorized("Invalid token"))?;
+
+    let password_valid =
+        bcrypt::verify(&payload.password, &user.password_hash).unwrap_or_else(|e| {
+            tracing::error!("Password verification error: {}", e);
+            false
+        });
+    if !password_valid {
+        record_password_failure(&pool, &keys).await?;
+        return Err(unauthorized("Incorrect password"));
+    }
+
+    if has_attempt_record {
+        if let Err(e) = repositories::users::clear_login_attempts(&pool, &keys.pair).await {
+            tracing::warn!("Failed to clear login attempts after sudo: {}", e);
+        }
+    }
+
+    let now = Utc::now();
+    let elevated = claims.with_sudo(now);
+    let token = auth::encode_claims(&elevated).map_err(internal_error("Failed to create token"))?;
+
+    let mut response_headers = HeaderMap::new();
+    auth::append_auth_cookie(
+        &mut response_headers,
+        auth::build_auth_cookie(&token, elevated.remaining_lifetime(now)),
+    );
+
+    let sudo_until = elevated
+        .sudo_until
+        .and_then(|until| DateTime::<Utc>::from_timestamp(until as i64, 0))
+        .ok_or_else(|| internal_error_plain("Failed to create token"))?;
+    tracing::info!(user = %claims.sub, "Entered sudo mode");
+    Ok((
+        response_headers,
+        Json(SudoResponse {
+            sudo_until: sudo_until.to_rfc3339(),
+        }),
+    ))
+}
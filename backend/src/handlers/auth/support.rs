@@ -1,42 +1,44 @@
 use super::*;
+use crate::repositories::users::LockoutPolicy;
+use std::net::IpAddr;
 
 /// Global salt for hashing login attempt identifiers.
 /// Initialized once at startup via init_login_attempt_salt().
 pub(super) static LOGIN_ATTEMPT_SALT: OnceLock<String> = OnceLock::new();
 
-/// Per-(IP+username) lockout: short block (10s) after this many failures.
-pub(super) const PAIR_SHORT_THRESHOLD: i64 = 3;
-/// Per-(IP+username) lockout: long block (60s) after this many failures.
-pub(super) const PAIR_LONG_THRESHOLD: i64 = 5;
+/// Per-(IP+username) lockout: 3 failures → 10s block, 5+ failures → 60s.
+/// Counts until the next successful login.
+pub(super) const PAIR_LOCKOUT: LockoutPolicy = LockoutPolicy {
+    key_type: "pair",
+    short_threshold: 3,
+    long_threshold: 5,
+    short_block_seconds: 10,
+    long_block_seconds: 60,
+    window_seconds: None,
+};
 
-/// IP-wide lockout thresholds. Looser than the pair key so shared addresses
-/// (NAT, office networks) are not punished for one user's typos, but tight
-/// enough that spraying many usernames from a single address stalls quickly.
-pub(super) const IP_WIDE_SHORT_THRESHOLD: i64 = 10;
-pub(super) const IP_WIDE_LONG_THRESHOLD: i64 = 20;
-/// IP-wide lockout: short block duration in seconds.
-pub(super) const IP_WIDE_SHORT_BLOCK_SECONDS: i64 = 60;
-/// IP-wide lockout: long block duration in seconds.
-pub(super) const IP_WIDE_LONG_BLOCK_SECONDS: i64 = 300;
+/// IP-wide lockout: 10 failures → 60s block, 20+ failures → 300s. Looser
+/// than the pair key so shared addresses (NAT, office networks) are not
+/// punished for one user's typos, but tight enough that spraying many
+/// usernames from a single address stalls quickly. A successful login does
+/// not reset it (that would let a sprayer interleave their own account);
+/// instead the count restarts after 15 quiet minutes.
+pub(super) const IP_WIDE_LOCKOUT: LockoutPolicy = LockoutPolicy {
+    key_type: "ip",
+    short_threshold: 10,
+    long_threshold: 20,
+    short_block_seconds: 60,
+    long_block_seconds: 300,
+    window_seconds: Some(15 * 60),
+};
 
-/// Initializes the login attempt salt from environment.
-///
-/// This salt is used to hash usernames before storing them in the
-/// login_attempts table, preventing username enumeration attacks.
-///
-/// # Returns
-/// - `Ok(())` if initialization succeeds
-/// - `Err(String)` with error message if validation fails
+/// Validates a candidate login attempt salt without installing it.
 ///
 /// # Errors
-/// - LOGIN_ATTEMPT_SALT environment variable not set
 /// - Salt is too short (< 32 characters)
 /// - Salt has insufficient entropy (< 10 unique characters)
-/// - Salt was already initialized
-pub fn init_login_attempt_salt() -> Result<(), String> {
-    let raw = env::var("LOGIN_ATTEMPT_SALT")
-        .map_err(|_| "LOGIN_ATTEMPT_SALT environment variable not set".to_string())?;
-    let trimmed = raw.trim();
+pub fn validate_login_attempt_salt(salt: &str) -> Result<(), String> {
+    let trimmed = salt.trim();
 
     if trimmed.len() < 32 {
         return Err("LOGIN_ATTEMPT_SALT must be at least 32 characters long".to_string());
@@ -50,8 +52,26 @@ pub fn init_login_attempt_salt() -> Result<(), String> {
         return Err("LOGIN_ATTEMPT_SALT must contain at least 10 unique characters".to_string());
     }
 
+    Ok(())
+}
+
+/// Initializes the login attempt salt.
+///
+/// This salt is used to hash usernames before storing them in the
+/// login_attempts table, preventing username enumeration attacks.
+///
+/// # Returns
+/// - `Ok(())` if initialization succeeds
+/// - `Err(String)` with error message if validation fails
+///
+/// # Errors
+/// - Salt fails [`validate_login_attempt_salt`]
+/// - Salt was already initialized
+pub fn init_login_attempt_salt(salt: &str) -> Result<(), String> {
+    validate_login_attempt_salt(salt)?;
+
     LOGIN_ATTEMPT_SALT
-        .set(trimmed.to_string())
+        .set(salt.trim().to_string())
         .map_err(|_| "LOGIN_ATTEMPT_SALT already initialized".to_string())?;
 
     Ok(())
@@ -89,6 +109,185 @@ pub(super) fn hash_login_identifier(username: &str) -> String {
     crate::security::sha256_hex(&data)
 }
 
+/// Hashes a commenter's IP address for `comment_blocklist`, with the same
+/// salt as the login attempt keys so the stored value reveals nothing.
+pub(crate) fn hash_comment_ip(ip: &str) -> String {
+    hash_login_identifier(&format!("comment:{ip}"))
+}
+
+/// Hashes a searcher's IP address for `search_log`, so distinct visitors can
+/// be counted without keeping their addresses.
+pub(crate) fn hash_search_ip(ip: &str) -> String {
+    hash_login_identifier(&format!("search:{ip}"))
+}
+
+/// The two `login_attempts` keys a password check is rate limited on: the
+/// (IP + username) pair and the client IP alone.
+pub(super) struct AttemptKeys {
+    pub pair: String,
+    pub ip: String,
+}
+
+impl AttemptKeys {
+    pub fn new(client_ip: IpAddr, username: &str) -> Self {
+        AttemptKeys {
+            pair: hash_login_identifier(&format!("{}:{}", client_ip, username)),
+            ip: hash_login_identifier(&format!("ip-wide:{}", client_ip)),
+        }
+    }
+}
+
+/// Rejects the attempt with 429 while either key is blocked.
+///
+/// Returns whether the pair key has a record, so a successful attempt knows
+/// whether there is anything to clear.
+pub(super) async fn enforce_lockout(pool: &DbPool, keys: &AttemptKeys) -> Result<bool, AppError> {
+    let attempt_record = repositories::users::get_login_attempt(pool, &keys.pair)
+        .await
+        .map_err(internal_error("Failed to load login attempts"))?;
+    let ip_attempt_record = repositories::users::get_login_attempt(pool, &keys.ip)
+        .await
+        .map_err(internal_error("Failed to load login attempts"))?;
+
+    let blocked_until = [&attempt_record, &ip_attempt_record]
+        .into_iter()
+        .flatten()
+        .filter_map(|record| parse_rfc3339_opt(&record.blocked_until))
+        .max();
+    if let Some(blocked_until) = blocked_until {
+        let now = Utc::now();
+        if blocked_until > now {
+            let remaining = (blocked_until - now).num_seconds().max(0);
+            rejections::record(pool, RejectionSource::Login, RejectionReason::LockedOut);
+            // Do not sleep here to avoid holding connections (DoS prevention)
+            return Err(api_error(
+                StatusCode::TOO_MANY_REQUESTS,
+                format!(
+                    "Too many failed attempts. Please wait {} second{}.",
+                    remaining,
+                    if remaining == 1 { "" } else { "s" }
+                ),
+            ));
+        }
+    }
+
+    Ok(attempt_record.is_some())
+}
+
+/// Counts a wrong password against both keys.
+pub(super) async fn record_password_failure(
+    pool: &DbPool,
+    keys: &AttemptKeys,
+) -> Result<(), AppError> {
+    repositories::users::record_failed_login(pool, &keys.pair, &PAIR_LOCKOUT)
+        .await
+        .map_err(internal_error("Failed to record login attempt"))?;
+    repositories::users::record_failed_login(pool, &keys.ip, &IP_WIDE_LOCKOUT)
+        .await
+        .map_err(internal_error("Failed to record login attempt"))?;
+
+    rejections::record(
+        pool,
+        RejectionSource::Login,
+        RejectionReason::InvalidCredentials,
+    );
+    Ok(())
+}
+
+/// Longest user agent kept with a session.
+const MAX_USER_AGENT_LENGTH: usize = 256;
+
+/// Where a login came from, as recorded in its `sessions` row.
+pub(super) struct SessionClient {
+    pub user_agent: Option<String>,
+    pub ip_hash: String,
+}
+
+impl SessionClient {
+    pub fn new(headers: &HeaderMap, client_ip: IpAddr) -> Self {
+        let user_agent = headers
+            .get(axum::http::header::USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.trim().chars().take(MAX_USER_AGENT_LENGTH).collect())
+            .filter(|value: &String| !value.is_empty());
+        SessionClient {
+            user_agent,
+            ip_hash: hash_login_identifier(&format!("session:{}", client_ip)),
+        }
+    }
+}
+
+/// Records a new session and issues the auth and CSRF cookies for a
+/// completed login. `remember` selects the longer "remember me" lifetime
+/// for the token and both cookies.
+pub(super) async fn start_session(
+    pool: &DbPool,
+    client: &SessionClient,
+    user_id: i64,
+    username: String,
+    role: String,
+    remember: bool,
+) -> Result<(HeaderMap, Json<LoginResponse>), AppError> {
+    let claims = auth::Claims {
+        uid: Some(user_id),
+        ..auth::Claims::for_login(username.clone(), role.clone(), remember)
+    };
+    let ttl = auth::session_ttl(remember);
+    repositories::sessions::create_session(
+        pool,
+        &claims.jti,
+        &username,
+        claims.exp as i64,
+        client.user_agent.as_deref(),
+        &client.ip_hash,
+    )
+    .await
+    .map_err(internal_error("Failed to record session"))?;
+    let token = auth::encode_claims(&claims).map_err(internal_error("Failed to create token"))?;
+
+    let mut headers = HeaderMap::new();
+    auth::append_auth_cookie(&mut headers, auth::build_auth_cookie(&token, ttl));
+
+    if let Ok(csrf_token) = csrf::issue_csrf_token(&username, ttl) {
+        csrf::append_csrf_cookie(&mut headers, &csrf_token, ttl);
+    } else {
+        tracing::error!("Failed to issue CSRF token for user {}", username);
+        return Err(internal_error_plain("Failed to create token"));
+    }
+
+    Ok((
+        headers,
+        Json(LoginResponse {
+            user: UserResponse { username, role },
+        }),
+    ))
+}
+
+/// Collects the session tokens a request presented, from the Authorization
+/// header and the auth cookie, without duplicates.
+pub(super) fn presented_tokens(headers: &HeaderMap, jar: &CookieJar) -> Vec<String> {
+    let mut tokens = Vec::new();
+
+    if let Some(auth_header) = headers.get(axum::http::header::AUTHORIZATION) {
+        if let Ok(value) = auth_header.to_str() {
+            if let Some(token) = value.strip_prefix("Bearer ").map(|t| t.trim()) {
+                if !token.is_empty() {
+                    tokens.push(token.to_string());
+                }
+            }
+        }
+    }
+
+    if let Some(cookie) = jar.get(auth::AUTH_COOKIE_NAME) {
+        let token = cookie.value();
+        if !tokens.iter().any(|existing| existing == token) {
+            tokens.push(token.to_string());
+        }
+    }
+
+    tokens
+}
+
 /// Parses an optional RFC3339 timestamp string into a UTC DateTime.
 ///
 /// # Arguments
@@ -108,6 +307,8 @@ pub(super) fn parse_rfc3339_opt(value: &Option<String>) -> Option<DateTime<Utc>>
 ///
 /// This hash is used during failed login attempts to ensure password
 /// verification takes constant time regardless of whether the user exists.
+/// It is generated with the configured `BCRYPT_COST` so it costs as much
+/// to check as a real account's hash.
 ///
 /// # Returns
 /// A static bcrypt hash string
@@ -118,13 +319,64 @@ pub(super) fn parse_rfc3339_opt(value: &Option<String>) -> Option<DateTime<Utc>>
 pub(super) fn dummy_bcrypt_hash() -> &'static str {
     static DUMMY_HASH: OnceLock<String> = OnceLock::new();
 
-    DUMMY_HASH.get_or_init(|| match bcrypt::hash("dummy", bcrypt::DEFAULT_COST) {
+    DUMMY_HASH.get_or_init(
+        || match bcrypt::hash("dummy", crate::config::get().bcrypt_cost) {
+            Ok(hash) => hash,
+            Err(err) => {
+                tracing::error!("Failed to generate dummy hash: {}", err);
+                "$2b$12$eImiTXuWVxfM37uY4JANjQPzMzXZjQDzqzQpMv0xoGrTplPPNaE3W".to_string()
+            }
+        },
+    )
+}
+
+/// Rehashes a just-verified password when its stored hash was made with a
+/// lower cost than `BCRYPT_COST`, so raising the setting strengthens
+/// existing accounts as they log in. The rehash only replaces `stored_hash`,
+/// so a password change made meanwhile is kept. Failures are logged and
+/// otherwise ignored; the old hash keeps working.
+pub(super) async fn upgrade_password_hash(
+    pool: &DbPool,
+    username: &str,
+    password: &str,
+    stored_hash: &str,
+) {
+    let cost = crate::config::get().bcrypt_cost;
+    let stored_cost = match stored_hash.parse::<bcrypt::HashParts>() {
+        Ok(parts) => parts.get_cost(),
+        Err(err) => {
+            tracing::warn!(
+                "Stored password hash of {} is unreadable: {}",
+                username,
+                err
+            );
+            return;
+        }
+    };
+    if stored_cost >= cost {
+        return;
+    }
+
+    let upgraded = match bcrypt::hash(password, cost) {
         Ok(hash) => hash,
         Err(err) => {
-            tracing::error!("Failed to generate dummy hash: {}", err);
-            "$2b$12$eImiTXuWVxfM37uY4JANjQPzMzXZjQDzqzQpMv0xoGrTplPPNaE3W".to_string()
+            tracing::error!("Failed to rehash password of {}: {}", username, err);
+            return;
         }
-    })
+    };
+    match repositories::users::replace_password_hash(pool, username, stored_hash, &upgraded).await {
+        Ok(false) => tracing::debug!(
+            user = %username,
+            "Password changed before its rehash was stored; keeping the new one"
+        ),
+        Ok(true) => tracing::info!(
+            user = %username,
+            "Upgraded password hash from cost {} to {}",
+            stored_cost,
+            cost
+        ),
+        Err(err) => tracing::warn!("Failed to store rehashed password of {}: {}", username, err),
+    }
 }
 
 /// Validates a username meets security and format requirements.
@@ -140,7 +392,7 @@ pub(super) fn dummy_bcrypt_hash() -> &'static str {
 /// - Not empty
 /// - Length ≤ 50 characters
 /// - Only alphanumeric, underscore, hyphen, and period allowed
-pub(super) fn validate_username(username: &str) -> Result<(), String> {
+pub(crate) fn validate_username(username: &str) -> Result<(), String> {
     if username.is_empty() {
         return Err("Username cannot be empty".to_string());
     }
@@ -157,6 +409,33 @@ pub(super) fn validate_username(username: &str) -> Result<(), String> {
     Ok(())
 }
 
+/// Minimum length for newly set passwords (NIST SP 800-63B recommendation).
+pub const MIN_PASSWORD_LEN: usize = 12;
+/// bcrypt ignores everything after the first 72 bytes.
+pub const MAX_PASSWORD_BYTES: usize = 72;
+
+/// Validates a password being set for an account (user creation, reset).
+///
+/// Follows NIST SP 800-63B: a length floor instead of composition rules.
+/// Passwords longer than bcrypt's 72-byte input are rejected rather than
+/// silently truncated.
+pub fn validate_password(password: &str) -> Result<(), String> {
+    if password.chars().count() < MIN_PASSWORD_LEN {
+        return Err(format!(
+            "Password must be at least {MIN_PASSWORD_LEN} characters long"
+        ));
+    }
+    if password.len() > MAX_PASSWORD_BYTES {
+        return Err(format!(
+            "Password must not exceed {MAX_PASSWORD_BYTES} bytes"
+        ));
+    }
+    if password.trim().is_empty() {
+        return Err("Password cannot be blank".to_string());
+    }
+    Ok(())
+}
+
 /// Validates a password submitted during login.
 ///
 /// Deliberately minimal: complexity rules belong to password creation,
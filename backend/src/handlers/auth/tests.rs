@@ -1,46 +1,29 @@
 use super::*;
-use crate::db::migrations::run_migrations;
-use sqlx::SqlitePool;
-
-async fn setup_test_db() -> DbPool {
-    let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
-    run_migrations(&pool)
-        .await
-        .expect("Failed to run migrations");
-    pool
-}
+use axum::extract::Path;
 
 fn init_salts() {
     if LOGIN_ATTEMPT_SALT.get().is_none() {
-        env::set_var(
-            "LOGIN_ATTEMPT_SALT",
-            "this_is_a_test_salt_for_login_attempts_at_least_32_chars",
-        );
-        let _ = init_login_attempt_salt();
+        let _ = init_login_attempt_salt("this_is_a_test_salt_for_login_attempts_at_least_32_chars");
     }
     if auth::JWT_SECRET.get().is_none() {
-        env::set_var(
-            "JWT_SECRET",
-            "this_is_a_test_jwt_secret_with_adequate_entropy_123_ABC_!!!",
-        );
-        let _ = auth::init_jwt_secret();
+        let _ =
+            auth::init_jwt_secret("this_is_a_test_jwt_secret_with_adequate_entropy_123_ABC_!!!");
     }
     // CSRF_SECRET is private, we just call init and ignore "already initialized" error
-    env::set_var(
-        "CSRF_SECRET",
+    let _ = csrf::init_csrf_secret(
         "this_is_a_very_long_secret_key_for_testing_purposes_only_at_least_32_bytes",
     );
-    let _ = csrf::init_csrf_secret();
 }
 
 #[tokio::test]
 async fn test_login_invalid_credentials() {
     init_salts();
-    let pool = setup_test_db().await;
+    let pool = crate::test_support::migrated_pool().await;
 
     let payload = LoginRequest {
         username: "nonexistent".to_string(),
         password: "InvalidPassword123!".to_string(),
+        remember_me: false,
     };
 
     let addr = "127.0.0.1:1234".parse().unwrap();
@@ -54,7 +37,7 @@ async fn test_login_invalid_credentials() {
     .await;
 
     assert!(result.is_err());
-    let (status, Json(body)) = result.unwrap_err();
+    let (status, body) = result.unwrap_err().into_parts();
     assert_eq!(status, StatusCode::UNAUTHORIZED);
     assert_eq!(body.error, "Invalid credentials");
 }
@@ -62,15 +45,15 @@ async fn test_login_invalid_credentials() {
 /// Regression test for the password-spraying gap: rotating usernames
 /// gives the attacker a fresh (IP+username) pair key on every attempt,
 /// so only the IP-wide counter can stop them. After
-/// IP_WIDE_SHORT_THRESHOLD failures from one address, the next attempt
+/// `IP_WIDE_LOCKOUT.short_threshold` failures from one address, the next attempt
 /// must be rejected with 429 regardless of which username it targets.
 #[tokio::test]
 async fn test_ip_wide_lockout_blocks_username_rotation() {
     init_salts();
-    let pool = setup_test_db().await;
+    let pool = crate::test_support::migrated_pool().await;
     let addr: std::net::SocketAddr = "127.0.0.2:1234".parse().unwrap();
 
-    for i in 0..IP_WIDE_SHORT_THRESHOLD {
+    for i in 0..IP_WIDE_LOCKOUT.short_threshold {
         let result = login(
             State(pool.clone()),
             HeaderMap::new(),
@@ -78,11 +61,14 @@ async fn test_ip_wide_lockout_blocks_username_rotation() {
             Json(LoginRequest {
                 username: format!("sprayed_user_{}", i),
                 password: "WrongPassword123!".to_string(),
+                remember_me: false,
             }),
         )
         .await;
 
-        let (status, _) = result.expect_err("login with bad password must fail");
+        let status = result
+            .expect_err("login with bad password must fail")
+            .status();
         assert_eq!(
             status,
             StatusCode::UNAUTHORIZED,
@@ -98,14 +84,636 @@ async fn test_ip_wide_lockout_blocks_username_rotation() {
         Json(LoginRequest {
             username: "yet_another_user".to_string(),
             password: "WrongPassword123!".to_string(),
+            remember_me: false,
         }),
     )
     .await;
 
-    let (status, _) = result.expect_err("attempt past the IP-wide threshold must fail");
+    let status = result
+        .expect_err("attempt past the IP-wide threshold must fail")
+        .status();
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+}
+
+async fn failed_login_status(pool: &DbPool, addr: &str, username: &str) -> StatusCode {
+    let status = login(
+        State(pool.clone()),
+        HeaderMap::new(),
+        ConnectInfo(addr.parse().unwrap()),
+        Json(LoginRequest {
+            username: username.to_string(),
+            password: "WrongPassword123!".to_string(),
+            remember_me: false,
+        }),
+    )
+    .await
+    .expect_err("login with bad password must fail")
+    .status();
+    status
+}
+
+#[tokio::test]
+async fn pair_lockout_leaves_other_users_and_addresses_alone() {
+    init_salts();
+    let pool = crate::test_support::migrated_pool().await;
+
+    for _ in 0..PAIR_LOCKOUT.short_threshold {
+        assert_eq!(
+            failed_login_status(&pool, "127.0.0.3:1234", "alice").await,
+            StatusCode::UNAUTHORIZED
+        );
+    }
+    assert_eq!(
+        failed_login_status(&pool, "127.0.0.3:1234", "alice").await,
+        StatusCode::TOO_MANY_REQUESTS
+    );
+
+    // Neither the IP-wide key nor the other pairs are over their limits.
+    assert_eq!(
+        failed_login_status(&pool, "127.0.0.3:1234", "bob").await,
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(
+        failed_login_status(&pool, "127.0.0.4:1234", "alice").await,
+        StatusCode::UNAUTHORIZED
+    );
+
+    let key_types: Vec<(String, i64)> = sqlx::query_as(
+        "SELECT key_type, COUNT(*) FROM login_attempts GROUP BY key_type ORDER BY key_type",
+    )
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+    assert_eq!(key_types, [("ip".to_string(), 2), ("pair".to_string(), 3)]);
+}
+
+#[tokio::test]
+async fn ip_wide_count_restarts_after_a_quiet_window() {
+    init_salts();
+    let pool = crate::test_support::migrated_pool().await;
+    let addr = "127.0.0.5:1234";
+    let below_threshold = IP_WIDE_LOCKOUT.short_threshold - 1;
+
+    for i in 0..below_threshold {
+        failed_login_status(&pool, addr, &format!("early_{i}")).await;
+    }
+
+    // Push the earlier failures out of the window.
+    let window = IP_WIDE_LOCKOUT.window_seconds.unwrap();
+    let quiet_since = (Utc::now() - ChronoDuration::seconds(window + 60)).to_rfc3339();
+    sqlx::query("UPDATE login_attempts SET last_attempt_at = ? WHERE key_type = 'ip'")
+        .bind(&quiet_since)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    for i in 0..below_threshold {
+        assert_eq!(
+            failed_login_status(&pool, addr, &format!("late_{i}")).await,
+            StatusCode::UNAUTHORIZED,
+            "attempt {i} after the quiet window must not be rate limited"
+        );
+    }
+    let fail_count: i64 =
+        sqlx::query_scalar("SELECT fail_count FROM login_attempts WHERE key_type = 'ip'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(fail_count, below_threshold);
+}
+
+async fn insert_user(pool: &DbPool, username: &str, role: &str) {
+    sqlx::query("INSERT INTO users (username, password_hash, role) VALUES (?, ?, ?)")
+        .bind(username)
+        .bind("not-a-real-hash")
+        .bind(role)
+        .execute(pool)
+        .await
+        .expect("insert user");
+}
+
+fn bearer(token: &str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        axum::http::header::AUTHORIZATION,
+        format!("Bearer {token}").parse().unwrap(),
+    );
+    headers
+}
+
+/// Signs `claims` and records their session, as a login would.
+async fn issue_token(pool: &DbPool, claims: &auth::Claims) -> String {
+    repositories::sessions::create_session(
+        pool,
+        &claims.jti,
+        &claims.sub,
+        claims.exp as i64,
+        None,
+        "test",
+    )
+    .await
+    .expect("record session");
+    auth::encode_claims(claims).unwrap()
+}
+
+fn admin_claims(username: &str) -> auth::Claims {
+    auth::Claims::new(username.to_string(), "admin".to_string())
+}
+
+fn claims_expiring_in(username: &str, seconds: i64) -> auth::Claims {
+    let now = Utc::now().timestamp();
+    auth::Claims {
+        exp: (now + seconds) as usize,
+        auth_time: now as usize,
+        ..admin_claims(username)
+    }
+}
+
+#[tokio::test]
+async fn refresh_issues_new_token_and_rejects_blacklisted_one() {
+    init_salts();
+    let pool = crate::test_support::migrated_pool().await;
+    insert_user(&pool, "refresher", "admin").await;
+
+    let token = issue_token(&pool, &admin_claims("refresher")).await;
+    let (headers, Json(body)) = refresh(State(pool.clone()), bearer(&token), csrf::CsrfGuard)
+        .await
+        .expect("refresh with a valid token");
+    assert_eq!(body.user.username, "refresher");
+    let cookies: Vec<_> = headers
+        .get_all(axum::http::header::SET_COOKIE)
+        .iter()
+        .map(|value| value.to_str().unwrap().to_string())
+        .collect();
+    assert!(cookies
+        .iter()
+        .any(|c| c.starts_with(auth::AUTH_COOKIE_NAME)));
+    assert!(cookies
+        .iter()
+        .any(|c| c.starts_with(csrf::csrf_cookie_name())));
+
+    // The old token was revoked by the refresh and cannot be used again.
+    assert!(
+        repositories::token_blacklist::is_token_blacklisted(&pool, &token)
+            .await
+            .unwrap()
+    );
+    let (status, body) = refresh(State(pool.clone()), bearer(&token), csrf::CsrfGuard)
+        .await
+        .expect_err("refresh with a blacklisted token")
+        .into_parts();
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body.error, "Token has been revoked");
+}
+
+#[tokio::test]
+async fn refresh_rejects_a_token_without_a_session() {
+    init_salts();
+    let pool = crate::test_support::migrated_pool().await;
+    insert_user(&pool, "sessionless", "admin").await;
+
+    // Validly signed, but no login recorded a session for it
+    let token = auth::create_jwt("sessionless".to_string(), "admin".to_string()).unwrap();
+    let (status, body) = refresh(State(pool), bearer(&token), csrf::CsrfGuard)
+        .await
+        .expect_err("refresh without a session")
+        .into_parts();
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body.error, "Token has been revoked");
+}
+
+#[tokio::test]
+async fn refresh_near_the_expiry_boundary() {
+    init_salts();
+    let pool = crate::test_support::migrated_pool().await;
+    insert_user(&pool, "boundary", "admin").await;
+
+    // Seconds away from expiry: still refreshable, and the new token gets a
+    // full lifetime again.
+    let expiring = issue_token(&pool, &claims_expiring_in("boundary", 5)).await;
+    let (headers, _) = refresh(State(pool.clone()), bearer(&expiring), csrf::CsrfGuard)
+        .await
+        .expect("refresh just before expiry");
+    let cookie = headers
+        .get(axum::http::header::SET_COOKIE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap();
+    let new_token = cookie
+        .split(';')
+        .next()
+        .and_then(|pair| pair.split_once('='))
+        .map(|(_, value)| value.to_string())
+        .unwrap();
+    let renewed = auth::verify_jwt(&new_token).unwrap();
+    assert!(renewed.exp as i64 > Utc::now().timestamp() + 23 * 3600);
+
+    // Just past expiry: `verify_jwt` would still accept it thanks to its
+    // clock-skew leeway, but refresh must not.
+    let expired = issue_token(&pool, &claims_expiring_in("boundary", -5)).await;
+    assert!(auth::verify_jwt(&expired).is_ok());
+    let (status, body) = refresh(State(pool), bearer(&expired), csrf::CsrfGuard)
+        .await
+        .expect_err("refresh just after expiry")
+        .into_parts();
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body.error, "Token has expired");
+}
+
+async fn change(
+    pool: &DbPool,
+    token: &str,
+    current: &str,
+    new: &str,
+) -> Result<(StatusCode, HeaderMap), AppError> {
+    change_password(
+        State(pool.clone()),
+        bearer(token),
+        CookieJar::new(),
+        ConnectInfo("127.0.0.3:1234".parse().unwrap()),
+        csrf::CsrfGuard,
+        auth::verify_jwt(token).unwrap(),
+        Json(ChangePasswordRequest {
+            current_password: current.to_string(),
+            new_password: new.to_string(),
+        }),
+    )
+    .await
+}
+
+#[tokio::test]
+async fn change_password_rotates_hash_and_revokes_token() {
+    init_salts();
+    let pool = crate::test_support::migrated_pool().await;
+    let hash = bcrypt::hash("old password 123", 4).unwrap();
+    sqlx::query("INSERT INTO users (username, password_hash, role) VALUES ('rotator', ?, 'admin')")
+        .bind(&hash)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let token = issue_token(&pool, &admin_claims("rotator")).await;
+
+    let status = change(&pool, &token, "old password 123", "short")
+        .await
+        .expect_err("weak new password")
+        .status();
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, headers) = change(&pool, &token, "old password 123", "a much better password")
+        .await
+        .expect("change password");
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    assert!(headers.get(axum::http::header::SET_COOKIE).is_some());
+
+    let user = repositories::users::get_user_by_username(&pool, "rotator")
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(bcrypt::verify("a much better password", &user.password_hash).unwrap());
+    assert!(
+        repositories::token_blacklist::is_token_blacklisted(&pool, &token)
+            .await
+            .unwrap()
+    );
+}
+
+#[tokio::test]
+async fn wrong_current_password_counts_toward_lockout() {
+    init_salts();
+    let pool = crate::test_support::migrated_pool().await;
+    let hash = bcrypt::hash("right password 123", 4).unwrap();
+    sqlx::query("INSERT INTO users (username, password_hash, role) VALUES ('guessed', ?, 'admin')")
+        .bind(&hash)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let token = issue_token(&pool, &admin_claims("guessed")).await;
+
+    for _ in 0..PAIR_LOCKOUT.short_threshold {
+        let (status, body) = change(&pool, &token, "wrong guess", "a brand new password")
+            .await
+            .expect_err("wrong current password")
+            .into_parts();
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body.error, "Current password is incorrect");
+    }
+
+    // Locked out now, even with the right password.
+    let status = change(&pool, &token, "right password 123", "a brand new password")
+        .await
+        .expect_err("locked out")
+        .status();
     assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
 }
 
+async fn password_login(pool: &DbPool, username: &str, password: &str) -> axum::response::Response {
+    login(
+        State(pool.clone()),
+        HeaderMap::new(),
+        ConnectInfo("127.0.0.9:1234".parse().unwrap()),
+        Json(LoginRequest {
+            username: username.to_string(),
+            password: password.to_string(),
+            remember_me: false,
+        }),
+    )
+    .await
+    .expect("password accepted")
+}
+
+async fn challenge_from(response: axum::response::Response) -> String {
+    assert_eq!(response.status(), StatusCode::ACCEPTED);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["two_factor_required"], true);
+    body["challenge"].as_str().unwrap().to_string()
+}
+
+async fn second_step(
+    pool: &DbPool,
+    challenge: &str,
+    code: &str,
+) -> Result<(HeaderMap, Json<LoginResponse>), AppError> {
+    login_two_factor(
+        State(pool.clone()),
+        HeaderMap::new(),
+        ConnectInfo("127.0.0.9:1234".parse().unwrap()),
+        Json(TwoFactorLoginRequest {
+            challenge: challenge.to_string(),
+            code: code.to_string(),
+        }),
+    )
+    .await
+}
+
+fn session_token(headers: &HeaderMap) -> String {
+    headers
+        .get_all(axum::http::header::SET_COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .find_map(|cookie| cookie.strip_prefix(&format!("{}=", auth::AUTH_COOKIE_NAME)))
+        .and_then(|rest| rest.split(';').next())
+        .expect("auth cookie")
+        .to_string()
+}
+
+#[tokio::test]
+async fn login_upgrades_weaker_password_hashes() {
+    init_salts();
+    let pool = crate::test_support::migrated_pool().await;
+    let hash = bcrypt::hash("cheap hash 123", 4).unwrap();
+    sqlx::query("INSERT INTO users (username, password_hash, role) VALUES ('legacy', ?, 'author')")
+        .bind(&hash)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    password_login(&pool, "legacy", "cheap hash 123").await;
+
+    let stored = repositories::users::get_user_by_username(&pool, "legacy")
+        .await
+        .unwrap()
+        .unwrap()
+        .password_hash;
+    let parts: bcrypt::HashParts = stored.parse().unwrap();
+    assert_eq!(parts.get_cost(), crate::config::get().bcrypt_cost);
+    assert!(bcrypt::verify("cheap hash 123", &stored).unwrap());
+}
+
+#[tokio::test]
+async fn password_rehash_does_not_override_a_newer_password() {
+    init_salts();
+    let pool = crate::test_support::migrated_pool().await;
+    let weak = bcrypt::hash("cheap hash 123", 4).unwrap();
+    sqlx::query("INSERT INTO users (username, password_hash, role) VALUES ('racer', ?, 'author')")
+        .bind(&weak)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let changed = bcrypt::hash("changed meanwhile 123", 4).unwrap();
+    repositories::users::update_password_hash(&pool, "racer", &changed)
+        .await
+        .unwrap();
+
+    support::upgrade_password_hash(&pool, "racer", "cheap hash 123", &weak).await;
+
+    let stored = repositories::users::get_user_by_username(&pool, "racer")
+        .await
+        .unwrap()
+        .unwrap()
+        .password_hash;
+    assert_eq!(stored, changed);
+}
+
+#[tokio::test]
+async fn sessions_are_listed_and_revoked() {
+    init_salts();
+    let pool = crate::test_support::migrated_pool().await;
+    let hash = bcrypt::hash("many devices 123", 4).unwrap();
+    sqlx::query("INSERT INTO users (username, password_hash, role) VALUES ('roamer', ?, 'admin')")
+        .bind(&hash)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let laptop = session_token(
+        password_login(&pool, "roamer", "many devices 123")
+            .await
+            .headers(),
+    );
+    let phone = session_token(
+        password_login(&pool, "roamer", "many devices 123")
+            .await
+            .headers(),
+    );
+    let laptop_claims = auth::verify_jwt(&laptop).unwrap();
+    let phone_claims = auth::verify_jwt(&phone).unwrap();
+    assert_ne!(laptop_claims.jti, phone_claims.jti);
+
+    let Json(listed) = list_sessions(State(pool.clone()), laptop_claims.clone())
+        .await
+        .unwrap();
+    assert_eq!(listed.items.len(), 2);
+    let current: Vec<_> = listed.items.iter().filter(|s| s.current).collect();
+    assert_eq!(current.len(), 1);
+    assert_eq!(current[0].jti, laptop_claims.jti);
+
+    // Revoking the phone's session rejects its token but not the laptop's.
+    let status = revoke_session(
+        State(pool.clone()),
+        csrf::CsrfGuard,
+        laptop_claims.clone(),
+        Path(phone_claims.jti.clone()),
+    )
+    .await
+    .unwrap();
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    assert!(auth::is_token_revoked(&pool, &phone, &phone_claims)
+        .await
+        .unwrap());
+    assert!(!auth::is_token_revoked(&pool, &laptop, &laptop_claims)
+        .await
+        .unwrap());
+    let status = revoke_session(
+        State(pool.clone()),
+        csrf::CsrfGuard,
+        laptop_claims.clone(),
+        Path(phone_claims.jti.clone()),
+    )
+    .await
+    .expect_err("already revoked")
+    .status();
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // A refresh continues the same session.
+    let (headers, _) = refresh(State(pool.clone()), bearer(&laptop), csrf::CsrfGuard)
+        .await
+        .unwrap();
+    let refreshed = session_token(&headers);
+    let refreshed_claims = auth::verify_jwt(&refreshed).unwrap();
+    assert_eq!(refreshed_claims.jti, laptop_claims.jti);
+    let Json(listed) = list_sessions(State(pool.clone()), refreshed_claims.clone())
+        .await
+        .unwrap();
+    assert_eq!(listed.items.len(), 1);
+
+    let tablet = session_token(
+        password_login(&pool, "roamer", "many devices 123")
+            .await
+            .headers(),
+    );
+    let tablet_claims = auth::verify_jwt(&tablet).unwrap();
+    let (status, _) = logout_all(
+        State(pool.clone()),
+        bearer(&refreshed),
+        CookieJar::new(),
+        csrf::CsrfGuard,
+        refreshed_claims.clone(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    assert!(auth::is_token_revoked(&pool, &refreshed, &refreshed_claims)
+        .await
+        .unwrap());
+    assert!(auth::is_token_revoked(&pool, &tablet, &tablet_claims)
+        .await
+        .unwrap());
+    let Json(listed) = list_sessions(State(pool.clone()), tablet_claims)
+        .await
+        .unwrap();
+    assert!(listed.items.is_empty());
+}
+
+#[tokio::test]
+async fn two_factor_enrollment_login_and_recovery() {
+    use crate::security::totp;
+
+    init_salts();
+    let _ = totp::init_totp_encryption_key("this_is_a_test_totp_encryption_key_0123456789");
+    let pool = crate::test_support::migrated_pool().await;
+    let hash = bcrypt::hash("second factor 123", 4).unwrap();
+    sqlx::query("INSERT INTO users (username, password_hash, role) VALUES ('careful', ?, 'admin')")
+        .bind(&hash)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let claims = claims_expiring_in("careful", 3600);
+
+    let Json(setup) = setup_two_factor(State(pool.clone()), csrf::CsrfGuard, claims.clone())
+        .await
+        .expect("setup");
+    assert!(setup.otpauth_uri.contains(&setup.secret));
+    // Not enabled until confirmed.
+    assert_eq!(
+        password_login(&pool, "careful", "second factor 123")
+            .await
+            .status(),
+        StatusCode::OK
+    );
+
+    let state = repositories::two_factor::get_state(&pool, "careful")
+        .await
+        .unwrap()
+        .unwrap();
+    let secret = totp::decrypt_secret(state.totp_secret.as_deref().unwrap()).unwrap();
+    let now = totp::step_at(Utc::now().timestamp() as u64);
+    let Json(enabled) = verify_two_factor(
+        State(pool.clone()),
+        csrf::CsrfGuard,
+        claims.clone(),
+        Json(TwoFactorVerifyRequest {
+            code: totp::code_at(&secret, now),
+        }),
+    )
+    .await
+    .expect("verify");
+    assert_eq!(enabled.recovery_codes.len(), totp::RECOVERY_CODE_COUNT);
+
+    // The password alone now only yields a challenge.
+    let challenge =
+        challenge_from(password_login(&pool, "careful", "second factor 123").await).await;
+    let status = second_step(&pool, &challenge, "000000")
+        .await
+        .expect_err("wrong code")
+        .status();
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    // The code used for enrollment cannot be replayed.
+    let status = second_step(&pool, &challenge, &totp::code_at(&secret, now))
+        .await
+        .expect_err("replayed code")
+        .status();
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (headers, Json(body)) = second_step(&pool, &challenge, &totp::code_at(&secret, now + 1))
+        .await
+        .expect("next code accepted");
+    assert_eq!(body.user.username, "careful");
+    assert!(headers.get(axum::http::header::SET_COOKIE).is_some());
+    let status = second_step(&pool, &challenge, &totp::code_at(&secret, now + 1))
+        .await
+        .expect_err("challenge is single-use")
+        .status();
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // Recovery codes work once.
+    let recovery = enabled.recovery_codes[0].to_uppercase();
+    let challenge =
+        challenge_from(password_login(&pool, "careful", "second factor 123").await).await;
+    let (_, Json(body)) = second_step(&pool, &challenge, &recovery)
+        .await
+        .expect("recovery code accepted");
+    assert_eq!(body.user.username, "careful");
+    let challenge =
+        challenge_from(password_login(&pool, "careful", "second factor 123").await).await;
+    let status = second_step(&pool, &challenge, &recovery)
+        .await
+        .expect_err("recovery code already spent")
+        .status();
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let status = disable_two_factor(
+        State(pool.clone()),
+        HeaderMap::new(),
+        ConnectInfo("127.0.0.9:1234".parse().unwrap()),
+        csrf::CsrfGuard,
+        claims,
+        Json(TwoFactorDisableRequest {
+            password: "second factor 123".to_string(),
+            code: enabled.recovery_codes[1].clone(),
+        }),
+    )
+    .await
+    .expect("disable");
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    assert_eq!(
+        password_login(&pool, "careful", "second factor 123")
+            .await
+            .status(),
+        StatusCode::OK
+    );
+}
+
 #[test]
 fn test_validate_username() {
     assert!(validate_username("admin").is_ok());
@@ -116,6 +724,15 @@ fn test_validate_username() {
     assert!(validate_username("a".repeat(51).as_str()).is_err());
 }
 
+#[test]
+fn test_validate_password() {
+    assert!(validate_password("correct horse battery").is_ok());
+    assert!(validate_password("elevenchars").is_err());
+    assert!(validate_password(&" ".repeat(20)).is_err());
+    assert!(validate_password(&"a".repeat(72)).is_ok());
+    assert!(validate_password(&"a".repeat(73)).is_err());
+}
+
 #[test]
 fn test_validate_login_password() {
     assert!(validate_login_password("ValidPassword123!").is_ok());
@@ -133,3 +750,182 @@ fn test_validate_login_password() {
     assert!(validate_login_password("").is_err());
     assert!(validate_login_password(&"a".repeat(129)).is_err());
 }
+
+fn cookie_max_age(headers: &HeaderMap, name: &str) -> i64 {
+    headers
+        .get_all(axum::http::header::SET_COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .filter(|cookie| cookie.starts_with(&format!("{name}=")))
+        .find_map(|cookie| {
+            cookie
+                .split(';')
+                .find_map(|attr| attr.trim().strip_prefix("Max-Age="))
+        })
+        .and_then(|value| value.parse().ok())
+        .expect("cookie with Max-Age")
+}
+
+#[tokio::test]
+async fn remembered_login_keeps_its_lifetime_across_refreshes() {
+    init_salts();
+    let pool = crate::test_support::migrated_pool().await;
+    let hash = bcrypt::hash("keep me signed in", 4).unwrap();
+    sqlx::query("INSERT INTO users (username, password_hash, role) VALUES ('keeper', ?, 'admin')")
+        .bind(&hash)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let response = login(
+        State(pool.clone()),
+        HeaderMap::new(),
+        ConnectInfo("127.0.0.9:1234".parse().unwrap()),
+        Json(LoginRequest {
+            username: "keeper".to_string(),
+            password: "keep me signed in".to_string(),
+            remember_me: true,
+        }),
+    )
+    .await
+    .expect("password accepted");
+    let ttl = auth::session_ttl(true).num_seconds();
+    assert!(ttl > auth::session_ttl(false).num_seconds());
+    assert_eq!(
+        cookie_max_age(response.headers(), auth::AUTH_COOKIE_NAME),
+        ttl
+    );
+    assert_eq!(
+        cookie_max_age(response.headers(), csrf::csrf_cookie_name()),
+        ttl
+    );
+
+    let token = session_token(response.headers());
+    let claims = auth::verify_jwt(&token).unwrap();
+    assert!(claims.remember);
+    assert!((claims.exp as i64 - Utc::now().timestamp() - ttl).abs() <= 5);
+
+    let (headers, _) = refresh(State(pool.clone()), bearer(&token), csrf::CsrfGuard)
+        .await
+        .unwrap();
+    let refreshed = auth::verify_jwt(&session_token(&headers)).unwrap();
+    assert!(refreshed.remember);
+    assert!((refreshed.exp as i64 - Utc::now().timestamp() - ttl).abs() <= 5);
+    assert!((cookie_max_age(&headers, csrf::csrf_cookie_name()) - ttl).abs() <= 5);
+}
+
+async fn require_sudo(pool: &DbPool, token: &str) -> Result<auth::Claims, AppError> {
+    use axum::extract::FromRequestParts;
+
+    let mut request = axum::http::Request::new(());
+    *request.headers_mut() = bearer(token);
+    let (mut parts, ()) = request.into_parts();
+    auth::RequireSudo::from_request_parts(&mut parts, pool)
+        .await
+        .map(|auth::RequireSudo(claims)| claims)
+}
+
+async fn sudo(
+    pool: &DbPool,
+    token: &str,
+    password: &str,
+) -> Result<(HeaderMap, Json<SudoResponse>), AppError> {
+    enter_sudo_mode(
+        State(pool.clone()),
+        bearer(token),
+        ConnectInfo("127.0.0.6:1234".parse().unwrap()),
+        csrf::CsrfGuard,
+        auth::verify_jwt(token).unwrap(),
+        Json(SudoRequest {
+            password: password.to_string(),
+        }),
+    )
+    .await
+}
+
+#[tokio::test]
+async fn sudo_mode_requires_the_password_and_expires() {
+    init_salts();
+    let pool = crate::test_support::migrated_pool().await;
+    let hash = bcrypt::hash("sudo password 123", 4).unwrap();
+    sqlx::query(
+        "INSERT INTO users (username, password_hash, role) VALUES ('elevated', ?, 'admin')",
+    )
+    .bind(&hash)
+    .execute(&pool)
+    .await
+    .unwrap();
+    let token = issue_token(&pool, &admin_claims("elevated")).await;
+
+    let (status, body) = require_sudo(&pool, &token)
+        .await
+        .expect_err("plain session")
+        .into_parts();
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body.code, auth::SUDO_REQUIRED_CODE);
+
+    let status = sudo(&pool, &token, "wrong password 123")
+        .await
+        .expect_err("wrong password")
+        .status();
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (headers, Json(body)) = sudo(&pool, &token, "sudo password 123")
+        .await
+        .expect("enter sudo mode");
+    let elevated_token = session_token(&headers);
+    let elevated = require_sudo(&pool, &elevated_token)
+        .await
+        .expect("elevated session");
+
+    // Same session and expiry, elevated for a limited time only.
+    let original = auth::verify_jwt(&token).unwrap();
+    assert_eq!(elevated.jti, original.jti);
+    assert_eq!(elevated.exp, original.exp);
+    let until = DateTime::parse_from_rfc3339(&body.sudo_until).unwrap();
+    assert_eq!(Some(until.timestamp() as usize), elevated.sudo_until);
+    let later = Utc::now() + ChronoDuration::seconds(auth::SUDO_MODE_TTL_SECONDS + 1);
+    assert!(!elevated.in_sudo_mode(later));
+}
+
+#[tokio::test]
+async fn csrf_endpoint_issues_a_token_the_guard_accepts() {
+    use axum::extract::FromRequestParts;
+
+    init_salts();
+    let pool = crate::test_support::migrated_pool().await;
+    let token = auth::create_jwt("csrf_user".to_string(), "user".to_string()).unwrap();
+    let claims = auth::verify_jwt(&token).unwrap();
+
+    let (headers, Json(body)) = csrf_token(claims.clone()).await.expect("issue token");
+    assert_eq!(
+        cookie_max_age(&headers, csrf::csrf_cookie_name()),
+        body.expires_in
+    );
+    let cookie_value = headers
+        .get_all(axum::http::header::SET_COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .find_map(|cookie| cookie.strip_prefix(&format!("{}=", csrf::csrf_cookie_name())))
+        .and_then(|rest| rest.split(';').next())
+        .expect("csrf cookie");
+    assert_eq!(cookie_value, body.csrf_token);
+
+    let mut request = axum::http::Request::post("/api/auth/logout")
+        .body(())
+        .unwrap();
+    *request.headers_mut() = bearer(&token);
+    request.headers_mut().insert(
+        axum::http::header::COOKIE,
+        format!("{}={}", csrf::csrf_cookie_name(), body.csrf_token)
+            .parse()
+            .unwrap(),
+    );
+    request
+        .headers_mut()
+        .insert(csrf::csrf_header_name(), body.csrf_token.parse().unwrap());
+    let (mut parts, ()) = request.into_parts();
+    assert!(csrf::CsrfGuard::from_request_parts(&mut parts, &pool)
+        .await
+        .is_ok());
+}
@@ -0,0 +1,356 @@
+//! Two-factor login with TOTP codes.
+//!
+//! Enrollment is two steps: `setup` stores a new secret and hands it to the
+//! user, `verify` confirms that the authenticator produces matching codes
+//! and only then turns the second factor on and issues recovery codes.
+//!
+//! At login, a correct password for such an account yields a single-use
+//! challenge instead of a session. The challenge expires after
+//! [`CHALLENGE_TTL_SECONDS`] and survives [`MAX_CHALLENGE_ATTEMPTS`] wrong
+//! codes; wrong codes also count toward the regular login lockout.
+
+use super::*;
+use crate::security::{sha256_hex, totp};
+use base64ct::{Base64UrlUnpadded, Encoding};
+
+/// Lifetime of a login challenge.
+pub(super) const CHALLENGE_TTL_SECONDS: i64 = 300;
+/// Wrong codes a single challenge accepts before it is discarded.
+pub(super) const MAX_CHALLENGE_ATTEMPTS: i64 = 5;
+
+const CHALLENGE_BYTES: usize = 32;
+
+fn not_configured() -> AppError {
+    api_error(
+        StatusCode::SERVICE_UNAVAILABLE,
+        "Two-factor authentication is not configured on this server",
+    )
+}
+
+fn invalid_code() -> AppError {
+    unauthorized("Invalid authentication code")
+}
+
+fn challenge_expired() -> AppError {
+    unauthorized("Login challenge expired or unknown; sign in again")
+}
+
+async fn state_for(pool: &DbPool, claims: &auth::Claims) -> Result<TwoFactorState, AppError> {
+    repositories::two_factor::get_state(pool, &claims.sub)
+        .await
+        .map_err(internal_error("Failed to load user"))?
+        .ok_or_else(|| unauthorized("Invalid token"))
+}
+
+fn decrypt(encrypted: &str) -> Result<Vec<u8>, AppError> {
+    if !totp::is_configured() {
+        return Err(not_configured());
+    }
+    totp::decrypt_secret(encrypted).map_err(|err| {
+        tracing::error!("Failed to read TOTP secret: {}", err);
+        internal_error_plain("Two-factor verification failed")
+    })
+}
+
+/// Checks a second factor: six digits are a TOTP code, anything else a
+/// recovery code. Accepted codes are consumed.
+async fn check_second_factor(
+    pool: &DbPool,
+    state: &TwoFactorState,
+    code: &str,
+) -> Result<bool, AppError> {
+    let digits: String = code.chars().filter(|c| !c.is_whitespace()).collect();
+    if digits.len() != totp::CODE_DIGITS || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return repositories::two_factor::consume_recovery_code(
+            pool,
+            state.user_id,
+            &totp::hash_recovery_code(code),
+        )
+        .await
+        .map_err(internal_error("Failed to verify recovery code"));
+    }
+
+    let Some(encrypted) = state.totp_secret.as_deref() else {
+        return Ok(false);
+    };
+    let secret = decrypt(encrypted)?;
+    let now = u64::try_from(Utc::now().timestamp()).unwrap_or_default();
+    let last_step = state
+        .totp_last_step
+        .and_then(|step| u64::try_from(step).ok());
+    match totp::verify_code(&secret, &digits, now, last_step) {
+        Some(step) => repositories::two_factor::advance_last_step(pool, state.user_id, step as i64)
+            .await
+            .map_err(internal_error("Failed to verify code")),
+        None => Ok(false),
+    }
+}
+
+/// Creates the challenge a password login hands out when the account has
+/// two-factor login enabled. The `remember_me` choice is kept with it and
+/// applied once the code is accepted.
+pub(super) async fn issue_challenge(
+    pool: &DbPool,
+    user_id: i64,
+    remember_me: bool,
+) -> Result<TwoFactorChallengeResponse, AppError> {
+    let mut raw = [0u8; CHALLENGE_BYTES];
+    rand::fill(&mut raw[..]);
+    let challenge = Base64UrlUnpadded::encode_string(&raw);
+    let expires_at = (Utc::now() + ChronoDuration::seconds(CHALLENGE_TTL_SECONDS)).to_rfc3339();
+
+    repositories::two_factor::create_challenge(
+        pool,
+        &sha256_hex(challenge.as_bytes()),
+        user_id,
+        &expires_at,
+        remember_me,
+    )
+    .await
+    .map_err(internal_error("Failed to start two-factor login"))?;
+
+    Ok(TwoFactorChallengeResponse {
+        two_factor_required: true,
+        challenge,
+        expires_in: CHALLENGE_TTL_SECONDS,
+    })
+}
+
+/// HTTP handler starting two-factor enrollment.
+///
+/// # Endpoint
+/// POST /api/auth/2fa/setup
+///
+/// # Response
+/// 200 OK with the new secret and its `otpauth://` URI. Nothing changes at
+/// login until the secret is confirmed through `POST /api/auth/2fa/verify`;
+/// calling setup again replaces an unconfirmed secret.
+///
+/// # Errors
+/// - 401 Unauthorized: Missing or invalid JWT
+/// - 403 Forbidden: Missing or invalid CSRF token
+/// - 409 Conflict: Two-factor login is already enabled
+/// - 503 Service Unavailable: `TOTP_ENCRYPTION_KEY` is not set
+pub async fn setup_two_factor(
+    State(pool): State<DbPool>,
+    _csrf: csrf::CsrfGuard,
+    claims: auth::Claims,
+) -> Result<Json<TwoFactorSetupResponse>, AppError> {
+    if !totp::is_configured() {
+        return Err(not_configured());
+    }
+    let state = state_for(&pool, &claims).await?;
+    if state.totp_enabled {
+        return Err(conflict("Two-factor authentication is already enabled"));
+    }
+
+    let secret = totp::generate_secret();
+    let encrypted = totp::encrypt_secret(&secret).map_err(|err| {
+        tracing::error!("Failed to encrypt TOTP secret: {}", err);
+        internal_error_plain("Failed to start two-factor setup")
+    })?;
+    let stored = repositories::two_factor::store_pending_secret(&pool, state.user_id, &encrypted)
+        .await
+        .map_err(internal_error("Failed to start two-factor setup"))?;
+    if !stored {
+        return Err(conflict("Two-factor authentication is already enabled"));
+    }
+
+    Ok(Json(TwoFactorSetupResponse {
+        secret: totp::base32_encode(&secret),
+        otpauth_uri: totp::otpauth_uri(&state.username, &secret),
+    }))
+}
+
+/// HTTP handler confirming enrollment with a code from the authenticator.
+///
+/// # Endpoint
+/// POST /api/auth/2fa/verify
+///
+/// # Response
+/// 200 OK with the recovery codes. They are shown only this once.
+///
+/// # Errors
+/// - 400 Bad Request: No setup in progress, or the code does not match
+/// - 401 Unauthorized: Missing or invalid JWT
+/// - 403 Forbidden: Missing or invalid CSRF token
+/// - 409 Conflict: Two-factor login is already enabled
+pub async fn verify_two_factor(
+    State(pool): State<DbPool>,
+    _csrf: csrf::CsrfGuard,
+    claims: auth::Claims,
+    Json(payload): Json<TwoFactorVerifyRequest>,
+) -> Result<Json<TwoFactorEnabledResponse>, AppError> {
+    let state = state_for(&pool, &claims).await?;
+    if state.totp_enabled {
+        return Err(conflict("Two-factor authentication is already enabled"));
+    }
+    let Some(encrypted) = state.totp_secret.as_deref() else {
+        return Err(bad_request("Start two-factor setup first"));
+    };
+
+    let secret = decrypt(encrypted)?;
+    let now = u64::try_from(Utc::now().timestamp()).unwrap_or_default();
+    let step = totp::verify_code(&secret, &payload.code, now, None)
+        .ok_or_else(|| bad_request("Invalid authentication code"))?;
+
+    let recovery_codes = totp::generate_recovery_codes();
+    let hashes: Vec<String> = recovery_codes
+        .iter()
+        .map(|code| totp::hash_recovery_code(code))
+        .collect();
+    let enabled = repositories::two_factor::enable(&pool, state.user_id, step as i64, &hashes)
+        .await
+        .map_err(internal_error("Failed to enable two-factor authentication"))?;
+    if !enabled {
+        return Err(conflict(
+            "Two-factor setup changed concurrently; start again",
+        ));
+    }
+
+    tracing::info!(user = %state.username, "Two-factor authentication enabled");
+    Ok(Json(TwoFactorEnabledResponse { recovery_codes }))
+}
+
+/// HTTP handler turning two-factor login off.
+///
+/// # Endpoint
+/// POST /api/auth/2fa/disable
+///
+/// # Request
+/// The account password plus an authenticator or recovery code, so a
+/// hijacked session alone cannot remove the second factor.
+///
+/// # Errors
+/// - 400 Bad Request: Two-factor login is not enabled
+/// - 401 Unauthorized: Missing or invalid JWT, wrong password or code
+/// - 403 Forbidden: Missing or invalid CSRF token
+/// - 429 Too Many Requests: Locked out by failed attempts
+pub async fn disable_two_factor(
+    State(pool): State<DbPool>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    _csrf: csrf::CsrfGuard,
+    claims: auth::Claims,
+    Json(payload): Json<TwoFactorDisableRequest>,
+) -> Result<StatusCode, AppError> {
+    validate_login_password(&payload.password).map_err(bad_request)?;
+
+    let client_ip = security_middleware::extract_client_ip(&headers, addr.ip());
+    let keys = AttemptKeys::new(client_ip, &claims.sub);
+    enforce_lockout(&pool, &keys).await?;
+
+    let user = repositories::users::get_user_by_username(&pool, &claims.sub)
+        .await
+        .map_err(internal_error("Failed to load user"))?
+        .ok_or_else(|| unauthorized("Invalid token"))?;
+    let password_valid =
+        bcrypt::verify(&payload.password, &user.password_hash).unwrap_or_else(|e| {
+            tracing::error!("Password verification error: {}", e);
+            false
+        });
+    if !password_valid {
+        record_password_failure(&pool, &keys).await?;
+        return Err(unauthorized("Invalid credentials"));
+    }
+
+    let state = state_for(&pool, &claims).await?;
+    if !state.totp_enabled {
+        return Err(bad_request("Two-factor authentication is not enabled"));
+    }
+    if !check_second_factor(&pool, &state, &payload.code).await? {
+        record_password_failure(&pool, &keys).await?;
+        return Err(invalid_code());
+    }
+
+    repositories::two_factor::disable(&pool, state.user_id)
+        .await
+        .map_err(internal_error(
+            "Failed to disable two-factor authentication",
+        ))?;
+    tracing::info!(user = %state.username, "Two-factor authentication disabled");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// HTTP handler completing a login that requires a second factor.
+///
+/// # Endpoint
+/// POST /api/auth/login/2fa
+///
+/// # Request
+/// ```json
+/// { "challenge": "<from POST /api/auth/login>", "code": "123456" }
+/// ```
+/// `code` may also be an unused recovery code, which is then spent.
+///
+/// # Response
+/// The same cookies and body as a plain password login.
+///
+/// # Errors
+/// - 401 Unauthorized: Unknown or expired challenge, or a wrong code
+/// - 429 Too Many Requests: Locked out by failed attempts
+pub async fn login_two_factor(
+    State(pool): State<DbPool>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Json(payload): Json<TwoFactorLoginRequest>,
+) -> Result<(HeaderMap, Json<LoginResponse>), AppError> {
+    let token_hash = sha256_hex(payload.challenge.trim().as_bytes());
+    let challenge = repositories::two_factor::get_challenge(&pool, &token_hash)
+        .await
+        .map_err(internal_error("Failed to load login challenge"))?
+        .ok_or_else(challenge_expired)?;
+    if parse_rfc3339_opt(&Some(challenge.expires_at)).is_none_or(|expiry| expiry <= Utc::now()) {
+        let _ = repositories::two_factor::delete_challenge(&pool, &token_hash).await;
+        return Err(challenge_expired());
+    }
+
+    let state = repositories::two_factor::get_state_by_id(&pool, challenge.user_id)
+        .await
+        .map_err(internal_error("Failed to load user"))?
+        .filter(|state| state.totp_enabled)
+        .ok_or_else(challenge_expired)?;
+
+    let client_ip = security_middleware::extract_client_ip(&headers, addr.ip());
+    let keys = AttemptKeys::new(client_ip, &state.username);
+    let has_attempt_record = enforce_lockout(&pool, &keys).await?;
+
+    if !check_second_factor(&pool, &state, &payload.code).await? {
+        repositories::two_factor::record_challenge_failure(
+            &pool,
+            &token_hash,
+            MAX_CHALLENGE_ATTEMPTS,
+        )
+        .await
+        .map_err(internal_error("Failed to record login attempt"))?;
+        record_password_failure(&pool, &keys).await?;
+        return Err(invalid_code());
+    }
+
+    let redeemed = repositories::two_factor::delete_challenge(&pool, &token_hash)
+        .await
+        .map_err(internal_error("Failed to complete login"))?;
+    if !redeemed {
+        return Err(challenge_expired());
+    }
+
+    if has_attempt_record {
+        if let Err(e) = repositories::users::clear_login_attempts(&pool, &keys.pair).await {
+            tracing::warn!(
+                "Failed to clear login attempts after two-factor login: {}",
+                e
+            );
+        }
+    }
+
+    let client = SessionClient::new(&headers, client_ip);
+    start_session(
+        &pool,
+        &client,
+        state.user_id,
+        state.username,
+        state.role,
+        challenge.remember_me,
+    )
+    .await
+}
@@ -0,0 +1,247 @@
+//! Public changelog of published and substantially updated content.
+//!
+//! Events are written by the tutorial and post handlers when a document first
+//! becomes visible or changes substantially (see
+//! [`classify_change`](crate::repositories::events::classify_change)); this
+//! module serves them as JSON and as an RSS 2.0 feed.
+
+use crate::{
+    db,
+    handlers::{common::request_origin, site_content::load_public_settings},
+    models::{internal_error, AppError, ChangelogEntry, ChangelogResponse, ContentEventKind},
+    repositories,
+};
+use axum::{
+    extract::{Query, State},
+    http::{header, HeaderMap},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Deserialize;
+
+const DEFAULT_CHANGELOG_LIMIT: i64 = 20;
+const MAX_CHANGELOG_LIMIT: i64 = 100;
+
+#[derive(Debug, Deserialize)]
+pub struct ChangelogQuery {
+    #[serde(default)]
+    pub limit: Option<i64>,
+}
+
+impl ChangelogQuery {
+    fn limit(&self) -> i64 {
+        self.limit
+            .unwrap_or(DEFAULT_CHANGELOG_LIMIT)
+            .clamp(1, MAX_CHANGELOG_LIMIT)
+    }
+}
+
+/// Records a changelog event. The changelog is secondary to the save that
+/// triggered it, so a failed insert is logged rather than failing the request.
+pub(crate) async fn record_event(
+    pool: &db::DbPool,
+    entity_type: &str,
+    entity_id: &str,
+    slug: &str,
+    title: &str,
+    kind: ContentEventKind,
+) {
+    if let Err(err) =
+        repositories::events::record_content_event(pool, entity_type, entity_id, slug, title, kind)
+            .await
+    {
+        tracing::warn!(
+            entity_type,
+            entity_id,
+            kind = kind.as_str(),
+            "Failed to record content event: {}",
+            err
+        );
+    }
+}
+
+/// Handler for `GET /api/public/changelog`.
+pub async fn get_changelog(
+    State(pool): State<db::DbPool>,
+    Query(query): Query<ChangelogQuery>,
+) -> Result<Json<ChangelogResponse>, AppError> {
+    let items = repositories::events::list_public_events(&pool, query.limit())
+        .await
+        .map_err(internal_error("Failed to load changelog"))?;
+
+    Ok(Json(ChangelogResponse { items }))
+}
+
+/// Handler for `GET /api/public/changelog.rss`.
+pub async fn get_changelog_rss(
+    State(pool): State<db::DbPool>,
+    Query(query): Query<ChangelogQuery>,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    let items = repositories::events::list_public_events(&pool, query.limit())
+        .await
+        .map_err(internal_error("Failed to load changelog"))?;
+    let settings = load_public_settings(&pool).await?;
+    let origin = request_origin(&headers).unwrap_or_default();
+
+    let body = render_rss(
+        &settings.site.title,
+        &settings.site.description,
+        &origin,
+        &items,
+    );
+    Ok((
+        [(header::CONTENT_TYPE, "application/rss+xml; charset=utf-8")],
+        body,
+    )
+        .into_response())
+}
+
+/// Renders the events as an RSS 2.0 document. Links are absolute when the
+/// request origin is known and site-relative otherwise.
+fn render_rss(title: &str, description: &str, origin: &str, items: &[ChangelogEntry]) -> String {
+    use html_escape::encode_text as esc;
+
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<rss version=\"2.0\">\n<channel>\n",
+    );
+    xml.push_str(&format!("<title>{}</title>\n", esc(title)));
+    xml.push_str(&format!("<link>{}/</link>\n", esc(origin)));
+    xml.push_str(&format!(
+        "<description>{}</description>\n",
+        esc(description)
+    ));
+
+    for item in items {
+        let action = if item.kind == ContentEventKind::Published.as_str() {
+            "Published"
+        } else {
+            "Updated"
+        };
+        let link = format!("{origin}{}", item.link);
+        xml.push_str("<item>\n");
+        xml.push_str(&format!(
+            "<title>{}: {}</title>\n",
+            action,
+            esc(&item.title)
+        ));
+        xml.push_str(&format!("<link>{}</link>\n", esc(&link)));
+        xml.push_str(&format!(
+            "<guid isPermaLink=\"false\">content-event-{}</guid>\n",
+            item.id
+        ));
+        if let Some(date) = rfc2822(&item.created_at) {
+            xml.push_str(&format!("<pubDate>{date}</pubDate>\n"));
+        }
+        xml.push_str("</item>\n");
+    }
+
+    xml.push_str("</channel>\n</rss>\n");
+    xml
+}
+
+/// Converts SQLite's `datetime('now')` format (UTC) to the RFC 2822 dates
+/// RSS requires.
+fn rfc2822(timestamp: &str) -> Option<String> {
+    chrono::NaiveDateTime::parse_from_str(timestamp, "%Y-%m-%d %H:%M:%S")
+        .ok()
+        .map(|naive| naive.and_utc().to_rfc2822())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn changelog_hides_unpublished_and_deleted_content() {
+        let pool = crate::test_support::migrated_pool().await;
+
+        sqlx::query(
+            "INSERT INTO site_pages (id, slug, title, is_published) VALUES ('p1', 'notes', 'Notes', 1)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        for (id, slug, published) in [("s1", "hello", 1), ("s2", "draft", 0)] {
+            sqlx::query(
+                "INSERT INTO site_posts (id, page_id, title, slug, content_markdown, is_published) VALUES (?, 'p1', ?, ?, 'x', ?)",
+            )
+            .bind(id)
+            .bind(slug)
+            .bind(slug)
+            .bind(published)
+            .execute(&pool)
+            .await
+            .unwrap();
+            repositories::events::record_content_event(
+                &pool,
+                "post",
+                id,
+                slug,
+                slug,
+                ContentEventKind::Published,
+            )
+            .await
+            .unwrap();
+        }
+        let tutorial_id: String = sqlx::query_scalar("SELECT id FROM tutorials LIMIT 1")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        repositories::events::record_content_event(
+            &pool,
+            "tutorial",
+            &tutorial_id,
+            &tutorial_id,
+            "Tutorial",
+            ContentEventKind::Updated,
+        )
+        .await
+        .unwrap();
+
+        let Json(response) =
+            get_changelog(State(pool.clone()), Query(ChangelogQuery { limit: None }))
+                .await
+                .unwrap();
+        let links: Vec<_> = response
+            .items
+            .iter()
+            .map(|item| item.link.as_str())
+            .collect();
+        assert_eq!(links.len(), 2);
+        assert!(links.contains(&"/posts/notes/hello"));
+        assert!(links.contains(&format!("/tutorials/{tutorial_id}").as_str()));
+
+        sqlx::query("DELETE FROM tutorials WHERE id = ?")
+            .bind(&tutorial_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let remaining: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM content_events WHERE entity_type = 'tutorial' AND entity_id = ?",
+        )
+        .bind(&tutorial_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(remaining, 0);
+    }
+
+    #[test]
+    fn rss_escapes_titles_and_uses_absolute_links() {
+        let items = vec![ChangelogEntry {
+            id: 7,
+            entity_type: "post".to_string(),
+            entity_id: "s1".to_string(),
+            title: "Tips & <Tricks>".to_string(),
+            kind: "published".to_string(),
+            created_at: "2024-05-01 12:00:00".to_string(),
+            link: "/posts/notes/tips".to_string(),
+        }];
+
+        let xml = render_rss("Blog", "Notes", "https://example.com", &items);
+        assert!(xml.contains("<title>Published: Tips &amp; &lt;Tricks&gt;</title>"));
+        assert!(xml.contains("<link>https://example.com/posts/notes/tips</link>"));
+        assert!(xml.contains("<pubDate>Wed, 1 May 2024 12:00:00 +0000</pubDate>"));
+    }
+}
@@ -0,0 +1,233 @@
+//! Comment Blocklist Handlers
+//!
+//! A cheap first line of defense against spam waves. Admins list words,
+//! client IPs and author names in `comment_blocklist`; every new comment is
+//! checked against them before it is stored:
+//! - a blocked word (case-insensitive substring) holds the comment back as
+//!   `pending`, without telling the author
+//! - a blocked IP or author name refuses the submission with a 403
+//!
+//! Submissions are checked against an in-process copy of the list, reloaded
+//! whenever it changes through these endpoints.
+
+use crate::{
+    db::DbPool,
+    handlers::{
+        auth::hash_comment_ip,
+        common::{ensure_admin, map_sqlx_error},
+    },
+    models::*,
+    repositories,
+    security::auth,
+};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use std::collections::HashSet;
+use std::net::IpAddr;
+use std::sync::{Arc, RwLock};
+
+/// Longest word or name that can be listed.
+const MAX_VALUE_LENGTH: usize = 100;
+
+/// The blocklist, split by kind, with words and names lowercased.
+#[derive(Debug, Default)]
+struct Blocklist {
+    words: Vec<String>,
+    ip_hashes: HashSet<String>,
+    author_names: HashSet<String>,
+}
+
+static CACHE: RwLock<Option<Arc<Blocklist>>> = RwLock::new(None);
+
+/// What to do with a submission, see [`check_submission`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Verdict {
+    Allow,
+    /// Store it as `pending`.
+    Hold,
+    /// Refuse it.
+    Refuse,
+}
+
+async fn load(pool: &DbPool) -> Result<Arc<Blocklist>, sqlx::Error> {
+    let mut blocklist = Blocklist::default();
+    for entry in repositories::comment_blocklist::list_entries(pool).await? {
+        match entry.kind.as_str() {
+            "word" => blocklist.words.push(entry.value.to_lowercase()),
+            "ip_hash" => {
+                blocklist.ip_hashes.insert(entry.value);
+            }
+            "author_name" => {
+                blocklist.author_names.insert(entry.value.to_lowercase());
+            }
+            _ => {}
+        }
+    }
+    Ok(Arc::new(blocklist))
+}
+
+/// Reloads the cache after a change. If that fails the cache is dropped,
+/// so the next submission loads it instead.
+async fn refresh_cache(pool: &DbPool) {
+    let loaded = load(pool).await;
+    if let Err(err) = &loaded {
+        tracing::error!("Failed to reload comment blocklist: {}", err);
+    }
+    if let Ok(mut cache) = CACHE.write() {
+        *cache = loaded.ok();
+    }
+}
+
+/// Checks a submission from `ip` by `author` against the blocklist.
+pub(crate) async fn check_submission(
+    pool: &DbPool,
+    author: &str,
+    content: &str,
+    ip: &str,
+) -> Result<Verdict, sqlx::Error> {
+    let cached = CACHE.read().ok().and_then(|cache| cache.clone());
+    let blocklist = match cached {
+        Some(blocklist) => blocklist,
+        None => {
+            let loaded = load(pool).await?;
+            // A refresh that finished meanwhile is newer; keep it.
+            if let Ok(mut cache) = CACHE.write() {
+                cache.get_or_insert_with(|| loaded.clone());
+            }
+            loaded
+        }
+    };
+
+    if blocklist
+        .author_names
+        .contains(&author.trim().to_lowercase())
+    {
+        return Ok(Verdict::Refuse);
+    }
+    if !blocklist.ip_hashes.is_empty() {
+        let ip = ip
+            .parse::<IpAddr>()
+            .map(|ip| ip.to_string())
+            .unwrap_or_else(|_| ip.to_string());
+        if blocklist.ip_hashes.contains(&hash_comment_ip(&ip)) {
+            return Ok(Verdict::Refuse);
+        }
+    }
+
+    let content = content.to_lowercase();
+    if blocklist.words.iter().any(|word| content.contains(word)) {
+        return Ok(Verdict::Hold);
+    }
+    Ok(Verdict::Allow)
+}
+
+/// Validates and normalizes a new entry's value: words and names are
+/// lowercased, a plain IP is hashed, and an existing hash is kept.
+fn normalize_entry(kind: &str, value: &str) -> Result<String, String> {
+    let value = value.trim();
+    match kind {
+        "word" | "author_name" => {
+            if value.is_empty() || value.chars().count() > MAX_VALUE_LENGTH {
+                return Err(format!("Value must be 1-{MAX_VALUE_LENGTH} characters"));
+            }
+            Ok(value.to_lowercase())
+        }
+        "ip_hash" => {
+            if let Ok(ip) = value.parse::<IpAddr>() {
+                return Ok(hash_comment_ip(&ip.to_string()));
+            }
+            if value.len() == 64
+                && value
+                    .bytes()
+                    .all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+            {
+                return Ok(value.to_string());
+            }
+            Err("Value must be an IP address or its hash".to_string())
+        }
+        _ => Err(format!(
+            "Kind must be one of: {}",
+            BLOCKLIST_KINDS.join(", ")
+        )),
+    }
+}
+
+/// Handler for `GET /api/admin/comment-blocklist`.
+/// Admin-only.
+pub async fn list_blocklist(
+    claims: auth::Claims,
+    State(pool): State<DbPool>,
+) -> Result<Json<CommentBlocklistResponse>, AppError> {
+    ensure_admin(&claims)?;
+
+    let items = repositories::comment_blocklist::list_entries(&pool)
+        .await
+        .map_err(|err| map_sqlx_error(err, "Blocklist entry"))?;
+    Ok(Json(CommentBlocklistResponse { items }))
+}
+
+/// Handler for `POST /api/admin/comment-blocklist`.
+/// Admin-only, protected by CSRF.
+pub async fn create_blocklist_entry(
+    claims: auth::Claims,
+    State(pool): State<DbPool>,
+    Json(payload): Json<CreateBlocklistEntryRequest>,
+) -> Result<(StatusCode, Json<CommentBlocklistEntry>), AppError> {
+    ensure_admin(&claims)?;
+
+    let kind = payload.kind.trim();
+    let value = normalize_entry(kind, &payload.value).map_err(bad_request)?;
+
+    let entry = repositories::comment_blocklist::create_entry(&pool, kind, &value, &claims.sub)
+        .await
+        .map_err(|err| map_sqlx_error(err, "Blocklist entry"))?
+        .ok_or_else(|| conflict("Entry is already on the blocklist"))?;
+    refresh_cache(&pool).await;
+
+    tracing::info!(action = "create_blocklist_entry", user = %claims.sub, kind = %entry.kind, "Admin extended comment blocklist");
+    Ok((StatusCode::CREATED, Json(entry)))
+}
+
+/// Handler for `DELETE /api/admin/comment-blocklist/{id}`.
+/// Admin-only, protected by CSRF.
+pub async fn delete_blocklist_entry(
+    claims: auth::Claims,
+    State(pool): State<DbPool>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, AppError> {
+    ensure_admin(&claims)?;
+
+    let deleted = repositories::comment_blocklist::delete_entry(&pool, id)
+        .await
+        .map_err(|err| map_sqlx_error(err, "Blocklist entry"))?;
+    refresh_cache(&pool).await;
+    if !deleted {
+        return Err(not_found("Blocklist entry not found"));
+    }
+
+    tracing::info!(action = "delete_blocklist_entry", user = %claims.sub, id, "Admin removed comment blocklist entry");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_are_normalized_by_kind() {
+        assert_eq!(normalize_entry("word", "  CaSiNo ").unwrap(), "casino");
+        assert_eq!(
+            normalize_entry("author_name", "Spam Bot").unwrap(),
+            "spam bot"
+        );
+        let hash = "a".repeat(64);
+        assert_eq!(normalize_entry("ip_hash", &hash).unwrap(), hash);
+
+        assert!(normalize_entry("word", "   ").is_err());
+        assert!(normalize_entry("ip_hash", "not-an-ip").is_err());
+        assert!(normalize_entry("email", "x@example.com").is_err());
+    }
+}
@@ -5,13 +5,33 @@
 //!
 //! # Endpoints
 //! - GET /api/tutorials/{id}/comments: List comments for a tutorial (public, paginated)
+//! - GET /api/tutorials/{id}/comments/count, /api/posts/{id}/comments/count:
+//!   Number of comments, for badges on list pages (public)
 //! - POST /api/tutorials/{id}/comments: Create comment (admin only, CSRF protected)
+//! - PUT /api/comments/{id}: Edit comment (author within the edit window, or admin; CSRF protected)
 //! - DELETE /api/comments/{id}: Delete comment (admin only, CSRF protected)
+//! - POST/DELETE /api/comments/{id}/vote: Vote on a comment or withdraw the vote
+//! - GET /api/comments/token: Anti-spam token a guest comment form must send back
+//! - GET /api/admin/comments: Comments across all tutorials and posts, filtered
+//!   for moderation (admin only)
+//! - DELETE /api/admin/comments: Delete a list of comments at once (admin only, CSRF protected)
 //!
 //! # Features
 //! - Pagination support (default 50 comments, configurable via query params)
+//! - Cursor pagination via `?after=<comment_id>` for long, busy threads
+//! - Threaded replies via `parent_id`, nested up to [`MAX_COMMENT_DEPTH`]
+//!   levels; pages hold top-level comments with their replies under `replies`
 //! - Author attribution from JWT claims
 //! - Content length validation (1-1000 characters)
+//! - Limited Markdown, stored raw and served rendered as `content_html`
+//! - Gravatar avatars: an optional guest `email` is reduced to `avatar_hash`
+//!   and never stored or returned
+//! - Guest submissions need a form token at least `COMMENT_MIN_FORM_SECONDS`
+//!   old, and a filled-in `website` honeypot discards them with a fake success
+//! - Blocklist checks (see [`crate::handlers::comment_blocklist`]); held-back
+//!   comments are `pending` and left out of listings and counts
+//! - New comments are reported to `NOTIFY_WEBHOOK_URL` (see
+//!   [`crate::notifications`])
 //! - Foreign key cascade deletion (comments deleted with tutorial)
 //!
 //! # Security
@@ -21,29 +41,47 @@
 //! - Tutorial ID validation prevents injection
 
 use crate::{
-    db::DbPool, handlers::tutorials::validate_tutorial_id,
-    middleware::security as security_middleware, models::*, repositories, security::auth,
+    db::DbPool,
+    handlers::comment_blocklist::{self, Verdict},
+    handlers::tutorials::validate_tutorial_id,
+    middleware::security as security_middleware,
+    models::*,
+    notifications, repositories,
+    security::{auth, comment_token},
 };
 use axum::{
     extract::{ConnectInfo, Path, Query, State},
     http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
     Json,
 };
 use serde::{Deserialize, Serialize};
 use std::net::SocketAddr;
 
 mod comment_models;
-use comment_models::sanitize_comment_content;
-use comment_models::{CommentListQuery, CommentResponse, CreateCommentRequest};
+use comment_models::{build_threads, sanitize_comment_content};
+use comment_models::{
+    CommentCountResponse, CommentCursorPage, CommentFormTokenResponse, CommentListQuery,
+    CommentListResponse, CommentResponse, CreateCommentRequest, UpdateCommentRequest,
+    VoteConflictResponse,
+};
+
+mod moderation;
+pub use moderation::{delete_comments_bulk, list_moderated_comments};
+
+/// Maximum nesting level of a thread: a top-level comment, a reply to it,
+/// and a reply to that reply.
+pub const MAX_COMMENT_DEPTH: i64 = 3;
 
 /// Handler for listing comments on a tutorial
 ///
 /// Returns a paginated list of comments for the specified tutorial.
 pub async fn list_comments(
     State(pool): State<DbPool>,
+    auth::OptionalClaims(claims): auth::OptionalClaims,
     Path(tutorial_id): Path<String>,
     Query(params): Query<CommentListQuery>,
-) -> Result<Json<Vec<CommentResponse>>, ApiError> {
+) -> Result<Json<CommentListResponse>, AppError> {
     validate_tutorial_id(&tutorial_id).map_err(bad_request)?;
 
     let exists = repositories::tutorials::check_tutorial_exists(&pool, &tutorial_id)
@@ -54,23 +92,14 @@ pub async fn list_comments(
         return Err(not_found("Tutorial not found"));
     }
 
-    let limit = params.limit.clamp(1, 200);
-    let offset = params.offset.max(0);
-
-    let comments = repositories::comments::list_comments(
+    fetch_comment_page(
         &pool,
-        &tutorial_id,
-        limit,
-        offset,
-        params.sort.as_deref(),
+        repositories::comments::CommentScope::Tutorial(&tutorial_id),
+        params,
+        claims.as_ref().map(|c| c.sub.as_str()),
     )
     .await
-    .map_err(internal_error("Failed to fetch comments"))?;
-
-    let response_comments: Vec<CommentResponse> =
-        comments.into_iter().map(CommentResponse::from).collect();
-
-    Ok(Json(response_comments))
+    .map(Json)
 }
 
 /// Handler for creating a comment on a tutorial
@@ -84,16 +113,17 @@ pub async fn create_comment(
     claims: auth::Claims,
     _csrf: crate::security::csrf::CsrfGuard,
     Json(payload): Json<CreateCommentRequest>,
-) -> Result<Json<CommentResponse>, ApiError> {
+) -> Result<Json<CommentResponse>, AppError> {
     validate_tutorial_id(&tutorial_id).map_err(bad_request)?;
 
-    // Verify tutorial exists
-    let exists = repositories::tutorials::check_tutorial_exists(&pool, &tutorial_id)
+    // Verify tutorial exists and is open for comments
+    let allowed = repositories::tutorials::comments_allowed(&pool, &tutorial_id)
         .await
-        .map_err(internal_error("Failed to create comment"))?;
+        .map_err(internal_error("Failed to create comment"))?
+        .ok_or_else(|| not_found("Tutorial not found"))?;
 
-    if !exists {
-        return Err(not_found("Tutorial not found"));
+    if !allowed {
+        return Err(forbidden("Comments are disabled for this tutorial"));
     }
 
     let client_ip = security_middleware::extract_client_ip(&headers, addr.ip());
@@ -114,9 +144,10 @@ pub async fn create_comment(
 /// Returns a paginated list of comments for the specified post.
 pub async fn list_post_comments(
     State(pool): State<DbPool>,
+    auth::OptionalClaims(claims): auth::OptionalClaims,
     Path(post_id): Path<String>,
     Query(params): Query<CommentListQuery>,
-) -> Result<Json<Vec<CommentResponse>>, ApiError> {
+) -> Result<Json<CommentListResponse>, AppError> {
     // Verify post exists
     let exists = repositories::posts::check_post_exists(&pool, &post_id)
         .await
@@ -126,23 +157,182 @@ pub async fn list_post_comments(
         return Err(not_found("Post not found"));
     }
 
+    fetch_comment_page(
+        &pool,
+        repositories::comments::CommentScope::Post(&post_id),
+        params,
+        claims.as_ref().map(|c| c.sub.as_str()),
+    )
+    .await
+    .map(Json)
+}
+
+/// Handler counting the comments on a tutorial
+///
+/// Includes replies. Public, like the listing.
+pub async fn count_tutorial_comments(
+    State(pool): State<DbPool>,
+    Path(tutorial_id): Path<String>,
+) -> Result<Json<CommentCountResponse>, AppError> {
+    validate_tutorial_id(&tutorial_id).map_err(bad_request)?;
+
+    let exists = repositories::tutorials::check_tutorial_exists(&pool, &tutorial_id)
+        .await
+        .map_err(internal_error("Failed to count comments"))?;
+
+    if !exists {
+        return Err(not_found("Tutorial not found"));
+    }
+
+    let count = repositories::comments::count_comments_for_tutorial(&pool, &tutorial_id)
+        .await
+        .map_err(internal_error("Failed to count comments"))?;
+
+    Ok(Json(CommentCountResponse { count }))
+}
+
+/// Handler counting the comments on a blog post
+///
+/// Includes replies. Public, like the listing.
+pub async fn count_post_comments(
+    State(pool): State<DbPool>,
+    Path(post_id): Path<String>,
+) -> Result<Json<CommentCountResponse>, AppError> {
+    let exists = repositories::posts::check_post_exists(&pool, &post_id)
+        .await
+        .map_err(internal_error("Failed to count comments"))?;
+
+    if !exists {
+        return Err(not_found("Post not found"));
+    }
+
+    let count = repositories::comments::count_comments_for_post(&pool, &post_id)
+        .await
+        .map_err(internal_error("Failed to count comments"))?;
+
+    Ok(Json(CommentCountResponse { count }))
+}
+
+/// Loads one page of comments in either offset or cursor mode.
+///
+/// Pages are made of top-level comments in the requested sort order; each
+/// carries its whole reply tree in chronological order.
+///
+/// Cursor mode is selected by the presence of `after` (an empty value starts
+/// from the first comment) and answers with a [`CommentCursorPage`]; plain
+/// offset requests keep returning a bare array for existing clients unless
+/// they ask for the [`Paginated`] envelope.
+///
+/// With a `voter` (the signed-in user's name) every comment reports
+/// `has_voted`.
+async fn fetch_comment_page(
+    pool: &DbPool,
+    scope: repositories::comments::CommentScope<'_>,
+    params: CommentListQuery,
+    voter: Option<&str>,
+) -> Result<CommentListResponse, AppError> {
     let limit = params.limit.clamp(1, 200);
     let offset = params.offset.max(0);
+    let sort = params.sort.as_deref();
 
-    let comments = repositories::comments::list_post_comments(
-        &pool,
-        &post_id,
-        limit,
-        offset,
-        params.sort.as_deref(),
+    let Some(after) = params.after else {
+        let comments = repositories::comments::list_scoped_comments(
+            pool, scope, limit, offset, sort, None, voter,
+        )
+        .await
+        .map_err(internal_error("Failed to fetch comments"))?;
+        let items = with_replies(pool, comments, voter).await?;
+        if !params.envelope {
+            return Ok(CommentListResponse::Offset(items));
+        }
+        let total = repositories::comments::count_threads(pool, scope)
+            .await
+            .map_err(internal_error("Failed to fetch comments"))?;
+        return Ok(CommentListResponse::Page(Paginated::new(
+            items, total, limit, offset,
+        )));
+    };
+
+    let after = after.trim();
+    let cursor = if after.is_empty() {
+        None
+    } else {
+        let cursor = repositories::comments::resolve_comment_cursor(pool, scope, after)
+            .await
+            .map_err(internal_error("Failed to fetch comments"))?
+            .ok_or_else(|| bad_request("Invalid cursor"))?;
+        Some(cursor)
+    };
+
+    // One extra row tells us whether another page exists without a COUNT.
+    let mut comments = repositories::comments::list_scoped_comments(
+        pool,
+        scope,
+        limit + 1,
+        0,
+        sort,
+        cursor.as_ref(),
+        voter,
     )
     .await
     .map_err(internal_error("Failed to fetch comments"))?;
+    let next_cursor = if comments.len() as i64 > limit {
+        comments.truncate(limit as usize);
+        comments.last().map(|row| row.comment.id.clone())
+    } else {
+        None
+    };
+
+    Ok(CommentListResponse::Cursor(CommentCursorPage {
+        items: with_replies(pool, comments, voter).await?,
+        next_cursor,
+    }))
+}
+
+/// Loads the replies below a page of top-level comments and nests them.
+async fn with_replies(
+    pool: &DbPool,
+    roots: Vec<repositories::comments::ListedComment>,
+    voter: Option<&str>,
+) -> Result<Vec<CommentResponse>, AppError> {
+    let root_ids: Vec<String> = roots.iter().map(|row| row.comment.id.clone()).collect();
+    let replies = repositories::comments::list_replies(pool, &root_ids, voter)
+        .await
+        .map_err(internal_error("Failed to fetch comments"))?;
+    Ok(build_threads(roots, replies, voter.is_some()))
+}
 
-    let response_comments: Vec<CommentResponse> =
-        comments.into_iter().map(CommentResponse::from).collect();
+/// Checks that `parent_id` names a comment on the same tutorial or post
+/// that can still take replies.
+async fn validate_parent(
+    pool: &DbPool,
+    parent_id: &str,
+    tutorial_id: Option<&str>,
+    post_id: Option<&str>,
+) -> Result<(), AppError> {
+    let parent = repositories::comments::get_comment(pool, parent_id)
+        .await
+        .map_err(internal_error("Failed to create comment"))?
+        .filter(|parent| parent.status == COMMENT_STATUS_PUBLISHED)
+        .ok_or_else(|| bad_request("Parent comment not found"))?;
+
+    if parent.tutorial_id.as_deref() != tutorial_id || parent.post_id.as_deref() != post_id {
+        return Err(bad_request(
+            "Parent comment belongs to a different tutorial or post",
+        ));
+    }
+
+    let depth = repositories::comments::comment_depth(pool, parent_id)
+        .await
+        .map_err(internal_error("Failed to create comment"))?
+        .unwrap_or(1);
+    if depth >= MAX_COMMENT_DEPTH {
+        return Err(bad_request(format!(
+            "Replies can only be nested {MAX_COMMENT_DEPTH} levels deep"
+        )));
+    }
 
-    Ok(Json(response_comments))
+    Ok(())
 }
 
 /// Handler for creating a comment on a blog post
@@ -156,7 +346,7 @@ pub async fn create_post_comment(
     auth::OptionalClaims(claims): auth::OptionalClaims,
     _csrf: crate::security::csrf::CsrfGuard,
     Json(payload): Json<CreateCommentRequest>,
-) -> Result<Json<CommentResponse>, ApiError> {
+) -> Result<Json<CommentResponse>, AppError> {
     // Verify post exists
     let exists = repositories::posts::check_post_exists(&pool, &post_id)
         .await
@@ -189,25 +379,49 @@ async fn create_comment_internal(
     payload: CreateCommentRequest,
     claims: Option<auth::Claims>,
     ip_address: String,
-) -> Result<Json<CommentResponse>, ApiError> {
+) -> Result<Json<CommentResponse>, AppError> {
+    if claims.is_none() {
+        // Bots fill in every field; people never see this one. Pretend the
+        // comment went through so the bot has no reason to try again.
+        if payload
+            .website
+            .as_deref()
+            .is_some_and(|v| !v.trim().is_empty())
+        {
+            tracing::info!("Discarded guest comment that filled in the honeypot");
+            return Ok(Json(discarded_comment(tutorial_id, post_id, &payload)));
+        }
+        check_form_token(payload.form_token.as_deref())?;
+    }
+
     let comment_content = sanitize_comment_content(&payload.content)?;
 
-    let (author, rate_limit_key, author_username, is_guest) = if let Some(ref c) = claims {
+    let parent_id = match payload.parent_id.as_deref().map(str::trim) {
+        Some(parent_id) => {
+            validate_parent(&pool, parent_id, tutorial_id.as_deref(), post_id.as_deref()).await?;
+            Some(parent_id.to_string())
+        }
+        None => None,
+    };
+
+    let (author, rate_limit_key, author_username, is_guest, user_id) = if let Some(ref c) = claims {
+        let user_id = account_id(&pool, c).await?;
         let display_name = if c.role == "admin" {
             "Administrator".to_string()
         } else {
             c.sub.clone()
         };
-        // Admin posts are stored with the display author, so rate limiting must use the same key.
-        // author_username/is_guest record the real identity for later ownership
-        // checks in delete_comment. Populated for admins too (harmless -- admin
-        // deletion is governed by the separate is_admin/role check, not these
-        // fields).
+        // user_id is the identity delete_comment checks ownership against;
+        // rate limiting is keyed by it too, so renaming the account does not
+        // reset the limit. author_username/is_guest are kept for exports.
+        // All of them are populated for admins too (harmless -- admin
+        // deletion is governed by the separate is_admin/role check).
         (
-            display_name.clone(),
             display_name,
+            format!("user:{user_id}"),
             Some(c.sub.clone()),
             Some(false),
+            Some(user_id),
         )
     } else {
         // Guest comment
@@ -239,12 +453,48 @@ async fn create_comment_internal(
 
                 // Use the IP address as the guest rate-limit key to prevent name-change bypasses.
                 // A guest never has a real identity to record.
-                (trimmed.to_string(), ip_address, None, Some(true))
+                (
+                    trimmed.to_string(),
+                    ip_address.clone(),
+                    None,
+                    Some(true),
+                    None,
+                )
             }
             None => return Err(bad_request("Name is required for guest comments")),
         }
     };
 
+    let email = match user_id {
+        Some(user_id) => repositories::users::get_user_email(&pool, user_id)
+            .await
+            .map_err(internal_error("Failed to create comment"))?,
+        None => match payload.email.as_deref().map(str::trim) {
+            Some(email) if !email.is_empty() => Some(
+                crate::handlers::newsletter::validate_and_normalize_email(email)
+                    .map_err(|_| bad_request("Invalid email address"))?,
+            ),
+            _ => None,
+        },
+    };
+    let avatar_hash = email.as_deref().map(gravatar_hash);
+
+    // Admins are trusted; everyone else goes through the blocklist. A blocked
+    // word only holds the comment back, so spammers learn nothing from it.
+    let is_admin = claims.as_ref().is_some_and(|c| c.role == "admin");
+    let verdict = if is_admin {
+        Verdict::Allow
+    } else {
+        comment_blocklist::check_submission(&pool, &author, &comment_content, &ip_address)
+            .await
+            .map_err(internal_error("Failed to create comment"))?
+    };
+    let status = match verdict {
+        Verdict::Refuse => return Err(forbidden("You are not allowed to comment")),
+        Verdict::Hold => COMMENT_STATUS_PENDING,
+        Verdict::Allow => COMMENT_STATUS_PUBLISHED,
+    };
+
     // Rate limiting
     let last_comment_time = repositories::comments::get_last_comment_time(&pool, &rate_limit_key)
         .await
@@ -269,18 +519,12 @@ async fn create_comment_internal(
     let id = uuid::Uuid::new_v4().to_string();
     let now = chrono::Utc::now().to_rfc3339();
 
-    // Determine if author is admin
-    let is_admin = if let Some(ref c) = claims {
-        c.role == "admin"
-    } else {
-        false
-    };
-
     let comment = repositories::comments::create_comment(
         &pool,
         &id,
         tutorial_id,
         post_id,
+        parent_id,
         &author,
         &rate_limit_key,
         &comment_content,
@@ -288,13 +532,185 @@ async fn create_comment_internal(
         is_admin,
         author_username,
         is_guest,
+        user_id,
+        avatar_hash,
+        status,
     )
     .await
     .map_err(internal_error("Failed to create comment"))?;
+    notifications::comment_created(&pool, &comment);
+
+    // Guest comments have no account to attribute them to
+    if let Some(ref c) = claims {
+        repositories::audit::append_entry(
+            &pool,
+            &c.sub,
+            "create",
+            "comment",
+            Some(&comment.id),
+            serde_json::json!({
+                "tutorial_id": comment.tutorial_id,
+                "post_id": comment.post_id,
+                "parent_id": comment.parent_id,
+            }),
+        )
+        .await;
+    }
 
     Ok(Json(CommentResponse::from(comment)))
 }
 
+/// Rejects a guest submission whose form token is missing, forged, sent back
+/// too quickly or too late (see [`crate::security::comment_token`]).
+fn check_form_token(token: Option<&str>) -> Result<(), AppError> {
+    let min_age =
+        chrono::Duration::seconds(i64::from(crate::config::get().comment_min_form_seconds));
+    let token = token.unwrap_or_default();
+    comment_token::verify_comment_token(token, min_age, chrono::Utc::now()).map_err(|err| match err
+    {
+        comment_token::TokenError::Invalid => {
+            bad_request("Missing or invalid form token; reload the page")
+        }
+        comment_token::TokenError::TooFast => api_error(
+            StatusCode::TOO_MANY_REQUESTS,
+            "Comment submitted too quickly; please try again",
+        ),
+        comment_token::TokenError::Expired => {
+            bad_request("The comment form expired; reload the page")
+        }
+    })
+}
+
+/// What a guest would get back for `payload`, without storing anything.
+fn discarded_comment(
+    tutorial_id: Option<String>,
+    post_id: Option<String>,
+    payload: &CreateCommentRequest,
+) -> CommentResponse {
+    CommentResponse::from(Comment {
+        id: uuid::Uuid::new_v4().to_string(),
+        tutorial_id,
+        post_id,
+        parent_id: payload.parent_id.clone(),
+        author: payload
+            .author
+            .as_deref()
+            .unwrap_or_default()
+            .trim()
+            .to_string(),
+        content: payload.content.trim().to_string(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+        edited_at: None,
+        votes: 0,
+        is_admin: false,
+        author_username: None,
+        is_guest: Some(true),
+        user_id: None,
+        avatar_hash: None,
+        status: COMMENT_STATUS_PUBLISHED.to_string(),
+    })
+}
+
+/// Handler for `GET /api/comments/token`: a token to send back with a guest
+/// comment, issued when the form is shown.
+pub async fn issue_form_token() -> Result<Json<CommentFormTokenResponse>, AppError> {
+    let token = comment_token::issue_comment_token()
+        .map_err(|_| internal_error_plain("Failed to issue form token"))?;
+    Ok(Json(CommentFormTokenResponse { token }))
+}
+
+/// Gravatar's hash of an email address: SHA-256 of the trimmed, lowercased
+/// address, hex encoded.
+fn gravatar_hash(email: &str) -> String {
+    crate::security::sha256_hex(email.trim().to_lowercase().as_bytes())
+}
+
+/// Resolves the `users.id` behind a token. Tokens issued before the `uid`
+/// claim existed are looked up by username.
+async fn account_id(pool: &DbPool, claims: &auth::Claims) -> Result<i64, AppError> {
+    if let Some(uid) = claims.uid {
+        return Ok(uid);
+    }
+    repositories::users::get_user_by_username(pool, &claims.sub)
+        .await
+        .map_err(internal_error("Failed to resolve account"))?
+        .map(|user| user.id)
+        .ok_or_else(|| forbidden("Account no longer exists"))
+}
+
+/// Whether `claims` belong to the account that wrote `comment`.
+///
+/// Ownership is the account ID recorded when the comment was created,
+/// never a name: `author` is free text that guests can set to anyone's
+/// username, and usernames can change or be reused after an account is
+/// deleted. Guest comments and comments whose account is gone have no
+/// owner. Admin-authored comments are excluded; they're covered by the
+/// is_admin role check.
+async fn is_comment_author(
+    pool: &DbPool,
+    comment: &Comment,
+    claims: &auth::Claims,
+) -> Result<bool, AppError> {
+    match comment.user_id {
+        Some(owner) if !comment.is_admin => Ok(owner == account_id(pool, claims).await?),
+        _ => Ok(false),
+    }
+}
+
+/// Handler for editing a comment
+///
+/// Admins can edit any comment at any time. Authors can edit their own
+/// comment for `COMMENT_EDIT_WINDOW_MINUTES` after posting; guests, having
+/// no account, cannot edit.
+pub async fn update_comment(
+    claims: auth::Claims,
+    State(pool): State<DbPool>,
+    Path(id): Path<String>,
+    _csrf: crate::security::csrf::CsrfGuard,
+    Json(payload): Json<UpdateCommentRequest>,
+) -> Result<Json<CommentResponse>, AppError> {
+    let content = sanitize_comment_content(&payload.content)?;
+
+    let comment = repositories::comments::get_comment(&pool, &id)
+        .await
+        .map_err(internal_error("Failed to fetch comment"))?
+        .ok_or_else(|| not_found("Comment not found"))?;
+
+    let is_admin = claims.role == "admin";
+    if !is_admin {
+        if !is_comment_author(&pool, &comment, &claims).await? {
+            return Err(forbidden("Insufficient permissions"));
+        }
+        let window_minutes = crate::config::get().comment_edit_window_minutes;
+        let window = chrono::Duration::minutes(i64::from(window_minutes));
+        let within_window = crate::handlers::common::parse_stored_timestamp(&comment.created_at)
+            .is_some_and(|created_at| chrono::Utc::now() - created_at <= window);
+        if !within_window {
+            return Err(forbidden(format!(
+                "Comments can only be edited within {window_minutes} minutes of posting"
+            )));
+        }
+    }
+
+    let edited_at = chrono::Utc::now().to_rfc3339();
+    let updated = repositories::comments::update_comment_content(&pool, &id, &content, &edited_at)
+        .await
+        .map_err(internal_error("Failed to update comment"))?
+        .ok_or_else(|| not_found("Comment not found"))?;
+
+    repositories::audit::append_entry(
+        &pool,
+        &claims.sub,
+        "update",
+        "comment",
+        Some(&id),
+        serde_json::json!({ "author": comment.author, "moderated": is_admin && !comment.is_admin }),
+    )
+    .await;
+
+    Ok(Json(CommentResponse::from(updated)))
+}
+
 /// Handler for deleting a comment
 ///
 /// Requires the user to be either an administrator or the original author.
@@ -303,7 +719,7 @@ pub async fn delete_comment(
     State(pool): State<DbPool>,
     Path(id): Path<String>,
     _csrf: crate::security::csrf::CsrfGuard,
-) -> Result<StatusCode, ApiError> {
+) -> Result<StatusCode, AppError> {
     // Fetch the comment first to check ownership
     let comment = repositories::comments::get_comment(&pool, &id)
         .await
@@ -311,49 +727,14 @@ pub async fn delete_comment(
         .ok_or_else(|| not_found("Comment not found"))?;
 
     // Check permissions: Admin or Author.
-    //
-    // Ownership is determined by the real authenticated identity
-    // (author_username), NOT the spoofable free-text `author` display name
-    // (guests can type any name, including another real user's username).
-    //
-    // Four states, disambiguated by (author_username, is_guest). Every arm
-    // is written out explicitly (no wildcard `_`) so that a row shape no
-    // current insert path produces can never silently fall through to the
-    // spoofable legacy comparison -- it must be reachable only for rows that
-    // are *provably* pre-migration (both columns NULL).
-    //   - author_username = Some(u): post-migration authenticated comment.
-    //     Compare real identity directly.
-    //   - author_username = None, is_guest = Some(true): post-migration guest
-    //     comment. A guest never has a real identity to match -- never allow
-    //     the display-name fallback, or the impersonation hole stays open
-    //     forever for new guest comments.
-    //   - author_username = None, is_guest = None: pre-migration legacy row
-    //     of unknown origin (could be guest or authenticated). Fall back to
-    //     the legacy display-name match to avoid regressing self-service
-    //     deletion for real users' historical comments. This is an accepted,
-    //     time-bounded residual risk limited to rows that already existed
-    //     when this fix shipped; it cannot apply to anything created after.
-    //   - author_username = None, is_guest = Some(false): inconsistent state
-    //     that no current code path produces (an authenticated comment
-    //     should always carry author_username). Reject rather than fall
-    //     back to the spoofable comparison, so a future bug or manual data
-    //     edit that produces this shape fails closed instead of silently
-    //     reopening the impersonation hole this migration closes.
-    // Admin-authored comments are excluded from all of the above; they're
-    // already covered by the is_admin role check.
     let is_admin = claims.role == "admin";
-    let is_author = match (&comment.author_username, comment.is_guest) {
-        (Some(username), _) => !comment.is_admin && *username == claims.sub,
-        (None, Some(true)) => false,
-        (None, None) => !comment.is_admin && comment.author == claims.sub,
-        (None, Some(false)) => false,
-    };
+    let is_author = is_comment_author(&pool, &comment, &claims).await?;
 
     if !is_admin && !is_author {
         return Err(forbidden("Insufficient permissions"));
     }
 
-    let deleted = repositories::comments::delete_comment(&pool, &id)
+    let deleted = repositories::comments::delete_comment(&pool, &id, &claims.sub)
         .await
         .map_err(internal_error("Failed to delete comment"))?;
 
@@ -362,18 +743,29 @@ pub async fn delete_comment(
         return Err(not_found("Comment not found"));
     }
 
+    repositories::audit::append_entry(
+        &pool,
+        &claims.sub,
+        "delete",
+        "comment",
+        Some(&id),
+        serde_json::json!({ "author": comment.author, "moderated": !is_author }),
+    )
+    .await;
+
     Ok(StatusCode::NO_CONTENT)
 }
 
 /// Handler for voting on a comment
 ///
-/// Authenticated users can upvote/downvote comments. Prevention logic ensures one vote per user.
+/// Authenticated users can upvote comments, once each. A repeated vote is a
+/// 409 whose body carries the current total so the UI can reconcile.
 pub async fn vote_comment(
     State(pool): State<DbPool>,
     claims: auth::Claims,
     Path(id): Path<String>,
     _csrf: crate::security::csrf::CsrfGuard,
-) -> Result<Json<CommentResponse>, ApiError> {
+) -> Result<Response, AppError> {
     // Check if comment exists
     let exists = repositories::comments::check_comment_exists(&pool, &id)
         .await
@@ -392,34 +784,84 @@ pub async fn vote_comment(
         .map_err(internal_error("Failed to check votes"))?;
 
     if has_voted {
-        return Err(api_error(
-            StatusCode::CONFLICT,
-            "You have already voted on this comment",
-        ));
+        return vote_conflict(&pool, &id, "You have already voted on this comment").await;
     }
 
     // Record vote and increment votes
-    repositories::comments::add_vote(&pool, &id, &voter_id)
-        .await
-        .map_err(|e| {
-            if let sqlx::Error::Database(db_err) = &e {
-                if db_err.is_unique_violation() {
-                    return api_error(
-                        StatusCode::CONFLICT,
-                        "You have already voted on this comment",
-                    );
-                }
+    if let Err(e) = repositories::comments::add_vote(&pool, &id, &voter_id).await {
+        if let sqlx::Error::Database(db_err) = &e {
+            if db_err.is_unique_violation() {
+                return vote_conflict(&pool, &id, "You have already voted on this comment").await;
             }
-            internal_error("Failed to record vote")(e)
-        })?;
+        }
+        return Err(internal_error("Failed to record vote")(e));
+    }
 
-    // Return updated comment
-    let comment = repositories::comments::get_comment(&pool, &id)
+    voted_comment_response(&pool, &id, true).await
+}
+
+/// Handler for withdrawing a vote on a comment
+///
+/// Removes the caller's vote and lowers the total. Without a vote to remove
+/// it answers 409 with the current total, mirroring [`vote_comment`].
+pub async fn unvote_comment(
+    State(pool): State<DbPool>,
+    claims: auth::Claims,
+    Path(id): Path<String>,
+    _csrf: crate::security::csrf::CsrfGuard,
+) -> Result<Response, AppError> {
+    let exists = repositories::comments::check_comment_exists(&pool, &id)
+        .await
+        .map_err(internal_error("Failed to remove vote"))?;
+
+    if !exists {
+        return Err(not_found("Comment not found"));
+    }
+
+    let removed = repositories::comments::remove_vote(&pool, &id, &claims.sub)
+        .await
+        .map_err(internal_error("Failed to remove vote"))?;
+
+    if !removed {
+        return vote_conflict(&pool, &id, "You have not voted on this comment").await;
+    }
+
+    voted_comment_response(&pool, &id, false).await
+}
+
+/// The comment after a vote change, with the caller's new vote state.
+async fn voted_comment_response(
+    pool: &DbPool,
+    id: &str,
+    has_voted: bool,
+) -> Result<Response, AppError> {
+    let comment = repositories::comments::get_comment(pool, id)
         .await
         .map_err(internal_error("Failed to fetch updated comment"))?
         .ok_or_else(|| internal_error_plain("Comment disappeared after voting"))?;
 
-    Ok(Json(CommentResponse::from(comment)))
+    Ok(Json(CommentResponse {
+        has_voted: Some(has_voted),
+        ..CommentResponse::from(comment)
+    })
+    .into_response())
+}
+
+/// 409 for a vote change that doesn't apply, with the comment's current total.
+async fn vote_conflict(pool: &DbPool, id: &str, message: &str) -> Result<Response, AppError> {
+    let votes = repositories::comments::get_comment(pool, id)
+        .await
+        .map_err(internal_error("Failed to fetch comment"))?
+        .map_or(0, |comment| comment.votes);
+
+    Ok((
+        StatusCode::CONFLICT,
+        Json(VoteConflictResponse {
+            error: message.to_string(),
+            votes,
+        }),
+    )
+        .into_response())
 }
 
 #[cfg(test)]
@@ -1,4 +1,6 @@
 use super::*;
+use crate::repositories::comments::ListedComment;
+use std::collections::HashMap;
 
 /// Request payload for creating a comment
 #[derive(Deserialize)]
@@ -7,6 +9,25 @@ pub struct CreateCommentRequest {
     pub(super) content: String,
     /// The author's name (optional for guests)
     pub(super) author: Option<String>,
+    /// ID of the comment being replied to, on the same tutorial or post
+    #[serde(default)]
+    pub(super) parent_id: Option<String>,
+    /// Guest email, only used to derive `avatar_hash`; never stored
+    #[serde(default)]
+    pub(super) email: Option<String>,
+    /// Token from `GET /api/comments/token`; required for guests
+    #[serde(default)]
+    pub(super) form_token: Option<String>,
+    /// Honeypot: hidden from people by the form, so only bots fill it in
+    #[serde(default)]
+    pub(super) website: Option<String>,
+}
+
+/// Request payload for editing a comment
+#[derive(Deserialize)]
+pub struct UpdateCommentRequest {
+    /// The new comment text
+    pub(super) content: String,
 }
 
 /// Query parameters for listing comments with pagination and sorting
@@ -23,6 +44,18 @@ pub struct CommentListQuery {
     /// Sorting criteria (e.g., "created_at:desc")
     #[serde(default)]
     pub(super) sort: Option<String>,
+
+    /// Keyset cursor: ID of the last comment from the previous page. Takes
+    /// precedence over `offset`; an empty value requests the first page in
+    /// cursor mode.
+    #[serde(default)]
+    pub(super) after: Option<String>,
+
+    /// In offset mode, wrap the page in a [`Paginated`] envelope (`items`,
+    /// `total`, `limit`, `offset`, `hasMore`) instead of returning a bare
+    /// array.
+    #[serde(default)]
+    pub(super) envelope: bool,
 }
 
 pub(super) fn default_comment_limit() -> i64 {
@@ -38,12 +71,20 @@ pub struct CommentResponse {
     pub tutorial_id: Option<String>,
     /// Optional parent post ID
     pub post_id: Option<String>,
+    /// ID of the comment this one replies to; `null` for a top-level comment
+    pub parent_id: Option<String>,
     /// Display name of the author
     pub author: String,
-    /// The comment content as plain text
+    /// The comment content as the author wrote it (Markdown)
     pub content: String,
+    /// `content` rendered to sanitized HTML, see
+    /// [`crate::markdown::render_comment_html`]
+    #[sqlx(skip)]
+    pub content_html: String,
     /// RFC3339 formatted creation timestamp
     pub created_at: String,
+    /// RFC3339 timestamp of the last edit; `null` if never edited
+    pub edited_at: Option<String>,
     /// Total number of votes/likes
     pub votes: i64,
     /// Whether the comment was posted by an administrator
@@ -59,12 +100,75 @@ pub struct CommentResponse {
     /// only. Never sent to clients, for the same reason as `author_username`.
     #[serde(skip_serializing)]
     pub is_guest: Option<bool>,
+    /// Account ID of the commenter. Never sent to clients either.
+    #[serde(skip_serializing)]
+    pub user_id: Option<i64>,
+    /// SHA-256 Gravatar hash of the commenter's email; `null` without one
+    pub avatar_hash: Option<String>,
+    /// Whether the signed-in user has voted on this comment. Only set in
+    /// listings requested with a session, and in vote responses.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[sqlx(skip)]
+    pub has_voted: Option<bool>,
+    /// Number of direct replies. Only set in comment listings.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[sqlx(skip)]
+    pub reply_count: Option<usize>,
+    /// Direct replies, oldest first, each carrying its own replies. Only set
+    /// in comment listings.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[sqlx(skip)]
+    pub replies: Option<Vec<CommentResponse>>,
+}
+
+/// Body of the comment count endpoints.
+#[derive(Serialize)]
+pub struct CommentCountResponse {
+    /// Number of comments, replies included
+    pub count: i64,
+}
+
+/// Body of `GET /api/comments/token`.
+#[derive(Serialize)]
+pub struct CommentFormTokenResponse {
+    /// Send back as `form_token` with the comment
+    pub token: String,
+}
+
+/// Body of a 409 from the vote endpoints.
+#[derive(Serialize)]
+pub struct VoteConflictResponse {
+    /// Why the vote change didn't apply
+    pub error: String,
+    /// The comment's current vote total
+    pub votes: i64,
+}
+
+/// Body of the comment list endpoints.
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum CommentListResponse {
+    /// Offset mode: a bare array, as returned before cursors existed.
+    Offset(Vec<CommentResponse>),
+    /// Cursor mode (`?after=`).
+    Cursor(CommentCursorPage),
+    /// Offset mode with `?envelope=true`.
+    Page(Paginated<CommentResponse>),
+}
+
+/// One page of comments in cursor mode.
+#[derive(Serialize)]
+pub struct CommentCursorPage {
+    /// Comments on this page, in the requested sort order.
+    pub items: Vec<CommentResponse>,
+    /// Value for `?after=` to fetch the next page; `null` on the last page.
+    pub next_cursor: Option<String>,
 }
 
 /// Converts the repository's `Comment` model into this handler's response
 /// DTO. Kept as an explicit `From` impl (rather than returning the model
 /// type directly) because the two types intentionally diverge on
-/// serialization: this DTO marks `author_username`/`is_guest` as
+/// serialization: this DTO marks `author_username`/`is_guest`/`user_id` as
 /// `#[serde(skip_serializing)]` so they never reach the client, while the
 /// model type serializes them (it's also used for internal deserialization).
 impl From<crate::models::Comment> for CommentResponse {
@@ -73,21 +177,69 @@ impl From<crate::models::Comment> for CommentResponse {
             id: c.id,
             tutorial_id: c.tutorial_id,
             post_id: c.post_id,
+            parent_id: c.parent_id,
             author: c.author,
+            content_html: crate::markdown::render_comment_html(&c.content),
             content: c.content,
             created_at: c.created_at,
+            edited_at: c.edited_at,
             votes: c.votes,
             is_admin: c.is_admin,
             author_username: c.author_username,
             is_guest: c.is_guest,
+            user_id: c.user_id,
+            avatar_hash: c.avatar_hash,
+            has_voted: None,
+            reply_count: None,
+            replies: None,
+        }
+    }
+}
+
+/// Nests `replies` (oldest first, any depth) under the top-level comments of
+/// a page, keeping the page's own order. `has_voted` is reported only with
+/// `vote_state`, i.e. when the listing was requested by a signed-in user.
+pub(super) fn build_threads(
+    roots: Vec<ListedComment>,
+    replies: Vec<ListedComment>,
+    vote_state: bool,
+) -> Vec<CommentResponse> {
+    let mut children: HashMap<String, Vec<ListedComment>> = HashMap::new();
+    for reply in replies {
+        if let Some(parent_id) = reply.comment.parent_id.clone() {
+            children.entry(parent_id).or_default().push(reply);
+        }
+    }
+
+    fn thread(
+        row: ListedComment,
+        children: &mut HashMap<String, Vec<ListedComment>>,
+        vote_state: bool,
+    ) -> CommentResponse {
+        let replies: Vec<CommentResponse> = children
+            .remove(&row.comment.id)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|reply| thread(reply, children, vote_state))
+            .collect();
+        CommentResponse {
+            has_voted: vote_state.then_some(row.has_voted),
+            reply_count: Some(replies.len()),
+            replies: Some(replies),
+            ..CommentResponse::from(row.comment)
         }
     }
+
+    roots
+        .into_iter()
+        .map(|root| thread(root, &mut children, vote_state))
+        .collect()
 }
 
 /// Validates and sanitizes comment content
 ///
 /// Trims whitespace and checks length constraints.
-pub(super) fn sanitize_comment_content(raw: &str) -> Result<String, ApiError> {
+pub(super) fn sanitize_comment_content(raw: &str) -> Result<String, AppError> {
     let trimmed = raw.trim();
 
     if trimmed.is_empty() {
@@ -98,8 +250,8 @@ pub(super) fn sanitize_comment_content(raw: &str) -> Result<String, ApiError> {
         return Err(bad_request("Comment too long (max 1000 characters)"));
     }
 
-    // Content is stored as raw text; escaping happens at render time in the
-    // frontend (React). Escaping here as well would double-encode and hurt
-    // searchability and future flexibility (e.g. markdown support).
+    // Content is stored as the raw Markdown; it is escaped once, when the
+    // response renders `content_html`. Escaping here as well would
+    // double-encode it and hurt searchability.
     Ok(trimmed.to_string())
 }
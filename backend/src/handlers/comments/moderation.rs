@@ -0,0 +1,207 @@
+//! Site-wide comment moderation: one list over the comments on every
+//! tutorial and post, and bulk deletion.
+
+use super::*;
+use crate::handlers::{common::ensure_admin, search::escape_like_pattern};
+use crate::repositories::comments::{ModeratedComment, ModerationFilter};
+use chrono::NaiveDate;
+
+const DEFAULT_LIST_LIMIT: i64 = 50;
+const MAX_LIST_LIMIT: i64 = 200;
+/// Most comments one bulk delete may name.
+const MAX_BULK_DELETE: usize = 200;
+
+/// Query parameters for `GET /api/admin/comments`.
+#[derive(Debug, Default, Deserialize)]
+pub struct ModerationQuery {
+    /// Display name or account name of the author.
+    #[serde(default)]
+    pub author: Option<String>,
+    /// `tutorial` or `post`.
+    #[serde(default)]
+    pub parent_type: Option<String>,
+    /// First day to include, `YYYY-MM-DD` (UTC).
+    #[serde(default)]
+    pub from: Option<String>,
+    /// Last day to include, `YYYY-MM-DD` (UTC).
+    #[serde(default)]
+    pub to: Option<String>,
+    /// Text the content must contain.
+    #[serde(default)]
+    pub q: Option<String>,
+    /// `published` or `pending`.
+    #[serde(default)]
+    pub status: Option<String>,
+    #[serde(default)]
+    pub limit: Option<i64>,
+    #[serde(default)]
+    pub offset: Option<i64>,
+}
+
+/// One row of the moderation table.
+#[derive(Serialize)]
+pub struct ModeratedCommentResponse {
+    #[serde(flatten)]
+    pub comment: CommentResponse,
+    /// `tutorial` or `post`.
+    pub parent_type: &'static str,
+    /// `published`, or `pending` if the blocklist held it back.
+    pub status: String,
+    /// Title of the tutorial or post the comment is on.
+    pub parent_title: Option<String>,
+    /// Slug of the page a post comment's post belongs to.
+    pub page_slug: Option<String>,
+}
+
+impl From<ModeratedComment> for ModeratedCommentResponse {
+    fn from(row: ModeratedComment) -> Self {
+        let is_tutorial = row.comment.tutorial_id.is_some();
+        let status = row.comment.status.clone();
+        Self {
+            comment: CommentResponse::from(row.comment),
+            parent_type: if is_tutorial { "tutorial" } else { "post" },
+            status,
+            parent_title: if is_tutorial {
+                row.tutorial_title
+            } else {
+                row.post_title
+            },
+            page_slug: row.page_slug,
+        }
+    }
+}
+
+/// Body of `DELETE /api/admin/comments`.
+#[derive(Serialize)]
+pub struct BulkDeleteCommentsResponse {
+    /// IDs that were deleted, directly or with their thread.
+    pub deleted: Vec<String>,
+    /// IDs that matched no comment.
+    pub not_found: Vec<String>,
+}
+
+/// Handler for `GET /api/admin/comments`: comments on all tutorials and
+/// posts, replies included, newest first. Admin-only.
+pub async fn list_moderated_comments(
+    claims: auth::Claims,
+    State(pool): State<DbPool>,
+    Query(params): Query<ModerationQuery>,
+) -> Result<Json<Paginated<ModeratedCommentResponse>>, AppError> {
+    ensure_admin(&claims)?;
+
+    let non_empty = |value: &Option<String>| {
+        value
+            .as_deref()
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+    };
+    let parent_type = non_empty(&params.parent_type);
+    if let Some(parent_type) = parent_type.as_deref() {
+        if parent_type != "tutorial" && parent_type != "post" {
+            return Err(bad_request("'parent_type' must be 'tutorial' or 'post'"));
+        }
+    }
+    let from = parse_day(non_empty(&params.from), "from")?;
+    let to = parse_day(non_empty(&params.to), "to")?;
+    if let (Some(from), Some(to)) = (&from, &to) {
+        if from > to {
+            return Err(bad_request("'from' must not be after 'to'"));
+        }
+    }
+    let status = non_empty(&params.status);
+    if let Some(status) = status.as_deref() {
+        if status != COMMENT_STATUS_PUBLISHED && status != COMMENT_STATUS_PENDING {
+            return Err(bad_request("'status' must be 'published' or 'pending'"));
+        }
+    }
+    let author = non_empty(&params.author);
+    let content_pattern = non_empty(&params.q).map(|q| format!("%{}%", escape_like_pattern(&q)));
+
+    let filter = ModerationFilter {
+        author: author.as_deref(),
+        parent_type: parent_type.as_deref(),
+        from: from.as_deref(),
+        to: to.as_deref(),
+        content_pattern: content_pattern.as_deref(),
+        status: status.as_deref(),
+    };
+    let limit = params
+        .limit
+        .unwrap_or(DEFAULT_LIST_LIMIT)
+        .clamp(1, MAX_LIST_LIMIT);
+    let offset = params.offset.unwrap_or(0).max(0);
+
+    let rows = repositories::comments::list_moderated_comments(&pool, &filter, limit, offset)
+        .await
+        .map_err(internal_error("Failed to load comments"))?;
+    let total = repositories::comments::count_moderated_comments(&pool, &filter)
+        .await
+        .map_err(internal_error("Failed to load comments"))?;
+
+    let items = rows
+        .into_iter()
+        .map(ModeratedCommentResponse::from)
+        .collect();
+    Ok(Json(Paginated::new(items, total, limit, offset)))
+}
+
+/// Handler for `DELETE /api/admin/comments`: deletes the comments whose IDs
+/// the body lists, in one transaction. Admin-only.
+pub async fn delete_comments_bulk(
+    claims: auth::Claims,
+    State(pool): State<DbPool>,
+    Json(ids): Json<Vec<String>>,
+) -> Result<Json<BulkDeleteCommentsResponse>, AppError> {
+    ensure_admin(&claims)?;
+
+    if ids.is_empty() {
+        return Err(bad_request("List at least one comment to delete"));
+    }
+    if ids.len() > MAX_BULK_DELETE {
+        return Err(bad_request(format!(
+            "At most {MAX_BULK_DELETE} comments can be deleted at once"
+        )));
+    }
+    let mut unique = Vec::with_capacity(ids.len());
+    for id in ids {
+        if id.trim().is_empty() || id.len() > 100 {
+            return Err(bad_request("Invalid comment ID"));
+        }
+        if !unique.contains(&id) {
+            unique.push(id);
+        }
+    }
+
+    let deleted = repositories::comments::delete_comments(&pool, &unique, &claims.sub)
+        .await
+        .map_err(internal_error("Failed to delete comments"))?;
+    let not_found = unique
+        .into_iter()
+        .filter(|id| !deleted.contains(id))
+        .collect();
+
+    if !deleted.is_empty() {
+        repositories::audit::append_entry(
+            &pool,
+            &claims.sub,
+            "bulk_delete",
+            "comment",
+            None,
+            serde_json::json!({ "ids": deleted }),
+        )
+        .await;
+    }
+
+    Ok(Json(BulkDeleteCommentsResponse { deleted, not_found }))
+}
+
+/// Normalizes an optional `YYYY-MM-DD` query parameter.
+fn parse_day(value: Option<String>, name: &str) -> Result<Option<String>, AppError> {
+    let Some(value) = value else {
+        return Ok(None);
+    };
+    NaiveDate::parse_from_str(&value, "%Y-%m-%d")
+        .map(|day| Some(day.format("%Y-%m-%d").to_string()))
+        .map_err(|_| bad_request(format!("'{name}' must be a date (YYYY-MM-DD)")))
+}
@@ -1,4 +1,5 @@
 use super::*;
+use crate::security::csrf;
 use sqlx::SqlitePool;
 
 async fn setup_comments_pool() -> SqlitePool {
@@ -12,6 +13,7 @@ async fn setup_comments_pool() -> SqlitePool {
                 id TEXT PRIMARY KEY,
                 tutorial_id TEXT,
                 post_id TEXT,
+                parent_id TEXT REFERENCES comments(id) ON DELETE CASCADE,
                 author TEXT NOT NULL,
                 rate_limit_key TEXT NOT NULL DEFAULT '',
                 content TEXT NOT NULL,
@@ -19,7 +21,11 @@ async fn setup_comments_pool() -> SqlitePool {
                 votes INTEGER NOT NULL DEFAULT 0,
                 is_admin BOOLEAN NOT NULL DEFAULT FALSE,
                 author_username TEXT DEFAULT NULL,
-                is_guest BOOLEAN DEFAULT NULL
+                is_guest BOOLEAN DEFAULT NULL,
+                user_id INTEGER DEFAULT NULL,
+                edited_at TEXT DEFAULT NULL,
+                avatar_hash TEXT DEFAULT NULL,
+                status TEXT NOT NULL DEFAULT 'published'
             )
             "#,
     )
@@ -27,45 +33,79 @@ async fn setup_comments_pool() -> SqlitePool {
     .await
     .expect("create comments table");
 
+    // Deletes snapshot the comment and its votes into the deletion log.
+    sqlx::query(include_str!(
+        "../../../migrations/20241119_create_comment_votes.sql"
+    ))
+    .execute(&pool)
+    .await
+    .expect("create comment_votes table");
+    sqlx::query(
+        r#"
+            CREATE TABLE deletion_log (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                entity_type TEXT NOT NULL,
+                entity_id TEXT NOT NULL,
+                snapshot_json TEXT NOT NULL,
+                deleted_by TEXT NOT NULL,
+                deleted_at TEXT NOT NULL DEFAULT (datetime('now'))
+            )
+            "#,
+    )
+    .execute(&pool)
+    .await
+    .expect("create deletion_log table");
+    sqlx::query(
+        "CREATE TABLE comment_blocklist (id INTEGER PRIMARY KEY, kind TEXT NOT NULL, \
+         value TEXT NOT NULL, created_by TEXT NOT NULL, created_at TEXT NOT NULL DEFAULT '')",
+    )
+    .execute(&pool)
+    .await
+    .expect("create comment_blocklist table");
+
     pool
 }
 
 /// Inserts a comment row directly with full control over every column,
 /// for exercising `delete_comment`'s ownership logic against specific
-/// (author, author_username, is_guest, is_admin) combinations.
-#[allow(clippy::too_many_arguments)]
+/// (author, user_id, is_guest, is_admin) combinations.
 async fn insert_comment_row(
     pool: &SqlitePool,
     id: &str,
     author: &str,
-    author_username: Option<&str>,
+    user_id: Option<i64>,
     is_guest: Option<bool>,
     is_admin: bool,
 ) {
     sqlx::query(concat!(
         "INSERT INTO comments ",
         "(id, tutorial_id, post_id, author, content, created_at, votes, is_admin, ",
-        "author_username, is_guest) ",
+        "is_guest, user_id) ",
         "VALUES (?, 'tutorial-1', NULL, ?, 'content', datetime('now'), 0, ?, ?, ?)"
     ))
     .bind(id)
     .bind(author)
     .bind(is_admin)
-    .bind(author_username)
     .bind(is_guest)
+    .bind(user_id)
     .execute(pool)
     .await
     .expect("insert comment row");
 }
 
+/// A guest form token old enough to pass the minimum form time.
+fn form_token() -> String {
+    let _ = csrf::init_csrf_secret(
+        "this_is_a_very_long_secret_key_for_testing_purposes_only_at_least_32_bytes",
+    );
+    comment_token::issue_comment_token_at(chrono::Utc::now() - chrono::Duration::seconds(30))
+        .expect("issue form token")
+}
+
 #[tokio::test]
 async fn admin_tutorial_comment_uses_claims_without_author_payload() {
     let pool = setup_comments_pool().await;
-    let claims = auth::Claims {
-        sub: "admin".to_string(),
-        role: "admin".to_string(),
-        exp: usize::MAX,
-    };
+    let claims = claims_for("admin", 1, "admin");
 
     let result = create_comment_internal(
         pool,
@@ -74,6 +114,10 @@ async fn admin_tutorial_comment_uses_claims_without_author_payload() {
         CreateCommentRequest {
             content: "Admin note".to_string(),
             author: None,
+            parent_id: None,
+            email: None,
+            form_token: None,
+            website: None,
         },
         Some(claims),
         "127.0.0.1".to_string(),
@@ -81,7 +125,7 @@ async fn admin_tutorial_comment_uses_claims_without_author_payload() {
     .await;
     let Json(comment) = match result {
         Ok(comment) => comment,
-        Err((status, _)) => panic!("admin comment failed with status {status}"),
+        Err(err) => panic!("admin comment failed with status {}", err.status()),
     };
 
     assert_eq!(comment.author, "Administrator");
@@ -90,6 +134,7 @@ async fn admin_tutorial_comment_uses_claims_without_author_payload() {
     // deletion is governed by the is_admin/role check, not this field).
     assert_eq!(comment.author_username, Some("admin".to_string()));
     assert_eq!(comment.is_guest, Some(false));
+    assert_eq!(comment.user_id, Some(1));
 }
 
 #[tokio::test]
@@ -103,6 +148,10 @@ async fn guest_rate_limit_uses_ip_even_when_author_changes() {
         CreateCommentRequest {
             content: "First comment".to_string(),
             author: Some("Alice".to_string()),
+            parent_id: None,
+            email: None,
+            form_token: Some(form_token()),
+            website: None,
         },
         None,
         "203.0.113.5".to_string(),
@@ -110,10 +159,11 @@ async fn guest_rate_limit_uses_ip_even_when_author_changes() {
     .await;
     let Json(first_comment) = match first_result {
         Ok(comment) => comment,
-        Err((status, _)) => panic!("first guest comment failed with status {status}"),
+        Err(err) => panic!("first guest comment failed with status {}", err.status()),
     };
     assert_eq!(first_comment.author_username, None);
     assert_eq!(first_comment.is_guest, Some(true));
+    assert_eq!(first_comment.user_id, None);
 
     let result = create_comment_internal(
         pool,
@@ -122,6 +172,10 @@ async fn guest_rate_limit_uses_ip_even_when_author_changes() {
         CreateCommentRequest {
             content: "Second comment".to_string(),
             author: Some("Bob".to_string()),
+            parent_id: None,
+            email: None,
+            form_token: Some(form_token()),
+            website: None,
         },
         None,
         "203.0.113.5".to_string(),
@@ -132,14 +186,13 @@ async fn guest_rate_limit_uses_ip_even_when_author_changes() {
         Err(err) => err,
     };
 
-    assert_eq!(err.0, StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(err.status(), StatusCode::TOO_MANY_REQUESTS);
 }
 
-fn claims_for(sub: &str, role: &str) -> auth::Claims {
+fn claims_for(sub: &str, uid: i64, role: &str) -> auth::Claims {
     auth::Claims {
-        sub: sub.to_string(),
-        role: role.to_string(),
-        exp: usize::MAX,
+        uid: Some(uid),
+        ..crate::test_support::claims_for(sub, role)
     }
 }
 
@@ -147,7 +200,7 @@ async fn call_delete_comment(
     pool: SqlitePool,
     id: &str,
     claims: auth::Claims,
-) -> Result<StatusCode, (StatusCode, Json<ErrorResponse>)> {
+) -> Result<StatusCode, AppError> {
     delete_comment(
         claims,
         State(pool),
@@ -158,109 +211,868 @@ async fn call_delete_comment(
 }
 
 #[tokio::test]
-async fn authenticated_user_can_delete_own_post_migration_comment() {
+async fn authenticated_user_can_delete_own_comment() {
     let pool = setup_comments_pool().await;
-    insert_comment_row(&pool, "c1", "bob", Some("bob"), Some(false), false).await;
+    insert_comment_row(&pool, "c1", "bob", Some(2), Some(false), false).await;
 
-    let result = call_delete_comment(pool, "c1", claims_for("bob", "user")).await;
+    let result = call_delete_comment(pool, "c1", claims_for("bob", 2, "user")).await;
 
     assert_eq!(result.unwrap(), StatusCode::NO_CONTENT);
 }
 
-/// Critical regression test: a guest can type any display name,
-/// including a real, registered user's username. Before the
-/// author_username/is_guest fix, `comment.author == claims.sub` would
-/// let that real user delete the guest's comment. This must now be
-/// rejected because the guest comment has no real identity attached.
+/// A guest can type any display name, including a real, registered
+/// user's username. The guest comment has no account attached, so that
+/// user must not be able to delete it.
 #[tokio::test]
 async fn authenticated_user_cannot_delete_guest_comment_with_spoofed_name() {
     let pool = setup_comments_pool().await;
     insert_comment_row(&pool, "c2", "bob", None, Some(true), false).await;
 
-    let result = call_delete_comment(pool, "c2", claims_for("bob", "user")).await;
+    let result = call_delete_comment(pool, "c2", claims_for("bob", 2, "user")).await;
 
-    let (status, _) = result.unwrap_err();
+    let status = result.unwrap_err().status();
     assert_eq!(status, StatusCode::FORBIDDEN);
 }
 
 #[tokio::test]
 async fn admin_can_delete_any_comment_regardless_of_authorship() {
     let pool = setup_comments_pool().await;
-    insert_comment_row(
-        &pool,
-        "c3",
-        "Administrator",
-        Some("realadminuser"),
-        Some(false),
-        true,
-    )
-    .await;
+    insert_comment_row(&pool, "c3", "Administrator", Some(7), Some(false), true).await;
 
-    let result = call_delete_comment(pool, "c3", claims_for("different-admin", "admin")).await;
+    let result = call_delete_comment(pool, "c3", claims_for("different-admin", 8, "admin")).await;
 
     assert_eq!(result.unwrap(), StatusCode::NO_CONTENT);
 }
 
 #[tokio::test]
-async fn non_admin_cannot_delete_admin_authored_comment_even_with_same_username() {
+async fn non_admin_cannot_delete_admin_authored_comment_even_as_same_account() {
     let pool = setup_comments_pool().await;
-    insert_comment_row(
-        &pool,
-        "c7",
-        "Administrator",
-        Some("alice"),
-        Some(false),
-        true,
-    )
-    .await;
+    insert_comment_row(&pool, "c7", "Administrator", Some(3), Some(false), true).await;
 
-    let result = call_delete_comment(pool, "c7", claims_for("alice", "user")).await;
+    let result = call_delete_comment(pool, "c7", claims_for("alice", 3, "user")).await;
 
-    let (status, _) = result.unwrap_err();
+    let status = result.unwrap_err().status();
     assert_eq!(status, StatusCode::FORBIDDEN);
 }
 
 #[tokio::test]
-async fn legacy_row_real_owner_can_still_self_delete() {
+async fn renamed_account_keeps_ownership() {
     let pool = setup_comments_pool().await;
-    // Simulates a genuinely pre-migration row: both new columns are NULL.
-    insert_comment_row(&pool, "c4", "carol", None, None, false).await;
+    insert_comment_row(&pool, "c4", "carol", Some(4), Some(false), false).await;
 
-    let result = call_delete_comment(pool, "c4", claims_for("carol", "user")).await;
+    let result = call_delete_comment(pool, "c4", claims_for("caroline", 4, "user")).await;
 
     assert_eq!(result.unwrap(), StatusCode::NO_CONTENT);
 }
 
-/// Documents the accepted, time-bounded residual risk: a pre-migration
-/// row (author_username and is_guest both NULL) that was actually
-/// posted by a guest who happened to type "carol" as their display name
-/// can still be deleted by the real user "carol" via the legacy
-/// fallback. This is intentional -- the alternative (blocking legacy
-/// self-service deletion entirely) was rejected as a worse regression --
-/// and applies only to rows that existed before this fix shipped.
+/// A new account that takes over a name must not inherit the comments of
+/// the account that used it before.
 #[tokio::test]
-async fn legacy_row_ambiguous_origin_is_a_known_accepted_gap() {
+async fn reused_username_does_not_grant_ownership() {
     let pool = setup_comments_pool().await;
-    insert_comment_row(&pool, "c5", "carol", None, None, false).await;
+    insert_comment_row(&pool, "c5", "carol", Some(4), Some(false), false).await;
 
-    let result = call_delete_comment(pool, "c5", claims_for("carol", "user")).await;
+    let result = call_delete_comment(pool, "c5", claims_for("carol", 9, "user")).await;
 
-    assert_eq!(result.unwrap(), StatusCode::NO_CONTENT);
+    let status = result.unwrap_err().status();
+    assert_eq!(status, StatusCode::FORBIDDEN);
 }
 
-/// Guards the fail-closed arm added for the (author_username=None,
-/// is_guest=Some(false)) state: no current insert path produces it (an
-/// authenticated comment always sets author_username), but nothing in
-/// the schema forbids it either. If a future bug or manual data edit
-/// ever produces this shape, ownership must be rejected rather than
-/// silently falling back to the spoofable `author == claims.sub` match.
+/// Rows without an owner (legacy rows that matched no account, or whose
+/// account was deleted) can only be removed by an admin.
 #[tokio::test]
-async fn inconsistent_row_with_no_username_but_marked_authenticated_is_rejected() {
+async fn comment_without_owner_is_rejected() {
     let pool = setup_comments_pool().await;
-    insert_comment_row(&pool, "c6", "carol", None, Some(false), false).await;
+    insert_comment_row(&pool, "c6", "carol", None, None, false).await;
 
-    let result = call_delete_comment(pool, "c6", claims_for("carol", "user")).await;
+    let result = call_delete_comment(pool, "c6", claims_for("carol", 4, "user")).await;
 
-    let (status, _) = result.unwrap_err();
+    let status = result.unwrap_err().status();
     assert_eq!(status, StatusCode::FORBIDDEN);
 }
+
+async fn insert_timed_comment(pool: &SqlitePool, id: &str, created_at: &str, votes: i64) {
+    sqlx::query(concat!(
+        "INSERT INTO comments (id, tutorial_id, author, content, created_at, votes) ",
+        "VALUES (?, 'tutorial-1', 'Reader', 'content', ?, ?)"
+    ))
+    .bind(id)
+    .bind(created_at)
+    .bind(votes)
+    .execute(pool)
+    .await
+    .expect("insert timed comment");
+}
+
+fn cursor_query(after: Option<&str>, sort: Option<&str>) -> CommentListQuery {
+    CommentListQuery {
+        limit: 2,
+        offset: 0,
+        sort: sort.map(str::to_string),
+        after: after.map(str::to_string),
+        envelope: false,
+    }
+}
+
+async fn cursor_page(
+    pool: &SqlitePool,
+    after: Option<&str>,
+    sort: Option<&str>,
+) -> (Vec<String>, Option<String>) {
+    let response = fetch_comment_page(
+        pool,
+        repositories::comments::CommentScope::Tutorial("tutorial-1"),
+        cursor_query(after, sort),
+        None,
+    )
+    .await
+    .expect("fetch comment page");
+
+    match response {
+        CommentListResponse::Cursor(page) => (
+            page.items.into_iter().map(|c| c.id).collect(),
+            page.next_cursor,
+        ),
+        _ => panic!("expected cursor envelope"),
+    }
+}
+
+#[tokio::test]
+async fn cursor_pagination_is_stable_when_comments_arrive_between_pages() {
+    let pool = setup_comments_pool().await;
+    // c2 and c3 share a timestamp so the id tiebreaker is exercised.
+    insert_timed_comment(&pool, "c1", "2024-01-01T00:00:01+00:00", 0).await;
+    insert_timed_comment(&pool, "c2", "2024-01-01T00:00:02+00:00", 0).await;
+    insert_timed_comment(&pool, "c3", "2024-01-01T00:00:02+00:00", 0).await;
+    insert_timed_comment(&pool, "c4", "2024-01-01T00:00:04+00:00", 0).await;
+
+    let (first, cursor) = cursor_page(&pool, Some(""), None).await;
+    assert_eq!(first, vec!["c4", "c3"]);
+    assert_eq!(cursor.as_deref(), Some("c3"));
+
+    // A new comment lands at the head of the list; offset paging would now
+    // repeat c3 on page two.
+    insert_timed_comment(&pool, "c5", "2024-01-01T00:00:05+00:00", 0).await;
+
+    let (second, cursor) = cursor_page(&pool, cursor.as_deref(), None).await;
+    assert_eq!(second, vec!["c2", "c1"]);
+    assert_eq!(cursor, None);
+}
+
+#[tokio::test]
+async fn cursor_pagination_follows_top_sort() {
+    let pool = setup_comments_pool().await;
+    insert_timed_comment(&pool, "low", "2024-01-01T00:00:03+00:00", 1).await;
+    insert_timed_comment(&pool, "high", "2024-01-01T00:00:01+00:00", 9).await;
+    insert_timed_comment(&pool, "mid-old", "2024-01-01T00:00:01+00:00", 5).await;
+    insert_timed_comment(&pool, "mid-new", "2024-01-01T00:00:02+00:00", 5).await;
+
+    let (first, cursor) = cursor_page(&pool, Some(""), Some("top")).await;
+    assert_eq!(first, vec!["high", "mid-new"]);
+
+    let (second, cursor) = cursor_page(&pool, cursor.as_deref(), Some("top")).await;
+    assert_eq!(second, vec!["mid-old", "low"]);
+    assert_eq!(cursor, None);
+}
+
+#[tokio::test]
+async fn cursor_from_another_thread_is_rejected() {
+    let pool = setup_comments_pool().await;
+    sqlx::query(
+        "INSERT INTO comments (id, post_id, author, content) VALUES ('foreign', 'post-1', 'A', 'B')",
+    )
+    .execute(&pool)
+    .await
+    .expect("insert post comment");
+
+    let err = fetch_comment_page(
+        &pool,
+        repositories::comments::CommentScope::Tutorial("tutorial-1"),
+        cursor_query(Some("foreign"), None),
+        None,
+    )
+    .await
+    .err()
+    .expect("foreign cursor must fail");
+    assert_eq!(err.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn offset_mode_still_returns_bare_array() {
+    let pool = setup_comments_pool().await;
+    insert_timed_comment(&pool, "c1", "2024-01-01T00:00:01+00:00", 0).await;
+
+    let response = fetch_comment_page(
+        &pool,
+        repositories::comments::CommentScope::Tutorial("tutorial-1"),
+        cursor_query(None, None),
+        None,
+    )
+    .await
+    .expect("fetch comment page");
+
+    let json = serde_json::to_value(&response).unwrap();
+    assert_eq!(json.as_array().map(Vec::len), Some(1));
+}
+
+#[tokio::test]
+async fn envelope_reports_total_and_whether_more_pages_follow() {
+    let pool = setup_comments_pool().await;
+    for (index, id) in ["c1", "c2", "c3", "c4"].into_iter().enumerate() {
+        let created_at = format!("2024-01-01T00:00:0{index}+00:00");
+        insert_timed_comment(&pool, id, &created_at, 0).await;
+    }
+
+    let page = |offset: i64| {
+        let pool = pool.clone();
+        async move {
+            let query = CommentListQuery {
+                limit: 2,
+                offset,
+                sort: None,
+                after: None,
+                envelope: true,
+            };
+            match fetch_comment_page(
+                &pool,
+                repositories::comments::CommentScope::Tutorial("tutorial-1"),
+                query,
+                None,
+            )
+            .await
+            .expect("fetch comment page")
+            {
+                CommentListResponse::Page(page) => (
+                    page.items.into_iter().map(|c| c.id).collect::<Vec<_>>(),
+                    page.total,
+                    page.has_more,
+                ),
+                _ => panic!("expected paginated envelope"),
+            }
+        }
+    };
+
+    assert_eq!(page(0).await, (vec!["c4".into(), "c3".into()], 4, true));
+    // The second page ends exactly at the last comment.
+    assert_eq!(page(2).await, (vec!["c2".into(), "c1".into()], 4, false));
+    assert_eq!(page(4).await, (Vec::<String>::new(), 4, false));
+}
+
+/// Posts a guest reply on `post-1`, each from its own IP so the per-IP
+/// comment throttle doesn't interfere.
+async fn guest_comment(
+    pool: &SqlitePool,
+    ip: &str,
+    parent_id: Option<&str>,
+) -> Result<CommentResponse, AppError> {
+    create_comment_internal(
+        pool.clone(),
+        None,
+        Some("post-1".to_string()),
+        CreateCommentRequest {
+            content: format!("From {ip}"),
+            author: Some("Guest".to_string()),
+            parent_id: parent_id.map(str::to_string),
+            email: None,
+            form_token: Some(form_token()),
+            website: None,
+        },
+        None,
+        ip.to_string(),
+    )
+    .await
+    .map(|Json(comment)| comment)
+}
+
+#[tokio::test]
+async fn replies_are_nested_under_their_thread() {
+    let pool = setup_comments_pool().await;
+    let root = guest_comment(&pool, "203.0.113.1", None).await.unwrap();
+    let reply = guest_comment(&pool, "203.0.113.2", Some(&root.id))
+        .await
+        .unwrap();
+    let nested = guest_comment(&pool, "203.0.113.3", Some(&reply.id))
+        .await
+        .unwrap();
+    assert_eq!(nested.parent_id.as_deref(), Some(reply.id.as_str()));
+
+    // A fourth level is one too many.
+    let Err(err) = guest_comment(&pool, "203.0.113.4", Some(&nested.id)).await else {
+        panic!("fourth-level reply was accepted");
+    };
+    assert_eq!(err.status(), StatusCode::BAD_REQUEST);
+
+    let response = fetch_comment_page(
+        &pool,
+        repositories::comments::CommentScope::Post("post-1"),
+        cursor_query(None, None),
+        None,
+    )
+    .await
+    .expect("fetch comment page");
+    let json = serde_json::to_value(&response).unwrap();
+    let threads = json.as_array().unwrap();
+    assert_eq!(threads.len(), 1);
+    assert_eq!(threads[0]["id"], root.id.as_str());
+    assert_eq!(threads[0]["reply_count"], 1);
+    let replies = threads[0]["replies"].as_array().unwrap();
+    assert_eq!(replies[0]["id"], reply.id.as_str());
+    assert_eq!(replies[0]["replies"][0]["id"], nested.id.as_str());
+    assert_eq!(replies[0]["replies"][0]["reply_count"], 0);
+}
+
+#[tokio::test]
+async fn markdown_is_stored_raw_and_rendered_once() {
+    let pool = setup_comments_pool().await;
+    let Json(comment) = create_comment_internal(
+        pool.clone(),
+        None,
+        Some("post-1".to_string()),
+        CreateCommentRequest {
+            content: "Try `ls <dir>` & [this](https://example.com)".to_string(),
+            author: Some("Guest".to_string()),
+            parent_id: None,
+            email: None,
+            form_token: Some(form_token()),
+            website: None,
+        },
+        None,
+        "203.0.113.20".to_string(),
+    )
+    .await
+    .unwrap_or_else(|_| panic!("create comment"));
+
+    assert_eq!(
+        comment.content,
+        "Try `ls <dir>` & [this](https://example.com)"
+    );
+    assert_eq!(
+        comment.content_html,
+        concat!(
+            "<p>Try <code>ls &lt;dir&gt;</code> &amp; ",
+            r#"<a href="https://example.com" rel="nofollow noopener noreferrer">this</a></p>"#,
+            "\n"
+        )
+    );
+}
+
+#[tokio::test]
+async fn guest_email_becomes_an_avatar_hash_and_is_never_returned() {
+    let pool = setup_comments_pool().await;
+    let request = |email: &str| CreateCommentRequest {
+        content: "Hello".to_string(),
+        author: Some("Guest".to_string()),
+        parent_id: None,
+        email: Some(email.to_string()),
+        form_token: Some(form_token()),
+        website: None,
+    };
+
+    let Err(err) = create_comment_internal(
+        pool.clone(),
+        None,
+        Some("post-1".to_string()),
+        request("not-an-email"),
+        None,
+        "203.0.113.30".to_string(),
+    )
+    .await
+    else {
+        panic!("invalid email was accepted");
+    };
+    assert_eq!(err.status(), StatusCode::BAD_REQUEST);
+
+    let Ok(Json(comment)) = create_comment_internal(
+        pool.clone(),
+        None,
+        Some("post-1".to_string()),
+        request("  Reader@Example.COM "),
+        None,
+        "203.0.113.31".to_string(),
+    )
+    .await
+    else {
+        panic!("create comment");
+    };
+    let expected = crate::security::sha256_hex(b"reader@example.com");
+    assert_eq!(comment.avatar_hash.as_deref(), Some(expected.as_str()));
+    let json = serde_json::to_string(&comment).unwrap();
+    assert!(!json.to_lowercase().contains("reader@example.com"));
+
+    let stored: Vec<String> = sqlx::query_scalar("SELECT content || author FROM comments")
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+    assert!(stored.iter().all(|row| !row.contains('@')));
+}
+
+#[tokio::test]
+async fn replies_must_stay_on_the_parents_post() {
+    let pool = setup_comments_pool().await;
+    insert_comment_row(&pool, "tutorial-comment", "Reader", None, Some(true), false).await;
+
+    for parent in ["tutorial-comment", "missing"] {
+        let Err(err) = guest_comment(&pool, "203.0.113.9", Some(parent)).await else {
+            panic!("reply to {parent} was accepted");
+        };
+        assert_eq!(err.status(), StatusCode::BAD_REQUEST, "{parent}");
+    }
+}
+
+async fn call_update_comment(
+    pool: SqlitePool,
+    id: &str,
+    claims: auth::Claims,
+) -> Result<CommentResponse, AppError> {
+    update_comment(
+        claims,
+        State(pool),
+        Path(id.to_string()),
+        crate::security::csrf::CsrfGuard,
+        Json(UpdateCommentRequest {
+            content: "  Fixed typo  ".to_string(),
+        }),
+    )
+    .await
+    .map(|Json(comment)| comment)
+}
+
+#[tokio::test]
+async fn author_can_edit_within_the_window() {
+    let pool = setup_comments_pool().await;
+    insert_comment_row(&pool, "e1", "bob", Some(2), Some(false), false).await;
+
+    let comment = call_update_comment(pool.clone(), "e1", claims_for("bob", 2, "user"))
+        .await
+        .unwrap_or_else(|err| panic!("edit failed with status {}", err.status()));
+
+    assert_eq!(comment.content, "Fixed typo");
+    assert!(comment.edited_at.is_some());
+    let Err(err) = call_update_comment(pool, "e1", claims_for("eve", 5, "user")).await else {
+        panic!("another user edited the comment");
+    };
+    assert_eq!(err.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn only_admins_can_edit_after_the_window() {
+    let pool = setup_comments_pool().await;
+    insert_comment_row(&pool, "e2", "bob", Some(2), Some(false), false).await;
+    sqlx::query("UPDATE comments SET created_at = datetime('now', '-1 day') WHERE id = 'e2'")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let Err(err) = call_update_comment(pool.clone(), "e2", claims_for("bob", 2, "user")).await
+    else {
+        panic!("edit after the window was accepted");
+    };
+    assert_eq!(err.status(), StatusCode::FORBIDDEN);
+
+    let comment = call_update_comment(pool, "e2", claims_for("admin", 1, "admin"))
+        .await
+        .unwrap_or_else(|err| panic!("admin edit failed with status {}", err.status()));
+    assert_eq!(comment.content, "Fixed typo");
+}
+
+#[tokio::test]
+async fn guest_comments_cannot_be_edited_by_name() {
+    let pool = setup_comments_pool().await;
+    insert_comment_row(&pool, "e3", "bob", None, Some(true), false).await;
+
+    let Err(err) = call_update_comment(pool, "e3", claims_for("bob", 2, "user")).await else {
+        panic!("guest comment was edited");
+    };
+    assert_eq!(err.status(), StatusCode::FORBIDDEN);
+}
+
+async fn response_json(response: axum::response::Response) -> (StatusCode, serde_json::Value) {
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&bytes).unwrap())
+}
+
+#[tokio::test]
+async fn votes_can_be_withdrawn_and_are_reported_per_user() {
+    let pool = setup_comments_pool().await;
+    insert_timed_comment(&pool, "v1", "2024-01-01T00:00:01+00:00", 0).await;
+    let vote = |voter: &'static str, withdraw: bool| {
+        let pool = pool.clone();
+        async move {
+            let claims = claims_for(voter, 2, "user");
+            let path = Path("v1".to_string());
+            let csrf = crate::security::csrf::CsrfGuard;
+            let response = if withdraw {
+                unvote_comment(State(pool), claims, path, csrf).await
+            } else {
+                vote_comment(State(pool), claims, path, csrf).await
+            };
+            match response {
+                Ok(response) => response_json(response).await,
+                Err(err) => panic!("vote change failed with status {}", err.status()),
+            }
+        }
+    };
+
+    let (status, body) = vote("bob", false).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        (body["votes"].as_i64(), body["has_voted"].as_bool()),
+        (Some(1), Some(true))
+    );
+
+    // Repeating a vote reports the current total.
+    let (status, body) = vote("bob", false).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["votes"], 1);
+
+    let listed = |voter: Option<&'static str>| {
+        let pool = pool.clone();
+        async move {
+            let response = fetch_comment_page(
+                &pool,
+                repositories::comments::CommentScope::Tutorial("tutorial-1"),
+                cursor_query(None, None),
+                voter,
+            )
+            .await
+            .expect("fetch comment page");
+            serde_json::to_value(&response).unwrap()[0]["has_voted"].clone()
+        }
+    };
+    assert_eq!(listed(Some("bob")).await, true);
+    assert_eq!(listed(Some("carol")).await, false);
+    assert!(listed(None).await.is_null());
+
+    let (status, body) = vote("bob", true).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        (body["votes"].as_i64(), body["has_voted"].as_bool()),
+        (Some(0), Some(false))
+    );
+
+    let (status, body) = vote("bob", true).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["votes"], 0);
+}
+
+#[tokio::test]
+async fn moderation_lists_comments_across_parents_and_deletes_in_bulk() {
+    let pool = crate::test_support::migrated_pool().await;
+    repositories::tutorials::create_tutorial(
+        &pool,
+        "mod-tutorial",
+        "Shell Basics",
+        "Description",
+        "content",
+        "Terminal",
+        "from-blue-500 to-indigo-600",
+        "[]",
+        &[],
+        TUTORIAL_STATUS_PUBLISHED,
+        true,
+        None,
+    )
+    .await
+    .expect("create tutorial");
+    sqlx::query(
+        "INSERT INTO site_pages (id, slug, title, is_published) VALUES ('pg', 'blog', 'Blog', 1)",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    sqlx::query(concat!(
+        "INSERT INTO site_posts (id, page_id, title, slug, content_markdown, is_published) ",
+        "VALUES ('post', 'pg', 'Release Notes', 'release-notes', 'x', 1)"
+    ))
+    .execute(&pool)
+    .await
+    .unwrap();
+    for (id, tutorial_id, post_id, parent_id, author, content, created_at) in [
+        (
+            "c1",
+            Some("mod-tutorial"),
+            None,
+            None,
+            "alice",
+            "great 100% read",
+            "2024-03-01T10:00:00+00:00",
+        ),
+        (
+            "c2",
+            None,
+            Some("post"),
+            None,
+            "bob",
+            "spam link",
+            "2024-03-02T10:00:00+00:00",
+        ),
+        (
+            "c3",
+            None,
+            Some("post"),
+            Some("c2"),
+            "Alice",
+            "more spam",
+            "2024-03-03T10:00:00+00:00",
+        ),
+    ] {
+        sqlx::query(concat!(
+            "INSERT INTO comments (id, tutorial_id, post_id, parent_id, author, content, created_at) ",
+            "VALUES (?, ?, ?, ?, ?, ?, ?)"
+        ))
+        .bind(id)
+        .bind(tutorial_id)
+        .bind(post_id)
+        .bind(parent_id)
+        .bind(author)
+        .bind(content)
+        .bind(created_at)
+        .execute(&pool)
+        .await
+        .expect("insert comment");
+    }
+
+    let admin = claims_for("root", 1, "admin");
+    let list = |query: moderation::ModerationQuery| {
+        let pool = pool.clone();
+        let admin = admin.clone();
+        async move {
+            let Ok(Json(page)) = list_moderated_comments(admin, State(pool), Query(query)).await
+            else {
+                panic!("moderation list failed");
+            };
+            serde_json::to_value(page).unwrap()
+        }
+    };
+
+    let all = list(moderation::ModerationQuery::default()).await;
+    assert_eq!(all["total"], 3);
+    let ids: Vec<_> = all["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["id"].as_str().unwrap())
+        .collect();
+    assert_eq!(ids, ["c3", "c2", "c1"]);
+    assert_eq!(all["items"][0]["parent_type"], "post");
+    assert_eq!(all["items"][0]["parent_title"], "Release Notes");
+    assert_eq!(all["items"][0]["page_slug"], "blog");
+    assert_eq!(all["items"][2]["parent_title"], "Shell Basics");
+
+    let filtered = list(moderation::ModerationQuery {
+        author: Some("ALICE".to_string()),
+        parent_type: Some("post".to_string()),
+        ..Default::default()
+    })
+    .await;
+    assert_eq!(filtered["total"], 1);
+    assert_eq!(filtered["items"][0]["id"], "c3");
+
+    // `%` in the search is literal, and the date range is inclusive.
+    let searched = list(moderation::ModerationQuery {
+        q: Some("100%".to_string()),
+        from: Some("2024-03-01".to_string()),
+        to: Some("2024-03-01".to_string()),
+        ..Default::default()
+    })
+    .await;
+    assert_eq!(searched["total"], 1);
+    assert_eq!(searched["items"][0]["id"], "c1");
+
+    let Err(err) = list_moderated_comments(
+        claims_for("bob", 2, "user"),
+        State(pool.clone()),
+        Query(moderation::ModerationQuery::default()),
+    )
+    .await
+    else {
+        panic!("moderation list is admin-only");
+    };
+    assert_eq!(err.status(), StatusCode::FORBIDDEN);
+
+    // Deleting c2 takes its reply c3 with it.
+    let Ok(Json(result)) = delete_comments_bulk(
+        admin.clone(),
+        State(pool.clone()),
+        Json(vec![
+            "c2".to_string(),
+            "c3".to_string(),
+            "missing".to_string(),
+        ]),
+    )
+    .await
+    else {
+        panic!("bulk delete failed");
+    };
+    assert_eq!(result.deleted, ["c2", "c3"]);
+    assert_eq!(result.not_found, ["missing"]);
+
+    let remaining = list(moderation::ModerationQuery::default()).await;
+    assert_eq!(remaining["total"], 1);
+    let logged: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM deletion_log WHERE entity_type = 'comment'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(logged, 1);
+}
+
+#[tokio::test]
+async fn blocklist_holds_back_words_and_refuses_ips_and_names() {
+    use crate::handlers::comment_blocklist::{create_blocklist_entry, delete_blocklist_entry};
+
+    let _ = crate::handlers::auth::init_login_attempt_salt(
+        "this_is_a_test_salt_for_login_attempts_at_least_32_chars",
+    );
+    let pool = crate::test_support::migrated_pool().await;
+    sqlx::query(
+        "INSERT INTO site_pages (id, slug, title, is_published) VALUES ('pg', 'blog', 'Blog', 1)",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    sqlx::query(concat!(
+        "INSERT INTO site_posts (id, page_id, title, slug, content_markdown, is_published) ",
+        "VALUES ('post-1', 'pg', 'Post', 'post', 'x', 1)"
+    ))
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let admin = claims_for("root", 1, "admin");
+    let mut ids = Vec::new();
+    for (kind, value) in [
+        ("word", "CheapPills"),
+        ("ip_hash", "198.51.100.77"),
+        ("author_name", "Spam Bot"),
+    ] {
+        let Ok((status, Json(entry))) = create_blocklist_entry(
+            admin.clone(),
+            State(pool.clone()),
+            Json(CreateBlocklistEntryRequest {
+                kind: kind.to_string(),
+                value: value.to_string(),
+            }),
+        )
+        .await
+        else {
+            panic!("create {kind} entry");
+        };
+        assert_eq!(status, StatusCode::CREATED);
+        ids.push(entry.id);
+    }
+
+    let submit = |author: &str, content: &str, ip: &str| {
+        create_comment_internal(
+            pool.clone(),
+            None,
+            Some("post-1".to_string()),
+            CreateCommentRequest {
+                content: content.to_string(),
+                author: Some(author.to_string()),
+                parent_id: None,
+                email: None,
+                form_token: Some(form_token()),
+                website: None,
+            },
+            None,
+            ip.to_string(),
+        )
+    };
+
+    // A blocked word is accepted as far as the author can tell...
+    let Ok(Json(held)) = submit("Reader", "buy cheappills now", "203.0.113.40").await else {
+        panic!("held comment was refused");
+    };
+    // ...but stays out of the public listing and counts.
+    let stored = repositories::comments::get_comment(&pool, &held.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored.status, COMMENT_STATUS_PENDING);
+    assert_eq!(
+        repositories::comments::count_comments_for_post(&pool, "post-1")
+            .await
+            .unwrap(),
+        0
+    );
+
+    for (author, ip) in [("Reader", "198.51.100.77"), ("spam bot", "203.0.113.41")] {
+        let Err(err) = submit(author, "hello", ip).await else {
+            panic!("submission by {author} from {ip} was accepted");
+        };
+        assert_eq!(err.status(), StatusCode::FORBIDDEN, "{author} {ip}");
+    }
+
+    // Removing the entries takes effect immediately.
+    for id in ids {
+        delete_blocklist_entry(admin.clone(), State(pool.clone()), Path(id))
+            .await
+            .expect("delete entry");
+    }
+    let Ok(Json(published)) = submit("Spam Bot", "cheappills", "198.51.100.77").await else {
+        panic!("comment was refused after the blocklist was cleared");
+    };
+    assert_eq!(
+        repositories::comments::get_comment(&pool, &published.id)
+            .await
+            .unwrap()
+            .unwrap()
+            .status,
+        COMMENT_STATUS_PUBLISHED
+    );
+}
+
+#[tokio::test]
+async fn guest_comments_need_a_settled_form_token_and_an_empty_honeypot() {
+    let pool = setup_comments_pool().await;
+    let request = |form_token: Option<String>, website: Option<&str>| CreateCommentRequest {
+        content: "Great post".to_string(),
+        author: Some("Guest".to_string()),
+        parent_id: None,
+        email: None,
+        form_token,
+        website: website.map(str::to_string),
+    };
+    let submit = |payload| {
+        create_comment_internal(
+            pool.clone(),
+            None,
+            Some("post-1".to_string()),
+            payload,
+            None,
+            "203.0.113.40".to_string(),
+        )
+    };
+
+    let Err(err) = submit(request(None, None)).await else {
+        panic!("a guest comment without a token must be refused");
+    };
+    assert_eq!(err.status(), StatusCode::BAD_REQUEST);
+
+    let fresh = comment_token::issue_comment_token().expect("issue form token");
+    let Err(err) = submit(request(Some(fresh), None)).await else {
+        panic!("a token sent back at once must be refused");
+    };
+    assert_eq!(err.status(), StatusCode::TOO_MANY_REQUESTS);
+
+    // The bot is told its comment went through, but nothing is stored.
+    let Ok(Json(discarded)) =
+        submit(request(Some(form_token()), Some("http://spam.example"))).await
+    else {
+        panic!("a filled-in honeypot must look like a success");
+    };
+    assert_eq!(discarded.content, "Great post");
+    let stored: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM comments")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(stored, 0);
+
+    let Ok(_) = submit(request(Some(form_token()), Some("  "))).await else {
+        panic!("a settled token with an empty honeypot must be accepted");
+    };
+}
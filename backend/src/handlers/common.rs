@@ -5,12 +5,16 @@
 //! stay consistent in how they authorize and how they translate database
 //! failures into HTTP responses.
 
-use crate::models::{api_error, bad_request, forbidden, internal_error_plain, not_found, ApiError};
-use crate::security::auth;
-use axum::http::StatusCode;
+use crate::models::{forbidden, internal_error, internal_error_plain, not_found, AppError};
+use crate::security::{auth, sha256_hex};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
 
-/// Ensures the current user has administrative privileges.
-pub fn ensure_admin(claims: &auth::Claims) -> Result<(), ApiError> {
+/// Ensures the current user has administrative privileges. Used for admin
+/// tooling that is not split into [`auth::Permission`]s.
+pub fn ensure_admin(claims: &auth::Claims) -> Result<(), AppError> {
     if claims.role != "admin" {
         Err(forbidden("Insufficient permissions"))
     } else {
@@ -18,39 +22,196 @@ pub fn ensure_admin(claims: &auth::Claims) -> Result<(), ApiError> {
     }
 }
 
+/// Ensures the current user's role grants `permission`.
+pub fn require_permission(
+    claims: &auth::Claims,
+    permission: auth::Permission,
+) -> Result<(), AppError> {
+    if claims.can(permission) {
+        Ok(())
+    } else {
+        Err(forbidden("Insufficient permissions"))
+    }
+}
+
+/// Representation of Markdown content in a public detail response.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContentFormat {
+    /// Markdown only, for the frontend to render.
+    #[default]
+    Markdown,
+    /// Markdown plus sanitized HTML in `content_html`.
+    Html,
+}
+
+/// `?format=` query parameter of the public tutorial and post endpoints.
+#[derive(Debug, Default, Deserialize)]
+pub struct ContentFormatQuery {
+    #[serde(default)]
+    pub format: ContentFormat,
+    /// Asks for the table of contents even of very long documents.
+    #[serde(default)]
+    pub toc: bool,
+}
+
+/// Table of contents of a detail response's Markdown, taken from `rendered`
+/// when the content was rendered anyway. Empty for documents above
+/// [`MAX_AUTO_TOC_BYTES`](crate::markdown::toc::MAX_AUTO_TOC_BYTES) unless
+/// the client asked with `?toc=true`.
+pub fn content_toc(
+    markdown: &str,
+    query: &ContentFormatQuery,
+    rendered: Option<&crate::markdown::render::RenderedDocument>,
+) -> Vec<crate::markdown::TocEntry> {
+    if !query.toc && markdown.len() > crate::markdown::toc::MAX_AUTO_TOC_BYTES {
+        return Vec::new();
+    }
+    match rendered {
+        Some(document) => document.toc.clone(),
+        None => crate::markdown::extract_toc(markdown),
+    }
+}
+
+/// Query parameters of `DELETE /api/pages/{id}` and `DELETE /api/posts/{id}`.
+#[derive(Debug, Deserialize)]
+pub struct DeleteQuery {
+    /// Remove the page or post for good instead of moving it to the trash.
+    #[serde(default)]
+    pub permanent: bool,
+}
+
+/// Query parameters of the update endpoints of pages and site content.
+#[derive(Debug, Default, Deserialize)]
+pub struct DryRunQuery {
+    /// Validate the payload and answer `204 No Content` without saving it.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
 /// Maps SQLx database errors to user-facing HTTP responses.
 ///
-/// - `RowNotFound` → 404 with the given context ("Site page not found").
-/// - `Protocol` → 400; the repository layer raises these for validation-style
-///   failures with messages written for end users (e.g. slug length).
-/// - Unique constraint violations → 409 Conflict.
-/// - Everything else → 500 with a generic message; the real error is logged
-///   but never sent to the client.
-pub fn map_sqlx_error(err: sqlx::Error, context: &str) -> ApiError {
-    match err {
-        sqlx::Error::RowNotFound => not_found(format!("{context} not found")),
-        sqlx::Error::Protocol(e) => bad_request(e.to_string()),
-        sqlx::Error::Database(db_err) => {
-            if db_err.is_unique_violation() {
-                api_error(
-                    StatusCode::CONFLICT,
-                    db_err
-                        .constraint()
-                        .map(|c| format!("Duplicate value violates unique constraint '{c}'"))
-                        .unwrap_or_else(|| {
-                            "Duplicate value violates unique constraint".to_string()
-                        }),
-                )
-            } else {
-                tracing::error!("{context}: database error: {db_err}");
-                internal_error_plain("Database error")
-            }
-        }
-        other => {
-            tracing::error!("{context}: unexpected database error: {other}");
+/// Classifies like `From<sqlx::Error> for AppError`, with `context` naming
+/// the missing row ("Site page not found") and prefixing the log line of an
+/// unexpected error. The real error is never sent to the client.
+pub fn map_sqlx_error(err: sqlx::Error, context: &str) -> AppError {
+    match AppError::from(err) {
+        AppError::NotFound(_) => not_found(format!("{context} not found")),
+        AppError::Database(err) => {
+            tracing::error!("{context}: database error: {err}");
             internal_error_plain("Database error")
         }
+        other => other,
+    }
+}
+
+/// Weak entity tag for a serialized response body. Weak because the bytes
+/// depend on serializer details; equal content always yields an equal tag.
+pub fn weak_etag(body: &[u8]) -> String {
+    format!("W/\"{}\"", &sha256_hex(body)[..32])
+}
+
+/// Whether the request's `If-None-Match` header matches `etag`, using the
+/// weak comparison RFC 9110 prescribes for conditional GETs.
+pub fn if_none_match(headers: &HeaderMap, etag: &str) -> bool {
+    let Some(value) = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+    else {
+        return false;
+    };
+
+    let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    let wanted = opaque(etag);
+    value
+        .split(',')
+        .any(|candidate| candidate.trim() == "*" || opaque(candidate) == wanted)
+}
+
+/// Parses a stored timestamp (SQLite's `datetime('now')` format, or RFC
+/// 3339) as UTC.
+pub(crate) fn parse_stored_timestamp(timestamp: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(timestamp, "%Y-%m-%d %H:%M:%S")
+        .map(|naive| naive.and_utc())
+        .or_else(|_| DateTime::parse_from_rfc3339(timestamp).map(|date| date.to_utc()))
+        .ok()
+}
+
+/// Whether the request's `If-Modified-Since` is at or after `last_modified`.
+/// Only consulted when the request has no `If-None-Match` (RFC 9110 13.1.3).
+fn not_modified_since(headers: &HeaderMap, last_modified: DateTime<Utc>) -> bool {
+    headers
+        .get(header::IF_MODIFIED_SINCE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| DateTime::parse_from_rfc2822(value.trim()).ok())
+        .is_some_and(|since| last_modified.timestamp() <= since.timestamp())
+}
+
+/// Serializes `value` as a JSON response tagged with a [`weak_etag`] and,
+/// when `updated_at` is given, a `Last-Modified` header. A request whose
+/// `If-None-Match` matches the tag, or that has none and an
+/// `If-Modified-Since` not older than `updated_at`, gets a bodiless 304.
+///
+/// Pass `updated_at` only when every change to the body bumps it; a list
+/// that can lose entries without any remaining entry changing should rely
+/// on the tag alone.
+pub fn conditional_json<T: Serialize>(
+    headers: &HeaderMap,
+    value: &T,
+    updated_at: Option<&str>,
+) -> Result<Response, AppError> {
+    let body = serde_json::to_vec(value).map_err(internal_error("Failed to encode response"))?;
+    let etag = weak_etag(&body);
+    let last_modified = updated_at.and_then(parse_stored_timestamp);
+
+    let not_modified = if headers.contains_key(header::IF_NONE_MATCH) {
+        if_none_match(headers, &etag)
+    } else {
+        last_modified.is_some_and(|date| not_modified_since(headers, date))
+    };
+
+    let mut validators = vec![(header::ETAG, etag)];
+    if let Some(date) = last_modified {
+        validators.push((
+            header::LAST_MODIFIED,
+            date.format("%a, %d %b %Y %H:%M:%S GMT").to_string(),
+        ));
+    }
+    let mut response = if not_modified {
+        StatusCode::NOT_MODIFIED.into_response()
+    } else {
+        ([(header::CONTENT_TYPE, "application/json")], body).into_response()
+    };
+    for (name, value) in validators {
+        if let Ok(value) = value.parse() {
+            response.headers_mut().insert(name, value);
+        }
+    }
+    Ok(response)
+}
+
+/// Public origin of the request, reconstructed from `Host` and the scheme
+/// forwarded by the reverse proxy (HTTPS unless it explicitly says `http`).
+/// Returns `None` for a missing or malformed host.
+pub fn request_origin(headers: &HeaderMap) -> Option<String> {
+    let host = headers.get(header::HOST)?.to_str().ok()?.trim();
+    let valid_host = !host.is_empty()
+        && host
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | ':' | '[' | ']'));
+    if !valid_host {
+        return None;
     }
+
+    let scheme = match headers
+        .get("x-forwarded-proto")
+        .and_then(|value| value.to_str().ok())
+    {
+        Some(proto) if proto.trim().eq_ignore_ascii_case("http") => "http",
+        _ => "https",
+    };
+
+    Some(format!("{scheme}://{host}"))
 }
 
 #[cfg(test)]
@@ -58,31 +219,103 @@ mod tests {
     use super::*;
 
     fn claims(role: &str) -> auth::Claims {
-        auth::Claims {
-            sub: "someone".to_string(),
-            role: role.to_string(),
-            exp: usize::MAX,
-        }
+        crate::test_support::claims_for("someone", role)
     }
 
     #[test]
     fn ensure_admin_accepts_admin_and_rejects_others() {
         assert!(ensure_admin(&claims("admin")).is_ok());
 
-        let (status, _) = ensure_admin(&claims("user")).unwrap_err();
+        let status = ensure_admin(&claims("user")).unwrap_err().status();
         assert_eq!(status, StatusCode::FORBIDDEN);
     }
 
+    #[test]
+    fn editors_may_write_content_but_not_manage_the_site() {
+        use auth::Permission::*;
+
+        for permission in [EditTutorials, EditPosts, UploadMedia, BypassMaintenance] {
+            assert!(require_permission(&claims("editor"), permission).is_ok());
+        }
+        for permission in [DeleteContent, ManagePages, ManageSiteContent, ManageUsers] {
+            let status = require_permission(&claims("editor"), permission)
+                .unwrap_err()
+                .status();
+            assert_eq!(status, StatusCode::FORBIDDEN);
+            assert!(require_permission(&claims("admin"), permission).is_ok());
+        }
+        assert!(require_permission(&claims("user"), EditTutorials).is_err());
+        assert!(ensure_admin(&claims("editor")).is_err());
+    }
+
     #[test]
     fn map_sqlx_error_never_leaks_unexpected_error_details() {
-        let (status, axum::Json(body)) = map_sqlx_error(sqlx::Error::PoolTimedOut, "Site page");
+        let (status, body) = map_sqlx_error(sqlx::Error::PoolTimedOut, "Site page").into_parts();
         assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
         assert_eq!(body.error, "Database error");
     }
 
+    #[test]
+    fn if_none_match_uses_weak_comparison() {
+        let etag = weak_etag(b"{}");
+        let mut headers = HeaderMap::new();
+        assert!(!if_none_match(&headers, &etag));
+
+        let strong = etag.trim_start_matches("W/").to_string();
+        headers.insert(
+            header::IF_NONE_MATCH,
+            format!("\"other\", {strong}").parse().unwrap(),
+        );
+        assert!(if_none_match(&headers, &etag));
+
+        headers.insert(header::IF_NONE_MATCH, "\"other\"".parse().unwrap());
+        assert!(!if_none_match(&headers, &etag));
+    }
+
+    #[test]
+    fn conditional_json_answers_revalidation_with_not_modified() {
+        let value = serde_json::json!({ "title": "Intro" });
+        let updated_at = Some("2024-03-01 12:00:00");
+        let fresh = conditional_json(&HeaderMap::new(), &value, updated_at).unwrap();
+        assert_eq!(fresh.status(), StatusCode::OK);
+        assert_eq!(
+            fresh.headers()[header::LAST_MODIFIED],
+            "Fri, 01 Mar 2024 12:00:00 GMT"
+        );
+        let etag = fresh.headers()[header::ETAG].clone();
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::IF_NONE_MATCH, etag);
+        let cached = conditional_json(&headers, &value, updated_at).unwrap();
+        assert_eq!(cached.status(), StatusCode::NOT_MODIFIED);
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::IF_MODIFIED_SINCE,
+            "Fri, 01 Mar 2024 12:00:00 GMT".parse().unwrap(),
+        );
+        let cached = conditional_json(&headers, &value, updated_at).unwrap();
+        assert_eq!(cached.status(), StatusCode::NOT_MODIFIED);
+        let no_date = conditional_json(&headers, &value, None).unwrap();
+        assert_eq!(no_date.status(), StatusCode::OK);
+
+        // A stale tag wins over a matching date
+        headers.insert(header::IF_NONE_MATCH, "W/\"stale\"".parse().unwrap());
+        let changed = conditional_json(&headers, &value, updated_at).unwrap();
+        assert_eq!(changed.status(), StatusCode::OK);
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::IF_MODIFIED_SINCE,
+            "Thu, 29 Feb 2024 12:00:00 GMT".parse().unwrap(),
+        );
+        let changed = conditional_json(&headers, &value, updated_at).unwrap();
+        assert_eq!(changed.status(), StatusCode::OK);
+    }
+
     #[test]
     fn map_sqlx_error_translates_row_not_found() {
-        let (status, axum::Json(body)) = map_sqlx_error(sqlx::Error::RowNotFound, "Site post");
+        let (status, body) = map_sqlx_error(sqlx::Error::RowNotFound, "Site post").into_parts();
         assert_eq!(status, StatusCode::NOT_FOUND);
         assert_eq!(body.error, "Site post not found");
     }
@@ -0,0 +1,244 @@
+//! Content Section Registry Handlers
+//!
+//! The site content API only serves and stores sections registered in
+//! `content_sections`, so a new frontend section ("testimonials", "faq")
+//! needs an admin to register it, not a backend release. Lookups go through
+//! a small in-process cache that every change through these endpoints
+//! invalidates.
+
+use crate::{
+    db::DbPool,
+    handlers::common::{ensure_admin, map_sqlx_error},
+    models::*,
+    repositories,
+    security::auth,
+};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use std::collections::HashSet;
+use std::sync::{Arc, RwLock};
+
+/// Longest section name that can be registered.
+const MAX_SECTION_NAME_LEN: usize = 50;
+
+static CACHE: RwLock<Option<Arc<HashSet<String>>>> = RwLock::new(None);
+
+/// Whether `name` is registered. A name missing from the cache is looked up
+/// again, so a section added elsewhere is picked up on first use.
+pub(crate) async fn is_registered(pool: &DbPool, name: &str) -> Result<bool, sqlx::Error> {
+    let cached = CACHE.read().ok().and_then(|cache| cache.clone());
+    if cached.is_some_and(|sections| sections.contains(name)) {
+        return Ok(true);
+    }
+
+    let sections = registered_sections(pool).await?;
+    Ok(sections.contains(name))
+}
+
+/// Every registered section name, refreshing the cache.
+pub(crate) async fn registered_sections(
+    pool: &DbPool,
+) -> Result<Arc<HashSet<String>>, sqlx::Error> {
+    let sections: Arc<HashSet<String>> = Arc::new(
+        repositories::content_sections::list_sections(pool)
+            .await?
+            .into_iter()
+            .map(|section| section.name)
+            .collect(),
+    );
+    if let Ok(mut cache) = CACHE.write() {
+        *cache = Some(sections.clone());
+    }
+    Ok(sections)
+}
+
+fn invalidate_cache() {
+    if let Ok(mut cache) = CACHE.write() {
+        *cache = None;
+    }
+}
+
+/// Section names are lowercase letters, digits and `_`, at most 50 long.
+pub(crate) fn validate_section_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.len() > MAX_SECTION_NAME_LEN {
+        return Err(format!(
+            "Section name must be between 1 and {MAX_SECTION_NAME_LEN} characters"
+        ));
+    }
+    if !name
+        .bytes()
+        .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'_')
+    {
+        return Err(
+            "Section name may only contain lowercase letters, digits and underscores".to_string(),
+        );
+    }
+    Ok(())
+}
+
+/// Handler for `GET /api/admin/content-sections`.
+/// Admin-only.
+pub async fn list_content_sections(
+    claims: auth::Claims,
+    State(pool): State<DbPool>,
+) -> Result<Json<ContentSectionListResponse>, AppError> {
+    ensure_admin(&claims)?;
+
+    let items = repositories::content_sections::list_sections(&pool)
+        .await
+        .map_err(|err| map_sqlx_error(err, "Content section"))?;
+    Ok(Json(ContentSectionListResponse { items }))
+}
+
+/// Handler for `POST /api/admin/content-sections`.
+/// Admin-only, protected by CSRF.
+pub async fn create_content_section(
+    claims: auth::Claims,
+    State(pool): State<DbPool>,
+    Json(payload): Json<CreateContentSectionRequest>,
+) -> Result<(StatusCode, Json<ContentSection>), AppError> {
+    ensure_admin(&claims)?;
+
+    let name = payload.name.trim();
+    validate_section_name(name).map_err(bad_request)?;
+
+    let section = repositories::content_sections::create_section(&pool, name)
+        .await
+        .map_err(|err| map_sqlx_error(err, "Content section"))?
+        .ok_or_else(|| conflict("Content section already exists"))?;
+    invalidate_cache();
+
+    tracing::info!(action = "create_content_section", user = %claims.sub, section = %section.name, "Admin registered content section");
+    Ok((StatusCode::CREATED, Json(section)))
+}
+
+/// Handler for `DELETE /api/admin/content-sections/{name}`. Removes the
+/// section's stored content and revisions too. Built-in sections are
+/// refused. Admin-only, protected by CSRF.
+pub async fn delete_content_section(
+    claims: auth::Claims,
+    State(pool): State<DbPool>,
+    Path(name): Path<String>,
+) -> Result<StatusCode, AppError> {
+    ensure_admin(&claims)?;
+
+    if BUILT_IN_CONTENT_SECTIONS.contains(&name.as_str()) {
+        return Err(conflict("Built-in content sections cannot be removed"));
+    }
+
+    let deleted = repositories::content_sections::delete_section(&pool, &name)
+        .await
+        .map_err(|err| map_sqlx_error(err, "Content section"))?;
+    invalidate_cache();
+    if !deleted {
+        return Err(not_found("Content section not found"));
+    }
+
+    tracing::info!(action = "delete_content_section", user = %claims.sub, section = %name, "Admin removed content section");
+    repositories::audit::append_entry(
+        &pool,
+        &claims.sub,
+        "delete",
+        "content_section",
+        Some(&name),
+        serde_json::json!({}),
+    )
+    .await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::site_content::{get_site_content, update_site_content};
+
+    #[test]
+    fn section_names_are_lowercase_slugs() {
+        for valid in ["faq", "testimonials", "tutorial_section", "block_2"] {
+            assert!(validate_section_name(valid).is_ok(), "{valid}");
+        }
+        for invalid in ["", "FAQ", "my-section", "über", "a b", "../etc"] {
+            assert!(validate_section_name(invalid).is_err(), "{invalid:?}");
+        }
+        assert!(validate_section_name(&"a".repeat(50)).is_ok());
+        assert!(validate_section_name(&"a".repeat(51)).is_err());
+    }
+
+    #[tokio::test]
+    async fn registered_sections_can_be_stored_until_removed() {
+        let pool = crate::test_support::migrated_pool().await;
+        let admin = crate::test_support::claims_for("root", "admin");
+        let create = |name: &str| {
+            create_content_section(
+                admin.clone(),
+                State(pool.clone()),
+                Json(CreateContentSectionRequest {
+                    name: name.to_string(),
+                }),
+            )
+        };
+        let update = |content: serde_json::Value| {
+            update_site_content(
+                admin.clone(),
+                State(pool.clone()),
+                Path("faq".to_string()),
+                axum::extract::Query(Default::default()),
+                Json(UpdateSiteContentRequest { content }),
+            )
+        };
+
+        let Json(listed) = list_content_sections(admin.clone(), State(pool.clone()))
+            .await
+            .unwrap();
+        assert_eq!(listed.items.len(), BUILT_IN_CONTENT_SECTIONS.len());
+        let status = update(serde_json::json!({})).await.unwrap_err().status();
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let (status, Json(section)) = create(" faq ").await.expect("register section");
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(section.name, "faq");
+        let status = create("faq").await.unwrap_err().status();
+        assert_eq!(status, StatusCode::CONFLICT);
+        let status = create("FAQ").await.unwrap_err().status();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        // Sections without a dedicated validator take any JSON object
+        let status = update(serde_json::json!(["not", "an", "object"]))
+            .await
+            .unwrap_err()
+            .status();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let _ = update(serde_json::json!({ "items": [{ "q": "Why?", "a": "Because." }] }))
+            .await
+            .expect("store section");
+        let Json(stored) = get_site_content(State(pool.clone()), Path("faq".to_string()))
+            .await
+            .expect("read section");
+        assert_eq!(stored.content["items"][0]["q"], "Why?");
+
+        let delete = |name: &str| {
+            delete_content_section(admin.clone(), State(pool.clone()), Path(name.into()))
+        };
+        let status = delete("header").await.unwrap_err().status();
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(delete("faq").await.unwrap(), StatusCode::NO_CONTENT);
+        let status = get_site_content(State(pool.clone()), Path("faq".to_string()))
+            .await
+            .unwrap_err()
+            .status();
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let status = delete("faq").await.unwrap_err().status();
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        // Registering it again starts from scratch
+        let _ = create("faq").await.expect("register section again");
+        let status = get_site_content(State(pool), Path("faq".to_string()))
+            .await
+            .unwrap_err()
+            .status();
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,223 @@
+//! Content Bundle Export and Import Handlers
+//!
+//! The API counterpart of the `export_content`/`import_content` binaries,
+//! for moving a few sections or pages between instances without shell
+//! access. Both sides share [`crate::content_transfer`], so a bundle from
+//! either can be fed to the other. Unlike the binaries, the import endpoint
+//! validates every item the way the regular write endpoints do before
+//! anything is written.
+
+use crate::{
+    content_transfer::{
+        self, ContentBundle, ExportFilter, ImportMode, ImportOutcome, TransferError,
+    },
+    db::DbPool,
+    handlers::{
+        common::{ensure_admin, map_sqlx_error},
+        content_sections,
+        site_content::{validate_content_size, validate_content_structure},
+        tutorials::{
+            sanitize_topics, validate_color, validate_icon, validate_tutorial_data,
+            validate_tutorial_id,
+        },
+    },
+    models::*,
+    repositories,
+    schemas::Schema,
+    security::auth,
+};
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use serde::Deserialize;
+
+/// Query parameters of `GET /api/admin/content/export`.
+#[derive(Debug, Deserialize)]
+pub struct ContentExportQuery {
+    /// Comma-separated section names; all sections when absent.
+    #[serde(default)]
+    sections: Option<String>,
+    /// Include site pages and their posts.
+    #[serde(default)]
+    include_pages: bool,
+    /// Include tutorials.
+    #[serde(default)]
+    include_tutorials: bool,
+}
+
+/// Query parameters of `POST /api/admin/content/import`.
+#[derive(Debug, Deserialize)]
+pub struct ContentImportQuery {
+    #[serde(default)]
+    mode: ImportMode,
+}
+
+/// Handler for `GET /api/admin/content/export`.
+/// Admin-only.
+pub async fn export_content(
+    claims: auth::Claims,
+    State(pool): State<DbPool>,
+    Query(params): Query<ContentExportQuery>,
+) -> Result<Json<ContentBundle>, AppError> {
+    ensure_admin(&claims)?;
+
+    let sections = params.sections.map(|list| {
+        list.split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+            .collect::<Vec<_>>()
+    });
+    for name in sections.iter().flatten() {
+        content_sections::validate_section_name(name).map_err(bad_request)?;
+    }
+
+    let filter = ExportFilter {
+        sections,
+        include_pages: params.include_pages,
+        include_tutorials: params.include_tutorials,
+    };
+    let bundle = content_transfer::export_bundle(&pool, &filter)
+        .await
+        .map_err(map_transfer_error)?;
+
+    Ok(Json(bundle))
+}
+
+/// Handler for `POST /api/admin/content/import`. The whole bundle is
+/// written in one transaction; `?mode=overwrite` replaces existing items,
+/// the default `skip_existing` leaves them alone. Admin-only, protected by
+/// CSRF.
+pub async fn import_content(
+    claims: auth::Claims,
+    State(pool): State<DbPool>,
+    Query(params): Query<ContentImportQuery>,
+    Json(bundle): Json<ContentBundle>,
+) -> Result<Json<ContentImportResponse>, AppError> {
+    ensure_admin(&claims)?;
+    validate_bundle(&pool, &bundle).await?;
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(internal_error("Failed to import content"))?;
+    let results = content_transfer::import_bundle(&mut tx, &bundle, params.mode)
+        .await
+        .map_err(map_transfer_error)?;
+    tx.commit()
+        .await
+        .map_err(internal_error("Failed to import content"))?;
+
+    for item in &bundle.site_content {
+        repositories::content::invalidate_site_content_cache(&item.section);
+    }
+    if !bundle.pages.is_empty() || !bundle.posts.is_empty() {
+        repositories::pages::invalidate_published_pages_cache();
+    }
+
+    let count = |outcome| results.iter().filter(|r| r.outcome == outcome).count();
+    let response = ContentImportResponse {
+        mode: params.mode,
+        created: count(ImportOutcome::Created),
+        overwritten: count(ImportOutcome::Overwritten),
+        skipped: count(ImportOutcome::Skipped),
+        results,
+    };
+
+    tracing::info!(
+        action = "import_content",
+        user = %claims.sub,
+        created = response.created,
+        overwritten = response.overwritten,
+        skipped = response.skipped,
+        "Admin imported content bundle"
+    );
+    repositories::audit::append_entry(
+        &pool,
+        &claims.sub,
+        "import",
+        "content",
+        None,
+        serde_json::json!({
+            "mode": params.mode,
+            "created": response.created,
+            "overwritten": response.overwritten,
+            "skipped": response.skipped,
+        }),
+    )
+    .await;
+
+    Ok(Json(response))
+}
+
+/// Checks every item like the regular write endpoints would, so an import
+/// can't store what the editor couldn't.
+async fn validate_bundle(pool: &DbPool, bundle: &ContentBundle) -> Result<(), AppError> {
+    for item in &bundle.site_content {
+        let registered = content_sections::is_registered(pool, &item.section)
+            .await
+            .map_err(internal_error("Failed to load content sections"))?;
+        if !registered {
+            return Err(bad_request(format!(
+                "Unknown content section '{}'",
+                item.section
+            )));
+        }
+        validate_content_size(&item.content)?;
+        validate_content_structure(&item.section, &item.content)?;
+    }
+
+    for page in &bundle.pages {
+        if page.id.trim().is_empty() || page.slug.trim().is_empty() {
+            return Err(bad_request("Pages need an id and a slug"));
+        }
+        for (value, schema, field) in [
+            (&page.hero, Schema::PageHero, "hero"),
+            (&page.layout, Schema::PageLayout, "layout"),
+        ] {
+            schema.validate(value).map_err(|violations| {
+                bad_request(format!(
+                    "Invalid {field} of page '{}': {}",
+                    page.slug,
+                    violations.join("; ")
+                ))
+            })?;
+        }
+    }
+
+    for post in &bundle.posts {
+        if post.id.trim().is_empty() || post.slug.trim().is_empty() {
+            return Err(bad_request("Posts need an id and a slug"));
+        }
+    }
+
+    for tutorial in &bundle.tutorials {
+        let invalid = |err: String| bad_request(format!("Tutorial '{}': {err}", tutorial.id));
+        validate_tutorial_id(&tutorial.id).map_err(invalid)?;
+        validate_tutorial_data(&tutorial.title, &tutorial.description, &tutorial.content)
+            .map_err(invalid)?;
+        validate_color(&tutorial.color).map_err(invalid)?;
+        sanitize_topics(&tutorial.topics).map_err(invalid)?;
+        validate_icon(pool, &tutorial.icon).await?;
+    }
+
+    Ok(())
+}
+
+/// A reference to a missing page is the bundle's fault, not the server's.
+fn map_transfer_error(err: TransferError) -> AppError {
+    match err {
+        TransferError::Database {
+            context,
+            source: sqlx::Error::Database(db_err),
+        } if db_err.is_foreign_key_violation() => bad_request(format!(
+            "{context}: it refers to a page that does not exist"
+        )),
+        TransferError::Database { context, source } => map_sqlx_error(source, &context),
+        TransferError::Json { context, source } => {
+            tracing::error!("{context}: {source}");
+            internal_error_plain("Stored content could not be read")
+        }
+    }
+}
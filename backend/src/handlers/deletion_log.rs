@@ -0,0 +1,737 @@
+//! Deletion Log Handlers
+//!
+//! Lists the snapshots that hard deletes leave behind and restores them.
+//! A restore re-creates the entity through the same validators and
+//! repository functions as the regular create handlers, so topic rows, the
+//! FTS index and content stats are rebuilt; original IDs and timestamps are
+//! kept. Changelog entries of the deleted content are not brought back.
+//!
+//! Cascaded children are restored best effort. A child that no longer
+//! validates, whose ID is taken, or a vote by a user that no longer exists
+//! is skipped and reported in `warnings` instead of failing the restore.
+//! The log entry itself is never consumed: restoring it again answers 409
+//! because the entity exists again.
+
+use crate::{
+    db::DbPool,
+    handlers::{
+        common::{ensure_admin, map_sqlx_error},
+        icons,
+        site_pages::sanitize_create_payload,
+        site_posts::{sanitize_slug, sanitize_tags, validate_post_fields},
+        tutorials::{
+            sanitize_topics, validate_color, validate_icon_name, validate_imported_comment,
+            validate_tutorial_data, validate_tutorial_id,
+        },
+    },
+    models::*,
+    repositories,
+    security::auth,
+};
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+const DEFAULT_LIST_LIMIT: i64 = 50;
+const MAX_LIST_LIMIT: i64 = 200;
+
+/// Query parameters for `GET /api/admin/deletion-log`.
+#[derive(Debug, Deserialize)]
+pub struct DeletionLogQuery {
+    #[serde(default)]
+    pub limit: Option<i64>,
+}
+
+/// Handler for `GET /api/admin/deletion-log`: newest entries first.
+/// Admin-only.
+pub async fn list_deletion_log(
+    claims: auth::Claims,
+    State(pool): State<DbPool>,
+    Query(params): Query<DeletionLogQuery>,
+) -> Result<Json<DeletionLogListResponse>, AppError> {
+    ensure_admin(&claims)?;
+
+    let limit = params
+        .limit
+        .unwrap_or(DEFAULT_LIST_LIMIT)
+        .clamp(1, MAX_LIST_LIMIT);
+    let records = repositories::deletion_log::list_entries(&pool, limit)
+        .await
+        .map_err(internal_error("Failed to load deletion log"))?;
+
+    let items = records
+        .into_iter()
+        .map(|record| {
+            let snapshot = serde_json::from_str::<DeletionSnapshot>(&record.snapshot_json)
+                .map_err(|err| {
+                    tracing::warn!("Unreadable deletion log entry {}: {}", record.id, err);
+                })
+                .ok();
+            DeletionLogItem {
+                id: record.id,
+                entity_type: record.entity_type,
+                entity_id: record.entity_id,
+                label: snapshot.as_ref().map(DeletionSnapshot::label),
+                children: snapshot.as_ref().map_or(0, DeletionSnapshot::child_count),
+                deleted_by: record.deleted_by,
+                deleted_at: record.deleted_at,
+            }
+        })
+        .collect();
+
+    Ok(Json(DeletionLogListResponse {
+        items,
+        retention_days: crate::config::get().deletion_log_retention_days,
+    }))
+}
+
+/// Handler for `POST /api/admin/deletion-log/{id}/restore`.
+/// Admin-only, protected by CSRF.
+///
+/// Answers 409 when the entity (or its ID, slug or parent) conflicts with
+/// the current data and 422 when the snapshot no longer passes validation.
+pub async fn restore_deletion(
+    claims: auth::Claims,
+    State(pool): State<DbPool>,
+    Path(id): Path<i64>,
+) -> Result<Json<DeletionRestoreResponse>, AppError> {
+    ensure_admin(&claims)?;
+
+    let record = repositories::deletion_log::get_entry(&pool, id)
+        .await
+        .map_err(internal_error("Failed to load deletion log"))?
+        .ok_or_else(|| not_found("Deletion log entry not found"))?;
+    let snapshot: DeletionSnapshot =
+        serde_json::from_str(&record.snapshot_json).map_err(unrestorable)?;
+
+    let mut report = RestoreReport::default();
+    match snapshot {
+        DeletionSnapshot::Tutorial {
+            tutorial,
+            sections,
+            comments,
+            votes,
+        } => restore_tutorial(&pool, tutorial, sections, comments, votes, &mut report).await?,
+        DeletionSnapshot::Page { page, posts } => {
+            restore_page(&pool, page, posts, &mut report).await?
+        }
+        DeletionSnapshot::Post { post } => {
+            let page_exists = repositories::pages::get_site_page_by_id(&pool, &post.page_id)
+                .await
+                .map_err(|err| map_sqlx_error(err, "Site page"))?
+                .is_some();
+            if !page_exists {
+                return Err(conflict(
+                    "The post's page no longer exists; restore the page first",
+                ));
+            }
+            ensure_absent(
+                repositories::posts::check_post_id_taken(&pool, &post.id).await,
+                "A post with this ID already exists",
+            )?;
+            restore_post(&pool, post).await?;
+        }
+        DeletionSnapshot::Comment {
+            comment,
+            replies,
+            votes,
+        } => restore_comment(&pool, comment, replies, votes, &mut report).await?,
+    }
+
+    tracing::info!(
+        action = "restore_deletion",
+        user = %claims.sub,
+        entry = id,
+        entity_type = %record.entity_type,
+        entity_id = %record.entity_id,
+        children = report.children,
+        warnings = report.warnings.len(),
+        "Admin restored deleted content"
+    );
+
+    Ok(Json(DeletionRestoreResponse {
+        entity_type: record.entity_type,
+        entity_id: record.entity_id,
+        children_restored: report.children,
+        warnings: report.warnings,
+    }))
+}
+
+#[derive(Default)]
+struct RestoreReport {
+    children: usize,
+    warnings: Vec<String>,
+}
+
+fn unrestorable(reason: impl std::fmt::Display) -> AppError {
+    api_error(
+        StatusCode::UNPROCESSABLE_ENTITY,
+        format!("Snapshot cannot be restored: {reason}"),
+    )
+}
+
+fn error_message(err: AppError) -> String {
+    err.message().to_string()
+}
+
+fn ensure_absent(exists: Result<bool, sqlx::Error>, message: &str) -> Result<(), AppError> {
+    match exists {
+        Ok(false) => Ok(()),
+        Ok(true) => Err(conflict(message)),
+        Err(err) => {
+            tracing::error!("Failed to check existing content: {}", err);
+            Err(internal_error_plain("Failed to restore content"))
+        }
+    }
+}
+
+async fn restore_tutorial(
+    pool: &DbPool,
+    tutorial: Tutorial,
+    sections: Vec<TutorialSection>,
+    comments: Vec<Comment>,
+    votes: Vec<CommentVote>,
+    report: &mut RestoreReport,
+) -> Result<(), AppError> {
+    let title = tutorial.title.trim();
+    let description = tutorial.description.trim();
+    let content = tutorial.content.trim();
+    validate_tutorial_id(&tutorial.id).map_err(unrestorable)?;
+    validate_tutorial_data(title, description, content).map_err(unrestorable)?;
+    validate_icon_name(&tutorial.icon).map_err(unrestorable)?;
+    if !icons::is_allowed(pool, &tutorial.icon)
+        .await
+        .map_err(internal_error("Failed to restore tutorial"))?
+    {
+        return Err(unrestorable(format!(
+            "icon '{}' is no longer allowed",
+            tutorial.icon
+        )));
+    }
+    validate_color(&tutorial.color).map_err(unrestorable)?;
+    let topics: Vec<String> = serde_json::from_str(&tutorial.topics).map_err(unrestorable)?;
+    let topics = sanitize_topics(&topics).map_err(unrestorable)?;
+    let topics_json =
+        serde_json::to_string(&topics).map_err(internal_error("Failed to restore tutorial"))?;
+
+    ensure_absent(
+        repositories::tutorials::check_tutorial_id_taken(pool, &tutorial.id).await,
+        "A tutorial with this ID already exists",
+    )?;
+
+    repositories::tutorials::create_tutorial(
+        pool,
+        &tutorial.id,
+        title,
+        description,
+        content,
+        &tutorial.icon,
+        &tutorial.color,
+        &topics_json,
+        &topics,
+        &tutorial.status,
+        tutorial.allow_comments,
+        tutorial.created_by.as_deref(),
+    )
+    .await
+    .map_err(|err| map_sqlx_error(err, "Tutorial"))?;
+    repositories::deletion_log::restore_timestamps(
+        pool,
+        "tutorial",
+        &tutorial.id,
+        &tutorial.created_at,
+        &tutorial.updated_at,
+    )
+    .await
+    .map_err(internal_error("Failed to restore tutorial"))?;
+
+    report.children += repositories::tutorial_sections::insert_sections(pool, &sections)
+        .await
+        .map_err(internal_error("Failed to restore tutorial"))?;
+    let (comments, votes) = restore_comments(pool, comments, votes, report).await?;
+    report.children += comments + votes;
+    Ok(())
+}
+
+async fn restore_page(
+    pool: &DbPool,
+    page: SitePage,
+    posts: Vec<SitePost>,
+    report: &mut RestoreReport,
+) -> Result<(), AppError> {
+    let taken = repositories::pages::check_page_id_taken(pool, &page.id)
+        .await
+        .map_err(|err| map_sqlx_error(err, "Site page"))?;
+    if taken {
+        return Err(conflict("A page with this ID already exists"));
+    }
+
+    let custom_headers: BTreeMap<String, String> =
+        serde_json::from_str(&page.custom_headers_json).map_err(unrestorable)?;
+    let payload = sanitize_create_payload(CreateSitePageRequest {
+        slug: page.slug,
+        title: page.title,
+        description: Some(page.description),
+        nav_label: page.nav_label,
+        show_in_nav: page.show_in_nav,
+        order_index: Some(page.order_index),
+        is_published: page.is_published,
+        hero: serde_json::from_str(&page.hero_json).map_err(unrestorable)?,
+        layout: serde_json::from_str(&page.layout_json).map_err(unrestorable)?,
+        meta_robots: page.meta_robots,
+        custom_headers,
+    })
+    .map_err(|err| unrestorable(error_message(err)))?;
+
+    repositories::pages::create_site_page_with_id(pool, &page.id, payload)
+        .await
+        .map_err(|err| map_sqlx_error(err, "Site page"))?;
+    repositories::deletion_log::restore_timestamps(
+        pool,
+        "page",
+        &page.id,
+        &page.created_at,
+        &page.updated_at,
+    )
+    .await
+    .map_err(internal_error("Failed to restore page"))?;
+
+    for post in posts {
+        let slug = post.slug.clone();
+        match restore_post(pool, post).await {
+            Ok(()) => report.children += 1,
+            Err(err) => report
+                .warnings
+                .push(format!("Post '{slug}' skipped: {}", error_message(err))),
+        }
+    }
+    Ok(())
+}
+
+/// Re-creates one post under its original ID. The parent page must exist.
+async fn restore_post(pool: &DbPool, post: SitePost) -> Result<(), AppError> {
+    let title = post.title.trim().to_string();
+    let slug = sanitize_slug(&post.slug);
+    let excerpt = post.excerpt.trim().to_string();
+    validate_post_fields(&title, &slug, Some(&excerpt), &post.content_markdown)
+        .map_err(|err| unrestorable(error_message(err)))?;
+    let tags = sanitize_tags(&post.tags).map_err(unrestorable)?;
+
+    repositories::posts::create_site_post_with_id(
+        pool,
+        &post.id,
+        &post.page_id,
+        CreateSitePostRequest {
+            title,
+            slug,
+            excerpt: Some(excerpt),
+            content_markdown: post.content_markdown,
+            is_published: post.is_published,
+            allow_comments: post.allow_comments,
+            published_at: post.published_at,
+            order_index: Some(post.order_index),
+            tags,
+            cover_image_url: post.cover_image_url,
+            meta_description: post.meta_description,
+            canonical_url: post.canonical_url,
+        },
+    )
+    .await
+    .map_err(|err| map_sqlx_error(err, "Site post"))?;
+    repositories::deletion_log::restore_timestamps(
+        pool,
+        "post",
+        &post.id,
+        &post.created_at,
+        &post.updated_at,
+    )
+    .await
+    .map_err(internal_error("Failed to restore post"))
+}
+
+async fn restore_comment(
+    pool: &DbPool,
+    comment: Comment,
+    replies: Vec<Comment>,
+    votes: Vec<CommentVote>,
+    report: &mut RestoreReport,
+) -> Result<(), AppError> {
+    validate_imported_comment(&comment_export(&comment)).map_err(unrestorable)?;
+
+    let parent_exists = match (&comment.tutorial_id, &comment.post_id) {
+        (Some(tutorial_id), _) => {
+            repositories::tutorials::check_tutorial_exists(pool, tutorial_id).await
+        }
+        (None, Some(post_id)) => repositories::posts::check_post_exists(pool, post_id).await,
+        (None, None) => return Err(unrestorable("comment has no tutorial or post")),
+    }
+    .map_err(internal_error("Failed to restore comment"))?;
+    if !parent_exists {
+        return Err(conflict(
+            "The comment's tutorial or post no longer exists; restore it first",
+        ));
+    }
+    ensure_absent(
+        repositories::comments::check_comment_exists(pool, &comment.id).await,
+        "A comment with this ID already exists",
+    )?;
+
+    // A reply to a comment that has been deleted since comes back top-level
+    let comments = std::iter::once(comment).chain(replies).collect();
+    let (restored, votes) = restore_comments(pool, comments, votes, report).await?;
+    if restored == 0 {
+        return Err(conflict("A comment with this ID already exists"));
+    }
+    report.children += restored - 1 + votes;
+    Ok(())
+}
+
+/// Re-inserts comments and their votes, skipping what no longer fits.
+/// Returns how many comments and votes were inserted.
+///
+/// A vote by a user that no longer exists is dropped and the comment's
+/// cached total lowered to match.
+async fn restore_comments(
+    pool: &DbPool,
+    comments: Vec<Comment>,
+    votes: Vec<CommentVote>,
+    report: &mut RestoreReport,
+) -> Result<(usize, usize), AppError> {
+    let mut voters: HashMap<String, bool> = HashMap::new();
+    let mut dropped: HashMap<String, i64> = HashMap::new();
+    let mut kept_votes = Vec::new();
+    for vote in votes {
+        let exists = match voters.get(&vote.voter_id) {
+            Some(exists) => *exists,
+            None => {
+                let exists = repositories::users::check_user_exists_by_name(pool, &vote.voter_id)
+                    .await
+                    .map_err(internal_error("Failed to restore votes"))?;
+                voters.insert(vote.voter_id.clone(), exists);
+                exists
+            }
+        };
+        if exists {
+            kept_votes.push(vote);
+        } else {
+            report.warnings.push(format!(
+                "Vote by '{}' on comment {} dropped: the user no longer exists",
+                vote.voter_id, vote.comment_id
+            ));
+            *dropped.entry(vote.comment_id).or_default() += 1;
+        }
+    }
+
+    let mut rows = Vec::new();
+    for mut comment in comments {
+        if let Err(reason) = validate_imported_comment(&comment_export(&comment)) {
+            report
+                .warnings
+                .push(format!("Comment {} skipped: {reason}", comment.id));
+            continue;
+        }
+        let taken = repositories::comments::check_comment_exists(pool, &comment.id)
+            .await
+            .map_err(internal_error("Failed to restore comments"))?;
+        if taken {
+            report.warnings.push(format!(
+                "Comment {} skipped: its ID is already in use",
+                comment.id
+            ));
+            continue;
+        }
+        comment.votes = (comment.votes - dropped.get(&comment.id).copied().unwrap_or(0)).max(0);
+        rows.push(comment);
+    }
+
+    let restored_ids: HashSet<&str> = rows.iter().map(|c| c.id.as_str()).collect();
+    kept_votes.retain(|vote| restored_ids.contains(vote.comment_id.as_str()));
+
+    let comments = repositories::comments::insert_imported_comments(pool, &rows)
+        .await
+        .map_err(internal_error("Failed to restore comments"))?;
+    let votes = repositories::deletion_log::insert_votes(pool, &kept_votes)
+        .await
+        .map_err(internal_error("Failed to restore votes"))?;
+    Ok((comments, votes))
+}
+
+/// View of a comment in the shape the import validator checks.
+fn comment_export(comment: &Comment) -> TutorialCommentExport {
+    TutorialCommentExport {
+        id: comment.id.clone(),
+        parent_id: comment.parent_id.clone(),
+        author: comment.author.clone(),
+        content: comment.content.clone(),
+        created_at: comment.created_at.clone(),
+        edited_at: comment.edited_at.clone(),
+        votes: comment.votes,
+        is_admin: comment.is_admin,
+        author_username: comment.author_username.clone(),
+        avatar_hash: comment.avatar_hash.clone(),
+        is_guest: comment.is_guest,
+        status: comment.status.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn admin() -> auth::Claims {
+        crate::test_support::claims_for("root", "admin")
+    }
+
+    async fn latest_entry(pool: &DbPool) -> DeletionLogItem {
+        let Json(list) = list_deletion_log(
+            admin(),
+            State(pool.clone()),
+            Query(DeletionLogQuery { limit: None }),
+        )
+        .await
+        .expect("list deletion log");
+        list.items.into_iter().next().expect("an entry")
+    }
+
+    #[tokio::test]
+    async fn page_with_posts_round_trips_through_the_log() {
+        let pool = crate::test_support::migrated_pool().await;
+        let page = repositories::pages::create_site_page(
+            &pool,
+            CreateSitePageRequest {
+                slug: "notes".to_string(),
+                title: "Notes".to_string(),
+                description: None,
+                nav_label: None,
+                show_in_nav: true,
+                order_index: Some(2),
+                is_published: true,
+                hero: json!({ "title": "Notes" }),
+                layout: json!({}),
+                meta_robots: None,
+                custom_headers: BTreeMap::new(),
+            },
+        )
+        .await
+        .unwrap();
+        for slug in ["first", "second"] {
+            repositories::posts::create_site_post(
+                &pool,
+                &page.id,
+                CreateSitePostRequest {
+                    title: slug.to_string(),
+                    slug: slug.to_string(),
+                    excerpt: None,
+                    content_markdown: "Body".to_string(),
+                    is_published: true,
+                    allow_comments: true,
+                    published_at: None,
+                    order_index: None,
+                    tags: vec![format!("{slug}-tag"), "notes".to_string()],
+                    cover_image_url: None,
+                    meta_description: None,
+                    canonical_url: None,
+                },
+            )
+            .await
+            .unwrap();
+        }
+        let posts_before = repositories::posts::list_site_posts_for_page(&pool, &page.id)
+            .await
+            .unwrap();
+
+        repositories::pages::delete_site_page(&pool, &page.id, "root")
+            .await
+            .unwrap();
+        let entry = latest_entry(&pool).await;
+        assert_eq!(entry.entity_type, "page");
+        assert_eq!(entry.entity_id, page.id);
+        assert_eq!(entry.children, 2);
+        assert_eq!(entry.deleted_by, "root");
+
+        let Json(restored) = restore_deletion(admin(), State(pool.clone()), Path(entry.id))
+            .await
+            .expect("restore page");
+        assert_eq!(restored.children_restored, 2);
+        assert!(restored.warnings.is_empty(), "{:?}", restored.warnings);
+
+        let page_after = repositories::pages::get_site_page_by_id(&pool, &page.id)
+            .await
+            .unwrap()
+            .expect("page is back");
+        assert_eq!(page_after.slug, "notes");
+        assert_eq!(page_after.created_at, page.created_at);
+        let posts_after = repositories::posts::list_site_posts_for_page(&pool, &page.id)
+            .await
+            .unwrap();
+        // Both posts share a timestamp, so compare them independent of order.
+        let keys = |posts: &[SitePost]| {
+            let mut keys = posts
+                .iter()
+                .map(|p| (p.id.clone(), p.created_at.clone(), p.tags.clone()))
+                .collect::<Vec<_>>();
+            keys.sort();
+            keys
+        };
+        assert_eq!(keys(&posts_after), keys(&posts_before));
+
+        // The entry is not consumed, but the page exists again.
+        let status = restore_deletion(admin(), State(pool.clone()), Path(entry.id))
+            .await
+            .expect_err("second restore")
+            .status();
+        assert_eq!(status, StatusCode::CONFLICT);
+    }
+
+    #[tokio::test]
+    async fn tutorial_restore_rebuilds_search_and_reports_lost_votes() {
+        let pool = crate::test_support::migrated_pool().await;
+        let topics = vec!["Linux".to_string()];
+        repositories::tutorials::create_tutorial(
+            &pool,
+            "restorable",
+            "Restorable tutorial",
+            "Comes back",
+            "Unmistakable zebracorn content",
+            "Terminal",
+            "from-blue-500 to-indigo-600",
+            &serde_json::to_string(&topics).unwrap(),
+            &topics,
+            TUTORIAL_STATUS_PUBLISHED,
+            true,
+            None,
+        )
+        .await
+        .unwrap();
+        let comment = Comment {
+            id: "restorable-comment".to_string(),
+            tutorial_id: Some("restorable".to_string()),
+            post_id: None,
+            parent_id: None,
+            author: "Reader".to_string(),
+            content: "Nice".to_string(),
+            created_at: "2024-01-01T00:00:00+00:00".to_string(),
+            edited_at: None,
+            votes: 0,
+            is_admin: false,
+            author_username: None,
+            is_guest: Some(true),
+            user_id: None,
+            avatar_hash: None,
+            status: COMMENT_STATUS_PUBLISHED.to_string(),
+        };
+        repositories::comments::insert_imported_comments(&pool, &[comment])
+            .await
+            .unwrap();
+        for voter in ["stays", "leaves"] {
+            repositories::users::create_user(&pool, voter, "not-a-real-hash", "user")
+                .await
+                .unwrap();
+            repositories::comments::add_vote(&pool, "restorable-comment", voter)
+                .await
+                .unwrap();
+        }
+
+        repositories::tutorials::delete_tutorial(&pool, "restorable", "root")
+            .await
+            .unwrap();
+        let leaves = repositories::users::get_user_by_username(&pool, "leaves")
+            .await
+            .unwrap()
+            .unwrap();
+        repositories::users::delete_user(&pool, leaves.id)
+            .await
+            .unwrap();
+
+        let entry = latest_entry(&pool).await;
+        assert_eq!(entry.children, 3);
+        let Json(restored) = restore_deletion(admin(), State(pool.clone()), Path(entry.id))
+            .await
+            .expect("restore tutorial");
+        assert_eq!(restored.children_restored, 2);
+        assert_eq!(restored.warnings.len(), 1);
+        assert!(restored.warnings[0].contains("'leaves'"));
+
+        let comment = repositories::comments::get_comment(&pool, "restorable-comment")
+            .await
+            .unwrap()
+            .expect("comment is back");
+        assert_eq!(comment.votes, 1);
+        let hits: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM tutorials_fts WHERE tutorials_fts MATCH 'zebracorn'",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(hits, 1);
+        let topic_rows: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM tutorial_topics WHERE tutorial_id = ?")
+                .bind("restorable")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(topic_rows, 1);
+    }
+
+    #[tokio::test]
+    async fn entries_are_immutable_and_purged_after_retention() {
+        let pool = crate::test_support::migrated_pool().await;
+        let comment = Comment {
+            id: "orphan".to_string(),
+            tutorial_id: None,
+            post_id: Some("gone-post".to_string()),
+            parent_id: None,
+            author: "Guest".to_string(),
+            content: "Hello".to_string(),
+            created_at: "2024-01-01T00:00:00+00:00".to_string(),
+            edited_at: None,
+            votes: 0,
+            is_admin: false,
+            author_username: None,
+            is_guest: Some(true),
+            user_id: None,
+            avatar_hash: None,
+            status: COMMENT_STATUS_PUBLISHED.to_string(),
+        };
+        repositories::comments::insert_imported_comments(&pool, &[comment])
+            .await
+            .unwrap();
+        assert!(
+            repositories::comments::delete_comment(&pool, "orphan", "root")
+                .await
+                .unwrap()
+        );
+
+        let entry = latest_entry(&pool).await;
+        let status = restore_deletion(admin(), State(pool.clone()), Path(entry.id))
+            .await
+            .expect_err("parent post is gone")
+            .status();
+        assert_eq!(status, StatusCode::CONFLICT);
+
+        assert!(
+            sqlx::query("UPDATE deletion_log SET deleted_by = 'someone else'")
+                .execute(&pool)
+                .await
+                .is_err()
+        );
+
+        assert_eq!(
+            repositories::deletion_log::purge_older_than(&pool, 30)
+                .await
+                .unwrap(),
+            0
+        );
+        assert_eq!(
+            repositories::deletion_log::purge_older_than(&pool, 0)
+                .await
+                .unwrap(),
+            1
+        );
+    }
+}
@@ -5,23 +5,27 @@
 //! and dynamically injects SEO metadata (title, description) from the database
 //! into the HTML response. This ensures search engines and social media crawlers
 //! see relevant page information even for a Single Page Application (SPA).
+//!
+//! Routes that belong to a site page additionally carry that page's robots
+//! directive (as a meta tag) and its allowlisted custom response headers.
 
 use crate::db;
+use crate::models::{
+    is_starter_site_title, SitePage, DEFAULT_SITE_DESCRIPTION, DEFAULT_SITE_TITLE,
+    PAGE_CUSTOM_HEADER_ALLOWLIST,
+};
 use axum::{
     extract::State,
-    http::StatusCode,
+    http::{HeaderMap, HeaderName, HeaderValue, StatusCode, Uri},
     response::{Html, IntoResponse},
 };
 use regex::{NoExpand, Regex};
 use reqwest::Client;
 use std::borrow::Cow;
-use std::env;
+use std::collections::BTreeMap;
 use std::sync::LazyLock;
 use std::time::Duration;
 
-/// Internal URL for the frontend service in the container network
-const DEFAULT_FRONTEND_URL: &str = "http://frontend";
-
 /// Upper bound on how long we wait for the frontend service to respond.
 /// Without this, a hung frontend upstream ties up backend request handlers
 /// indefinitely on every page load (reqwest has no timeout by default).
@@ -58,6 +62,64 @@ static OG_DESC_REGEX: LazyLock<Regex> = LazyLock::new(|| {
         .expect("valid og:description regex")
 });
 
+/// Matches <meta name="robots" content="...">.
+static ROBOTS_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?i)<meta\s+name=["']robots["']\s+content=["'].*?["']\s*/?>"#)
+        .expect("valid robots regex")
+});
+
+/// Extracts the site page slug from a frontend route, if the route belongs
+/// to a page: `/pages/{slug}`, `/pages/{slug}/posts/{post}` and
+/// `/posts/{slug}/{post}`.
+fn page_slug_for_path(path: &str) -> Option<String> {
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    let slug = match segments.as_slice() {
+        ["pages", slug] | ["pages", slug, "posts", _] | ["posts", slug, _] => *slug,
+        _ => return None,
+    };
+    let slug = slug.trim().to_lowercase();
+    (!slug.is_empty()).then_some(slug)
+}
+
+/// Looks up the published page a route belongs to. Lookup failures only cost
+/// the page-specific extras, so they are logged rather than surfaced.
+async fn page_for_path(pool: &db::DbPool, path: &str) -> Option<SitePage> {
+    let slug = page_slug_for_path(path)?;
+    match crate::repositories::pages::list_published_pages_cached(pool).await {
+        Ok(pages) => pages.iter().find(|page| page.slug == slug).cloned(),
+        Err(err) => {
+            tracing::warn!("Failed to load pages for route metadata: {}", err);
+            None
+        }
+    }
+}
+
+/// Converts a page's stored header map into response headers. Names are
+/// re-checked against the allowlist so a hand-edited row cannot set
+/// arbitrary headers.
+fn page_response_headers(page: &SitePage) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    let stored: BTreeMap<String, String> =
+        serde_json::from_str(&page.custom_headers_json).unwrap_or_default();
+
+    for (name, value) in stored {
+        let allowed = PAGE_CUSTOM_HEADER_ALLOWLIST
+            .iter()
+            .any(|allowed| allowed.eq_ignore_ascii_case(&name));
+        if !allowed {
+            tracing::warn!(page = %page.slug, header = %name, "Skipping disallowed page header");
+            continue;
+        }
+        if let (Ok(name), Ok(value)) = (
+            HeaderName::from_bytes(name.as_bytes()),
+            HeaderValue::from_str(&value),
+        ) {
+            headers.append(name, value);
+        }
+    }
+    headers
+}
+
 /// Replaces the first regex match with `replacement`, treating the
 /// replacement as a literal string.
 ///
@@ -90,10 +152,10 @@ fn inject_or_warn(regex: &Regex, html: String, replacement: &str, target: &str)
 /// 2. Fetches global site metadata (site_meta section) from the database.
 /// 3. Performs string-based injection of <title> and <meta> tags.
 /// 4. Provides fallback defaults if database records are missing.
-pub async fn serve_index(State(pool): State<db::DbPool>) -> impl IntoResponse {
-    let frontend_url =
-        env::var("FRONTEND_URL").unwrap_or_else(|_| DEFAULT_FRONTEND_URL.to_string());
-    let index_url = format!("{}/index.html", frontend_url);
+/// 5. Applies the robots directive and custom headers of the page the route
+///    belongs to, if any.
+pub async fn serve_index(State(pool): State<db::DbPool>, uri: Uri) -> impl IntoResponse {
+    let index_url = format!("{}/index.html", crate::config::get().frontend_url);
 
     // Proxied Fetch: Retrieve the template from the frontend service.
     // Upstream failures must surface as 502, not 200: crawlers, caches, and
@@ -139,11 +201,10 @@ pub async fn serve_index(State(pool): State<db::DbPool>) -> impl IntoResponse {
     let stored_title = site_meta
         .get("title")
         .and_then(|v| v.as_str())
-        .unwrap_or("minos – Persönlicher Blog");
-    let is_starter_content =
-        stored_title.starts_with("Linux Tutorial") || stored_title.starts_with("IT Wissensportal");
+        .unwrap_or(DEFAULT_SITE_TITLE);
+    let is_starter_content = is_starter_site_title(stored_title);
     let title = if is_starter_content {
-        "minos – Persönlicher Blog"
+        DEFAULT_SITE_TITLE
     } else {
         stored_title
     };
@@ -152,9 +213,9 @@ pub async fn serve_index(State(pool): State<db::DbPool>) -> impl IntoResponse {
     let stored_description = site_meta
         .get("description")
         .and_then(|v| v.as_str())
-        .unwrap_or("Persönliche Notizen über Technik, Projekte, Ideen und alles dazwischen.");
+        .unwrap_or(DEFAULT_SITE_DESCRIPTION);
     let description = if is_starter_content {
-        "Persönliche Notizen über Technik, Projekte, Ideen und alles dazwischen."
+        DEFAULT_SITE_DESCRIPTION
     } else {
         stored_description
     };
@@ -215,7 +276,22 @@ pub async fn serve_index(State(pool): State<db::DbPool>) -> impl IntoResponse {
         "og:description",
     );
 
-    Html(injected_html).into_response()
+    // Page-specific robots directive and response headers
+    let page = page_for_path(&pool, uri.path()).await;
+    if let Some(robots) = page.as_ref().and_then(|page| page.meta_robots.as_deref()) {
+        injected_html = inject_or_warn(
+            &ROBOTS_REGEX,
+            injected_html,
+            &format!(
+                r#"<meta name="robots" content="{}" />"#,
+                html_escape::encode_double_quoted_attribute(robots)
+            ),
+            "meta robots",
+        );
+    }
+    let headers = page.as_ref().map(page_response_headers).unwrap_or_default();
+
+    (headers, Html(injected_html)).into_response()
 }
 
 #[cfg(test)]
@@ -265,6 +341,35 @@ mod tests {
         assert!(replaced.contains(r#"content="New OG description""#));
     }
 
+    #[test]
+    fn page_routes_resolve_to_their_page_slug() {
+        assert_eq!(page_slug_for_path("/pages/About"), Some("about".into()));
+        assert_eq!(
+            page_slug_for_path("/pages/blog/posts/hello"),
+            Some("blog".into())
+        );
+        assert_eq!(
+            page_slug_for_path("/posts/blog/hello/"),
+            Some("blog".into())
+        );
+        assert_eq!(page_slug_for_path("/"), None);
+        assert_eq!(page_slug_for_path("/tutorials/abc"), None);
+        assert_eq!(page_slug_for_path("/pages/blog/extra"), None);
+    }
+
+    #[test]
+    fn robots_meta_tag_is_replaced() {
+        let html = r#"<head><meta name="robots" content="index, follow" /></head>"#.to_string();
+        let replaced = inject_or_warn(
+            &ROBOTS_REGEX,
+            html,
+            r#"<meta name="robots" content="noindex,nofollow" />"#,
+            "meta robots",
+        );
+        assert!(replaced.contains(r#"content="noindex,nofollow""#));
+        assert!(!replaced.contains("index, follow"));
+    }
+
     /// Regression test: replacement strings are database-sourced, so `$`
     /// must be treated literally. Without `NoExpand`, a title like
     /// "Save 50% - only $0.99" would expand `$0` to the entire matched
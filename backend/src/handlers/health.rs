@@ -0,0 +1,156 @@
+//! Health and Readiness Probes
+//!
+//! `/api/health` is a bare liveness check. `/api/health/ready` checks the
+//! dependencies a request needs, the database and a writable upload
+//! directory, and answers 503 when one of them fails. It also reports the
+//! pool utilization and how long boot warmup took, so deploy tooling can
+//! watch for regressions in cold-start cost. Both are mounted outside the
+//! rate-limited and authenticated routers.
+
+use crate::{
+    db::DbPool,
+    models::{DependencyCheck, PoolUtilization, ReadinessChecks, ReadinessResponse},
+    warmup,
+};
+use axum::{extract::State, http::StatusCode, Json};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// How long the database may take to answer `SELECT 1`.
+const DATABASE_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Readiness probe. 200 when every check passed, 503 otherwise; the body
+/// breaks the checks down either way.
+pub async fn ready(State(pool): State<DbPool>) -> (StatusCode, Json<ReadinessResponse>) {
+    // Before the probe's own query takes a connection
+    let size = pool.size();
+    let idle = u32::try_from(pool.num_idle()).unwrap_or(u32::MAX).min(size);
+    let pool_utilization = PoolUtilization {
+        size,
+        idle,
+        in_use: size - idle,
+        max: pool.options().get_max_connections(),
+    };
+
+    let checks = ReadinessChecks {
+        database: check_database(&pool).await,
+        upload_dir: check_writable(&crate::config::get().upload_dir).await,
+    };
+    let healthy = checks.database.ok && checks.upload_dir.ok;
+
+    let report = warmup::report();
+    let response = ReadinessResponse {
+        status: if healthy { "ready" } else { "unavailable" },
+        checks,
+        pool: pool_utilization,
+        warmup_ms: report.map(|report| report.total_ms),
+        warmup: report.cloned(),
+    };
+    let status = if healthy {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(response))
+}
+
+async fn check_database(pool: &DbPool) -> DependencyCheck {
+    let started = Instant::now();
+    let outcome = tokio::time::timeout(
+        DATABASE_CHECK_TIMEOUT,
+        sqlx::query_scalar::<_, i64>("SELECT 1").fetch_one(pool),
+    )
+    .await;
+    let error = match outcome {
+        Ok(Ok(_)) => None,
+        Ok(Err(err)) => {
+            tracing::error!("Readiness check: database query failed: {}", err);
+            Some("query failed")
+        }
+        Err(_) => {
+            tracing::error!(
+                "Readiness check: database did not answer within {:?}",
+                DATABASE_CHECK_TIMEOUT
+            );
+            Some("timed out")
+        }
+    };
+    finished(started, error)
+}
+
+/// Writes and removes a probe file in `dir`. Its name starts with a dot, so
+/// it is never served or taken for an upload if it is left behind.
+async fn check_writable(dir: &Path) -> DependencyCheck {
+    let started = Instant::now();
+    let probe = dir.join(format!(".ready-probe-{}", uuid::Uuid::new_v4()));
+    let error = match tokio::fs::write(&probe, b"ok").await {
+        Ok(()) => {
+            if let Err(err) = tokio::fs::remove_file(&probe).await {
+                tracing::warn!(
+                    "Readiness check: could not remove {}: {}",
+                    probe.display(),
+                    err
+                );
+            }
+            None
+        }
+        Err(err) => {
+            tracing::error!(
+                "Readiness check: upload directory {} is not writable: {}",
+                dir.display(),
+                err
+            );
+            Some("not writable")
+        }
+    };
+    finished(started, error)
+}
+
+fn finished(started: Instant, error: Option<&'static str>) -> DependencyCheck {
+    DependencyCheck {
+        ok: error.is_none(),
+        latency_ms: u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX),
+        error,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn memory_pool() -> DbPool {
+        SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .expect("create sqlite pool")
+    }
+
+    #[tokio::test]
+    async fn database_check_fails_once_the_pool_is_gone() {
+        let pool = memory_pool().await;
+        assert!(check_database(&pool).await.ok);
+
+        pool.close().await;
+        let check = check_database(&pool).await;
+        assert!(!check.ok);
+        assert_eq!(check.error, Some("query failed"));
+
+        let (status, Json(body)) = ready(State(pool)).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body.status, "unavailable");
+        assert!(!body.checks.database.ok);
+    }
+
+    #[tokio::test]
+    async fn upload_dir_check_leaves_nothing_behind() {
+        let dir = std::env::temp_dir().join(format!("minos-ready-{}", uuid::Uuid::new_v4()));
+        assert_eq!(check_writable(&dir).await.error, Some("not writable"));
+
+        std::fs::create_dir_all(&dir).unwrap();
+        assert!(check_writable(&dir).await.ok);
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
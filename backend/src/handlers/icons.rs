@@ -0,0 +1,183 @@
+//! Icon Registry Handlers
+//!
+//! Tutorials name a Lucide icon that the frontend renders. The names a
+//! tutorial may use live in `allowed_icons`, so an admin can enable another
+//! icon without a backend release. Lookups go through a small in-process
+//! cache that every change through these endpoints invalidates.
+
+use crate::{
+    db::DbPool,
+    handlers::{
+        common::{ensure_admin, map_sqlx_error},
+        tutorials::validate_icon_name,
+    },
+    models::*,
+    repositories,
+    security::auth,
+};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use std::collections::HashSet;
+use std::sync::{Arc, RwLock};
+
+static CACHE: RwLock<Option<Arc<HashSet<String>>>> = RwLock::new(None);
+
+/// Whether `name` is registered. A name missing from the cache is looked up
+/// again, so an icon added elsewhere is picked up on first use.
+pub(crate) async fn is_allowed(pool: &DbPool, name: &str) -> Result<bool, sqlx::Error> {
+    let cached = CACHE.read().ok().and_then(|cache| cache.clone());
+    if cached.is_some_and(|icons| icons.contains(name)) {
+        return Ok(true);
+    }
+
+    let icons: HashSet<String> = repositories::icons::list_icons(pool)
+        .await?
+        .into_iter()
+        .map(|icon| icon.name)
+        .collect();
+    let allowed = icons.contains(name);
+    if let Ok(mut cache) = CACHE.write() {
+        *cache = Some(Arc::new(icons));
+    }
+    Ok(allowed)
+}
+
+fn invalidate_cache() {
+    if let Ok(mut cache) = CACHE.write() {
+        *cache = None;
+    }
+}
+
+/// Handler for `GET /api/admin/icons`.
+/// Admin-only.
+pub async fn list_icons(
+    claims: auth::Claims,
+    State(pool): State<DbPool>,
+) -> Result<Json<IconListResponse>, AppError> {
+    ensure_admin(&claims)?;
+
+    let items = repositories::icons::list_icons(&pool)
+        .await
+        .map_err(|err| map_sqlx_error(err, "Icon"))?;
+    Ok(Json(IconListResponse { items }))
+}
+
+/// Handler for `POST /api/admin/icons`.
+/// Admin-only, protected by CSRF.
+pub async fn create_icon(
+    claims: auth::Claims,
+    State(pool): State<DbPool>,
+    Json(payload): Json<CreateIconRequest>,
+) -> Result<(StatusCode, Json<AllowedIcon>), AppError> {
+    ensure_admin(&claims)?;
+
+    let name = payload.name.trim();
+    validate_icon_name(name).map_err(bad_request)?;
+
+    let icon = repositories::icons::create_icon(&pool, name)
+        .await
+        .map_err(|err| map_sqlx_error(err, "Icon"))?
+        .ok_or_else(|| conflict("Icon is already allowed"))?;
+    invalidate_cache();
+
+    tracing::info!(action = "create_icon", user = %claims.sub, icon = %icon.name, "Admin allowed icon");
+    Ok((StatusCode::CREATED, Json(icon)))
+}
+
+/// Handler for `DELETE /api/admin/icons/{name}`. Refused while a tutorial,
+/// including one in the trash, still uses the icon.
+/// Admin-only, protected by CSRF.
+pub async fn delete_icon(
+    claims: auth::Claims,
+    State(pool): State<DbPool>,
+    Path(name): Path<String>,
+) -> Result<StatusCode, AppError> {
+    ensure_admin(&claims)?;
+
+    let in_use = repositories::icons::count_tutorials_using(&pool, &name)
+        .await
+        .map_err(|err| map_sqlx_error(err, "Icon"))?;
+    if in_use > 0 {
+        return Err(conflict(format!("Icon is used by {in_use} tutorial(s)")));
+    }
+
+    let deleted = repositories::icons::delete_icon(&pool, &name)
+        .await
+        .map_err(|err| map_sqlx_error(err, "Icon"))?;
+    invalidate_cache();
+    if !deleted {
+        return Err(not_found("Icon not found"));
+    }
+
+    tracing::info!(action = "delete_icon", user = %claims.sub, icon = %name, "Admin removed icon");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::tutorials::validate_icon;
+
+    #[tokio::test]
+    async fn added_icons_become_usable_until_removed() {
+        let pool = crate::test_support::migrated_pool().await;
+        let admin = crate::test_support::claims_for("root", "admin");
+        let create = |name: &str| {
+            create_icon(
+                admin.clone(),
+                State(pool.clone()),
+                Json(CreateIconRequest {
+                    name: name.to_string(),
+                }),
+            )
+        };
+
+        assert!(validate_icon(&pool, "Terminal").await.is_ok());
+        assert!(validate_icon(&pool, "Rocket").await.is_err());
+
+        let (status, Json(icon)) = create(" Rocket ").await.expect("add icon");
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(icon.name, "Rocket");
+        assert!(validate_icon(&pool, "Rocket").await.is_ok());
+
+        let status = create("Rocket").await.unwrap_err().status();
+        assert_eq!(status, StatusCode::CONFLICT);
+        for invalid in ["rocket", "Rock-et", ""] {
+            let status = create(invalid).await.unwrap_err().status();
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{invalid:?}");
+        }
+
+        repositories::tutorials::create_tutorial(
+            &pool,
+            "uses-rocket",
+            "Uses rocket",
+            "Description",
+            "Body",
+            "Rocket",
+            "from-blue-500 to-indigo-600",
+            "[]",
+            &[],
+            TUTORIAL_STATUS_PUBLISHED,
+            true,
+            None,
+        )
+        .await
+        .unwrap();
+        let delete =
+            |name: &str| delete_icon(admin.clone(), State(pool.clone()), Path(name.into()));
+        let status = delete("Rocket").await.unwrap_err().status();
+        assert_eq!(status, StatusCode::CONFLICT);
+
+        sqlx::query("DELETE FROM tutorials WHERE id = 'uses-rocket'")
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert_eq!(delete("Rocket").await.unwrap(), StatusCode::NO_CONTENT);
+        assert!(validate_icon(&pool, "Rocket").await.is_err());
+        let status = delete("Rocket").await.unwrap_err().status();
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+}
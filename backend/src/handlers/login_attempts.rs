@@ -0,0 +1,137 @@
+//! Admin Login Lockout Handlers
+//!
+//! Failed logins lock out an (IP, username) pair and, under heavier load, a
+//! whole IP; see [`crate::handlers::auth`]. These endpoints let an admin see
+//! the lockouts in force and lift them. Keys are salted hashes, so the list
+//! cannot tell which account a lockout belongs to; clearing everything is
+//! the way out for a locked-out admin.
+
+use crate::{
+    db::DbPool,
+    handlers::common::{ensure_admin, map_sqlx_error},
+    models::*,
+    repositories,
+    security::auth,
+};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+
+/// Handler for `GET /api/admin/login-attempts`.
+/// Admin-only.
+pub async fn list_blocked_login_attempts(
+    claims: auth::Claims,
+    State(pool): State<DbPool>,
+) -> Result<Json<BlockedLoginAttemptListResponse>, AppError> {
+    ensure_admin(&claims)?;
+
+    let items = repositories::users::list_blocked_login_attempts(&pool)
+        .await
+        .map_err(|err| map_sqlx_error(err, "Login attempt"))?;
+    Ok(Json(BlockedLoginAttemptListResponse { items }))
+}
+
+/// Handler for `DELETE /api/admin/login-attempts/{key}`.
+/// Admin-only, protected by CSRF.
+pub async fn clear_login_attempt(
+    claims: auth::Claims,
+    State(pool): State<DbPool>,
+    Path(key): Path<String>,
+) -> Result<StatusCode, AppError> {
+    ensure_admin(&claims)?;
+
+    let cleared = repositories::users::delete_login_attempt(&pool, &key)
+        .await
+        .map_err(|err| map_sqlx_error(err, "Login attempt"))?;
+    if !cleared {
+        return Err(not_found("Login attempt not found"));
+    }
+
+    tracing::info!(action = "clear_login_attempt", user = %claims.sub, key = %key, "Admin lifted lockout");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Handler for `DELETE /api/admin/login-attempts`.
+/// Admin-only, protected by CSRF.
+pub async fn clear_all_login_attempts(
+    claims: auth::Claims,
+    State(pool): State<DbPool>,
+) -> Result<Json<ClearedLoginAttemptsResponse>, AppError> {
+    ensure_admin(&claims)?;
+
+    let cleared = repositories::users::delete_all_login_attempts(&pool)
+        .await
+        .map_err(|err| map_sqlx_error(err, "Login attempt"))?;
+
+    tracing::info!(action = "clear_login_attempts", user = %claims.sub, cleared, "Admin lifted all lockouts");
+    Ok(Json(ClearedLoginAttemptsResponse { cleared }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn lists_and_clears_active_lockouts() {
+        let pool = crate::test_support::migrated_pool().await;
+        let admin = crate::test_support::claims_for("root", "admin");
+
+        let soon = (chrono::Utc::now() + chrono::Duration::minutes(1)).to_rfc3339();
+        let past = (chrono::Utc::now() - chrono::Duration::minutes(1)).to_rfc3339();
+        for (key, blocked_until) in [
+            ("locked", Some(&soon)),
+            ("expired", Some(&past)),
+            ("counting", None),
+        ] {
+            sqlx::query(
+                "INSERT INTO login_attempts (username, fail_count, blocked_until) VALUES (?, 3, ?)",
+            )
+            .bind(key)
+            .bind(blocked_until)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        let Json(listed) = list_blocked_login_attempts(admin.clone(), State(pool.clone()))
+            .await
+            .unwrap();
+        let keys: Vec<_> = listed.items.iter().map(|item| item.key.as_str()).collect();
+        assert_eq!(keys, ["locked"]);
+
+        let status = clear_login_attempt(
+            admin.clone(),
+            State(pool.clone()),
+            Path("locked".to_string()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        let status = clear_login_attempt(
+            admin.clone(),
+            State(pool.clone()),
+            Path("locked".to_string()),
+        )
+        .await
+        .expect_err("already cleared")
+        .status();
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let editor = auth::Claims {
+            role: "editor".to_string(),
+            ..admin.clone()
+        };
+        let status = clear_all_login_attempts(editor, State(pool.clone()))
+            .await
+            .expect_err("admin only")
+            .status();
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        let Json(cleared) = clear_all_login_attempts(admin, State(pool.clone()))
+            .await
+            .unwrap();
+        assert_eq!(cleared.cleared, 2);
+    }
+}
@@ -0,0 +1,173 @@
+//! Admin Maintenance Handlers
+//!
+//! Lets an admin run the background pruning task on demand, e.g. after a
+//! burst of failed logins, instead of waiting for the next interval, switch
+//! the site into maintenance mode, and back up the database.
+
+use crate::{
+    backup::{self, BackupReport},
+    db::DbPool,
+    handlers::common::ensure_admin,
+    maintenance::{self, PruneReport},
+    middleware::maintenance as maintenance_mode,
+    models::{
+        bad_request, internal_error, not_found, AppError, BackupListResponse, MaintenanceMode,
+        SetMaintenanceModeRequest,
+    },
+    repositories,
+    security::auth,
+};
+use axum::{
+    body::Body,
+    extract::{Path, State},
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use tokio_util::io::ReaderStream;
+
+/// Handler for `POST /api/admin/maintenance/prune`.
+/// Admin-only, protected by CSRF. Returns the rows removed per table.
+pub async fn prune_now(
+    claims: auth::Claims,
+    State(pool): State<DbPool>,
+) -> Result<Json<PruneReport>, AppError> {
+    ensure_admin(&claims)?;
+
+    let report = maintenance::prune(&pool).await;
+    tracing::info!(action = "prune", user = %claims.sub, "Admin ran maintenance");
+    Ok(Json(report))
+}
+
+/// Longest maintenance message accepted.
+const MAX_MAINTENANCE_MESSAGE_LEN: usize = 500;
+
+/// Handler for `POST /api/admin/maintenance`. Turns maintenance mode on or
+/// off; while on, public reads answer 503 with the given message.
+/// Admin-only, protected by CSRF.
+pub async fn set_maintenance_mode(
+    claims: auth::Claims,
+    State(pool): State<DbPool>,
+    Json(payload): Json<SetMaintenanceModeRequest>,
+) -> Result<Json<MaintenanceMode>, AppError> {
+    ensure_admin(&claims)?;
+
+    let message = payload
+        .message
+        .map(|message| message.trim().to_string())
+        .filter(|message| !message.is_empty());
+    if message
+        .as_ref()
+        .is_some_and(|message| message.chars().count() > MAX_MAINTENANCE_MESSAGE_LEN)
+    {
+        return Err(bad_request(format!(
+            "Message must be at most {MAX_MAINTENANCE_MESSAGE_LEN} characters"
+        )));
+    }
+
+    let mode = MaintenanceMode {
+        enabled: payload.enabled,
+        message,
+    };
+    maintenance_mode::set_mode(&pool, mode.clone())
+        .await
+        .map_err(internal_error("Failed to update maintenance mode"))?;
+
+    tracing::info!(action = "set_maintenance_mode", user = %claims.sub, enabled = mode.enabled, "Admin toggled maintenance mode");
+    repositories::audit::append_entry(
+        &pool,
+        &claims.sub,
+        if mode.enabled { "enable" } else { "disable" },
+        "maintenance_mode",
+        None,
+        serde_json::json!({ "message": mode.message }),
+    )
+    .await;
+    Ok(Json(mode))
+}
+
+/// Handler for `POST /api/admin/backup`. Writes a consistent copy of the
+/// database to `BACKUP_DIR` and prunes backups past their retention.
+/// Admin-only, protected by CSRF, in sudo mode.
+pub async fn create_backup(
+    auth::RequireSudo(claims): auth::RequireSudo,
+    State(pool): State<DbPool>,
+) -> Result<(StatusCode, Json<BackupReport>), AppError> {
+    ensure_admin(&claims)?;
+
+    let config = crate::config::get();
+    let report = backup::create_backup(&pool, &config.backup_dir, config.backup_retention_days)
+        .await
+        .map_err(internal_error("Failed to back up the database"))?;
+
+    tracing::info!(action = "create_backup", user = %claims.sub, backup = %report.backup.name, size_bytes = report.backup.size_bytes, "Admin backed up the database");
+    repositories::audit::append_entry(
+        &pool,
+        &claims.sub,
+        "create",
+        "backup",
+        Some(&report.backup.name),
+        serde_json::json!({
+            "size_bytes": report.backup.size_bytes,
+            "pruned": report.pruned,
+        }),
+    )
+    .await;
+    Ok((StatusCode::CREATED, Json(report)))
+}
+
+/// Handler for `GET /api/admin/backups`. Admin-only.
+pub async fn list_backups(claims: auth::Claims) -> Result<Json<BackupListResponse>, AppError> {
+    ensure_admin(&claims)?;
+
+    let config = crate::config::get();
+    let items = backup::list_backups(&config.backup_dir)
+        .await
+        .map_err(internal_error("Failed to list backups"))?;
+    Ok(Json(BackupListResponse {
+        items,
+        retention_days: config.backup_retention_days,
+    }))
+}
+
+/// Handler for `GET /api/admin/backups/{name}`: the backup as a download.
+/// It holds every password hash and secret in the database, so this is
+/// admin-only and in sudo mode.
+pub async fn download_backup(
+    auth::RequireSudo(claims): auth::RequireSudo,
+    Path(name): Path<String>,
+) -> Result<Response, AppError> {
+    ensure_admin(&claims)?;
+
+    let dir = &crate::config::get().backup_dir;
+    let path = backup::backup_path(dir, &name)
+        .await
+        .map_err(internal_error("Failed to read backup"))?
+        .ok_or_else(|| not_found("Backup not found"))?;
+    let file = tokio::fs::File::open(&path)
+        .await
+        .map_err(internal_error("Failed to read backup"))?;
+    let size = file
+        .metadata()
+        .await
+        .map_err(internal_error("Failed to read backup"))?
+        .len();
+
+    tracing::info!(action = "download_backup", user = %claims.sub, backup = %name, "Admin downloaded a database backup");
+    // Backup names are plain ASCII, see `backup::is_backup_name`
+    let disposition = HeaderValue::from_str(&format!("attachment; filename=\"{name}\""))
+        .expect("backup names are ASCII");
+    Ok((
+        [
+            (
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("application/vnd.sqlite3"),
+            ),
+            (header::CONTENT_DISPOSITION, disposition),
+            (header::CONTENT_LENGTH, HeaderValue::from(size)),
+            (header::CACHE_CONTROL, HeaderValue::from_static("no-store")),
+        ],
+        Body::from_stream(ReaderStream::new(file)),
+    )
+        .into_response())
+}
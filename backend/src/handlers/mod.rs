@@ -21,15 +21,118 @@
  * - `GET /api/search/tutorials` - Tutorial search with FTS5
  * - `GET /api/search/topics` - Topic discovery and filtering
  *
+ * ### [`health`](mod@health)
+ * **Probes**
+ * - `GET /api/health` - Liveness
+ * - `GET /api/health/ready` - Readiness: database (`SELECT 1`, 2 s timeout) and writable
+ *   upload directory, pool utilization and boot warmup timing (`warmup_ms`); 503 when a
+ *   check fails
+ *
+ * - `GET /api/auth/csrf` - A fresh CSRF token, as cookie and in the body
+ * - `GET /api/auth/jwks` - Public token signing key (empty unless RS256/EdDSA is configured)
+ * - `POST /api/auth/refresh` - Exchange a valid session token for a fresh one
+ * - `POST /api/auth/change-password` - Change the caller's password and end the session
+ * - `POST /api/auth/sudo` - Re-enter the password to allow deletions and account changes for 10 minutes
+ * - `GET /api/auth/sessions` - The caller's active sessions
+ * - `DELETE /api/auth/sessions/{jti}` - Revoke one of them
+ * - `POST /api/auth/logout-all` - Revoke all of them, including the current one
+ * - `POST /api/auth/login/2fa` - Finish a login with a TOTP or recovery code
+ * - `POST /api/auth/2fa/setup` - Start TOTP enrollment (secret + otpauth URI)
+ * - `POST /api/auth/2fa/verify` - Confirm enrollment, returns recovery codes
+ * - `POST /api/auth/2fa/disable` - Turn two-factor login off (password + code)
+ *
+ * ### [`stats`](mod@stats)
+ * **Admin Dashboard Statistics**
+ * - `GET /api/admin/stats/content` - Word counts, drafts and stale documents (admin)
+ * - `GET /api/admin/stats/views` - Views per tutorial or post over a date range (admin)
+ * - `GET /api/admin/security/summary` - CSRF/token/login rejections, last 30 days (admin)
+ *
+ * ### [`icons`](mod@icons)
+ * **Icon Registry** (admin)
+ * - `GET /api/admin/icons` - Icons tutorials may use
+ * - `POST /api/admin/icons` - Allow another Lucide icon
+ * - `DELETE /api/admin/icons/{name}` - Remove an icon no tutorial uses
+ *
+ * ### [`content_sections`](mod@content_sections)
+ * **Content Section Registry** (admin)
+ * - `GET /api/admin/content-sections` - Site content sections the API accepts
+ * - `POST /api/admin/content-sections` - Register another section
+ * - `DELETE /api/admin/content-sections/{name}` - Remove a section with its content
+ *
+ * ### [`content_transfer`](mod@content_transfer)
+ * **Content Bundles** (admin)
+ * - `GET /api/admin/content/export` - Bundle of the `sections` given (all by default), with
+ *   `include_pages` and `include_tutorials`
+ * - `POST /api/admin/content/import` - Import a bundle in one transaction, `?mode=skip_existing`
+ *   (default) or `overwrite`
+ *
+ * ### [`upload`](mod@upload)
+ * **Media Library** (admin)
+ * - `GET /api/admin/uploads` - Uploaded files, newest first, `?q=` searches file names
+ * - `POST /api/admin/uploads/fetch` - Import the image at a public `url` like an upload;
+ *   internal addresses are refused, at most 3 redirects
+ * - `PUT /api/admin/uploads/{id}` - Set the `alt` text (max 300 chars) and `caption` (max
+ *   1000 chars); an empty value clears it
+ * - `DELETE /api/admin/uploads/{id}` - Delete an upload and its file; 409 listing the
+ *   content that still references it
+ * - `POST /api/admin/uploads/cleanup` - Uploads and stored files no content references, older
+ *   than 24 hours; deleted with `?dry_run=false`
+ * - `GET /uploads/{key}` - Stored file from the configured store, cacheable for a year, with
+ *   an ETag (`If-None-Match`) and single `Range` requests; 404 JSON when missing
+ *
+ * ### [`login_attempts`](mod@login_attempts)
+ * **Login Lockouts** (admin)
+ * - `GET /api/admin/login-attempts` - Lockouts currently in force (hashed keys)
+ * - `DELETE /api/admin/login-attempts/{key}` - Lift one lockout
+ * - `DELETE /api/admin/login-attempts` - Lift all lockouts
+ *
+ * ### [`maintenance`](mod@maintenance)
+ * **Maintenance** (admin)
+ * - `POST /api/admin/maintenance/prune` - Prune expired tokens, login attempts and other stale rows now
+ * - `POST /api/admin/maintenance` - Turn maintenance mode (503 for public reads) on or off
+ * - `POST /api/admin/backup` - Back up the database with `VACUUM INTO` to `BACKUP_DIR`; sudo
+ *   mode. Backups older than `BACKUP_RETENTION_DAYS` are deleted
+ * - `GET /api/admin/backups` - Backups, newest first
+ * - `GET /api/admin/backups/{name}` - Download a backup; sudo mode
+ *
+ * ### [`users`](mod@users)
+ * **User Management** (admin)
+ * - `GET /api/admin/users` - List accounts
+ * - `POST /api/admin/users` - Create an account
+ * - `PUT /api/admin/users/{id}` - Change role or reset password
+ * - `DELETE /api/admin/users/{id}` - Delete an account
+ *
+ * ### [`api_keys`](mod@api_keys)
+ * **API Keys** (admin)
+ * - `GET /api/admin/api-keys` - List keys, including revoked ones
+ * - `POST /api/admin/api-keys` - Create a key; the plaintext is returned only once
+ * - `DELETE /api/admin/api-keys/{id}` - Revoke a key
+ *
  * ## Content Management
  *
  * ### [`tutorials`](mod@tutorials)
  * **Tutorial CRUD Operations**
- * - `GET /api/tutorials` - List all tutorials
- * - `GET /api/tutorials/{id}` - Get specific tutorial
- * - `POST /api/tutorials` - Create new tutorial (admin)
- * - `PUT /api/tutorials/{id}` - Update tutorial (admin)
- * - `DELETE /api/tutorials/{id}` - Delete tutorial (admin)
+ * - `GET /api/tutorials` - List tutorials, optionally by `topic`/`topics` (drafts only for
+ *   admins and editors; ETag-revalidated)
+ * - `GET /api/tutorials/{id}` - Get specific tutorial (ETag/Last-Modified-revalidated)
+ * - `POST /api/tutorials` - Create new tutorial (admin, editor)
+ * - `PUT /api/tutorials/{id}` - Update tutorial (admin, editor)
+ * - `PATCH /api/tutorials/{id}` - Apply an RFC 6902 JSON Patch (admin, editor)
+ * - `POST /api/tutorials/{id}/publish` - Publish a draft tutorial (admin, editor)
+ * - `PUT /api/tutorials/order` - Set the order tutorials are listed in (admin, editor)
+ * - `POST /api/tutorials/{id}/duplicate` - Copy a tutorial as a new draft (admin)
+ * - `POST /api/tutorials/{id}/rename` - Change a tutorial's ID, redirecting the old one
+ *   (admin, editor)
+ * - `DELETE /api/tutorials/{id}` - Move a tutorial to the trash, or delete it for good with
+ *   `?permanent=true` (admin)
+ * - `POST /api/tutorials/{id}/restore` - Restore a tutorial from the trash (admin)
+ * - `GET /api/admin/tutorials/trash` - List tutorials in the trash (admin)
+ * - `GET /api/tutorials/{id}/revisions` - List earlier versions of a tutorial (admin, editor)
+ * - `GET /api/tutorials/{id}/revisions/{version}` - Get one earlier version (admin, editor)
+ * - `POST /api/tutorials/{id}/revisions/{version}/restore` - Save an earlier version as the
+ *   current one (admin, editor)
+ * - `GET /api/tutorials/{id}/export.json` - Export one tutorial as JSON (admin)
+ * - `POST /api/admin/tutorials/import-one` - Import an exported tutorial (admin)
  *
  * ### [`comments`](mod@comments)
  * **Comment System**
@@ -37,36 +140,90 @@
  * - `POST /api/tutorials/{id}/comments` - Create comment (admin)
  * - `DELETE /api/comments/{id}` - Delete comment (admin)
  *
+ * ### [`audit_log`](mod@audit_log)
+ * **Audit Log** (admin)
+ * - `GET /api/admin/audit-log` - Content changes, newest first; filter by `entity_type` and `username`, page with `limit`/`offset`
+ *
+ * ### [`deletion_log`](mod@deletion_log)
+ * **Deletion Log** (admin)
+ * - `GET /api/admin/deletion-log` - Recently deleted tutorials, pages, posts and comments
+ * - `POST /api/admin/deletion-log/{id}/restore` - Re-create an entry under its original ID
+ *
  * ## Site Content Management
  *
  * ### [`site_content`](mod@site_content)
  * **Dynamic Site Content**
  * - `GET /api/content` - List all content sections
  * - `GET /api/content/{section}` - Get specific section content
- * - `PUT /api/content/{section}` - Update section content (admin)
+ * - `PUT /api/content/{section}` - Update section content; `?dry_run=true` only validates (admin)
+ * - `GET /api/content/{section}/revisions` - List saved versions of a section (admin)
+ * - `GET /api/content/{section}/revisions/{id}` - Get one saved version (admin)
+ * - `POST /api/content/{section}/revisions/{id}/restore` - Save an earlier version as the
+ *   current content (admin)
+ *
+ * ### [`well_known`](mod@well_known)
+ * **Crawler and Security Contact Files**
+ * - `GET /robots.txt` - `robots` section, or allow-all with a sitemap link
+ * - `GET /.well-known/security.txt` - `security_txt` section, 404 when unset
+ * - `GET /sitemap.xml` - Published pages, posts and tutorials, 404 without `PUBLIC_BASE_URL`
+ * - `GET /sitemaps/{n}.xml` - Parts of the sitemap index beyond 50,000 URLs
  *
  * ### [`site_pages`](mod@site_pages)
  * **Static Page Management**
- * - `GET /api/pages` - List all pages (admin)
- * - `GET /api/pages/{id}` - Get specific page (admin)
+ * - `GET /api/pages` - List all pages (admin, editor)
+ * - `GET /api/pages/{id}` - Get specific page (admin, editor)
  * - `POST /api/pages` - Create new page (admin)
- * - `PUT /api/pages/{id}` - Update page (admin)
- * - `DELETE /api/pages/{id}` - Delete page (admin)
+ * - `PUT /api/pages/{id}` - Update page; `?dry_run=true` only validates (admin)
+ * - `PATCH /api/pages/{id}` - Apply an RFC 6902 JSON Patch; `?dry_run=true` only validates
+ *   (admin)
+ * - `DELETE /api/pages/{id}` - Move a page and with it its posts to the trash, or delete
+ *   them for good with `?permanent=true` (admin)
+ * - `POST /api/pages/{id}/restore` - Restore a page from the trash (admin)
+ * - `GET /api/admin/trash` - List pages and posts in the trash (admin)
+ * - `POST /api/pages/{id}/duplicate` - Copy a page as an unpublished draft, with its posts
+ *   when `?include_posts=true` (admin)
  *
  * ### [`site_posts`](mod@site_posts)
  * **Blog Post Management**
- * - `GET /api/pages/{page_id}/posts` - List posts for page (admin)
- * - `GET /api/posts/{id}` - Get specific post (admin)
- * - `POST /api/pages/{page_id}/posts` - Create post (admin)
- * - `PUT /api/posts/{id}` - Update post (admin)
- * - `DELETE /api/posts/{id}` - Delete post (admin)
+ * - `GET /api/pages/{page_id}/posts` - List posts for page (admin, editor)
+ * - `GET /api/posts/{id}` - Get specific post (admin, editor)
+ * - `POST /api/pages/{page_id}/posts` - Create post (admin, editor)
+ * - `PUT /api/posts/{id}` - Update post (admin, editor)
+ * - `DELETE /api/posts/{id}` - Move a post to the trash, or delete it for good with
+ *   `?permanent=true` (admin)
+ * - `POST /api/posts/{id}/restore` - Restore a post from the trash (admin)
+ * - `GET /api/posts/{id}/revisions` - List earlier states of a post (admin, editor)
+ * - `GET /api/posts/{id}/revisions/{revision}` - Get one earlier state (admin, editor)
+ * - `POST /api/posts/{id}/revisions/{revision}/restore` - Save an earlier state as the
+ *   current one (admin, editor)
+ *
+ * ### [`previews`](mod@previews)
+ * **Preview Links**
+ * - `POST /api/admin/pages/{id}/preview-token` - Link to an unpublished page (admin)
+ * - `DELETE /api/admin/pages/{id}/preview-token` - Revoke the page's preview links (admin)
+ * - `POST /api/admin/posts/{id}/preview-token` - Link to an unpublished post (admin, editor)
+ * - `DELETE /api/admin/posts/{id}/preview-token` - Revoke the post's preview links
+ *   (admin, editor)
  *
  * ## Public Endpoints
  *
  * These are automatically accessible without authentication:
- * - `GET /api/public/pages/{slug}` - Get published page by slug
- * - `GET /api/public/pages/{slug}/posts/{post_slug}` - Get published post
+ * - `GET /api/public/pages/{slug}` - Get published page by slug with its first 10 post
+ *   summaries and `posts_total` (ETag-revalidated); an unpublished one with a valid
+ *   `?preview=` token
+ * - `GET /api/public/pages/{slug}/posts` - Paginated post summaries of a page
+ *   (`limit`, `offset`, `sort=published_at:desc`, `tag`)
+ * - `GET /api/public/pages/{slug}/posts/{post_slug}` - Get published post with links to
+ *   its neighbors (ETag-revalidated); an unpublished one with a valid `?preview=` token;
+ *   a former slug of a renamed post answers with the post and its `canonical_slug`
+ * - `GET /api/public/pages/{slug}/archive` - Post counts per year and month of a page
+ * - `GET /api/public/pages/{slug}/archive/{year}/{month}` - Paginated post summaries of
+ *   one archive month (`limit`, `offset`)
+ * - `GET /api/public/tags` - Tags of published posts with usage counts
  * - `GET /api/public/navigation` - Get site navigation structure
+ * - `GET /api/public/settings` - Typed client settings (ETag-revalidated)
+ * - `GET /api/public/changelog` - Recently published/updated content
+ * - `GET /api/public/changelog.rss` - The changelog as an RSS 2.0 feed
  * - `GET /api/public/published-pages` - List published page slugs
  *
  * # Security Features
@@ -80,7 +237,7 @@
  * ## CSRF Protection
  * - Double-submit cookie pattern for state-changing requests
  * - HMAC-SHA256 signed tokens with user binding
- * - Short TTL (6 hours) to reduce attack window
+ * - Expires together with the session it belongs to
  * - Constant-time signature verification
  *
  * ## Input Validation
@@ -109,18 +266,29 @@
  * ```json
  * {
  *   "error": "Human-readable error message",
- *   "details": "Additional error context (optional)"
+ *   "code": "not_found"   // Machine-readable, see below
  * }
  * ```
+ * Every error carries a `code`: `not_found`, `validation_failed`,
+ * `conflict`, `unauthorized`, `forbidden`, `database_error` or
+ * `internal_error`, one for the status (`rate_limited`,
+ * `payload_too_large`, ...) otherwise, or a specific one a client reacts
+ * to (`sudo_required`, `maintenance`).
  *
  * ## List Responses
+ * The tutorial and comment lists return a bare array by default; with
+ * `?envelope=true` they return:
  * ```json
  * {
  *   "items": [ ... ],     // Array of items
  *   "total": 42,          // Total count (when paginated)
+ *   "limit": 20,          // Page size after clamping
+ *   "offset": 0,          // Position of the first item
  *   "hasMore": true       // Pagination indicator
  * }
  * ```
+ * Comment totals count top-level threads, the unit comment pages are made
+ * of; `GET .../comments/count` reports every comment including replies.
  *
  * # Rate Limiting
  *
@@ -141,18 +309,35 @@
 // HTTP Handler Modules - Organized by Domain
 
 // Core System Handlers
+pub mod api_keys; // Admin-issued API keys
+pub mod audit_log; // Admin view of the audit log
 pub mod auth; // Authentication and authorization
+pub mod changelog; // Public content changelog and RSS feed
 pub mod common; // Helpers shared across handler modules
+pub mod deletion_log; // Restorable snapshots of hard deletes
+pub mod health; // Liveness and readiness probes
+pub mod icons; // Icon registry for tutorials
+pub mod login_attempts; // Admin view of login lockouts
+pub mod maintenance; // On-demand pruning of expired rows
+pub mod notifications; // Webhook notification test
 pub mod search; // Full-text search functionality
+pub mod stats; // Admin dashboard statistics
+pub mod users; // Admin user management
 
 // Content Management Handlers
+pub mod comment_blocklist; // Spam blocklist for comment submissions
 pub mod comments; // Comment system management
 pub mod newsletter; // Public newsletter subscriptions
+pub mod patch; // RFC 6902 JSON Patch application
+pub mod previews; // Shareable preview links for unpublished pages and posts
 pub mod tutorials; // Tutorial CRUD operations
 pub mod upload; // Image upload
 
 // Site Content Handlers
+pub mod content_sections; // Registry of site content sections
+pub mod content_transfer; // Content bundle export and import
 pub mod frontend_proxy; // Frontend proxy for server-side injection
 pub mod site_content; // Dynamic site content sections
 pub mod site_pages; // Static page management
 pub mod site_posts; // Blog post management
+pub mod well_known; // robots.txt, security.txt and the sitemap
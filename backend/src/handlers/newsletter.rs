@@ -1,6 +1,6 @@
 use crate::{
     db::DbPool,
-    models::{bad_request, internal_error, ApiError},
+    models::{bad_request, internal_error, AppError},
     repositories,
 };
 use axum::{extract::State, Json};
@@ -20,7 +20,7 @@ pub struct NewsletterSubscriptionResponse {
 pub async fn subscribe_to_newsletter(
     State(pool): State<DbPool>,
     Json(payload): Json<NewsletterSubscriptionRequest>,
-) -> Result<Json<NewsletterSubscriptionResponse>, ApiError> {
+) -> Result<Json<NewsletterSubscriptionResponse>, AppError> {
     let email = validate_and_normalize_email(&payload.email).map_err(bad_request)?;
 
     repositories::newsletter::subscribe(&pool, &email)
@@ -30,7 +30,7 @@ pub async fn subscribe_to_newsletter(
     Ok(Json(NewsletterSubscriptionResponse { subscribed: true }))
 }
 
-fn validate_and_normalize_email(value: &str) -> Result<String, &'static str> {
+pub(crate) fn validate_and_normalize_email(value: &str) -> Result<String, &'static str> {
     const INVALID_EMAIL: &str = "Ungültige E-Mail-Adresse";
     let email = value.trim();
     if email.is_empty() || email.len() > 254 || !email.is_ascii() {
@@ -0,0 +1,40 @@
+//! Admin Notification Handlers
+//!
+//! Lets an admin check the `NOTIFY_WEBHOOK_URL` setup without waiting for
+//! a real comment.
+
+use crate::{
+    handlers::common::ensure_admin,
+    models::{api_error, conflict, AppError},
+    notifications::{self, Notification},
+    security::auth,
+};
+use axum::http::StatusCode;
+
+/// Handler for `POST /api/admin/notifications/test`.
+/// Admin-only, protected by CSRF. Unlike real events the delivery is
+/// awaited, so the response reports whether the webhook accepted it.
+pub async fn send_test_notification(claims: auth::Claims) -> Result<StatusCode, AppError> {
+    ensure_admin(&claims)?;
+
+    let Some(url) = notifications::webhook_url() else {
+        return Err(conflict("Notifications are off; set NOTIFY_WEBHOOK_URL"));
+    };
+    let notification = Notification {
+        event: "notification.test",
+        summary: format!("Test notification sent by {}", claims.sub),
+        data: serde_json::json!({ "requested_by": claims.sub }),
+    };
+    notifications::deliver(url, &notification)
+        .await
+        .map_err(|err| {
+            tracing::warn!("Test notification failed: {}", err);
+            api_error(
+                StatusCode::BAD_GATEWAY,
+                format!("Webhook delivery failed: {err}"),
+            )
+        })?;
+
+    tracing::info!(action = "test_notification", user = %claims.sub, "Admin sent test notification");
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,202 @@
+//! RFC 6902 JSON Patch support.
+//!
+//! Large documents (a 100KB tutorial, a page layout with dozens of blocks)
+//! can be edited by sending a list of operations instead of the whole body.
+//! A patch is applied to the entity's *patch document* — the editable fields
+//! in the same shape the admin GET returns — and the result then goes
+//! through the entity's regular validators before anything is written.
+//!
+//! Patch documents also carry one read-only concurrency field (`version` for
+//! tutorials, `updated_at` for pages). Clients may assert on it with a
+//! `test` operation; any attempt to change it is rejected.
+
+use crate::models::{api_error, bad_request, conflict, AppError};
+use axum::http::StatusCode;
+use json_patch::{Patch, PatchErrorKind};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+/// Largest accepted patch body (256KB).
+pub const MAX_PATCH_BYTES: usize = 256 * 1024;
+/// Most operations accepted in a single patch.
+pub const MAX_PATCH_OPERATIONS: usize = 200;
+
+/// Parses a JSON Patch request body, enforcing the size and operation caps.
+pub(crate) fn parse_patch(body: &[u8]) -> Result<Patch, AppError> {
+    if body.len() > MAX_PATCH_BYTES {
+        return Err(api_error(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            format!("Patch exceeds maximum size of {MAX_PATCH_BYTES} bytes"),
+        ));
+    }
+
+    let patch: Patch = serde_json::from_slice(body)
+        .map_err(|err| bad_request(format!("Invalid JSON Patch document: {err}")))?;
+
+    if patch.0.is_empty() {
+        return Err(bad_request("Patch must contain at least one operation"));
+    }
+    if patch.0.len() > MAX_PATCH_OPERATIONS {
+        return Err(bad_request(format!(
+            "Patch has too many operations (max {MAX_PATCH_OPERATIONS})"
+        )));
+    }
+
+    Ok(patch)
+}
+
+/// Applies `patch` to `current` and decodes the result back into `T`.
+///
+/// `read_only` names top-level fields that must come out unchanged. A failed
+/// `test` operation answers 409 so clients can tell a stale document apart
+/// from a malformed patch (422).
+pub(crate) fn apply_patch<T>(current: &T, patch: &Patch, read_only: &[&str]) -> Result<T, AppError>
+where
+    T: Serialize + DeserializeOwned,
+{
+    let original = serde_json::to_value(current)
+        .map_err(|err| bad_request(format!("Document cannot be patched: {err}")))?;
+    let mut document = original.clone();
+
+    json_patch::patch(&mut document, patch).map_err(|err| match err.kind {
+        PatchErrorKind::TestFailed => conflict(format!("Patch test failed at '{}'", err.path)),
+        _ => api_error(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            format!("Patch operation {} failed: {err}", err.operation),
+        ),
+    })?;
+
+    for field in read_only {
+        if document.get(field) != original.get(field) {
+            return Err(bad_request(format!("Field '{field}' is read-only")));
+        }
+    }
+
+    decode(document)
+}
+
+fn decode<T: DeserializeOwned>(document: Value) -> Result<T, AppError> {
+    serde_json::from_value(document).map_err(|err| {
+        api_error(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            format!("Patched document is invalid: {err}"),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use serde_json::json;
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    #[serde(deny_unknown_fields)]
+    struct PageDoc {
+        title: String,
+        layout: Value,
+        updated_at: String,
+    }
+
+    fn page() -> PageDoc {
+        PageDoc {
+            title: "Home".to_string(),
+            layout: json!({
+                "blocks": [
+                    { "type": "text", "props": { "body": "Hello", "align": "left" } },
+                    { "type": "image", "props": { "src": "/uploads/a.png" } }
+                ]
+            }),
+            updated_at: "2024-01-01 00:00:00".to_string(),
+        }
+    }
+
+    fn patch(ops: Value) -> Patch {
+        parse_patch(ops.to_string().as_bytes()).expect("valid patch")
+    }
+
+    #[test]
+    fn add_remove_replace_on_nested_layout_blocks() {
+        let ops = patch(json!([
+            { "op": "replace", "path": "/layout/blocks/0/props/body", "value": "Hi" },
+            { "op": "remove", "path": "/layout/blocks/0/props/align" },
+            { "op": "add", "path": "/layout/blocks/1/props/alt", "value": "Diagram" },
+            { "op": "add", "path": "/layout/blocks/-", "value": { "type": "divider" } },
+            { "op": "remove", "path": "/layout/blocks/1" }
+        ]));
+
+        let patched = apply_patch(&page(), &ops, &["updated_at"]).unwrap();
+        assert_eq!(
+            patched.layout,
+            json!({
+                "blocks": [
+                    { "type": "text", "props": { "body": "Hi" } },
+                    { "type": "divider" }
+                ]
+            })
+        );
+        assert_eq!(patched.title, "Home");
+    }
+
+    #[test]
+    fn nonexistent_path_is_rejected_and_nothing_is_applied() {
+        let ops = patch(json!([
+            { "op": "replace", "path": "/title", "value": "Changed" },
+            { "op": "replace", "path": "/layout/blocks/7/props/body", "value": "x" }
+        ]));
+
+        let (status, body) = apply_patch(&page(), &ops, &[]).unwrap_err().into_parts();
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(body.error.contains("operation 1"), "{}", body.error);
+
+        let ops = patch(json!([{ "op": "remove", "path": "/layout/missing" }]));
+        assert!(apply_patch(&page(), &ops, &[]).is_err());
+    }
+
+    #[test]
+    fn failed_test_operation_is_a_conflict() {
+        let ops = patch(json!([
+            { "op": "test", "path": "/updated_at", "value": "2023-12-31 00:00:00" },
+            { "op": "replace", "path": "/title", "value": "Changed" }
+        ]));
+        let status = apply_patch(&page(), &ops, &["updated_at"])
+            .unwrap_err()
+            .status();
+        assert_eq!(status, StatusCode::CONFLICT);
+    }
+
+    #[test]
+    fn read_only_and_unknown_fields_are_rejected() {
+        let ops = patch(json!([
+            { "op": "replace", "path": "/updated_at", "value": "2099-01-01 00:00:00" }
+        ]));
+        let status = apply_patch(&page(), &ops, &["updated_at"])
+            .unwrap_err()
+            .status();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let ops = patch(json!([{ "op": "add", "path": "/id", "value": "other" }]));
+        let status = apply_patch(&page(), &ops, &["updated_at"])
+            .unwrap_err()
+            .status();
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[test]
+    fn patch_size_and_operation_count_are_capped() {
+        let too_many: Vec<Value> = (0..=MAX_PATCH_OPERATIONS)
+            .map(|_| json!({ "op": "test", "path": "/title", "value": "Home" }))
+            .collect();
+        let status = parse_patch(Value::from(too_many).to_string().as_bytes())
+            .unwrap_err()
+            .status();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let huge = vec![b' '; MAX_PATCH_BYTES + 1];
+        let status = parse_patch(&huge).unwrap_err().status();
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+
+        assert!(parse_patch(b"[]").is_err());
+        assert!(parse_patch(b"{\"op\":\"remove\"}").is_err());
+    }
+}
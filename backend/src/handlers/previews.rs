@@ -0,0 +1,173 @@
+//! Preview Link Handlers
+//!
+//! Editors can share an unpublished page or post with a reviewer who has no
+//! account: `POST .../preview-token` returns a token that, passed as
+//! `?preview=<token>` to the public page or post endpoint, shows that one
+//! draft until it expires (`PREVIEW_TOKEN_TTL_HOURS`). `DELETE` on the same
+//! path revokes every link issued for it so far. See
+//! [`crate::security::preview_token`].
+
+use crate::{
+    db::DbPool,
+    handlers::common::{map_sqlx_error, require_permission},
+    models::*,
+    repositories,
+    security::{
+        auth::{self, Permission},
+        preview_token::{self, PreviewKind},
+    },
+};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::{Duration, SecondsFormat, Utc};
+use serde::Deserialize;
+
+/// `?preview=` query parameter of the public page and post endpoints.
+#[derive(Debug, Default, Deserialize)]
+pub struct PreviewQuery {
+    #[serde(default)]
+    pub preview: Option<String>,
+}
+
+/// Whether `token` opens the preview of page or post `id`.
+pub(crate) async fn preview_grants(
+    pool: &DbPool,
+    kind: PreviewKind,
+    id: &str,
+    token: Option<&str>,
+) -> Result<bool, AppError> {
+    let Some(token) = token.filter(|token| !token.trim().is_empty()) else {
+        return Ok(false);
+    };
+    let Some(nonce) = repositories::previews::preview_nonce(pool, kind, id)
+        .await
+        .map_err(internal_error("Failed to check preview link"))?
+    else {
+        return Ok(false);
+    };
+    Ok(preview_token::verify_preview_token(
+        token,
+        kind,
+        id,
+        &nonce,
+        Utc::now(),
+    ))
+}
+
+async fn issue(
+    pool: &DbPool,
+    claims: &auth::Claims,
+    kind: PreviewKind,
+    id: &str,
+) -> Result<Json<PreviewTokenResponse>, AppError> {
+    let label = match kind {
+        PreviewKind::Page => "Site page",
+        PreviewKind::Post => "Post",
+    };
+    let nonce = repositories::previews::ensure_preview_nonce(pool, kind, id)
+        .await
+        .map_err(|err| map_sqlx_error(err, label))?
+        .ok_or_else(|| not_found(format!("{label} not found")))?;
+
+    let ttl_hours = crate::config::get().preview_token_ttl_hours;
+    let expires_at = Utc::now() + Duration::hours(i64::from(ttl_hours));
+    let token =
+        preview_token::issue_preview_token(kind, id, &nonce, expires_at).map_err(|err| {
+            tracing::error!("Failed to sign preview token: {}", err);
+            internal_error_plain("Failed to create preview link")
+        })?;
+
+    tracing::info!(action = "issue_preview_token", user = %claims.sub, kind = kind.as_str(), id, "Editor created preview link");
+    repositories::audit::append_entry(
+        pool,
+        &claims.sub,
+        "preview_link",
+        kind.as_str(),
+        Some(id),
+        serde_json::json!({ "expires_at": expires_at.to_rfc3339_opts(SecondsFormat::Secs, true) }),
+    )
+    .await;
+
+    Ok(Json(PreviewTokenResponse {
+        token,
+        expires_at: expires_at.to_rfc3339_opts(SecondsFormat::Secs, true),
+    }))
+}
+
+async fn revoke(
+    pool: &DbPool,
+    claims: &auth::Claims,
+    kind: PreviewKind,
+    id: &str,
+) -> Result<StatusCode, AppError> {
+    let label = match kind {
+        PreviewKind::Page => "Site page",
+        PreviewKind::Post => "Post",
+    };
+    if !repositories::previews::clear_preview_nonce(pool, kind, id)
+        .await
+        .map_err(|err| map_sqlx_error(err, label))?
+    {
+        return Err(not_found(format!("{label} not found")));
+    }
+
+    tracing::info!(action = "revoke_preview_tokens", user = %claims.sub, kind = kind.as_str(), id, "Editor revoked preview links");
+    repositories::audit::append_entry(
+        pool,
+        &claims.sub,
+        "revoke_preview_links",
+        kind.as_str(),
+        Some(id),
+        serde_json::json!({}),
+    )
+    .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Handler for `POST /api/admin/pages/{id}/preview-token`.
+/// Admin-only, protected by CSRF.
+pub async fn issue_page_preview_token(
+    claims: auth::Claims,
+    State(pool): State<DbPool>,
+    Path(id): Path<String>,
+) -> Result<Json<PreviewTokenResponse>, AppError> {
+    require_permission(&claims, Permission::ManagePages)?;
+    issue(&pool, &claims, PreviewKind::Page, &id).await
+}
+
+/// Handler for `DELETE /api/admin/pages/{id}/preview-token`: revokes all
+/// preview links of the page. Admin-only, protected by CSRF.
+pub async fn revoke_page_preview_tokens(
+    claims: auth::Claims,
+    State(pool): State<DbPool>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, AppError> {
+    require_permission(&claims, Permission::ManagePages)?;
+    revoke(&pool, &claims, PreviewKind::Page, &id).await
+}
+
+/// Handler for `POST /api/admin/posts/{id}/preview-token`.
+/// Admins and editors, protected by CSRF.
+pub async fn issue_post_preview_token(
+    claims: auth::Claims,
+    State(pool): State<DbPool>,
+    Path(id): Path<String>,
+) -> Result<Json<PreviewTokenResponse>, AppError> {
+    require_permission(&claims, Permission::EditPosts)?;
+    issue(&pool, &claims, PreviewKind::Post, &id).await
+}
+
+/// Handler for `DELETE /api/admin/posts/{id}/preview-token`: revokes all
+/// preview links of the post. Admins and editors, protected by CSRF.
+pub async fn revoke_post_preview_tokens(
+    claims: auth::Claims,
+    State(pool): State<DbPool>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, AppError> {
+    require_permission(&claims, Permission::EditPosts)?;
+    revoke(&pool, &claims, PreviewKind::Post, &id).await
+}
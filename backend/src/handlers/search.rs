@@ -5,14 +5,29 @@
 //!
 //! # Endpoints
 //! - GET /api/search/tutorials: Search tutorials by keyword (public)
-//! - GET /api/search/topics: Get all unique topics (public)
+//! - GET /api/search/topics: Get all unique topics, with `?with_counts=true`
+//!   each with its number of tutorials (public)
+//! - GET /api/search/suggest: Title and topic suggestions while typing
+//!   (public, rate-limited)
+//! - GET /api/admin/search/status: Row counts of the index and its sources,
+//!   to detect drift (admin only)
+//! - POST /api/admin/search/reindex: Rebuild the index from scratch (admin
+//!   only, CSRF protected)
+//! - GET /api/admin/search/analytics: Top queries, queries without results
+//!   and daily volumes from the search log (admin only)
 //!
 //! # Search Features
 //! - Full-text search across title, description, content, and topics
 //! - Topic-based filtering (optional)
-//! - Pagination support (default 20 results, configurable)
-//! - Ranked results (FTS5 BM25 ranking algorithm)
+//! - Offset pagination (default 20 results, configurable) with the total
+//!   number of hits
+//! - Ranked results (FTS5 BM25 ranking algorithm), or newest/recently
+//!   updated first with `sort`
+//! - Creation date range via `created_after`/`created_before` (RFC 3339)
+//! - Draft tutorials only for callers who may edit tutorials
 //! - Query sanitization to prevent FTS5 syntax errors
+//! - Visitor searches are logged in the background for the analytics (see
+//!   [`crate::search_log`])
 //!
 //! # Query Processing
 //! - Splits query into tokens
@@ -26,13 +41,27 @@
 //! - Automatic index updates via triggers on tutorial changes
 //! - Result limit prevents excessive data transfer
 
-use crate::{db::DbPool, models::*};
+use crate::{
+    db::DbPool,
+    handlers::{
+        common::ensure_admin,
+        tutorials::{can_see_drafts, mask_authorship},
+    },
+    middleware::security as security_middleware,
+    models::*,
+    repositories::{self, search_index::IndexStatus},
+    search_log,
+    security::auth::{self, OptionalClaims},
+};
 use axum::{
-    extract::{Query, State},
+    extract::{ConnectInfo, Query, State},
+    http::HeaderMap,
     Json,
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::convert::TryInto;
+use std::net::SocketAddr;
+use std::time::Instant;
 
 /// Query parameters for searching tutorials
 #[derive(Deserialize)]
@@ -47,12 +76,63 @@ pub struct SearchQuery {
     /// Maximum number of results (default: 20)
     #[serde(default = "default_limit")]
     limit: i64,
+
+    /// Number of results to skip (default: 0)
+    #[serde(default)]
+    offset: i64,
+
+    /// Only tutorials created at or after this RFC 3339 time
+    #[serde(default)]
+    created_after: Option<String>,
+
+    /// Only tutorials created at or before this RFC 3339 time
+    #[serde(default)]
+    created_before: Option<String>,
+
+    /// `relevance` (default), `newest` or `updated`
+    #[serde(default)]
+    sort: Option<String>,
 }
 
 fn default_limit() -> i64 {
     20
 }
 
+/// Shortest query that gets suggestions.
+const MIN_SUGGEST_QUERY_CHARS: usize = 2;
+/// Most suggestions one request may ask for.
+const MAX_SUGGEST_LIMIT: i64 = 20;
+
+/// Query parameters for search suggestions
+#[derive(Deserialize)]
+pub struct SuggestQuery {
+    /// What has been typed so far
+    q: String,
+
+    /// Maximum number of suggestions (default: 8)
+    #[serde(default = "default_suggest_limit")]
+    limit: i64,
+}
+
+fn default_suggest_limit() -> i64 {
+    8
+}
+
+/// One search suggestion, kept small since it is fetched on every keystroke.
+#[derive(Debug, Serialize)]
+pub struct Suggestion {
+    /// `tutorial`, `post` or `topic`
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub label: String,
+    /// Tutorial ID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// `{page_slug}/{post_slug}` for posts
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slug: Option<String>,
+}
+
 /// Sanitizes a raw string into a format suitable for SQLite FTS5 queries.
 /// Removes special characters, handles prefix matching, and ensures tokens are quoted.
 pub fn sanitize_fts_query(raw: &str) -> Result<String, String> {
@@ -64,7 +144,8 @@ pub fn sanitize_fts_query(raw: &str) -> Result<String, String> {
             let sanitized: String = token
                 .chars()
                 .filter(|c| {
-                    c.is_ascii_alphanumeric()
+                    // Letters beyond ASCII ("Übung") are left to the tokenizer
+                    c.is_alphanumeric()
                         || matches!(
                             c,
                             '*' | '-'
@@ -133,7 +214,7 @@ pub fn sanitize_fts_query(raw: &str) -> Result<String, String> {
 }
 
 /// Escapes special characters for SQL LIKE patterns (`%`, `_`, and `\`).
-fn escape_like_pattern(value: &str) -> String {
+pub(crate) fn escape_like_pattern(value: &str) -> String {
     let mut escaped = String::with_capacity(value.len());
     for ch in value.chars() {
         match ch {
@@ -149,11 +230,99 @@ fn escape_like_pattern(value: &str) -> String {
     escaped
 }
 
-/// Searches tutorials using full-text and optional topic filtering.
+// `bm25()` (and other FTS5 auxiliary functions like `highlight()`/
+// `snippet()`) only recognize the FTS5 virtual table when it is referenced by
+// its real name, NOT through a JOIN alias -- aliasing `tutorials_fts` as `fts`
+// made SQLite fail every single search (filtered or not) with "no such
+// column: fts", since `bm25(fts)` could no longer resolve the table it was
+// ranking. Verified directly against SQLite: `bm25(<alias>)` errors while
+// `bm25(tutorials_fts)` with an unaliased join succeeds. The ESCAPE clause
+// must be a single-character string, since SQLite rejects a two-character one.
+//
+// The hit list and its count share this clause, so the total always agrees
+// with the items. `datetime()` normalizes stored timestamps of either
+// spelling (`2024-05-01 12:00:00`, RFC 3339) before they are compared.
+const SEARCH_FROM: &str = r#"
+    FROM tutorials t
+    INNER JOIN tutorials_fts ON t.id = tutorials_fts.tutorial_id
+    WHERE tutorials_fts MATCH ?
+    AND (? IS NULL OR t.topics LIKE ? ESCAPE '\')
+    AND (? IS NULL OR datetime(t.created_at) >= ?)
+    AND (? IS NULL OR datetime(t.created_at) <= ?)
+    AND t.deleted_at IS NULL AND (? OR t.status = ?)
+"#;
+
+/// Values bound into [`SEARCH_FROM`].
+struct SearchFilter<'a> {
+    /// Sanitized FTS5 query.
+    query: &'a str,
+    /// Escaped `LIKE` pattern the topics must match.
+    topic_pattern: Option<&'a str>,
+    /// Bounds in SQLite's `YYYY-MM-DD HH:MM:SS` UTC form.
+    created_after: Option<&'a str>,
+    created_before: Option<&'a str>,
+    include_drafts: bool,
+}
+
+fn bind_search_filter<'q, O>(
+    query: sqlx::query::QueryAs<'q, sqlx::Sqlite, O, sqlx::sqlite::SqliteArguments<'q>>,
+    filter: &SearchFilter<'q>,
+) -> sqlx::query::QueryAs<'q, sqlx::Sqlite, O, sqlx::sqlite::SqliteArguments<'q>> {
+    query
+        .bind(filter.query)
+        .bind(filter.topic_pattern)
+        .bind(filter.topic_pattern)
+        .bind(filter.created_after)
+        .bind(filter.created_after)
+        .bind(filter.created_before)
+        .bind(filter.created_before)
+        .bind(filter.include_drafts)
+        .bind(TUTORIAL_STATUS_PUBLISHED)
+}
+
+/// `ORDER BY` clause for a `sort` parameter. Only these fixed clauses ever
+/// reach the SQL.
+fn search_order(sort: Option<&str>) -> Result<&'static str, AppError> {
+    match sort.map(str::trim).filter(|sort| !sort.is_empty()) {
+        None | Some("relevance") => Ok("bm25(tutorials_fts)"),
+        Some("newest") => Ok("datetime(t.created_at) DESC, t.id"),
+        Some("updated") => Ok("datetime(t.updated_at) DESC, t.id"),
+        Some(_) => Err(bad_request(
+            "'sort' must be 'relevance', 'newest' or 'updated'",
+        )),
+    }
+}
+
+/// Parses an optional RFC 3339 bound into SQLite's UTC `datetime()` form.
+fn parse_search_bound(value: Option<&str>, name: &str) -> Result<Option<String>, AppError> {
+    let Some(value) = value.map(str::trim).filter(|value| !value.is_empty()) else {
+        return Ok(None);
+    };
+    chrono::DateTime::parse_from_rfc3339(value)
+        .map(|time| {
+            Some(
+                time.with_timezone(&chrono::Utc)
+                    .format("%Y-%m-%d %H:%M:%S")
+                    .to_string(),
+            )
+        })
+        .map_err(|_| {
+            bad_request(format!(
+                "'{name}' must be an RFC 3339 date-time such as 2024-05-01T00:00:00Z"
+            ))
+        })
+}
+
+/// Searches tutorials using full-text and optional topic and creation date
+/// filtering. The first page of a visitor's search is logged; editors, who
+/// also search drafts, are left out of the analytics.
 pub async fn search_tutorials(
     State(pool): State<DbPool>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    OptionalClaims(claims): OptionalClaims,
     Query(params): Query<SearchQuery>,
-) -> Result<Json<Vec<TutorialResponse>>, ApiError> {
+) -> Result<Json<Paginated<TutorialResponse>>, AppError> {
     // Basic validation: search query can't be just whitespace
     if params.q.trim().is_empty() {
         return Err(bad_request("Search query cannot be empty"));
@@ -164,8 +333,10 @@ pub async fn search_tutorials(
         return Err(bad_request("Search query too long"));
     }
 
-    // Set reasonable bounds on total results
+    // Set reasonable bounds on the page
     let limit = params.limit.clamp(1, 100);
+    let offset = params.offset.max(0);
+    let include_drafts = can_see_drafts(claims.as_ref());
 
     // Sanitize the user input for FTS5 engine
     let search_query = sanitize_fts_query(params.q.trim()).map_err(bad_request)?;
@@ -181,75 +352,369 @@ pub async fn search_tutorials(
         }
     });
 
-    // Execute the search query
-    //
-    // SECURITY/CORRECTNESS: `bm25()` (and other FTS5 auxiliary functions like
-    // `highlight()`/`snippet()`) only recognize the FTS5 virtual table when it
-    // is referenced by its real name, NOT through a JOIN alias -- aliasing
-    // `tutorials_fts` as `fts` here made SQLite fail every single search
-    // (filtered or not) with "no such column: fts", since `bm25(fts)` could
-    // no longer resolve the table it was ranking. Verified directly against
-    // SQLite: `bm25(<alias>)` errors while `bm25(tutorials_fts)` with an
-    // unaliased join succeeds. Also fixed the ESCAPE clause below to a
-    // single-character string, since SQLite rejects a two-character one.
-    let tutorials = if let Some(pattern) = topic_pattern {
-        // Query variant that includes topic filtering
-        sqlx::query_as::<_, Tutorial>(
-            r#"
-            SELECT t.* FROM tutorials t
-            INNER JOIN tutorials_fts ON t.id = tutorials_fts.tutorial_id
-            WHERE tutorials_fts MATCH ?
-            AND t.topics LIKE ? ESCAPE '\'
-            ORDER BY bm25(tutorials_fts)
-            LIMIT ?
-            "#,
-        )
-        .bind(&search_query) // Bind the FTS sanitized query
-        .bind(&pattern) // Bind the LIKE pattern for topics
-        .bind(limit) // Bind the result limit
+    let created_after = parse_search_bound(params.created_after.as_deref(), "created_after")?;
+    let created_before = parse_search_bound(params.created_before.as_deref(), "created_before")?;
+    if let (Some(after), Some(before)) = (&created_after, &created_before) {
+        if after > before {
+            return Err(bad_request(
+                "'created_after' must not be later than 'created_before'",
+            ));
+        }
+    }
+    let order = search_order(params.sort.as_deref())?;
+
+    let filter = SearchFilter {
+        query: &search_query,
+        topic_pattern: topic_pattern.as_deref(),
+        created_after: created_after.as_deref(),
+        created_before: created_before.as_deref(),
+        include_drafts,
+    };
+    let items_sql = format!("SELECT t.* {SEARCH_FROM} ORDER BY {order} LIMIT ? OFFSET ?");
+    let tutorials = bind_search_filter(sqlx::query_as::<_, Tutorial>(&items_sql), &filter)
+        .bind(limit)
+        .bind(offset)
         .fetch_all(&pool)
         .await
-    } else {
-        // Simple full-text search without topic filter
-        sqlx::query_as::<_, Tutorial>(
-            r#"
-            SELECT t.* FROM tutorials t
-            INNER JOIN tutorials_fts ON t.id = tutorials_fts.tutorial_id
-            WHERE tutorials_fts MATCH ?
-            ORDER BY bm25(tutorials_fts)
-            LIMIT ?
-            "#,
-        )
-        .bind(&search_query) // Bind the FTS sanitized query
-        .bind(limit) // Bind the result limit
-        .fetch_all(&pool)
+        .map_err(internal_error("Failed to search tutorials"))?;
+
+    let count_sql = format!("SELECT COUNT(*) {SEARCH_FROM}");
+    let (total,): (i64,) = bind_search_filter(sqlx::query_as(&count_sql), &filter)
+        .fetch_one(&pool)
         .await
+        .map_err(internal_error("Failed to search tutorials"))?;
+
+    if offset == 0 && !include_drafts {
+        let client_ip = security_middleware::extract_client_ip(&headers, addr.ip());
+        search_log::record(&pool, &params.q, total, client_ip);
     }
-    .map_err(internal_error("Failed to search tutorials"))?;
 
     // Convert raw tutorial records into mapped responses
     let mut responses = Vec::with_capacity(tutorials.len());
     for tutorial in tutorials {
         // Try to convert each record; this handles JSON parsing of topics
-        let response: TutorialResponse = tutorial.try_into().map_err(|err: String| {
+        let mut response: TutorialResponse = tutorial.try_into().map_err(|err: String| {
             tracing::error!("Tutorial data corruption detected: {}", err);
             internal_error_plain("Failed to parse tutorial data")
         })?;
+        if !include_drafts {
+            mask_authorship(&mut response.created_by, &mut response.updated_by);
+        }
         responses.push(response);
     }
 
-    Ok(Json(responses))
+    Ok(Json(Paginated::new(responses, total, limit, offset)))
 }
 
-/// Retrieves a list of all unique topics currently available in tutorials.
-pub async fn get_all_topics(State(pool): State<DbPool>) -> Result<Json<Vec<String>>, ApiError> {
+/// Suggests tutorial titles, post titles and topics starting with what has
+/// been typed. Tutorial titles are matched with an FTS5 prefix query; posts
+/// have no search index, so their titles and the topics are matched with
+/// `LIKE` on word starts. Duplicates are dropped and labels that start with
+/// the query come first.
+pub async fn suggest(
+    State(pool): State<DbPool>,
+    OptionalClaims(claims): OptionalClaims,
+    Query(params): Query<SuggestQuery>,
+) -> Result<Json<Vec<Suggestion>>, AppError> {
+    let q = params.q.trim();
+    if q.chars().count() < MIN_SUGGEST_QUERY_CHARS {
+        return Err(bad_request(format!(
+            "Query must be at least {MIN_SUGGEST_QUERY_CHARS} characters"
+        )));
+    }
+    if q.len() > 100 {
+        return Err(bad_request("Search query too long"));
+    }
+    let limit = params.limit.clamp(1, MAX_SUGGEST_LIMIT);
+    let include_drafts = can_see_drafts(claims.as_ref());
+
+    let title_query = format!("title : ({})", sanitize_fts_query(q).map_err(bad_request)?);
+    let tutorials: Vec<(String, String)> = sqlx::query_as(concat!(
+        "SELECT t.id, t.title FROM tutorials t ",
+        "INNER JOIN tutorials_fts ON t.id = tutorials_fts.tutorial_id ",
+        "WHERE tutorials_fts MATCH ? AND t.deleted_at IS NULL AND (? OR t.status = ?) ",
+        "ORDER BY bm25(tutorials_fts) LIMIT ?"
+    ))
+    .bind(&title_query)
+    .bind(include_drafts)
+    .bind(TUTORIAL_STATUS_PUBLISHED)
+    .bind(limit)
+    .fetch_all(&pool)
+    .await
+    .map_err(internal_error("Failed to load suggestions"))?;
+
+    let escaped = escape_like_pattern(q);
+    let starts_with = format!("{escaped}%");
+    let word_starts_with = format!("% {escaped}%");
+    let posts: Vec<(String, String, String)> = sqlx::query_as(concat!(
+        "SELECT p.title, pg.slug, p.slug FROM site_posts p ",
+        "INNER JOIN site_pages pg ON pg.id = p.page_id ",
+        "WHERE p.is_published = 1 AND pg.is_published = 1 ",
+        "AND p.deleted_at IS NULL AND pg.deleted_at IS NULL ",
+        "AND (p.title LIKE ? ESCAPE '\\' OR p.title LIKE ? ESCAPE '\\') ",
+        "ORDER BY p.title LIMIT ?"
+    ))
+    .bind(&starts_with)
+    .bind(&word_starts_with)
+    .bind(limit)
+    .fetch_all(&pool)
+    .await
+    .map_err(internal_error("Failed to load suggestions"))?;
+
+    let topics: Vec<(String,)> = sqlx::query_as(concat!(
+        "SELECT DISTINCT tt.topic FROM tutorial_topics tt ",
+        "INNER JOIN tutorials t ON t.id = tt.tutorial_id ",
+        "WHERE t.status = ? AND t.deleted_at IS NULL ",
+        "AND (tt.topic LIKE ? ESCAPE '\\' OR tt.topic LIKE ? ESCAPE '\\') ",
+        "ORDER BY tt.topic LIMIT ?"
+    ))
+    .bind(TUTORIAL_STATUS_PUBLISHED)
+    .bind(&starts_with)
+    .bind(&word_starts_with)
+    .bind(limit)
+    .fetch_all(&pool)
+    .await
+    .map_err(internal_error("Failed to load suggestions"))?;
+
+    let candidates = tutorials
+        .into_iter()
+        .map(|(id, title)| Suggestion {
+            kind: "tutorial",
+            label: title,
+            id: Some(id),
+            slug: None,
+        })
+        .chain(posts.into_iter().map(|(title, page, post)| Suggestion {
+            kind: "post",
+            label: title,
+            id: None,
+            slug: Some(format!("{page}/{post}")),
+        }))
+        .chain(topics.into_iter().map(|(topic,)| Suggestion {
+            kind: "topic",
+            label: topic,
+            id: None,
+            slug: None,
+        }));
+
+    Ok(Json(rank_suggestions(candidates, q, limit as usize)))
+}
+
+/// Drops repeated labels of the same type and moves labels that start with
+/// `q` to the front, keeping the order within each group.
+fn rank_suggestions(
+    candidates: impl Iterator<Item = Suggestion>,
+    q: &str,
+    limit: usize,
+) -> Vec<Suggestion> {
+    let q = q.to_lowercase();
+    let mut seen = std::collections::HashSet::new();
+    let mut suggestions: Vec<Suggestion> = candidates
+        .filter(|s| seen.insert((s.kind, s.label.to_lowercase())))
+        .collect();
+    suggestions.sort_by_key(|s| !s.label.to_lowercase().starts_with(&q));
+    suggestions.truncate(limit);
+    suggestions
+}
+
+/// Query parameters for the topic list
+#[derive(Deserialize)]
+pub struct TopicListQuery {
+    /// Return `[{ topic, count }]` instead of bare names
+    #[serde(default)]
+    with_counts: bool,
+}
+
+/// Retrieves a list of all unique topics currently available in published
+/// tutorials, optionally with how many tutorials carry each.
+pub async fn get_all_topics(
+    State(pool): State<DbPool>,
+    Query(params): Query<TopicListQuery>,
+) -> Result<Json<TopicListResponse>, AppError> {
+    if params.with_counts {
+        let counts: Vec<TopicCount> = sqlx::query_as(concat!(
+            "SELECT tt.topic, COUNT(DISTINCT tt.tutorial_id) AS count FROM tutorial_topics tt ",
+            "INNER JOIN tutorials t ON t.id = tt.tutorial_id ",
+            "WHERE t.status = ? AND t.deleted_at IS NULL ",
+            "GROUP BY tt.topic ORDER BY tt.topic ASC"
+        ))
+        .bind(TUTORIAL_STATUS_PUBLISHED)
+        .fetch_all(&pool)
+        .await
+        .map_err(internal_error("Failed to fetch topics"))?;
+        return Ok(Json(TopicListResponse::Counts(counts)));
+    }
+
     // Select unique topics from the denormalized tutorial_topics table
-    let topics: Vec<(String,)> =
-        sqlx::query_as("SELECT DISTINCT topic FROM tutorial_topics ORDER BY topic ASC")
-            .fetch_all(&pool)
-            .await
-            .map_err(internal_error("Failed to fetch topics"))?;
+    let topics: Vec<(String,)> = sqlx::query_as(concat!(
+        "SELECT DISTINCT tt.topic FROM tutorial_topics tt ",
+        "INNER JOIN tutorials t ON t.id = tt.tutorial_id ",
+        "WHERE t.status = ? AND t.deleted_at IS NULL ORDER BY tt.topic ASC"
+    ))
+    .bind(TUTORIAL_STATUS_PUBLISHED)
+    .fetch_all(&pool)
+    .await
+    .map_err(internal_error("Failed to fetch topics"))?;
 
     // Extract strings from the tuple and return as a list
-    Ok(Json(topics.into_iter().map(|(t,)| t).collect()))
+    Ok(Json(TopicListResponse::Names(
+        topics.into_iter().map(|(t,)| t).collect(),
+    )))
+}
+
+/// Body of `GET /api/admin/search/status`.
+#[derive(Serialize)]
+pub struct SearchIndexStatusResponse {
+    pub indexes: Vec<IndexStatus>,
+    /// Tokenizer a rebuild would use (`FTS_TOKENIZER`); indexes that report
+    /// another one need a rebuild to pick it up.
+    pub configured_tokenizer: String,
+}
+
+/// Rows written to one index by a rebuild.
+#[derive(Serialize)]
+pub struct ReindexedIndex {
+    pub index: &'static str,
+    pub indexed: u64,
+}
+
+/// Body of `POST /api/admin/search/reindex`.
+#[derive(Serialize)]
+pub struct ReindexResponse {
+    pub indexes: Vec<ReindexedIndex>,
+    pub duration_ms: u64,
+}
+
+/// Handler for `GET /api/admin/search/status`: how many rows each index
+/// holds against how many it should. Admin-only.
+pub async fn search_index_status(
+    claims: auth::Claims,
+    State(pool): State<DbPool>,
+) -> Result<Json<SearchIndexStatusResponse>, AppError> {
+    ensure_admin(&claims)?;
+
+    let tutorials = repositories::search_index::tutorials_status(&pool)
+        .await
+        .map_err(internal_error("Failed to check search index"))?;
+    Ok(Json(SearchIndexStatusResponse {
+        indexes: vec![tutorials],
+        configured_tokenizer: crate::config::get().fts_tokenizer.clone(),
+    }))
+}
+
+/// Handler for `POST /api/admin/search/reindex`: recreates every index with
+/// the configured `FTS_TOKENIZER` and fills it from its source tables.
+/// Admin-only, protected by CSRF.
+pub async fn reindex_search(
+    claims: auth::Claims,
+    State(pool): State<DbPool>,
+) -> Result<Json<ReindexResponse>, AppError> {
+    ensure_admin(&claims)?;
+
+    let started = Instant::now();
+    let tokenizer = &crate::config::get().fts_tokenizer;
+    let tutorials = repositories::search_index::rebuild_tutorials(&pool, tokenizer)
+        .await
+        .map_err(internal_error("Failed to rebuild search index"))?;
+    let duration_ms = u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX);
+
+    tracing::info!(action = "reindex_search", user = %claims.sub, tutorials, duration_ms, "Admin rebuilt search index");
+    Ok(Json(ReindexResponse {
+        indexes: vec![ReindexedIndex {
+            index: "tutorials_fts",
+            indexed: tutorials,
+        }],
+        duration_ms,
+    }))
+}
+
+/// Longest period the search analytics may cover.
+const MAX_ANALYTICS_DAYS: i64 = 365;
+/// Queries listed per ranking in the search analytics.
+const ANALYTICS_TOP_QUERIES: i64 = 25;
+
+/// Handler for `GET /api/admin/search/analytics`: what visitors searched
+/// for in the last `days` days (default 30), which searches found nothing
+/// and how many searches there were per day. Admin-only.
+pub async fn search_analytics(
+    claims: auth::Claims,
+    State(pool): State<DbPool>,
+    Query(params): Query<SearchAnalyticsQuery>,
+) -> Result<Json<SearchAnalyticsResponse>, AppError> {
+    ensure_admin(&claims)?;
+
+    let days = params.days.unwrap_or(30);
+    if !(1..=MAX_ANALYTICS_DAYS).contains(&days) {
+        return Err(bad_request(format!(
+            "'days' must be between 1 and {MAX_ANALYTICS_DAYS}"
+        )));
+    }
+
+    let top_queries =
+        repositories::search_log::top_queries(&pool, days, false, ANALYTICS_TOP_QUERIES)
+            .await
+            .map_err(internal_error("Failed to load search analytics"))?;
+    let zero_result_queries =
+        repositories::search_log::top_queries(&pool, days, true, ANALYTICS_TOP_QUERIES)
+            .await
+            .map_err(internal_error("Failed to load search analytics"))?;
+    let daily = repositories::search_log::daily_volumes(&pool, days)
+        .await
+        .map_err(internal_error("Failed to load search analytics"))?;
+
+    Ok(Json(SearchAnalyticsResponse {
+        days,
+        retention_days: crate::config::get().search_log_retention_days,
+        total_searches: daily.iter().map(|day| day.searches).sum(),
+        top_queries,
+        zero_result_queries,
+        daily,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn suggestion(kind: &'static str, label: &str) -> Suggestion {
+        Suggestion {
+            kind,
+            label: label.to_string(),
+            id: None,
+            slug: None,
+        }
+    }
+
+    #[test]
+    fn suggestions_put_exact_prefixes_first_without_duplicates() {
+        let ranked = rank_suggestions(
+            [
+                suggestion("tutorial", "Advanced terminal tricks"),
+                suggestion("tutorial", "Terminal basics"),
+                suggestion("post", "Terminal basics"),
+                suggestion("topic", "terminal"),
+                suggestion("topic", "Terminal"),
+            ]
+            .into_iter(),
+            "Ter",
+            10,
+        );
+        let labels: Vec<(&str, &str)> = ranked.iter().map(|s| (s.kind, s.label.as_str())).collect();
+        assert_eq!(
+            labels,
+            [
+                ("tutorial", "Terminal basics"),
+                ("post", "Terminal basics"),
+                ("topic", "terminal"),
+                ("tutorial", "Advanced terminal tricks"),
+            ]
+        );
+
+        let ranked = rank_suggestions(
+            [suggestion("topic", "a1"), suggestion("topic", "a2")].into_iter(),
+            "a",
+            1,
+        );
+        assert_eq!(ranked.len(), 1);
+    }
 }
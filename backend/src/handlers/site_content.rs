@@ -6,200 +6,129 @@
 //! schema-less landing page design.
 
 use crate::{
-    db,
+    cache, db,
+    handlers::{
+        common::{conditional_json, require_permission, DryRunQuery},
+        content_sections,
+    },
     models::{
-        api_error, bad_request, forbidden, internal_error, not_found, ApiError,
-        SiteContentListResponse, SiteContentResponse, UpdateSiteContentRequest,
+        api_error, bad_request, internal_error, is_starter_site_title, not_found, AppError,
+        PublicSettings, SiteContentListResponse, SiteContentResponse, SiteContentRevisionResponse,
+        SiteContentRevisionSummary, UpdateSiteContentRequest,
     },
     repositories,
-    security::auth,
+    schemas::Schema,
+    security::auth::{self, Permission},
 };
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
     Json,
 };
 use serde_json::Value;
-use std::collections::HashSet;
 
 /// Maximum size allowed for a single content section's JSON payload (5MB)
 const MAX_CONTENT_BYTES: usize = 5_000_000;
 
-/// A globally initialized set of section names that the API is allowed to manage.
-/// Prevents accidental or malicious creation of arbitrary content sections.
-fn allowed_sections() -> &'static HashSet<&'static str> {
-    use std::sync::OnceLock;
-
-    static ALLOWED: OnceLock<HashSet<&'static str>> = OnceLock::new();
-    ALLOWED.get_or_init(|| {
-        [
-            "hero",             // Landing page hero
-            "tutorial_section", // Tutorial overview header
-            "header",           // Main navigation
-            "footer",           // Footer links/info
-            "site_meta",        // SEO titles/description
-            "stats",            // Numbers/stats display
-            "cta_section",      // Call to action
-            "about",            // Personal homepage introduction
-            "settings",         // System-wide toggles
-            "login",            // Custom login page text
-        ]
-        .into_iter()
-        .collect()
-    })
-}
+/// Upper bound for the plain-text `robots` section body (32KB).
+const MAX_ROBOTS_BYTES: usize = 32 * 1024;
+
+/// Upper bound for the plain-text `security_txt` section body (8KB).
+const MAX_SECURITY_TXT_BYTES: usize = 8 * 1024;
 
-/// Validates if a section name is within the whitelist of allowed sections.
-fn validate_section(section: &str) -> Result<(), ApiError> {
-    if allowed_sections().contains(section) {
+/// Checks the section is registered in `content_sections`. Unknown names
+/// are reported as missing, so the API never creates arbitrary sections.
+async fn validate_section(pool: &db::DbPool, section: &str) -> Result<(), AppError> {
+    let registered = content_sections::is_registered(pool, section)
+        .await
+        .map_err(internal_error("Failed to load content sections"))?;
+    if registered {
         Ok(())
     } else {
         Err(not_found(format!("Unknown content section '{section}'")))
     }
 }
 
-/// Dispatches validation to section-specific structure checkers.
-/// Ensures the incoming JSON follows the expected format for that section.
-fn validate_content_structure(section: &str, content: &Value) -> Result<(), ApiError> {
+/// Dispatches validation to the section's JSON Schema (see [`crate::schemas`])
+/// or, for the plain-text and runtime-registered sections, a structure checker.
+/// A rejection lists the JSON pointers of the first few violations.
+pub(crate) fn validate_content_structure(section: &str, content: &Value) -> Result<(), AppError> {
+    let invalid = |detail: &str| {
+        bad_request(format!(
+            "Invalid structure for section '{section}': {detail}"
+        ))
+    };
+
+    if let Some(schema) = Schema::for_section(section) {
+        return schema
+            .validate(content)
+            .map_err(|violations| invalid(&violations.join("; ")));
+    }
+
     let result = match section {
-        "hero" => validate_hero_structure(content),
-        "tutorial_section" => validate_tutorial_section_structure(content),
-        "header" => validate_header_structure(content),
-        "footer" => validate_footer_structure(content),
-        "settings" => validate_settings_structure(content),
-        "site_meta" => validate_site_meta_structure(content),
         "game_config" => Ok(()), // Legacy/Future use
         "stats" => Ok(()),
         "cta_section" => Ok(()),
-        "login" => validate_login_structure(content),
-        _ => Ok(()),
+        "robots" => validate_plain_text_structure(content, MAX_ROBOTS_BYTES),
+        "security_txt" => validate_plain_text_structure(content, MAX_SECURITY_TXT_BYTES),
+        // Sections registered at runtime have no dedicated validator
+        _ => validate_generic_structure(content),
     };
 
-    result.map_err(|err| bad_request(format!("Invalid structure for section '{section}': {err}")))
+    result.map_err(invalid)
 }
 
-/// Validates the site metadata (SEO) structure.
-fn validate_site_meta_structure(content: &Value) -> Result<(), &'static str> {
-    let obj = content.as_object().ok_or("Expected JSON object")?;
-    // Ensure basic SEO fields are present
-    if !obj.contains_key("title") {
-        return Err("Missing required field 'title'");
-    }
-    if !obj.contains_key("description") {
-        return Err("Missing required field 'description'");
-    }
-    // Perform type checking on optional fields
-    if let Some(kw) = obj.get("keywords") {
-        if !kw.is_string() {
-            return Err("Field 'keywords' must be a string");
-        }
+/// Fallback for sections without a dedicated validator: any JSON object.
+/// The size limit is enforced separately by [`validate_content_size`].
+fn validate_generic_structure(content: &Value) -> Result<(), &'static str> {
+    if content.is_object() {
+        Ok(())
+    } else {
+        Err("Expected JSON object")
     }
-    Ok(())
 }
 
-/// Validates the hero section structure.
-fn validate_hero_structure(content: &Value) -> Result<(), &'static str> {
+/// Validates sections that are served as plain-text files (`robots`,
+/// `security_txt`). The body lives in a single `content` string; markup and
+/// control characters other than line breaks and tabs are rejected so the
+/// file can't be mistaken for HTML by a sniffing client.
+fn validate_plain_text_structure(content: &Value, max_bytes: usize) -> Result<(), &'static str> {
     let obj = content.as_object().ok_or("Expected JSON object")?;
-    // Hero needs a title and a list of feature highlights
-    if !obj.contains_key("title") || !obj.contains_key("features") {
-        return Err("Missing required fields 'title' or 'features'");
-    }
-    if !obj.get("features").map(|v| v.is_array()).unwrap_or(false) {
-        return Err("Field 'features' must be an array");
-    }
-    Ok(())
-}
+    let text = obj
+        .get("content")
+        .and_then(Value::as_str)
+        .ok_or("Field 'content' must be a string")?;
 
-/// Validates the tutorial overview section structure.
-fn validate_tutorial_section_structure(content: &Value) -> Result<(), &'static str> {
-    let obj = content.as_object().ok_or("Expected JSON object")?;
-    if !obj.contains_key("title") || !obj.contains_key("description") {
-        return Err("Missing required fields 'title' or 'description'");
+    if text.len() > max_bytes {
+        return Err("Field 'content' is too large");
     }
-    Ok(())
-}
-
-/// Validates the global header (navigation) structure.
-fn validate_header_structure(content: &Value) -> Result<(), &'static str> {
-    let obj = content.as_object().ok_or("Expected JSON object")?;
-    // Must have brand info (logo/name) and a list of nav items
-    if !obj.contains_key("brand") || !obj.contains_key("navItems") {
-        return Err("Missing required fields 'brand' or 'navItems'");
+    if text
+        .chars()
+        .any(|c| c.is_control() && !matches!(c, '\n' | '\r' | '\t'))
+    {
+        return Err("Field 'content' contains control characters");
     }
-
-    // Check if navItems is an array.
-    let nav_items = obj
-        .get("navItems")
-        .and_then(|v| v.as_array())
-        .ok_or("Field 'navItems' must be an array")?;
-
-    // Each item needs an id, a label, and a valid target such as path, slug, or URL.
-    for item in nav_items {
-        let item_obj = item.as_object().ok_or("Nav item must be an object")?;
-
-        if !item_obj.contains_key("id") || !item_obj.contains_key("label") {
-            return Err("Nav item missing required fields 'id' or 'label'");
-        }
-
-        // Check for at least one target field if it's not a section header (type="section" might not need a path)
-        let has_target = item_obj.contains_key("path")
-            || item_obj.contains_key("slug")
-            || item_obj.contains_key("url")
-            || item_obj.contains_key("value")
-            || item_obj
-                .get("type")
-                .map(|v| v == "section")
-                .unwrap_or(false);
-
-        if !has_target {
-            return Err(
-                "Nav item must have a target (path, slug, url, or value) or be type='section'",
-            );
-        }
-
-        // Validate slug is not empty if present
-        if let Some(slug) = item_obj.get("slug").and_then(|v| v.as_str()) {
-            if slug.trim().is_empty() {
-                return Err("Nav item slug cannot be empty");
-            }
-        }
-    }
-    Ok(())
-}
-
-/// Validates the global footer structure.
-fn validate_footer_structure(content: &Value) -> Result<(), &'static str> {
-    let obj = content.as_object().ok_or("Expected JSON object")?;
-    if !obj.contains_key("brand") || !obj.contains_key("quickLinks") {
-        return Err("Missing required fields 'brand' or 'quickLinks'");
+    if text.contains('<') {
+        return Err("Field 'content' must be plain text");
     }
     Ok(())
 }
 
-/// Validates global site settings structure.
-fn validate_settings_structure(content: &Value) -> Result<(), &'static str> {
-    let obj = content.as_object().ok_or("Expected JSON object")?;
-    // Example: check for a boolean PDF toggle
-    if let Some(val) = obj.get("pdfEnabled") {
-        if !val.is_boolean() {
-            return Err("Field 'pdfEnabled' must be a boolean");
-        }
-    }
-    Ok(())
-}
-
-/// Validates customizations for the login page.
-fn validate_login_structure(content: &Value) -> Result<(), &'static str> {
-    let obj = content.as_object().ok_or("Expected JSON object")?;
-    // Ensure the login page at least defines a welcome title
-    if !obj.contains_key("title") {
-        return Err("Missing required field 'title'");
+/// Extracts the plain-text body of a `robots`/`security_txt` record, or
+/// `None` when the stored value is empty or not in the expected shape.
+pub(crate) fn plain_text_body(record: &crate::models::SiteContent) -> Option<String> {
+    let value: Value = serde_json::from_str(&record.content_json).ok()?;
+    let text = value.get("content")?.as_str()?;
+    if text.trim().is_empty() {
+        None
+    } else {
+        Some(text.to_string())
     }
-    Ok(())
 }
 
 /// Ensures the size of the serialized JSON doesn't exceed the safe threshold.
-fn validate_content_size(content: &Value) -> Result<(), ApiError> {
+pub(crate) fn validate_content_size(content: &Value) -> Result<(), AppError> {
     match serde_json::to_string(content) {
         // If length is within boundaries, accept it
         Ok(serialized) if serialized.len() <= MAX_CONTENT_BYTES => Ok(()),
@@ -215,7 +144,7 @@ fn validate_content_size(content: &Value) -> Result<(), ApiError> {
 
 /// Maps a database content record to a public response structure.
 /// Involves decoding the stored JSON string back into a JSON object.
-fn map_record(record: crate::models::SiteContent) -> Result<SiteContentResponse, ApiError> {
+fn map_record(record: crate::models::SiteContent) -> Result<SiteContentResponse, AppError> {
     // Attempt to parse the stored string from the 'content_json' table column
     let content: Value = serde_json::from_str(&record.content_json)
         .map_err(internal_error("Failed to parse stored content"))?;
@@ -229,30 +158,32 @@ fn map_record(record: crate::models::SiteContent) -> Result<SiteContentResponse,
 }
 
 /// Handler to fetch all managed site content sections in bulk.
-pub async fn list_site_content(
-    State(pool): State<db::DbPool>,
-) -> Result<Json<SiteContentListResponse>, ApiError> {
-    // Fetch all records from the site_content table
-    let records = repositories::content::fetch_all_site_content(&pool)
+/// Served from a cache, reported in `Cache-Status`.
+pub async fn list_site_content(State(pool): State<db::DbPool>) -> Result<Response, AppError> {
+    // Fetch all records from the site_content table, or the cache
+    let (records, cache_status) = repositories::content::fetch_all_site_content_cached(&pool)
         .await
         .map_err(internal_error("Failed to load site content"))?;
 
     // Convert each record from string-based JSON to object-based JSON
     let mut items = Vec::with_capacity(records.len());
-    for record in records {
-        items.push(map_record(record)?);
+    for record in records.iter() {
+        items.push(map_record(record.clone())?);
     }
 
-    Ok(Json(SiteContentListResponse { items }))
+    Ok(cache::with_cache_status(
+        Json(SiteContentListResponse { items }).into_response(),
+        cache_status,
+    ))
 }
 
 /// Handler to fetch a single content section by its name.
 pub async fn get_site_content(
     State(pool): State<db::DbPool>,
     Path(section): Path<String>,
-) -> Result<Json<SiteContentResponse>, ApiError> {
+) -> Result<Json<SiteContentResponse>, AppError> {
     // Security check: only allow pre-defined sections
-    validate_section(&section)?;
+    validate_section(&pool, &section).await?;
 
     // Retrieve from database
     let record = repositories::content::fetch_site_content_by_section(&pool, &section)
@@ -266,35 +197,227 @@ pub async fn get_site_content(
 }
 
 /// Handler to update or create a content section.
-/// Admin-only endpoint with strict validation on structure and size.
+/// Admin-only endpoint with strict validation on structure and size;
+/// `?dry_run=true` only runs the validation.
 pub async fn update_site_content(
     claims: auth::Claims,
     State(pool): State<db::DbPool>,
     Path(section): Path<String>,
+    Query(params): Query<DryRunQuery>,
     Json(payload): Json<UpdateSiteContentRequest>,
-) -> Result<Json<SiteContentResponse>, ApiError> {
-    // RBAC: Verify user has 'admin' role
-    if claims.role != "admin" {
-        return Err(forbidden("Insufficient permissions"));
-    }
+) -> Result<Response, AppError> {
+    // RBAC: Site content is admin-managed; editors cannot change it
+    require_permission(&claims, Permission::ManageSiteContent)?;
 
     // Comprehensive validation
-    validate_section(&section)?; // Whitelist check
+    validate_section(&pool, &section).await?; // Whitelist check
     validate_content_size(&payload.content)?; // Size sanity check
     validate_content_structure(&section, &payload.content)?; // Format correctness check
+    if params.dry_run {
+        return Ok(StatusCode::NO_CONTENT.into_response());
+    }
 
     // Upsert (Insert or Update) in database
-    let record = repositories::content::upsert_site_content(&pool, &section, &payload.content)
-        .await
-        .map_err(internal_error("Failed to update site content"))?;
+    let record =
+        repositories::content::upsert_site_content(&pool, &section, &payload.content, &claims.sub)
+            .await
+            .map_err(internal_error("Failed to update site content"))?;
+
+    repositories::audit::append_entry(
+        &pool,
+        &claims.sub,
+        "update",
+        "site_content",
+        Some(&section),
+        serde_json::json!({}),
+    )
+    .await;
 
     // Return the updated state
+    Ok(Json(map_record(record)?).into_response())
+}
+
+/// Handler listing the saved versions of a section, newest first.
+/// Admin-only.
+pub async fn list_site_content_revisions(
+    claims: auth::Claims,
+    State(pool): State<db::DbPool>,
+    Path(section): Path<String>,
+) -> Result<Json<Vec<SiteContentRevisionSummary>>, AppError> {
+    require_permission(&claims, Permission::ManageSiteContent)?;
+    validate_section(&pool, &section).await?;
+
+    let revisions = repositories::content::list_revisions(&pool, &section)
+        .await
+        .map_err(internal_error("Failed to load site content revisions"))?;
+
+    Ok(Json(revisions))
+}
+
+/// Handler returning one saved version of a section in full.
+/// Admin-only.
+pub async fn get_site_content_revision(
+    claims: auth::Claims,
+    State(pool): State<db::DbPool>,
+    Path((section, id)): Path<(String, i64)>,
+) -> Result<Json<SiteContentRevisionResponse>, AppError> {
+    require_permission(&claims, Permission::ManageSiteContent)?;
+    validate_section(&pool, &section).await?;
+
+    let revision = load_revision(&pool, &section, id).await?;
+    Ok(Json(revision))
+}
+
+/// Handler writing a saved version back as the section's content. The
+/// restore is saved as a new revision, so it can be undone the same way.
+/// The snapshot is validated like any update, since the section's rules
+/// may have tightened since it was saved. Admin-only.
+pub async fn restore_site_content_revision(
+    claims: auth::Claims,
+    State(pool): State<db::DbPool>,
+    Path((section, id)): Path<(String, i64)>,
+) -> Result<Json<SiteContentResponse>, AppError> {
+    require_permission(&claims, Permission::ManageSiteContent)?;
+    validate_section(&pool, &section).await?;
+
+    let revision = load_revision(&pool, &section, id).await?;
+    validate_content_size(&revision.content)?;
+    validate_content_structure(&section, &revision.content)?;
+
+    let record =
+        repositories::content::upsert_site_content(&pool, &section, &revision.content, &claims.sub)
+            .await
+            .map_err(internal_error("Failed to update site content"))?;
+
+    repositories::audit::append_entry(
+        &pool,
+        &claims.sub,
+        "restore_revision",
+        "site_content",
+        Some(&section),
+        serde_json::json!({ "revision": id }),
+    )
+    .await;
+
     Ok(Json(map_record(record)?))
 }
 
+async fn load_revision(
+    pool: &db::DbPool,
+    section: &str,
+    id: i64,
+) -> Result<SiteContentRevisionResponse, AppError> {
+    let revision = repositories::content::get_revision(pool, section, id)
+        .await
+        .map_err(internal_error("Failed to load site content revision"))?
+        .ok_or_else(|| not_found("Revision not found"))?;
+    let content: Value = serde_json::from_str(&revision.content_json)
+        .map_err(internal_error("Failed to parse stored content"))?;
+
+    Ok(SiteContentRevisionResponse {
+        id: revision.id,
+        section: revision.section,
+        content,
+        updated_by: revision.updated_by,
+        created_at: revision.created_at,
+    })
+}
+
+/// Builds the typed public settings from the raw `site_meta` and `settings`
+/// sections, applying defaults for anything missing or mistyped.
+fn assemble_public_settings(site_meta: Option<&Value>, settings: Option<&Value>) -> PublicSettings {
+    let mut result = PublicSettings::default();
+
+    // Same rule as the server-rendered meta tags: stock starter titles mean
+    // the whole section is still the shipped placeholder.
+    if let Some(meta) = site_meta {
+        let title = meta.get("title").and_then(Value::as_str);
+        if !title.is_some_and(is_starter_site_title) {
+            if let Some(title) = title.filter(|t| !t.trim().is_empty()) {
+                result.site.title = title.to_string();
+            }
+            if let Some(description) = meta.get("description").and_then(Value::as_str) {
+                result.site.description = description.to_string();
+            }
+        }
+    }
+
+    let Some(settings) = settings else {
+        return result;
+    };
+    let flag =
+        |value: Option<&Value>, default: bool| value.and_then(Value::as_bool).unwrap_or(default);
+
+    result.pdf_enabled = flag(settings.get("pdfEnabled"), result.pdf_enabled);
+    if let Some(comments) = settings.get("comments") {
+        result.comments.enabled = flag(comments.get("enabled"), result.comments.enabled);
+        result.comments.allow_guests =
+            flag(comments.get("allowGuests"), result.comments.allow_guests);
+    }
+    if let Some(features) = settings.get("features").and_then(Value::as_object) {
+        result.features = features
+            .iter()
+            .filter(|(_, feature)| flag(feature.get("public"), false))
+            .map(|(name, feature)| (name.clone(), flag(feature.get("enabled"), false)))
+            .collect();
+    }
+    if let Some(locales) = settings.get("locales") {
+        if let Some(available) = locales.get("available").and_then(Value::as_array) {
+            let available: Vec<String> = available
+                .iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect();
+            if !available.is_empty() {
+                result.locales.available = available;
+            }
+        }
+        if let Some(default) = locales.get("default").and_then(Value::as_str) {
+            result.locales.default = default.to_string();
+        }
+        if !result.locales.available.contains(&result.locales.default) {
+            result.locales.default = result.locales.available[0].clone();
+        }
+    }
+
+    result
+}
+
+/// Reads a section through the content cache and parses its JSON, treating
+/// unreadable rows as unset so the public settings always resolve.
+async fn cached_section_json(pool: &db::DbPool, section: &str) -> Result<Option<Value>, AppError> {
+    let record = repositories::content::fetch_site_content_by_section_cached(pool, section)
+        .await
+        .map_err(internal_error("Failed to load site content"))?;
+    Ok(record.and_then(|record| serde_json::from_str(&record.content_json).ok()))
+}
+
+/// Resolves the public settings from the (cached) content sections.
+pub(crate) async fn load_public_settings(pool: &db::DbPool) -> Result<PublicSettings, AppError> {
+    let site_meta = cached_section_json(pool, "site_meta").await?;
+    let settings = cached_section_json(pool, "settings").await?;
+    Ok(assemble_public_settings(
+        site_meta.as_ref(),
+        settings.as_ref(),
+    ))
+}
+
+/// Handler for the typed, versioned client settings.
+///
+/// Served from the content cache and tagged with a weak ETag, so clients
+/// revalidating an unchanged document get a bodiless 304.
+pub async fn get_public_settings(
+    State(pool): State<db::DbPool>,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    let public = load_public_settings(&pool).await?;
+    conditional_json(&headers, &public, None)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use axum::http::header;
     use serde_json::json;
 
     #[test]
@@ -306,7 +429,7 @@ mod tests {
                 { "id": "1", "label": "Blog", "path": "/blog" }
             ]
         });
-        assert!(validate_header_structure(&content_standard).is_ok());
+        assert!(validate_content_structure("header", &content_standard).is_ok());
 
         // Case 2: Section link with type="section" (no explicit target field)
         let content_section = json!({
@@ -316,7 +439,7 @@ mod tests {
             ]
         });
         assert!(
-            validate_header_structure(&content_section).is_ok(),
+            validate_content_structure("header", &content_section).is_ok(),
             "Should accept type='section' without other target fields"
         );
 
@@ -328,7 +451,7 @@ mod tests {
             ]
         });
         assert!(
-            validate_header_structure(&content_value).is_ok(),
+            validate_content_structure("header", &content_value).is_ok(),
             "Should accept 'value' field as target"
         );
 
@@ -339,7 +462,7 @@ mod tests {
                 { "id": "3", "label": "Invalid" }
             ]
         });
-        assert!(validate_header_structure(&content_invalid).is_err());
+        assert!(validate_content_structure("header", &content_invalid).is_err());
     }
 
     #[test]
@@ -349,13 +472,124 @@ mod tests {
             "title": "Login",
             "subtitle": "Welcome back"
         });
-        assert!(validate_login_structure(&content_valid).is_ok());
+        assert!(validate_content_structure("login", &content_valid).is_ok());
 
         // Case 2: Missing title
         let content_invalid = json!({
             "subtitle": "Welcome back"
         });
-        assert!(validate_login_structure(&content_invalid).is_err());
+        assert!(validate_content_structure("login", &content_invalid).is_err());
+    }
+
+    #[test]
+    fn test_validate_plain_text_structure() {
+        let robots = json!({ "content": "User-agent: *\nDisallow: /admin\n" });
+        assert!(validate_plain_text_structure(&robots, MAX_ROBOTS_BYTES).is_ok());
+
+        assert!(validate_plain_text_structure(&json!({}), MAX_ROBOTS_BYTES).is_err());
+        assert!(
+            validate_plain_text_structure(&json!({ "content": 42 }), MAX_ROBOTS_BYTES).is_err()
+        );
+        assert!(validate_plain_text_structure(
+            &json!({ "content": "<script>alert(1)</script>" }),
+            MAX_ROBOTS_BYTES
+        )
+        .is_err());
+        assert!(validate_plain_text_structure(
+            &json!({ "content": "Contact: x\u{0}" }),
+            MAX_SECURITY_TXT_BYTES
+        )
+        .is_err());
+
+        let oversized = json!({ "content": "a".repeat(MAX_SECURITY_TXT_BYTES + 1) });
+        assert!(validate_plain_text_structure(&oversized, MAX_SECURITY_TXT_BYTES).is_err());
+    }
+
+    #[test]
+    fn test_assemble_public_settings_defaults_and_overrides() {
+        assert_eq!(
+            assemble_public_settings(None, None),
+            PublicSettings::default()
+        );
+
+        let meta = json!({ "title": "My Blog", "description": "Notes" });
+        let settings = json!({
+            "pdfEnabled": false,
+            "comments": { "allowGuests": false },
+            "features": {
+                "newsletter": { "enabled": true, "public": true },
+                "beta": { "enabled": true },
+            },
+            "locales": { "default": "fr", "available": ["en"] },
+        });
+        let assembled = assemble_public_settings(Some(&meta), Some(&settings));
+
+        assert_eq!(assembled.site.title, "My Blog");
+        assert_eq!(assembled.site.description, "Notes");
+        assert!(!assembled.pdf_enabled);
+        assert!(assembled.comments.enabled);
+        assert!(!assembled.comments.allow_guests);
+        assert_eq!(assembled.features.len(), 1);
+        assert_eq!(assembled.features.get("newsletter"), Some(&true));
+        assert_eq!(assembled.locales.available, vec!["en".to_string()]);
+        assert_eq!(assembled.locales.default, "en");
+    }
+
+    #[tokio::test]
+    async fn test_public_settings_etag_tracks_section_updates() {
+        let pool = crate::test_support::migrated_pool().await;
+
+        let response = get_public_settings(State(pool.clone()), HeaderMap::new())
+            .await
+            .expect("settings");
+        assert_eq!(response.status(), StatusCode::OK);
+        let etag = response.headers()[header::ETAG].clone();
+
+        let mut conditional = HeaderMap::new();
+        conditional.insert(header::IF_NONE_MATCH, etag.clone());
+        let response = get_public_settings(State(pool.clone()), conditional.clone())
+            .await
+            .expect("settings");
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+        repositories::content::upsert_site_content(
+            &pool,
+            "settings",
+            &json!({ "pdfEnabled": false }),
+            "admin",
+        )
+        .await
+        .expect("update settings");
+        let response = get_public_settings(State(pool), conditional)
+            .await
+            .expect("settings");
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_ne!(response.headers()[header::ETAG], etag);
+    }
+
+    #[test]
+    fn test_validate_settings_structure_checks_public_fields() {
+        assert!(validate_content_structure(
+            "settings",
+            &json!({
+                "features": { "newsletter": { "enabled": true, "public": true } },
+                "locales": { "default": "de", "available": ["de", "en"] },
+            })
+        )
+        .is_ok());
+        assert!(validate_content_structure(
+            "settings",
+            &json!({ "comments": { "enabled": "yes" } })
+        )
+        .is_err());
+        assert!(
+            validate_content_structure("settings", &json!({ "features": { "x": true } })).is_err()
+        );
+        assert!(validate_content_structure(
+            "settings",
+            &json!({ "locales": { "available": "de" } })
+        )
+        .is_err());
     }
 
     #[test]
@@ -368,7 +602,7 @@ mod tests {
             ]
         });
         assert!(
-            validate_header_structure(&content_empty_slug).is_err(),
+            validate_content_structure("header", &content_empty_slug).is_err(),
             "Should reject empty slug"
         );
 
@@ -380,7 +614,7 @@ mod tests {
             ]
         });
         assert!(
-            validate_header_structure(&content_whitespace_slug).is_err(),
+            validate_content_structure("header", &content_whitespace_slug).is_err(),
             "Should reject whitespace-only slug"
         );
     }
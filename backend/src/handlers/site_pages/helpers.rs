@@ -1,4 +1,5 @@
 use super::*;
+use crate::schemas::Schema;
 
 /// Maximum length for a page title (200 characters)
 pub(super) const MAX_TITLE_LEN: usize = 200;
@@ -8,9 +9,100 @@ pub(super) const MAX_DESCRIPTION_LEN: usize = 1000;
 pub(super) const MAX_NAV_LABEL_LEN: usize = 100;
 /// Maximum allowed size for hero/layout JSON payloads (200KB)
 pub(super) const MAX_JSON_BYTES: usize = 200_000;
+/// Maximum number of custom response headers per page
+pub(super) const MAX_CUSTOM_HEADERS: usize = 8;
+/// Maximum length of a single custom header value
+pub(super) const MAX_CUSTOM_HEADER_VALUE_LEN: usize = 512;
+
+/// Directives accepted in `meta_robots`.
+const ROBOTS_DIRECTIVES: &[&str] = &[
+    "all",
+    "index",
+    "noindex",
+    "follow",
+    "nofollow",
+    "none",
+    "noarchive",
+    "nosnippet",
+    "noimageindex",
+    "notranslate",
+];
+
+/// Normalizes a robots directive list to lowercase, comma-separated tokens.
+/// An empty value clears the setting.
+pub(super) fn normalize_meta_robots(value: Option<String>) -> Result<Option<String>, AppError> {
+    let Some(value) = value else {
+        return Ok(None);
+    };
+
+    let mut directives = Vec::new();
+    for token in value.split(',') {
+        let token = token.trim().to_ascii_lowercase();
+        if token.is_empty() {
+            continue;
+        }
+        if !ROBOTS_DIRECTIVES.contains(&token.as_str()) {
+            return Err(bad_request(format!(
+                "Unknown robots directive '{token}'. Allowed: {}",
+                ROBOTS_DIRECTIVES.join(", ")
+            )));
+        }
+        if !directives.contains(&token) {
+            directives.push(token);
+        }
+    }
+
+    Ok((!directives.is_empty()).then(|| directives.join(",")))
+}
+
+/// Checks a custom header map against the allowlist and value limits,
+/// returning it with header names in their canonical spelling.
+pub(super) fn sanitize_custom_headers(
+    headers: BTreeMap<String, String>,
+) -> Result<BTreeMap<String, String>, AppError> {
+    if headers.len() > MAX_CUSTOM_HEADERS {
+        return Err(bad_request(format!(
+            "Too many custom headers (max {MAX_CUSTOM_HEADERS})"
+        )));
+    }
+
+    let mut sanitized = BTreeMap::new();
+    for (name, value) in headers {
+        let canonical = PAGE_CUSTOM_HEADER_ALLOWLIST
+            .iter()
+            .find(|allowed| allowed.eq_ignore_ascii_case(name.trim()))
+            .ok_or_else(|| {
+                bad_request(format!(
+                    "Header '{name}' is not allowed. Allowed: {}",
+                    PAGE_CUSTOM_HEADER_ALLOWLIST.join(", ")
+                ))
+            })?;
+
+        let value = value.trim();
+        if value.is_empty() || value.len() > MAX_CUSTOM_HEADER_VALUE_LEN {
+            return Err(bad_request(format!(
+                "Header '{canonical}' must have a value of 1..={MAX_CUSTOM_HEADER_VALUE_LEN} characters"
+            )));
+        }
+        if value.chars().any(|c| c.is_control()) || HeaderValue::from_str(value).is_err() {
+            return Err(bad_request(format!(
+                "Header '{canonical}' contains characters not allowed in a header value"
+            )));
+        }
+
+        if sanitized
+            .insert(canonical.to_string(), value.to_string())
+            .is_some()
+        {
+            return Err(bad_request(format!("Header '{canonical}' is set twice")));
+        }
+    }
+
+    Ok(sanitized)
+}
 
 /// Validates that a JSON value, when serialized, doesn't exceed the byte limit.
-pub(super) fn validate_json_size(value: &Value, field: &str) -> Result<(), ApiError> {
+pub(super) fn validate_json_size(value: &Value, field: &str) -> Result<(), AppError> {
     match serde_json::to_string(value) {
         // Within bounds
         Ok(serialized) if serialized.len() <= MAX_JSON_BYTES => Ok(()),
@@ -23,10 +115,22 @@ pub(super) fn validate_json_size(value: &Value, field: &str) -> Result<(), ApiEr
     }
 }
 
+/// Checks a hero/layout blob against its JSON Schema, naming the JSON
+/// pointers of the first few violations on failure.
+pub(super) fn validate_json_schema(
+    value: &Value,
+    schema: Schema,
+    field: &str,
+) -> Result<(), AppError> {
+    schema
+        .validate(value)
+        .map_err(|violations| bad_request(format!("Invalid {field}: {}", violations.join("; "))))
+}
+
 /// Normalizes and validates a payload for creating a new site page.
-pub(super) fn sanitize_create_payload(
+pub(crate) fn sanitize_create_payload(
     mut payload: CreateSitePageRequest,
-) -> Result<CreateSitePageRequest, ApiError> {
+) -> Result<CreateSitePageRequest, AppError> {
     // Slug normalization: trim and lowercase
     payload.slug = payload.slug.trim().to_lowercase();
     if payload.slug.is_empty() {
@@ -74,6 +178,12 @@ pub(super) fn sanitize_create_payload(
     // Large JSON field size validation
     validate_json_size(&payload.hero, "hero")?;
     validate_json_size(&payload.layout, "layout")?;
+    validate_json_schema(&payload.hero, Schema::PageHero, "hero")?;
+    validate_json_schema(&payload.layout, Schema::PageLayout, "layout")?;
+
+    // Crawler directives and response headers
+    payload.meta_robots = normalize_meta_robots(payload.meta_robots)?;
+    payload.custom_headers = sanitize_custom_headers(std::mem::take(&mut payload.custom_headers))?;
 
     Ok(payload)
 }
@@ -81,7 +191,7 @@ pub(super) fn sanitize_create_payload(
 /// Normalizes and validates a payload for updating an existing site page.
 pub(super) fn sanitize_update_payload(
     mut payload: UpdateSitePageRequest,
-) -> Result<UpdateSitePageRequest, ApiError> {
+) -> Result<UpdateSitePageRequest, AppError> {
     // Partial slug update
     if let Some(ref mut slug) = payload.slug {
         *slug = slug.trim().to_lowercase();
@@ -138,16 +248,26 @@ pub(super) fn sanitize_update_payload(
     // Partial JSON field update
     if let Some(ref hero) = payload.hero {
         validate_json_size(hero, "hero")?;
+        validate_json_schema(hero, Schema::PageHero, "hero")?;
     }
     if let Some(ref layout) = payload.layout {
         validate_json_size(layout, "layout")?;
+        validate_json_schema(layout, Schema::PageLayout, "layout")?;
+    }
+
+    // Crawler directives and response headers
+    if let Some(meta_robots) = payload.meta_robots.take() {
+        payload.meta_robots = Some(normalize_meta_robots(meta_robots)?);
+    }
+    if let Some(headers) = payload.custom_headers.take() {
+        payload.custom_headers = Some(sanitize_custom_headers(headers)?);
     }
 
     Ok(payload)
 }
 
 /// Maps a database SitePage record to a rich response model, including JSON parsing.
-pub(super) fn map_page(page: crate::models::SitePage) -> Result<SitePageResponse, ApiError> {
+pub(super) fn map_page(page: crate::models::SitePage) -> Result<SitePageResponse, AppError> {
     let crate::models::SitePage {
         id,
         slug,
@@ -159,6 +279,8 @@ pub(super) fn map_page(page: crate::models::SitePage) -> Result<SitePageResponse
         is_published,
         hero_json,
         layout_json,
+        meta_robots,
+        custom_headers_json,
         created_at,
         updated_at,
     } = page;
@@ -171,6 +293,10 @@ pub(super) fn map_page(page: crate::models::SitePage) -> Result<SitePageResponse
     let layout = serde_json::from_str::<Value>(&layout_json)
         .map_err(internal_error("Failed to parse stored layout JSON"))?;
 
+    // Parse the stored header map
+    let custom_headers = serde_json::from_str::<BTreeMap<String, String>>(&custom_headers_json)
+        .map_err(internal_error("Failed to parse stored custom headers"))?;
+
     // Normalize slug for output
     let sanitized_slug = slug.trim().to_lowercase();
 
@@ -205,11 +331,21 @@ pub(super) fn map_page(page: crate::models::SitePage) -> Result<SitePageResponse
         is_published,
         hero,
         layout,
+        meta_robots,
+        custom_headers: Some(custom_headers),
         created_at,
         updated_at,
     })
 }
 
+/// Public variant of [`map_page`]: custom headers are applied to responses
+/// by the frontend proxy and are not part of the public contract.
+pub(super) fn map_public_page(page: crate::models::SitePage) -> Result<SitePageResponse, AppError> {
+    let mut response = map_page(page)?;
+    response.custom_headers = None;
+    Ok(response)
+}
+
 /// Maps a database SitePost record to a public response model.
 pub(super) fn map_post(post: crate::models::SitePost) -> SitePostResponse {
     SitePostResponse {
@@ -219,11 +355,61 @@ pub(super) fn map_post(post: crate::models::SitePost) -> SitePostResponse {
         slug: post.slug,
         excerpt: post.excerpt,
         content_markdown: post.content_markdown,
+        content_html: None,
         is_published: post.is_published,
         published_at: post.published_at,
         order_index: post.order_index,
+        cover_image_url: post.cover_image_url,
+        meta_description: post.meta_description,
+        canonical_url: post.canonical_url,
         created_at: post.created_at,
         updated_at: post.updated_at,
         allow_comments: post.allow_comments,
+        tags: post.tags,
+        view_count: None,
+        toc: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn meta_robots_is_normalized_and_checked() {
+        assert_eq!(
+            normalize_meta_robots(Some(" NoIndex, follow,noindex ".into())).unwrap(),
+            Some("noindex,follow".into())
+        );
+        assert_eq!(normalize_meta_robots(Some(" , ".into())).unwrap(), None);
+        assert!(normalize_meta_robots(Some("noindex, max-snippet".into())).is_err());
+    }
+
+    #[test]
+    fn custom_headers_are_allowlisted_and_capped() {
+        let headers = BTreeMap::from([("x-robots-tag".to_string(), " noindex ".to_string())]);
+        let sanitized = sanitize_custom_headers(headers).unwrap();
+        assert_eq!(
+            sanitized.get("X-Robots-Tag").map(String::as_str),
+            Some("noindex")
+        );
+
+        let disallowed = BTreeMap::from([("Set-Cookie".to_string(), "a=b".to_string())]);
+        assert!(sanitize_custom_headers(disallowed).is_err());
+
+        let too_long = BTreeMap::from([(
+            "Link".to_string(),
+            "a".repeat(MAX_CUSTOM_HEADER_VALUE_LEN + 1),
+        )]);
+        assert!(sanitize_custom_headers(too_long).is_err());
+
+        let injected = BTreeMap::from([("Link".to_string(), "<a>\r\nX-Evil: 1".to_string())]);
+        assert!(sanitize_custom_headers(injected).is_err());
+
+        let duplicated = BTreeMap::from([
+            ("Link".to_string(), "<a>".to_string()),
+            ("link".to_string(), "<b>".to_string()),
+        ]);
+        assert!(sanitize_custom_headers(duplicated).is_err());
     }
 }
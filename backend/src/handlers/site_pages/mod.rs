@@ -5,34 +5,58 @@
 //! CRUD operations and public-facing content retrieval.
 
 use crate::{
+    cache::{self, CacheStatus},
     db,
-    handlers::common::{ensure_admin, map_sqlx_error},
+    handlers::{
+        common::{
+            conditional_json, content_toc, map_sqlx_error, require_permission, ContentFormat,
+            ContentFormatQuery, DeleteQuery, DryRunQuery,
+        },
+        patch,
+        previews::{preview_grants, PreviewQuery},
+        site_posts::normalize_tag,
+    },
+    middleware::security as security_middleware,
     models::{
-        bad_request, internal_error, not_found, ApiError, CreateSitePageRequest,
-        NavigationItemResponse, NavigationResponse, SitePageListResponse, SitePageResponse,
-        SitePageWithPostsResponse, SitePostDetailResponse, SitePostResponse, UpdateSitePageRequest,
+        bad_request, conflict, internal_error, not_found, robots_excludes_indexing, AppError,
+        ArchiveMonth, CreateSitePageRequest, NavigationItemResponse, NavigationResponse, Paginated,
+        SitePage, SitePageListResponse, SitePagePatchDocument, SitePageResponse,
+        SitePageWithPostsResponse, SitePostDetailResponse, SitePostResponse,
+        SitePostSummaryResponse, TagCount, TrashResponse, UpdateSitePageRequest,
+        PAGE_CUSTOM_HEADER_ALLOWLIST,
+    },
+    repositories::{self, posts::PostFilter},
+    security::{
+        auth::{self, Permission},
+        preview_token::PreviewKind,
     },
-    repositories,
-    security::auth,
+    views,
 };
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
+    body::Bytes,
+    extract::{ConnectInfo, Path, Query, State},
+    http::{HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
     Json,
 };
+use serde::Deserialize;
 use serde_json::Value;
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
 
 mod helpers;
+pub(crate) use helpers::sanitize_create_payload;
 use helpers::*;
 
 /// Handler for listing all site pages.
-/// Admin-only. Used for managing the page tree in the CMS.
+/// Admins and editors, who need the page list to file posts. Used for
+/// managing the page tree in the CMS.
 pub async fn list_site_pages(
     claims: auth::Claims,
     State(pool): State<db::DbPool>,
-) -> Result<Json<SitePageListResponse>, ApiError> {
-    // RBAC: Verify admin role
-    ensure_admin(&claims)?;
+) -> Result<Json<SitePageListResponse>, AppError> {
+    // RBAC: Readable by every role that edits posts
+    require_permission(&claims, Permission::EditPosts)?;
 
     // Fetch records
     let records = repositories::pages::list_site_pages(&pool)
@@ -49,14 +73,14 @@ pub async fn list_site_pages(
 }
 
 /// Handler to retrieve full details of a single site page by its ID.
-/// Admin-only.
+/// Admins and editors.
 pub async fn get_site_page(
     claims: auth::Claims,
     State(pool): State<db::DbPool>,
     Path(id): Path<String>,
-) -> Result<Json<SitePageResponse>, ApiError> {
-    // RBAC: Verify admin role
-    ensure_admin(&claims)?;
+) -> Result<Json<SitePageResponse>, AppError> {
+    // RBAC: Readable by every role that edits posts
+    require_permission(&claims, Permission::EditPosts)?;
 
     // Retrieve from repository
     let record = repositories::pages::get_site_page_by_id(&pool, &id)
@@ -74,9 +98,9 @@ pub async fn create_site_page(
     claims: auth::Claims,
     State(pool): State<db::DbPool>,
     Json(payload): Json<CreateSitePageRequest>,
-) -> Result<Json<SitePageResponse>, ApiError> {
+) -> Result<Json<SitePageResponse>, AppError> {
     // RBAC: Ensure admin privileges
-    ensure_admin(&claims)?;
+    require_permission(&claims, Permission::ManagePages)?;
 
     // Validate and clean the request payload
     let payload = sanitize_create_payload(payload)?;
@@ -94,24 +118,38 @@ pub async fn create_site_page(
         page_slug = %record.slug,
         "Admin created new page"
     );
+    repositories::audit::append_entry(
+        &pool,
+        &claims.sub,
+        "create",
+        "page",
+        Some(&record.id),
+        serde_json::json!({ "slug": record.slug }),
+    )
+    .await;
 
     // Return the newly created state
     Ok(Json(map_page(record)?))
 }
 
 /// Handler to update an existing site page.
-/// Admin-only. Supports partial updates via UpdateSitePageRequest.
+/// Admin-only. Supports partial updates via UpdateSitePageRequest;
+/// `?dry_run=true` only validates them.
 pub async fn update_site_page(
     claims: auth::Claims,
     State(pool): State<db::DbPool>,
     Path(id): Path<String>,
+    Query(params): Query<DryRunQuery>,
     Json(payload): Json<UpdateSitePageRequest>,
-) -> Result<Json<SitePageResponse>, ApiError> {
+) -> Result<Response, AppError> {
     // RBAC: Ensure admin privileges
-    ensure_admin(&claims)?;
+    require_permission(&claims, Permission::ManagePages)?;
 
     // Clean and validate the partial update
     let payload = sanitize_update_payload(payload)?;
+    if params.dry_run {
+        return Ok(StatusCode::NO_CONTENT.into_response());
+    }
 
     // Perform database UPDATE
     let record = repositories::pages::update_site_page(&pool, &id, payload)
@@ -125,85 +163,496 @@ pub async fn update_site_page(
         page_id = %id,
         "Admin updated page"
     );
+    repositories::audit::append_entry(
+        &pool,
+        &claims.sub,
+        "update",
+        "page",
+        Some(&id),
+        serde_json::json!({ "slug": record.slug }),
+    )
+    .await;
 
     // Return updated record
-    Ok(Json(map_page(record)?))
+    Ok(Json(map_page(record)?).into_response())
 }
 
-/// Handler to permanently delete a site page and its references.
-/// Admin-only.
+/// Handler to apply an RFC 6902 JSON Patch to a site page.
+/// Admin-only. The patched page passes the same validation as a full update
+/// and is only written if nobody else changed the page in the meantime.
+/// `?dry_run=true` only validates the patched page.
+pub async fn patch_site_page(
+    claims: auth::Claims,
+    State(pool): State<db::DbPool>,
+    Path(id): Path<String>,
+    Query(params): Query<DryRunQuery>,
+    body: Bytes,
+) -> Result<Response, AppError> {
+    require_permission(&claims, Permission::ManagePages)?;
+    let operations = patch::parse_patch(&body)?;
+
+    let current = repositories::pages::get_site_page_by_id(&pool, &id)
+        .await
+        .map_err(|err| map_sqlx_error(err, "Site page"))?
+        .ok_or_else(|| not_found("Site page not found"))?;
+
+    let document = SitePagePatchDocument::from(map_page(current.clone())?);
+    let patched: SitePagePatchDocument =
+        patch::apply_patch(&document, &operations, &["updated_at"])?;
+    let payload = sanitize_update_payload(patched.into())?;
+    if params.dry_run {
+        return Ok(StatusCode::NO_CONTENT.into_response());
+    }
+
+    let record = repositories::pages::update_site_page_if_unmodified(&pool, &current, payload)
+        .await
+        .map_err(|err| map_sqlx_error(err, "Site page"))?
+        .ok_or_else(|| {
+            conflict("Page was modified by another request. Please refresh and try again.")
+        })?;
+
+    tracing::info!(
+        action = "patch_page",
+        user = %claims.sub,
+        page_id = %id,
+        operations = operations.0.len(),
+        "Admin patched page"
+    );
+    repositories::audit::append_entry(
+        &pool,
+        &claims.sub,
+        "patch",
+        "page",
+        Some(&id),
+        serde_json::json!({ "operations": operations.0.len() }),
+    )
+    .await;
+
+    Ok(Json(map_page(record)?).into_response())
+}
+
+/// Handler to delete a site page.
+/// Admin-only. By default the page moves to the trash, taking its posts out
+/// of every public listing with it; `?permanent=true` removes it (trashed
+/// or not) together with its posts, which requires sudo mode.
 pub async fn delete_site_page(
     claims: auth::Claims,
     State(pool): State<db::DbPool>,
     Path(id): Path<String>,
-) -> Result<StatusCode, ApiError> {
+    Query(params): Query<DeleteQuery>,
+) -> Result<StatusCode, AppError> {
     // RBAC: Verify admin role
-    ensure_admin(&claims)?;
-
-    // Execute deletion logic
-    repositories::pages::delete_site_page(&pool, &id)
+    require_permission(&claims, Permission::ManagePages)?;
+
+    if params.permanent {
+        claims.require_sudo()?;
+        repositories::pages::delete_site_page(&pool, &id, &claims.sub)
+            .await
+            .map_err(|err| map_sqlx_error(err, "Site page"))?;
+    } else if !repositories::pages::trash_site_page(&pool, &id)
         .await
-        .map_err(|err| map_sqlx_error(err, "Site page"))?;
+        .map_err(|err| map_sqlx_error(err, "Site page"))?
+    {
+        return Err(not_found("Site page not found"));
+    }
 
     // Audit log
     tracing::info!(
-        action = "delete_page",
+        action = if params.permanent { "delete_page" } else { "trash_page" },
         user = %claims.sub,
         page_id = %id,
         "Admin deleted page"
     );
+    repositories::audit::append_entry(
+        &pool,
+        &claims.sub,
+        if params.permanent { "delete" } else { "trash" },
+        "page",
+        Some(&id),
+        serde_json::json!({}),
+    )
+    .await;
 
     Ok(StatusCode::NO_CONTENT)
 }
 
-/// Handler to retrieve a published page (and its associated posts) by its URL slug.
-/// Publicly accessible.
+/// Handler to take a site page out of the trash, together with the posts
+/// it hid. Admin-only, like deleting.
+pub async fn restore_site_page(
+    claims: auth::Claims,
+    State(pool): State<db::DbPool>,
+    Path(id): Path<String>,
+) -> Result<Json<SitePageResponse>, AppError> {
+    require_permission(&claims, Permission::ManagePages)?;
+
+    if !repositories::pages::restore_site_page(&pool, &id)
+        .await
+        .map_err(|err| map_sqlx_error(err, "Site page"))?
+    {
+        return Err(not_found("Site page is not in the trash"));
+    }
+    let page = repositories::pages::get_site_page_by_id(&pool, &id)
+        .await
+        .map_err(|err| map_sqlx_error(err, "Site page"))?
+        .ok_or_else(|| not_found("Site page not found"))?;
+
+    repositories::audit::append_entry(
+        &pool,
+        &claims.sub,
+        "restore",
+        "page",
+        Some(&id),
+        serde_json::json!({ "title": page.title }),
+    )
+    .await;
+
+    Ok(Json(map_page(page)?))
+}
+
+/// Handler listing the pages and posts in the trash.
+/// Admin-only. A trashed page's own posts are not listed separately; they
+/// come back with the page.
+pub async fn list_trash(
+    claims: auth::Claims,
+    State(pool): State<db::DbPool>,
+) -> Result<Json<TrashResponse>, AppError> {
+    require_permission(&claims, Permission::DeleteContent)?;
+
+    let pages = repositories::pages::list_trashed_pages(&pool)
+        .await
+        .map_err(|err| map_sqlx_error(err, "Trash"))?;
+    let posts = repositories::posts::list_trashed_posts(&pool)
+        .await
+        .map_err(|err| map_sqlx_error(err, "Trash"))?;
+
+    Ok(Json(TrashResponse { pages, posts }))
+}
+
+/// Query of `POST /api/pages/{id}/duplicate`.
+#[derive(Debug, Deserialize)]
+pub struct DuplicatePageQuery {
+    /// Copy the page's posts along with it.
+    #[serde(default)]
+    pub include_posts: bool,
+}
+
+/// Handler to copy a site page, optionally with its posts.
+/// Admin-only. The copy gets a `-copy` slug and starts unpublished, so it
+/// can be reworked before it goes live.
+pub async fn duplicate_site_page(
+    claims: auth::Claims,
+    State(pool): State<db::DbPool>,
+    Path(id): Path<String>,
+    Query(query): Query<DuplicatePageQuery>,
+) -> Result<Json<SitePageResponse>, AppError> {
+    require_permission(&claims, Permission::ManagePages)?;
+
+    let record = repositories::pages::duplicate_site_page(&pool, &id, query.include_posts)
+        .await
+        .map_err(|err| map_sqlx_error(err, "Site page"))?;
+
+    tracing::info!(
+        action = "duplicate_page",
+        user = %claims.sub,
+        page_id = %id,
+        copy_id = %record.id,
+        include_posts = query.include_posts,
+        "Admin duplicated page"
+    );
+    repositories::audit::append_entry(
+        &pool,
+        &claims.sub,
+        "duplicate",
+        "page",
+        Some(&record.id),
+        serde_json::json!({
+            "source": id,
+            "slug": record.slug,
+            "include_posts": query.include_posts,
+        }),
+    )
+    .await;
+
+    Ok(Json(map_page(record)?))
+}
+
+/// Handler to retrieve a published page (and the first page of its post
+/// summaries) by its URL slug. Publicly accessible. Tagged with an ETag for
+/// conditional requests. An unpublished page is shown with a `?preview=`
+/// token issued for it; its unpublished posts are never listed. Published
+/// bundles are cached per slug, reported in `Cache-Status`.
 pub async fn get_published_page_by_slug(
     State(pool): State<db::DbPool>,
+    headers: HeaderMap,
     Path(slug): Path<String>,
-) -> Result<Json<SitePageWithPostsResponse>, ApiError> {
+    Query(preview): Query<PreviewQuery>,
+) -> Result<Response, AppError> {
     // Normalize lookup slug
     let lookup_slug = slug.trim().to_lowercase();
     if lookup_slug.is_empty() {
         return Err(bad_request("Slug cannot be empty"));
     }
 
+    if let Some(bundle) = cache::PAGE_BUNDLES.get(&lookup_slug) {
+        let response = conditional_json(&headers, bundle.as_ref(), None)?;
+        return Ok(cache::with_cache_status(response, CacheStatus::Hit));
+    }
+
     // Fetch page metadata from repository
     let page = repositories::pages::get_site_page_by_slug(&pool, &lookup_slug)
         .await
         .map_err(|err| map_sqlx_error(err, "Site page"))?
         .ok_or_else(|| not_found("Page not found"))?;
 
-    // SECURITY: Ensure the page is actually marked as published
-    if !page.is_published {
+    // SECURITY: Ensure the page is actually marked as published, or that
+    // the preview token was issued for this very page
+    let previewing = !page.is_published;
+    if previewing
+        && !preview_grants(
+            &pool,
+            PreviewKind::Page,
+            &page.id,
+            preview.preview.as_deref(),
+        )
+        .await?
+    {
         return Err(not_found("Page not published"));
     }
 
-    // Load child posts for the page (only published ones)
-    let posts = repositories::posts::list_published_posts_for_page(&pool, &page.id)
+    // Load the first page of child posts (only published ones)
+    let posts_total =
+        repositories::posts::count_published_posts(&pool, &page.id, PostFilter::default())
+            .await
+            .map_err(|err| map_sqlx_error(err, "Posts"))?;
+    let posts = repositories::posts::list_published_post_summaries(
+        &pool,
+        &page.id,
+        PostFilter::default(),
+        DEFAULT_POST_ORDER,
+        EMBEDDED_POSTS_LIMIT,
+        0,
+    )
+    .await
+    .map_err(|err| map_sqlx_error(err, "Posts"))?;
+
+    // Return the bundle. No Last-Modified: unpublishing or deleting a post
+    // changes the bundle without bumping anything left in it.
+    let bundle = SitePageWithPostsResponse {
+        page: map_public_page(page)?,
+        posts: posts
+            .into_iter()
+            .map(SitePostSummaryResponse::from)
+            .collect(),
+        posts_total,
+    };
+    let response = conditional_json(&headers, &bundle, None)?;
+    if previewing {
+        return Ok(mark_preview(response));
+    }
+    cache::PAGE_BUNDLES.insert(lookup_slug, std::sync::Arc::new(bundle));
+    Ok(cache::with_cache_status(response, CacheStatus::Miss))
+}
+
+/// Published posts embedded in the page bundle; the rest are paged
+/// through `GET /api/public/pages/{slug}/posts`.
+const EMBEDDED_POSTS_LIMIT: i64 = 10;
+
+/// Manual order of the posts of a page, as set in the admin UI.
+const DEFAULT_POST_ORDER: &str = "order_index, COALESCE(published_at, created_at), id";
+
+/// Order of publication, as in the archive.
+const CHRONOLOGICAL_POST_ORDER: &str = "COALESCE(published_at, created_at), id";
+
+fn default_post_limit() -> i64 {
+    EMBEDDED_POSTS_LIMIT
+}
+
+/// Query of `GET /api/public/pages/{slug}/posts`.
+#[derive(Debug, Deserialize)]
+pub struct PublicPostListQuery {
+    /// Number of posts to return (default: 10, max: 100)
+    #[serde(default = "default_post_limit")]
+    pub limit: i64,
+    /// Number of posts to skip
+    #[serde(default)]
+    pub offset: i64,
+    /// `field` or `field:asc|desc`, see [`post_order`]
+    pub sort: Option<String>,
+    /// Only posts carrying this tag (case-insensitive).
+    pub tag: Option<String>,
+}
+
+/// `ORDER BY` clause for a `sort` parameter. Only these fixed clauses ever
+/// reach the SQL. Without `sort`, posts keep their manual order.
+fn post_order(sort: Option<&str>) -> Result<&'static str, AppError> {
+    let Some(sort) = sort.map(str::trim).filter(|sort| !sort.is_empty()) else {
+        return Ok(DEFAULT_POST_ORDER);
+    };
+    let (field, direction) = sort.split_once(':').unwrap_or((sort, "asc"));
+    let order = match (field, direction) {
+        ("order_index", "asc") => DEFAULT_POST_ORDER,
+        ("order_index", "desc") => "order_index DESC, COALESCE(published_at, created_at) DESC, id",
+        ("published_at", "asc") => CHRONOLOGICAL_POST_ORDER,
+        ("published_at", "desc") => "COALESCE(published_at, created_at) DESC, id",
+        ("created_at", "asc") => "created_at, id",
+        ("created_at", "desc") => "created_at DESC, id",
+        ("updated_at", "asc") => "updated_at, id",
+        ("updated_at", "desc") => "updated_at DESC, id",
+        ("title", "asc") => "title COLLATE NOCASE, id",
+        ("title", "desc") => "title COLLATE NOCASE DESC, id",
+        _ => {
+            return Err(bad_request(
+                "'sort' must be order_index, published_at, created_at, updated_at or title, \
+                 optionally followed by ':asc' or ':desc'",
+            ))
+        }
+    };
+    Ok(order)
+}
+
+/// Handler to list the published posts of a published page, paginated and
+/// without their bodies, optionally only those with `?tag=`. Publicly
+/// accessible.
+pub async fn list_published_posts(
+    State(pool): State<db::DbPool>,
+    Path(slug): Path<String>,
+    Query(params): Query<PublicPostListQuery>,
+) -> Result<Json<Paginated<SitePostSummaryResponse>>, AppError> {
+    let limit = params.limit.clamp(1, 100);
+    let offset = params.offset.max(0);
+    let order_by = post_order(params.sort.as_deref())?;
+
+    let page = find_published_page(&pool, &slug).await?;
+
+    let tag = params
+        .tag
+        .as_deref()
+        .map(normalize_tag)
+        .filter(|tag| !tag.is_empty());
+    let filter = PostFilter {
+        tag: tag.as_deref(),
+        month: None,
+    };
+    list_post_summaries(&pool, &page.id, filter, order_by, limit, offset).await
+}
+
+/// Query of `GET /api/public/pages/{slug}/archive/{year}/{month}`.
+#[derive(Debug, Deserialize)]
+pub struct ArchivePostsQuery {
+    /// Number of posts to return (default: 10, max: 100)
+    #[serde(default = "default_post_limit")]
+    pub limit: i64,
+    /// Number of posts to skip
+    #[serde(default)]
+    pub offset: i64,
+}
+
+/// Handler to list the months in which posts of a published page were
+/// published, with the number of posts in each, newest first. Publicly
+/// accessible.
+pub async fn get_archive(
+    State(pool): State<db::DbPool>,
+    Path(slug): Path<String>,
+) -> Result<Json<Vec<ArchiveMonth>>, AppError> {
+    let page = find_published_page(&pool, &slug).await?;
+    let months = repositories::posts::list_archive(&pool, &page.id)
         .await
         .map_err(|err| map_sqlx_error(err, "Posts"))?;
+    Ok(Json(months))
+}
 
-    // Map posts to public DTOs
-    let mut post_responses = Vec::with_capacity(posts.len());
-    for post in posts {
-        post_responses.push(map_post(post));
+/// Handler to list the published posts of one archive month, oldest first,
+/// paginated and without their bodies. Publicly accessible.
+pub async fn list_archive_posts(
+    State(pool): State<db::DbPool>,
+    Path((slug, year, month)): Path<(String, i32, u32)>,
+    Query(params): Query<ArchivePostsQuery>,
+) -> Result<Json<Paginated<SitePostSummaryResponse>>, AppError> {
+    if !(1..=9999).contains(&year) || !(1..=12).contains(&month) {
+        return Err(bad_request("Invalid archive month"));
+    }
+    let limit = params.limit.clamp(1, 100);
+    let offset = params.offset.max(0);
+
+    let page = find_published_page(&pool, &slug).await?;
+    let month = format!("{year:04}-{month:02}");
+    let filter = PostFilter {
+        tag: None,
+        month: Some(&month),
+    };
+    list_post_summaries(
+        &pool,
+        &page.id,
+        filter,
+        CHRONOLOGICAL_POST_ORDER,
+        limit,
+        offset,
+    )
+    .await
+}
+
+/// The published page `slug`, or 404.
+async fn find_published_page(pool: &db::DbPool, slug: &str) -> Result<SitePage, AppError> {
+    let lookup_slug = slug.trim().to_lowercase();
+    if lookup_slug.is_empty() {
+        return Err(bad_request("Slug cannot be empty"));
     }
 
-    // Return the bundle
-    Ok(Json(SitePageWithPostsResponse {
-        page: map_page(page)?,
-        posts: post_responses,
-    }))
+    repositories::pages::get_site_page_by_slug(pool, &lookup_slug)
+        .await
+        .map_err(|err| map_sqlx_error(err, "Site page"))?
+        .filter(|page| page.is_published)
+        .ok_or_else(|| not_found("Page not found"))
+}
+
+async fn list_post_summaries(
+    pool: &db::DbPool,
+    page_id: &str,
+    filter: PostFilter<'_>,
+    order_by: &'static str,
+    limit: i64,
+    offset: i64,
+) -> Result<Json<Paginated<SitePostSummaryResponse>>, AppError> {
+    let total = repositories::posts::count_published_posts(pool, page_id, filter)
+        .await
+        .map_err(|err| map_sqlx_error(err, "Posts"))?;
+    let posts = repositories::posts::list_published_post_summaries(
+        pool, page_id, filter, order_by, limit, offset,
+    )
+    .await
+    .map_err(|err| map_sqlx_error(err, "Posts"))?;
+
+    let items = posts
+        .into_iter()
+        .map(SitePostSummaryResponse::from)
+        .collect();
+    Ok(Json(Paginated::new(items, total, limit, offset)))
+}
+
+/// Handler to list the tags of published posts with how many posts carry
+/// each. Publicly accessible.
+pub async fn list_tags(State(pool): State<db::DbPool>) -> Result<Json<Vec<TagCount>>, AppError> {
+    let tags = repositories::posts::list_tag_counts(&pool)
+        .await
+        .map_err(internal_error("Failed to fetch tags"))?;
+    Ok(Json(tags))
+}
+
+/// Keeps a draft shown through a preview link out of search engines.
+fn mark_preview(mut response: Response) -> Response {
+    response.headers_mut().insert(
+        "x-robots-tag",
+        HeaderValue::from_static("noindex, nofollow"),
+    );
+    response
 }
 
 /// Handler to retrieve the dynamic navigation menu.
 /// Publicly accessible. Generates a list of navigation items ordered by index.
-pub async fn get_navigation(
-    State(pool): State<db::DbPool>,
-) -> Result<Json<NavigationResponse>, ApiError> {
+/// Served from the published page cache, reported in `Cache-Status`.
+pub async fn get_navigation(State(pool): State<db::DbPool>) -> Result<Response, AppError> {
     // Fetch all records marked for navigation display
-    let pages = repositories::pages::list_nav_pages(&pool)
+    let (pages, cache_status) = repositories::pages::list_nav_pages_cached(&pool)
         .await
         .map_err(|err| map_sqlx_error(err, "Navigation"))?;
 
@@ -227,15 +676,30 @@ pub async fn get_navigation(
         });
     }
 
-    Ok(Json(NavigationResponse { items }))
+    Ok(cache::with_cache_status(
+        Json(NavigationResponse { items }).into_response(),
+        cache_status,
+    ))
 }
 
 /// Handler to retrieve a specific published post by both page and post slugs.
-/// Publicly accessible. Used for the dynamic routing of blog posts.
+/// Publicly accessible. Used for the dynamic routing of blog posts. Each
+/// read counts as a view of the post. With `?format=html` the content is
+/// also returned rendered and sanitized. `toc` lists the headings, empty
+/// for very long posts unless asked for with `?toc=true`. `previous` and `next` link to the
+/// neighboring published posts of the page. A post renamed since is found
+/// under its former slugs, with `canonical_slug` naming the current one.
+/// Supports `If-None-Match`. An unpublished post is shown, without counting
+/// a view, with a `?preview=` token issued for it; its page must be
+/// published.
 pub async fn get_published_post_by_slug(
     State(pool): State<db::DbPool>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
     Path((page_slug, post_slug)): Path<(String, String)>,
-) -> Result<Json<SitePostDetailResponse>, ApiError> {
+    Query(query): Query<ContentFormatQuery>,
+    Query(preview): Query<PreviewQuery>,
+) -> Result<Response, AppError> {
     // Basic validation of slug components
     let lookup_page_slug = page_slug.trim().to_lowercase();
     let lookup_post_slug = post_slug.trim().to_lowercase();
@@ -254,32 +718,96 @@ pub async fn get_published_post_by_slug(
         return Err(not_found("Page not published"));
     }
 
-    // Step 2: Find the specific post belonging to this page
-    let post = repositories::posts::get_published_post_by_slug(&pool, &page.id, &lookup_post_slug)
+    // Step 2: Find the specific post belonging to this page; a draft only
+    // with a preview token issued for it
+    let mut post = if preview.preview.is_some() {
+        repositories::posts::get_post_by_slug(&pool, &page.id, &lookup_post_slug).await
+    } else {
+        repositories::posts::get_published_post_by_slug(&pool, &page.id, &lookup_post_slug).await
+    }
+    .map_err(|err| map_sqlx_error(err, "Post"))?;
+
+    // A renamed post is still found under its former slugs
+    let mut canonical_slug = None;
+    if post.is_none() {
+        post = repositories::posts::get_published_post_by_former_slug(
+            &pool,
+            &page.id,
+            &lookup_post_slug,
+        )
         .await
-        .map_err(|err| map_sqlx_error(err, "Post"))?
-        .ok_or_else(|| not_found("Post not found"))?;
+        .map_err(|err| map_sqlx_error(err, "Post"))?;
+        canonical_slug = post.as_ref().map(|post| post.slug.clone());
+    }
+    let post = post.ok_or_else(|| not_found("Post not found"))?;
+
+    let previewing = !post.is_published;
+    if previewing {
+        if !preview_grants(
+            &pool,
+            PreviewKind::Post,
+            &post.id,
+            preview.preview.as_deref(),
+        )
+        .await?
+        {
+            return Err(not_found("Post not found"));
+        }
+    } else {
+        let client_ip = security_middleware::extract_client_ip(&headers, addr.ip());
+        views::record(&pool, "post", &post.id, client_ip);
+    }
+
+    let (previous, next) = repositories::posts::get_adjacent_posts(&pool, &page.id, &post.id)
+        .await
+        .map_err(|err| map_sqlx_error(err, "Post"))?;
 
     // Assemble the full detail response
-    Ok(Json(SitePostDetailResponse {
-        page: map_page(page)?,
-        post: map_post(post),
-    }))
+    let mut post = map_post(post);
+    let rendered = (query.format == ContentFormat::Html).then(|| {
+        // Posts have no version counter and `updated_at` only has second
+        // resolution, so the content digest stands in for one
+        let version = crate::security::sha256_hex(post.content_markdown.as_bytes());
+        crate::markdown::render_document_cached("post", &post.id, &version, &post.content_markdown)
+    });
+    post.toc = Some(content_toc(
+        &post.content_markdown,
+        &query,
+        rendered.as_deref(),
+    ));
+    post.content_html = rendered.map(|document| document.html.clone());
+    let detail = SitePostDetailResponse {
+        page: map_public_page(page)?,
+        post,
+        previous,
+        next,
+        canonical_slug,
+    };
+    // No Last-Modified: the neighbors can change without bumping anything
+    // in the response.
+    let response = conditional_json(&headers, &detail, None)?;
+    Ok(if previewing {
+        mark_preview(response)
+    } else {
+        response
+    })
 }
 
 /// Handler to list all published page slugs.
-/// Publicly accessible. Useful for generating sitemaps or static path pre-generation.
+/// Publicly accessible. Useful for generating sitemaps or static path pre-generation,
+/// so pages excluded from indexing are left out.
 pub async fn list_published_page_slugs(
     State(pool): State<db::DbPool>,
-) -> Result<Json<Vec<String>>, ApiError> {
+) -> Result<Json<Vec<String>>, AppError> {
     // Load all published pages
-    let pages = repositories::pages::list_published_pages(&pool)
+    let pages = repositories::pages::list_published_pages_cached(&pool)
         .await
         .map_err(|err| map_sqlx_error(err, "Navigation"))?;
 
     // Extract and normalize slugs
     let slugs = pages
-        .into_iter()
+        .iter()
+        .filter(|page| !robots_excludes_indexing(page.meta_robots.as_deref()))
         .filter_map(|page| {
             let normalized = page.slug.trim().to_lowercase();
             if normalized.is_empty() {
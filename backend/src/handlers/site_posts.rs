@@ -5,16 +5,20 @@
 
 use crate::{
     db,
-    handlers::common::{ensure_admin, map_sqlx_error},
+    handlers::{
+        changelog,
+        common::{map_sqlx_error, require_permission, DeleteQuery},
+    },
     models::{
-        bad_request, not_found, ApiError, CreateSitePostRequest, SitePostListResponse,
-        SitePostResponse, UpdateSitePostRequest,
+        bad_request, not_found, AppError, ContentEventKind, CreateSitePostRequest,
+        SitePostListResponse, SitePostResponse, SitePostRevision, SitePostRevisionSummary,
+        UpdateSitePostRequest,
     },
     repositories,
-    security::auth,
+    security::auth::{self, Permission},
 };
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     Json,
 };
@@ -27,6 +31,14 @@ const MAX_SLUG_LEN: usize = 100;
 const MAX_EXCERPT_LEN: usize = 500;
 /// Maximum length for the markdown content of a post (100KB)
 const MAX_CONTENT_LEN: usize = 100_000;
+/// Maximum number of tags on a post
+const MAX_TAGS: usize = 20;
+/// Maximum length of a single tag (50 characters)
+const MAX_TAG_LEN: usize = 50;
+/// Maximum length of the meta description (300 characters)
+const MAX_META_DESCRIPTION_LEN: usize = 300;
+/// Maximum length of the cover image and canonical URLs
+const MAX_URL_LEN: usize = 2048;
 
 /// Maps a database SitePost record to a public response structure.
 fn map_post(record: crate::models::SitePost) -> SitePostResponse {
@@ -37,26 +49,141 @@ fn map_post(record: crate::models::SitePost) -> SitePostResponse {
         slug: record.slug,
         excerpt: record.excerpt,
         content_markdown: record.content_markdown,
+        content_html: None,
         is_published: record.is_published,
         published_at: record.published_at,
         order_index: record.order_index,
+        cover_image_url: record.cover_image_url,
+        meta_description: record.meta_description,
+        canonical_url: record.canonical_url,
         created_at: record.created_at,
         updated_at: record.updated_at,
         allow_comments: record.allow_comments,
+        tags: record.tags,
+        view_count: None,
+        toc: None,
     }
 }
 
 /// Normalizes a slug (trims and converts to lowercase).
-fn sanitize_slug(slug: &str) -> String {
+pub(crate) fn sanitize_slug(slug: &str) -> String {
     slug.trim().to_lowercase()
 }
 
-fn validate_post_fields(
+/// Normalizes a tag the way it is stored and looked up: trimmed,
+/// lowercase and cut to [`MAX_TAG_LEN`] characters.
+pub(crate) fn normalize_tag(tag: &str) -> String {
+    tag.trim()
+        .to_lowercase()
+        .chars()
+        .take(MAX_TAG_LEN)
+        .collect()
+}
+
+/// Sanitizes the tags of a post, like `sanitize_topics` does for tutorials:
+/// normalizes each with [`normalize_tag`], skips blank ones and rejects
+/// duplicates. Returns the tags sorted; an empty list is fine.
+pub(crate) fn sanitize_tags(tags: &[String]) -> Result<Vec<String>, String> {
+    // SECURITY: Limit number of tags to keep the tag index small
+    if tags.len() > MAX_TAGS {
+        return Err(format!("Too many tags (max {MAX_TAGS})"));
+    }
+
+    let mut sanitized = Vec::with_capacity(tags.len());
+    for tag in tags {
+        let normalized = normalize_tag(tag);
+        if normalized.is_empty() {
+            continue;
+        }
+        if sanitized.contains(&normalized) {
+            return Err("Duplicate tags are not allowed".to_string());
+        }
+        sanitized.push(normalized);
+    }
+
+    sanitized.sort();
+    Ok(sanitized)
+}
+
+/// Checks a cover image reference: a path below `/uploads/` or an https
+/// URL. An empty value clears it.
+pub(crate) fn normalize_cover_image_url(value: Option<String>) -> Result<Option<String>, AppError> {
+    let Some(value) = non_empty(value) else {
+        return Ok(None);
+    };
+    if value.len() > MAX_URL_LEN {
+        return Err(bad_request(format!(
+            "Cover image URL too long (max {MAX_URL_LEN} characters)"
+        )));
+    }
+
+    let valid = if let Some(path) = value.strip_prefix("/uploads/") {
+        !path.is_empty()
+            && !path
+                .split('/')
+                .any(|segment| segment.is_empty() || segment == "..")
+            && !path.contains(|c: char| c.is_whitespace() || c.is_control() || c == '\\')
+    } else {
+        is_absolute_url(&value, &["https"])
+    };
+    if !valid {
+        return Err(bad_request(
+            "Cover image must be an /uploads/ path or an https URL",
+        ));
+    }
+    Ok(Some(value))
+}
+
+/// Checks a canonical URL: an absolute http(s) URL. An empty value clears
+/// it.
+pub(crate) fn normalize_canonical_url(value: Option<String>) -> Result<Option<String>, AppError> {
+    let Some(value) = non_empty(value) else {
+        return Ok(None);
+    };
+    if value.len() > MAX_URL_LEN {
+        return Err(bad_request(format!(
+            "Canonical URL too long (max {MAX_URL_LEN} characters)"
+        )));
+    }
+    if !is_absolute_url(&value, &["http", "https"]) {
+        return Err(bad_request("Canonical URL must be an absolute http(s) URL"));
+    }
+    Ok(Some(value))
+}
+
+/// Trims a meta description and checks its length. An empty value clears
+/// it.
+pub(crate) fn normalize_meta_description(
+    value: Option<String>,
+) -> Result<Option<String>, AppError> {
+    let Some(value) = non_empty(value) else {
+        return Ok(None);
+    };
+    if value.chars().count() > MAX_META_DESCRIPTION_LEN {
+        return Err(bad_request(format!(
+            "Meta description too long (max {MAX_META_DESCRIPTION_LEN} characters)"
+        )));
+    }
+    Ok(Some(value))
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+fn is_absolute_url(value: &str, schemes: &[&str]) -> bool {
+    url::Url::parse(value)
+        .is_ok_and(|url| schemes.contains(&url.scheme()) && url.host_str().is_some())
+}
+
+pub(crate) fn validate_post_fields(
     title: &str,
     slug: &str,
     excerpt: Option<&str>,
     content: &str,
-) -> Result<(), ApiError> {
+) -> Result<(), AppError> {
     let title = title.trim();
     if title.is_empty() {
         return Err(bad_request("Title cannot be empty"));
@@ -95,13 +222,13 @@ fn validate_post_fields(
 }
 
 /// Handler for listing all posts belonging to a specific site page.
-/// Admin-only.
+/// Admins and editors.
 pub async fn list_posts_for_page(
     claims: auth::Claims,
     State(pool): State<db::DbPool>,
     Path(page_id): Path<String>,
-) -> Result<Json<SitePostListResponse>, ApiError> {
-    ensure_admin(&claims)?;
+) -> Result<Json<SitePostListResponse>, AppError> {
+    require_permission(&claims, Permission::EditPosts)?;
 
     repositories::pages::get_site_page_by_id(&pool, &page_id)
         .await
@@ -112,41 +239,58 @@ pub async fn list_posts_for_page(
         .await
         .map_err(|err| map_sqlx_error(err, "Site post"))?;
 
+    let ids: Vec<String> = posts.iter().map(|post| post.id.clone()).collect();
+    let views = repositories::views::view_counts(&pool, "post", &ids)
+        .await
+        .map_err(|err| map_sqlx_error(err, "Site post"))?;
+
     let mut items = Vec::with_capacity(posts.len());
     for post in posts {
-        items.push(map_post(post));
+        let view_count = views.get(&post.id).copied().unwrap_or(0);
+        items.push(SitePostResponse {
+            view_count: Some(view_count),
+            ..map_post(post)
+        });
     }
 
     Ok(Json(SitePostListResponse { items }))
 }
 
 /// Handler to retrieve a single site post by its ID.
-/// Admin-only.
+/// Admins and editors.
 pub async fn get_post(
     claims: auth::Claims,
     State(pool): State<db::DbPool>,
     Path(id): Path<String>,
-) -> Result<Json<SitePostResponse>, ApiError> {
-    ensure_admin(&claims)?;
+) -> Result<Json<SitePostResponse>, AppError> {
+    require_permission(&claims, Permission::EditPosts)?;
 
     let post = repositories::posts::get_site_post_by_id(&pool, &id)
         .await
         .map_err(|err| map_sqlx_error(err, "Site post"))?
         .ok_or_else(|| not_found("Site post not found"))?;
 
-    Ok(Json(map_post(post)))
+    let views = repositories::views::view_counts(&pool, "post", std::slice::from_ref(&post.id))
+        .await
+        .map_err(|err| map_sqlx_error(err, "Site post"))?;
+    let view_count = views.get(&post.id).copied().unwrap_or(0);
+
+    Ok(Json(SitePostResponse {
+        view_count: Some(view_count),
+        ..map_post(post)
+    }))
 }
 
 /// Handler to create a new site post for a specific page.
-/// Admin-only, protected by CSRF.
+/// Admins and editors, protected by CSRF.
 pub async fn create_post(
     claims: auth::Claims,
     _csrf: crate::security::csrf::CsrfGuard,
     State(pool): State<db::DbPool>,
     Path(page_id): Path<String>,
     Json(payload): Json<CreateSitePostRequest>,
-) -> Result<Json<SitePostResponse>, ApiError> {
-    ensure_admin(&claims)?;
+) -> Result<Json<SitePostResponse>, AppError> {
+    require_permission(&claims, Permission::EditPosts)?;
 
     let trimmed_title = payload.title.trim().to_string();
     let sanitized_slug = sanitize_slug(&payload.slug);
@@ -157,6 +301,10 @@ pub async fn create_post(
         excerpt,
         &payload.content_markdown,
     )?;
+    let tags = sanitize_tags(&payload.tags).map_err(bad_request)?;
+    let cover_image_url = normalize_cover_image_url(payload.cover_image_url)?;
+    let meta_description = normalize_meta_description(payload.meta_description)?;
+    let canonical_url = normalize_canonical_url(payload.canonical_url)?;
 
     repositories::pages::get_site_page_by_id(&pool, &page_id)
         .await
@@ -175,11 +323,27 @@ pub async fn create_post(
             published_at: payload.published_at,
             order_index: payload.order_index,
             allow_comments: payload.allow_comments,
+            tags,
+            cover_image_url,
+            meta_description,
+            canonical_url,
         },
     )
     .await
     .map_err(|err| map_sqlx_error(err, "Site post"))?;
 
+    if record.is_published {
+        changelog::record_event(
+            &pool,
+            "post",
+            &record.id,
+            &record.slug,
+            &record.title,
+            ContentEventKind::Published,
+        )
+        .await;
+    }
+
     tracing::info!(
         action = "create_post",
         user = %claims.sub,
@@ -188,20 +352,29 @@ pub async fn create_post(
         page_id = %record.page_id,
         "Admin created new post"
     );
+    repositories::audit::append_entry(
+        &pool,
+        &claims.sub,
+        "create",
+        "post",
+        Some(&record.id),
+        serde_json::json!({ "title": record.title, "page_id": record.page_id }),
+    )
+    .await;
 
     Ok(Json(map_post(record)))
 }
 
 /// Handler to update an existing site post.
-/// Admin-only, protected by CSRF. Supports partial updates via UpdateSitePostRequest.
+/// Admins and editors, protected by CSRF. Supports partial updates via UpdateSitePostRequest.
 pub async fn update_post(
     claims: auth::Claims,
     _csrf: crate::security::csrf::CsrfGuard,
     State(pool): State<db::DbPool>,
     Path(id): Path<String>,
     Json(payload): Json<UpdateSitePostRequest>,
-) -> Result<Json<SitePostResponse>, ApiError> {
-    ensure_admin(&claims)?;
+) -> Result<Json<SitePostResponse>, AppError> {
+    require_permission(&claims, Permission::EditPosts)?;
 
     if let Some(ref slug) = payload.slug {
         let sanitized = sanitize_slug(slug);
@@ -250,10 +423,32 @@ pub async fn update_post(
     if let Some(slug) = payload.slug.as_mut() {
         *slug = sanitize_slug(slug);
     }
+    if let Some(tags) = payload.tags.as_mut() {
+        *tags = sanitize_tags(tags).map_err(bad_request)?;
+    }
+    if let Some(cover_image_url) = payload.cover_image_url.take() {
+        payload.cover_image_url = Some(normalize_cover_image_url(cover_image_url)?);
+    }
+    if let Some(meta_description) = payload.meta_description.take() {
+        payload.meta_description = Some(normalize_meta_description(meta_description)?);
+    }
+    if let Some(canonical_url) = payload.canonical_url.take() {
+        payload.canonical_url = Some(normalize_canonical_url(canonical_url)?);
+    }
 
-    let record = repositories::posts::update_site_post(&pool, &id, payload)
-        .await
-        .map_err(|err| map_sqlx_error(err, "Site post"))?;
+    let (previous, record) =
+        repositories::posts::update_site_post(&pool, &claims.sub, &id, payload)
+            .await
+            .map_err(|err| map_sqlx_error(err, "Site post"))?;
+
+    if let Some(kind) = repositories::events::classify_change(
+        previous.is_published,
+        record.is_published,
+        previous.content_markdown.len(),
+        record.content_markdown.len(),
+    ) {
+        changelog::record_event(&pool, "post", &record.id, &record.slug, &record.title, kind).await;
+    }
 
     tracing::info!(
         action = "update_post",
@@ -261,30 +456,248 @@ pub async fn update_post(
         post_id = %id,
         "Admin updated post"
     );
+    repositories::audit::append_entry(
+        &pool,
+        &claims.sub,
+        "update",
+        "post",
+        Some(&id),
+        serde_json::json!({ "title": record.title }),
+    )
+    .await;
+
+    Ok(Json(map_post(record)))
+}
+
+/// Handler listing the stored revisions of a post, newest first.
+/// Admins and editors.
+pub async fn list_post_revisions(
+    claims: auth::Claims,
+    State(pool): State<db::DbPool>,
+    Path(id): Path<String>,
+) -> Result<Json<Vec<SitePostRevisionSummary>>, AppError> {
+    require_permission(&claims, Permission::EditPosts)?;
+    ensure_post_exists(&pool, &id).await?;
+
+    let revisions = repositories::posts::list_revisions(&pool, &id)
+        .await
+        .map_err(|err| map_sqlx_error(err, "Site post revisions"))?;
+
+    Ok(Json(revisions))
+}
+
+/// Handler returning one stored revision of a post in full.
+/// Admins and editors.
+pub async fn get_post_revision(
+    claims: auth::Claims,
+    State(pool): State<db::DbPool>,
+    Path((id, revision)): Path<(String, i64)>,
+) -> Result<Json<SitePostRevision>, AppError> {
+    require_permission(&claims, Permission::EditPosts)?;
+    ensure_post_exists(&pool, &id).await?;
+
+    let revision = repositories::posts::get_revision(&pool, &id, revision)
+        .await
+        .map_err(|err| map_sqlx_error(err, "Site post revision"))?
+        .ok_or_else(|| not_found("Revision not found"))?;
+
+    Ok(Json(revision))
+}
+
+/// Handler saving a stored revision's title, slug, excerpt, content and
+/// publication state back onto the post. This is an ordinary update, so
+/// the state it replaces becomes a new revision. Tags, comment settings,
+/// ordering and the cover and SEO fields are left as they are. Admins and
+/// editors, protected by CSRF.
+pub async fn restore_post_revision(
+    claims: auth::Claims,
+    _csrf: crate::security::csrf::CsrfGuard,
+    State(pool): State<db::DbPool>,
+    Path((id, revision)): Path<(String, i64)>,
+) -> Result<Json<SitePostResponse>, AppError> {
+    require_permission(&claims, Permission::EditPosts)?;
+    ensure_post_exists(&pool, &id).await?;
+
+    let stored = repositories::posts::get_revision(&pool, &id, revision)
+        .await
+        .map_err(|err| map_sqlx_error(err, "Site post revision"))?
+        .ok_or_else(|| not_found("Revision not found"))?;
+
+    let payload = UpdateSitePostRequest {
+        title: Some(stored.title),
+        slug: Some(stored.slug),
+        excerpt: Some(stored.excerpt),
+        content_markdown: Some(stored.content_markdown),
+        is_published: Some(stored.is_published),
+        allow_comments: None,
+        published_at: Some(stored.published_at),
+        order_index: None,
+        tags: None,
+        cover_image_url: None,
+        meta_description: None,
+        canonical_url: None,
+    };
+    let (previous, record) =
+        repositories::posts::update_site_post(&pool, &claims.sub, &id, payload)
+            .await
+            .map_err(|err| map_sqlx_error(err, "Site post"))?;
+
+    if let Some(kind) = repositories::events::classify_change(
+        previous.is_published,
+        record.is_published,
+        previous.content_markdown.len(),
+        record.content_markdown.len(),
+    ) {
+        changelog::record_event(&pool, "post", &record.id, &record.slug, &record.title, kind).await;
+    }
+
+    tracing::info!(
+        action = "restore_post_revision",
+        user = %claims.sub,
+        post_id = %id,
+        revision,
+        "Admin restored post revision"
+    );
+    repositories::audit::append_entry(
+        &pool,
+        &claims.sub,
+        "restore_revision",
+        "post",
+        Some(&id),
+        serde_json::json!({ "revision": revision }),
+    )
+    .await;
 
     Ok(Json(map_post(record)))
 }
 
-/// Handler to permanently delete a site post.
-/// Admin-only, protected by CSRF.
+async fn ensure_post_exists(pool: &db::DbPool, id: &str) -> Result<(), AppError> {
+    repositories::posts::get_site_post_by_id(pool, id)
+        .await
+        .map_err(|err| map_sqlx_error(err, "Site post"))?
+        .ok_or_else(|| not_found("Site post not found"))?;
+    Ok(())
+}
+
+/// Handler to delete a site post.
+/// Admin-only (editors cannot delete), protected by CSRF. By default the
+/// post moves to the trash; `?permanent=true` removes it (trashed or not),
+/// which requires sudo mode.
 pub async fn delete_post(
     claims: auth::Claims,
     _csrf: crate::security::csrf::CsrfGuard,
     State(pool): State<db::DbPool>,
     Path(id): Path<String>,
-) -> Result<StatusCode, ApiError> {
-    ensure_admin(&claims)?;
-
-    repositories::posts::delete_site_post(&pool, &id)
+    Query(params): Query<DeleteQuery>,
+) -> Result<StatusCode, AppError> {
+    require_permission(&claims, Permission::DeleteContent)?;
+
+    if params.permanent {
+        claims.require_sudo()?;
+        repositories::posts::delete_site_post(&pool, &id, &claims.sub)
+            .await
+            .map_err(|err| map_sqlx_error(err, "Site post"))?;
+    } else if !repositories::posts::trash_site_post(&pool, &id)
         .await
-        .map_err(|err| map_sqlx_error(err, "Site post"))?;
+        .map_err(|err| map_sqlx_error(err, "Site post"))?
+    {
+        return Err(not_found("Site post not found"));
+    }
 
     tracing::info!(
-        action = "delete_post",
+        action = if params.permanent { "delete_post" } else { "trash_post" },
         user = %claims.sub,
         post_id = %id,
         "Admin deleted post"
     );
+    repositories::audit::append_entry(
+        &pool,
+        &claims.sub,
+        if params.permanent { "delete" } else { "trash" },
+        "post",
+        Some(&id),
+        serde_json::json!({}),
+    )
+    .await;
 
     Ok(StatusCode::NO_CONTENT)
 }
+
+/// Handler to take a site post out of the trash.
+/// Admin-only, like deleting. A post of a trashed page stays hidden until
+/// the page is restored too.
+pub async fn restore_post(
+    claims: auth::Claims,
+    _csrf: crate::security::csrf::CsrfGuard,
+    State(pool): State<db::DbPool>,
+    Path(id): Path<String>,
+) -> Result<Json<SitePostResponse>, AppError> {
+    require_permission(&claims, Permission::DeleteContent)?;
+
+    if !repositories::posts::restore_site_post(&pool, &id)
+        .await
+        .map_err(|err| map_sqlx_error(err, "Site post"))?
+    {
+        return Err(not_found("Site post is not in the trash"));
+    }
+    let post = repositories::posts::get_site_post_by_id(&pool, &id)
+        .await
+        .map_err(|err| map_sqlx_error(err, "Site post"))?
+        .ok_or_else(|| not_found("Site post not found"))?;
+
+    repositories::audit::append_entry(
+        &pool,
+        &claims.sub,
+        "restore",
+        "post",
+        Some(&id),
+        serde_json::json!({ "title": post.title }),
+    )
+    .await;
+
+    Ok(Json(map_post(post)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cover_images_must_be_uploads_or_https() {
+        assert_eq!(
+            normalize_cover_image_url(Some(" /uploads/cover.webp ".into())).unwrap(),
+            Some("/uploads/cover.webp".to_string())
+        );
+        assert!(normalize_cover_image_url(Some("https://cdn.example.com/a.png".into())).is_ok());
+        assert_eq!(normalize_cover_image_url(Some("  ".into())).unwrap(), None);
+
+        for invalid in [
+            "http://cdn.example.com/a.png",
+            "/uploads/../secret",
+            "/uploads/",
+            "/static/a.png",
+            "javascript:alert(1)",
+            "https://",
+        ] {
+            assert!(
+                normalize_cover_image_url(Some(invalid.into())).is_err(),
+                "{invalid}"
+            );
+        }
+    }
+
+    #[test]
+    fn canonical_urls_and_meta_descriptions_are_checked() {
+        assert!(normalize_canonical_url(Some("http://example.com/post".into())).is_ok());
+        assert!(normalize_canonical_url(Some("/posts/journal/a".into())).is_err());
+        assert!(normalize_canonical_url(Some("ftp://example.com/a".into())).is_err());
+
+        let at_limit = "ä".repeat(MAX_META_DESCRIPTION_LEN);
+        assert!(normalize_meta_description(Some(at_limit.clone())).is_ok());
+        assert!(normalize_meta_description(Some(format!("{at_limit}a"))).is_err());
+        assert_eq!(
+            normalize_meta_description(Some(String::new())).unwrap(),
+            None
+        );
+    }
+}
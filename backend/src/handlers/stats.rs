@@ -0,0 +1,241 @@
+//! Admin Dashboard Statistics Handlers
+//!
+//! Exposes aggregate content volume (word counts, drafts, stale documents)
+//! so editors can see at a glance where content is thin or outdated, and
+//! daily counts of rejected CSRF checks, tokens and logins, and how often
+//! tutorials and posts were read.
+
+use crate::{
+    db::DbPool,
+    handlers::common::ensure_admin,
+    models::*,
+    repositories,
+    security::{auth, rejections},
+};
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use chrono::NaiveDate;
+use std::collections::BTreeMap;
+
+/// Documents untouched for longer than this are flagged for review.
+const STALE_AFTER_DAYS: i64 = 365;
+
+/// Handler for `GET /api/admin/stats/content`.
+/// Admin-only. Word counts are cached at write time, so this stays cheap
+/// even with hundreds of documents.
+pub async fn content_stats(
+    claims: auth::Claims,
+    State(pool): State<DbPool>,
+) -> Result<Json<ContentStatsResponse>, AppError> {
+    ensure_admin(&claims)?;
+
+    // Rows inserted outside the repositories (e.g. by the import binary)
+    // since startup have no cached counts yet. A no-op in the common case.
+    repositories::stats::backfill_content_stats(&pool)
+        .await
+        .map_err(internal_error("Failed to compute content statistics"))?;
+
+    let stats = repositories::stats::content_overview(&pool, STALE_AFTER_DAYS)
+        .await
+        .map_err(internal_error("Failed to compute content statistics"))?;
+
+    Ok(Json(stats))
+}
+
+/// Handler for `GET /api/admin/security/summary`.
+/// Admin-only. Covers the last [`rejections::SUMMARY_DAYS`] days; a spike in
+/// one reason usually means an attack or a frontend regression.
+pub async fn security_summary(
+    claims: auth::Claims,
+    State(pool): State<DbPool>,
+) -> Result<Json<SecuritySummaryResponse>, AppError> {
+    ensure_admin(&claims)?;
+
+    let daily = repositories::security_counters::list_recent(&pool, rejections::SUMMARY_DAYS)
+        .await
+        .map_err(internal_error("Failed to load security counters"))?;
+
+    let mut totals: BTreeMap<(String, String), i64> = BTreeMap::new();
+    for row in &daily {
+        *totals
+            .entry((row.source.clone(), row.reason.clone()))
+            .or_default() += row.count;
+    }
+    let mut by_reason: Vec<SecurityReasonTotal> = totals
+        .into_iter()
+        .map(|((source, reason), count)| SecurityReasonTotal {
+            source,
+            reason,
+            count,
+        })
+        .collect();
+    by_reason.sort_by_key(|entry| std::cmp::Reverse(entry.count));
+
+    Ok(Json(SecuritySummaryResponse {
+        days: rejections::SUMMARY_DAYS,
+        total: by_reason.iter().map(|entry| entry.count).sum(),
+        by_reason,
+        daily,
+    }))
+}
+
+/// Handler for `GET /api/admin/stats/views`.
+/// Admin-only. `entity_type` is `tutorial` (default) or `post`; `from` and
+/// `to` are inclusive `YYYY-MM-DD` days and may each be left open.
+pub async fn view_stats(
+    claims: auth::Claims,
+    State(pool): State<DbPool>,
+    Query(params): Query<ViewStatsQuery>,
+) -> Result<Json<ViewStatsResponse>, AppError> {
+    ensure_admin(&claims)?;
+
+    let entity_type = params.entity_type.as_deref().unwrap_or("tutorial");
+    if !matches!(entity_type, "tutorial" | "post") {
+        return Err(bad_request("entity_type must be 'tutorial' or 'post'"));
+    }
+    let from = parse_day(params.from.as_deref(), "from")?;
+    let to = parse_day(params.to.as_deref(), "to")?;
+    if let (Some(from), Some(to)) = (&from, &to) {
+        if from > to {
+            return Err(bad_request("'from' must not be after 'to'"));
+        }
+    }
+
+    let totals = repositories::views::totals(&pool, entity_type, from.as_deref(), to.as_deref())
+        .await
+        .map_err(internal_error("Failed to load view statistics"))?;
+
+    Ok(Json(ViewStatsResponse {
+        entity_type: entity_type.to_string(),
+        from,
+        to,
+        totals,
+    }))
+}
+
+/// Normalizes an optional `YYYY-MM-DD` query parameter.
+fn parse_day(value: Option<&str>, name: &str) -> Result<Option<String>, AppError> {
+    let Some(value) = value.map(str::trim).filter(|value| !value.is_empty()) else {
+        return Ok(None);
+    };
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map(|day| Some(day.format("%Y-%m-%d").to_string()))
+        .map_err(|_| bad_request(format!("'{name}' must be a date (YYYY-MM-DD)")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn overview_reports_word_counts_drafts_and_stale_documents() {
+        let pool = crate::test_support::migrated_pool().await;
+
+        sqlx::query("DELETE FROM tutorials")
+            .execute(&pool)
+            .await
+            .unwrap();
+        for (id, content) in [("short", "one two"), ("long", "one two three four")] {
+            repositories::tutorials::create_tutorial(
+                &pool,
+                id,
+                id,
+                "Description",
+                content,
+                "Terminal",
+                "from-blue-500 to-indigo-600",
+                "[]",
+                &[],
+                TUTORIAL_STATUS_PUBLISHED,
+                true,
+                None,
+            )
+            .await
+            .unwrap();
+        }
+        sqlx::query("UPDATE tutorials SET updated_at = '2020-01-01 00:00:00' WHERE id = 'short'")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        sqlx::query(
+            "INSERT INTO site_pages (id, slug, title, is_published) VALUES ('p1', 'blog', 'Blog', 1)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        // Inserted without content_stats, like the import binary does.
+        sqlx::query(concat!(
+            "INSERT INTO site_posts (id, page_id, title, slug, content_markdown, is_published) ",
+            "VALUES ('a', 'p1', 'A', 'a', 'alpha beta gamma', 1), ",
+            "('b', 'p1', 'B', 'b', 'delta', 0)"
+        ))
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let claims = crate::test_support::claims_for("admin", "admin");
+        let Json(stats) = content_stats(claims, State(pool)).await.expect("stats");
+
+        assert_eq!(stats.tutorials.documents, 2);
+        assert_eq!(stats.tutorials.total_words, 6);
+        assert_eq!(stats.tutorials.average_words, 3.0);
+        assert_eq!(stats.tutorials.longest.as_ref().unwrap().id, "long");
+        assert_eq!(stats.tutorials.shortest.as_ref().unwrap().id, "short");
+
+        let blog = stats.pages.iter().find(|p| p.page_id == "p1").unwrap();
+        assert_eq!(blog.posts.documents, 2);
+        assert_eq!(blog.posts.published, 1);
+        assert_eq!(blog.posts.drafts, 1);
+        assert_eq!(blog.posts.total_words, 4);
+        assert_eq!(blog.posts.longest.as_ref().unwrap().id, "a");
+        assert_eq!(blog.posts.shortest.as_ref().unwrap().id, "b");
+
+        let stale: Vec<_> = stats
+            .stale_documents
+            .iter()
+            .map(|d| d.id.as_str())
+            .collect();
+        assert_eq!(stale, vec!["short"]);
+    }
+
+    #[tokio::test]
+    async fn security_summary_totals_recorded_rejections() {
+        let pool = crate::test_support::migrated_pool().await;
+
+        for reason in [
+            RejectionReason::Mismatch,
+            RejectionReason::Mismatch,
+            RejectionReason::Missing,
+        ] {
+            rejections::record(&pool, RejectionSource::Csrf, reason);
+        }
+        rejections::record(&pool, RejectionSource::Token, RejectionReason::Expired);
+
+        // Recording is fire-and-forget; wait for the spawned writes.
+        let claims = crate::test_support::claims_for("admin", "admin");
+        let mut summary = None;
+        for _ in 0..50 {
+            let Json(current) = security_summary(claims.clone(), State(pool.clone()))
+                .await
+                .expect("summary");
+            if current.total == 4 {
+                summary = Some(current);
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        let summary = summary.expect("all rejections recorded");
+
+        assert_eq!(summary.days, rejections::SUMMARY_DAYS);
+        let top = &summary.by_reason[0];
+        assert_eq!(
+            (top.source.as_str(), top.reason.as_str(), top.count),
+            ("csrf", "mismatch", 2)
+        );
+        assert_eq!(summary.by_reason.len(), 3);
+        assert_eq!(summary.daily.len(), 3);
+    }
+}
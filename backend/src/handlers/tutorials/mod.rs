@@ -6,24 +6,61 @@
 //!
 //! Tutorials are structured with:
 //! - Metadata: Title, Description, Topics, Icon (Lucide), Color (Tailwind)
-//! - Content: Markdown-based learning material
+//! - Content: Markdown-based learning material, optionally split into
+//!   ordered sections that can be edited and deep-linked one at a time
 //! - Versioning: Optimistic concurrency control via version numbers
 //! - Identifiers: Custom slugs or auto-generated UUIDs
+//! - Status: `draft` tutorials are visible only to roles that may edit
+//!   tutorials until they are published
+//! - Trash: deleting moves a tutorial to the trash, from which an admin can
+//!   restore it; only `?permanent=true` removes it for good
 
-use crate::{db::DbPool, handlers::common::ensure_admin, models::*, repositories, security::auth};
+use crate::{
+    db::DbPool,
+    handlers::{
+        changelog,
+        common::{
+            conditional_json, content_toc, ensure_admin, require_permission, ContentFormat,
+            ContentFormatQuery,
+        },
+        patch,
+    },
+    middleware::security as security_middleware,
+    models::*,
+    repositories,
+    security::auth::{self, Permission},
+    views,
+};
 use axum::{
-    extract::{Path, Query, State},
-    http::StatusCode,
+    body::Bytes,
+    extract::{ConnectInfo, Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::Response,
     Json,
 };
 use serde::Deserialize;
 use std::collections::HashSet;
 use std::convert::TryInto;
+use std::net::SocketAddr;
 use uuid::Uuid;
 
+mod revisions;
+mod sections;
+mod transfer;
 mod validation;
-use validation::*;
-pub(crate) use validation::{validate_color, validate_icon, validate_tutorial_id};
+pub use revisions::{get_revision, list_revisions, restore_revision};
+pub use sections::{
+    create_section, delete_section, list_sections, reorder_sections, update_section,
+};
+pub(crate) use transfer::validate_imported_comment;
+pub use transfer::{
+    export_tutorial, export_tutorial_markdown, import_tutorial, import_tutorial_markdown,
+};
+use validation::parse_topic_filter;
+pub(crate) use validation::{
+    sanitize_topics, validate_color, validate_icon, validate_icon_name, validate_tutorial_data,
+    validate_tutorial_id, validate_tutorial_status,
+};
 
 /// Query parameters for paginated tutorial listing.
 #[derive(Deserialize)]
@@ -35,6 +72,39 @@ pub struct TutorialListQuery {
     /// Number of items to skip for pagination
     #[serde(default)]
     offset: i64,
+
+    /// Only tutorials with this topic (case-insensitive)
+    #[serde(default)]
+    topic: Option<String>,
+
+    /// Only tutorials with all of these comma-separated topics
+    #[serde(default)]
+    topics: Option<String>,
+
+    /// Return a `{ items, total, limit, offset, hasMore }` envelope instead
+    /// of a bare array
+    #[serde(default)]
+    envelope: bool,
+}
+
+/// Query parameters of `GET /api/topics/{topic}/tutorials`.
+#[derive(Deserialize)]
+pub struct TopicTutorialsQuery {
+    /// Number of items to return (default: 50, max: 100)
+    #[serde(default = "default_tutorial_limit")]
+    limit: i64,
+
+    /// Number of items to skip for pagination
+    #[serde(default)]
+    offset: i64,
+}
+
+/// Query parameters of `DELETE /api/tutorials/{id}`.
+#[derive(Deserialize)]
+pub struct DeleteTutorialQuery {
+    /// Remove the tutorial for good instead of moving it to the trash.
+    #[serde(default)]
+    permanent: bool,
 }
 
 /// Default limit for tutorial lists
@@ -42,69 +112,256 @@ fn default_tutorial_limit() -> i64 {
     50
 }
 
-/// Handler for listing tutorials with pagination.
+/// Whether the caller may see draft tutorials: anyone allowed to edit them.
+pub(crate) fn can_see_drafts(claims: Option<&auth::Claims>) -> bool {
+    claims.is_some_and(|claims| claims.can(Permission::EditTutorials))
+}
+
+/// 404s for unknown and trashed tutorials, whose revisions and sections
+/// stay hidden along with them.
+async fn ensure_tutorial_exists(pool: &DbPool, id: &str) -> Result<(), AppError> {
+    let exists = repositories::tutorials::check_tutorial_exists(pool, id)
+        .await
+        .map_err(internal_error("Failed to fetch tutorial"))?;
+    if exists {
+        Ok(())
+    } else {
+        Err(not_found("Tutorial not found"))
+    }
+}
+
+/// Replaces recorded usernames with `PUBLIC_AUTHOR_NAME` for callers who
+/// may not edit tutorials, or drops them while that is unset. Usernames are
+/// login names and are not meant for the public.
+pub(crate) fn mask_authorship(created_by: &mut Option<String>, updated_by: &mut Option<String>) {
+    let public_name = crate::config::get().public_author_name.as_ref();
+    for name in [created_by, updated_by] {
+        *name = name.as_ref().and(public_name).cloned();
+    }
+}
+
+/// Handler for listing tutorials with pagination, optionally narrowed to
+/// tutorials having every topic given in `topic` and `topics`.
 /// Publicly accessible. Excludes full tutorial content to minimize payload size.
+/// Drafts are listed only for callers who may edit tutorials. With
+/// `envelope=true` the page comes wrapped with the total count. Tagged with
+/// an ETag for conditional requests.
 pub async fn list_tutorials(
     State(pool): State<DbPool>,
+    auth::OptionalClaims(claims): auth::OptionalClaims,
+    headers: HeaderMap,
     Query(params): Query<TutorialListQuery>,
-) -> Result<Json<Vec<TutorialSummaryResponse>>, ApiError> {
+) -> Result<Response, AppError> {
     // Clamp pagination parameters
     let limit = params.limit.clamp(1, 100);
     let offset = params.offset.max(0);
+    let topics = parse_topic_filter(params.topic.as_deref(), params.topics.as_deref())
+        .map_err(bad_request)?;
 
     // Optimized repository call: Fetches summary data without markdown content
-    let tutorials = repositories::tutorials::list_tutorials(&pool, limit, offset)
+    let include_drafts = can_see_drafts(claims.as_ref());
+    let tutorials = if topics.is_empty() {
+        repositories::tutorials::list_tutorials(&pool, limit, offset, include_drafts).await
+    } else {
+        repositories::tutorials::list_tutorials_by_topics(
+            &pool,
+            &topics,
+            limit,
+            offset,
+            include_drafts,
+        )
+        .await
+    }
+    .map_err(internal_error("Failed to fetch tutorials"))?;
+
+    let responses = summarize(&pool, tutorials, include_drafts).await?;
+
+    let list = if params.envelope {
+        let total = repositories::tutorials::count_tutorials(&pool, &topics, include_drafts)
+            .await
+            .map_err(internal_error("Failed to fetch tutorials"))?;
+        TutorialListResponse::Page(Paginated::new(responses, total, limit, offset))
+    } else {
+        TutorialListResponse::Items(responses)
+    };
+
+    // No Last-Modified: deleting a tutorial changes the list without
+    // bumping any remaining `updated_at`.
+    conditional_json(&headers, &list, None)
+}
+
+/// Handler for `GET /api/topics/{topic}/tutorials`: the tutorials tagged
+/// with `topic` (case-insensitive), paginated. Publicly accessible, with
+/// drafts only for callers who may edit tutorials. 404s for a topic no
+/// visible tutorial has.
+pub async fn list_topic_tutorials(
+    State(pool): State<DbPool>,
+    auth::OptionalClaims(claims): auth::OptionalClaims,
+    Path(topic): Path<String>,
+    Query(params): Query<TopicTutorialsQuery>,
+) -> Result<Json<Paginated<TutorialSummaryResponse>>, AppError> {
+    let limit = params.limit.clamp(1, 100);
+    let offset = params.offset.max(0);
+    let topics = parse_topic_filter(Some(&topic), None).map_err(bad_request)?;
+    if topics.is_empty() {
+        return Err(bad_request("Topic must not be empty"));
+    }
+
+    let include_drafts = can_see_drafts(claims.as_ref());
+    let total = repositories::tutorials::count_tutorials(&pool, &topics, include_drafts)
         .await
         .map_err(internal_error("Failed to fetch tutorials"))?;
+    if total == 0 {
+        return Err(not_found("Topic not found"));
+    }
+    let tutorials = repositories::tutorials::list_tutorials_by_topics(
+        &pool,
+        &topics,
+        limit,
+        offset,
+        include_drafts,
+    )
+    .await
+    .map_err(internal_error("Failed to fetch tutorials"))?;
+
+    let responses = summarize(&pool, tutorials, include_drafts).await?;
+    Ok(Json(Paginated::new(responses, total, limit, offset)))
+}
+
+/// Turns listed tutorials into summaries: editors get view counts, everyone
+/// else masked authorship.
+async fn summarize(
+    pool: &DbPool,
+    tutorials: Vec<Tutorial>,
+    include_drafts: bool,
+) -> Result<Vec<TutorialSummaryResponse>, AppError> {
+    // View counts are editor-only
+    let views = if include_drafts {
+        let ids: Vec<String> = tutorials.iter().map(|t| t.id.clone()).collect();
+        Some(
+            repositories::views::view_counts(pool, "tutorial", &ids)
+                .await
+                .map_err(internal_error("Failed to fetch tutorials"))?,
+        )
+    } else {
+        None
+    };
 
     // Transform database records into summary response models
     let mut responses = Vec::with_capacity(tutorials.len());
     for tutorial in tutorials {
         // TryInto implementation handles JSON parsing of the 'topics' field
-        let response: TutorialSummaryResponse = tutorial
+        let mut response: TutorialSummaryResponse = tutorial
             .try_into()
             .map_err(internal_error("Failed to parse stored tutorial data"))?;
+        if let Some(views) = &views {
+            response.view_count = Some(views.get(&response.id).copied().unwrap_or(0));
+        } else {
+            mask_authorship(&mut response.created_by, &mut response.updated_by);
+        }
         responses.push(response);
     }
-
-    Ok(Json(responses))
+    Ok(responses)
 }
 
 /// Handler to retrieve full details of a specific tutorial by its string ID.
-/// Publicly accessible. Includes full markdown content.
+/// Publicly accessible. Includes full markdown content. A draft is reported
+/// as missing to callers who may not edit tutorials. A former ID of a
+/// renamed tutorial serves the tutorial with `canonical_id` set. Each read of
+/// a published tutorial counts as a view. The tutorial's sections come along
+/// in reading order; when there are none, `content` is the whole tutorial.
+/// With `?format=html` the content is also returned rendered and sanitized.
+/// `toc` lists the headings of `content`; it stays empty for very long
+/// documents unless asked for with `?toc=true`. Supports `If-None-Match`
+/// and `If-Modified-Since`.
 pub async fn get_tutorial(
     State(pool): State<DbPool>,
+    auth::OptionalClaims(claims): auth::OptionalClaims,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
     Path(id): Path<String>,
-) -> Result<Json<TutorialResponse>, ApiError> {
+    Query(query): Query<ContentFormatQuery>,
+) -> Result<Response, AppError> {
     // Validate ID format before touching the database
     validate_tutorial_id(&id).map_err(bad_request)?;
 
     // Attempt to retrieve record from database
-    let tutorial = repositories::tutorials::get_tutorial(&pool, &id)
+    let mut tutorial = repositories::tutorials::get_tutorial(&pool, &id)
         .await
-        .map_err(internal_error("Failed to fetch tutorial"))?
+        .map_err(internal_error("Failed to fetch tutorial"))?;
+    let mut redirected = false;
+    if tutorial.is_none() {
+        // Old links to a renamed tutorial
+        if let Some(current_id) = repositories::tutorials::resolve_redirect(&pool, &id)
+            .await
+            .map_err(internal_error("Failed to fetch tutorial"))?
+        {
+            tutorial = repositories::tutorials::get_tutorial(&pool, &current_id)
+                .await
+                .map_err(internal_error("Failed to fetch tutorial"))?;
+            redirected = true;
+        }
+    }
+    let tutorial = tutorial
         // Handle 404
+        .filter(|tutorial| tutorial.is_published() || can_see_drafts(claims.as_ref()))
         .ok_or_else(|| not_found("Tutorial not found"))?;
 
+    if tutorial.is_published() {
+        let client_ip = security_middleware::extract_client_ip(&headers, addr.ip());
+        views::record(&pool, "tutorial", &tutorial.id, client_ip);
+    }
+
     // Transform database record (Tutorial) into full response model (TutorialResponse)
     // This step parses the 'topics' JSON string into a Vec<String>.
-    let response: TutorialResponse = tutorial
+    let mut response: TutorialResponse = tutorial
         .try_into()
         .map_err(internal_error("Failed to parse stored tutorial data"))?;
+    if redirected {
+        response.canonical_id = Some(response.id.clone());
+    }
+    let sections = repositories::tutorial_sections::list_sections(&pool, &response.id)
+        .await
+        .map_err(internal_error("Failed to fetch tutorial"))?;
+    response.sections = Some(sections::section_responses(sections, query.format));
+    let rendered = (query.format == ContentFormat::Html).then(|| {
+        // `created_at` tells apart a tutorial recreated under a deleted ID
+        let version = format!("{}@{}", response.created_at, response.version);
+        crate::markdown::render_document_cached(
+            "tutorial",
+            &response.id,
+            &version,
+            &response.content,
+        )
+    });
+    response.toc = Some(content_toc(&response.content, &query, rendered.as_deref()));
+    response.content_html = rendered.map(|document| document.html.clone());
+    let editor = can_see_drafts(claims.as_ref());
+    if !editor {
+        mask_authorship(&mut response.created_by, &mut response.updated_by);
+    } else {
+        let views =
+            repositories::views::view_counts(&pool, "tutorial", std::slice::from_ref(&response.id))
+                .await
+                .map_err(internal_error("Failed to fetch tutorial"))?;
+        response.view_count = Some(views.get(&response.id).copied().unwrap_or(0));
+    }
 
-    Ok(Json(response))
+    // The view count editors see changes without `updated_at`
+    let last_modified = (!editor).then_some(response.updated_at.as_str());
+    conditional_json(&headers, &response, last_modified)
 }
 
 /// Handler to create a new tutorial.
-/// Admin-only. Protected by RBAC (claims check).
+/// Admins and editors. Protected by RBAC (claims check).
 /// Performs comprehensive validation of ID, titles, content, icons, colors, and topics.
 pub async fn create_tutorial(
     claims: auth::Claims,
     State(pool): State<DbPool>,
     Json(payload): Json<CreateTutorialRequest>,
-) -> Result<Json<TutorialResponse>, ApiError> {
-    // RBAC: Verify admin privileges
-    ensure_admin(&claims)?;
+) -> Result<Json<TutorialResponse>, AppError> {
+    // RBAC: Verify the role may edit tutorials
+    require_permission(&claims, Permission::EditTutorials)?;
 
     // Sanitize basic text fields
     let title = payload.title.trim().to_string();
@@ -113,23 +370,26 @@ pub async fn create_tutorial(
 
     // Perform deep validation of tutorial metadata
     validate_tutorial_data(&title, &description, &content).map_err(bad_request)?;
-    validate_icon(&payload.icon).map_err(bad_request)?;
+    validate_icon(&pool, &payload.icon).await?;
     validate_color(&payload.color).map_err(bad_request)?;
+    let status = payload
+        .status
+        .as_deref()
+        .map(str::trim)
+        .unwrap_or(TUTORIAL_STATUS_PUBLISHED);
+    validate_tutorial_status(status).map_err(bad_request)?;
 
     // Determine ID: either custom (validated/checked for collisions) or auto-generated UUID
     let id = if let Some(custom_id) = &payload.id {
         let trimmed = custom_id.trim();
         validate_tutorial_id(trimmed).map_err(bad_request)?;
-        // Collision detection for custom IDs
-        let exists = repositories::tutorials::check_tutorial_exists(&pool, trimmed)
+        // Collision detection for custom IDs, including tutorials in the trash
+        let exists = repositories::tutorials::check_tutorial_id_taken(&pool, trimmed)
             .await
             .map_err(internal_error("Failed to create tutorial"))?;
 
         if exists {
-            return Err(api_error(
-                StatusCode::CONFLICT,
-                "Tutorial ID already exists",
-            ));
+            return Err(conflict("Tutorial ID already exists"));
         }
         trimmed.to_string()
     } else {
@@ -153,10 +413,35 @@ pub async fn create_tutorial(
         &payload.color,
         &topics_json,
         &sanitized_topics,
+        status,
+        payload.allow_comments,
+        Some(&claims.sub),
     )
     .await
     .map_err(internal_error("Failed to create tutorial"))?;
 
+    // Drafts are announced once they are published
+    if tutorial.is_published() {
+        changelog::record_event(
+            &pool,
+            "tutorial",
+            &tutorial.id,
+            &tutorial.id,
+            &tutorial.title,
+            ContentEventKind::Published,
+        )
+        .await;
+    }
+    repositories::audit::append_entry(
+        &pool,
+        &claims.sub,
+        "create",
+        "tutorial",
+        Some(&tutorial.id),
+        serde_json::json!({ "title": tutorial.title, "status": tutorial.status }),
+    )
+    .await;
+
     // Final mapping to response model
     let response: TutorialResponse = tutorial
         .try_into()
@@ -165,18 +450,173 @@ pub async fn create_tutorial(
     Ok(Json(response))
 }
 
+/// Handler to copy a tutorial as the starting point for a new one.
+/// Admin-only. The copy keeps icon, color, topics, content and sections, gets
+/// "Copy of " in front of its title and starts over as a draft at version 1.
+pub async fn duplicate_tutorial(
+    claims: auth::Claims,
+    State(pool): State<DbPool>,
+    Path(id): Path<String>,
+    payload: Option<Json<DuplicateTutorialRequest>>,
+) -> Result<Json<TutorialResponse>, AppError> {
+    ensure_admin(&claims)?;
+    validate_tutorial_id(&id).map_err(bad_request)?;
+    let Json(payload) = payload.unwrap_or_default();
+
+    let source = repositories::tutorials::get_tutorial(&pool, &id)
+        .await
+        .map_err(internal_error("Failed to fetch tutorial"))?
+        .ok_or_else(|| not_found("Tutorial not found"))?;
+
+    let copy_id = match payload.id.as_deref().map(str::trim) {
+        Some(custom_id) => {
+            validate_tutorial_id(custom_id).map_err(bad_request)?;
+            let exists = repositories::tutorials::check_tutorial_id_taken(&pool, custom_id)
+                .await
+                .map_err(internal_error("Failed to duplicate tutorial"))?;
+            if exists {
+                return Err(conflict("Tutorial ID already exists"));
+            }
+            custom_id.to_string()
+        }
+        None => Uuid::new_v4().to_string(),
+    };
+
+    let title = format!("Copy of {}", source.title.trim());
+    validate_tutorial_data(&title, &source.description, &source.content).map_err(bad_request)?;
+    let topics = serde_json::from_str::<Vec<String>>(&source.topics)
+        .map_err(internal_error("Failed to read stored tutorial topics"))?;
+
+    // The copy and its sections are written together or not at all
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(internal_error("Failed to duplicate tutorial"))?;
+    repositories::tutorials::create_tutorial_tx(
+        &mut tx,
+        &copy_id,
+        &title,
+        &source.description,
+        &source.content,
+        &source.icon,
+        &source.color,
+        &source.topics,
+        &topics,
+        TUTORIAL_STATUS_DRAFT,
+        source.allow_comments,
+        Some(&claims.sub),
+    )
+    .await
+    .map_err(internal_error("Failed to duplicate tutorial"))?;
+    let sections = repositories::tutorial_sections::list_sections_tx(&mut tx, &source.id)
+        .await
+        .map_err(internal_error("Failed to duplicate tutorial"))?;
+    for section in &sections {
+        repositories::tutorial_sections::create_section_tx(
+            &mut tx,
+            &claims.sub,
+            &copy_id,
+            &section.section_id,
+            &section.title,
+            &section.content,
+        )
+        .await
+        .map_err(internal_error("Failed to duplicate tutorial"))?;
+    }
+    tx.commit()
+        .await
+        .map_err(internal_error("Failed to duplicate tutorial"))?;
+    crate::sitemap::invalidate();
+
+    // Re-read so `updated_at` reflects the section inserts
+    let tutorial = repositories::tutorials::get_tutorial(&pool, &copy_id)
+        .await
+        .map_err(internal_error("Failed to fetch tutorial"))?
+        .ok_or_else(|| not_found("Tutorial not found"))?;
+
+    repositories::audit::append_entry(
+        &pool,
+        &claims.sub,
+        "duplicate",
+        "tutorial",
+        Some(&tutorial.id),
+        serde_json::json!({ "source": source.id, "title": tutorial.title }),
+    )
+    .await;
+    let response: TutorialResponse = tutorial
+        .try_into()
+        .map_err(internal_error("Failed to parse stored tutorial data"))?;
+
+    Ok(Json(response))
+}
+
+/// Handler to change a tutorial's ID, which is also its URL slug.
+/// Admins and editors. Comments, topics and revisions move along, and the old
+/// ID keeps resolving through a redirect.
+pub async fn rename_tutorial(
+    claims: auth::Claims,
+    State(pool): State<DbPool>,
+    Path(id): Path<String>,
+    Json(payload): Json<RenameTutorialRequest>,
+) -> Result<Json<TutorialResponse>, AppError> {
+    require_permission(&claims, Permission::EditTutorials)?;
+    validate_tutorial_id(&id).map_err(bad_request)?;
+    let new_id = payload.new_id.trim();
+    validate_tutorial_id(new_id).map_err(bad_request)?;
+    if new_id == id {
+        return Err(bad_request("Tutorial already has this ID"));
+    }
+
+    let taken = repositories::tutorials::check_tutorial_id_taken(&pool, new_id)
+        .await
+        .map_err(internal_error("Failed to rename tutorial"))?;
+    if taken {
+        return Err(conflict("Tutorial ID already exists"));
+    }
+
+    let renamed = repositories::tutorials::rename_tutorial(&pool, &id, new_id)
+        .await
+        .map_err(internal_error("Failed to rename tutorial"))?;
+    if !renamed {
+        return Err(not_found("Tutorial not found"));
+    }
+    let tutorial = repositories::tutorials::get_tutorial(&pool, new_id)
+        .await
+        .map_err(internal_error("Failed to fetch tutorial"))?
+        .ok_or_else(|| not_found("Tutorial not found"))?;
+
+    tracing::info!("Renamed tutorial {} to {}", id, new_id);
+    repositories::audit::append_entry(
+        &pool,
+        &claims.sub,
+        "rename",
+        "tutorial",
+        Some(new_id),
+        serde_json::json!({ "from": id }),
+    )
+    .await;
+    let response: TutorialResponse = tutorial
+        .try_into()
+        .map_err(internal_error("Failed to parse stored tutorial data"))?;
+
+    Ok(Json(response))
+}
+
 /// Handler to update an existing tutorial.
-/// Admin-only. Implements optimistic concurrency control using a version number.
+/// Admins and editors. Implements optimistic concurrency control using a version number:
+/// the client names the version its copy was read at through `expected_version` or
+/// `If-Match`, and a stale copy gets `409 Conflict`.
 pub async fn update_tutorial(
     claims: auth::Claims,
     State(pool): State<DbPool>,
     Path(id): Path<String>,
-    Json(payload): Json<UpdateTutorialRequest>,
-) -> Result<Json<TutorialResponse>, ApiError> {
+    headers: HeaderMap,
+    Json(mut payload): Json<UpdateTutorialRequest>,
+) -> Result<Json<TutorialResponse>, AppError> {
     tracing::info!("Updating tutorial with id: {}", id);
 
-    // RBAC: Verify admin role
-    if claims.role != "admin" {
+    // RBAC: Verify the role may edit tutorials
+    if !claims.can(Permission::EditTutorials) {
         tracing::warn!(
             "Unauthorized update attempt for tutorial {} by user {}",
             id,
@@ -191,12 +631,134 @@ pub async fn update_tutorial(
         bad_request(e)
     })?;
 
+    // The body's expected_version wins over an If-Match header
+    if payload.expected_version.is_none() {
+        payload.expected_version = if_match_version(&headers)?;
+    }
+
     // Step 1: Pre-fetch current state to check existence and current version
     let tutorial = repositories::tutorials::get_tutorial(&pool, &id)
         .await
         .map_err(internal_error("Failed to fetch tutorial"))?
         .ok_or_else(|| not_found("Tutorial not found"))?;
 
+    let updated_tutorial = apply_tutorial_update(&pool, &claims.sub, tutorial, payload).await?;
+
+    // Success mapping
+    tracing::info!("Successfully updated tutorial {}", id);
+    repositories::audit::append_entry(
+        &pool,
+        &claims.sub,
+        "update",
+        "tutorial",
+        Some(&id),
+        serde_json::json!({ "title": updated_tutorial.title, "version": updated_tutorial.version }),
+    )
+    .await;
+    let response: TutorialResponse = updated_tutorial
+        .try_into()
+        .map_err(internal_error("Failed to parse stored tutorial data"))?;
+
+    Ok(Json(response))
+}
+
+/// Handler to apply an RFC 6902 JSON Patch to a tutorial.
+/// Admins and editors. The patch runs against the stored version and the result goes
+/// through the same validation and version check as a full update, including an
+/// `If-Match` version.
+pub async fn patch_tutorial(
+    claims: auth::Claims,
+    State(pool): State<DbPool>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<TutorialResponse>, AppError> {
+    require_permission(&claims, Permission::EditTutorials)?;
+    validate_tutorial_id(&id).map_err(bad_request)?;
+    let operations = patch::parse_patch(&body)?;
+    let expected_version = if_match_version(&headers)?;
+
+    let tutorial = repositories::tutorials::get_tutorial(&pool, &id)
+        .await
+        .map_err(internal_error("Failed to fetch tutorial"))?
+        .ok_or_else(|| not_found("Tutorial not found"))?;
+
+    let document: TutorialPatchDocument = TutorialResponse::try_from(tutorial.clone())
+        .map_err(internal_error("Failed to parse stored tutorial data"))?
+        .into();
+    let patched: TutorialPatchDocument = patch::apply_patch(&document, &operations, &["version"])?;
+
+    let update = UpdateTutorialRequest {
+        expected_version,
+        ..patched.into()
+    };
+    let updated_tutorial = apply_tutorial_update(&pool, &claims.sub, tutorial, update).await?;
+
+    tracing::info!(
+        "Patched tutorial {} with {} operation(s)",
+        id,
+        operations.0.len()
+    );
+    repositories::audit::append_entry(
+        &pool,
+        &claims.sub,
+        "patch",
+        "tutorial",
+        Some(&id),
+        serde_json::json!({
+            "operations": operations.0.len(),
+            "version": updated_tutorial.version,
+        }),
+    )
+    .await;
+    let response: TutorialResponse = updated_tutorial
+        .try_into()
+        .map_err(internal_error("Failed to parse stored tutorial data"))?;
+
+    Ok(Json(response))
+}
+
+/// Reads the version a client's copy was read at from `If-Match`. Accepts
+/// the bare number as well as a (weak) entity tag wrapping it; `*` and a
+/// missing header impose no expectation.
+fn if_match_version(headers: &HeaderMap) -> Result<Option<i64>, AppError> {
+    let Some(value) = headers.get(header::IF_MATCH) else {
+        return Ok(None);
+    };
+    let invalid = || bad_request("If-Match must hold the tutorial version");
+    let value = value.to_str().map_err(|_| invalid())?.trim();
+    if value == "*" {
+        return Ok(None);
+    }
+    let tag = value.strip_prefix("W/").unwrap_or(value);
+    let tag = tag
+        .strip_prefix('"')
+        .and_then(|tag| tag.strip_suffix('"'))
+        .unwrap_or(tag);
+    tag.parse().map(Some).map_err(|_| invalid())
+}
+
+fn version_conflict() -> AppError {
+    conflict("Tutorial was modified by another request. Please refresh and try again.")
+}
+
+/// Merges `payload` into `tutorial`, validates the result and persists it
+/// fenced on `payload.expected_version`, or on the version `tutorial` was
+/// read at when the client sent none. The replaced version is kept as a
+/// revision attributed to `editor`.
+async fn apply_tutorial_update(
+    pool: &DbPool,
+    editor: &str,
+    tutorial: Tutorial,
+    payload: UpdateTutorialRequest,
+) -> Result<Tutorial, AppError> {
+    // Step 1b: A client that edited an older copy would overwrite changes it
+    // never saw
+    let expected_version = payload.expected_version.unwrap_or(tutorial.version);
+    if expected_version != tutorial.version {
+        return Err(version_conflict());
+    }
+
     // Step 2: Merge partial updates with existing data
     // Title update
     let title = match payload.title {
@@ -222,6 +784,7 @@ pub async fn update_tutorial(
         None => tutorial.description.trim().to_string(),
     };
 
+    let was_published = tutorial.is_published();
     let icon = payload.icon.unwrap_or(tutorial.icon);
     let color = payload.color.unwrap_or(tutorial.color);
 
@@ -246,13 +809,21 @@ pub async fn update_tutorial(
 
     // Step 3: Deep validation of merged tutorial state
     validate_tutorial_data(&title, &description, &content).map_err(|e| {
-        tracing::warn!("Validation failed for tutorial {}: {}", id, e);
+        tracing::warn!("Validation failed for tutorial {}: {}", tutorial.id, e);
         bad_request(e)
     })?;
 
-    validate_icon(&icon).map_err(bad_request)?;
+    validate_icon(pool, &icon).await?;
     validate_color(&color).map_err(bad_request)?;
 
+    // Step 4: Status, carried over unless the payload moves it
+    let status = match payload.status {
+        Some(value) => value.trim().to_string(),
+        None => tutorial.status,
+    };
+    validate_tutorial_status(&status).map_err(bad_request)?;
+    let allow_comments = payload.allow_comments.unwrap_or(tutorial.allow_comments);
+
     // Step 5: Handle topics serialization
     let (topics_json, topics_vec) = if let Some(t) = payload.topics {
         // Sanitize new topics if provided
@@ -272,8 +843,9 @@ pub async fn update_tutorial(
     // Step 6: Atomic Update operation in repository
     // The repository handles the version increment and the WHERE version = current_version check
     let updated_tutorial = repositories::tutorials::update_tutorial(
-        &pool,
-        &id,
+        pool,
+        editor,
+        &tutorial.id,
         &title,
         &description,
         &content,
@@ -281,49 +853,219 @@ pub async fn update_tutorial(
         &color,
         &topics_json,
         &topics_vec,
-        tutorial.version as i32, // The repository checks WHERE version = current_version
+        &status,
+        allow_comments,
+        expected_version as i32, // The repository checks WHERE version = current_version
     )
     .await
     .map_err(internal_error("Failed to update tutorial"))?
-    .ok_or_else(|| {
-        // If query returns None, it likely means the version ID mismatch (concurrency conflict)
-        api_error(
-            StatusCode::CONFLICT,
-            "Tutorial was modified by another request. Please refresh and try again.",
+    // If query returns None, it likely means the version ID mismatch (concurrency conflict)
+    .ok_or_else(version_conflict)?;
+
+    if let Some(kind) = repositories::events::classify_change(
+        was_published,
+        updated_tutorial.is_published(),
+        tutorial.content.len(),
+        content.len(),
+    ) {
+        changelog::record_event(
+            pool,
+            "tutorial",
+            &updated_tutorial.id,
+            &updated_tutorial.id,
+            &updated_tutorial.title,
+            kind,
         )
-    })?;
+        .await;
+    }
 
-    // Success mapping
-    tracing::info!("Successfully updated tutorial {}", id);
-    let response: TutorialResponse = updated_tutorial
+    Ok(updated_tutorial)
+}
+
+/// Handler to publish a draft tutorial and stamp its `published_at`.
+/// Admins and editors. Publishing an already published tutorial changes
+/// nothing and returns it as is.
+pub async fn publish_tutorial(
+    claims: auth::Claims,
+    State(pool): State<DbPool>,
+    Path(id): Path<String>,
+) -> Result<Json<TutorialResponse>, AppError> {
+    require_permission(&claims, Permission::EditTutorials)?;
+    validate_tutorial_id(&id).map_err(bad_request)?;
+
+    let published = repositories::tutorials::publish_tutorial(&pool, &id)
+        .await
+        .map_err(internal_error("Failed to publish tutorial"))?;
+    let tutorial = repositories::tutorials::get_tutorial(&pool, &id)
+        .await
+        .map_err(internal_error("Failed to fetch tutorial"))?
+        .ok_or_else(|| not_found("Tutorial not found"))?;
+
+    if published {
+        changelog::record_event(
+            &pool,
+            "tutorial",
+            &tutorial.id,
+            &tutorial.id,
+            &tutorial.title,
+            ContentEventKind::Published,
+        )
+        .await;
+        repositories::audit::append_entry(
+            &pool,
+            &claims.sub,
+            "publish",
+            "tutorial",
+            Some(&tutorial.id),
+            serde_json::json!({ "title": tutorial.title }),
+        )
+        .await;
+    }
+
+    let response: TutorialResponse = tutorial
         .try_into()
         .map_err(internal_error("Failed to parse stored tutorial data"))?;
 
     Ok(Json(response))
 }
 
-/// Handler to permanently delete a tutorial.
-/// Admin-only.
+/// Handler to set the order tutorials are listed in.
+/// Admins and editors. Takes tutorial IDs in their new order; tutorials left
+/// out keep their relative order after the listed ones. Returns the full
+/// resulting ordering.
+pub async fn reorder_tutorials(
+    claims: auth::Claims,
+    State(pool): State<DbPool>,
+    Json(ids): Json<Vec<String>>,
+) -> Result<Json<Vec<TutorialOrderEntry>>, AppError> {
+    require_permission(&claims, Permission::EditTutorials)?;
+
+    if ids.is_empty() {
+        return Err(bad_request("Ordering must list at least one tutorial"));
+    }
+    let mut seen = HashSet::with_capacity(ids.len());
+    for id in &ids {
+        validate_tutorial_id(id).map_err(bad_request)?;
+        if !seen.insert(id.as_str()) {
+            return Err(bad_request(format!(
+                "Tutorial {id} is listed more than once"
+            )));
+        }
+    }
+
+    let ordering = repositories::tutorials::reorder_tutorials(&pool, &ids)
+        .await
+        .map_err(internal_error("Failed to reorder tutorials"))?
+        .ok_or_else(|| bad_request("Ordering lists an unknown or deleted tutorial"))?;
+
+    repositories::audit::append_entry(
+        &pool,
+        &claims.sub,
+        "reorder",
+        "tutorial",
+        None,
+        serde_json::json!({ "ids": ids }),
+    )
+    .await;
+
+    Ok(Json(ordering))
+}
+
+/// Handler to delete a tutorial.
+/// Admin-only; editors cannot delete. By default the tutorial moves to the
+/// trash and disappears from every public listing; `?permanent=true`
+/// removes it (trashed or not) together with its comments, which requires
+/// sudo mode.
 pub async fn delete_tutorial(
     claims: auth::Claims,
     State(pool): State<DbPool>,
     Path(id): Path<String>,
-) -> Result<StatusCode, ApiError> {
-    // RBAC: Verify admin role
-    ensure_admin(&claims)?;
+    Query(params): Query<DeleteTutorialQuery>,
+) -> Result<StatusCode, AppError> {
+    // RBAC: Verify the role may delete content
+    require_permission(&claims, Permission::DeleteContent)?;
 
     // Validate ID before database interaction
     validate_tutorial_id(&id).map_err(bad_request)?;
 
-    // Attempt deletion in repository
-    let deleted = repositories::tutorials::delete_tutorial(&pool, &id)
-        .await
-        .map_err(internal_error("Failed to delete tutorial"))?;
+    let deleted = if params.permanent {
+        claims.require_sudo()?;
+        repositories::tutorials::delete_tutorial(&pool, &id, &claims.sub)
+            .await
+            .map_err(internal_error("Failed to delete tutorial"))?
+    } else {
+        repositories::tutorials::trash_tutorial(&pool, &id)
+            .await
+            .map_err(internal_error("Failed to delete tutorial"))?
+    };
 
     // Handle 404
     if !deleted {
         return Err(not_found("Tutorial not found"));
     }
 
+    repositories::audit::append_entry(
+        &pool,
+        &claims.sub,
+        if params.permanent { "delete" } else { "trash" },
+        "tutorial",
+        Some(&id),
+        serde_json::json!({}),
+    )
+    .await;
+
     Ok(StatusCode::NO_CONTENT)
 }
+
+/// Handler to take a tutorial out of the trash.
+/// Admin-only, like deleting.
+pub async fn restore_tutorial(
+    claims: auth::Claims,
+    State(pool): State<DbPool>,
+    Path(id): Path<String>,
+) -> Result<Json<TutorialResponse>, AppError> {
+    require_permission(&claims, Permission::DeleteContent)?;
+    validate_tutorial_id(&id).map_err(bad_request)?;
+
+    let restored = repositories::tutorials::restore_tutorial(&pool, &id)
+        .await
+        .map_err(internal_error("Failed to restore tutorial"))?;
+    if !restored {
+        return Err(not_found("Tutorial is not in the trash"));
+    }
+    let tutorial = repositories::tutorials::get_tutorial(&pool, &id)
+        .await
+        .map_err(internal_error("Failed to fetch tutorial"))?
+        .ok_or_else(|| not_found("Tutorial not found"))?;
+
+    repositories::audit::append_entry(
+        &pool,
+        &claims.sub,
+        "restore",
+        "tutorial",
+        Some(&id),
+        serde_json::json!({ "title": tutorial.title }),
+    )
+    .await;
+
+    let response: TutorialResponse = tutorial
+        .try_into()
+        .map_err(internal_error("Failed to parse stored tutorial data"))?;
+
+    Ok(Json(response))
+}
+
+/// Handler listing the tutorials in the trash.
+/// Admin-only.
+pub async fn list_trashed_tutorials(
+    claims: auth::Claims,
+    State(pool): State<DbPool>,
+) -> Result<Json<Vec<TrashedTutorial>>, AppError> {
+    require_permission(&claims, Permission::DeleteContent)?;
+
+    let tutorials = repositories::tutorials::list_trashed_tutorials(&pool)
+        .await
+        .map_err(internal_error("Failed to fetch trashed tutorials"))?;
+
+    Ok(Json(tutorials))
+}
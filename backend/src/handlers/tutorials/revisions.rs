@@ -0,0 +1,99 @@
+//! Revision history of a tutorial: every update keeps the version it
+//! replaced, and any kept version can be restored as a new one.
+
+use super::*;
+
+/// Handler listing the stored revisions of a tutorial, newest first.
+/// Admins and editors.
+pub async fn list_revisions(
+    claims: auth::Claims,
+    State(pool): State<DbPool>,
+    Path(id): Path<String>,
+) -> Result<Json<Vec<TutorialRevisionSummary>>, AppError> {
+    require_permission(&claims, Permission::EditTutorials)?;
+    validate_tutorial_id(&id).map_err(bad_request)?;
+    ensure_tutorial_exists(&pool, &id).await?;
+
+    let revisions = repositories::tutorials::list_revisions(&pool, &id)
+        .await
+        .map_err(internal_error("Failed to fetch revisions"))?;
+
+    Ok(Json(revisions))
+}
+
+/// Handler returning one stored revision in full.
+/// Admins and editors.
+pub async fn get_revision(
+    claims: auth::Claims,
+    State(pool): State<DbPool>,
+    Path((id, version)): Path<(String, i64)>,
+) -> Result<Json<TutorialRevisionResponse>, AppError> {
+    require_permission(&claims, Permission::EditTutorials)?;
+    validate_tutorial_id(&id).map_err(bad_request)?;
+    ensure_tutorial_exists(&pool, &id).await?;
+
+    let revision = repositories::tutorials::get_revision(&pool, &id, version)
+        .await
+        .map_err(internal_error("Failed to fetch revision"))?
+        .ok_or_else(|| not_found("Revision not found"))?;
+
+    Ok(Json(revision.into()))
+}
+
+/// Handler saving a stored revision's title, description, content and
+/// topics as the tutorial's next version. Goes through the same validation
+/// and version check as any other update, so the current version becomes a
+/// revision itself. Admins and editors.
+pub async fn restore_revision(
+    claims: auth::Claims,
+    State(pool): State<DbPool>,
+    Path((id, version)): Path<(String, i64)>,
+) -> Result<Json<TutorialResponse>, AppError> {
+    require_permission(&claims, Permission::EditTutorials)?;
+    validate_tutorial_id(&id).map_err(bad_request)?;
+
+    let tutorial = repositories::tutorials::get_tutorial(&pool, &id)
+        .await
+        .map_err(internal_error("Failed to fetch tutorial"))?
+        .ok_or_else(|| not_found("Tutorial not found"))?;
+    let revision: TutorialRevisionResponse =
+        repositories::tutorials::get_revision(&pool, &id, version)
+            .await
+            .map_err(internal_error("Failed to fetch revision"))?
+            .ok_or_else(|| not_found("Revision not found"))?
+            .into();
+
+    let payload = UpdateTutorialRequest {
+        title: Some(revision.title),
+        description: Some(revision.description),
+        icon: None,
+        color: None,
+        topics: Some(revision.topics),
+        content: Some(revision.content),
+        status: None,
+        allow_comments: None,
+        expected_version: None,
+    };
+    let updated_tutorial = apply_tutorial_update(&pool, &claims.sub, tutorial, payload).await?;
+
+    tracing::info!(
+        "Restored tutorial {} to revision {} as version {}",
+        id,
+        version,
+        updated_tutorial.version
+    );
+    repositories::audit::append_entry(
+        &pool,
+        &claims.sub,
+        "restore_revision",
+        "tutorial",
+        Some(&id),
+        serde_json::json!({ "revision": version, "version": updated_tutorial.version }),
+    )
+    .await;
+    let response: TutorialResponse = updated_tutorial
+        .try_into()
+        .map_err(internal_error("Failed to parse stored tutorial data"))?;
+
+    Ok(Json(response))
+}
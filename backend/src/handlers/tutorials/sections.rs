@@ -0,0 +1,339 @@
+//! Sections (chapters) of a tutorial. A tutorial may be split into ordered
+//! sections that are edited one at a time and deep-linked by their slug;
+//! tutorials without sections keep serving their single `content` document.
+
+use super::*;
+
+/// Maximum length of a section title.
+const MAX_SECTION_TITLE_LEN: usize = 200;
+/// Maximum length of a section's markdown content (100KB).
+const MAX_SECTION_CONTENT_LEN: usize = 100_000;
+/// Maximum length of a section slug.
+const MAX_SECTION_ID_LEN: usize = 100;
+
+/// Validates a section slug: lowercase ASCII letters, digits and single
+/// hyphens, so it can be used as a URL fragment unchanged. `order` is taken
+/// by the reorder route.
+pub(crate) fn validate_section_id(id: &str) -> Result<(), String> {
+    if id.is_empty() || id.len() > MAX_SECTION_ID_LEN {
+        return Err(format!(
+            "Invalid section ID (must be 1-{MAX_SECTION_ID_LEN} characters)"
+        ));
+    }
+    if !id
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        || id.starts_with('-')
+        || id.ends_with('-')
+        || id.contains("--")
+    {
+        return Err(
+            "Section ID may only contain lowercase letters, digits and single hyphens".to_string(),
+        );
+    }
+    if id == "order" {
+        return Err("Section ID 'order' is reserved".to_string());
+    }
+    Ok(())
+}
+
+/// Derives a section slug from its title ("Install & Configure" becomes
+/// `install-configure`). Empty when the title has no ASCII letters or digits.
+pub(crate) fn section_slug(title: &str) -> String {
+    let mut slug = String::with_capacity(title.len());
+    for c in title.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let mut slug = slug.trim_end_matches('-').to_string();
+    if slug.len() > MAX_SECTION_ID_LEN {
+        slug.truncate(MAX_SECTION_ID_LEN);
+        slug = slug.trim_end_matches('-').to_string();
+    }
+    slug
+}
+
+fn validate_section_data(title: &str, content: &str) -> Result<(), String> {
+    if title.is_empty() {
+        return Err("Section title cannot be empty".to_string());
+    }
+    if title.len() > MAX_SECTION_TITLE_LEN {
+        return Err(format!(
+            "Section title too long (max {MAX_SECTION_TITLE_LEN} characters)"
+        ));
+    }
+    if content.is_empty() {
+        return Err("Section content cannot be empty".to_string());
+    }
+    if content.len() > MAX_SECTION_CONTENT_LEN {
+        return Err("Section content too long (max 100,000 characters)".to_string());
+    }
+    Ok(())
+}
+
+/// Maps stored sections to responses, rendering them with `?format=html`.
+pub(super) fn section_responses(
+    sections: Vec<TutorialSection>,
+    format: ContentFormat,
+) -> Vec<TutorialSectionResponse> {
+    sections
+        .into_iter()
+        .map(|section| {
+            let html = (format == ContentFormat::Html).then(|| {
+                // Section edits don't bump the tutorial version
+                let key = format!("{}/{}", section.tutorial_id, section.section_id);
+                let digest = crate::security::sha256_hex(section.content.as_bytes());
+                crate::markdown::render_document_cached(
+                    "tutorial_section",
+                    &key,
+                    &digest,
+                    &section.content,
+                )
+                .html
+                .clone()
+            });
+            TutorialSectionResponse {
+                content_html: html,
+                ..section.into()
+            }
+        })
+        .collect()
+}
+
+/// Handler listing a tutorial's sections in reading order.
+/// Publicly accessible; a draft's sections are hidden like the draft.
+pub async fn list_sections(
+    State(pool): State<DbPool>,
+    auth::OptionalClaims(claims): auth::OptionalClaims,
+    Path(id): Path<String>,
+    Query(query): Query<ContentFormatQuery>,
+) -> Result<Json<Vec<TutorialSectionResponse>>, AppError> {
+    validate_tutorial_id(&id).map_err(bad_request)?;
+    repositories::tutorials::get_tutorial(&pool, &id)
+        .await
+        .map_err(internal_error("Failed to fetch tutorial"))?
+        .filter(|tutorial| tutorial.is_published() || can_see_drafts(claims.as_ref()))
+        .ok_or_else(|| not_found("Tutorial not found"))?;
+
+    let sections = repositories::tutorial_sections::list_sections(&pool, &id)
+        .await
+        .map_err(internal_error("Failed to fetch sections"))?;
+
+    Ok(Json(section_responses(sections, query.format)))
+}
+
+/// Handler appending a section to a tutorial.
+/// Admins and editors. Without an explicit `id` the slug is derived from
+/// the title; either way an ID already used in the tutorial is a conflict.
+pub async fn create_section(
+    claims: auth::Claims,
+    State(pool): State<DbPool>,
+    Path(id): Path<String>,
+    Json(payload): Json<CreateTutorialSectionRequest>,
+) -> Result<(StatusCode, Json<TutorialSectionResponse>), AppError> {
+    require_permission(&claims, Permission::EditTutorials)?;
+    validate_tutorial_id(&id).map_err(bad_request)?;
+
+    let title = payload.title.trim();
+    let content = payload.content.trim();
+    validate_section_data(title, content).map_err(bad_request)?;
+    let section_id = match payload.id.as_deref().map(str::trim) {
+        Some(section_id) => section_id.to_string(),
+        None => section_slug(title),
+    };
+    validate_section_id(&section_id).map_err(bad_request)?;
+    ensure_tutorial_exists(&pool, &id).await?;
+
+    let section = repositories::tutorial_sections::create_section(
+        &pool,
+        &claims.sub,
+        &id,
+        &section_id,
+        title,
+        content,
+    )
+    .await
+    .map_err(internal_error("Failed to create section"))?
+    .ok_or_else(|| conflict(format!("Tutorial already has a section '{section_id}'")))?;
+
+    repositories::audit::append_entry(
+        &pool,
+        &claims.sub,
+        "create_section",
+        "tutorial",
+        Some(&id),
+        serde_json::json!({ "section": section_id, "title": title }),
+    )
+    .await;
+
+    Ok((StatusCode::CREATED, Json(section.into())))
+}
+
+/// Handler to change a section's title and/or content.
+/// Admins and editors. The section keeps its ID so existing links stay valid.
+pub async fn update_section(
+    claims: auth::Claims,
+    State(pool): State<DbPool>,
+    Path((id, section_id)): Path<(String, String)>,
+    Json(payload): Json<UpdateTutorialSectionRequest>,
+) -> Result<Json<TutorialSectionResponse>, AppError> {
+    require_permission(&claims, Permission::EditTutorials)?;
+    validate_tutorial_id(&id).map_err(bad_request)?;
+    validate_section_id(&section_id).map_err(bad_request)?;
+    ensure_tutorial_exists(&pool, &id).await?;
+
+    let current = repositories::tutorial_sections::list_sections(&pool, &id)
+        .await
+        .map_err(internal_error("Failed to fetch sections"))?
+        .into_iter()
+        .find(|section| section.section_id == section_id)
+        .ok_or_else(|| not_found("Section not found"))?;
+    let title = payload
+        .title
+        .as_deref()
+        .map(str::trim)
+        .unwrap_or(&current.title);
+    let content = payload
+        .content
+        .as_deref()
+        .map(str::trim)
+        .unwrap_or(&current.content);
+    validate_section_data(title, content).map_err(bad_request)?;
+
+    let section = repositories::tutorial_sections::update_section(
+        &pool,
+        &claims.sub,
+        &id,
+        &section_id,
+        title,
+        content,
+    )
+    .await
+    .map_err(internal_error("Failed to update section"))?
+    .ok_or_else(|| not_found("Section not found"))?;
+
+    repositories::audit::append_entry(
+        &pool,
+        &claims.sub,
+        "update_section",
+        "tutorial",
+        Some(&id),
+        serde_json::json!({ "section": section_id }),
+    )
+    .await;
+
+    Ok(Json(section.into()))
+}
+
+/// Handler removing a section from a tutorial.
+/// Admins and editors.
+pub async fn delete_section(
+    claims: auth::Claims,
+    State(pool): State<DbPool>,
+    Path((id, section_id)): Path<(String, String)>,
+) -> Result<StatusCode, AppError> {
+    require_permission(&claims, Permission::EditTutorials)?;
+    validate_tutorial_id(&id).map_err(bad_request)?;
+    validate_section_id(&section_id).map_err(bad_request)?;
+    ensure_tutorial_exists(&pool, &id).await?;
+
+    let deleted =
+        repositories::tutorial_sections::delete_section(&pool, &claims.sub, &id, &section_id)
+            .await
+            .map_err(internal_error("Failed to delete section"))?;
+    if !deleted {
+        return Err(not_found("Section not found"));
+    }
+
+    repositories::audit::append_entry(
+        &pool,
+        &claims.sub,
+        "delete_section",
+        "tutorial",
+        Some(&id),
+        serde_json::json!({ "section": section_id }),
+    )
+    .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Handler to set the order of a tutorial's sections.
+/// Admins and editors. Takes section IDs in their new order; sections left
+/// out keep their relative order after the listed ones. Returns all sections
+/// in the resulting order.
+pub async fn reorder_sections(
+    claims: auth::Claims,
+    State(pool): State<DbPool>,
+    Path(id): Path<String>,
+    Json(ids): Json<Vec<String>>,
+) -> Result<Json<Vec<TutorialSectionResponse>>, AppError> {
+    require_permission(&claims, Permission::EditTutorials)?;
+    validate_tutorial_id(&id).map_err(bad_request)?;
+
+    if ids.is_empty() {
+        return Err(bad_request("Ordering must list at least one section"));
+    }
+    let mut seen = HashSet::with_capacity(ids.len());
+    for section_id in &ids {
+        validate_section_id(section_id).map_err(bad_request)?;
+        if !seen.insert(section_id.as_str()) {
+            return Err(bad_request(format!(
+                "Section {section_id} is listed more than once"
+            )));
+        }
+    }
+    ensure_tutorial_exists(&pool, &id).await?;
+
+    let sections = repositories::tutorial_sections::reorder_sections(&pool, &claims.sub, &id, &ids)
+        .await
+        .map_err(internal_error("Failed to reorder sections"))?
+        .ok_or_else(|| bad_request("Ordering lists an unknown section"))?;
+
+    repositories::audit::append_entry(
+        &pool,
+        &claims.sub,
+        "reorder_sections",
+        "tutorial",
+        Some(&id),
+        serde_json::json!({ "ids": ids }),
+    )
+    .await;
+
+    Ok(Json(section_responses(sections, ContentFormat::Markdown)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn section_slugs_are_derived_from_titles() {
+        assert_eq!(section_slug("Install & Configure"), "install-configure");
+        assert_eq!(section_slug("  Step 2: SSH keys!  "), "step-2-ssh-keys");
+        assert_eq!(section_slug("Ünïcode only ✓"), "n-code-only");
+        assert_eq!(section_slug("✓✓"), "");
+        assert_eq!(section_slug(&"a ".repeat(80)).len(), 99);
+    }
+
+    #[test]
+    fn section_ids_are_url_fragments() {
+        assert!(validate_section_id("install-configure").is_ok());
+        assert!(validate_section_id("step-2").is_ok());
+        for invalid in [
+            "",
+            "Upper",
+            "two--hyphens",
+            "-lead",
+            "trail-",
+            "a b",
+            "order",
+        ] {
+            assert!(validate_section_id(invalid).is_err(), "{invalid}");
+        }
+        assert!(validate_section_id(&"a".repeat(101)).is_err());
+    }
+}
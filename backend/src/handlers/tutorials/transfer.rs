@@ -0,0 +1,787 @@
+//! Single-tutorial export and import.
+//!
+//! The full `export_content`/`import_content` bundles move an entire site;
+//! these endpoints move exactly one tutorial (optionally with its comments)
+//! between instances as a small, versioned JSON document, or as a standalone
+//! Markdown file with YAML front matter for review and publishing elsewhere.
+//! Imports go through the same validation and repository functions as the
+//! regular create and update handlers, so topics and the FTS index stay in
+//! sync.
+
+use super::*;
+use crate::handlers::common::ensure_admin;
+use crate::markdown::front_matter;
+use axum::http::{header, HeaderValue};
+use axum::response::{IntoResponse, Response};
+use std::collections::HashMap;
+
+/// Upper bound on comments accepted in a single import document.
+const MAX_IMPORTED_COMMENTS: usize = 10_000;
+
+/// Rejection for an import whose tutorial ID belongs to a trashed tutorial.
+const TRASHED_ID_CONFLICT: &str =
+    "A tutorial with this ID is in the trash. Restore or permanently delete it first.";
+
+/// Query parameters for `GET /api/tutorials/{id}/export.json`.
+#[derive(Deserialize)]
+pub struct TutorialExportQuery {
+    /// Include the tutorial's comments in the document.
+    #[serde(default)]
+    include_comments: bool,
+}
+
+/// What to do when the imported tutorial ID already exists.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ImportConflictStrategy {
+    /// Leave the existing tutorial untouched.
+    #[default]
+    Skip,
+    /// Replace the existing tutorial's fields with the imported ones.
+    Overwrite,
+    /// Import under a freshly generated ID.
+    NewId,
+}
+
+/// Query parameters for `POST /api/admin/tutorials/import-one`.
+#[derive(Default, Deserialize)]
+pub struct TutorialImportQuery {
+    #[serde(default)]
+    on_conflict: ImportConflictStrategy,
+}
+
+/// Handler exporting a single tutorial as a downloadable JSON document.
+/// Admin-only.
+pub async fn export_tutorial(
+    claims: auth::Claims,
+    State(pool): State<DbPool>,
+    Path(id): Path<String>,
+    Query(params): Query<TutorialExportQuery>,
+) -> Result<Response, AppError> {
+    ensure_admin(&claims)?;
+    validate_tutorial_id(&id).map_err(bad_request)?;
+
+    let document = build_export_document(&pool, &id, params.include_comments).await?;
+
+    let disposition = attachment_disposition(&id, "json")?;
+
+    Ok(([(header::CONTENT_DISPOSITION, disposition)], Json(document)).into_response())
+}
+
+/// Handler exporting a single tutorial as a Markdown file whose front
+/// matter carries the metadata. Admin-only.
+pub async fn export_tutorial_markdown(
+    claims: auth::Claims,
+    State(pool): State<DbPool>,
+    Path(id): Path<String>,
+) -> Result<Response, AppError> {
+    ensure_admin(&claims)?;
+    validate_tutorial_id(&id).map_err(bad_request)?;
+
+    let tutorial = repositories::tutorials::get_tutorial(&pool, &id)
+        .await
+        .map_err(internal_error("Failed to export tutorial"))?
+        .ok_or_else(|| not_found("Tutorial not found"))?;
+    let topics: Vec<String> = serde_json::from_str(&tutorial.topics)
+        .map_err(internal_error("Failed to read stored tutorial topics"))?;
+
+    let document = front_matter::render(
+        &[
+            ("id", tutorial.id.into()),
+            ("title", tutorial.title.into()),
+            ("description", tutorial.description.into()),
+            ("topics", topics.into()),
+            ("icon", tutorial.icon.into()),
+            ("color", tutorial.color.into()),
+            ("status", tutorial.status.into()),
+            ("allow_comments", tutorial.allow_comments.into()),
+            ("version", tutorial.version.into()),
+            ("created_at", tutorial.created_at.into()),
+            ("updated_at", tutorial.updated_at.into()),
+        ],
+        &tutorial.content,
+    );
+    let disposition = attachment_disposition(&id, "md")?;
+
+    Ok((
+        [
+            (
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("text/markdown; charset=utf-8"),
+            ),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        document,
+    )
+        .into_response())
+}
+
+/// `Content-Disposition` for a downloaded `tutorial-{id}.{extension}`.
+fn attachment_disposition(id: &str, extension: &str) -> Result<HeaderValue, AppError> {
+    let filename: String = id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    HeaderValue::from_str(&format!(
+        "attachment; filename=\"tutorial-{filename}.{extension}\""
+    ))
+    .map_err(internal_error("Failed to export tutorial"))
+}
+
+/// Handler importing a Markdown file produced by
+/// [`export_tutorial_markdown`]. Admin-only.
+///
+/// A file naming an existing tutorial updates it like a regular edit: the
+/// replaced version becomes a revision, and a `version` older than the
+/// stored one is a conflict. Otherwise the tutorial is created, under the
+/// file's ID or a fresh UUID.
+pub async fn import_tutorial_markdown(
+    claims: auth::Claims,
+    State(pool): State<DbPool>,
+    body: String,
+) -> Result<Json<TutorialImportResponse>, AppError> {
+    ensure_admin(&claims)?;
+
+    let (fields, content) = front_matter::parse(&body).map_err(bad_request)?;
+    let front: TutorialFrontMatter = serde_json::from_value(serde_json::Value::Object(fields))
+        .map_err(|err| bad_request(format!("Invalid front matter: {err}")))?;
+
+    let title = front.title.trim().to_string();
+    let description = front.description.trim().to_string();
+    let content = content.trim().to_string();
+    validate_tutorial_data(&title, &description, &content).map_err(bad_request)?;
+    validate_icon(&pool, &front.icon).await?;
+    validate_color(&front.color).map_err(bad_request)?;
+    if let Some(status) = &front.status {
+        validate_tutorial_status(status.trim()).map_err(bad_request)?;
+    }
+    let topics = sanitize_topics(&front.topics).map_err(bad_request)?;
+
+    let id = match front.id.as_deref().map(str::trim) {
+        Some(id) => {
+            validate_tutorial_id(id).map_err(bad_request)?;
+            id.to_string()
+        }
+        None => Uuid::new_v4().to_string(),
+    };
+    let existing = repositories::tutorials::get_tutorial(&pool, &id)
+        .await
+        .map_err(internal_error("Failed to import tutorial"))?;
+
+    let (outcome, tutorial) = match existing {
+        Some(existing) => {
+            let payload = UpdateTutorialRequest {
+                title: Some(title),
+                description: Some(description),
+                icon: Some(front.icon),
+                color: Some(front.color),
+                topics: Some(topics),
+                content: Some(content),
+                status: front.status,
+                allow_comments: front.allow_comments,
+                expected_version: front.version,
+            };
+            let updated = apply_tutorial_update(&pool, &claims.sub, existing, payload).await?;
+            ("updated", updated)
+        }
+        None => {
+            let taken = repositories::tutorials::check_tutorial_id_taken(&pool, &id)
+                .await
+                .map_err(internal_error("Failed to import tutorial"))?;
+            if taken {
+                return Err(conflict(TRASHED_ID_CONFLICT));
+            }
+            let topics_json = serde_json::to_string(&topics)
+                .map_err(internal_error("Failed to import tutorial"))?;
+            let status = front
+                .status
+                .as_deref()
+                .map(str::trim)
+                .unwrap_or(TUTORIAL_STATUS_PUBLISHED);
+            let created = repositories::tutorials::create_tutorial(
+                &pool,
+                &id,
+                &title,
+                &description,
+                &content,
+                &front.icon,
+                &front.color,
+                &topics_json,
+                &topics,
+                status,
+                front.allow_comments.unwrap_or(true),
+                Some(&claims.sub),
+            )
+            .await
+            .map_err(internal_error("Failed to import tutorial"))?;
+            ("created", created)
+        }
+    };
+
+    repositories::audit::append_entry(
+        &pool,
+        &claims.sub,
+        "import",
+        "tutorial",
+        Some(&tutorial.id),
+        serde_json::json!({ "format": "markdown", "outcome": outcome }),
+    )
+    .await;
+
+    let tutorial: TutorialResponse = tutorial
+        .try_into()
+        .map_err(internal_error("Failed to parse stored tutorial data"))?;
+
+    Ok(Json(TutorialImportResponse {
+        outcome,
+        tutorial,
+        comments_imported: 0,
+        comments_skipped: 0,
+    }))
+}
+
+/// Handler importing a document produced by [`export_tutorial`].
+/// Admin-only.
+pub async fn import_tutorial(
+    claims: auth::Claims,
+    State(pool): State<DbPool>,
+    Query(params): Query<TutorialImportQuery>,
+    Json(document): Json<TutorialExportDocument>,
+) -> Result<Json<TutorialImportResponse>, AppError> {
+    ensure_admin(&claims)?;
+
+    if document.format != TUTORIAL_EXPORT_FORMAT {
+        return Err(bad_request(format!(
+            "Unsupported export format {} (expected {})",
+            document.format, TUTORIAL_EXPORT_FORMAT
+        )));
+    }
+
+    let source = document.tutorial;
+    let source_id = source.id.trim().to_string();
+    validate_tutorial_id(&source_id).map_err(bad_request)?;
+
+    let title = source.title.trim().to_string();
+    let description = source.description.trim().to_string();
+    let content = source.content.trim().to_string();
+    validate_tutorial_data(&title, &description, &content).map_err(bad_request)?;
+    validate_icon(&pool, &source.icon).await?;
+    validate_color(&source.color).map_err(bad_request)?;
+    let status = source.status.trim();
+    validate_tutorial_status(status).map_err(bad_request)?;
+
+    let topics = sanitize_topics(&source.topics).map_err(bad_request)?;
+    let topics_json =
+        serde_json::to_string(&topics).map_err(internal_error("Failed to import tutorial"))?;
+
+    let comments = document.comments.unwrap_or_default();
+    if comments.len() > MAX_IMPORTED_COMMENTS {
+        return Err(bad_request(format!(
+            "Too many comments (max {MAX_IMPORTED_COMMENTS})"
+        )));
+    }
+    for comment in &comments {
+        validate_imported_comment(comment).map_err(bad_request)?;
+    }
+
+    let existing = repositories::tutorials::get_tutorial(&pool, &source_id)
+        .await
+        .map_err(internal_error("Failed to import tutorial"))?;
+    if existing.is_none()
+        && repositories::tutorials::check_tutorial_id_taken(&pool, &source_id)
+            .await
+            .map_err(internal_error("Failed to import tutorial"))?
+    {
+        return Err(conflict(TRASHED_ID_CONFLICT));
+    }
+
+    // The tutorial and its comments are written together or not at all
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(internal_error("Failed to import tutorial"))?;
+
+    let (outcome, tutorial, fresh_comment_ids) = match (existing, params.on_conflict) {
+        (Some(existing), ImportConflictStrategy::Skip) => ("skipped", existing, false),
+        (Some(existing), ImportConflictStrategy::Overwrite) => {
+            let updated = repositories::tutorials::update_tutorial_tx(
+                &mut tx,
+                &claims.sub,
+                &source_id,
+                &title,
+                &description,
+                &content,
+                &source.icon,
+                &source.color,
+                &topics_json,
+                &topics,
+                status,
+                source.allow_comments,
+                existing.version as i32,
+            )
+            .await
+            .map_err(internal_error("Failed to import tutorial"))?
+            .ok_or_else(|| {
+                conflict("Tutorial was modified by another request. Please retry the import.")
+            })?;
+            ("overwritten", updated, false)
+        }
+        (existing, strategy) => {
+            // Either the ID is free, or the caller asked for a new one. Comment
+            // IDs are regenerated alongside a new tutorial ID so the copy never
+            // collides with the original's comments.
+            let fresh = existing.is_some() && strategy == ImportConflictStrategy::NewId;
+            let id = if fresh {
+                Uuid::new_v4().to_string()
+            } else {
+                source_id.clone()
+            };
+            let created = repositories::tutorials::create_tutorial_tx(
+                &mut tx,
+                &id,
+                &title,
+                &description,
+                &content,
+                &source.icon,
+                &source.color,
+                &topics_json,
+                &topics,
+                status,
+                source.allow_comments,
+                Some(&claims.sub),
+            )
+            .await
+            .map_err(internal_error("Failed to import tutorial"))?;
+            ("created", created, fresh)
+        }
+    };
+
+    let comments_total = comments.len();
+    let comments_imported = if outcome == "skipped" || comments.is_empty() {
+        0
+    } else {
+        // Replies follow their parents (comments are exported oldest first),
+        // so a regenerated parent ID is always known by the time it's needed.
+        let mut fresh_ids: HashMap<String, String> = HashMap::new();
+        let rows: Vec<Comment> = comments
+            .into_iter()
+            .map(|c| {
+                let source_id = c.id.trim().to_string();
+                let (id, parent_id) = if fresh_comment_ids {
+                    let id = Uuid::new_v4().to_string();
+                    fresh_ids.insert(source_id, id.clone());
+                    let parent_id = c.parent_id.and_then(|p| fresh_ids.get(p.trim()).cloned());
+                    (id, parent_id)
+                } else {
+                    (source_id, c.parent_id.map(|p| p.trim().to_string()))
+                };
+                Comment {
+                    id,
+                    tutorial_id: Some(tutorial.id.clone()),
+                    post_id: None,
+                    parent_id,
+                    author: c.author.trim().to_string(),
+                    content: c.content.trim().to_string(),
+                    created_at: c.created_at,
+                    edited_at: c.edited_at,
+                    votes: c.votes.max(0),
+                    is_admin: c.is_admin,
+                    author_username: c.author_username,
+                    is_guest: c.is_guest,
+                    avatar_hash: c.avatar_hash,
+                    status: c.status,
+                    // Account IDs are local to the exporting site, so imported
+                    // comments are not owned by anyone here.
+                    user_id: None,
+                }
+            })
+            .collect();
+
+        repositories::comments::insert_imported_comments_tx(&mut tx, &rows)
+            .await
+            .map_err(internal_error("Failed to import tutorial comments"))?
+    };
+    let comments_skipped = comments_total - comments_imported;
+
+    tx.commit()
+        .await
+        .map_err(internal_error("Failed to import tutorial"))?;
+    if outcome != "skipped" {
+        crate::sitemap::invalidate();
+    }
+
+    tracing::info!(
+        action = "import_tutorial",
+        user = %claims.sub,
+        source_id = %source_id,
+        tutorial_id = %tutorial.id,
+        outcome,
+        comments_imported,
+        comments_skipped,
+        "Tutorial import processed"
+    );
+    if outcome != "skipped" {
+        repositories::audit::append_entry(
+            &pool,
+            &claims.sub,
+            "import",
+            "tutorial",
+            Some(&tutorial.id),
+            serde_json::json!({
+                "source_id": source_id,
+                "outcome": outcome,
+                "comments_imported": comments_imported,
+                "comments_skipped": comments_skipped,
+            }),
+        )
+        .await;
+    }
+
+    let tutorial: TutorialResponse = tutorial
+        .try_into()
+        .map_err(internal_error("Failed to parse stored tutorial data"))?;
+
+    Ok(Json(TutorialImportResponse {
+        outcome,
+        tutorial,
+        comments_imported,
+        comments_skipped,
+    }))
+}
+
+/// Loads a tutorial (and optionally its comments) into an export document.
+async fn build_export_document(
+    pool: &DbPool,
+    id: &str,
+    include_comments: bool,
+) -> Result<TutorialExportDocument, AppError> {
+    let tutorial = repositories::tutorials::get_tutorial(pool, id)
+        .await
+        .map_err(internal_error("Failed to export tutorial"))?
+        .ok_or_else(|| not_found("Tutorial not found"))?;
+
+    let topics: Vec<String> = serde_json::from_str(&tutorial.topics)
+        .map_err(internal_error("Failed to read stored tutorial topics"))?;
+
+    let comments = if include_comments {
+        let rows = repositories::comments::list_all_tutorial_comments(pool, id)
+            .await
+            .map_err(internal_error("Failed to export tutorial comments"))?;
+        Some(
+            rows.into_iter()
+                .map(|c| TutorialCommentExport {
+                    id: c.id,
+                    parent_id: c.parent_id,
+                    author: c.author,
+                    content: c.content,
+                    created_at: c.created_at,
+                    edited_at: c.edited_at,
+                    votes: c.votes,
+                    is_admin: c.is_admin,
+                    author_username: c.author_username,
+                    avatar_hash: c.avatar_hash,
+                    is_guest: c.is_guest,
+                    status: c.status,
+                })
+                .collect(),
+        )
+    } else {
+        None
+    };
+
+    Ok(TutorialExportDocument {
+        format: TUTORIAL_EXPORT_FORMAT,
+        exported_at: Some(chrono::Utc::now().to_rfc3339()),
+        tutorial: TutorialExport {
+            id: tutorial.id,
+            title: tutorial.title,
+            description: tutorial.description,
+            icon: tutorial.icon,
+            color: tutorial.color,
+            topics,
+            content: tutorial.content,
+            status: tutorial.status,
+            allow_comments: tutorial.allow_comments,
+            version: Some(tutorial.version),
+            created_at: Some(tutorial.created_at),
+            updated_at: Some(tutorial.updated_at),
+        },
+        comments,
+    })
+}
+
+/// Applies the same bounds the comment handlers enforce on new comments.
+pub(crate) fn validate_imported_comment(comment: &TutorialCommentExport) -> Result<(), String> {
+    let id = comment.id.trim();
+    if id.is_empty() || id.len() > 100 {
+        return Err("Invalid comment ID (must be 1-100 characters)".to_string());
+    }
+
+    let author = comment.author.trim();
+    if author.is_empty() || author.len() > 100 {
+        return Err(format!("Comment {id}: author must be 1-100 characters"));
+    }
+
+    let content = comment.content.trim();
+    if content.is_empty() || content.len() > 1_000 {
+        return Err(format!("Comment {id}: content must be 1-1000 characters"));
+    }
+
+    if comment.created_at.trim().is_empty() || comment.created_at.len() > 64 {
+        return Err(format!("Comment {id}: invalid created_at"));
+    }
+
+    if comment
+        .edited_at
+        .as_ref()
+        .is_some_and(|edited_at| edited_at.len() > 64)
+    {
+        return Err(format!("Comment {id}: invalid edited_at"));
+    }
+
+    if comment.avatar_hash.as_ref().is_some_and(|hash| {
+        hash.len() != 64 || !hash.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+    }) {
+        return Err(format!("Comment {id}: invalid avatar_hash"));
+    }
+
+    if comment.status != COMMENT_STATUS_PUBLISHED && comment.status != COMMENT_STATUS_PENDING {
+        return Err(format!("Comment {id}: invalid status"));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn admin() -> auth::Claims {
+        crate::test_support::claims_for("admin", "admin")
+    }
+
+    async fn seed_tutorial(pool: &DbPool, id: &str) {
+        let topics = vec!["Rust".to_string(), "Async".to_string()];
+        repositories::tutorials::create_tutorial(
+            pool,
+            id,
+            "Round trip",
+            "Moves between instances",
+            "# Heading\n\nBody text.",
+            "Terminal",
+            "from-blue-500 to-indigo-600",
+            &serde_json::to_string(&topics).unwrap(),
+            &topics,
+            TUTORIAL_STATUS_PUBLISHED,
+            true,
+            None,
+        )
+        .await
+        .expect("seed tutorial");
+
+        let comment = Comment {
+            id: format!("{id}-comment"),
+            tutorial_id: Some(id.to_string()),
+            post_id: None,
+            parent_id: None,
+            author: "Reader".to_string(),
+            content: "Great read".to_string(),
+            created_at: "2024-01-01T00:00:00+00:00".to_string(),
+            edited_at: None,
+            votes: 3,
+            is_admin: false,
+            author_username: Some("reader".to_string()),
+            is_guest: Some(false),
+            user_id: None,
+            avatar_hash: None,
+            status: COMMENT_STATUS_PUBLISHED.to_string(),
+        };
+        repositories::comments::insert_imported_comments(pool, &[comment])
+            .await
+            .expect("seed comment");
+    }
+
+    async fn export(pool: &DbPool, id: &str) -> serde_json::Value {
+        let document = build_export_document(pool, id, true)
+            .await
+            .expect("export tutorial");
+        let mut value = serde_json::to_value(document).unwrap();
+        // Fields describing the source instance are not carried over.
+        value.as_object_mut().unwrap().remove("exported_at");
+        let tutorial = value["tutorial"].as_object_mut().unwrap();
+        for volatile in ["version", "created_at", "updated_at"] {
+            tutorial.remove(volatile);
+        }
+        value
+    }
+
+    async fn import(
+        pool: &DbPool,
+        document: serde_json::Value,
+        on_conflict: ImportConflictStrategy,
+    ) -> TutorialImportResponse {
+        let Json(response) = import_tutorial(
+            admin(),
+            State(pool.clone()),
+            Query(TutorialImportQuery { on_conflict }),
+            Json(serde_json::from_value(document).unwrap()),
+        )
+        .await
+        .expect("import tutorial");
+        response
+    }
+
+    #[tokio::test]
+    async fn export_import_export_round_trips() {
+        let source = crate::test_support::migrated_pool().await;
+        seed_tutorial(&source, "round-trip").await;
+        let exported = export(&source, "round-trip").await;
+
+        let target = crate::test_support::migrated_pool().await;
+        let response = import(&target, exported.clone(), ImportConflictStrategy::Skip).await;
+        assert_eq!(response.outcome, "created");
+        assert_eq!(response.comments_imported, 1);
+
+        assert_eq!(export(&target, "round-trip").await, exported);
+    }
+
+    #[tokio::test]
+    async fn conflict_strategies_behave_as_documented() {
+        let pool = crate::test_support::migrated_pool().await;
+        seed_tutorial(&pool, "conflict").await;
+        let mut document = export(&pool, "conflict").await;
+        document["tutorial"]["title"] = serde_json::json!("Changed title");
+
+        let skipped = import(&pool, document.clone(), ImportConflictStrategy::Skip).await;
+        assert_eq!(skipped.outcome, "skipped");
+        assert_eq!(skipped.tutorial.title, "Round trip");
+
+        let overwritten = import(&pool, document.clone(), ImportConflictStrategy::Overwrite).await;
+        assert_eq!(overwritten.outcome, "overwritten");
+        assert_eq!(overwritten.tutorial.title, "Changed title");
+        assert_eq!(overwritten.tutorial.version, 2);
+        // The comment already exists under its original ID.
+        assert_eq!(overwritten.comments_imported, 0);
+        assert_eq!(overwritten.comments_skipped, 1);
+
+        let copied = import(&pool, document, ImportConflictStrategy::NewId).await;
+        assert_eq!(copied.outcome, "created");
+        assert_ne!(copied.tutorial.id, "conflict");
+        assert_eq!(copied.comments_imported, 1);
+        assert_eq!(copied.comments_skipped, 0);
+        assert_eq!(copied.tutorial.topics, vec!["Rust", "Async"]);
+    }
+
+    #[tokio::test]
+    async fn failed_comment_insert_rolls_back_the_tutorial() {
+        let source = crate::test_support::migrated_pool().await;
+        seed_tutorial(&source, "atomic").await;
+        let document = export(&source, "atomic").await;
+
+        let target = crate::test_support::migrated_pool().await;
+        sqlx::query(
+            "CREATE TRIGGER reject_comments BEFORE INSERT ON comments \
+             BEGIN SELECT RAISE(ABORT, 'comments are read-only'); END",
+        )
+        .execute(&target)
+        .await
+        .unwrap();
+
+        let err = import_tutorial(
+            admin(),
+            State(target.clone()),
+            Query(TutorialImportQuery::default()),
+            Json(serde_json::from_value(document).unwrap()),
+        )
+        .await
+        .expect_err("comment insert fails");
+        assert_eq!(err.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(repositories::tutorials::get_tutorial(&target, "atomic")
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn import_rejects_unknown_format_and_invalid_fields() {
+        let pool = crate::test_support::migrated_pool().await;
+        seed_tutorial(&pool, "invalid").await;
+        let document = export(&pool, "invalid").await;
+
+        let mut future_format = document.clone();
+        future_format["format"] = serde_json::json!(2);
+        let mut bad_icon = document;
+        bad_icon["tutorial"]["id"] = serde_json::json!("other-id");
+        bad_icon["tutorial"]["icon"] = serde_json::json!("NotAnIcon");
+
+        for body in [future_format, bad_icon] {
+            let status = import_tutorial(
+                admin(),
+                State(pool.clone()),
+                Query(TutorialImportQuery {
+                    on_conflict: ImportConflictStrategy::Skip,
+                }),
+                Json(serde_json::from_value(body).unwrap()),
+            )
+            .await
+            .unwrap_err()
+            .status();
+            assert_eq!(status, StatusCode::BAD_REQUEST);
+        }
+
+        assert!(
+            !repositories::tutorials::check_tutorial_exists(&pool, "other-id")
+                .await
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn markdown_export_imports_as_create_then_update() {
+        let source = crate::test_support::migrated_pool().await;
+        seed_tutorial(&source, "markdown").await;
+        let response =
+            export_tutorial_markdown(admin(), State(source.clone()), Path("markdown".to_string()))
+                .await
+                .expect("export markdown");
+        assert_eq!(
+            response.headers()[header::CONTENT_DISPOSITION],
+            "attachment; filename=\"tutorial-markdown.md\""
+        );
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let document = String::from_utf8(bytes.to_vec()).unwrap();
+        assert!(document.starts_with("---\nid: \"markdown\"\n"));
+
+        let target = crate::test_support::migrated_pool().await;
+        let Json(created) =
+            import_tutorial_markdown(admin(), State(target.clone()), document.clone())
+                .await
+                .expect("import markdown");
+        assert_eq!(created.outcome, "created");
+        assert_eq!(created.tutorial.content, "# Heading\n\nBody text.");
+        assert_eq!(created.tutorial.topics, vec!["Rust", "Async"]);
+
+        let edited = document.replace("Round trip", "Edited elsewhere");
+        let Json(updated) = import_tutorial_markdown(admin(), State(target.clone()), edited)
+            .await
+            .expect("reimport markdown");
+        assert_eq!(updated.outcome, "updated");
+        assert_eq!(updated.tutorial.title, "Edited elsewhere");
+        assert_eq!(updated.tutorial.version, 2);
+
+        // The file still carries version 1, which is now stale.
+        let status = import_tutorial_markdown(admin(), State(target), document)
+            .await
+            .unwrap_err()
+            .status();
+        assert_eq!(status, StatusCode::CONFLICT);
+    }
+}
@@ -21,7 +21,7 @@ pub(crate) fn validate_tutorial_id(id: &str) -> Result<(), String> {
 }
 
 /// Validates the core text content of a tutorial.
-pub(super) fn validate_tutorial_data(
+pub(crate) fn validate_tutorial_data(
     title: &str,
     description: &str,
     content: &str,
@@ -55,26 +55,50 @@ pub(super) fn validate_tutorial_data(
     Ok(())
 }
 
-/// Validates that the provided icon name is within the allowed Lucide whitelist.
-pub(crate) fn validate_icon(icon: &str) -> Result<(), String> {
-    /// Whitelist of Lucide icon identifiers used in the frontend
-    const ALLOWED_ICONS: &[&str] = &[
-        "Terminal",   // Command line and shell tutorials
-        "FolderTree", // File system and directory tutorials
-        "FileText",   // Text editing and file manipulation
-        "Settings",   // System configuration and settings
-        "Shield",     // Security and permissions
-        "Network",    // Networking and connectivity
-        "Database",   // Database and data management
-        "Server",     // Server administration and services
-    ];
-
-    if ALLOWED_ICONS.contains(&icon) {
+/// Maximum length of an icon name; the longest Lucide names are around 30.
+const MAX_ICON_NAME_LEN: usize = 64;
+
+/// Checks that `icon` looks like a Lucide component name (`FolderTree`,
+/// `Building2`) before it is looked up in the icon registry.
+pub(crate) fn validate_icon_name(icon: &str) -> Result<(), String> {
+    if icon.is_empty() || icon.len() > MAX_ICON_NAME_LEN {
+        return Err(format!(
+            "Invalid icon (must be 1-{MAX_ICON_NAME_LEN} characters)"
+        ));
+    }
+    if !icon.starts_with(|c: char| c.is_ascii_uppercase())
+        || !icon.chars().all(|c| c.is_ascii_alphanumeric())
+    {
+        return Err(format!(
+            "Invalid icon '{icon}' (expected a Lucide name such as 'FolderTree')"
+        ));
+    }
+    Ok(())
+}
+
+/// Validates that `icon` is registered in `allowed_icons`
+/// (see [`crate::handlers::icons`]).
+pub(crate) async fn validate_icon(pool: &DbPool, icon: &str) -> Result<(), AppError> {
+    validate_icon_name(icon).map_err(bad_request)?;
+    let allowed = crate::handlers::icons::is_allowed(pool, icon)
+        .await
+        .map_err(internal_error("Failed to check icon"))?;
+    if allowed {
+        Ok(())
+    } else {
+        Err(bad_request(format!(
+            "Icon '{icon}' is not allowed; an admin can add it under /api/admin/icons"
+        )))
+    }
+}
+
+/// Validates a tutorial `status`: `draft` or `published`.
+pub(crate) fn validate_tutorial_status(status: &str) -> Result<(), String> {
+    if status == TUTORIAL_STATUS_DRAFT || status == TUTORIAL_STATUS_PUBLISHED {
         Ok(())
     } else {
         Err(format!(
-            "Invalid icon '{}'. Must be one of: {:?}",
-            icon, ALLOWED_ICONS
+            "Invalid status '{status}'. Must be '{TUTORIAL_STATUS_DRAFT}' or '{TUTORIAL_STATUS_PUBLISHED}'"
         ))
     }
 }
@@ -150,7 +174,7 @@ pub(crate) fn validate_color(color: &str) -> Result<(), String> {
 
 /// Sanitizes a list of topics.
 /// Normalizes to lowercase, removes duplicates, and trims long strings.
-pub(super) fn sanitize_topics(topics: &[String]) -> Result<Vec<String>, String> {
+pub(crate) fn sanitize_topics(topics: &[String]) -> Result<Vec<String>, String> {
     // SECURITY: Limit number of topics to prevent indexing DoS
     if topics.len() > 20 {
         return Err("Too many topics (max 20)".to_string());
@@ -192,3 +216,33 @@ pub(super) fn sanitize_topics(topics: &[String]) -> Result<Vec<String>, String>
 
     Ok(sanitized)
 }
+
+/// Parses the topic filter of the tutorial list: `topic` plus the
+/// comma-separated `topics`, all of which a tutorial must have. Returns the
+/// topics lowercased and without duplicates; empty when there is no filter.
+pub(crate) fn parse_topic_filter(
+    topic: Option<&str>,
+    topics: Option<&str>,
+) -> Result<Vec<String>, String> {
+    let requested = topic
+        .into_iter()
+        .chain(topics.into_iter().flat_map(|list| list.split(',')))
+        .map(str::trim)
+        .filter(|topic| !topic.is_empty());
+
+    let mut filter = Vec::new();
+    for topic in requested {
+        if topic.chars().count() > 100 {
+            return Err("Topic filter values must be at most 100 characters".to_string());
+        }
+        let canonical = topic.to_ascii_lowercase();
+        if !filter.contains(&canonical) {
+            filter.push(canonical);
+        }
+    }
+    if filter.len() > 20 {
+        return Err("Too many topics to filter by (max 20)".to_string());
+    }
+
+    Ok(filter)
+}
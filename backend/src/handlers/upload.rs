@@ -2,27 +2,101 @@
 //!
 //! This module provides secure image upload capabilities with several safeguards:
 //! - RBAC: Admin role required
-//! - File size limits (10MB)
+//! - File size limits (`MAX_UPLOAD_SIZE_MB`, 10MB by default)
 //! - Filename extension whitelisting
 //! - Magic byte (MIME) inference to prevent extension spoofing
+//! - A full decode of the written file, with a cap on the pixel dimensions
+//!   against decompression bombs; JPEGs and PNGs are re-encoded without
+//!   EXIF and other metadata
 //! - Atomic-like file writing with cleanup on failure
+//! - A record in `uploads` (who, when, type, size, dimensions) that is
+//!   committed only once the file is in the configured [`Store`]
+//! - Narrower WebP variants for pages that show the image small (see
+//!   [`crate::media`]); if they can't be made, only the original is kept
 //! - UUID-based filename generation to prevent collisions and path injection
+//!
+//! Images can also be imported from a public URL; those downloads are
+//! guarded against server-side request forgery by [`outbound`].
+//!
+//! The recorded uploads form the media library, which admins can browse and
+//! prune; an upload still referenced by any content can't be deleted.
+//! Unreferenced ones can be cleaned up in bulk (see [`upload_cleanup`]).
 
 use crate::{
+    db,
+    handlers::{
+        common::{ensure_admin, require_permission},
+        search::escape_like_pattern,
+    },
+    media::{self, ProcessedUpload, UploadImageError},
     models::{
-        bad_request, forbidden, internal_error, internal_error_plain, ApiError, UploadResponse,
+        api_error, bad_request, internal_error, internal_error_plain, not_found, AppError,
+        Paginated, UpdateUploadRequest, Upload, UploadInUseResponse, UploadResponse,
+    },
+    repositories,
+    security::{
+        auth::{self, Permission},
+        outbound,
     },
-    security::auth,
+    storage::{self, Store, StoreError},
+    upload_cleanup::{self, CleanupReport},
 };
-use axum::{extract::Multipart, Json};
+use axum::{
+    extract::{Multipart, Path as UrlPath, Query, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Deserialize;
+use std::ops::RangeInclusive;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
 use tokio::fs;
+use url::Url;
 use uuid::Uuid;
 
-/// Maximum allowed file size for uploads (10 megabytes)
-const MAX_FILE_SIZE: usize = 10 * 1024 * 1024;
 /// List of allowed file extensions for image uploads
 const ALLOWED_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "gif", "webp"];
+/// Time allowed for fetching an image from a URL, redirects included.
+const FETCH_TIMEOUT: Duration = Duration::from_secs(15);
+/// Redirects followed when fetching an image from a URL.
+const MAX_FETCH_REDIRECTS: usize = 3;
+/// Longest client file name that is recorded.
+const MAX_ORIGINAL_NAME_CHARS: usize = 255;
+/// Longest alt text and caption of an upload, after trimming.
+const MAX_ALT_CHARS: usize = 300;
+const MAX_CAPTION_CHARS: usize = 1000;
+
+const DEFAULT_LIST_LIMIT: i64 = 50;
+const MAX_LIST_LIMIT: i64 = 200;
+
+/// Query parameters for `GET /api/admin/uploads`.
+#[derive(Debug, Deserialize)]
+pub struct UploadListQuery {
+    /// Substring of the stored or the original file name.
+    #[serde(default)]
+    pub q: Option<String>,
+    #[serde(default)]
+    pub limit: Option<i64>,
+    #[serde(default)]
+    pub offset: Option<i64>,
+}
+
+/// Query parameters for `POST /api/admin/uploads/cleanup`.
+#[derive(Debug, Deserialize)]
+pub struct UploadCleanupQuery {
+    /// Only report the orphans; the default.
+    #[serde(default)]
+    pub dry_run: Option<bool>,
+}
+
+/// Body of `POST /api/admin/uploads/fetch`.
+#[derive(Debug, Deserialize)]
+pub struct FetchUploadRequest {
+    /// Public http(s) URL of the image.
+    pub url: String,
+}
 
 /// Removes orphaned `.tmp` files left in the upload directory by uploads
 /// that were interrupted by a crash or restart.
@@ -71,25 +145,56 @@ fn is_own_temp_upload(path: &Path) -> bool {
 
 /// Processes a multipart form-data request to upload an image.
 /// Implements strict security validations before saving to disk.
+///
+/// Besides the `file` field, the form may carry `alt` and `caption` text
+/// fields, in any order.
 pub async fn upload_image(
     claims: auth::Claims,
+    State(pool): State<db::DbPool>,
+    State(store): State<Arc<dyn Store>>,
     mut multipart: Multipart,
-) -> Result<Json<UploadResponse>, ApiError> {
-    // SECURITY: Ensure only authorized administrators can upload assets
-    if claims.role != "admin" {
-        return Err(forbidden("Insufficient permissions"));
-    }
+) -> Result<Json<UploadResponse>, AppError> {
+    // SECURITY: Ensure only roles that edit content can upload assets
+    require_permission(&claims, Permission::UploadMedia)?;
+
+    let max_file_size = crate::config::get().max_upload_bytes();
+    let mut staged_upload: Option<StagedUpload> = None;
+    let mut alt = None;
+    let mut caption = None;
 
     // Iterate through multipart fields
-    while let Some(mut field) = multipart
-        .next_field()
-        .await
-        .map_err(|err| bad_request(format!("Failed to process multipart field: {}", err)))?
-    {
-        // We only care about fields named "file"
+    loop {
+        let mut field = match multipart.next_field().await {
+            Ok(Some(field)) => field,
+            Ok(None) => break,
+            Err(err) => {
+                discard_staged(staged_upload).await;
+                return Err(bad_request(format!(
+                    "Failed to process multipart field: {}",
+                    err
+                )));
+            }
+        };
         let name = field.name().unwrap_or("").to_string();
 
-        if name == "file" {
+        if name == "alt" || name == "caption" {
+            let text = match field.text().await {
+                Ok(text) => text,
+                Err(err) => {
+                    discard_staged(staged_upload).await;
+                    return Err(bad_request(format!("Failed to read {}: {}", name, err)));
+                }
+            };
+            if name == "alt" {
+                alt = Some(text);
+            } else {
+                caption = Some(text);
+            }
+            continue;
+        }
+
+        // Only the first "file" field is stored
+        if name == "file" && staged_upload.is_none() {
             let file_name = field.file_name().unwrap_or("unknown").to_string();
 
             // Extract and normalize the file extension
@@ -118,41 +223,7 @@ pub async fn upload_image(
             };
 
             // VALIDATION: Verify the file content matches an allowed image type
-            if let Some(kind) = infer::get(&first_chunk) {
-                let detected_ext = kind.extension();
-                // Normalize "jpeg" vs "jpg" for comparison
-                let normalized_detected = if detected_ext == "jpeg" {
-                    "jpg"
-                } else {
-                    detected_ext
-                };
-                let normalized_ext = if ext == "jpeg" { "jpg" } else { ext.as_str() };
-
-                // SECURITY: Reject outright if the detected content type is not one of our
-                // allowed image formats. This must be checked independently of the mismatch
-                // check below: a detected type outside the allowlist (e.g. exe, zip, pdf)
-                // would otherwise pass through unrejected and be saved under the client's
-                // claimed extension.
-                if !ALLOWED_EXTENSIONS.contains(&normalized_detected) {
-                    return Err(bad_request(format!(
-                        "Invalid file content. Detected type '{}' is not an allowed image format",
-                        detected_ext
-                    )));
-                }
-
-                // SECURITY: Reject if the detected type contradicts the provided file extension.
-                if normalized_detected != normalized_ext {
-                    return Err(bad_request(format!(
-                        "File extension mismatch. Expected '{}', but detected '{}'",
-                        ext, detected_ext
-                    )));
-                }
-            } else {
-                // REJECT if we can't determine what it is; this is safer than allowing mystery blobs.
-                return Err(bad_request(
-                    "Could not determine file type from magic bytes",
-                ));
-            }
+            let (_, mime) = detect_image_type(&first_chunk, Some(&ext))?;
 
             // ENFORCEMENT: The size cap must also cover the first chunk. The
             // check inside the streaming loop below only runs from the second
@@ -160,29 +231,16 @@ pub async fn upload_image(
             // be written to disk before any limit applied (the router's
             // DefaultBodyLimit backstops this, but the handler must enforce
             // its own invariant).
-            if first_chunk.len() > MAX_FILE_SIZE {
-                return Err(bad_request(format!(
-                    "File too large. Max size: {} bytes",
-                    MAX_FILE_SIZE
-                )));
+            if first_chunk.len() > max_file_size {
+                return Err(file_too_large());
             }
 
             // Generate a random ID for the filename to prevent path injection and name collisions
             let id = Uuid::new_v4();
             let new_filename = format!("{}.{}", id, ext);
 
-            // Resolve the upload directory from environment or default to local "uploads"
-            let upload_dir = std::env::var("UPLOAD_DIR").unwrap_or_else(|_| "uploads".to_string());
-            let upload_path_base = PathBuf::from(upload_dir);
-
-            // BOOTSTRAP: Ensure the physical directory exists
-            if !upload_path_base.exists() {
-                fs::create_dir_all(&upload_path_base)
-                    .await
-                    .map_err(internal_error("Failed to create uploads directory"))?;
-            }
+            let upload_path_base = staging_dir().await?;
 
-            let filepath = upload_path_base.join(&new_filename);
             // Create a temporary file path
             let temp_filename = format!("{}.tmp", id);
             let temp_filepath = upload_path_base.join(&temp_filename);
@@ -230,12 +288,9 @@ pub async fn upload_image(
 
                 // ENFORCEMENT: Track total size to prevent Disk Space exhaustion (DoS)
                 total_size += chunk.len();
-                if total_size > MAX_FILE_SIZE {
+                if total_size > max_file_size {
                     let _ = tokio::fs::remove_file(&temp_filepath).await;
-                    return Err(bad_request(format!(
-                        "File too large. Max size: {} bytes",
-                        MAX_FILE_SIZE
-                    )));
+                    return Err(file_too_large());
                 }
 
                 // Write chunk to disk
@@ -250,42 +305,861 @@ pub async fn upload_image(
                 }
             }
 
-            // Sync buffers to disk
+            // Sync buffers to disk before the file is read back for decoding
             if let Err(e) = file.flush().await {
                 tracing::error!("Failed to flush file {}: {}", temp_filepath.display(), e);
                 let _ = tokio::fs::remove_file(&temp_filepath).await;
                 return Err(internal_error_plain("Failed to save file"));
             }
+            drop(file);
 
-            // Atomic rename from temp to final
-            if let Err(e) = tokio::fs::rename(&temp_filepath, &filepath).await {
-                tracing::error!(
-                    "Failed to rename temp file {} to {}: {}",
-                    temp_filepath.display(),
-                    filepath.display(),
-                    e
-                );
-                let _ = tokio::fs::remove_file(&temp_filepath).await;
-                return Err(internal_error_plain("Failed to save file"));
+            staged_upload = Some(StagedUpload {
+                id: id.to_string(),
+                filename: new_filename,
+                mime,
+                original_name: sanitize_original_name(&file_name),
+                alt: None,
+                caption: None,
+                path: temp_filepath,
+            });
+        }
+    }
+
+    // Default error if for some reason the "file" field was missing
+    let Some(mut staged) = staged_upload else {
+        return Err(bad_request("No file found in request"));
+    };
+    let descriptions =
+        normalize_description(alt.as_deref(), "alt", MAX_ALT_CHARS).and_then(|alt| {
+            normalize_description(caption.as_deref(), "caption", MAX_CAPTION_CHARS)
+                .map(|caption| (alt, caption))
+        });
+    match descriptions {
+        Ok((alt, caption)) => {
+            staged.alt = alt;
+            staged.caption = caption;
+        }
+        Err(err) => {
+            discard_staged(Some(staged)).await;
+            return Err(err);
+        }
+    }
+
+    finish_upload(&pool, store.as_ref(), &claims.sub, staged, None)
+        .await
+        .map(Json)
+}
+
+/// 413 naming the configured `MAX_UPLOAD_SIZE_MB`.
+fn file_too_large() -> AppError {
+    api_error(
+        StatusCode::PAYLOAD_TOO_LARGE,
+        format!(
+            "File too large. Max size: {} MB",
+            crate::config::get().max_upload_size_mb
+        ),
+    )
+}
+
+/// Trims an alt text or caption; blank ones become `None`. Longer than
+/// `max_chars` is an error.
+fn normalize_description(
+    value: Option<&str>,
+    field: &str,
+    max_chars: usize,
+) -> Result<Option<String>, AppError> {
+    let Some(value) = value.map(str::trim).filter(|value| !value.is_empty()) else {
+        return Ok(None);
+    };
+    if value.chars().count() > max_chars {
+        return Err(bad_request(format!(
+            "{field} must be at most {max_chars} characters"
+        )));
+    }
+    Ok(Some(value.to_string()))
+}
+
+async fn discard_staged(staged: Option<StagedUpload>) {
+    if let Some(staged) = staged {
+        let _ = fs::remove_file(&staged.path).await;
+    }
+}
+
+/// Handler for `POST /api/admin/uploads/fetch`: imports the image at a
+/// public URL. The download goes through the same checks (size cap, magic
+/// bytes, full decode) and pipeline as a multipart upload.
+///
+/// SECURITY: Only http(s) URLs whose host resolves to public addresses are
+/// fetched, and the connection is pinned to the vetted addresses; every
+/// redirect (at most [`MAX_FETCH_REDIRECTS`]) is vetted again.
+pub async fn fetch_upload(
+    claims: auth::Claims,
+    State(pool): State<db::DbPool>,
+    State(store): State<Arc<dyn Store>>,
+    Json(payload): Json<FetchUploadRequest>,
+) -> Result<Json<UploadResponse>, AppError> {
+    require_permission(&claims, Permission::UploadMedia)?;
+
+    let url = Url::parse(payload.url.trim()).map_err(|_| bad_request("Invalid URL"))?;
+    let id = Uuid::new_v4();
+    let upload_dir = staging_dir().await?;
+    let temp_filepath = upload_dir.join(format!("{id}.tmp"));
+
+    let detected = match download(url.clone(), &temp_filepath).await {
+        Ok(()) => read_file_head(&temp_filepath)
+            .await
+            .map_err(internal_error("Failed to read file"))
+            .and_then(|head| detect_image_type(&head, None)),
+        Err(err) => Err(err),
+    };
+    let (ext, mime) = match detected {
+        Ok(detected) => detected,
+        Err(err) => {
+            let _ = fs::remove_file(&temp_filepath).await;
+            return Err(err);
+        }
+    };
+
+    let remote_name = url
+        .path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .filter(|segment| !segment.is_empty())
+        .unwrap_or("unknown");
+    let staged = StagedUpload {
+        id: id.to_string(),
+        filename: format!("{id}.{ext}"),
+        mime,
+        original_name: sanitize_original_name(remote_name),
+        alt: None,
+        caption: None,
+        path: temp_filepath,
+    };
+    finish_upload(
+        &pool,
+        store.as_ref(),
+        &claims.sub,
+        staged,
+        Some(url.as_str()),
+    )
+    .await
+    .map(Json)
+}
+
+/// Streams the resource at `url` into `target`, following redirects and
+/// enforcing `MAX_UPLOAD_SIZE_MB` and [`FETCH_TIMEOUT`]. `target` may be left
+/// behind on error.
+async fn download(url: Url, target: &Path) -> Result<(), AppError> {
+    let timed_out = || {
+        api_error(
+            StatusCode::GATEWAY_TIMEOUT,
+            "The URL did not answer in time",
+        )
+    };
+    tokio::time::timeout(FETCH_TIMEOUT, download_following_redirects(url, target))
+        .await
+        .map_err(|_| timed_out())?
+}
+
+async fn download_following_redirects(mut url: Url, target: &Path) -> Result<(), AppError> {
+    let unreachable = |err: reqwest::Error| {
+        tracing::warn!("Fetching an upload failed: {}", err);
+        api_error(StatusCode::BAD_GATEWAY, "The URL could not be fetched")
+    };
+
+    let mut redirects = 0;
+    let mut response = loop {
+        let addrs = outbound::resolve_public(&url)
+            .await
+            .map_err(|err| bad_request(format!("URL not allowed: {err}")))?;
+        // Connect only to the vetted addresses, never through a proxy
+        let mut client = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .no_proxy();
+        if let Some(domain) = url.domain() {
+            client = client.resolve_to_addrs(domain, &addrs);
+        }
+        let client = client
+            .build()
+            .map_err(internal_error("Failed to fetch URL"))?;
+        let response = client
+            .get(url.clone())
+            .header(header::ACCEPT, "image/*")
+            .send()
+            .await
+            .map_err(unreachable)?;
+
+        if !response.status().is_redirection() {
+            break response;
+        }
+        if redirects == MAX_FETCH_REDIRECTS {
+            return Err(bad_request("The URL redirects too often"));
+        }
+        redirects += 1;
+        let location = response
+            .headers()
+            .get(header::LOCATION)
+            .and_then(|value| value.to_str().ok())
+            .ok_or_else(|| bad_request("The URL redirects without a location"))?;
+        url = url
+            .join(location)
+            .map_err(|_| bad_request("The URL redirects to an invalid location"))?;
+    };
+
+    if !response.status().is_success() {
+        return Err(bad_request(format!(
+            "The URL answered {}",
+            response.status()
+        )));
+    }
+    let max_file_size = crate::config::get().max_upload_bytes();
+    if response
+        .content_length()
+        .is_some_and(|length| length > max_file_size as u64)
+    {
+        return Err(file_too_large());
+    }
+
+    use tokio::io::AsyncWriteExt; // Required for write_all and flush
+
+    let mut file = fs::File::create(target)
+        .await
+        .map_err(internal_error("Failed to create file"))?;
+    let mut total_size = 0;
+    while let Some(chunk) = response.chunk().await.map_err(unreachable)? {
+        // ENFORCEMENT: The declared length may be missing or wrong
+        total_size += chunk.len();
+        if total_size > max_file_size {
+            return Err(file_too_large());
+        }
+        file.write_all(&chunk)
+            .await
+            .map_err(internal_error("Failed to write file"))?;
+    }
+    if total_size == 0 {
+        return Err(bad_request("File is empty"));
+    }
+    file.flush()
+        .await
+        .map_err(internal_error("Failed to save file"))
+}
+
+/// The first bytes of `path`, enough for magic byte detection.
+async fn read_file_head(path: &Path) -> std::io::Result<Vec<u8>> {
+    use tokio::io::AsyncReadExt;
+
+    let mut head = Vec::with_capacity(64);
+    fs::File::open(path)
+        .await?
+        .take(64)
+        .read_to_end(&mut head)
+        .await?;
+    Ok(head)
+}
+
+/// The upload directory, created if needed. Uploads are staged and
+/// processed there, then handed to the store.
+async fn staging_dir() -> Result<PathBuf, AppError> {
+    let upload_dir = crate::config::get().upload_dir.clone();
+    // BOOTSTRAP: Ensure the physical directory exists
+    if !upload_dir.exists() {
+        fs::create_dir_all(&upload_dir)
+            .await
+            .map_err(internal_error("Failed to create uploads directory"))?;
+    }
+    Ok(upload_dir)
+}
+
+/// Checks the magic bytes at the start of a file: the detected type must be
+/// an allowed image format and match `claimed_ext`, the extension of the
+/// client's file name, if there is one. Returns the detected extension and
+/// MIME type.
+fn detect_image_type(
+    first_chunk: &[u8],
+    claimed_ext: Option<&str>,
+) -> Result<(&'static str, &'static str), AppError> {
+    // REJECT if we can't determine what it is; this is safer than allowing mystery blobs.
+    let Some(kind) = infer::get(first_chunk) else {
+        return Err(bad_request(
+            "Could not determine file type from magic bytes",
+        ));
+    };
+    let detected_ext = kind.extension();
+    // Normalize "jpeg" vs "jpg" for comparison
+    let normalized_detected = if detected_ext == "jpeg" {
+        "jpg"
+    } else {
+        detected_ext
+    };
+
+    // SECURITY: Reject outright if the detected content type is not one of our
+    // allowed image formats. This must be checked independently of the mismatch
+    // check below: a detected type outside the allowlist (e.g. exe, zip, pdf)
+    // would otherwise pass through unrejected and be saved under the client's
+    // claimed extension.
+    if !ALLOWED_EXTENSIONS.contains(&normalized_detected) {
+        return Err(bad_request(format!(
+            "Invalid file content. Detected type '{}' is not an allowed image format",
+            detected_ext
+        )));
+    }
+
+    // SECURITY: Reject if the detected type contradicts the provided file extension.
+    if let Some(ext) = claimed_ext {
+        let normalized_ext = if ext == "jpeg" { "jpg" } else { ext };
+        if normalized_detected != normalized_ext {
+            return Err(bad_request(format!(
+                "File extension mismatch. Expected '{}', but detected '{}'",
+                ext, detected_ext
+            )));
+        }
+    }
+    Ok((normalized_detected, kind.mime_type()))
+}
+
+/// A file written to the upload directory and checked by magic bytes,
+/// not yet decoded.
+struct StagedUpload {
+    id: String,
+    /// Final name, `<id>.<extension>`.
+    filename: String,
+    mime: &'static str,
+    original_name: String,
+    alt: Option<String>,
+    caption: Option<String>,
+    path: PathBuf,
+}
+
+/// Decodes, records and stores a staged upload. The staged file is gone
+/// afterwards, whatever the outcome.
+async fn finish_upload(
+    pool: &db::DbPool,
+    store: &dyn Store,
+    uploader: &str,
+    staged: StagedUpload,
+    source_url: Option<&str>,
+) -> Result<UploadResponse, AppError> {
+    let upload_path_base = crate::config::get().upload_dir.clone();
+    let StagedUpload {
+        id,
+        filename: new_filename,
+        mime,
+        original_name,
+        alt,
+        caption,
+        path: temp_filepath,
+    } = staged;
+
+    // VALIDATION: The whole file must decode within the size limits;
+    // JPEGs and PNGs come back without their metadata
+    let processed = match process_image(&temp_filepath, &upload_path_base, &id).await {
+        Ok(processed) => processed,
+        Err(err) => {
+            let _ = fs::remove_file(&temp_filepath).await;
+            return Err(err);
+        }
+    };
+    let variants = processed.variants;
+
+    // Record the upload. The row is only committed once the file is in
+    // place, and the files are removed again if the row can't be written.
+    let new_upload = repositories::uploads::NewUpload {
+        id: &id,
+        filename: &new_filename,
+        original_name: &original_name,
+        mime,
+        size_bytes: processed.size_bytes as i64,
+        width: Some(i64::from(processed.width)),
+        height: Some(i64::from(processed.height)),
+        alt: alt.as_deref(),
+        caption: caption.as_deref(),
+        uploaded_by: uploader,
+        variants: &variants,
+    };
+    let inserted = match pool.begin().await {
+        Ok(mut tx) => repositories::uploads::insert_upload(&mut tx, &new_upload)
+            .await
+            .map(|upload| (tx, upload)),
+        Err(e) => Err(e),
+    };
+    let (tx, upload) = match inserted {
+        Ok(inserted) => inserted,
+        Err(e) => {
+            tracing::error!("Failed to record upload {}: {}", new_filename, e);
+            let _ = fs::remove_file(&temp_filepath).await;
+            media::remove_variants(&upload_path_base, &variants);
+            return Err(internal_error_plain("Failed to save file"));
+        }
+    };
+
+    // Hand the files to the store; dropping `tx` on failure rolls the row
+    // back
+    let mut staged = vec![(new_filename.clone(), temp_filepath.clone(), mime)];
+    staged.extend(variants.iter().map(|variant| {
+        (
+            variant.filename.clone(),
+            upload_path_base.join(&variant.filename),
+            "image/webp",
+        )
+    }));
+    if let Err(e) = store_files(store, &staged).await {
+        tracing::error!("Failed to store upload {}: {}", new_filename, e);
+        return Err(internal_error_plain("Failed to save file"));
+    }
+
+    if let Err(e) = tx.commit().await {
+        tracing::error!("Failed to commit upload record {}: {}", new_filename, e);
+        for (key, _, _) in &staged {
+            let _ = store.delete(key).await;
+        }
+        return Err(internal_error_plain("Failed to save file"));
+    }
+
+    // SUCCESS path
+    tracing::info!("Successfully uploaded image: {}", new_filename);
+    let mut details = serde_json::json!({ "original_name": upload.original_name });
+    if let Some(source_url) = source_url {
+        details["source_url"] = serde_json::json!(source_url);
+    }
+    repositories::audit::append_entry(
+        pool,
+        uploader,
+        "upload",
+        "upload",
+        Some(&new_filename),
+        details,
+    )
+    .await;
+
+    Ok(UploadResponse {
+        // Return the public-facing URL
+        url: store.public_url(&new_filename),
+        upload: with_variant_urls(upload, store),
+    })
+}
+
+/// Handler for `GET /api/admin/uploads`: the media library, newest first.
+/// Admin-only.
+pub async fn list_uploads(
+    claims: auth::Claims,
+    State(pool): State<db::DbPool>,
+    State(store): State<Arc<dyn Store>>,
+    Query(params): Query<UploadListQuery>,
+) -> Result<Json<Paginated<Upload>>, AppError> {
+    ensure_admin(&claims)?;
+
+    let pattern = params
+        .q
+        .as_deref()
+        .map(str::trim)
+        .filter(|q| !q.is_empty())
+        .map(|q| format!("%{}%", escape_like_pattern(q)));
+    let limit = params
+        .limit
+        .unwrap_or(DEFAULT_LIST_LIMIT)
+        .clamp(1, MAX_LIST_LIMIT);
+    let offset = params.offset.unwrap_or(0).max(0);
+
+    let items = repositories::uploads::list_uploads(&pool, pattern.as_deref(), limit, offset)
+        .await
+        .map_err(internal_error("Failed to load uploads"))?;
+    let total = repositories::uploads::count_uploads(&pool, pattern.as_deref())
+        .await
+        .map_err(internal_error("Failed to load uploads"))?;
+
+    let items = items
+        .into_iter()
+        .map(|upload| with_variant_urls(upload, store.as_ref()))
+        .collect();
+    Ok(Json(Paginated::new(items, total, limit, offset)))
+}
+
+/// Handler for `DELETE /api/admin/uploads/{id}`. Removes the record and the
+/// file, or answers 409 listing the content that still references the
+/// file. Admin-only, protected by CSRF.
+pub async fn delete_upload(
+    claims: auth::Claims,
+    State(pool): State<db::DbPool>,
+    State(store): State<Arc<dyn Store>>,
+    UrlPath(id): UrlPath<String>,
+) -> Result<Response, AppError> {
+    ensure_admin(&claims)?;
+
+    let upload = repositories::uploads::get_upload(&pool, &id)
+        .await
+        .map_err(internal_error("Failed to load upload"))?
+        .ok_or_else(|| not_found("Upload not found"))?;
+
+    let references = repositories::uploads::find_references(&pool, &upload.id)
+        .await
+        .map_err(internal_error("Failed to check upload references"))?;
+    if !references.is_empty() {
+        return Ok((
+            StatusCode::CONFLICT,
+            Json(UploadInUseResponse {
+                error: "Upload is still referenced by content".to_string(),
+                code: "upload_in_use".to_string(),
+                references,
+            }),
+        )
+            .into_response());
+    }
+
+    // The record goes only if the files do
+    let upload = upload_cleanup::remove_upload(&pool, store.as_ref(), &id)
+        .await
+        .map_err(internal_error("Failed to delete upload"))?
+        .ok_or_else(|| not_found("Upload not found"))?;
+
+    tracing::info!(action = "delete_upload", user = %claims.sub, filename = %upload.filename, "Admin deleted upload");
+    repositories::audit::append_entry(
+        &pool,
+        &claims.sub,
+        "delete",
+        "upload",
+        Some(&upload.filename),
+        serde_json::json!({ "original_name": upload.original_name }),
+    )
+    .await;
+
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+/// Handler for `PUT /api/admin/uploads/{id}`: changes the alt text and
+/// caption of an upload. Admin-only, protected by CSRF.
+pub async fn update_upload(
+    claims: auth::Claims,
+    State(pool): State<db::DbPool>,
+    State(store): State<Arc<dyn Store>>,
+    UrlPath(id): UrlPath<String>,
+    Json(payload): Json<UpdateUploadRequest>,
+) -> Result<Json<Upload>, AppError> {
+    ensure_admin(&claims)?;
+
+    let upload = repositories::uploads::get_upload(&pool, &id)
+        .await
+        .map_err(internal_error("Failed to load upload"))?
+        .ok_or_else(|| not_found("Upload not found"))?;
+
+    let alt = match payload.alt.as_deref() {
+        Some(alt) => normalize_description(Some(alt), "alt", MAX_ALT_CHARS)?,
+        None => upload.alt,
+    };
+    let caption = match payload.caption.as_deref() {
+        Some(caption) => normalize_description(Some(caption), "caption", MAX_CAPTION_CHARS)?,
+        None => upload.caption,
+    };
+
+    let upload = repositories::uploads::update_upload_description(
+        &pool,
+        &id,
+        alt.as_deref(),
+        caption.as_deref(),
+    )
+    .await
+    .map_err(internal_error("Failed to update upload"))?
+    .ok_or_else(|| not_found("Upload not found"))?;
+
+    repositories::audit::append_entry(
+        &pool,
+        &claims.sub,
+        "update",
+        "upload",
+        Some(&upload.filename),
+        serde_json::json!({ "alt": upload.alt, "caption": upload.caption }),
+    )
+    .await;
+
+    Ok(Json(with_variant_urls(upload, store.as_ref())))
+}
+
+/// Handler for `POST /api/admin/uploads/cleanup`: reports the uploads and
+/// stored files no content references, and deletes them with
+/// `?dry_run=false`. Admin-only, protected by CSRF.
+pub async fn clean_up_uploads(
+    claims: auth::Claims,
+    State(pool): State<db::DbPool>,
+    State(store): State<Arc<dyn Store>>,
+    Query(params): Query<UploadCleanupQuery>,
+) -> Result<Json<CleanupReport>, AppError> {
+    ensure_admin(&claims)?;
+
+    let dry_run = params.dry_run.unwrap_or(true);
+    let report = upload_cleanup::clean_up(&pool, store.as_ref(), dry_run)
+        .await
+        .map_err(internal_error("Failed to clean up uploads"))?;
+
+    let removed_any = !(report.uploads.is_empty() && report.files.is_empty());
+    if !dry_run && removed_any {
+        tracing::info!(action = "cleanup_uploads", user = %claims.sub, uploads = report.uploads.len(), files = report.files.len(), "Admin removed orphaned uploads");
+        repositories::audit::append_entry(
+            &pool,
+            &claims.sub,
+            "cleanup",
+            "upload",
+            None,
+            serde_json::json!({
+                "uploads": report.uploads.iter().map(|upload| &upload.filename).collect::<Vec<_>>(),
+                "files": report.files.iter().map(|file| &file.key).collect::<Vec<_>>(),
+                "bytes": report.bytes,
+            }),
+        )
+        .await;
+    }
+    Ok(Json(report))
+}
+
+/// Handler for `GET /uploads/{key}`: streams a stored file with a
+/// year-long `Cache-Control` (set by the security headers middleware; file
+/// names are UUIDs, so a name never gets other content), an ETag for
+/// `If-None-Match` and single-range `Range` requests.
+///
+/// The Content-Type comes from the upload's record. Files uploaded before
+/// uploads were recorded have none and are served whole, typed by their
+/// extension.
+pub async fn serve_upload(
+    State(pool): State<db::DbPool>,
+    State(store): State<Arc<dyn Store>>,
+    UrlPath(key): UrlPath<String>,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    if !storage::is_valid_key(&key) {
+        return Err(not_found("Upload not found"));
+    }
+    let record = repositories::uploads::find_stored_file(&pool, &key)
+        .await
+        .map_err(internal_error("Failed to load upload"))?;
+    let (mime, size) = match record {
+        Some((mime, size_bytes)) => (mime, u64::try_from(size_bytes).ok()),
+        None => match content_type_for(&key) {
+            Some(mime) => (mime.to_string(), None),
+            None => return Err(not_found("Upload not found")),
+        },
+    };
+    let etag = format!("\"{key}\"");
+
+    if etag_matches(&headers, &etag) {
+        if size.is_none()
+            && !store
+                .exists(&key)
+                .await
+                .map_err(internal_error("Failed to load upload"))?
+        {
+            return Err(not_found("Upload not found"));
+        }
+        return Ok((StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response());
+    }
+
+    // `If-Range` with another validator asks for the whole file
+    let if_range_holds = headers
+        .get(header::IF_RANGE)
+        .is_none_or(|value| value.as_bytes() == etag.as_bytes());
+    let requested_range = headers
+        .get(header::RANGE)
+        .and_then(|value| value.to_str().ok())
+        .filter(|_| if_range_holds);
+    let range = match (requested_range, size) {
+        (Some(spec), Some(size)) => match parse_range(spec, size) {
+            RangeOutcome::Whole => None,
+            RangeOutcome::Partial(range) => Some(range),
+            RangeOutcome::Unsatisfiable => {
+                return Ok((
+                    StatusCode::RANGE_NOT_SATISFIABLE,
+                    [(header::CONTENT_RANGE, format!("bytes */{size}"))],
+                )
+                    .into_response());
             }
+        },
+        _ => None,
+    };
 
-            // SUCCESS path
-            tracing::info!("Successfully uploaded image: {}", filepath.display());
+    let object = store
+        .get(&key, range.clone())
+        .await
+        .map_err(internal_error("Failed to load upload"))?
+        .ok_or_else(|| not_found("Upload not found"))?;
 
-            return Ok(Json(UploadResponse {
-                // Return the public-facing URL
-                url: format!("/uploads/{}", new_filename),
-            }));
+    let mut response = Response::new(object.body);
+    let response_headers = response.headers_mut();
+    if let Ok(value) = HeaderValue::from_str(&mime) {
+        response_headers.insert(header::CONTENT_TYPE, value);
+    }
+    response_headers.insert(
+        header::CONTENT_LENGTH,
+        HeaderValue::from(object.content_length),
+    );
+    response_headers.insert(
+        header::ETAG,
+        HeaderValue::from_str(&etag).expect("key is ASCII"),
+    );
+    if let Some(size) = size {
+        response_headers.insert(header::ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+        if let Some(range) = range {
+            let content_range = format!("bytes {}-{}/{size}", range.start(), range.end());
+            response_headers.insert(
+                header::CONTENT_RANGE,
+                HeaderValue::from_str(&content_range).expect("content range is ASCII"),
+            );
+            *response.status_mut() = StatusCode::PARTIAL_CONTENT;
         }
     }
+    Ok(response)
+}
 
-    // Default error if for some reason the "file" field was missing
-    Err(bad_request("No file found in request"))
+/// Whether `If-None-Match` lists `etag` (weakly compared) or is `*`.
+fn etag_matches(headers: &HeaderMap, etag: &str) -> bool {
+    headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|candidate| candidate.trim())
+        .any(|candidate| candidate == "*" || candidate.trim_start_matches("W/") == etag)
+}
+
+/// How to answer a `Range` header.
+#[derive(Debug, PartialEq)]
+enum RangeOutcome {
+    /// No usable range: malformed, several ranges or a unit other than
+    /// bytes. The whole file is sent, as the header may be ignored.
+    Whole,
+    Partial(RangeInclusive<u64>),
+    Unsatisfiable,
+}
+
+/// Resolves a single `bytes=` range against a file of `size` bytes.
+fn parse_range(spec: &str, size: u64) -> RangeOutcome {
+    let Some(spec) = spec.trim().strip_prefix("bytes=") else {
+        return RangeOutcome::Whole;
+    };
+    if spec.contains(',') {
+        return RangeOutcome::Whole;
+    }
+    let Some((start, end)) = spec.trim().split_once('-') else {
+        return RangeOutcome::Whole;
+    };
+    let parse = |value: &str| value.trim().parse::<u64>().ok();
+
+    match (start.trim().is_empty(), end.trim().is_empty()) {
+        // `bytes=-500`: the last 500 bytes
+        (true, false) => match parse(end) {
+            Some(0) => RangeOutcome::Unsatisfiable,
+            Some(_) if size == 0 => RangeOutcome::Unsatisfiable,
+            Some(suffix) => RangeOutcome::Partial(size.saturating_sub(suffix)..=size - 1),
+            None => RangeOutcome::Whole,
+        },
+        // `bytes=500-` or `bytes=500-999`
+        (false, open_ended) => {
+            let Some(start) = parse(start) else {
+                return RangeOutcome::Whole;
+            };
+            let end = if open_ended {
+                u64::MAX
+            } else {
+                match parse(end) {
+                    Some(end) if end >= start => end,
+                    _ => return RangeOutcome::Whole,
+                }
+            };
+            if start >= size {
+                RangeOutcome::Unsatisfiable
+            } else {
+                RangeOutcome::Partial(start..=end.min(size - 1))
+            }
+        }
+        (true, true) => RangeOutcome::Whole,
+    }
+}
+
+/// Content type of an upload from its extension.
+fn content_type_for(key: &str) -> Option<&'static str> {
+    match Path::new(key).extension()?.to_str()? {
+        "jpg" | "jpeg" => Some("image/jpeg"),
+        "png" => Some("image/png"),
+        "gif" => Some("image/gif"),
+        "webp" => Some("image/webp"),
+        _ => None,
+    }
+}
+
+/// Hands each staged `(key, path, content type)` to `store`. On failure,
+/// the files already stored are deleted again and the rest of the staged
+/// files removed.
+async fn store_files(
+    store: &dyn Store,
+    staged: &[(String, PathBuf, &str)],
+) -> Result<(), StoreError> {
+    for (index, (key, path, content_type)) in staged.iter().enumerate() {
+        if let Err(e) = store.put(key, path, content_type).await {
+            for (stored_key, _, _) in &staged[..index] {
+                let _ = store.delete(stored_key).await;
+            }
+            for (_, staged_path, _) in &staged[index + 1..] {
+                let _ = fs::remove_file(staged_path).await;
+            }
+            return Err(e);
+        }
+    }
+    Ok(())
+}
+
+/// `upload` with the public URLs of its variants filled in.
+fn with_variant_urls(mut upload: Upload, store: &dyn Store) -> Upload {
+    for variant in &mut upload.variants {
+        variant.url = store.public_url(&variant.filename);
+    }
+    upload
+}
+
+/// Runs [`media::process_upload`] on the upload at `source` off the async
+/// runtime. Images that don't decode or are too large are a 400.
+async fn process_image(source: &Path, dir: &Path, id: &str) -> Result<ProcessedUpload, AppError> {
+    let config = crate::config::get();
+    let (source, dir, id) = (source.to_path_buf(), dir.to_path_buf(), id.to_string());
+    let processed = tokio::task::spawn_blocking(move || {
+        media::process_upload(
+            &source,
+            &dir,
+            &id,
+            config.upload_max_dimension,
+            &config.upload_variant_widths,
+        )
+    })
+    .await;
+
+    match processed {
+        Ok(Ok(processed)) => Ok(processed),
+        Ok(Err(UploadImageError::Io(e))) => {
+            tracing::error!("Failed to process upload: {}", e);
+            Err(internal_error_plain("Failed to save file"))
+        }
+        Ok(Err(e)) => Err(bad_request(e.to_string())),
+        Err(e) => {
+            tracing::error!("Upload processing task failed: {}", e);
+            Err(internal_error_plain("Failed to save file"))
+        }
+    }
+}
+
+/// The client's file name without any directory part or control
+/// characters, cut to a length that fits any listing.
+fn sanitize_original_name(file_name: &str) -> String {
+    let base = file_name.rsplit(['/', '\\']).next().unwrap_or_default();
+    let cleaned: String = base
+        .chars()
+        .filter(|c| !c.is_control())
+        .take(MAX_ORIGINAL_NAME_CHARS)
+        .collect();
+    let cleaned = cleaned.trim();
+    if cleaned.is_empty() {
+        "unknown".to_string()
+    } else {
+        cleaned.to_string()
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::is_own_temp_upload;
+    use super::{is_own_temp_upload, parse_range, sanitize_original_name, RangeOutcome};
     use std::path::Path;
 
     #[test]
@@ -298,4 +1172,48 @@ mod tests {
             "550e8400-e29b-41d4-a716-446655440000.png"
         )));
     }
+
+    #[test]
+    fn original_names_lose_directories_and_control_characters() {
+        assert_eq!(sanitize_original_name("C:\\Users\\me\\cat.png"), "cat.png");
+        assert_eq!(sanitize_original_name("../../etc/cat\u{0}.png"), "cat.png");
+        assert_eq!(sanitize_original_name("  "), "unknown");
+        assert_eq!(sanitize_original_name(&"a".repeat(300)).len(), 255);
+    }
+
+    #[test]
+    fn single_byte_ranges_are_resolved_against_the_size() {
+        assert_eq!(
+            parse_range("bytes=0-99", 1000),
+            RangeOutcome::Partial(0..=99)
+        );
+        assert_eq!(
+            parse_range("bytes=900-", 1000),
+            RangeOutcome::Partial(900..=999)
+        );
+        assert_eq!(
+            parse_range("bytes=900-5000", 1000),
+            RangeOutcome::Partial(900..=999)
+        );
+        assert_eq!(
+            parse_range("bytes=-100", 1000),
+            RangeOutcome::Partial(900..=999)
+        );
+        assert_eq!(
+            parse_range("bytes=-5000", 1000),
+            RangeOutcome::Partial(0..=999)
+        );
+
+        assert_eq!(
+            parse_range("bytes=1000-", 1000),
+            RangeOutcome::Unsatisfiable
+        );
+        assert_eq!(parse_range("bytes=-0", 1000), RangeOutcome::Unsatisfiable);
+        assert_eq!(parse_range("bytes=0-", 0), RangeOutcome::Unsatisfiable);
+
+        assert_eq!(parse_range("bytes=0-1,5-9", 1000), RangeOutcome::Whole);
+        assert_eq!(parse_range("bytes=9-5", 1000), RangeOutcome::Whole);
+        assert_eq!(parse_range("items=0-9", 1000), RangeOutcome::Whole);
+        assert_eq!(parse_range("bytes=abc", 1000), RangeOutcome::Whole);
+    }
 }
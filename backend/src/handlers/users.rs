@@ -0,0 +1,309 @@
+//! Admin User Management Handlers
+//!
+//! CRUD over the `users` table for administrators. Password hashes never
+//! leave the server, and every write keeps at least one admin account: the
+//! last admin can be neither demoted nor deleted. Writes require sudo mode
+//! ([`auth::RequireSudo`]).
+//!
+//! Changing an account's role or password, or deleting it, revokes every
+//! session it holds: tokens carry the role they were issued with, so a
+//! session left open would keep the old permissions.
+
+use crate::{
+    db::DbPool,
+    handlers::{
+        auth::{validate_password, validate_username},
+        common::{map_sqlx_error, require_permission},
+    },
+    models::*,
+    repositories::{self, users::AdminGuarded},
+    security::auth::{self, Permission},
+};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+
+const LAST_ADMIN_MESSAGE: &str = "The last remaining admin account cannot be demoted or deleted";
+
+fn validate_role(role: &str) -> Result<(), AppError> {
+    if USER_ROLES.contains(&role) {
+        Ok(())
+    } else {
+        Err(bad_request(format!(
+            "Unknown role '{role}'. Allowed: {}",
+            USER_ROLES.join(", ")
+        )))
+    }
+}
+
+fn hash_password(password: &str) -> Result<String, AppError> {
+    validate_password(password).map_err(bad_request)?;
+    bcrypt::hash(password, crate::config::get().bcrypt_cost)
+        .map_err(internal_error("Failed to hash password"))
+}
+
+fn resolve_guard<T>(outcome: AdminGuarded<T>) -> Result<T, AppError> {
+    match outcome {
+        AdminGuarded::Applied(value) => Ok(value),
+        AdminGuarded::NotFound => Err(not_found("User not found")),
+        AdminGuarded::LastAdmin => Err(conflict(LAST_ADMIN_MESSAGE)),
+    }
+}
+
+/// Handler for `GET /api/admin/users`.
+/// Admin-only.
+pub async fn list_users(
+    claims: auth::Claims,
+    State(pool): State<DbPool>,
+) -> Result<Json<AdminUserListResponse>, AppError> {
+    require_permission(&claims, Permission::ManageUsers)?;
+
+    let users = repositories::users::list_users(&pool)
+        .await
+        .map_err(|err| map_sqlx_error(err, "User"))?;
+
+    Ok(Json(AdminUserListResponse {
+        items: users.into_iter().map(AdminUserResponse::from).collect(),
+    }))
+}
+
+/// Handler for `POST /api/admin/users`.
+/// Admin-only, protected by CSRF, in sudo mode.
+pub async fn create_user(
+    auth::RequireSudo(claims): auth::RequireSudo,
+    State(pool): State<DbPool>,
+    Json(payload): Json<CreateUserRequest>,
+) -> Result<(StatusCode, Json<AdminUserResponse>), AppError> {
+    require_permission(&claims, Permission::ManageUsers)?;
+
+    let username = payload.username.trim();
+    validate_username(username).map_err(bad_request)?;
+    let role = payload.role.trim();
+    validate_role(role)?;
+    let password_hash = hash_password(&payload.password)?;
+
+    let user = repositories::users::create_user(&pool, username, &password_hash, role)
+        .await
+        .map_err(|err| match err {
+            sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
+                conflict("Username is already taken")
+            }
+            other => map_sqlx_error(other, "User"),
+        })?;
+
+    tracing::info!(
+        action = "create_user",
+        user = %claims.sub,
+        target = %user.username,
+        role = %user.role,
+        "Admin created user"
+    );
+
+    Ok((StatusCode::CREATED, Json(user.into())))
+}
+
+/// Handler for `PUT /api/admin/users/{id}`: change the role and/or reset
+/// the password. Admin-only, protected by CSRF, in sudo mode.
+pub async fn update_user(
+    auth::RequireSudo(claims): auth::RequireSudo,
+    State(pool): State<DbPool>,
+    Path(id): Path<i64>,
+    Json(payload): Json<UpdateUserRequest>,
+) -> Result<Json<AdminUserResponse>, AppError> {
+    require_permission(&claims, Permission::ManageUsers)?;
+
+    let role = payload.role.as_deref().map(str::trim);
+    if let Some(role) = role {
+        validate_role(role)?;
+    }
+    let password_hash = payload.password.as_deref().map(hash_password).transpose()?;
+    if role.is_none() && password_hash.is_none() {
+        return Err(bad_request(
+            "Nothing to update: provide a role or a password",
+        ));
+    }
+
+    let outcome = repositories::users::update_user(&pool, id, role, password_hash.as_deref())
+        .await
+        .map_err(|err| map_sqlx_error(err, "User"))?;
+    let user = resolve_guard(outcome)?;
+
+    tracing::info!(
+        action = "update_user",
+        user = %claims.sub,
+        target = %user.username,
+        role = %user.role,
+        password_reset = password_hash.is_some(),
+        "Admin updated user"
+    );
+
+    Ok(Json(user.into()))
+}
+
+/// Handler for `DELETE /api/admin/users/{id}`.
+/// Admin-only, protected by CSRF, in sudo mode. Admins cannot delete their
+/// own account.
+pub async fn delete_user(
+    auth::RequireSudo(claims): auth::RequireSudo,
+    State(pool): State<DbPool>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, AppError> {
+    require_permission(&claims, Permission::ManageUsers)?;
+
+    let target = repositories::users::get_user_by_id(&pool, id)
+        .await
+        .map_err(|err| map_sqlx_error(err, "User"))?
+        .ok_or_else(|| not_found("User not found"))?;
+    if target.username == claims.sub {
+        return Err(bad_request("You cannot delete your own account"));
+    }
+
+    let outcome = repositories::users::delete_user(&pool, id)
+        .await
+        .map_err(|err| map_sqlx_error(err, "User"))?;
+    resolve_guard(outcome)?;
+
+    tracing::info!(
+        action = "delete_user",
+        user = %claims.sub,
+        target = %target.username,
+        "Admin deleted user"
+    );
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn admin(name: &str) -> auth::Claims {
+        crate::test_support::claims_for(name, "admin")
+    }
+
+    /// Handlers behind [`auth::RequireSudo`] take the claims it unwraps.
+    fn sudo_admin(name: &str) -> auth::RequireSudo {
+        auth::RequireSudo(admin(name))
+    }
+
+    async fn insert(pool: &DbPool, username: &str, role: &str) -> i64 {
+        repositories::users::create_user(pool, username, "not-a-real-hash", role)
+            .await
+            .expect("insert user")
+            .id
+    }
+
+    #[tokio::test]
+    async fn last_admin_cannot_be_demoted_or_deleted() {
+        let pool = crate::test_support::migrated_pool().await;
+        let root = insert(&pool, "root", "admin").await;
+        let other = insert(&pool, "other", "admin").await;
+
+        // Two admins: one may be demoted.
+        let Json(demoted) = update_user(
+            sudo_admin("root"),
+            State(pool.clone()),
+            Path(other),
+            Json(UpdateUserRequest {
+                role: Some("user".to_string()),
+                password: None,
+            }),
+        )
+        .await
+        .expect("demote second admin");
+        assert_eq!(demoted.role, "user");
+
+        // Now `root` is the only admin.
+        let status = update_user(
+            sudo_admin("other"),
+            State(pool.clone()),
+            Path(root),
+            Json(UpdateUserRequest {
+                role: Some("user".to_string()),
+                password: None,
+            }),
+        )
+        .await
+        .expect_err("last admin demotion")
+        .status();
+        assert_eq!(status, StatusCode::CONFLICT);
+
+        let status = delete_user(sudo_admin("other"), State(pool.clone()), Path(root))
+            .await
+            .expect_err("last admin deletion")
+            .status();
+        assert_eq!(status, StatusCode::CONFLICT);
+
+        // A non-admin account can still be deleted.
+        let status = delete_user(sudo_admin("root"), State(pool.clone()), Path(other))
+            .await
+            .expect("delete regular user");
+        assert_eq!(status, StatusCode::NO_CONTENT);
+
+        let Json(list) = list_users(admin("root"), State(pool)).await.unwrap();
+        let names: Vec<_> = list.items.iter().map(|u| u.username.as_str()).collect();
+        assert_eq!(names, ["root"]);
+    }
+
+    #[tokio::test]
+    async fn create_validates_input_and_rejects_duplicates() {
+        let pool = crate::test_support::migrated_pool().await;
+        let request = |username: &str, password: &str, role: &str| {
+            Json(CreateUserRequest {
+                username: username.to_string(),
+                password: password.to_string(),
+                role: role.to_string(),
+            })
+        };
+
+        let status = create_user(
+            sudo_admin("root"),
+            State(pool.clone()),
+            request("writer", "short", "user"),
+        )
+        .await
+        .expect_err("weak password")
+        .status();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let status = create_user(
+            sudo_admin("root"),
+            State(pool.clone()),
+            request("writer", "a sufficiently long password", "owner"),
+        )
+        .await
+        .expect_err("unknown role")
+        .status();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (status, Json(created)) = create_user(
+            sudo_admin("root"),
+            State(pool.clone()),
+            request("writer", "a sufficiently long password", "user"),
+        )
+        .await
+        .expect("create user");
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(created.username, "writer");
+
+        let status = create_user(
+            sudo_admin("root"),
+            State(pool.clone()),
+            request("writer", "a sufficiently long password", "user"),
+        )
+        .await
+        .expect_err("duplicate username")
+        .status();
+        assert_eq!(status, StatusCode::CONFLICT);
+
+        let mut editor = admin("writer");
+        editor.role = "user".to_string();
+        let status = list_users(editor, State(pool))
+            .await
+            .expect_err("non-admin")
+            .status();
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+}
@@ -0,0 +1,143 @@
+//! Crawler and security contact files.
+//!
+//! `/robots.txt` and `/.well-known/security.txt` are served from the `robots`
+//! and `security_txt` site content sections so they can be edited from the
+//! admin UI like any other section. `/sitemap.xml` and its parts are
+//! generated from the published content, see [`crate::sitemap`]. All are
+//! registered ahead of the SPA fallback, which would otherwise answer with
+//! `index.html`.
+
+use crate::{
+    config, db,
+    handlers::{common::request_origin, site_content::plain_text_body},
+    repositories, sitemap,
+};
+use axum::{
+    extract::{Path, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+
+const TEXT_PLAIN: &str = "text/plain; charset=utf-8";
+const APPLICATION_XML: &str = "application/xml; charset=utf-8";
+
+/// Serves `/robots.txt`, falling back to an allow-all policy that points
+/// crawlers at the sitemap of `PUBLIC_BASE_URL`, or of the requested host
+/// while that is unset.
+pub async fn robots_txt(State(pool): State<db::DbPool>, headers: HeaderMap) -> Response {
+    let stored = match repositories::content::fetch_site_content_by_section(&pool, "robots").await {
+        Ok(record) => record.as_ref().and_then(plain_text_body),
+        Err(err) => {
+            // Crawlers treat a 5xx robots.txt as "disallow everything", so
+            // a database hiccup degrades to the default policy instead.
+            tracing::error!("Failed to load robots section: {}", err);
+            None
+        }
+    };
+
+    let body = stored.unwrap_or_else(|| default_robots(&headers));
+    plain_text(StatusCode::OK, body)
+}
+
+/// Serves `/.well-known/security.txt`. There is no sensible default contact,
+/// so an unset section answers 404.
+pub async fn security_txt(State(pool): State<db::DbPool>) -> Response {
+    match repositories::content::fetch_site_content_by_section(&pool, "security_txt").await {
+        Ok(record) => match record.as_ref().and_then(plain_text_body) {
+            Some(body) => plain_text(StatusCode::OK, body),
+            None => plain_text(StatusCode::NOT_FOUND, String::new()),
+        },
+        Err(err) => {
+            tracing::error!("Failed to load security_txt section: {}", err);
+            plain_text(StatusCode::INTERNAL_SERVER_ERROR, String::new())
+        }
+    }
+}
+
+/// Serves `/sitemap.xml`: the whole sitemap, or the sitemap index once the
+/// content outgrows a single file. 404 while `PUBLIC_BASE_URL` is unset.
+pub async fn sitemap_xml(State(pool): State<db::DbPool>) -> Response {
+    match load_sitemap(&pool).await {
+        Ok(sitemap) => xml(StatusCode::OK, sitemap.root.clone()),
+        Err(status) => xml(status, String::new()),
+    }
+}
+
+/// Serves `/sitemaps/{n}.xml`, the n-th part listed in the sitemap index.
+pub async fn sitemap_part(State(pool): State<db::DbPool>, Path(file): Path<String>) -> Response {
+    let Some(number) = file
+        .strip_suffix(".xml")
+        .and_then(|number| number.parse::<usize>().ok())
+    else {
+        return xml(StatusCode::NOT_FOUND, String::new());
+    };
+
+    match load_sitemap(&pool).await {
+        Ok(sitemap) => match sitemap.part(number) {
+            Some(body) => xml(StatusCode::OK, body.to_string()),
+            None => xml(StatusCode::NOT_FOUND, String::new()),
+        },
+        Err(status) => xml(status, String::new()),
+    }
+}
+
+async fn load_sitemap(pool: &db::DbPool) -> Result<std::sync::Arc<sitemap::Sitemap>, StatusCode> {
+    let Some(base_url) = config::get().public_base_url.as_deref() else {
+        return Err(StatusCode::NOT_FOUND);
+    };
+    sitemap::cached(pool, base_url).await.map_err(|err| {
+        tracing::error!("Failed to build sitemap: {}", err);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })
+}
+
+fn xml(status: StatusCode, body: String) -> Response {
+    (status, [(header::CONTENT_TYPE, APPLICATION_XML)], body).into_response()
+}
+
+fn plain_text(status: StatusCode, mut body: String) -> Response {
+    if !body.is_empty() && !body.ends_with('\n') {
+        body.push('\n');
+    }
+    (status, [(header::CONTENT_TYPE, TEXT_PLAIN)], body).into_response()
+}
+
+fn default_robots(headers: &HeaderMap) -> String {
+    let mut body = String::from("User-agent: *\nAllow: /\n");
+    let origin = config::get()
+        .public_base_url
+        .clone()
+        .or_else(|| request_origin(headers));
+    if let Some(origin) = origin {
+        body.push_str(&format!("\nSitemap: {origin}/sitemap.xml\n"));
+    }
+    body
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    #[test]
+    fn default_robots_links_sitemap_for_request_host() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::HOST, HeaderValue::from_static("blog.example.com"));
+
+        let body = default_robots(&headers);
+        assert!(body.starts_with("User-agent: *\nAllow: /\n"));
+        assert!(body.contains("Sitemap: https://blog.example.com/sitemap.xml"));
+
+        headers.insert("x-forwarded-proto", HeaderValue::from_static("http"));
+        assert!(default_robots(&headers).contains("Sitemap: http://blog.example.com/sitemap.xml"));
+    }
+
+    #[test]
+    fn default_robots_omits_sitemap_for_bad_host() {
+        assert!(!default_robots(&HeaderMap::new()).contains("Sitemap"));
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::HOST, HeaderValue::from_static("evil.com/path?x"));
+        assert!(!default_robots(&headers).contains("Sitemap"));
+    }
+}
@@ -57,7 +57,7 @@
 //! ## CSRF Protection
 //! - HMAC-SHA256 signed tokens
 //! - Per-user token binding
-//! - Short token TTL (6 hours)
+//! - Token TTL tied to the session lifetime
 //! - Double-submit cookie pattern
 //!
 //! ## Data Security
@@ -79,10 +79,17 @@
 //! The backend requires several environment variables for proper operation:
 //! - `DATABASE_URL`: SQLite database connection string
 //! - `JWT_SECRET`: Secret key for JWT token signing
+//! - `JWT_PRIVATE_KEY_PEM` / `JWT_PUBLIC_KEY_PEM`: RSA or Ed25519 keypair
+//!   that replaces `JWT_SECRET` for signing (optional)
 //! - `CSRF_SECRET`: Secret key for CSRF token signing
+//! - `LOGIN_ATTEMPT_SALT`: Salt for hashing login attempt identifiers
 //! - `ADMIN_USERNAME`: Initial admin user (optional)
 //! - `ADMIN_PASSWORD`: Initial admin password (optional)
 //!
+//! All of them except the admin seed are resolved and validated by
+//! [`config::Config::from_env`]. Run the server with `--check-config` to
+//! print the resolved values (secrets redacted) and every problem found.
+//!
 //! # Usage
 //!
 //! This library can be used as:
@@ -104,10 +111,29 @@
 //! ```
 
 // Core application modules
+pub mod backup; // Database backups with VACUUM INTO
+pub mod cache; // In-process caches for hot public reads
+pub mod config; // Environment configuration
+pub mod content_transfer; // Content bundle export and import
 pub mod db; // Database operations and migrations
 pub mod handlers; // HTTP request handlers
+pub mod maintenance; // Periodic pruning of expired rows
+pub mod markdown; // Markdown content analysis
+pub mod media; // Resized variants of uploaded images
 pub mod middleware; // HTTP middleware
 pub mod models; // Data structures and API models
+pub mod notifications; // Webhook notifications for new comments
 pub mod repositories; // Database repositories
 pub mod routes; // Route definitions
+pub mod schemas; // JSON Schemas for free-form content blobs
+pub mod search_log; // Logging of public search queries
 pub mod security; // Authentication, authorization, and CSRF protection
+pub mod sitemap; // Cached sitemap.xml of the published content
+pub mod state; // Shared application state
+pub mod storage; // Where uploaded files are kept
+pub mod upload_cleanup; // Removal of uploads no content references
+pub mod views; // Deduplicated view counting
+pub mod warmup; // Optional boot-time cache warmup
+
+#[cfg(test)]
+mod test_support; // Fixtures shared by the unit tests
@@ -1,9 +1,12 @@
 // The binary is a thin wrapper around the library crate (lib.rs). Declaring
 // the modules here a second time would compile the whole tree twice and make
 // `main.rs` types distinct from the library's types.
-use minos_backend::{db, handlers, routes, security};
+use minos_backend::state::AppState;
+use minos_backend::{config, db, handlers, maintenance, routes, security, storage, warmup};
 
-use minos_backend::middleware::{cors, security as security_middleware};
+use minos_backend::middleware::{
+    cors, maintenance as maintenance_middleware, security as security_middleware,
+};
 
 // HTTP-related imports for building the web server
 use axum::{extract::DefaultBodyLimit, routing::get, Router};
@@ -13,6 +16,8 @@ use dotenvy::dotenv;
 use std::env;
 use std::io::ErrorKind;
 use std::net::SocketAddr;
+use std::process::ExitCode;
+use std::time::Duration;
 use tokio::signal;
 use tower_http::cors::CorsLayer;
 
@@ -23,30 +28,79 @@ use axum::http::{
 };
 
 /// Main application entry point.
+///
+/// `--check-config` validates the environment, prints the resolved settings
+/// with secrets redacted, and exits without starting the server.
 #[tokio::main]
-async fn main() {
+async fn main() -> ExitCode {
     // Load environment variables from .env file (if present)
     dotenv().ok();
 
+    if env::args().skip(1).any(|arg| arg == "--check-config") {
+        return check_config();
+    }
+
     // Initialize structured logging
     tracing_subscriber::fmt::init();
 
-    security::auth::init_jwt_secret().expect("Failed to initialize JWT secret");
-    tracing::info!("JWT secret initialized successfully");
+    let config = match config::Config::from_env() {
+        Ok(config) => config::init(config),
+        Err(problems) => {
+            eprintln!("Invalid configuration:");
+            for problem in &problems {
+                eprintln!("  - {problem}");
+            }
+            return ExitCode::FAILURE;
+        }
+    };
+    for note in &config.notes {
+        tracing::warn!("{}", note);
+    }
+
+    match (&config.jwt_private_key_pem, &config.jwt_public_key_pem) {
+        (Some(private), Some(public)) => {
+            let algorithm = security::auth::init_jwt_keypair(private, public)
+                .expect("Failed to initialize JWT keypair");
+            tracing::info!("JWT keypair initialized successfully ({:?})", algorithm);
+        }
+        _ => {
+            security::auth::init_jwt_secret(&config.jwt_secret)
+                .expect("Failed to initialize JWT secret");
+            tracing::info!("JWT secret initialized successfully");
+        }
+    }
 
-    security::csrf::init_csrf_secret().expect("Failed to initialize CSRF secret");
+    security::csrf::init_csrf_secret(&config.csrf_secret)
+        .expect("Failed to initialize CSRF secret");
     tracing::info!("CSRF secret initialized successfully");
 
-    handlers::auth::init_login_attempt_salt().expect("Failed to initialize login attempt salt");
+    handlers::auth::init_login_attempt_salt(&config.login_attempt_salt)
+        .expect("Failed to initialize login attempt salt");
     tracing::info!("Login attempt salt initialized successfully");
 
+    match &config.totp_encryption_key {
+        Some(key) => security::totp::init_totp_encryption_key(key)
+            .expect("Failed to initialize TOTP encryption key"),
+        None => tracing::info!("TOTP_ENCRYPTION_KEY not set; two-factor enrollment is disabled"),
+    }
+
     let pool = db::create_pool()
         .await
         .expect("Failed to create database pool");
 
-    // Ensure uploads directory exists
-    let upload_dir = env::var("UPLOAD_DIR").unwrap_or_else(|_| "uploads".to_string());
-    if !std::path::Path::new(&upload_dir).exists() {
+    if config.warmup {
+        warmup::run(&pool).await;
+    }
+
+    maintenance::spawn(
+        pool.clone(),
+        Duration::from_secs(u64::from(config.maintenance_interval_minutes) * 60),
+    );
+
+    // Ensure the uploads directory exists; uploads are staged here even
+    // when they are stored elsewhere
+    let upload_dir = config.upload_dir.to_string_lossy().into_owned();
+    if !config.upload_dir.exists() {
         tokio::fs::create_dir_all(&upload_dir)
             .await
             .expect("Failed to create uploads directory");
@@ -57,27 +111,7 @@ async fn main() {
     handlers::upload::cleanup_stale_temp_files(&upload_dir).await;
 
     // Configure CORS (Cross-Origin Resource Sharing)
-    let cors_origins = match env::var("CORS_ALLOWED_ORIGINS") {
-        Ok(val) => Some(val),
-        Err(_) => match env::var("FRONTEND_ORIGINS") {
-            Ok(val) => {
-                tracing::warn!("FRONTEND_ORIGINS is deprecated. Use CORS_ALLOWED_ORIGINS instead.");
-                Some(val)
-            }
-            Err(_) => None,
-        },
-    }
-    .map(|val| {
-        val.split(',')
-            .map(|s| s.trim().to_string())
-            .collect::<Vec<_>>()
-    })
-    .unwrap_or_else(|| {
-        cors::DEV_DEFAULT_FRONTEND_ORIGINS
-            .iter()
-            .map(|&s| s.to_string())
-            .collect()
-    });
+    let cors_origins = &config.cors_allowed_origins;
 
     let allowed_origins = cors::parse_allowed_origins(cors_origins.iter().map(|s| s.as_str()));
 
@@ -90,6 +124,7 @@ async fn main() {
             Method::GET,
             Method::POST,
             Method::PUT,
+            Method::PATCH,
             Method::DELETE,
             Method::OPTIONS,
         ])
@@ -114,21 +149,30 @@ async fn main() {
     }
 
     // Create routes
-    let app_routes = routes::create_routes(pool.clone(), upload_dir);
+    let state = AppState {
+        pool: pool.clone(),
+        store: storage::from_config(config),
+    };
+    let app_routes = routes::create_routes(pool.clone());
 
     // Define the application router with all routes and middleware
     let app = Router::new()
         .merge(app_routes)
         .route("/api/health", get(|| async { "OK" }))
+        .route("/api/health/ready", get(handlers::health::ready))
         // Serve index.html with server-side injection for root and fallback
         .route("/", get(handlers::frontend_proxy::serve_index))
         .route("/{*path}", get(handlers::frontend_proxy::serve_index))
+        .layer(axum::middleware::from_fn_with_state(
+            pool.clone(),
+            maintenance_middleware::maintenance_mode,
+        ))
         .layer(axum::middleware::from_fn(
             security_middleware::security_headers,
         ))
         .layer(cors_layer)
-        .layer(DefaultBodyLimit::max(11 * 1024 * 1024)) // 10MB uploads plus multipart overhead
-        .with_state(pool.clone());
+        .layer(DefaultBodyLimit::max(routes::DEFAULT_BODY_LIMIT))
+        .with_state(state);
 
     // Apply trusted proxy middleware if configured
     let app = if trust_proxy_ip_headers {
@@ -138,17 +182,7 @@ async fn main() {
             security_middleware::strip_untrusted_forwarded_headers,
         ))
     };
-    let port_str = env::var("PORT").unwrap_or_else(|_| "8489".to_string());
-    let port: u16 = match port_str.parse() {
-        Ok(port) => port,
-        Err(e) => {
-            panic!(
-                "Invalid PORT '{}': {}. Please set PORT to a valid u16 value.",
-                port_str, e
-            );
-        }
-    };
-
+    let port = config.port;
     if port < 1024 {
         tracing::warn!(
             "PORT {} is in privileged range (< 1024). May require elevated permissions.",
@@ -182,6 +216,29 @@ async fn main() {
     }
 
     tracing::info!("Server shutdown complete");
+    ExitCode::SUCCESS
+}
+
+/// Implements `--check-config`: reports every problem at once, or the
+/// resolved settings when there are none.
+fn check_config() -> ExitCode {
+    match config::Config::from_env() {
+        Ok(config) => {
+            print!("{}", config.report());
+            for note in &config.notes {
+                println!("note: {note}");
+            }
+            println!("Configuration OK");
+            ExitCode::SUCCESS
+        }
+        Err(problems) => {
+            eprintln!("Configuration has {} problem(s):", problems.len());
+            for problem in &problems {
+                eprintln!("  - {problem}");
+            }
+            ExitCode::FAILURE
+        }
+    }
 }
 
 /// Waits for a shutdown signal and initiates graceful shutdown.
@@ -0,0 +1,162 @@
+//! Background Pruning
+//!
+//! Several tables gain rows on every login, logout or rejected request and
+//! never shrink on their own: the token blacklist, login attempt counters,
+//! sessions, two-factor challenges, daily security counters, the deletion
+//! log and the search log. Pages and posts left in the trash are deleted
+//! for good once they expire. [`spawn`] runs [`prune`] every
+//! `MAINTENANCE_INTERVAL_MINUTES`; admins can also trigger a run through
+//! `POST /api/admin/maintenance/prune`.
+
+use crate::{db::DbPool, repositories, security::rejections};
+use serde::Serialize;
+use std::time::Duration;
+use tokio::time::MissedTickBehavior;
+
+/// Rows removed by one [`prune`] run, per table. A step that failed is
+/// logged and reported as zero.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PruneReport {
+    pub blacklisted_tokens: u64,
+    pub login_attempts: u64,
+    pub sessions: u64,
+    pub two_factor_challenges: u64,
+    pub security_counters: u64,
+    pub deletion_log: u64,
+    pub search_log: u64,
+    pub trashed_pages: u64,
+    pub trashed_posts: u64,
+}
+
+/// Recorded as the deleting user when expired trash is purged.
+const TRASH_PURGE_ACTOR: &str = "trash-retention";
+
+fn removed(step: &str, result: Result<u64, sqlx::Error>) -> u64 {
+    result.unwrap_or_else(|e| {
+        tracing::error!("Failed to prune {}: {}", step, e);
+        0
+    })
+}
+
+/// Deletes expired and stale rows from every table that needs it.
+pub async fn prune(pool: &DbPool) -> PruneReport {
+    let config = crate::config::get();
+    let retention_days = i64::from(config.deletion_log_retention_days);
+    let trash_days = i64::from(config.trash_retention_days);
+    // Pages first: their posts go with them
+    let trashed_pages = removed(
+        "trashed pages",
+        repositories::pages::purge_trash_older_than(pool, trash_days, TRASH_PURGE_ACTOR).await,
+    );
+    let trashed_posts = removed(
+        "trashed posts",
+        repositories::posts::purge_trash_older_than(pool, trash_days, TRASH_PURGE_ACTOR).await,
+    );
+    let report = PruneReport {
+        blacklisted_tokens: removed(
+            "token blacklist",
+            repositories::token_blacklist::cleanup_expired(pool).await,
+        ),
+        login_attempts: removed(
+            "login attempts",
+            repositories::users::cleanup_stale_login_attempts(pool).await,
+        ),
+        sessions: removed(
+            "sessions",
+            repositories::sessions::cleanup_expired_sessions(pool).await,
+        ),
+        two_factor_challenges: removed(
+            "two-factor challenges",
+            repositories::two_factor::cleanup_expired_challenges(pool).await,
+        ),
+        security_counters: removed(
+            "security counters",
+            repositories::security_counters::prune_older_than(pool, rejections::SUMMARY_DAYS).await,
+        ),
+        deletion_log: removed(
+            "deletion log",
+            repositories::deletion_log::purge_older_than(pool, retention_days).await,
+        ),
+        search_log: removed(
+            "search log",
+            repositories::search_log::purge_older_than(
+                pool,
+                i64::from(config.search_log_retention_days),
+            )
+            .await,
+        ),
+        trashed_pages,
+        trashed_posts,
+    };
+
+    tracing::info!(
+        blacklisted_tokens = report.blacklisted_tokens,
+        login_attempts = report.login_attempts,
+        sessions = report.sessions,
+        two_factor_challenges = report.two_factor_challenges,
+        security_counters = report.security_counters,
+        deletion_log = report.deletion_log,
+        search_log = report.search_log,
+        trashed_pages = report.trashed_pages,
+        trashed_posts = report.trashed_posts,
+        "Pruned expired rows"
+    );
+    report
+}
+
+/// Starts the periodic task. The first run happens right away, so a
+/// restart also catches up on pruning.
+pub fn spawn(pool: DbPool, every: Duration) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(every);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            prune(&pool).await;
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn prune_removes_only_expired_rows() {
+        let pool = crate::test_support::migrated_pool().await;
+
+        let now = chrono::Utc::now().timestamp();
+        repositories::token_blacklist::blacklist_token(&pool, "expired.jwt", now - 60)
+            .await
+            .unwrap();
+        repositories::token_blacklist::blacklist_token(&pool, "live.jwt", now + 3600)
+            .await
+            .unwrap();
+        let long_ago = (chrono::Utc::now() - chrono::Duration::days(2)).to_rfc3339();
+        let recent = chrono::Utc::now().to_rfc3339();
+        for (key, last_attempt_at) in [("stale", &long_ago), ("fresh", &recent)] {
+            sqlx::query(
+                "INSERT INTO login_attempts (username, fail_count, last_attempt_at) VALUES (?, 2, ?)",
+            )
+            .bind(key)
+            .bind(last_attempt_at)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        let report = prune(&pool).await;
+        assert_eq!(report.blacklisted_tokens, 1);
+        assert_eq!(report.login_attempts, 1);
+        assert!(
+            repositories::token_blacklist::is_token_blacklisted(&pool, "live.jwt")
+                .await
+                .unwrap()
+        );
+        let remaining: Vec<String> = sqlx::query_scalar("SELECT username FROM login_attempts")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(remaining, ["fresh"]);
+    }
+}
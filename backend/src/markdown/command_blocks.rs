@@ -0,0 +1,254 @@
+//! Runnable command blocks.
+//!
+//! Tutorials mark copyable terminal sessions with a `shell-session` fence and
+//! an optional header between `---` lines:
+//!
+//! ````markdown
+//! ```shell-session
+//! ---
+//! description: Install the toolchain
+//! prompt: "$ "
+//! ---
+//! $ sudo apt install build-essential \
+//!     pkg-config
+//! Reading package lists... Done
+//! ```
+//! ````
+//!
+//! Lines starting with the prompt (and their `\` continuations) are the
+//! command; everything else is expected output. The header accepts only the
+//! `description` and `prompt` keys, as `key: value` lines with optionally
+//! quoted values.
+//!
+//! Extraction never fails a request. A block with a malformed header, no
+//! command or anything over the size caps is skipped and simply renders as
+//! ordinary code.
+
+use super::fenced_blocks;
+use crate::models::CommandBlock;
+
+/// Fence language that marks a command block.
+pub const COMMAND_BLOCK_LANGUAGE: &str = "shell-session";
+/// Most command blocks extracted from one tutorial.
+pub const MAX_COMMAND_BLOCKS: usize = 50;
+/// Longest accepted command, in bytes.
+pub const MAX_COMMAND_BYTES: usize = 4 * 1024;
+/// Longest accepted expected output, in bytes.
+pub const MAX_EXPECTED_OUTPUT_BYTES: usize = 8 * 1024;
+/// Longest accepted description, in characters.
+pub const MAX_DESCRIPTION_CHARS: usize = 300;
+
+/// Most lines between the header's `---` delimiters.
+const MAX_HEADER_LINES: usize = 8;
+/// Longest accepted custom prompt, in characters.
+const MAX_PROMPT_CHARS: usize = 16;
+const DEFAULT_PROMPT: &str = "$";
+const HEADER_DELIMITER: &str = "---";
+
+/// Extracts the command blocks of a tutorial's Markdown content.
+///
+/// [`CommandBlock::index`] counts every `shell-session` fence in the
+/// document, skipped ones included, so it keeps pointing at the right code
+/// block in the rendered page.
+pub fn extract_command_blocks(markdown: &str) -> Vec<CommandBlock> {
+    fenced_blocks(markdown)
+        .into_iter()
+        .filter(|block| block.language == COMMAND_BLOCK_LANGUAGE)
+        .enumerate()
+        .filter_map(|(index, block)| parse_block(index, &block.lines))
+        .take(MAX_COMMAND_BLOCKS)
+        .collect()
+}
+
+#[derive(Default)]
+struct Header {
+    description: Option<String>,
+    prompt: Option<String>,
+}
+
+fn parse_block(index: usize, lines: &[&str]) -> Option<CommandBlock> {
+    let (header, body) = split_header(lines)?;
+    let prompt = header.prompt.as_deref().unwrap_or(DEFAULT_PROMPT);
+
+    let mut commands: Vec<&str> = Vec::new();
+    let mut output: Vec<&str> = Vec::new();
+    let mut continues = false;
+    for line in body {
+        if continues {
+            commands.push(line);
+        } else if let Some(command) = strip_prompt(line, prompt) {
+            commands.push(command);
+        } else {
+            output.push(line);
+            continue;
+        }
+        continues = line.trim_end().ends_with('\\');
+    }
+
+    let command = commands.join("\n");
+    if command.trim().is_empty() || command.len() > MAX_COMMAND_BYTES {
+        return None;
+    }
+    let expected_output = output.join("\n").trim_end().to_string();
+    if expected_output.len() > MAX_EXPECTED_OUTPUT_BYTES {
+        return None;
+    }
+
+    Some(CommandBlock {
+        index,
+        command,
+        description: header.description,
+        expected_output: (!expected_output.trim().is_empty()).then_some(expected_output),
+    })
+}
+
+/// The prompt only counts when followed by a space or the end of the line,
+/// so output such as `$HOME is set` stays output.
+fn strip_prompt<'a>(line: &'a str, prompt: &str) -> Option<&'a str> {
+    let rest = line.strip_prefix(prompt)?;
+    rest.strip_prefix(' ').or(rest.is_empty().then_some(rest))
+}
+
+/// Splits the optional header off a block. Returns `None` when a header is
+/// opened but malformed.
+fn split_header<'a, 'b>(lines: &'b [&'a str]) -> Option<(Header, &'b [&'a str])> {
+    if lines.first().map(|line| line.trim()) != Some(HEADER_DELIMITER) {
+        return Some((Header::default(), lines));
+    }
+
+    let close = lines
+        .iter()
+        .skip(1)
+        .take(MAX_HEADER_LINES + 1)
+        .position(|line| line.trim() == HEADER_DELIMITER)?
+        + 1;
+
+    let mut header = Header::default();
+    for line in &lines[1..close] {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let (key, value) = line.split_once(':')?;
+        let value = unquote(value.trim())?;
+        let slot = match key.trim() {
+            "description" if value.chars().count() <= MAX_DESCRIPTION_CHARS => {
+                &mut header.description
+            }
+            "prompt" if !value.trim().is_empty() && value.chars().count() <= MAX_PROMPT_CHARS => {
+                &mut header.prompt
+            }
+            _ => return None,
+        };
+        if slot.replace(value.to_string()).is_some() {
+            return None;
+        }
+    }
+
+    // A trailing space in a quoted prompt is matched by the separator rule.
+    if let Some(prompt) = header.prompt.as_mut() {
+        prompt.truncate(prompt.trim_end().len());
+    }
+    header.description = header.description.filter(|d| !d.is_empty());
+
+    Some((header, &lines[close + 1..]))
+}
+
+/// Strips one pair of matching quotes. Returns `None` for an unbalanced
+/// quote or a control character.
+fn unquote(value: &str) -> Option<&str> {
+    let inner = match value.chars().next() {
+        Some(quote @ ('"' | '\'')) => value
+            .strip_prefix(quote)
+            .and_then(|rest| rest.strip_suffix(quote))?,
+        _ => value,
+    };
+    (!inner.chars().any(char::is_control)).then_some(inner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_header_commands_continuations_and_output() {
+        let markdown = concat!(
+            "# Setup\n\n",
+            "```shell-session\n",
+            "---\n",
+            "description: \"Install the toolchain\"\n",
+            "prompt: '# '\n",
+            "---\n",
+            "# apt install build-essential \\\n",
+            "    pkg-config\n",
+            "Reading package lists... Done\n",
+            "# make\n",
+            "```\n",
+        );
+
+        let blocks = extract_command_blocks(markdown);
+        assert_eq!(
+            blocks,
+            [CommandBlock {
+                index: 0,
+                command: "apt install build-essential \\\n    pkg-config\nmake".to_string(),
+                description: Some("Install the toolchain".to_string()),
+                expected_output: Some("Reading package lists... Done".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn other_languages_and_nested_examples_are_left_untouched() {
+        let markdown = concat!(
+            "```bash\n$ echo not a session\n```\n",
+            "````markdown\n```shell-session\n$ echo example\n```\n````\n",
+            "```shell-session\n$ echo real\n$HOME is real\n```\n",
+        );
+
+        let blocks = extract_command_blocks(markdown);
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].index, 0);
+        assert_eq!(blocks[0].command, "echo real");
+        assert_eq!(blocks[0].description, None);
+        assert_eq!(blocks[0].expected_output.as_deref(), Some("$HOME is real"));
+    }
+
+    #[test]
+    fn malformed_blocks_are_skipped_but_keep_their_index() {
+        let markdown = concat!(
+            "```shell-session\n---\ndescription: never closed\n$ ls\n```\n",
+            "```shell-session\n---\nauthor: me\n---\n$ ls\n```\n",
+            "```shell-session\n---\ndescription: \"unbalanced\n---\n$ ls\n```\n",
+            "```shell-session\njust output, no prompt\n```\n",
+            "```shell-session\n$ pwd\n```\n",
+        );
+
+        let blocks = extract_command_blocks(markdown);
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].index, 4);
+        assert_eq!(blocks[0].command, "pwd");
+        assert_eq!(blocks[0].expected_output, None);
+    }
+
+    #[test]
+    fn size_caps_are_enforced() {
+        let long_command = format!(
+            "```shell-session\n$ echo {}\n```\n",
+            "x".repeat(MAX_COMMAND_BYTES)
+        );
+        let long_description = format!(
+            "```shell-session\n---\ndescription: {}\n---\n$ ls\n```\n",
+            "d".repeat(MAX_DESCRIPTION_CHARS + 1)
+        );
+        let long_output = format!(
+            "```shell-session\n$ ls\n{}\n```\n",
+            "o".repeat(MAX_EXPECTED_OUTPUT_BYTES + 1)
+        );
+        for markdown in [long_command, long_description, long_output] {
+            assert!(extract_command_blocks(&markdown).is_empty());
+        }
+
+        let many = "```shell-session\n$ ls\n```\n".repeat(MAX_COMMAND_BLOCKS + 5);
+        assert_eq!(extract_command_blocks(&many).len(), MAX_COMMAND_BLOCKS);
+    }
+}
@@ -0,0 +1,193 @@
+//! YAML front matter for standalone Markdown documents.
+//!
+//! Exports only need flat metadata (strings, numbers, booleans and lists of
+//! strings), so rather than pulling in a YAML library this module writes and
+//! reads that subset directly. Written values are JSON literals, which are
+//! also valid YAML; when reading, hand-edited files may additionally use
+//! plain and single-quoted scalars, `[a, b]` flow lists and `- item` block
+//! lists. Anything else (nested maps, anchors, multi-line scalars) is
+//! rejected with a message naming the offending line.
+
+use serde_json::{Map, Value};
+
+const DELIMITER: &str = "---";
+
+/// Renders `fields` as a front matter block followed by `body`.
+pub fn render(fields: &[(&str, Value)], body: &str) -> String {
+    let mut document = String::with_capacity(body.len() + 512);
+    document.push_str(DELIMITER);
+    document.push('\n');
+    for (key, value) in fields {
+        document.push_str(key);
+        document.push_str(": ");
+        // JSON literals are valid YAML flow scalars and sequences
+        document.push_str(&value.to_string());
+        document.push('\n');
+    }
+    document.push_str(DELIMITER);
+    document.push_str("\n\n");
+    document.push_str(body.trim());
+    document.push('\n');
+    document
+}
+
+/// Splits `document` into its front matter fields and the Markdown after
+/// it. Fails when the document does not open with a front matter block.
+pub fn parse(document: &str) -> Result<(Map<String, Value>, &str), String> {
+    let document = document.strip_prefix('\u{feff}').unwrap_or(document);
+    let mut lines = document.split_inclusive('\n');
+    if lines.next().map(str::trim_end) != Some(DELIMITER) {
+        return Err("Document must start with a '---' front matter block".to_string());
+    }
+
+    let mut fields = Map::new();
+    let mut offset = document.split_inclusive('\n').next().map_or(0, str::len);
+    let mut open_list: Option<String> = None;
+    for (number, raw_line) in lines.enumerate() {
+        let line_number = number + 2;
+        offset += raw_line.len();
+        let line = raw_line.trim_end();
+        if line == DELIMITER || line == "..." {
+            return Ok((fields, &document[offset..]));
+        }
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+
+        if let Some(item) = line.trim_start().strip_prefix("- ") {
+            let Some(key) = &open_list else {
+                return Err(format!("Line {line_number}: list item without a key"));
+            };
+            let item = match parse_scalar(item.trim(), line_number)? {
+                Value::String(item) => item,
+                other => other.to_string(),
+            };
+            if let Some(Value::Array(items)) = fields.get_mut(key) {
+                items.push(Value::String(item));
+            }
+            continue;
+        }
+        if line.starts_with(char::is_whitespace) {
+            return Err(format!(
+                "Line {line_number}: nested values are not supported"
+            ));
+        }
+
+        let Some((key, value)) = line.split_once(':') else {
+            return Err(format!("Line {line_number}: expected 'key: value'"));
+        };
+        let key = key.trim();
+        if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(format!("Line {line_number}: invalid key '{key}'"));
+        }
+        if fields.contains_key(key) {
+            return Err(format!("Line {line_number}: duplicate key '{key}'"));
+        }
+
+        let value = value.trim();
+        open_list = None;
+        let value = if value.is_empty() {
+            // Either an empty value or the start of a block list
+            open_list = Some(key.to_string());
+            Value::Array(Vec::new())
+        } else {
+            parse_value(value, line_number)?
+        };
+        fields.insert(key.to_string(), value);
+    }
+
+    Err("Front matter block is not closed with '---'".to_string())
+}
+
+fn parse_value(value: &str, line_number: usize) -> Result<Value, String> {
+    let Some(inner) = value.strip_prefix('[') else {
+        return parse_scalar(value, line_number);
+    };
+    if let Ok(list) = serde_json::from_str::<Value>(value) {
+        return Ok(list);
+    }
+    let inner = inner
+        .strip_suffix(']')
+        .ok_or_else(|| format!("Line {line_number}: unterminated list"))?;
+    if inner.trim().is_empty() {
+        return Ok(Value::Array(Vec::new()));
+    }
+    inner
+        .split(',')
+        .map(|item| parse_scalar(item.trim(), line_number))
+        .collect::<Result<Vec<_>, _>>()
+        .map(Value::Array)
+}
+
+fn parse_scalar(value: &str, line_number: usize) -> Result<Value, String> {
+    if value.starts_with('"') {
+        return serde_json::from_str::<String>(value)
+            .map(Value::String)
+            .map_err(|_| format!("Line {line_number}: invalid double-quoted string"));
+    }
+    if let Some(inner) = value.strip_prefix('\'') {
+        let inner = inner
+            .strip_suffix('\'')
+            .ok_or_else(|| format!("Line {line_number}: unterminated single-quoted string"))?;
+        return Ok(Value::String(inner.replace("''", "'")));
+    }
+    if value.starts_with(['{', '&', '*', '|', '>', '!']) {
+        return Err(format!("Line {line_number}: unsupported YAML syntax"));
+    }
+    Ok(match value {
+        "true" => Value::Bool(true),
+        "false" => Value::Bool(false),
+        "null" | "~" => Value::Null,
+        _ => match value.parse::<i64>() {
+            Ok(number) => Value::from(number),
+            Err(_) => Value::String(value.to_string()),
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn rendered_documents_parse_back() {
+        let fields = [
+            ("title", json!("Quotes \" and: colons")),
+            ("topics", json!(["Linux", "Shell"])),
+            ("version", json!(3)),
+            ("allow_comments", json!(false)),
+        ];
+        let document = render(&fields, "# Heading\n\n---\n\nBody");
+
+        let (parsed, body) = parse(&document).unwrap();
+        assert_eq!(parsed["title"], "Quotes \" and: colons");
+        assert_eq!(parsed["topics"], json!(["Linux", "Shell"]));
+        assert_eq!(parsed["version"], 3);
+        assert_eq!(parsed["allow_comments"], false);
+        // A thematic break in the body is not a delimiter
+        assert_eq!(body.trim(), "# Heading\n\n---\n\nBody");
+    }
+
+    #[test]
+    fn hand_written_yaml_subset_is_accepted() {
+        let document = "---\n# exported by hand\ntitle: Plain title\ndescription: 'It''s quoted'\ntopics:\n  - Linux\n  - \"Shell\"\ntags: [a, b]\n...\nBody\n";
+
+        let (parsed, body) = parse(document).unwrap();
+        assert_eq!(parsed["title"], "Plain title");
+        assert_eq!(parsed["description"], "It's quoted");
+        assert_eq!(parsed["topics"], json!(["Linux", "Shell"]));
+        assert_eq!(parsed["tags"], json!(["a", "b"]));
+        assert_eq!(body, "Body\n");
+    }
+
+    #[test]
+    fn malformed_front_matter_is_rejected() {
+        assert!(parse("# No front matter").is_err());
+        assert!(parse("---\ntitle: Never closed\n").is_err());
+        assert!(parse("---\ntitle: a\ntitle: b\n---\n").is_err());
+        assert!(parse("---\nnested:\n  key: value\n---\n").is_err());
+        assert!(parse("---\nanchor: &a value\n---\n").is_err());
+        assert!(parse("---\n- orphan\n---\n").is_err());
+    }
+}
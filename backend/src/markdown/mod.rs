@@ -0,0 +1,148 @@
+//! Markdown Content Analysis
+//!
+//! Tutorial content is stored and served as Markdown and usually rendered by
+//! the frontend. A few features still need to look inside it on the server,
+//! for example to pull runnable commands out of code blocks. This module holds
+//! that analysis, built on a small CommonMark fence scanner so every
+//! extractor agrees on what is and is not inside a code block, the
+//! sanitized HTML rendering served on request, the table of contents of
+//! long documents, and the front matter used by standalone Markdown exports.
+
+pub mod command_blocks;
+pub mod front_matter;
+pub mod render;
+pub mod toc;
+
+pub use command_blocks::extract_command_blocks;
+pub use render::{render_comment_html, render_document, render_document_cached, render_html};
+pub use toc::{extract_toc, TocEntry};
+
+/// A fenced code block found in a Markdown document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct FencedBlock<'a> {
+    /// First word of the info string (`rust` for ```` ```rust title ````),
+    /// empty when the fence has none.
+    pub language: &'a str,
+    /// Lines between the opening and closing fence, with the fence's own
+    /// indentation removed.
+    pub lines: Vec<&'a str>,
+}
+
+/// Opening fence of a code block: the marker character, its run length and
+/// the indentation to strip from content lines.
+struct Fence<'a> {
+    marker: char,
+    len: usize,
+    indent: usize,
+    info: &'a str,
+}
+
+/// Splits off up to three spaces of indentation, as CommonMark allows
+/// before a fence. Returns `None` for deeper (indented code) lines.
+fn fence_indent(line: &str) -> Option<(usize, &str)> {
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    (indent <= 3).then(|| (indent, &line[indent..]))
+}
+
+fn opening_fence(line: &str) -> Option<Fence<'_>> {
+    let (indent, rest) = fence_indent(line)?;
+    let marker = rest.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let len = rest.len() - rest.trim_start_matches(marker).len();
+    if len < 3 {
+        return None;
+    }
+    let info = rest[len..].trim();
+    // A backtick fence's info string may not contain backticks; otherwise the
+    // line is inline code, not a fence.
+    if marker == '`' && info.contains('`') {
+        return None;
+    }
+    Some(Fence {
+        marker,
+        len,
+        indent,
+        info,
+    })
+}
+
+fn closes(fence: &Fence<'_>, line: &str) -> bool {
+    let Some((_, rest)) = fence_indent(line) else {
+        return false;
+    };
+    let run = rest.len() - rest.trim_start_matches(fence.marker).len();
+    run >= fence.len && rest[run..].trim().is_empty()
+}
+
+/// Removes up to `indent` leading spaces, mirroring how CommonMark strips
+/// the opening fence's indentation from content lines.
+fn strip_indent(line: &str, indent: usize) -> &str {
+    let spaces = line.len() - line.trim_start_matches(' ').len();
+    &line[spaces.min(indent)..]
+}
+
+/// Returns every top-level fenced code block in `markdown`, in document
+/// order.
+///
+/// A fence only closes on a run of the same character at least as long as
+/// the opener, so a ```` ```` ```` block can show a ```` ``` ```` example
+/// without the inner fences being treated as blocks. An unclosed fence runs
+/// to the end of the document.
+pub(crate) fn fenced_blocks(markdown: &str) -> Vec<FencedBlock<'_>> {
+    let mut blocks = Vec::new();
+    let mut lines = markdown.lines();
+
+    while let Some(line) = lines.next() {
+        let Some(fence) = opening_fence(line) else {
+            continue;
+        };
+        let mut content = Vec::new();
+        for line in lines.by_ref() {
+            if closes(&fence, line) {
+                break;
+            }
+            content.push(strip_indent(line, fence.indent));
+        }
+        blocks.push(FencedBlock {
+            language: fence.info.split_whitespace().next().unwrap_or(""),
+            lines: content,
+        });
+    }
+
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nested_fences_stay_inside_the_outer_block() {
+        let markdown = "Intro\n\n````markdown\n```shell-session\n$ ls\n```\n````\n\n```rust\nfn main() {}\n```\n";
+        let blocks = fenced_blocks(markdown);
+
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].language, "markdown");
+        assert_eq!(blocks[0].lines, ["```shell-session", "$ ls", "```"]);
+        assert_eq!(blocks[1].language, "rust");
+        assert_eq!(blocks[1].lines, ["fn main() {}"]);
+    }
+
+    #[test]
+    fn tildes_indentation_and_unclosed_fences() {
+        let markdown = "  ~~~ python extra\n  print(1)\n ~~~\n\n    ```indented\n\n```\nopen";
+        let blocks = fenced_blocks(markdown);
+
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].language, "python");
+        assert_eq!(blocks[0].lines, ["print(1)"]);
+        // Four spaces is indented code, not a fence; the bare fence that
+        // follows is never closed and runs to the end.
+        assert_eq!(blocks[1].language, "");
+        assert_eq!(blocks[1].lines, ["open"]);
+    }
+
+    #[test]
+    fn inline_code_with_backticks_is_not_a_fence() {
+        assert!(fenced_blocks("```not `a` fence```\ntext").is_empty());
+    }
+}
@@ -0,0 +1,257 @@
+//! Server-side HTML Rendering
+//!
+//! Renders tutorial and post Markdown to HTML for clients that ask for it
+//! (`?format=html`), e.g. for SEO prerendering. The output of pulldown-cmark
+//! is always passed through ammonia: raw HTML in the source, scripts,
+//! iframes, event handlers and `javascript:` URLs are removed, while code
+//! blocks, tables and images served from `/uploads/` survive.
+//!
+//! Headings get the same anchor ids as in the frontend, and the table of
+//! contents is collected in the same pass (see [`super::toc`]).
+//!
+//! Rendering a large document is not free, so results are cached per
+//! entity and version; a new version simply replaces the cached entry.
+//!
+//! Comments get a much smaller subset, see [`render_comment_html`].
+
+use super::toc::{annotate_headings, TocEntry};
+use ammonia::Builder;
+use pulldown_cmark::{html, Event, Options, Parser};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, RwLock};
+
+/// Upper bound on cached documents; the cache is dropped as a whole when
+/// it fills up, which is cheap and rare enough for this workload.
+const MAX_CACHED_DOCUMENTS: usize = 256;
+
+/// Path prefix images must use to be kept.
+const UPLOADS_PREFIX: &str = "/uploads/";
+
+/// Rendered documents keyed by `(entity type, entity id)`, tagged with the
+/// version they were rendered from.
+type RenderCache = HashMap<(&'static str, String), (String, Arc<RenderedDocument>)>;
+
+static CACHE: LazyLock<RwLock<RenderCache>> = LazyLock::new(Default::default);
+
+static SANITIZER: LazyLock<Builder<'static>> = LazyLock::new(|| {
+    let mut builder = Builder::default();
+    builder
+        .add_tag_attributes("code", &["class"])
+        .add_tag_attributes("h1", &["id"])
+        .add_tag_attributes("h2", &["id"])
+        .add_tag_attributes("h3", &["id"])
+        .add_tag_attributes("h4", &["id"])
+        .add_tag_attributes("h5", &["id"])
+        .add_tag_attributes("h6", &["id"])
+        .attribute_filter(|element, attribute, value| match (element, attribute) {
+            // Only the highlighter hint pulldown-cmark emits
+            ("code", "class") => value
+                .starts_with("language-")
+                .then(|| Cow::Owned(value.to_string())),
+            ("img", "src") => value
+                .starts_with(UPLOADS_PREFIX)
+                .then(|| Cow::Owned(value.to_string())),
+            _ => Some(Cow::Owned(value.to_string())),
+        });
+    builder
+});
+
+/// Tags a rendered comment may contain. Images are not on the list: a
+/// comment must not be able to load anything from anywhere.
+const COMMENT_TAGS: &[&str] = &["p", "br", "strong", "em", "code", "pre", "a"];
+
+static COMMENT_SANITIZER: LazyLock<Builder<'static>> = LazyLock::new(|| {
+    let mut builder = Builder::empty();
+    builder
+        .tags(COMMENT_TAGS.iter().copied().collect())
+        .add_tag_attributes("a", &["href"])
+        .url_schemes(["http", "https", "mailto"].into_iter().collect())
+        .link_rel(Some("nofollow noopener noreferrer"));
+    builder
+});
+
+/// A document rendered by [`render_document`].
+#[derive(Debug)]
+pub struct RenderedDocument {
+    /// Sanitized HTML.
+    pub html: String,
+    /// Headings of levels 2 to 4, matching the ids in `html`.
+    pub toc: Vec<TocEntry>,
+}
+
+/// Markdown extensions of tutorials and posts.
+pub(super) fn options() -> Options {
+    Options::ENABLE_TABLES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_FOOTNOTES
+}
+
+/// Renders `markdown` to sanitized HTML and collects its table of contents
+/// from the same parse.
+pub fn render_document(markdown: &str) -> RenderedDocument {
+    let mut events: Vec<Event<'_>> = Parser::new_ext(markdown, options()).collect();
+    let toc = annotate_headings(&mut events);
+    let mut unsafe_html = String::with_capacity(markdown.len() * 3 / 2);
+    html::push_html(&mut unsafe_html, events.into_iter());
+    RenderedDocument {
+        html: SANITIZER.clean(&unsafe_html).to_string(),
+        toc,
+    }
+}
+
+/// Renders `markdown` to sanitized HTML.
+pub fn render_html(markdown: &str) -> String {
+    render_document(markdown).html
+}
+
+/// Renders a comment's Markdown to HTML: bold, italics, inline code, fenced
+/// code blocks and `rel="nofollow"` links. Raw HTML in the source is shown
+/// as text rather than interpreted, so `#include <stdio.h>` survives.
+pub fn render_comment_html(markdown: &str) -> String {
+    let events = Parser::new(markdown).map(|event| match event {
+        Event::Html(raw) | Event::InlineHtml(raw) => Event::Text(raw),
+        event => event,
+    });
+    let mut unsafe_html = String::with_capacity(markdown.len() * 3 / 2);
+    html::push_html(&mut unsafe_html, events);
+    COMMENT_SANITIZER.clean(&unsafe_html).to_string()
+}
+
+/// Like [`render_document`], but reuses the last rendering of the same
+/// entity when `version` has not changed.
+pub fn render_document_cached(
+    entity_type: &'static str,
+    id: &str,
+    version: &str,
+    markdown: &str,
+) -> Arc<RenderedDocument> {
+    let key = (entity_type, id.to_string());
+    if let Ok(cache) = CACHE.read() {
+        if let Some((cached_version, document)) = cache.get(&key) {
+            if cached_version == version {
+                return document.clone();
+            }
+        }
+    }
+
+    let document = Arc::new(render_document(markdown));
+    if let Ok(mut cache) = CACHE.write() {
+        if cache.len() >= MAX_CACHED_DOCUMENTS && !cache.contains_key(&key) {
+            cache.clear();
+        }
+        cache.insert(key, (version.to_string(), document.clone()));
+    }
+    document
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_code_blocks_and_tables() {
+        let html = render_html(
+            "# Title\n\n```rust\nfn main() {}\n```\n\n| a | b |\n|---|---|\n| 1 | 2 |\n",
+        );
+
+        assert!(html.contains(r#"<h1 id="title">Title</h1>"#));
+        assert!(html.contains(r#"<code class="language-rust">fn main() {}"#));
+        assert!(html.contains("<table>"));
+        assert!(html.contains("<td>2</td>"));
+    }
+
+    #[test]
+    fn strips_scripts_iframes_and_event_handlers() {
+        let html = render_html(
+            "Hi <script>alert(1)</script>\n\n<iframe src=\"https://evil.example\"></iframe>\n\n<img src=\"/uploads/a.png\" onerror=\"alert(1)\">\n\n<div style=\"x\" onclick=\"alert(1)\">raw</div>",
+        );
+
+        assert!(!html.contains("<script"));
+        assert!(!html.contains("alert(1)"));
+        assert!(!html.contains("<iframe"));
+        assert!(!html.contains("onerror"));
+        assert!(!html.contains("onclick"));
+        assert!(!html.contains("style"));
+        assert!(html.contains(r#"<img src="/uploads/a.png">"#));
+    }
+
+    #[test]
+    fn drops_javascript_links_and_foreign_images() {
+        let html = render_html(
+            "[click](javascript:alert(1)) [ok](https://example.com)\n\n![x](https://tracker.example/p.gif) ![y](/uploads/y.png)\n\n<a href=\"JaVaScRiPt:alert(1)\">raw</a>",
+        );
+
+        assert!(!html.to_lowercase().contains("javascript:"));
+        assert!(html.contains(r#"href="https://example.com""#));
+        assert!(!html.contains("tracker.example"));
+        assert!(html.contains(r#"src="/uploads/y.png""#));
+    }
+
+    #[test]
+    fn code_classes_other_than_language_hints_are_dropped() {
+        let html = render_html("<code class=\"evil\">x</code>");
+        assert!(!html.contains("evil"));
+    }
+
+    #[test]
+    fn comments_keep_emphasis_code_and_nofollow_links() {
+        let html = render_comment_html(
+            "**bold** _it_ `ls -la`\n\n```sh\nrm -rf <dir>\n```\n\n[docs](https://example.com)",
+        );
+
+        assert!(html.contains("<strong>bold</strong>"));
+        assert!(html.contains("<em>it</em>"));
+        assert!(html.contains("<code>ls -la</code>"));
+        assert!(html.contains("<pre><code>rm -rf &lt;dir&gt;\n</code></pre>"));
+        assert!(html.contains(
+            r#"<a href="https://example.com" rel="nofollow noopener noreferrer">docs</a>"#
+        ));
+    }
+
+    #[test]
+    fn comments_cannot_inject_scripts_through_links_html_or_images() {
+        let html = render_comment_html(
+            "[x](javascript:alert(1)) [y](data:text/html,hi) <script>alert(2)</script>\n\n![img](/uploads/a.png) ![t](https://tracker.example/p.gif \"t\")\n\n<a href=\"https://evil.example\" onclick=\"alert(3)\">raw</a> # <h1>x</h1>",
+        );
+
+        assert!(!html.to_lowercase().contains("javascript:"));
+        assert!(!html.contains("data:"));
+        assert!(!html.contains("<script"));
+        assert!(!html.contains("<img"));
+        assert!(!html.contains("/uploads/a.png"));
+        assert!(!html.contains("tracker.example"));
+        assert!(!html.contains("<a href=\"https://evil.example\""));
+        assert!(!html.contains("<h1>"));
+        // Raw HTML is escaped text, encoded exactly once.
+        assert!(html.contains("&lt;script&gt;alert(2)&lt;/script&gt;"));
+        assert!(!html.contains("&amp;lt;"));
+    }
+
+    #[test]
+    fn cache_is_keyed_by_version() {
+        let first = render_document_cached("test", "doc", "1", "one");
+        let same = render_document_cached("test", "doc", "1", "changed without a version bump");
+        let next = render_document_cached("test", "doc", "2", "two");
+
+        assert!(Arc::ptr_eq(&first, &same));
+        assert_eq!(next.html, "<p>two</p>\n");
+    }
+
+    #[test]
+    fn headings_carry_the_anchors_listed_in_the_toc() {
+        let document =
+            render_document("## Setup\n\n### Setup\n\n<h2 id=\"x\" onclick=\"y\">raw</h2>");
+
+        assert!(document.html.contains(r#"<h2 id="setup">Setup</h2>"#));
+        assert!(document.html.contains(r#"<h3 id="setup-1">Setup</h3>"#));
+        assert!(!document.html.contains("onclick"));
+        let anchors: Vec<_> = document
+            .toc
+            .iter()
+            .map(|entry| entry.anchor.as_str())
+            .collect();
+        assert_eq!(anchors, ["setup", "setup-1"]);
+    }
+}
@@ -0,0 +1,157 @@
+//! Table of Contents
+//!
+//! Headings get GitHub-style anchors: the heading text lowercased, stripped
+//! of everything but letters, numbers, `_`, `-` and spaces, with spaces
+//! turned into hyphens. A repeated anchor is suffixed `-1`, `-2`, ... in
+//! document order, counting headings of every level. The frontend renderer
+//! assigns the same ids (`src/utils/remarkHeadingIds.js`), so both sides
+//! must change together.
+//!
+//! The anchors are attached while walking the parser's events, so the HTML
+//! rendering in [`super::render`] gets its heading ids and the table of
+//! contents from the same pass.
+
+use pulldown_cmark::{CowStr, Event, Parser, Tag, TagEnd};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Heading levels listed in the table of contents.
+const TOC_LEVELS: std::ops::RangeInclusive<u8> = 2..=4;
+
+/// Documents larger than this (in bytes) get an empty table of contents
+/// unless the client asks for one with `?toc=true`.
+pub const MAX_AUTO_TOC_BYTES: usize = 64 * 1024;
+
+/// One heading in a document's table of contents.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TocEntry {
+    /// Heading level, 2 to 4.
+    pub level: u8,
+    /// Plain text of the heading.
+    pub text: String,
+    /// Fragment identifier of the heading, without the `#`.
+    pub anchor: String,
+}
+
+/// Hands out unique GitHub-style anchors for one document.
+#[derive(Debug, Default)]
+pub struct Slugger {
+    occurrences: HashMap<String, usize>,
+}
+
+impl Slugger {
+    /// Returns the anchor for a heading reading `text`, suffixed when an
+    /// earlier heading already took it.
+    pub fn slug(&mut self, text: &str) -> String {
+        let original: String = text
+            .to_lowercase()
+            .chars()
+            .filter(|c| c.is_alphabetic() || c.is_numeric() || matches!(c, '_' | '-' | ' '))
+            .map(|c| if c == ' ' { '-' } else { c })
+            .collect();
+        let mut slug = original.clone();
+        while self.occurrences.contains_key(&slug) {
+            let count = self.occurrences.entry(original.clone()).or_default();
+            *count += 1;
+            slug = format!("{original}-{count}");
+        }
+        self.occurrences.insert(slug.clone(), 0);
+        slug
+    }
+}
+
+/// Gives every heading in `events` its anchor as `id` and returns the
+/// table of contents.
+pub(crate) fn annotate_headings(events: &mut [Event<'_>]) -> Vec<TocEntry> {
+    let mut slugger = Slugger::default();
+    let mut toc = Vec::new();
+    let mut index = 0;
+
+    while index < events.len() {
+        let Event::Start(Tag::Heading { level, .. }) = &events[index] else {
+            index += 1;
+            continue;
+        };
+        let level = *level as u8;
+        let start = index;
+        let mut text = String::new();
+        index += 1;
+        while index < events.len() && !matches!(events[index], Event::End(TagEnd::Heading(_))) {
+            match &events[index] {
+                Event::Text(value) | Event::Code(value) => text.push_str(value),
+                Event::SoftBreak => text.push('\n'),
+                _ => {}
+            }
+            index += 1;
+        }
+
+        let anchor = slugger.slug(&text);
+        if let Event::Start(Tag::Heading { id, .. }) = &mut events[start] {
+            *id = Some(CowStr::from(anchor.clone()));
+        }
+        if TOC_LEVELS.contains(&level) {
+            toc.push(TocEntry {
+                level,
+                text: text.split_whitespace().collect::<Vec<_>>().join(" "),
+                anchor,
+            });
+        }
+    }
+
+    toc
+}
+
+/// Returns the table of contents of `markdown`.
+pub fn extract_toc(markdown: &str) -> Vec<TocEntry> {
+    let mut events: Vec<Event<'_>> = Parser::new_ext(markdown, super::render::options()).collect();
+    annotate_headings(&mut events)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slugs_follow_github_rules() {
+        let mut slugger = Slugger::default();
+
+        assert_eq!(slugger.slug("Getting Started"), "getting-started");
+        assert_eq!(slugger.slug("What's new in v2.0?"), "whats-new-in-v20");
+        assert_eq!(
+            slugger.slug("snake_case & kebab-case"),
+            "snake_case--kebab-case"
+        );
+        assert_eq!(slugger.slug("Über Größe"), "über-größe");
+    }
+
+    #[test]
+    fn duplicates_are_suffixed_in_order() {
+        let mut slugger = Slugger::default();
+
+        assert_eq!(slugger.slug("Setup"), "setup");
+        assert_eq!(slugger.slug("Setup"), "setup-1");
+        assert_eq!(slugger.slug("Setup 1"), "setup-1-1");
+        assert_eq!(slugger.slug("Setup"), "setup-2");
+    }
+
+    #[test]
+    fn toc_lists_levels_two_to_four() {
+        let toc = extract_toc(
+            "# Title\n\n## Install `cargo`\n\ntext\n\n### *Linux*\n\n#### macOS\n\n##### Deep\n\n## Install `cargo`\n\n```md\n## not a heading\n```\n",
+        );
+        let entries: Vec<_> = toc
+            .iter()
+            .map(|entry| (entry.level, entry.text.as_str(), entry.anchor.as_str()))
+            .collect();
+
+        assert_eq!(
+            entries,
+            [
+                (2, "Install cargo", "install-cargo"),
+                (3, "Linux", "linux"),
+                (4, "macOS", "macos"),
+                (2, "Install cargo", "install-cargo-1"),
+            ]
+        );
+    }
+}
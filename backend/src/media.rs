@@ -0,0 +1,383 @@
+//! Image Processing for Uploads
+//!
+//! Every uploaded image is decoded once before it is stored. That rejects
+//! files that only claim to be images and decompression bombs (small files
+//! with huge pixel dimensions), and lets JPEGs and PNGs be re-encoded
+//! without their metadata, such as the GPS position and camera details in
+//! a photo's EXIF block.
+//!
+//! Pages often show an upload far smaller than it was uploaded, e.g. as a
+//! 300px card image. For those, narrower WebP copies ("variants") are
+//! stored next to the original as `<id>_<width>.webp`.
+//!
+//! Decoding and encoding are CPU-bound, so callers run [`process_upload`]
+//! through `spawn_blocking`.
+
+use image::{
+    codecs::{gif::GifDecoder, jpeg::JpegEncoder, png::PngEncoder},
+    imageops::FilterType,
+    AnimationDecoder, DynamicImage, ImageDecoder, ImageFormat, ImageReader,
+};
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+
+/// Quality of re-encoded JPEGs.
+const JPEG_QUALITY: u8 = 90;
+
+/// A resized copy written by [`generate_variants`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneratedVariant {
+    pub width: u32,
+    pub height: u32,
+    /// Name within the upload directory.
+    pub filename: String,
+    pub size_bytes: u64,
+}
+
+/// An upload that passed [`process_upload`].
+#[derive(Debug)]
+pub struct ProcessedUpload {
+    /// Pixel dimensions, upright.
+    pub width: u32,
+    pub height: u32,
+    /// Size of the file after metadata was stripped.
+    pub size_bytes: u64,
+    pub variants: Vec<GeneratedVariant>,
+}
+
+/// Why an upload was not accepted as an image.
+#[derive(Debug)]
+pub enum UploadImageError {
+    /// Wider or taller than the configured maximum.
+    TooLarge { width: u32, height: u32, max: u32 },
+    /// Not decodable as the image format it claims to be.
+    Invalid(image::ImageError),
+    /// The file could not be read or rewritten.
+    Io(std::io::Error),
+}
+
+impl fmt::Display for UploadImageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UploadImageError::TooLarge { width, height, max } => write!(
+                f,
+                "Image is {width}x{height} pixels; at most {max} pixels per side are allowed"
+            ),
+            UploadImageError::Invalid(_) => f.write_str("File is not a valid image"),
+            UploadImageError::Io(err) => write!(f, "Failed to process image: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for UploadImageError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            UploadImageError::TooLarge { .. } => None,
+            UploadImageError::Invalid(err) => Some(err),
+            UploadImageError::Io(err) => Some(err),
+        }
+    }
+}
+
+impl From<image::ImageError> for UploadImageError {
+    fn from(err: image::ImageError) -> Self {
+        match err {
+            image::ImageError::IoError(err) => UploadImageError::Io(err),
+            err => UploadImageError::Invalid(err),
+        }
+    }
+}
+
+impl From<std::io::Error> for UploadImageError {
+    fn from(err: std::io::Error) -> Self {
+        UploadImageError::Io(err)
+    }
+}
+
+/// Name of the `width` variant of the upload `id`.
+pub fn variant_filename(id: &str, width: u32) -> String {
+    format!("{id}_{width}.webp")
+}
+
+/// Checks the freshly written upload at `source`, rewrites JPEGs and PNGs
+/// in place without their metadata (turned upright first, since the EXIF
+/// orientation goes with the rest), and writes the variants of `widths`
+/// into `dir`.
+///
+/// Images wider or taller than `max_dimension` are rejected from their
+/// header, before any pixels are decoded. Failing to make the variants
+/// only leaves the upload without them. Animated GIFs are checked but
+/// stored untouched, without variants.
+pub fn process_upload(
+    source: &Path,
+    dir: &Path,
+    id: &str,
+    max_dimension: u32,
+    widths: &[u32],
+) -> Result<ProcessedUpload, UploadImageError> {
+    let reader = ImageReader::open(source)?.with_guessed_format()?;
+    let format = reader.format();
+    let mut decoder = reader.into_decoder()?;
+    let (width, height) = decoder.dimensions();
+    if width > max_dimension || height > max_dimension {
+        return Err(UploadImageError::TooLarge {
+            width,
+            height,
+            max: max_dimension,
+        });
+    }
+    let orientation = decoder.orientation()?;
+    let mut image = DynamicImage::from_decoder(decoder)?;
+
+    if format == Some(ImageFormat::Gif) && is_animated_gif(source)? {
+        return Ok(ProcessedUpload {
+            width,
+            height,
+            size_bytes: std::fs::metadata(source)?.len(),
+            variants: Vec::new(),
+        });
+    }
+
+    if let Some(format @ (ImageFormat::Jpeg | ImageFormat::Png)) = format {
+        image.apply_orientation(orientation);
+        strip_metadata(&image, format, source)?;
+    }
+
+    let variants = generate_variants(&image, dir, id, widths).unwrap_or_else(|err| {
+        tracing::warn!("Storing upload {} without variants: {}", id, err);
+        Vec::new()
+    });
+    Ok(ProcessedUpload {
+        width: image.width(),
+        height: image.height(),
+        size_bytes: std::fs::metadata(source)?.len(),
+        variants,
+    })
+}
+
+/// Overwrites `path` with `image` encoded afresh, which carries no EXIF,
+/// XMP or text chunks.
+fn strip_metadata(
+    image: &DynamicImage,
+    format: ImageFormat,
+    path: &Path,
+) -> Result<(), UploadImageError> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    match format {
+        // JPEG has no alpha channel
+        ImageFormat::Jpeg => DynamicImage::ImageRgb8(image.to_rgb8())
+            .write_with_encoder(JpegEncoder::new_with_quality(&mut writer, JPEG_QUALITY))?,
+        _ => image.write_with_encoder(PngEncoder::new(&mut writer))?,
+    }
+    writer
+        .into_inner()
+        .map_err(|err| err.into_error())?
+        .sync_all()?;
+    Ok(())
+}
+
+/// Writes a WebP variant of `image` into `dir` for every width in `widths`
+/// narrower than the image.
+///
+/// On error, the variants written so far are removed again.
+pub fn generate_variants(
+    image: &DynamicImage,
+    dir: &Path,
+    id: &str,
+    widths: &[u32],
+) -> Result<Vec<GeneratedVariant>, image::ImageError> {
+    let mut variants = Vec::new();
+    for &width in widths.iter().filter(|&&width| width < image.width()) {
+        match write_variant(image, dir, id, width) {
+            Ok(variant) => variants.push(variant),
+            Err(err) => {
+                remove_variants(dir, &variants);
+                return Err(err);
+            }
+        }
+    }
+    Ok(variants)
+}
+
+/// Removes the files of `variants` from `dir`, ignoring those already gone.
+pub fn remove_variants(dir: &Path, variants: &[GeneratedVariant]) {
+    for variant in variants {
+        let _ = std::fs::remove_file(dir.join(&variant.filename));
+    }
+}
+
+fn write_variant(
+    image: &DynamicImage,
+    dir: &Path,
+    id: &str,
+    width: u32,
+) -> Result<GeneratedVariant, image::ImageError> {
+    let height = (u64::from(image.height()) * u64::from(width) / u64::from(image.width())).max(1);
+    let resized = image.resize_exact(width, height as u32, FilterType::Lanczos3);
+    // The WebP encoder takes 8-bit RGB(A) only
+    let resized = if resized.color().has_alpha() {
+        DynamicImage::ImageRgba8(resized.into_rgba8())
+    } else {
+        DynamicImage::ImageRgb8(resized.into_rgb8())
+    };
+
+    let filename = variant_filename(id, width);
+    let path: PathBuf = dir.join(&filename);
+    let written = File::create(&path)
+        .map_err(image::ImageError::IoError)
+        .and_then(|file| resized.write_to(&mut BufWriter::new(file), ImageFormat::WebP))
+        .and_then(|()| std::fs::metadata(&path).map_err(image::ImageError::IoError));
+    match written {
+        Ok(metadata) => Ok(GeneratedVariant {
+            width,
+            height: resized.height(),
+            filename,
+            size_bytes: metadata.len(),
+        }),
+        Err(err) => {
+            let _ = std::fs::remove_file(&path);
+            Err(err)
+        }
+    }
+}
+
+fn is_animated_gif(source: &Path) -> Result<bool, image::ImageError> {
+    let decoder = GifDecoder::new(BufReader::new(File::open(source)?))?;
+    Ok(decoder.into_frames().take(2).count() > 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{codecs::gif::GifEncoder, Delay, Frame, Rgba, RgbaImage};
+
+    fn scratch_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("minos-media-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Whether the JPEG carries an APP1 segment with an EXIF header.
+    fn has_exif_segment(jpeg: &[u8]) -> bool {
+        let mut pos = 2;
+        while pos + 4 <= jpeg.len() && jpeg[pos] == 0xFF {
+            let marker = jpeg[pos + 1];
+            // Start of scan: the headers are over
+            if marker == 0xDA {
+                break;
+            }
+            let length = usize::from(u16::from_be_bytes([jpeg[pos + 2], jpeg[pos + 3]]));
+            if marker == 0xE1 && jpeg[pos + 4..].starts_with(b"Exif\0\0") {
+                return true;
+            }
+            pos += 2 + length;
+        }
+        false
+    }
+
+    #[test]
+    fn variants_are_generated_for_narrower_widths_only() {
+        let dir = scratch_dir();
+        let source = dir.join("upload.tmp");
+        DynamicImage::ImageRgba8(RgbaImage::from_pixel(640, 100, Rgba([200, 10, 10, 255])))
+            .save_with_format(&source, ImageFormat::Png)
+            .unwrap();
+
+        let processed = process_upload(&source, &dir, "upload", 8000, &[320, 960]);
+        let variant_bytes = std::fs::read(dir.join("upload_320.webp"));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let processed = processed.expect("image accepted");
+        assert_eq!((processed.width, processed.height), (640, 100));
+        let variants = processed.variants;
+        assert_eq!(variants.len(), 1);
+        assert_eq!((variants[0].width, variants[0].height), (320, 50));
+        assert_eq!(variants[0].filename, "upload_320.webp");
+        let variant_bytes = variant_bytes.expect("variant written");
+        assert_eq!(variants[0].size_bytes, variant_bytes.len() as u64);
+        assert_eq!(
+            image::guess_format(&variant_bytes).unwrap(),
+            ImageFormat::WebP
+        );
+    }
+
+    #[test]
+    fn animated_gifs_are_passed_through() {
+        let dir = scratch_dir();
+        let source = dir.join("animated.tmp");
+        {
+            let mut encoder = GifEncoder::new(File::create(&source).unwrap());
+            for shade in [0, 255] {
+                let frame = RgbaImage::from_pixel(640, 10, Rgba([shade, shade, shade, 255]));
+                encoder
+                    .encode_frame(Frame::from_parts(
+                        frame,
+                        0,
+                        0,
+                        Delay::from_numer_denom_ms(100, 1),
+                    ))
+                    .unwrap();
+            }
+        }
+        let original = std::fs::read(&source).unwrap();
+
+        let processed = process_upload(&source, &dir, "animated", 8000, &[320]);
+        let stored = std::fs::read(&source).unwrap();
+        let leftovers = std::fs::read_dir(&dir).unwrap().count();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert!(processed.unwrap().variants.is_empty());
+        assert_eq!(stored, original);
+        assert_eq!(leftovers, 1);
+    }
+
+    #[test]
+    fn jpeg_metadata_is_stripped() {
+        let fixture = include_bytes!("../tests/fixtures/gps_tagged.jpg");
+        assert!(has_exif_segment(fixture));
+        let dir = scratch_dir();
+        let source = dir.join("photo.tmp");
+        std::fs::write(&source, fixture).unwrap();
+
+        let processed = process_upload(&source, &dir, "photo", 8000, &[]);
+        let stored = std::fs::read(&source).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let processed = processed.expect("image accepted");
+        assert_eq!((processed.width, processed.height), (64, 48));
+        assert_eq!(processed.size_bytes, stored.len() as u64);
+        assert_eq!(image::guess_format(&stored).unwrap(), ImageFormat::Jpeg);
+        assert!(!has_exif_segment(&stored));
+        assert!(!stored.windows(10).any(|window| window == b"FixtureCam"));
+    }
+
+    #[test]
+    fn oversized_and_undecodable_images_are_rejected() {
+        let dir = scratch_dir();
+        let wide = dir.join("wide.tmp");
+        DynamicImage::ImageLuma8(image::GrayImage::new(200, 10))
+            .save_with_format(&wide, ImageFormat::Png)
+            .unwrap();
+        let broken = dir.join("broken.tmp");
+        // A PNG signature and header, but no image data
+        let mut png = b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0dIHDR".to_vec();
+        png.extend_from_slice(&[0, 0, 0, 3, 0, 0, 0, 2, 8, 2, 0, 0, 0, 0, 0, 0, 0]);
+        std::fs::write(&broken, png).unwrap();
+
+        let too_large = process_upload(&wide, &dir, "wide", 100, &[]);
+        let invalid = process_upload(&broken, &dir, "broken", 100, &[]);
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert!(matches!(
+            too_large,
+            Err(UploadImageError::TooLarge {
+                width: 200,
+                height: 10,
+                max: 100
+            })
+        ));
+        assert!(matches!(invalid, Err(UploadImageError::Invalid(_))));
+    }
+}
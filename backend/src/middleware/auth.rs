@@ -10,65 +10,66 @@
 //! request extensions. This allows downstream handlers to simply
 //! use the `Claims` extractor to identify the user and their role.
 
-use crate::{repositories, security::auth};
-use axum::{http::StatusCode, Json};
+use crate::{
+    models::{internal_error_plain, unauthorized, AppError, RejectionReason},
+    security::{auth, rejections},
+};
 
 /// Middleware to enforce authentication on a per-route or per-router basis.
 ///
 /// Process Flow:
+/// 0. **API Key**: An `X-Api-Key` header is resolved to its owner's claims instead.
 /// 1. **Extraction**: Checks both Authorization header and ltcms_session cookie.
 /// 2. **Verification**: Validates the JWT signature and expiration.
-/// 3. **Revocation Check**: Queries the database to ensure the token isn't blacklisted (e.g., after logout).
+/// 3. **Revocation Check**: Queries the database to ensure neither the token nor its session was revoked (e.g., after logout).
 /// 4. **Injection**: Places the verified Claims into the request lifecycle.
 pub async fn auth_middleware(
     axum::extract::State(pool): axum::extract::State<crate::db::DbPool>,
     mut request: axum::extract::Request,
     next: axum::middleware::Next,
-) -> Result<axum::response::Response, (StatusCode, Json<crate::models::ErrorResponse>)> {
+) -> Result<axum::response::Response, AppError> {
+    // Step 0: API Key
+    // Programmatic clients authenticate with `X-Api-Key` instead of a token.
+    if let Some(key) = auth::extract_api_key(request.headers()) {
+        let key = key.to_owned();
+        auth::authenticate_api_key(request.extensions_mut(), &pool, &key).await?;
+        return Ok(next.run(request).await);
+    }
+
     // Step 1: Token Extraction
     // Checks for 'Bearer' token or 'ltcms_session' fallback cookie.
-    let token = auth::extract_token(request.headers()).ok_or_else(|| {
-        (
-            StatusCode::UNAUTHORIZED,
-            Json(crate::models::ErrorResponse {
-                error: "Missing authentication token".to_string(),
-            }),
-        )
-    })?;
+    let token = auth::extract_token(request.headers())
+        .ok_or_else(|| unauthorized("Missing authentication token"))?;
 
     // Step 2: Cryptographic Verification
     // Validates the HMAC signature and ensured the token has not expired.
     let claims = auth::verify_jwt(&token).map_err(|e| {
-        (
-            StatusCode::UNAUTHORIZED,
-            Json(crate::models::ErrorResponse {
-                error: format!("Invalid token: {}", e),
-            }),
-        )
+        auth::record_token_rejection(
+            request.extensions_mut(),
+            &pool,
+            rejections::token_error_reason(&e),
+        );
+        unauthorized(format!("Invalid token: {}", e))
     })?;
 
-    // Step 3: Revocation Check (Blacklist)
-    // Even a cryptographically valid token is rejected if the user has logged out.
+    // Step 3: Revocation Check (Blacklist and Sessions)
+    // Even a cryptographically valid token is rejected if the user has logged out,
+    // here or from another device.
     // Fail CLOSED: a database error here must NOT be treated as "not blacklisted".
-    let is_blacklisted = repositories::token_blacklist::is_token_blacklisted(&pool, &token)
+    let is_revoked = auth::is_token_revoked(&pool, &token, &claims)
         .await
         .map_err(|e| {
             tracing::error!("Database error checking token blacklist: {}", e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(crate::models::ErrorResponse {
-                    error: "Internal server error".to_string(),
-                }),
-            )
+            internal_error_plain("Internal server error")
         })?;
 
-    if is_blacklisted {
-        return Err((
-            StatusCode::UNAUTHORIZED,
-            Json(crate::models::ErrorResponse {
-                error: "Token has been revoked".to_string(),
-            }),
-        ));
+    if is_revoked {
+        auth::record_token_rejection(
+            request.extensions_mut(),
+            &pool,
+            RejectionReason::Blacklisted,
+        );
+        return Err(unauthorized("Token has been revoked"));
     }
 
     // Step 4: Extension Injection
@@ -0,0 +1,244 @@
+//! Maintenance Mode Middleware
+//!
+//! While an admin has the site in maintenance mode, public reads answer 503
+//! with `Retry-After` and the admin's message instead of half-edited
+//! content. Health probes, the auth API and staff whose role grants
+//! [`Permission::BypassMaintenance`] (admins and editors) keep working, as
+//! do the `/login` and `/admin` app shells they need to get back in.
+//! Writes are not affected; they either need a login already or are
+//! rejected by their own handlers.
+//!
+//! The flag lives in `app_metadata` and is cached in process. Toggling it
+//! through [`set_mode`] refreshes the cache.
+
+use crate::{
+    db::DbPool,
+    models::{api_error_with_code, MaintenanceMode},
+    repositories,
+    security::auth::{OptionalClaims, Permission},
+};
+use axum::{
+    extract::{FromRequestParts, Request, State},
+    http::{header::RETRY_AFTER, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::sync::{Arc, RwLock};
+
+/// `app_metadata` key holding the JSON-encoded [`MaintenanceMode`].
+const METADATA_KEY: &str = "maintenance_mode";
+
+/// Seconds clients are asked to wait before retrying.
+const RETRY_AFTER_SECS: &str = "300";
+
+/// Notice shown when the admin gave no message.
+pub const DEFAULT_MAINTENANCE_MESSAGE: &str =
+    "The site is down for maintenance. Please check back soon.";
+
+static CACHE: RwLock<Option<Arc<MaintenanceMode>>> = RwLock::new(None);
+
+/// The current mode, read from the database only when not cached.
+pub async fn current_mode(pool: &DbPool) -> Result<Arc<MaintenanceMode>, sqlx::Error> {
+    if let Some(mode) = CACHE.read().ok().and_then(|cache| cache.clone()) {
+        return Ok(mode);
+    }
+
+    let mode = match repositories::app_metadata::get_metadata(pool, METADATA_KEY).await? {
+        Some(raw) => serde_json::from_str(&raw).unwrap_or_else(|err| {
+            tracing::warn!(error = %err, "Ignoring unreadable maintenance mode flag");
+            MaintenanceMode::default()
+        }),
+        None => MaintenanceMode::default(),
+    };
+    let mode = Arc::new(mode);
+    if let Ok(mut cache) = CACHE.write() {
+        *cache = Some(mode.clone());
+    }
+    Ok(mode)
+}
+
+/// Stores `mode` and makes it effective for the next request.
+pub async fn set_mode(pool: &DbPool, mode: MaintenanceMode) -> Result<(), sqlx::Error> {
+    let raw = serde_json::to_string(&mode).map_err(|err| sqlx::Error::Encode(Box::new(err)))?;
+    repositories::app_metadata::set_metadata(pool, METADATA_KEY, &raw).await?;
+    if let Ok(mut cache) = CACHE.write() {
+        *cache = Some(Arc::new(mode));
+    }
+    Ok(())
+}
+
+/// Paths that stay reachable for everyone during maintenance.
+fn is_exempt(path: &str) -> bool {
+    path == "/api/health"
+        || path.starts_with("/api/health/")
+        || path.starts_with("/api/auth/")
+        || path == "/login"
+        || path == "/admin"
+        || path.starts_with("/admin/")
+}
+
+/// Answers public reads with 503 while maintenance mode is on.
+///
+/// The flag is checked first, so the token of a request is only verified
+/// while the site is actually in maintenance. A failed lookup lets the
+/// request through rather than taking the site down.
+pub async fn maintenance_mode(
+    State(pool): State<DbPool>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if !matches!(*request.method(), Method::GET | Method::HEAD) || is_exempt(request.uri().path()) {
+        return next.run(request).await;
+    }
+
+    let mode = match current_mode(&pool).await {
+        Ok(mode) => mode,
+        Err(err) => {
+            tracing::error!(error = %err, "Failed to load maintenance mode");
+            return next.run(request).await;
+        }
+    };
+    if !mode.enabled {
+        return next.run(request).await;
+    }
+
+    let (mut parts, body) = request.into_parts();
+    let is_staff = matches!(
+        OptionalClaims::from_request_parts(&mut parts, &pool).await,
+        Ok(OptionalClaims(Some(claims))) if claims.can(Permission::BypassMaintenance)
+    );
+    if is_staff {
+        return next.run(Request::from_parts(parts, body)).await;
+    }
+
+    let message = mode
+        .message
+        .as_deref()
+        .filter(|message| !message.trim().is_empty())
+        .unwrap_or(DEFAULT_MAINTENANCE_MESSAGE);
+    let mut response = api_error_with_code(StatusCode::SERVICE_UNAVAILABLE, "maintenance", message)
+        .into_response();
+    response
+        .headers_mut()
+        .insert(RETRY_AFTER, HeaderValue::from_static(RETRY_AFTER_SECS));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::auth;
+    use axum::{
+        body::Body,
+        http::header,
+        routing::{get, post},
+        Router,
+    };
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn maintenance_mode_blocks_public_reads_only() {
+        if auth::JWT_SECRET.get().is_none() {
+            let _ = auth::init_jwt_secret(
+                "this_is_a_test_jwt_secret_with_adequate_entropy_123_ABC_!!!",
+            );
+        }
+        let pool = crate::test_support::migrated_pool().await;
+
+        let app = Router::new()
+            .route("/api/public/settings", get(|| async { "settings" }))
+            .route("/api/health", get(|| async { "OK" }))
+            .route("/api/auth/me", get(|| async { "me" }))
+            .route("/api/posts/{id}/comments", post(|| async { "comment" }))
+            .route("/login", get(|| async { "login" }))
+            .layer(axum::middleware::from_fn_with_state(
+                pool.clone(),
+                maintenance_mode,
+            ));
+        let send = |method: Method, uri: &str, token: Option<String>| {
+            let mut builder = Request::builder().method(method).uri(uri);
+            if let Some(token) = token {
+                builder = builder.header(header::AUTHORIZATION, format!("Bearer {token}"));
+            }
+            app.clone().oneshot(builder.body(Body::empty()).unwrap())
+        };
+        let sign_in = |username: &str, role: &str| {
+            let claims = auth::Claims::new(username.to_string(), role.to_string());
+            let pool = pool.clone();
+            async move {
+                crate::repositories::sessions::create_session(
+                    &pool,
+                    &claims.jti,
+                    &claims.sub,
+                    claims.exp as i64,
+                    None,
+                    "test",
+                )
+                .await
+                .unwrap();
+                auth::encode_claims(&claims).unwrap()
+            }
+        };
+        let admin = sign_in("root", "admin").await;
+        let editor = sign_in("writer", "editor").await;
+        let reader = sign_in("reader", "user").await;
+
+        let response = send(Method::GET, "/api/public/settings", None)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        set_mode(
+            &pool,
+            MaintenanceMode {
+                enabled: true,
+                message: Some("Back at noon".to_string()),
+            },
+        )
+        .await
+        .unwrap();
+
+        let response = send(Method::GET, "/api/public/settings", None)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.headers()[RETRY_AFTER], RETRY_AFTER_SECS);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["error"], "Back at noon");
+        assert_eq!(body["code"], "maintenance");
+
+        let response = send(Method::GET, "/api/public/settings", Some(reader))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        for token in [editor, admin] {
+            let response = send(Method::GET, "/api/public/settings", Some(token))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+        for uri in ["/api/health", "/api/auth/me", "/login"] {
+            let response = send(Method::GET, uri, None).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK, "{uri}");
+        }
+        let response = send(Method::POST, "/api/posts/1/comments", None)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // Stored, so a restart keeps the site in maintenance
+        if let Ok(mut cache) = CACHE.write() {
+            *cache = None;
+        }
+        assert!(current_mode(&pool).await.unwrap().enabled);
+
+        set_mode(&pool, MaintenanceMode::default()).await.unwrap();
+        let response = send(Method::GET, "/api/public/settings", None)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
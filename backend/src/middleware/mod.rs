@@ -5,4 +5,5 @@
 
 pub mod auth; // Identity and session verification
 pub mod cors; // Cross-origin resource sharing
+pub mod maintenance; // Maintenance mode for public reads
 pub mod security; // Defense-in-depth security policies
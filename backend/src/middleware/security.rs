@@ -11,16 +11,12 @@ use axum::{
             CACHE_CONTROL, CONTENT_SECURITY_POLICY, EXPIRES, PRAGMA, STRICT_TRANSPORT_SECURITY,
             X_CONTENT_TYPE_OPTIONS, X_FRAME_OPTIONS,
         },
-        HeaderMap, HeaderName, HeaderValue, Method,
+        HeaderMap, HeaderName, HeaderValue, Method, StatusCode,
     },
     middleware::Next,
     response::Response,
 };
-use std::{
-    env,
-    net::{IpAddr, SocketAddr},
-    sync::OnceLock,
-};
+use std::net::{IpAddr, SocketAddr};
 use tower_governor::{errors::GovernorError, key_extractor::KeyExtractor};
 
 // Custom HTTP header constants for security policies
@@ -35,24 +31,6 @@ const X_FORWARDED_PROTO_HEADER: HeaderName = HeaderName::from_static("x-forwarde
 const X_FORWARDED_HOST_HEADER: HeaderName = HeaderName::from_static("x-forwarded-host");
 const X_REAL_IP_HEADER: HeaderName = HeaderName::from_static("x-real-ip");
 
-/// Helper to parse environment variables as boolean flags.
-/// Supports common truthy/falsy strings like '1', 'true', 'yes', 'on', '0', 'false', etc.
-pub fn parse_env_bool(key: &str, default: bool) -> bool {
-    env::var(key)
-        .ok()
-        .and_then(|value| {
-            match value.trim().to_ascii_lowercase().as_str() {
-                "1" | "true" | "yes" | "on" => Some(true),
-                "0" | "false" | "no" | "off" => Some(false),
-                _ => {
-                    tracing::warn!(key = %key, value = %value, "Invalid boolean env value; using default");
-                    None
-                }
-            }
-        })
-        .unwrap_or(default)
-}
-
 /// SECURITY: This function must only ever trust IP data that a proxy we
 /// control *overwrites* or *appends*, never data a client can freely set.
 ///
@@ -96,12 +74,10 @@ fn resolve_client_ip(headers: &HeaderMap, fallback: IpAddr, trust_proxy_headers:
 
 /// Whether proxy-supplied IP headers are trusted for this process.
 ///
-/// The environment variable cannot change for the lifetime of the process, so
-/// it is read once and cached instead of hitting the environment on every
-/// request that resolves a client IP.
+/// Controlled by `TRUST_PROXY_IP_HEADERS`, resolved once at startup by
+/// [`crate::config`].
 pub fn trust_proxy_ip_headers() -> bool {
-    static TRUST_PROXY_IP_HEADERS: OnceLock<bool> = OnceLock::new();
-    *TRUST_PROXY_IP_HEADERS.get_or_init(|| parse_env_bool("TRUST_PROXY_IP_HEADERS", false))
+    crate::config::get().trust_proxy_ip_headers
 }
 
 /// Resolves the effective client IP for rate limiting and audit purposes.
@@ -163,7 +139,8 @@ pub async fn strip_untrusted_forwarded_headers(mut request: Request, next: Next)
 /// Middleware to add security and privacy headers to all HTTP responses.
 ///
 /// Implementations:
-/// - **Cache-Control**: Dynamic based on path (public vs sensitive).
+/// - **Cache-Control**: Dynamic based on path (public vs sensitive); a year
+///   for served uploads.
 /// - **CSP**: Strict policy to prevent XSS and data injection.
 /// - **HSTS**: Enforce HTTPS for a year (only if ENABLE_HSTS=true is set explicitly).
 /// - **X-Content-Type-Options**: Prevent MIME-sniffing.
@@ -173,18 +150,43 @@ pub async fn strip_untrusted_forwarded_headers(mut request: Request, next: Next)
 pub async fn security_headers(request: Request, next: Next) -> Response {
     let method = request.method().clone();
     let path = request.uri().path().to_string();
+    // Preview links show drafts and can be revoked, so shared caches must
+    // not keep them
+    let preview = request
+        .uri()
+        .query()
+        .is_some_and(|query| query.split('&').any(|pair| pair.starts_with("preview=")));
 
     let mut response = next.run(request).await;
+    // A 503 during maintenance or a failing handler must not be cached
+    let server_error = response.status().is_server_error();
+    // Upload file names are UUIDs: a URL never gets other content
+    let immutable_upload = matches!(method, Method::GET | Method::HEAD)
+        && path.starts_with("/uploads/")
+        && (response.status().is_success() || response.status() == StatusCode::NOT_MODIFIED);
     let headers = response.headers_mut();
 
     // Step 1: Configure cache control based on endpoint type
     // Public endpoints can be cached to improve performance, sensitive endpoints cannot.
     let cacheable = method == Method::GET
+        && !preview
+        && !server_error
         && (path == "/api/tutorials"
             || path.starts_with("/api/tutorials/")
-            || path.starts_with("/api/public/"));
+            || path.starts_with("/api/public/")
+            || path == "/robots.txt"
+            || path == "/sitemap.xml"
+            || path.starts_with("/sitemaps/")
+            || path == "/.well-known/security.txt");
 
-    if cacheable {
+    if immutable_upload {
+        headers.insert(
+            CACHE_CONTROL,
+            HeaderValue::from_static("public, max-age=31536000, immutable"),
+        );
+        headers.remove(PRAGMA);
+        headers.remove(EXPIRES);
+    } else if cacheable {
         // Optimized caching for public read-only endpoints (5 minute TTL)
         headers.insert(
             CACHE_CONTROL,
@@ -230,7 +232,7 @@ pub async fn security_headers(request: Request, next: Next) -> Response {
     // middleware runs, so a client with direct access to the backend port
     // could set it themselves. Require deployments behind a TLS-terminating
     // proxy to opt in explicitly via ENABLE_HSTS instead.
-    let hsts_enabled = parse_env_bool("ENABLE_HSTS", false);
+    let hsts_enabled = crate::config::get().enable_hsts;
     if hsts_enabled {
         headers.insert(
             STRICT_TRANSPORT_SECURITY,
@@ -328,4 +330,34 @@ mod tests {
         );
         assert_eq!(resolve_client_ip(&headers, fallback, false), fallback);
     }
+
+    #[tokio::test]
+    async fn preview_links_are_never_publicly_cacheable() {
+        use axum::{body::Body, routing::get, Router};
+        use tower::ServiceExt;
+
+        let app = Router::new()
+            .route("/api/public/pages/{slug}", get(|| async { "page" }))
+            .layer(axum::middleware::from_fn(security_headers));
+        let cache_control = |uri: &'static str| {
+            let app = app.clone();
+            async move {
+                let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+                let response = app.oneshot(request).await.unwrap();
+                response.headers()[CACHE_CONTROL]
+                    .to_str()
+                    .unwrap()
+                    .to_string()
+            }
+        };
+
+        assert!(cache_control("/api/public/pages/about")
+            .await
+            .starts_with("public"));
+        assert!(
+            cache_control("/api/public/pages/about?format=html&preview=p1.1.x")
+                .await
+                .starts_with("no-store")
+        );
+    }
 }
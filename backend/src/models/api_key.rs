@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// An API key as listed to admins. The key itself is never stored; only
+/// its hash and the first few characters (`prefix`) for recognition.
+#[derive(Debug, Serialize, FromRow)]
+pub struct ApiKey {
+    pub id: i64,
+    pub name: String,
+    pub prefix: String,
+    /// Account whose role the key acts with.
+    pub username: String,
+    pub created_at: String,
+    pub last_used_at: Option<String>,
+    pub revoked_at: Option<String>,
+}
+
+/// The account behind an active API key.
+#[derive(Debug, FromRow)]
+pub struct ApiKeyOwner {
+    pub key_id: i64,
+    pub user_id: i64,
+    pub username: String,
+    pub role: String,
+}
+
+/// Payload of `POST /api/admin/api-keys`.
+#[derive(Debug, Deserialize)]
+pub struct CreateApiKeyRequest {
+    /// Label shown in the key list, e.g. the name of the integration.
+    pub name: String,
+    /// Owner of the key. Defaults to the calling admin.
+    #[serde(default)]
+    pub user_id: Option<i64>,
+}
+
+/// Response of `POST /api/admin/api-keys`. `key` is shown only this once.
+#[derive(Debug, Serialize)]
+pub struct CreatedApiKeyResponse {
+    #[serde(flatten)]
+    pub api_key: ApiKey,
+    pub key: String,
+}
+
+/// Response of `GET /api/admin/api-keys`.
+#[derive(Debug, Serialize)]
+pub struct ApiKeyListResponse {
+    pub items: Vec<ApiKey>,
+}
@@ -0,0 +1,61 @@
+use serde::Serialize;
+use sqlx::FromRow;
+
+/// A row of `audit_log`; `details` is the stored JSON text.
+#[derive(Debug, FromRow)]
+pub struct AuditLogRecord {
+    pub id: i64,
+    pub created_at: String,
+    pub username: String,
+    pub action: String,
+    pub entity_type: String,
+    pub entity_id: Option<String>,
+    pub details: String,
+}
+
+/// An audit log entry as returned by `GET /api/admin/audit-log`.
+#[derive(Debug, Serialize)]
+pub struct AuditLogEntry {
+    pub id: i64,
+    pub created_at: String,
+    /// Account that made the change.
+    pub username: String,
+    /// `create`, `update`, `patch`, `delete`, `import` or `upload`.
+    pub action: String,
+    /// `tutorial`, `page`, `post`, `site_content`, `comment` or `upload`.
+    pub entity_type: String,
+    pub entity_id: Option<String>,
+    /// Action-specific context, e.g. a title or the number of patch
+    /// operations.
+    pub details: serde_json::Value,
+}
+
+impl From<AuditLogRecord> for AuditLogEntry {
+    fn from(record: AuditLogRecord) -> Self {
+        let details = serde_json::from_str(&record.details).unwrap_or_else(|err| {
+            tracing::warn!(
+                "Unreadable audit log details on entry {}: {}",
+                record.id,
+                err
+            );
+            serde_json::Value::Null
+        });
+        AuditLogEntry {
+            id: record.id,
+            created_at: record.created_at,
+            username: record.username,
+            action: record.action,
+            entity_type: record.entity_type,
+            entity_id: record.entity_id,
+            details,
+        }
+    }
+}
+
+/// Response of `GET /api/admin/audit-log`.
+#[derive(Debug, Serialize)]
+pub struct AuditLogListResponse {
+    pub items: Vec<AuditLogEntry>,
+    /// Entries matching the filters, across all pages.
+    pub total: i64,
+}
@@ -0,0 +1,43 @@
+//! Public changelog of content publication events.
+
+use serde::Serialize;
+use sqlx::FromRow;
+
+/// What happened to a document in a `content_events` row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentEventKind {
+    /// First time the document became publicly visible.
+    Published,
+    /// A published document received a substantial content change.
+    Updated,
+}
+
+impl ContentEventKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ContentEventKind::Published => "published",
+            ContentEventKind::Updated => "updated",
+        }
+    }
+}
+
+/// One entry of `GET /api/public/changelog`.
+#[derive(Debug, Serialize, FromRow)]
+pub struct ChangelogEntry {
+    pub id: i64,
+    /// `tutorial` or `post`.
+    pub entity_type: String,
+    pub entity_id: String,
+    /// Title at the time of the event.
+    pub title: String,
+    /// `published` or `updated`.
+    pub kind: String,
+    pub created_at: String,
+    /// Site-relative path of the document's current location.
+    pub link: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChangelogResponse {
+    pub items: Vec<ChangelogEntry>,
+}
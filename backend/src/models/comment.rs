@@ -1,10 +1,19 @@
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 
+/// Status of a comment everyone can see.
+pub const COMMENT_STATUS_PUBLISHED: &str = "published";
+/// Status of a comment held back by the blocklist; only moderators see it.
+pub const COMMENT_STATUS_PENDING: &str = "pending";
+
+fn default_comment_status() -> String {
+    COMMENT_STATUS_PUBLISHED.to_string()
+}
+
 /// Represents a user-submitted comment on content.
 ///
 /// Comments can be attached to either a `Tutorial` or a `SitePost`.
-#[derive(Debug, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Comment {
     /// Unique UUID (v4) for the comment.
     pub id: String,
@@ -12,12 +21,18 @@ pub struct Comment {
     pub tutorial_id: Option<String>,
     /// ID of the blog post this comment belongs to (if any).
     pub post_id: Option<String>,
+    /// ID of the comment this one replies to; `None` for a top-level comment.
+    #[serde(default)]
+    pub parent_id: Option<String>,
     /// Display name of the author.
     pub author: String,
     /// The comment body, supports Markdown syntax.
     pub content: String,
     /// ISO 8601 timestamp of creation.
     pub created_at: String,
+    /// RFC 3339 timestamp of the last edit; `None` if never edited.
+    #[serde(default)]
+    pub edited_at: Option<String>,
     /// Net karma score (upvotes minus downvotes).
     pub votes: i64,
     /// Whether the comment author is an administrator.
@@ -32,4 +47,57 @@ pub struct Comment {
     /// authenticated comment, `None` = pre-migration row of unknown origin.
     #[serde(default)]
     pub is_guest: Option<bool>,
+    /// `users.id` of an authenticated commenter; what ownership checks
+    /// compare. `None` for guests and for rows whose account is gone.
+    #[serde(default)]
+    pub user_id: Option<i64>,
+    /// SHA-256 Gravatar hash of the commenter's trimmed, lowercased email.
+    /// The email itself is never stored.
+    #[serde(default)]
+    pub avatar_hash: Option<String>,
+    /// [`COMMENT_STATUS_PUBLISHED`] or [`COMMENT_STATUS_PENDING`].
+    #[serde(default = "default_comment_status")]
+    pub status: String,
+}
+
+/// A row of `comment_votes`: one user's vote on one comment.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct CommentVote {
+    pub comment_id: String,
+    /// Username of the voter.
+    pub voter_id: String,
+    pub created_at: Option<String>,
+}
+
+/// Kinds of [`CommentBlocklistEntry`].
+pub const BLOCKLIST_KINDS: &[&str] = &["word", "ip_hash", "author_name"];
+
+/// A row of `comment_blocklist`.
+///
+/// `word` entries hold back comments containing the word; `ip_hash` and
+/// `author_name` entries refuse submissions from that client or name.
+#[derive(Debug, Serialize, FromRow)]
+pub struct CommentBlocklistEntry {
+    pub id: i64,
+    /// `word`, `ip_hash` or `author_name`.
+    pub kind: String,
+    /// Lowercased word or name, or the salted hash of a client IP.
+    pub value: String,
+    pub created_by: String,
+    pub created_at: String,
+}
+
+/// Payload of `POST /api/admin/comment-blocklist`.
+#[derive(Debug, Deserialize)]
+pub struct CreateBlocklistEntryRequest {
+    pub kind: String,
+    /// The word, the author name, or for `ip_hash` the plain client IP,
+    /// which is hashed before it is stored.
+    pub value: String,
+}
+
+/// Response of `GET /api/admin/comment-blocklist`.
+#[derive(Debug, Serialize)]
+pub struct CommentBlocklistResponse {
+    pub items: Vec<CommentBlocklistEntry>,
 }
@@ -0,0 +1,133 @@
+use super::{Comment, CommentVote, SitePage, SitePost, Tutorial, TutorialSection};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// Everything a hard delete removed, as stored in `deletion_log.snapshot_json`.
+///
+/// Children that go with their parent through `ON DELETE CASCADE` are
+/// captured alongside it: a tutorial's sections, comments and their votes,
+/// a page's posts, a comment's votes. Comments on a post have no cascade and
+/// survive the post, so they are not part of a post snapshot.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "entity_type", rename_all = "snake_case")]
+pub enum DeletionSnapshot {
+    Tutorial {
+        tutorial: Tutorial,
+        #[serde(default)]
+        sections: Vec<TutorialSection>,
+        #[serde(default)]
+        comments: Vec<Comment>,
+        #[serde(default)]
+        votes: Vec<CommentVote>,
+    },
+    Page {
+        page: SitePage,
+        #[serde(default)]
+        posts: Vec<SitePost>,
+    },
+    Post {
+        post: SitePost,
+    },
+    Comment {
+        comment: Comment,
+        /// Replies removed with the comment, parents before children.
+        #[serde(default)]
+        replies: Vec<Comment>,
+        #[serde(default)]
+        votes: Vec<CommentVote>,
+    },
+}
+
+impl DeletionSnapshot {
+    /// Value of the `entity_type` column.
+    pub fn entity_type(&self) -> &'static str {
+        match self {
+            DeletionSnapshot::Tutorial { .. } => "tutorial",
+            DeletionSnapshot::Page { .. } => "page",
+            DeletionSnapshot::Post { .. } => "post",
+            DeletionSnapshot::Comment { .. } => "comment",
+        }
+    }
+
+    /// ID of the deleted parent row.
+    pub fn entity_id(&self) -> &str {
+        match self {
+            DeletionSnapshot::Tutorial { tutorial, .. } => &tutorial.id,
+            DeletionSnapshot::Page { page, .. } => &page.id,
+            DeletionSnapshot::Post { post } => &post.id,
+            DeletionSnapshot::Comment { comment, .. } => &comment.id,
+        }
+    }
+
+    /// Human-readable label for listings.
+    pub fn label(&self) -> String {
+        match self {
+            DeletionSnapshot::Tutorial { tutorial, .. } => tutorial.title.clone(),
+            DeletionSnapshot::Page { page, .. } => page.title.clone(),
+            DeletionSnapshot::Post { post } => post.title.clone(),
+            DeletionSnapshot::Comment { comment, .. } => {
+                format!("Comment by {}", comment.author)
+            }
+        }
+    }
+
+    /// Number of cascaded child rows captured with the parent.
+    pub fn child_count(&self) -> usize {
+        match self {
+            DeletionSnapshot::Tutorial {
+                sections,
+                comments,
+                votes,
+                ..
+            } => sections.len() + comments.len() + votes.len(),
+            DeletionSnapshot::Page { posts, .. } => posts.len(),
+            DeletionSnapshot::Post { .. } => 0,
+            DeletionSnapshot::Comment { replies, votes, .. } => replies.len() + votes.len(),
+        }
+    }
+}
+
+/// A row of the append-only `deletion_log` table.
+#[derive(Debug, FromRow)]
+pub struct DeletionLogRecord {
+    pub id: i64,
+    pub entity_type: String,
+    pub entity_id: String,
+    pub snapshot_json: String,
+    pub deleted_by: String,
+    pub deleted_at: String,
+}
+
+/// Listing entry for `GET /api/admin/deletion-log`. The snapshot itself is
+/// left out to keep the listing small.
+#[derive(Debug, Serialize)]
+pub struct DeletionLogItem {
+    pub id: i64,
+    pub entity_type: String,
+    pub entity_id: String,
+    /// Title of the deleted entity, if the snapshot could be read.
+    pub label: Option<String>,
+    /// Cascaded rows captured with it.
+    pub children: usize,
+    pub deleted_by: String,
+    pub deleted_at: String,
+}
+
+/// Response of `GET /api/admin/deletion-log`.
+#[derive(Debug, Serialize)]
+pub struct DeletionLogListResponse {
+    pub items: Vec<DeletionLogItem>,
+    /// Days an entry stays restorable before it is purged.
+    pub retention_days: u32,
+}
+
+/// Response of `POST /api/admin/deletion-log/{id}/restore`.
+#[derive(Debug, Serialize)]
+pub struct DeletionRestoreResponse {
+    pub entity_type: String,
+    pub entity_id: String,
+    /// Cascaded rows put back alongside the entity.
+    pub children_restored: usize,
+    /// Parts of the snapshot that could not be reconstructed.
+    pub warnings: Vec<String>,
+}
@@ -1,8 +1,10 @@
-//! Shared HTTP error helpers.
+//! Shared HTTP error type.
 //!
-//! Every handler returns errors as `(StatusCode, Json<ErrorResponse>)`. These
-//! helpers centralize the construction of that tuple so handlers don't repeat
-//! the same eight-line `map_err` blocks for logging and response shaping.
+//! Handlers, extractors and middleware fail with an [`AppError`]. It is
+//! rendered in one place, [`AppError::into_response`], as an
+//! [`ErrorResponse`]: the public message plus a machine-readable `code`
+//! that clients can branch on. The helpers below keep handlers from
+//! repeating the same `map_err` blocks for logging and response shaping.
 //!
 //! # Usage
 //! ```rust,ignore
@@ -14,35 +16,206 @@
 //! ```
 
 use super::ErrorResponse;
-use axum::{http::StatusCode, Json};
-use std::fmt::Display;
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use std::fmt::{self, Display};
 
-/// The uniform error type returned by all HTTP handlers.
-pub type ApiError = (StatusCode, Json<ErrorResponse>);
+/// The error returned by all HTTP handlers and extractors.
+#[derive(Debug)]
+pub enum AppError {
+    /// 404.
+    NotFound(String),
+    /// 400: the request is malformed or fails validation.
+    Validation(String),
+    /// 409.
+    Conflict(String),
+    /// 401.
+    Unauthorized(String),
+    /// 403.
+    Forbidden(String),
+    /// 500 for a failed query. The cause is logged when the response is
+    /// built; the client only sees "Database error".
+    Database(sqlx::Error),
+    /// 500 with a public message; the cause, if any, is logged already.
+    Internal(String),
+    /// Any other status, e.g. 413, 429 or 503.
+    Status(StatusCode, String),
+    /// An error a client is expected to react to, told apart by its own
+    /// `code` (for example `sudo_required`).
+    Coded {
+        status: StatusCode,
+        code: &'static str,
+        message: String,
+    },
+}
+
+impl AppError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::Validation(_) => StatusCode::BAD_REQUEST,
+            AppError::Conflict(_) => StatusCode::CONFLICT,
+            AppError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            AppError::Forbidden(_) => StatusCode::FORBIDDEN,
+            AppError::Database(_) | AppError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::Status(status, _) | AppError::Coded { status, .. } => *status,
+        }
+    }
+
+    /// The `code` of the response body.
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::NotFound(_) => "not_found",
+            AppError::Validation(_) => "validation_failed",
+            AppError::Conflict(_) => "conflict",
+            AppError::Unauthorized(_) => "unauthorized",
+            AppError::Forbidden(_) => "forbidden",
+            AppError::Database(_) => "database_error",
+            AppError::Internal(_) => "internal_error",
+            AppError::Status(status, _) => status_code_name(*status),
+            AppError::Coded { code, .. } => code,
+        }
+    }
+
+    /// The public message; never the cause of a [`AppError::Database`].
+    pub fn message(&self) -> &str {
+        match self {
+            AppError::Database(_) => "Database error",
+            AppError::NotFound(message)
+            | AppError::Validation(message)
+            | AppError::Conflict(message)
+            | AppError::Unauthorized(message)
+            | AppError::Forbidden(message)
+            | AppError::Internal(message)
+            | AppError::Status(_, message)
+            | AppError::Coded { message, .. } => message,
+        }
+    }
+
+    /// The status and the body this error is rendered as.
+    pub fn into_parts(self) -> (StatusCode, ErrorResponse) {
+        if let AppError::Database(err) = &self {
+            tracing::error!("Database error: {err}");
+        }
+        let body = ErrorResponse {
+            error: self.message().to_string(),
+            code: self.code().to_string(),
+        };
+        (self.status(), body)
+    }
+}
+
+/// `code` of an [`AppError::Status`].
+fn status_code_name(status: StatusCode) -> &'static str {
+    match status {
+        StatusCode::BAD_GATEWAY => "bad_gateway",
+        StatusCode::GATEWAY_TIMEOUT => "gateway_timeout",
+        StatusCode::PAYLOAD_TOO_LARGE => "payload_too_large",
+        StatusCode::RANGE_NOT_SATISFIABLE => "range_not_satisfiable",
+        StatusCode::SERVICE_UNAVAILABLE => "service_unavailable",
+        StatusCode::TOO_MANY_REQUESTS => "rate_limited",
+        StatusCode::UNPROCESSABLE_ENTITY => "unprocessable_entity",
+        status if status.is_server_error() => "internal_error",
+        _ => "error",
+    }
+}
+
+impl Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppError::Database(err) => write!(f, "database error: {err}"),
+            other => write!(f, "{} {}", other.status().as_u16(), other.message()),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let (status, body) = self.into_parts();
+        (status, Json(body)).into_response()
+    }
+}
+
+/// Classifies a database error:
+///
+/// - `RowNotFound` → [`AppError::NotFound`].
+/// - `Protocol` → [`AppError::Validation`]; the repository layer raises these
+///   for validation-style failures with messages written for end users
+///   (e.g. slug length).
+/// - Unique constraint violations → [`AppError::Conflict`].
+/// - Everything else → [`AppError::Database`].
+impl From<sqlx::Error> for AppError {
+    fn from(err: sqlx::Error) -> Self {
+        match err {
+            sqlx::Error::RowNotFound => AppError::NotFound("Not found".to_string()),
+            sqlx::Error::Protocol(message) => AppError::Validation(message),
+            sqlx::Error::Database(db_err) if db_err.is_unique_violation() => AppError::Conflict(
+                db_err
+                    .constraint()
+                    .map(|c| format!("Duplicate value violates unique constraint '{c}'"))
+                    .unwrap_or_else(|| "Duplicate value violates unique constraint".to_string()),
+            ),
+            other => AppError::Database(other),
+        }
+    }
+}
 
-/// Builds an [`ApiError`] with an arbitrary status code and public message.
-pub fn api_error(status: StatusCode, message: impl Into<String>) -> ApiError {
-    (
+/// Builds an [`AppError`] with an arbitrary status code and public message,
+/// as the variant for that status if there is one.
+pub fn api_error(status: StatusCode, message: impl Into<String>) -> AppError {
+    let message = message.into();
+    match status {
+        StatusCode::NOT_FOUND => AppError::NotFound(message),
+        StatusCode::BAD_REQUEST => AppError::Validation(message),
+        StatusCode::CONFLICT => AppError::Conflict(message),
+        StatusCode::UNAUTHORIZED => AppError::Unauthorized(message),
+        StatusCode::FORBIDDEN => AppError::Forbidden(message),
+        StatusCode::INTERNAL_SERVER_ERROR => AppError::Internal(message),
+        status => AppError::Status(status, message),
+    }
+}
+
+/// Like [`api_error`], with a machine-readable `code` for the client.
+pub fn api_error_with_code(
+    status: StatusCode,
+    code: &'static str,
+    message: impl Into<String>,
+) -> AppError {
+    AppError::Coded {
         status,
-        Json(ErrorResponse {
-            error: message.into(),
-        }),
-    )
+        code,
+        message: message.into(),
+    }
 }
 
 /// 400 Bad Request with the given public message.
-pub fn bad_request(message: impl Into<String>) -> ApiError {
-    api_error(StatusCode::BAD_REQUEST, message)
+pub fn bad_request(message: impl Into<String>) -> AppError {
+    AppError::Validation(message.into())
+}
+
+/// 401 Unauthorized with the given public message.
+pub fn unauthorized(message: impl Into<String>) -> AppError {
+    AppError::Unauthorized(message.into())
 }
 
 /// 403 Forbidden with the given public message.
-pub fn forbidden(message: impl Into<String>) -> ApiError {
-    api_error(StatusCode::FORBIDDEN, message)
+pub fn forbidden(message: impl Into<String>) -> AppError {
+    AppError::Forbidden(message.into())
 }
 
 /// 404 Not Found with the given public message.
-pub fn not_found(message: impl Into<String>) -> ApiError {
-    api_error(StatusCode::NOT_FOUND, message)
+pub fn not_found(message: impl Into<String>) -> AppError {
+    AppError::NotFound(message.into())
+}
+
+/// 409 Conflict with the given public message.
+pub fn conflict(message: impl Into<String>) -> AppError {
+    AppError::Conflict(message.into())
 }
 
 /// 500 Internal Server Error that logs the underlying cause.
@@ -50,43 +223,102 @@ pub fn not_found(message: impl Into<String>) -> ApiError {
 /// Returns a closure for use with `map_err`: the real error is logged with
 /// the given context while the client only ever sees the generic public
 /// message, so internal details (SQL errors, file paths) never leak.
-pub fn internal_error<E: Display>(public_message: &'static str) -> impl FnOnce(E) -> ApiError {
+pub fn internal_error<E: Display>(public_message: &'static str) -> impl FnOnce(E) -> AppError {
     move |err| {
         tracing::error!("{public_message}: {err}");
-        api_error(StatusCode::INTERNAL_SERVER_ERROR, public_message)
+        AppError::Internal(public_message.to_string())
     }
 }
 
 /// 500 Internal Server Error without an underlying error value to log.
-pub fn internal_error_plain(public_message: impl Into<String>) -> ApiError {
-    api_error(StatusCode::INTERNAL_SERVER_ERROR, public_message)
+pub fn internal_error_plain(public_message: impl Into<String>) -> AppError {
+    AppError::Internal(public_message.into())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    async fn body_of(err: AppError) -> (StatusCode, serde_json::Value) {
+        let response = err.into_response();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
     #[test]
     fn helpers_produce_expected_status_and_body() {
-        let (status, Json(body)) = bad_request("bad input");
-        assert_eq!(status, StatusCode::BAD_REQUEST);
-        assert_eq!(body.error, "bad input");
+        let err = bad_request("bad input");
+        assert_eq!(err.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(err.message(), "bad input");
 
-        let (status, Json(body)) = not_found("missing");
-        assert_eq!(status, StatusCode::NOT_FOUND);
-        assert_eq!(body.error, "missing");
+        let err = not_found("missing");
+        assert_eq!(err.status(), StatusCode::NOT_FOUND);
+        assert_eq!(err.message(), "missing");
 
-        let (status, Json(body)) = forbidden("nope");
-        assert_eq!(status, StatusCode::FORBIDDEN);
-        assert_eq!(body.error, "nope");
+        let err = forbidden("nope");
+        assert_eq!(err.status(), StatusCode::FORBIDDEN);
+        assert_eq!(err.message(), "nope");
+
+        assert!(matches!(
+            api_error(StatusCode::CONFLICT, "taken"),
+            AppError::Conflict(_)
+        ));
     }
 
     #[test]
     fn internal_error_hides_cause_from_client() {
         let map = internal_error("Failed to do the thing");
-        let (status, Json(body)) = map(std::io::Error::other("secret detail"));
+        let err = map(std::io::Error::other("secret detail"));
+        assert_eq!(err.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(err.message(), "Failed to do the thing");
+        assert!(!err.message().contains("secret detail"));
+    }
+
+    #[tokio::test]
+    async fn every_error_renders_as_json_with_a_code() {
+        let (status, body) = body_of(not_found("Comment not found")).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(
+            body,
+            serde_json::json!({ "error": "Comment not found", "code": "not_found" })
+        );
+
+        let (status, body) = body_of(api_error(StatusCode::TOO_MANY_REQUESTS, "Slow down")).await;
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(body["code"], "rate_limited");
+
+        let (status, body) = body_of(api_error_with_code(
+            StatusCode::FORBIDDEN,
+            "sudo_required",
+            "Confirm your password to continue",
+        ))
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["code"], "sudo_required");
+
+        let (status, body) = body_of(AppError::Database(sqlx::Error::PoolTimedOut)).await;
         assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
-        assert_eq!(body.error, "Failed to do the thing");
-        assert!(!body.error.contains("secret detail"));
+        assert_eq!(
+            body,
+            serde_json::json!({ "error": "Database error", "code": "database_error" })
+        );
+    }
+
+    #[test]
+    fn database_errors_are_classified() {
+        assert!(matches!(
+            AppError::from(sqlx::Error::RowNotFound),
+            AppError::NotFound(_)
+        ));
+        let err = AppError::from(sqlx::Error::Protocol("Slug is too long".into()));
+        assert_eq!(err.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(err.message(), "Slug is too long");
+        assert!(matches!(
+            AppError::from(sqlx::Error::PoolClosed),
+            AppError::Database(_)
+        ));
     }
 }
@@ -0,0 +1,46 @@
+use crate::warmup::WarmupReport;
+use serde::Serialize;
+
+/// Response of `GET /api/health/ready`.
+#[derive(Debug, Serialize)]
+pub struct ReadinessResponse {
+    /// `ready`, or `unavailable` (with 503) when a check failed.
+    pub status: &'static str,
+    pub checks: ReadinessChecks,
+    pub pool: PoolUtilization,
+    /// Total boot warmup time; `None` when warmup is disabled.
+    pub warmup_ms: Option<u64>,
+    /// Per-step breakdown of the warmup run.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warmup: Option<WarmupReport>,
+}
+
+/// The dependencies the readiness probe checks; all of them are critical.
+#[derive(Debug, Serialize)]
+pub struct ReadinessChecks {
+    /// A `SELECT 1` through the pool.
+    pub database: DependencyCheck,
+    /// Writing a file to `UPLOAD_DIR`, where every upload is staged.
+    pub upload_dir: DependencyCheck,
+}
+
+/// Outcome of one readiness check.
+#[derive(Debug, Serialize)]
+pub struct DependencyCheck {
+    pub ok: bool,
+    pub latency_ms: u64,
+    /// What failed, without internal details; the cause is logged.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<&'static str>,
+}
+
+/// Connections of the database pool when the probe ran.
+#[derive(Debug, Serialize)]
+pub struct PoolUtilization {
+    /// Open connections.
+    pub size: u32,
+    pub idle: u32,
+    pub in_use: u32,
+    /// `DB_MAX_CONNECTIONS`.
+    pub max: u32,
+}
@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// A Lucide icon name tutorials may use.
+#[derive(Debug, Serialize, FromRow)]
+pub struct AllowedIcon {
+    pub name: String,
+    pub created_at: String,
+}
+
+/// Payload of `POST /api/admin/icons`.
+#[derive(Debug, Deserialize)]
+pub struct CreateIconRequest {
+    /// Lucide component name, e.g. `FolderTree`.
+    pub name: String,
+}
+
+/// Response of `GET /api/admin/icons`.
+#[derive(Debug, Serialize)]
+pub struct IconListResponse {
+    pub items: Vec<AllowedIcon>,
+}
@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+
+/// Maintenance mode as stored under the `maintenance_mode` app metadata key.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MaintenanceMode {
+    pub enabled: bool,
+    /// Shown to visitors instead of the default notice.
+    pub message: Option<String>,
+}
+
+/// Payload of `POST /api/admin/maintenance`.
+#[derive(Debug, Deserialize)]
+pub struct SetMaintenanceModeRequest {
+    pub enabled: bool,
+    pub message: Option<String>,
+}
+
+/// Response of `GET /api/admin/backups`.
+#[derive(Debug, Serialize)]
+pub struct BackupListResponse {
+    /// Newest first.
+    pub items: Vec<crate::backup::BackupFile>,
+    /// Days a backup is kept.
+    pub retention_days: u32,
+}
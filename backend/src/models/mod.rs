@@ -1,11 +1,39 @@
+pub mod api_key;
+pub mod audit;
+pub mod changelog;
 pub mod comment;
+pub mod deletion;
 pub mod error;
+pub mod health;
+pub mod icon;
+pub mod maintenance;
+pub mod pagination;
+pub mod security;
+pub mod session;
+pub mod settings;
 pub mod site;
+pub mod stats;
 pub mod tutorial;
+pub mod two_factor;
+pub mod upload;
 pub mod user;
 
+pub use api_key::*;
+pub use audit::*;
+pub use changelog::*;
 pub use comment::*;
+pub use deletion::*;
 pub use error::*;
+pub use health::*;
+pub use icon::*;
+pub use maintenance::*;
+pub use pagination::*;
+pub use security::*;
+pub use session::*;
+pub use settings::*;
 pub use site::*;
+pub use stats::*;
 pub use tutorial::*;
+pub use two_factor::*;
+pub use upload::*;
 pub use user::*;
@@ -0,0 +1,67 @@
+use serde::Serialize;
+
+/// One page of a list together with the size of the whole list.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Paginated<T> {
+    pub items: Vec<T>,
+    /// Items in the whole list, across all pages.
+    pub total: i64,
+    /// Page size the page was requested with (after clamping).
+    pub limit: i64,
+    /// Position of the page's first item in the whole list.
+    pub offset: i64,
+    /// Whether items follow this page.
+    pub has_more: bool,
+}
+
+impl<T> Paginated<T> {
+    /// Wraps the page of at most `limit` items that starts `offset` items
+    /// into a list of `total`.
+    pub fn new(items: Vec<T>, total: i64, limit: i64, offset: i64) -> Self {
+        let has_more = offset.saturating_add(items.len() as i64) < total;
+        Self {
+            items,
+            total,
+            limit,
+            offset,
+            has_more,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn has_more_is_false_once_a_page_reaches_the_end() {
+        // 10 items in pages of 5: the second page ends exactly at the total.
+        assert!(Paginated::new(vec![0; 5], 10, 5, 0).has_more);
+        assert!(!Paginated::new(vec![0; 5], 10, 5, 5).has_more);
+        assert!(Paginated::new(vec![0; 5], 11, 5, 5).has_more);
+        assert!(!Paginated::new(vec![0; 1], 11, 5, 10).has_more);
+    }
+
+    #[test]
+    fn has_more_is_false_past_the_end_and_for_empty_lists() {
+        assert!(!Paginated::<i32>::new(Vec::new(), 10, 5, 10).has_more);
+        assert!(!Paginated::<i32>::new(Vec::new(), 10, 5, 50).has_more);
+        assert!(!Paginated::<i32>::new(Vec::new(), 0, 5, 0).has_more);
+    }
+
+    #[test]
+    fn serializes_with_camel_case_keys() {
+        let json = serde_json::to_value(Paginated::new(vec![1, 2], 3, 2, 0)).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "items": [1, 2],
+                "total": 3,
+                "limit": 2,
+                "offset": 0,
+                "hasMore": true
+            })
+        );
+    }
+}
@@ -0,0 +1,95 @@
+//! Rejected CSRF checks, tokens and logins, for the security summary.
+
+use serde::Serialize;
+use sqlx::FromRow;
+
+/// Which check rejected a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RejectionSource {
+    /// `CsrfGuard` (double-submit token or browser origin).
+    Csrf,
+    /// JWT verification in the auth middleware and extractors.
+    Token,
+    /// The login handler.
+    Login,
+}
+
+impl RejectionSource {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RejectionSource::Csrf => "csrf",
+            RejectionSource::Token => "token",
+            RejectionSource::Login => "login",
+        }
+    }
+}
+
+/// Why a request was rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RejectionReason {
+    /// Token past its expiry.
+    Expired,
+    /// Token signature did not verify.
+    Signature,
+    /// Token could not be parsed or failed another structural check.
+    Malformed,
+    /// Token was revoked (logout, refresh).
+    Blacklisted,
+    /// CSRF header and cookie differ, or the token belongs to another account.
+    Mismatch,
+    /// CSRF header or cookie absent.
+    Missing,
+    /// Anonymous request from a foreign browser origin.
+    Origin,
+    /// Wrong username or password.
+    InvalidCredentials,
+    /// Login attempted while locked out.
+    LockedOut,
+}
+
+impl RejectionReason {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RejectionReason::Expired => "expired",
+            RejectionReason::Signature => "signature",
+            RejectionReason::Malformed => "malformed",
+            RejectionReason::Blacklisted => "blacklisted",
+            RejectionReason::Mismatch => "mismatch",
+            RejectionReason::Missing => "missing",
+            RejectionReason::Origin => "origin",
+            RejectionReason::InvalidCredentials => "invalid_credentials",
+            RejectionReason::LockedOut => "locked_out",
+        }
+    }
+}
+
+/// Rejections of one kind on one day.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct SecurityCounterRow {
+    /// UTC date, `YYYY-MM-DD`.
+    pub day: String,
+    pub source: String,
+    pub reason: String,
+    pub count: i64,
+}
+
+/// Total over the whole window for one `(source, reason)` pair.
+#[derive(Debug, Serialize)]
+pub struct SecurityReasonTotal {
+    pub source: String,
+    pub reason: String,
+    pub count: i64,
+}
+
+/// Response of `GET /api/admin/security/summary`.
+#[derive(Debug, Serialize)]
+pub struct SecuritySummaryResponse {
+    /// Number of days covered, today included.
+    pub days: i64,
+    /// All rejections in the window.
+    pub total: i64,
+    /// Totals per source and reason, largest first.
+    pub by_reason: Vec<SecurityReasonTotal>,
+    /// Per-day rows, newest day first. Days without rejections are omitted.
+    pub daily: Vec<SecurityCounterRow>,
+}
@@ -0,0 +1,22 @@
+use serde::Serialize;
+use sqlx::FromRow;
+
+/// An active login session of the calling user.
+#[derive(Debug, Serialize, FromRow)]
+pub struct Session {
+    /// The `jti` claim of the session's tokens.
+    pub jti: String,
+    pub issued_at: String,
+    /// Unix timestamp; moves forward when the session is refreshed.
+    pub expires_at: i64,
+    pub user_agent: Option<String>,
+    /// Whether this is the session the request was made with.
+    #[sqlx(default)]
+    pub current: bool,
+}
+
+/// Response of `GET /api/auth/sessions`.
+#[derive(Debug, Serialize)]
+pub struct SessionListResponse {
+    pub items: Vec<Session>,
+}
@@ -0,0 +1,80 @@
+//! Client-facing settings contract.
+//!
+//! Assembled from the `site_meta` and `settings` content sections so the
+//! frontend gets one typed object instead of guessing at free-form JSON.
+//! Bump [`PUBLIC_SETTINGS_VERSION`] whenever a field is renamed or removed.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Schema version reported in [`PublicSettings::version`].
+pub const PUBLIC_SETTINGS_VERSION: u32 = 1;
+
+/// Site title used when `site_meta` is unset.
+pub const DEFAULT_SITE_TITLE: &str = "minos – Persönlicher Blog";
+
+/// Site description used when `site_meta` is unset.
+pub const DEFAULT_SITE_DESCRIPTION: &str =
+    "Persönliche Notizen über Technik, Projekte, Ideen und alles dazwischen.";
+
+/// Response body of `GET /api/public/settings`.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PublicSettings {
+    pub version: u32,
+    pub site: SiteSettings,
+    pub pdf_enabled: bool,
+    pub comments: CommentSettings,
+    /// Only flags explicitly marked `public` in the `settings` section.
+    pub features: BTreeMap<String, bool>,
+    pub locales: LocaleSettings,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SiteSettings {
+    pub title: String,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CommentSettings {
+    pub enabled: bool,
+    pub allow_guests: bool,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LocaleSettings {
+    pub default: String,
+    pub available: Vec<String>,
+}
+
+impl Default for PublicSettings {
+    fn default() -> Self {
+        PublicSettings {
+            version: PUBLIC_SETTINGS_VERSION,
+            site: SiteSettings {
+                title: DEFAULT_SITE_TITLE.to_string(),
+                description: DEFAULT_SITE_DESCRIPTION.to_string(),
+            },
+            pdf_enabled: true,
+            comments: CommentSettings {
+                enabled: true,
+                allow_guests: true,
+            },
+            features: BTreeMap::new(),
+            locales: LocaleSettings {
+                default: "de".to_string(),
+                available: vec!["de".to_string(), "en".to_string()],
+            },
+        }
+    }
+}
+
+/// Whether `title` is one of the stock titles shipped by earlier releases,
+/// which should be presented as the current default instead.
+pub fn is_starter_site_title(title: &str) -> bool {
+    title.starts_with("Linux Tutorial") || title.starts_with("IT Wissensportal")
+}
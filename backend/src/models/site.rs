@@ -1,9 +1,73 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use sqlx::FromRow;
+use std::collections::BTreeMap;
+
+/// Response headers a page may set through `custom_headers`. Anything that
+/// could weaken the security headers (CSP, framing, CORS) is deliberately
+/// absent.
+pub const PAGE_CUSTOM_HEADER_ALLOWLIST: &[&str] = &["X-Robots-Tag", "Link"];
+
+/// Whether a normalized robots directive list keeps the page out of search
+/// indexes, sitemaps and feeds.
+pub fn robots_excludes_indexing(meta_robots: Option<&str>) -> bool {
+    meta_robots.is_some_and(|value| {
+        value
+            .split(',')
+            .any(|token| matches!(token.trim(), "noindex" | "none"))
+    })
+}
+
+/// Sections the backend itself reads or validates. They are seeded into
+/// `content_sections` and cannot be unregistered.
+pub const BUILT_IN_CONTENT_SECTIONS: &[&str] = &[
+    "hero",             // Landing page hero
+    "tutorial_section", // Tutorial overview header
+    "header",           // Main navigation
+    "footer",           // Footer links/info
+    "site_meta",        // SEO titles/description
+    "stats",            // Numbers/stats display
+    "cta_section",      // Call to action
+    "about",            // Personal homepage introduction
+    "settings",         // System-wide toggles
+    "login",            // Custom login page text
+    "robots",           // Served verbatim as /robots.txt
+    "security_txt",     // Served verbatim as /.well-known/security.txt
+];
+
+/// A site content section name the API accepts, from `content_sections`.
+#[derive(Debug, Serialize, FromRow)]
+pub struct ContentSection {
+    pub name: String,
+    pub created_at: String,
+}
+
+/// Payload of `POST /api/admin/content-sections`.
+#[derive(Debug, Deserialize)]
+pub struct CreateContentSectionRequest {
+    /// Lowercase letters, digits and `_`, e.g. `testimonials`.
+    pub name: String,
+}
+
+/// Response of `GET /api/admin/content-sections`.
+#[derive(Debug, Serialize)]
+pub struct ContentSectionListResponse {
+    pub items: Vec<ContentSection>,
+}
+
+/// Response of `POST /api/admin/content/import`.
+#[derive(Debug, Serialize)]
+pub struct ContentImportResponse {
+    pub mode: crate::content_transfer::ImportMode,
+    pub created: usize,
+    pub overwritten: usize,
+    pub skipped: usize,
+    /// One entry per imported item, in bundle order.
+    pub results: Vec<crate::content_transfer::ImportItemResult>,
+}
 
 /// Represents dynamic content for a site section.
-#[derive(Debug, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct SiteContent {
     /// The section identifier (e.g., "features", "cta").
     pub section: String,
@@ -38,6 +102,38 @@ pub struct UpdateSiteContentRequest {
     pub content: Value,
 }
 
+/// A saved version of a site content section, from `site_content_revisions`.
+#[derive(Debug, Clone, FromRow)]
+pub struct SiteContentRevision {
+    pub id: i64,
+    pub section: String,
+    /// JSON string of the section content as saved.
+    pub content_json: String,
+    /// Username of whoever saved this version; `None` for content that
+    /// predates revision tracking.
+    pub updated_by: Option<String>,
+    pub created_at: String,
+}
+
+/// Entry of `GET /api/content/{section}/revisions`.
+#[derive(Debug, Serialize, FromRow)]
+pub struct SiteContentRevisionSummary {
+    pub id: i64,
+    pub updated_by: Option<String>,
+    pub created_at: String,
+}
+
+/// Response of `GET /api/content/{section}/revisions/{id}`.
+#[derive(Debug, Serialize)]
+pub struct SiteContentRevisionResponse {
+    pub id: i64,
+    pub section: String,
+    /// Parsed JSON content of this version.
+    pub content: Value,
+    pub updated_by: Option<String>,
+    pub created_at: String,
+}
+
 /// Represents a standalone page in the site structure.
 #[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
 pub struct SitePage {
@@ -61,6 +157,10 @@ pub struct SitePage {
     pub hero_json: String,
     /// JSON string representing the page layout configuration.
     pub layout_json: String,
+    /// Robots directives (e.g. "noindex,nofollow"); NULL uses the site default.
+    pub meta_robots: Option<String>,
+    /// JSON object of extra response headers for this page's route.
+    pub custom_headers_json: String,
     /// Creation timestamp.
     pub created_at: String,
     /// Last update timestamp.
@@ -90,6 +190,11 @@ pub struct SitePageResponse {
     pub hero: Value,
     /// Parsed layout object.
     pub layout: Value,
+    /// Robots directives for the page.
+    pub meta_robots: Option<String>,
+    /// Extra response headers; admin responses only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_headers: Option<BTreeMap<String, String>>,
     /// Creation timestamp.
     pub created_at: String,
     /// Update timestamp.
@@ -108,8 +213,33 @@ pub struct SitePageListResponse {
 pub struct SitePageWithPostsResponse {
     /// The full page details.
     pub page: SitePageResponse,
-    /// List of posts belonging to this page.
-    pub posts: Vec<SitePostResponse>,
+    /// First page of the page's published posts, in their default order;
+    /// the rest come from `GET /api/public/pages/{slug}/posts`.
+    pub posts: Vec<SitePostSummaryResponse>,
+    /// Number of published posts on the page.
+    pub posts_total: i64,
+}
+
+/// A publicly visible document listed in `/sitemap.xml`.
+#[derive(Debug, Clone, FromRow)]
+pub struct SitemapDocument {
+    /// `page`, `post` or `tutorial`.
+    pub kind: String,
+    /// Site-relative path of the document in the frontend.
+    pub path: String,
+    /// Last update timestamp.
+    pub updated_at: String,
+    /// Robots directives of the page (for posts, of their page).
+    pub meta_robots: Option<String>,
+}
+
+/// Response of the preview link endpoints.
+#[derive(Debug, Serialize)]
+pub struct PreviewTokenResponse {
+    /// Value for the `?preview=` parameter of the public page or post.
+    pub token: String,
+    /// RFC 3339 time the link stops working.
+    pub expires_at: String,
 }
 
 /// Response containing detailed view of a single post and its parent page.
@@ -119,6 +249,22 @@ pub struct SitePostDetailResponse {
     pub page: SitePageResponse,
     /// The post details.
     pub post: SitePostResponse,
+    /// The published post before this one on the page, if any.
+    pub previous: Option<AdjacentPost>,
+    /// The published post after this one on the page, if any.
+    pub next: Option<AdjacentPost>,
+    /// The post's current slug, present only when it was requested under a
+    /// former one; clients should switch to it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub canonical_slug: Option<String>,
+}
+
+/// Link to a neighboring post in [`SitePostDetailResponse`].
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct AdjacentPost {
+    pub slug: String,
+    pub title: String,
+    pub excerpt: String,
 }
 
 /// Payload to create a new site page.
@@ -146,6 +292,11 @@ pub struct CreateSitePageRequest {
     /// Layout config (default: null/empty).
     #[serde(default)]
     pub layout: Value,
+    /// Robots directives (optional).
+    pub meta_robots: Option<String>,
+    /// Extra response headers (default: none).
+    #[serde(default)]
+    pub custom_headers: BTreeMap<String, String>,
 }
 
 /// Payload to update an existing page.
@@ -169,6 +320,67 @@ pub struct UpdateSitePageRequest {
     pub hero: Option<Value>,
     /// Update layout config.
     pub layout: Option<Value>,
+    /// Update robots directives. Double Option allows clearing them.
+    pub meta_robots: Option<Option<String>>,
+    /// Replace the custom header map.
+    pub custom_headers: Option<BTreeMap<String, String>>,
+}
+
+/// Editable view of a page that JSON Patch operations are applied to
+/// (`PATCH /api/pages/{id}`).
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SitePagePatchDocument {
+    pub slug: String,
+    pub title: String,
+    pub description: String,
+    pub nav_label: Option<String>,
+    pub show_in_nav: bool,
+    pub order_index: i64,
+    pub is_published: bool,
+    pub hero: Value,
+    pub layout: Value,
+    pub meta_robots: Option<String>,
+    pub custom_headers: BTreeMap<String, String>,
+    /// Read-only; lets a patch `test` the revision it was computed against.
+    pub updated_at: String,
+}
+
+impl From<SitePageResponse> for SitePagePatchDocument {
+    fn from(page: SitePageResponse) -> Self {
+        SitePagePatchDocument {
+            slug: page.slug,
+            title: page.title,
+            description: page.description,
+            nav_label: page.nav_label,
+            show_in_nav: page.show_in_nav,
+            order_index: page.order_index,
+            is_published: page.is_published,
+            hero: page.hero,
+            layout: page.layout,
+            meta_robots: page.meta_robots,
+            custom_headers: page.custom_headers.unwrap_or_default(),
+            updated_at: page.updated_at,
+        }
+    }
+}
+
+impl From<SitePagePatchDocument> for UpdateSitePageRequest {
+    fn from(document: SitePagePatchDocument) -> Self {
+        UpdateSitePageRequest {
+            slug: Some(document.slug),
+            title: Some(document.title),
+            description: Some(document.description),
+            nav_label: Some(document.nav_label),
+            show_in_nav: Some(document.show_in_nav),
+            order_index: Some(document.order_index),
+            is_published: Some(document.is_published),
+            hero: Some(document.hero),
+            layout: Some(document.layout),
+            meta_robots: Some(document.meta_robots),
+            custom_headers: Some(document.custom_headers),
+        }
+    }
 }
 
 /// Represents a blog post or page content item.
@@ -194,10 +406,20 @@ pub struct SitePost {
     pub published_at: Option<String>,
     /// Sort order.
     pub order_index: i64,
+    /// Cover image, a `/uploads/...` path or an https URL.
+    pub cover_image_url: Option<String>,
+    /// Description for search results and link previews.
+    pub meta_description: Option<String>,
+    /// Canonical address when the post is also published elsewhere.
+    pub canonical_url: Option<String>,
     /// Creation timestamp.
     pub created_at: String,
     /// Last update timestamp.
     pub updated_at: String,
+    /// Lowercase tags from `post_tags`, filled in by the repository.
+    #[sqlx(skip)]
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 /// Public response for a site post.
@@ -215,6 +437,10 @@ pub struct SitePostResponse {
     pub excerpt: String,
     /// Content (Markdown).
     pub content_markdown: String,
+    /// `content_markdown` rendered to sanitized HTML; only with
+    /// `?format=html`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_html: Option<String>,
     /// Publication status.
     pub is_published: bool,
     /// Comment status.
@@ -223,10 +449,114 @@ pub struct SitePostResponse {
     pub published_at: Option<String>,
     /// Sort order.
     pub order_index: i64,
+    /// Cover image, a `/uploads/...` path or an https URL.
+    pub cover_image_url: Option<String>,
+    /// Description for search results and link previews.
+    pub meta_description: Option<String>,
+    /// Canonical address when the post is also published elsewhere.
+    pub canonical_url: Option<String>,
+    /// Creation time.
+    pub created_at: String,
+    /// Update time.
+    pub updated_at: String,
+    /// Lowercase tags, alphabetically.
+    pub tags: Vec<String>,
+    /// All-time view count; only reported on the editor endpoints.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub view_count: Option<i64>,
+    /// Headings of `content_markdown`; only on the public post detail.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub toc: Option<Vec<crate::markdown::TocEntry>>,
+}
+
+/// A published post as listed on its page: everything but the body.
+#[derive(Debug, Clone, FromRow)]
+pub struct SitePostSummary {
+    pub id: String,
+    pub page_id: String,
+    pub title: String,
+    pub slug: String,
+    pub excerpt: String,
+    pub published_at: Option<String>,
+    pub order_index: i64,
+    pub created_at: String,
+    pub updated_at: String,
+    /// Words in the body, from `content_stats`.
+    pub word_count: i64,
+    /// Lowercase tags from `post_tags`, filled in by the repository.
+    #[sqlx(skip)]
+    pub tags: Vec<String>,
+}
+
+/// Public listing entry for a post; [`SitePostResponse`] without the body.
+#[derive(Debug, Serialize)]
+pub struct SitePostSummaryResponse {
+    /// Post ID.
+    pub id: String,
+    /// Parent Page ID.
+    pub page_id: String,
+    /// Title.
+    pub title: String,
+    /// Slug.
+    pub slug: String,
+    /// Excerpt.
+    pub excerpt: String,
+    /// Publishing timestamp.
+    pub published_at: Option<String>,
+    /// Sort order.
+    pub order_index: i64,
     /// Creation time.
     pub created_at: String,
     /// Update time.
     pub updated_at: String,
+    /// Words in the body, for reading time estimates.
+    pub word_count: i64,
+    /// Lowercase tags, alphabetically.
+    pub tags: Vec<String>,
+}
+
+impl From<SitePostSummary> for SitePostSummaryResponse {
+    fn from(post: SitePostSummary) -> Self {
+        SitePostSummaryResponse {
+            id: post.id,
+            page_id: post.page_id,
+            title: post.title,
+            slug: post.slug,
+            excerpt: post.excerpt,
+            published_at: post.published_at,
+            order_index: post.order_index,
+            created_at: post.created_at,
+            updated_at: post.updated_at,
+            word_count: post.word_count,
+            tags: post.tags,
+        }
+    }
+}
+
+/// A site post as it was before an update, from `site_post_revisions`.
+#[derive(Debug, Serialize, FromRow)]
+pub struct SitePostRevision {
+    /// Number of the revision, counting up from 1 per post.
+    pub revision: i64,
+    pub title: String,
+    pub slug: String,
+    pub excerpt: String,
+    pub content_markdown: String,
+    pub is_published: bool,
+    pub published_at: Option<String>,
+    /// Username of whoever saved the update that replaced this state.
+    pub editor: String,
+    /// When this state was replaced.
+    pub created_at: String,
+}
+
+/// Entry of `GET /api/posts/{id}/revisions`.
+#[derive(Debug, Serialize, FromRow)]
+pub struct SitePostRevisionSummary {
+    pub revision: i64,
+    pub title: String,
+    pub editor: String,
+    pub created_at: String,
 }
 
 /// List response for posts.
@@ -257,6 +587,18 @@ pub struct CreateSitePostRequest {
     pub published_at: Option<String>,
     /// Sort order.
     pub order_index: Option<i64>,
+    /// Tags (default: none).
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Cover image, a `/uploads/...` path or an https URL.
+    #[serde(default)]
+    pub cover_image_url: Option<String>,
+    /// Description for search results and link previews (max 300 characters).
+    #[serde(default)]
+    pub meta_description: Option<String>,
+    /// Canonical address, an absolute http(s) URL.
+    #[serde(default)]
+    pub canonical_url: Option<String>,
 }
 
 /// Helper to default `allow_comments` to true.
@@ -283,6 +625,63 @@ pub struct UpdateSitePostRequest {
     pub published_at: Option<Option<String>>,
     /// Update sort order.
     pub order_index: Option<i64>,
+    /// Replace the tags.
+    pub tags: Option<Vec<String>>,
+    /// Update the cover image; an empty value removes it.
+    pub cover_image_url: Option<Option<String>>,
+    /// Update the meta description; an empty value removes it.
+    pub meta_description: Option<Option<String>>,
+    /// Update the canonical URL; an empty value removes it.
+    pub canonical_url: Option<Option<String>>,
+}
+
+/// A month of the public post archive with its number of posts.
+#[derive(Debug, Serialize, FromRow, PartialEq)]
+pub struct ArchiveMonth {
+    pub year: i64,
+    /// 1 to 12.
+    pub month: i64,
+    pub count: i64,
+}
+
+/// A page in the trash, as listed by `GET /api/admin/trash`.
+#[derive(Debug, Serialize, FromRow)]
+pub struct TrashedPage {
+    pub id: String,
+    pub slug: String,
+    pub title: String,
+    /// Posts that come back with the page when it is restored.
+    pub post_count: i64,
+    /// When it was moved to the trash.
+    pub deleted_at: String,
+    pub updated_at: String,
+}
+
+/// A post in the trash, as listed by `GET /api/admin/trash`.
+#[derive(Debug, Serialize, FromRow)]
+pub struct TrashedPost {
+    pub id: String,
+    pub page_id: String,
+    pub page_title: String,
+    pub title: String,
+    pub slug: String,
+    /// When it was moved to the trash.
+    pub deleted_at: String,
+    pub updated_at: String,
+}
+
+/// Response of `GET /api/admin/trash`.
+#[derive(Debug, Serialize)]
+pub struct TrashResponse {
+    pub pages: Vec<TrashedPage>,
+    pub posts: Vec<TrashedPost>,
+}
+
+/// A tag with the number of published posts carrying it.
+#[derive(Debug, Serialize, FromRow)]
+pub struct TagCount {
+    pub tag: String,
+    pub count: i64,
 }
 
 /// Item in the navigation menu.
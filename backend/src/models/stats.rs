@@ -0,0 +1,162 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// Aggregated content volume for the admin dashboard.
+#[derive(Debug, Serialize)]
+pub struct ContentStatsResponse {
+    /// RFC 3339 timestamp of when the overview was computed.
+    pub generated_at: String,
+    /// Age in days after which a document is listed in `stale_documents`.
+    pub stale_after_days: i64,
+    /// Totals across all tutorials.
+    pub tutorials: ContentAreaStats,
+    /// Totals for the posts of each site page.
+    pub pages: Vec<PageContentStats>,
+    /// Documents not updated within `stale_after_days`, oldest first.
+    pub stale_documents: Vec<StaleDocument>,
+}
+
+/// Word statistics over a set of documents.
+#[derive(Debug, Default, Serialize)]
+pub struct ContentAreaStats {
+    /// Number of documents.
+    pub documents: i64,
+    /// Documents visible to the public.
+    pub published: i64,
+    /// Documents not (yet) visible to the public.
+    pub drafts: i64,
+    /// Sum of words over all documents.
+    pub total_words: i64,
+    /// Mean words per document (0 when there are none).
+    pub average_words: f64,
+    /// Document with the most words.
+    pub longest: Option<DocumentWordCount>,
+    /// Document with the fewest words.
+    pub shortest: Option<DocumentWordCount>,
+}
+
+/// Per-page statistics over the page's posts.
+#[derive(Debug, Serialize)]
+pub struct PageContentStats {
+    /// Page ID.
+    pub page_id: String,
+    /// Page slug.
+    pub slug: String,
+    /// Page title.
+    pub title: String,
+    /// Whether the page itself is published.
+    pub is_published: bool,
+    /// Statistics over the page's posts.
+    pub posts: ContentAreaStats,
+}
+
+/// A document together with its cached word count.
+#[derive(Debug, Serialize, FromRow)]
+pub struct DocumentWordCount {
+    /// Document ID.
+    pub id: String,
+    /// Document title.
+    pub title: String,
+    /// Cached word count.
+    pub words: i64,
+}
+
+/// A document that is a candidate for review.
+#[derive(Debug, Serialize, FromRow)]
+pub struct StaleDocument {
+    /// `tutorial` or `post`.
+    pub kind: String,
+    /// Document ID.
+    pub id: String,
+    /// Document title.
+    pub title: String,
+    /// Last update timestamp.
+    pub updated_at: String,
+}
+
+/// Query parameters of `GET /api/admin/stats/views`.
+#[derive(Debug, Deserialize)]
+pub struct ViewStatsQuery {
+    /// `tutorial` (default) or `post`.
+    #[serde(default)]
+    pub entity_type: Option<String>,
+    /// First day to include (`YYYY-MM-DD`).
+    #[serde(default)]
+    pub from: Option<String>,
+    /// Last day to include (`YYYY-MM-DD`).
+    #[serde(default)]
+    pub to: Option<String>,
+}
+
+/// Views of a single tutorial or post over the requested range.
+#[derive(Debug, Serialize, FromRow)]
+pub struct ContentViewTotal {
+    /// Tutorial or post ID.
+    pub entity_id: String,
+    /// Current title, `None` if the document was deleted.
+    pub title: Option<String>,
+    /// Number of views.
+    pub views: i64,
+}
+
+/// Response of `GET /api/admin/stats/views`.
+#[derive(Debug, Serialize)]
+pub struct ViewStatsResponse {
+    /// `tutorial` or `post`.
+    pub entity_type: String,
+    /// First day included, if bounded.
+    pub from: Option<String>,
+    /// Last day included, if bounded.
+    pub to: Option<String>,
+    /// Per-entity totals, most viewed first.
+    pub totals: Vec<ContentViewTotal>,
+}
+
+/// Query parameters of `GET /api/admin/search/analytics`.
+#[derive(Debug, Deserialize)]
+pub struct SearchAnalyticsQuery {
+    /// Number of days to cover, today included (default 30).
+    #[serde(default)]
+    pub days: Option<i64>,
+}
+
+/// How often a normalized query was searched.
+#[derive(Debug, Serialize, FromRow)]
+pub struct SearchQueryCount {
+    /// Normalized query.
+    pub query: String,
+    /// Number of searches.
+    pub searches: i64,
+    /// Number of distinct client IPs that searched it.
+    pub visitors: i64,
+    /// Time of the latest search.
+    pub last_searched_at: String,
+}
+
+/// Search volume of one day.
+#[derive(Debug, Serialize, FromRow)]
+pub struct SearchDailyVolume {
+    /// `YYYY-MM-DD`.
+    pub day: String,
+    /// Number of searches.
+    pub searches: i64,
+    /// Searches that found nothing.
+    pub zero_results: i64,
+}
+
+/// Response of `GET /api/admin/search/analytics`.
+#[derive(Debug, Serialize)]
+pub struct SearchAnalyticsResponse {
+    /// Number of days covered.
+    pub days: i64,
+    /// Days logged searches are kept for.
+    pub retention_days: u32,
+    /// Searches over the covered days.
+    pub total_searches: i64,
+    /// Most frequent queries.
+    pub top_queries: Vec<SearchQueryCount>,
+    /// Most frequent queries that found nothing.
+    pub zero_result_queries: Vec<SearchQueryCount>,
+    /// Searches per day, oldest first; days without searches are left out.
+    pub daily: Vec<SearchDailyVolume>,
+}
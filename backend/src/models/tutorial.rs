@@ -1,7 +1,21 @@
+use super::Paginated;
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use std::convert::TryFrom;
 
+/// `status` of a tutorial that only editors can see.
+pub const TUTORIAL_STATUS_DRAFT: &str = "draft";
+/// `status` of a publicly visible tutorial.
+pub const TUTORIAL_STATUS_PUBLISHED: &str = "published";
+
+fn default_tutorial_status() -> String {
+    TUTORIAL_STATUS_PUBLISHED.to_string()
+}
+
+fn default_allow_comments() -> bool {
+    true
+}
+
 /// Represents a coding tutorial.
 #[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
 pub struct Tutorial {
@@ -21,12 +35,176 @@ pub struct Tutorial {
     pub content: String,
     /// Version number for optimistic concurrency.
     pub version: i64,
+    /// `draft` or `published`. Deletion log snapshots taken before drafts
+    /// existed deserialize as published.
+    #[serde(default = "default_tutorial_status")]
+    pub status: String,
+    /// When the tutorial was last published; `None` while it is a draft.
+    #[serde(default)]
+    pub published_at: Option<String>,
+    /// Position in listings, lowest first.
+    #[serde(default)]
+    pub order_index: i64,
+    /// Whether new comments are accepted. Existing comments stay readable.
+    #[serde(default = "default_allow_comments")]
+    pub allow_comments: bool,
+    /// Username of the author; `None` for tutorials from before authors
+    /// were recorded.
+    #[serde(default)]
+    pub created_by: Option<String>,
+    /// Username of the last editor.
+    #[serde(default)]
+    pub updated_by: Option<String>,
     /// Creation timestamp.
     pub created_at: String,
     /// Update timestamp.
     pub updated_at: String,
 }
 
+impl Tutorial {
+    /// Whether anonymous visitors may see the tutorial.
+    pub fn is_published(&self) -> bool {
+        self.status == TUTORIAL_STATUS_PUBLISHED
+    }
+}
+
+/// A tutorial in the trash, as listed by `GET /api/admin/tutorials/trash`.
+#[derive(Debug, Serialize, FromRow)]
+pub struct TrashedTutorial {
+    pub id: String,
+    pub title: String,
+    pub description: String,
+    pub status: String,
+    /// When it was moved to the trash.
+    pub deleted_at: String,
+    pub updated_at: String,
+}
+
+/// One tutorial's position, as returned by `PUT /api/tutorials/order`.
+#[derive(Debug, Serialize)]
+pub struct TutorialOrderEntry {
+    pub id: String,
+    pub order_index: i64,
+}
+
+/// A chapter of a tutorial, from `tutorial_sections`.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct TutorialSection {
+    pub tutorial_id: String,
+    /// Slug unique within the tutorial; doubles as the deep-link anchor.
+    pub section_id: String,
+    pub title: String,
+    /// Markdown.
+    pub content: String,
+    pub order_index: i64,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// A section as returned by the section endpoints and inside
+/// [`TutorialResponse::sections`].
+#[derive(Debug, Serialize)]
+pub struct TutorialSectionResponse {
+    /// Section slug, stable across edits; link to it as `#{id}`.
+    pub id: String,
+    pub title: String,
+    pub content: String,
+    /// `content` rendered to sanitized HTML; only with `?format=html`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_html: Option<String>,
+    pub order_index: i64,
+    pub updated_at: String,
+}
+
+impl From<TutorialSection> for TutorialSectionResponse {
+    fn from(section: TutorialSection) -> Self {
+        TutorialSectionResponse {
+            id: section.section_id,
+            title: section.title,
+            content: section.content,
+            content_html: None,
+            order_index: section.order_index,
+            updated_at: section.updated_at,
+        }
+    }
+}
+
+/// Payload of `POST /api/tutorials/{id}/sections`.
+#[derive(Debug, Deserialize)]
+pub struct CreateTutorialSectionRequest {
+    /// Section slug; derived from the title when omitted.
+    #[serde(default)]
+    pub id: Option<String>,
+    pub title: String,
+    pub content: String,
+}
+
+/// Payload of `PUT /api/tutorials/{id}/sections/{section_id}`.
+#[derive(Debug, Deserialize)]
+pub struct UpdateTutorialSectionRequest {
+    pub title: Option<String>,
+    pub content: Option<String>,
+}
+
+/// A tutorial as it was before an update, from `tutorial_revisions`.
+#[derive(Debug, FromRow)]
+pub struct TutorialRevision {
+    /// The tutorial's version at the time.
+    pub version: i64,
+    pub title: String,
+    pub description: String,
+    pub content: String,
+    /// JSON string, like [`Tutorial::topics`].
+    pub topics: String,
+    /// Username of whoever saved the update that replaced this version.
+    pub editor: String,
+    /// When this version was replaced.
+    pub created_at: String,
+}
+
+/// Response of `GET /api/tutorials/{id}/revisions/{version}`.
+#[derive(Debug, Serialize)]
+pub struct TutorialRevisionResponse {
+    pub version: i64,
+    pub title: String,
+    pub description: String,
+    pub content: String,
+    pub topics: Vec<String>,
+    pub editor: String,
+    pub created_at: String,
+}
+
+impl From<TutorialRevision> for TutorialRevisionResponse {
+    fn from(revision: TutorialRevision) -> Self {
+        let topics = serde_json::from_str(&revision.topics).unwrap_or_else(|e| {
+            tracing::error!(
+                "Failed to parse topics JSON of tutorial revision {}: {}",
+                revision.version,
+                e
+            );
+            Vec::new()
+        });
+        TutorialRevisionResponse {
+            version: revision.version,
+            title: revision.title,
+            description: revision.description,
+            content: revision.content,
+            topics,
+            editor: revision.editor,
+            created_at: revision.created_at,
+        }
+    }
+}
+
+/// Entry of `GET /api/tutorials/{id}/revisions`.
+#[derive(Debug, Serialize, FromRow)]
+pub struct TutorialRevisionSummary {
+    pub version: i64,
+    pub title: String,
+    pub editor: String,
+    pub created_at: String,
+}
+
 /// Payload to create a new tutorial.
 #[derive(Debug, Deserialize)]
 pub struct CreateTutorialRequest {
@@ -44,6 +222,27 @@ pub struct CreateTutorialRequest {
     pub content: String,
     /// Optional ID (for pre-determined UUIDs).
     pub id: Option<String>,
+    /// `draft` or `published` (the default).
+    #[serde(default)]
+    pub status: Option<String>,
+    /// Whether readers may comment (default: true).
+    #[serde(default = "default_allow_comments")]
+    pub allow_comments: bool,
+}
+
+/// Optional payload of `POST /api/tutorials/{id}/duplicate`.
+#[derive(Debug, Default, Deserialize)]
+pub struct DuplicateTutorialRequest {
+    /// ID for the copy; a UUID is generated when omitted.
+    #[serde(default)]
+    pub id: Option<String>,
+}
+
+/// Payload of `POST /api/tutorials/{id}/rename`.
+#[derive(Debug, Deserialize)]
+pub struct RenameTutorialRequest {
+    /// The tutorial's new ID, which is also its URL slug.
+    pub new_id: String,
 }
 
 /// Payload to update an existing tutorial.
@@ -61,6 +260,16 @@ pub struct UpdateTutorialRequest {
     pub topics: Option<Vec<String>>,
     /// Update content.
     pub content: Option<String>,
+    /// Move to `draft` or `published`.
+    #[serde(default)]
+    pub status: Option<String>,
+    /// Open or close the tutorial for new comments.
+    #[serde(default)]
+    pub allow_comments: Option<bool>,
+    /// The `version` the client's copy was read at. When given, the update
+    /// is rejected with `409 Conflict` if the tutorial has moved on since.
+    #[serde(default)]
+    pub expected_version: Option<i64>,
 }
 
 /// Public response for a tutorial.
@@ -80,12 +289,57 @@ pub struct TutorialResponse {
     pub topics: Vec<String>,
     /// Content.
     pub content: String,
+    /// `content` rendered to sanitized HTML; only with `?format=html`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_html: Option<String>,
+    /// Runnable `shell-session` blocks found in `content`.
+    pub command_blocks: Vec<CommandBlock>,
     /// Version.
     pub version: i64,
+    /// `draft` or `published`.
+    pub status: String,
+    /// Last publication time.
+    pub published_at: Option<String>,
+    /// Whether new comments are accepted.
+    pub allow_comments: bool,
+    /// Author's username, or the public author name for visitors.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_by: Option<String>,
+    /// Last editor's username, or the public author name for visitors.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_by: Option<String>,
     /// Created at.
     pub created_at: String,
     /// Updated at.
     pub updated_at: String,
+    /// Set to `id` when the tutorial was requested under a former ID, so
+    /// clients can update their links.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub canonical_id: Option<String>,
+    /// All-time view count; only reported to editors.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub view_count: Option<i64>,
+    /// Ordered chapters, on `GET /api/tutorials/{id}`. Empty for tutorials
+    /// kept as a single document in `content`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sections: Option<Vec<TutorialSectionResponse>>,
+    /// Headings of `content`, on `GET /api/tutorials/{id}`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub toc: Option<Vec<crate::markdown::TocEntry>>,
+}
+
+/// A copyable command extracted from a `shell-session` code block in a
+/// tutorial's content (see [`crate::markdown::command_blocks`]).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CommandBlock {
+    /// Position among the content's `shell-session` blocks, counted from 0.
+    pub index: usize,
+    /// Command text without prompts; continuation lines are kept.
+    pub command: String,
+    /// Description from the block header.
+    pub description: Option<String>,
+    /// Output shown after the command, if any.
+    pub expected_output: Option<String>,
 }
 
 /// Summary response (excludes heavy content).
@@ -105,10 +359,52 @@ pub struct TutorialSummaryResponse {
     pub topics: Vec<String>,
     /// Version.
     pub version: i64,
+    /// `draft` or `published`.
+    pub status: String,
+    /// Last publication time.
+    pub published_at: Option<String>,
+    /// Position in listings, lowest first.
+    pub order_index: i64,
+    /// Author's username, or the public author name for visitors.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_by: Option<String>,
+    /// Last editor's username, or the public author name for visitors.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_by: Option<String>,
     /// Created at.
     pub created_at: String,
     /// Updated at.
     pub updated_at: String,
+    /// All-time view count; only reported to editors.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub view_count: Option<i64>,
+}
+
+/// Body of `GET /api/tutorials`.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum TutorialListResponse {
+    /// A bare array, as returned before the envelope existed.
+    Items(Vec<TutorialSummaryResponse>),
+    /// With `?envelope=true`.
+    Page(Paginated<TutorialSummaryResponse>),
+}
+
+/// A topic with the number of published tutorials tagged with it.
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct TopicCount {
+    pub topic: String,
+    pub count: i64,
+}
+
+/// Body of `GET /api/search/topics`.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum TopicListResponse {
+    /// Topic names, as returned before counts existed.
+    Names(Vec<String>),
+    /// With `?with_counts=true`.
+    Counts(Vec<TopicCount>),
 }
 
 impl TryFrom<Tutorial> for TutorialResponse {
@@ -135,10 +431,21 @@ impl TryFrom<Tutorial> for TutorialResponse {
             icon: tutorial.icon,
             color: tutorial.color,
             topics,
+            command_blocks: crate::markdown::extract_command_blocks(&tutorial.content),
             content: tutorial.content,
+            content_html: None,
             version: tutorial.version,
+            status: tutorial.status,
+            published_at: tutorial.published_at,
+            allow_comments: tutorial.allow_comments,
+            created_by: tutorial.created_by,
+            updated_by: tutorial.updated_by,
             created_at: tutorial.created_at,
             updated_at: tutorial.updated_at,
+            canonical_id: None,
+            view_count: None,
+            sections: None,
+            toc: None,
         })
     }
 }
@@ -166,22 +473,218 @@ impl TryFrom<Tutorial> for TutorialSummaryResponse {
             color: tutorial.color,
             topics,
             version: tutorial.version,
+            status: tutorial.status,
+            published_at: tutorial.published_at,
+            order_index: tutorial.order_index,
+            created_by: tutorial.created_by,
+            updated_by: tutorial.updated_by,
             created_at: tutorial.created_at,
             updated_at: tutorial.updated_at,
+            view_count: None,
         })
     }
 }
 
+/// Editable view of a tutorial that JSON Patch operations are applied to
+/// (`PATCH /api/tutorials/{id}`).
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TutorialPatchDocument {
+    pub title: String,
+    pub description: String,
+    pub icon: String,
+    pub color: String,
+    pub topics: Vec<String>,
+    pub content: String,
+    pub status: String,
+    pub allow_comments: bool,
+    /// Read-only; lets a patch `test` the version it was computed against.
+    pub version: i64,
+}
+
+impl From<TutorialResponse> for TutorialPatchDocument {
+    fn from(tutorial: TutorialResponse) -> Self {
+        TutorialPatchDocument {
+            title: tutorial.title,
+            description: tutorial.description,
+            icon: tutorial.icon,
+            color: tutorial.color,
+            topics: tutorial.topics,
+            content: tutorial.content,
+            status: tutorial.status,
+            allow_comments: tutorial.allow_comments,
+            version: tutorial.version,
+        }
+    }
+}
+
+impl From<TutorialPatchDocument> for UpdateTutorialRequest {
+    fn from(document: TutorialPatchDocument) -> Self {
+        UpdateTutorialRequest {
+            title: Some(document.title),
+            description: Some(document.description),
+            icon: Some(document.icon),
+            color: Some(document.color),
+            topics: Some(document.topics),
+            content: Some(document.content),
+            status: Some(document.status),
+            allow_comments: Some(document.allow_comments),
+            expected_version: None,
+        }
+    }
+}
+
+/// Current version of the single-tutorial export document.
+///
+/// Bump this whenever the shape of [`TutorialExportDocument`] changes in a
+/// way older importers would misread, and keep accepting older versions.
+pub const TUTORIAL_EXPORT_FORMAT: u32 = 1;
+
+/// Self-contained export of one tutorial, as produced by
+/// `GET /api/tutorials/{id}/export.json` and accepted by
+/// `POST /api/admin/tutorials/import-one`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TutorialExportDocument {
+    /// Document format version (see [`TUTORIAL_EXPORT_FORMAT`]).
+    pub format: u32,
+    /// RFC 3339 timestamp of the export. Informational only.
+    #[serde(default)]
+    pub exported_at: Option<String>,
+    /// The tutorial itself.
+    pub tutorial: TutorialExport,
+    /// Comments on the tutorial, present only when requested on export.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comments: Option<Vec<TutorialCommentExport>>,
+}
+
+/// Tutorial fields carried by a [`TutorialExportDocument`].
+///
+/// `version`, `created_at` and `updated_at` describe the source instance and
+/// are not restored on import.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TutorialExport {
+    /// Source ID.
+    pub id: String,
+    /// Title.
+    pub title: String,
+    /// Description.
+    pub description: String,
+    /// Icon.
+    pub icon: String,
+    /// Color.
+    pub color: String,
+    /// Topics.
+    pub topics: Vec<String>,
+    /// Content.
+    pub content: String,
+    /// `draft` or `published`; documents from before drafts existed are
+    /// published.
+    #[serde(default = "default_tutorial_status")]
+    pub status: String,
+    /// Whether readers may comment; absent in documents from before the
+    /// setting existed.
+    #[serde(default = "default_allow_comments")]
+    pub allow_comments: bool,
+    /// Source version.
+    #[serde(default)]
+    pub version: Option<i64>,
+    /// Source creation timestamp.
+    #[serde(default)]
+    pub created_at: Option<String>,
+    /// Source update timestamp.
+    #[serde(default)]
+    pub updated_at: Option<String>,
+}
+
+/// Front matter of a standalone Markdown export, as produced by
+/// `GET /api/tutorials/{id}/export` and accepted by
+/// `POST /api/admin/tutorials/import-markdown`. The Markdown after it is the
+/// tutorial's `content`; exported timestamps are informational and ignored.
+#[derive(Debug, Deserialize)]
+pub struct TutorialFrontMatter {
+    /// Tutorial ID; a UUID is generated on import when absent.
+    #[serde(default)]
+    pub id: Option<String>,
+    pub title: String,
+    pub description: String,
+    #[serde(default)]
+    pub topics: Vec<String>,
+    pub icon: String,
+    pub color: String,
+    /// `draft` or `published`; new tutorials default to published and
+    /// existing ones keep their status.
+    #[serde(default)]
+    pub status: Option<String>,
+    #[serde(default)]
+    pub allow_comments: Option<bool>,
+    /// Version the file was exported at. Importing over an existing
+    /// tutorial that has moved on since is a conflict.
+    #[serde(default)]
+    pub version: Option<i64>,
+}
+
+/// A comment carried by a [`TutorialExportDocument`].
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TutorialCommentExport {
+    /// Source comment ID.
+    pub id: String,
+    /// Source ID of the comment this one replies to.
+    #[serde(default)]
+    pub parent_id: Option<String>,
+    /// Display name.
+    pub author: String,
+    /// Comment body.
+    pub content: String,
+    /// Creation timestamp.
+    pub created_at: String,
+    /// Timestamp of the last edit, if the comment was edited.
+    #[serde(default)]
+    pub edited_at: Option<String>,
+    /// Vote count at export time.
+    #[serde(default)]
+    pub votes: i64,
+    /// Whether an administrator wrote the comment.
+    #[serde(default)]
+    pub is_admin: bool,
+    /// Real username of an authenticated author.
+    #[serde(default)]
+    pub author_username: Option<String>,
+    /// Gravatar hash of the author's email. Exports never carry the email.
+    #[serde(default)]
+    pub avatar_hash: Option<String>,
+    /// Guest marker (see [`crate::models::Comment::is_guest`]).
+    #[serde(default)]
+    pub is_guest: Option<bool>,
+    /// `published`, or `pending` for a comment held back by the blocklist.
+    #[serde(default = "default_comment_export_status")]
+    pub status: String,
+}
+
+fn default_comment_export_status() -> String {
+    crate::models::COMMENT_STATUS_PUBLISHED.to_string()
+}
+
+/// Result of `POST /api/admin/tutorials/import-one` and
+/// `POST /api/admin/tutorials/import-markdown`.
+#[derive(Debug, Serialize)]
+pub struct TutorialImportResponse {
+    /// `created`, `updated`, `overwritten` or `skipped`.
+    pub outcome: &'static str,
+    /// The tutorial as stored after the import.
+    pub tutorial: TutorialResponse,
+    /// Number of comments inserted.
+    pub comments_imported: usize,
+    /// Comments of the document that were not inserted: those whose ID is
+    /// already taken, or all of them when the tutorial was skipped.
+    pub comments_skipped: usize,
+}
+
 /// Standard error response.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ErrorResponse {
     /// The error message.
     pub error: String,
-}
-
-/// Response for file uploads.
-#[derive(Debug, Serialize, Deserialize)]
-pub struct UploadResponse {
-    /// The URL of the uploaded file.
-    pub url: String,
+    /// Stable, machine-readable identifier of the error, e.g. `not_found`
+    /// or `sudo_required`; see [`crate::models::AppError::code`].
+    pub code: String,
 }
@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// The two-factor columns of a `users` row.
+#[derive(Debug, FromRow)]
+pub struct TwoFactorState {
+    pub user_id: i64,
+    pub username: String,
+    pub role: String,
+    /// Encrypted secret; set from the start of enrollment.
+    pub totp_secret: Option<String>,
+    pub totp_enabled: bool,
+    pub totp_last_step: Option<i64>,
+}
+
+/// A pending second login step, as stored in `two_factor_challenges`.
+#[derive(Debug, FromRow)]
+pub struct TwoFactorChallenge {
+    pub user_id: i64,
+    pub expires_at: String,
+    pub attempts: i64,
+    /// The "remember me" choice made at the password step.
+    pub remember_me: bool,
+}
+
+/// Response of `POST /api/auth/2fa/setup`.
+#[derive(Debug, Serialize)]
+pub struct TwoFactorSetupResponse {
+    /// Base32 secret for manual entry.
+    pub secret: String,
+    /// `otpauth://` URI for QR codes.
+    pub otpauth_uri: String,
+}
+
+/// Payload of `POST /api/auth/2fa/verify`.
+#[derive(Debug, Deserialize)]
+pub struct TwoFactorVerifyRequest {
+    /// A current code from the authenticator app.
+    pub code: String,
+}
+
+/// Response of `POST /api/auth/2fa/verify`. The recovery codes are shown
+/// only this once; the server keeps just their hashes.
+#[derive(Debug, Serialize)]
+pub struct TwoFactorEnabledResponse {
+    pub recovery_codes: Vec<String>,
+}
+
+/// Payload of `POST /api/auth/2fa/disable`.
+#[derive(Debug, Deserialize)]
+pub struct TwoFactorDisableRequest {
+    pub password: String,
+    /// An authenticator code or an unused recovery code.
+    pub code: String,
+}
+
+/// Returned by `POST /api/auth/login` (with `202 Accepted`) instead of a
+/// session when the account has two-factor login enabled.
+#[derive(Debug, Serialize)]
+pub struct TwoFactorChallengeResponse {
+    pub two_factor_required: bool,
+    /// Exchanged, together with a code, at `POST /api/auth/login/2fa`.
+    pub challenge: String,
+    /// Seconds until the challenge expires.
+    pub expires_in: i64,
+}
+
+/// Payload of `POST /api/auth/login/2fa`.
+#[derive(Debug, Deserialize)]
+pub struct TwoFactorLoginRequest {
+    pub challenge: String,
+    /// An authenticator code or an unused recovery code.
+    pub code: String,
+}
@@ -0,0 +1,81 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// A file stored in the upload directory, as recorded in `uploads`.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Upload {
+    pub id: String,
+    /// Name on disk, `<id>.<extension>`; served under `/uploads/`.
+    pub filename: String,
+    /// File name the client sent, for display only.
+    pub original_name: String,
+    pub mime: String,
+    pub size_bytes: i64,
+    /// Pixel dimensions, upright.
+    pub width: Option<i64>,
+    pub height: Option<i64>,
+    /// Text alternative for screen readers. Plain text, like `caption`:
+    /// escape it wherever it goes into HTML.
+    pub alt: Option<String>,
+    pub caption: Option<String>,
+    pub uploaded_by: Option<String>,
+    pub created_at: String,
+    /// Narrower WebP copies, narrowest first; filled in by the repository.
+    #[sqlx(skip)]
+    #[serde(default)]
+    pub variants: Vec<UploadVariant>,
+}
+
+/// A resized WebP copy of an uploaded image, as recorded in
+/// `upload_variants`.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct UploadVariant {
+    pub width: i64,
+    pub height: i64,
+    /// Name on disk, `<id>_<width>.webp`.
+    pub filename: String,
+    /// Public URL of the copy; filled in by the handlers, which know the
+    /// store.
+    #[sqlx(skip)]
+    #[serde(default)]
+    pub url: String,
+    pub size_bytes: i64,
+}
+
+/// Payload of `PUT /api/admin/uploads/{id}`. Fields left out stay as they
+/// are; an empty one is cleared.
+#[derive(Debug, Deserialize)]
+pub struct UpdateUploadRequest {
+    #[serde(default)]
+    pub alt: Option<String>,
+    #[serde(default)]
+    pub caption: Option<String>,
+}
+
+/// Response for file uploads.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UploadResponse {
+    /// The URL of the uploaded file.
+    pub url: String,
+    #[serde(flatten)]
+    pub upload: Upload,
+}
+
+/// Content that still mentions an upload.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct UploadReference {
+    /// `tutorial`, `tutorial_section`, `post`, `page` or `site_content`.
+    pub kind: String,
+    /// ID of the referencing item (the tutorial's for a section, the section
+    /// name for site content).
+    pub id: String,
+    pub title: String,
+}
+
+/// Body of the 409 answer to deleting an upload that is still referenced.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UploadInUseResponse {
+    pub error: String,
+    pub code: String,
+    pub references: Vec<UploadReference>,
+}
@@ -1,6 +1,10 @@
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 
+/// Roles an account can hold. What each may do is defined by
+/// [`Claims::can`](crate::security::auth::Claims::can).
+pub const USER_ROLES: &[&str] = &["admin", "editor", "user"];
+
 /// Represents a registered system user.
 ///
 /// This struct maps directly to the `users` database table.
@@ -15,7 +19,7 @@ pub struct User {
     /// Marked with `#[serde(skip_serializing)]` to prevent accidental exposure in API responses.
     #[serde(skip_serializing)]
     pub password_hash: String,
-    /// User's role (e.g., "admin").
+    /// User's role, one of [`USER_ROLES`].
     pub role: String,
     /// ISO 8601 timestamp of account creation.
     pub created_at: String,
@@ -28,6 +32,9 @@ pub struct LoginRequest {
     pub username: String,
     /// The password for authentication.
     pub password: String,
+    /// Issue a longer-lived session (`AUTH_REMEMBER_ME_TTL_HOURS`).
+    #[serde(default)]
+    pub remember_me: bool,
 }
 
 /// Response payload for a successful login.
@@ -39,6 +46,38 @@ pub struct LoginResponse {
     pub user: UserResponse,
 }
 
+/// Payload for `POST /api/auth/change-password`.
+#[derive(Debug, Deserialize)]
+pub struct ChangePasswordRequest {
+    /// The account's current password, re-checked before anything changes.
+    pub current_password: String,
+    /// The replacement; subject to the usual password rules.
+    pub new_password: String,
+}
+
+/// Response of `GET /api/auth/csrf`.
+#[derive(Debug, Serialize)]
+pub struct CsrfTokenResponse {
+    /// Send back in the `X-CSRF-Token` header; the same value is also set
+    /// as the CSRF cookie.
+    pub csrf_token: String,
+    /// Seconds until the token expires, which is when the session does.
+    pub expires_in: i64,
+}
+
+/// Payload for `POST /api/auth/sudo`.
+#[derive(Debug, Deserialize)]
+pub struct SudoRequest {
+    pub password: String,
+}
+
+/// Response of `POST /api/auth/sudo`.
+#[derive(Debug, Serialize)]
+pub struct SudoResponse {
+    /// When sudo mode ends (RFC 3339).
+    pub sudo_until: String,
+}
+
 /// A public view of the User model, stripping sensitive data.
 #[derive(Debug, Serialize)]
 pub struct UserResponse {
@@ -47,3 +86,71 @@ pub struct UserResponse {
     /// The user's role.
     pub role: String,
 }
+
+/// An account as listed by the user management API.
+#[derive(Debug, Serialize)]
+pub struct AdminUserResponse {
+    pub id: i64,
+    pub username: String,
+    pub role: String,
+    pub created_at: String,
+}
+
+impl From<User> for AdminUserResponse {
+    fn from(user: User) -> Self {
+        AdminUserResponse {
+            id: user.id,
+            username: user.username,
+            role: user.role,
+            created_at: user.created_at,
+        }
+    }
+}
+
+/// Response of `GET /api/admin/users`.
+#[derive(Debug, Serialize)]
+pub struct AdminUserListResponse {
+    pub items: Vec<AdminUserResponse>,
+}
+
+/// Payload of `POST /api/admin/users`.
+#[derive(Debug, Deserialize)]
+pub struct CreateUserRequest {
+    pub username: String,
+    pub password: String,
+    /// One of [`USER_ROLES`].
+    pub role: String,
+}
+
+/// Payload of `PUT /api/admin/users/{id}`. Omitted fields stay unchanged.
+#[derive(Debug, Deserialize)]
+pub struct UpdateUserRequest {
+    /// New role, one of [`USER_ROLES`].
+    pub role: Option<String>,
+    /// New password; replaces the stored hash.
+    pub password: Option<String>,
+}
+
+/// A `login_attempts` row that currently blocks logins. `key` is the salted
+/// hash the lockout is tracked under, of either an IP and username pair or
+/// an IP alone; `key_type` says which (`pair` or `ip`).
+#[derive(Debug, Serialize, FromRow)]
+pub struct BlockedLoginAttempt {
+    pub key: String,
+    pub key_type: String,
+    pub fail_count: i64,
+    pub blocked_until: String,
+    pub last_attempt_at: Option<String>,
+}
+
+/// Response of `GET /api/admin/login-attempts`.
+#[derive(Debug, Serialize)]
+pub struct BlockedLoginAttemptListResponse {
+    pub items: Vec<BlockedLoginAttempt>,
+}
+
+/// Response of `DELETE /api/admin/login-attempts`.
+#[derive(Debug, Serialize)]
+pub struct ClearedLoginAttemptsResponse {
+    pub cleared: u64,
+}
@@ -0,0 +1,254 @@
+//! Outgoing Notifications
+//!
+//! With `NOTIFY_WEBHOOK_URL` set, events such as a new comment are POSTed
+//! to that URL as JSON:
+//!
+//! ```json
+//! {
+//!   "event": "comment.created",
+//!   "occurred_at": "2024-05-01T12:00:00+00:00",
+//!   "text": "New comment by Alice on \"Intro\": ...",
+//!   "content": "New comment by Alice on \"Intro\": ...",
+//!   "data": { "comment_id": "...", "author": "Alice", ... }
+//! }
+//! ```
+//!
+//! `data` depends on the event. `text` and `content` carry the same one-line
+//! summary, which is what Slack and Discord incoming webhooks display.
+//!
+//! Delivery never delays or fails the request that caused it: it runs on a
+//! spawned task, each attempt times out after a few seconds, a failed attempt
+//! is retried once, and a final failure is only logged.
+
+use crate::db::DbPool;
+use crate::models::Comment;
+use chrono::Utc;
+use reqwest::Client;
+use serde::Serialize;
+use std::sync::LazyLock;
+use std::time::Duration;
+
+/// Upper bound on one delivery attempt.
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(5);
+/// Pause before the single retry.
+const RETRY_DELAY: Duration = Duration::from_secs(2);
+/// Characters of a comment quoted in its notification.
+const EXCERPT_CHARS: usize = 200;
+
+static HTTP_CLIENT: LazyLock<Client> = LazyLock::new(|| {
+    Client::builder()
+        .timeout(WEBHOOK_TIMEOUT)
+        .build()
+        .expect("failed to build notification HTTP client")
+});
+
+/// An event to report.
+#[derive(Debug, Clone)]
+pub struct Notification {
+    /// Dotted event name, e.g. `comment.created`.
+    pub event: &'static str,
+    /// One-line summary for chat webhooks.
+    pub summary: String,
+    /// Event-specific details.
+    pub data: serde_json::Value,
+}
+
+#[derive(Serialize)]
+struct Payload<'a> {
+    event: &'a str,
+    occurred_at: String,
+    text: &'a str,
+    content: &'a str,
+    data: &'a serde_json::Value,
+}
+
+/// The configured webhook, if notifications are on.
+pub fn webhook_url() -> Option<&'static str> {
+    crate::config::get().notify_webhook_url.as_deref()
+}
+
+/// POSTs `notification` to `url`, retrying once if the first attempt fails.
+pub async fn deliver(url: &str, notification: &Notification) -> Result<(), String> {
+    let payload = Payload {
+        event: notification.event,
+        occurred_at: Utc::now().to_rfc3339(),
+        text: &notification.summary,
+        content: &notification.summary,
+        data: &notification.data,
+    };
+    match post(url, &payload).await {
+        Ok(()) => Ok(()),
+        Err(first) => {
+            tracing::debug!("Notification delivery failed, retrying: {}", first);
+            tokio::time::sleep(RETRY_DELAY).await;
+            post(url, &payload).await
+        }
+    }
+}
+
+async fn post(url: &str, payload: &Payload<'_>) -> Result<(), String> {
+    let response = HTTP_CLIENT
+        .post(url)
+        .json(payload)
+        .send()
+        .await
+        .map_err(|err| err.without_url().to_string())?;
+    let status = response.status();
+    if status.is_success() {
+        Ok(())
+    } else {
+        Err(format!("webhook answered {status}"))
+    }
+}
+
+/// Reports a newly stored comment. Returns at once; the parent lookup and
+/// the delivery run on a spawned task.
+pub fn comment_created(pool: &DbPool, comment: &Comment) {
+    let Some(url) = webhook_url() else {
+        return;
+    };
+    let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+        return;
+    };
+    let pool = pool.clone();
+    let comment = comment.clone();
+    runtime.spawn(async move {
+        let parent = crate::repositories::comments::get_comment_parent(
+            &pool,
+            comment.tutorial_id.as_deref(),
+            comment.post_id.as_deref(),
+        )
+        .await
+        .unwrap_or_else(|err| {
+            tracing::warn!("Failed to look up comment parent for notification: {}", err);
+            None
+        });
+        let notification = comment_notification(&comment, parent.as_ref());
+        if let Err(err) = deliver(url, &notification).await {
+            tracing::warn!(
+                "Failed to deliver notification for comment {}: {}",
+                comment.id,
+                err
+            );
+        }
+    });
+}
+
+fn comment_notification(
+    comment: &Comment,
+    parent: Option<&crate::repositories::comments::CommentParent>,
+) -> Notification {
+    let excerpt = excerpt(&comment.content);
+    let parent_title = parent.map(|parent| parent.title.clone());
+    let path = match (&comment.tutorial_id, parent) {
+        (Some(tutorial_id), _) => Some(format!("/tutorials/{tutorial_id}")),
+        (None, Some(parent)) => parent
+            .page_slug
+            .as_deref()
+            .zip(parent.post_slug.as_deref())
+            .map(|(page, post)| format!("/posts/{page}/{post}")),
+        (None, None) => None,
+    };
+    // The first allowed CORS origin is where the public site is served.
+    let link = path.map(
+        |path| match crate::config::get().cors_allowed_origins.first() {
+            Some(origin) => format!("{}{path}", origin.trim_end_matches('/')),
+            None => path,
+        },
+    );
+
+    let mut summary = format!("New comment by {}", comment.author);
+    if let Some(title) = &parent_title {
+        summary.push_str(&format!(" on \"{title}\""));
+    }
+    if comment.status != crate::models::COMMENT_STATUS_PUBLISHED {
+        summary.push_str(&format!(" ({})", comment.status));
+    }
+    summary.push_str(&format!(": {excerpt}"));
+    if let Some(link) = &link {
+        summary.push_str(&format!(" {link}"));
+    }
+
+    Notification {
+        event: "comment.created",
+        summary,
+        data: serde_json::json!({
+            "comment_id": comment.id,
+            "author": comment.author,
+            "excerpt": excerpt,
+            "status": comment.status,
+            "tutorial_id": comment.tutorial_id,
+            "post_id": comment.post_id,
+            "parent_id": comment.parent_id,
+            "parent_title": parent_title,
+            "link": link,
+        }),
+    }
+}
+
+/// The start of `content` on one line, at most [`EXCERPT_CHARS`] long.
+fn excerpt(content: &str) -> String {
+    let flat = content.split_whitespace().collect::<Vec<_>>().join(" ");
+    if flat.chars().count() <= EXCERPT_CHARS {
+        return flat;
+    }
+    let mut cut: String = flat.chars().take(EXCERPT_CHARS - 1).collect();
+    cut.push('…');
+    cut
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn excerpts_are_flattened_and_shortened() {
+        assert_eq!(excerpt("Hello\n\n  world"), "Hello world");
+        let long = "a".repeat(500);
+        let short = excerpt(&long);
+        assert_eq!(short.chars().count(), EXCERPT_CHARS);
+        assert!(short.ends_with('…'));
+    }
+
+    #[tokio::test]
+    async fn a_failed_delivery_is_retried_once() {
+        use axum::{extract::State, http::StatusCode, routing::post, Json, Router};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        // Fails the first attempt of every pair.
+        let hits = Arc::new(AtomicUsize::new(0));
+        let app =
+            Router::new()
+                .route(
+                    "/hook",
+                    post(
+                        |State(hits): State<Arc<AtomicUsize>>,
+                         Json(body): Json<serde_json::Value>| async move {
+                            assert_eq!(body["event"], "notification.test");
+                            assert_eq!(body["text"], body["content"]);
+                            if hits.fetch_add(1, Ordering::SeqCst) % 2 == 0 {
+                                StatusCode::INTERNAL_SERVER_ERROR
+                            } else {
+                                StatusCode::NO_CONTENT
+                            }
+                        },
+                    ),
+                )
+                .with_state(hits.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let notification = Notification {
+            event: "notification.test",
+            summary: "Test".to_string(),
+            data: serde_json::json!({}),
+        };
+        deliver(&url, &notification).await.expect("retry succeeds");
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+
+        let unreachable = "http://127.0.0.1:9/hook";
+        assert!(deliver(unreachable, &notification).await.is_err());
+    }
+}
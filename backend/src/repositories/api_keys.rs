@@ -0,0 +1,74 @@
+//! Persistence for API keys. Keys are looked up by their SHA-256 hash; the
+//! plaintext is never written.
+
+use crate::db::DbPool;
+use crate::models::{ApiKey, ApiKeyOwner};
+
+const KEY_COLUMNS: &str = concat!(
+    "k.id, k.name, k.prefix, u.username, k.created_at, k.last_used_at, k.revoked_at ",
+    "FROM api_keys k JOIN users u ON u.id = k.user_id"
+);
+
+pub async fn list_keys(pool: &DbPool) -> Result<Vec<ApiKey>, sqlx::Error> {
+    sqlx::query_as::<_, ApiKey>(&format!("SELECT {KEY_COLUMNS} ORDER BY k.id DESC"))
+        .fetch_all(pool)
+        .await
+}
+
+pub async fn create_key(
+    pool: &DbPool,
+    user_id: i64,
+    name: &str,
+    key_hash: &str,
+    prefix: &str,
+) -> Result<ApiKey, sqlx::Error> {
+    let id =
+        sqlx::query("INSERT INTO api_keys (user_id, name, key_hash, prefix) VALUES (?, ?, ?, ?)")
+            .bind(user_id)
+            .bind(name)
+            .bind(key_hash)
+            .bind(prefix)
+            .execute(pool)
+            .await?
+            .last_insert_rowid();
+
+    sqlx::query_as::<_, ApiKey>(&format!("SELECT {KEY_COLUMNS} WHERE k.id = ?"))
+        .bind(id)
+        .fetch_one(pool)
+        .await
+}
+
+/// Marks a key as revoked. Returns `false` if no active key has that ID.
+pub async fn revoke_key(pool: &DbPool, id: i64) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE api_keys SET revoked_at = datetime('now') WHERE id = ? AND revoked_at IS NULL",
+    )
+    .bind(id)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Resolves an unrevoked key to its owner and records the use. The role is
+/// read from `users`, so role changes apply to existing keys.
+pub async fn find_owner_by_hash(
+    pool: &DbPool,
+    key_hash: &str,
+) -> Result<Option<ApiKeyOwner>, sqlx::Error> {
+    let owner = sqlx::query_as::<_, ApiKeyOwner>(concat!(
+        "SELECT k.id AS key_id, u.id AS user_id, u.username, u.role ",
+        "FROM api_keys k JOIN users u ON u.id = k.user_id ",
+        "WHERE k.key_hash = ? AND k.revoked_at IS NULL"
+    ))
+    .bind(key_hash)
+    .fetch_optional(pool)
+    .await?;
+
+    if let Some(owner) = &owner {
+        sqlx::query("UPDATE api_keys SET last_used_at = datetime('now') WHERE id = ?")
+            .bind(owner.key_id)
+            .execute(pool)
+            .await?;
+    }
+    Ok(owner)
+}
@@ -0,0 +1,92 @@
+//! Audit log of admin content changes: who created, changed or deleted
+//! what, and when. Rows are only ever appended.
+
+use crate::db::DbPool;
+use crate::models::AuditLogRecord;
+
+/// Filters for [`list_entries`] and [`count_entries`]; `None` matches all.
+#[derive(Debug, Default)]
+pub struct AuditFilter<'a> {
+    pub entity_type: Option<&'a str>,
+    pub username: Option<&'a str>,
+}
+
+pub async fn insert_entry(
+    pool: &DbPool,
+    username: &str,
+    action: &str,
+    entity_type: &str,
+    entity_id: Option<&str>,
+    details: &serde_json::Value,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(concat!(
+        "INSERT INTO audit_log (username, action, entity_type, entity_id, details) ",
+        "VALUES (?, ?, ?, ?, ?)"
+    ))
+    .bind(username)
+    .bind(action)
+    .bind(entity_type)
+    .bind(entity_id)
+    .bind(details.to_string())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Records an admin action. The audit log must never get in the way of the
+/// change it describes, so a failed insert is traced and otherwise ignored.
+pub async fn append_entry(
+    pool: &DbPool,
+    username: &str,
+    action: &str,
+    entity_type: &str,
+    entity_id: Option<&str>,
+    details: serde_json::Value,
+) {
+    if let Err(err) = insert_entry(pool, username, action, entity_type, entity_id, &details).await {
+        tracing::error!(
+            username,
+            action,
+            entity_type,
+            entity_id,
+            "Failed to write audit log entry: {}",
+            err
+        );
+    }
+}
+
+/// Newest entries first.
+pub async fn list_entries(
+    pool: &DbPool,
+    filter: &AuditFilter<'_>,
+    limit: i64,
+    offset: i64,
+) -> Result<Vec<AuditLogRecord>, sqlx::Error> {
+    sqlx::query_as::<_, AuditLogRecord>(concat!(
+        "SELECT id, created_at, username, action, entity_type, entity_id, details ",
+        "FROM audit_log ",
+        "WHERE (? IS NULL OR entity_type = ?) AND (? IS NULL OR username = ?) ",
+        "ORDER BY id DESC LIMIT ? OFFSET ?"
+    ))
+    .bind(filter.entity_type)
+    .bind(filter.entity_type)
+    .bind(filter.username)
+    .bind(filter.username)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn count_entries(pool: &DbPool, filter: &AuditFilter<'_>) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar(concat!(
+        "SELECT COUNT(*) FROM audit_log ",
+        "WHERE (? IS NULL OR entity_type = ?) AND (? IS NULL OR username = ?)"
+    ))
+    .bind(filter.entity_type)
+    .bind(filter.entity_type)
+    .bind(filter.username)
+    .bind(filter.username)
+    .fetch_one(pool)
+    .await
+}
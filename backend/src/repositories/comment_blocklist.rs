@@ -0,0 +1,41 @@
+//! Persistence for the words, client IPs and names comment submissions are
+//! checked against.
+
+use crate::db::DbPool;
+use crate::models::CommentBlocklistEntry;
+
+pub async fn list_entries(pool: &DbPool) -> Result<Vec<CommentBlocklistEntry>, sqlx::Error> {
+    sqlx::query_as::<_, CommentBlocklistEntry>(
+        "SELECT id, kind, value, created_by, created_at FROM comment_blocklist ORDER BY kind, value",
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Adds an entry. Returns `None` if the same kind and value is already listed.
+pub async fn create_entry(
+    pool: &DbPool,
+    kind: &str,
+    value: &str,
+    created_by: &str,
+) -> Result<Option<CommentBlocklistEntry>, sqlx::Error> {
+    sqlx::query_as::<_, CommentBlocklistEntry>(
+        "INSERT INTO comment_blocklist (kind, value, created_by) VALUES (?, ?, ?) \
+         ON CONFLICT(kind, value) DO NOTHING \
+         RETURNING id, kind, value, created_by, created_at",
+    )
+    .bind(kind)
+    .bind(value)
+    .bind(created_by)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Removes an entry. Returns `false` if it did not exist.
+pub async fn delete_entry(pool: &DbPool, id: i64) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM comment_blocklist WHERE id = ?")
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
@@ -1,81 +1,323 @@
 use crate::db::DbPool;
 use crate::models::Comment;
+use crate::repositories::deletion_log;
 use sqlx;
 
-/// Fetches a paginated list of comments for a specific tutorial, with optional sorting.
-pub async fn list_comments(
+/// Position of the last comment on a page, used as a keyset cursor.
+///
+/// Resolved from a comment ID with [`resolve_comment_cursor`]; the list
+/// functions then continue strictly after this position under the requested
+/// sort, so comments inserted between page loads never shift later pages.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct CommentCursor {
+    pub id: String,
+    pub created_at: String,
+    pub votes: i64,
+}
+
+/// Parent a comment list is scoped to.
+#[derive(Debug, Clone, Copy)]
+pub enum CommentScope<'a> {
+    Tutorial(&'a str),
+    Post(&'a str),
+}
+
+impl CommentScope<'_> {
+    fn column(&self) -> &'static str {
+        match self {
+            CommentScope::Tutorial(_) => "tutorial_id",
+            CommentScope::Post(_) => "post_id",
+        }
+    }
+
+    fn id(&self) -> &str {
+        match self {
+            CommentScope::Tutorial(id) | CommentScope::Post(id) => id,
+        }
+    }
+}
+
+/// Looks up the sort keys of the comment a cursor points at.
+///
+/// Returns `None` when the comment doesn't exist, is a reply or held back,
+/// or belongs to a different tutorial/post than the list being paged.
+pub async fn resolve_comment_cursor(
     pool: &DbPool,
-    tutorial_id: &str,
+    scope: CommentScope<'_>,
+    comment_id: &str,
+) -> Result<Option<CommentCursor>, sqlx::Error> {
+    let mut query_builder =
+        sqlx::QueryBuilder::new("SELECT id, created_at, votes FROM comments WHERE id = ");
+    query_builder.push_bind(comment_id);
+    query_builder.push(format!(" AND {} = ", scope.column()));
+    query_builder.push_bind(scope.id());
+    query_builder.push(" AND parent_id IS NULL AND status = 'published'");
+
+    query_builder
+        .build_query_as::<CommentCursor>()
+        .fetch_optional(pool)
+        .await
+}
+
+/// A listed comment with the requesting user's vote state.
+#[derive(Debug, sqlx::FromRow)]
+pub struct ListedComment {
+    #[sqlx(flatten)]
+    pub comment: Comment,
+    /// Whether the voter passed to the list query has voted on it.
+    pub has_voted: bool,
+}
+
+/// Columns of [`ListedComment`] over `comments c` joined to the voter's
+/// `comment_votes v` row.
+const LISTED_COLUMNS: &str = concat!(
+    "c.id, c.tutorial_id, c.post_id, c.parent_id, c.author, c.content, c.created_at, ",
+    "c.votes, c.is_admin, c.author_username, c.is_guest, c.user_id, c.edited_at, ",
+    "c.avatar_hash, c.status, ",
+    "v.voter_id IS NOT NULL AS has_voted"
+);
+
+/// Shared list query over published top-level comments; replies are loaded per page
+/// with [`list_replies`]. With a cursor, `offset` is ignored and a keyset
+/// condition matching the ORDER BY is used instead; `id` breaks ties so the
+/// ordering is total and no comment is skipped or repeated across pages.
+///
+/// `voter`'s votes are joined in for [`ListedComment::has_voted`]; without
+/// one it is always false.
+pub async fn list_scoped_comments(
+    pool: &DbPool,
+    scope: CommentScope<'_>,
     limit: i64,
     offset: i64,
     sort: Option<&str>,
-) -> Result<Vec<Comment>, sqlx::Error> {
+    after: Option<&CommentCursor>,
+    voter: Option<&str>,
+) -> Result<Vec<ListedComment>, sqlx::Error> {
     // Dynamic query building for different sort orders
-    let mut query_builder = sqlx::QueryBuilder::new(concat!(
-        "SELECT id, tutorial_id, post_id, author, content, created_at, votes, is_admin, ",
-        "author_username, is_guest FROM comments WHERE tutorial_id = "
+    let mut query_builder = sqlx::QueryBuilder::new(format!(
+        "SELECT {LISTED_COLUMNS} FROM comments c LEFT JOIN comment_votes v \
+         ON v.comment_id = c.id AND v.voter_id = "
     ));
-    query_builder.push_bind(tutorial_id);
+    query_builder.push_bind(voter);
+    query_builder.push(format!(" WHERE c.{} = ", scope.column()));
+    query_builder.push_bind(scope.id());
+    query_builder.push(" AND c.parent_id IS NULL AND c.status = 'published'");
 
-    match sort {
-        Some("top") => {
-            query_builder.push(" ORDER BY votes DESC, created_at DESC");
-        }
-        _ => {
-            query_builder.push(" ORDER BY created_at DESC");
+    let top = sort == Some("top");
+
+    if let Some(cursor) = after {
+        if top {
+            query_builder.push(" AND (c.votes < ");
+            query_builder.push_bind(cursor.votes);
+            query_builder.push(" OR (c.votes = ");
+            query_builder.push_bind(cursor.votes);
+            query_builder.push(" AND (c.created_at < ");
+            query_builder.push_bind(&cursor.created_at);
+            query_builder.push(" OR (c.created_at = ");
+            query_builder.push_bind(&cursor.created_at);
+            query_builder.push(" AND c.id < ");
+            query_builder.push_bind(&cursor.id);
+            query_builder.push("))))");
+        } else {
+            query_builder.push(" AND (c.created_at < ");
+            query_builder.push_bind(&cursor.created_at);
+            query_builder.push(" OR (c.created_at = ");
+            query_builder.push_bind(&cursor.created_at);
+            query_builder.push(" AND c.id < ");
+            query_builder.push_bind(&cursor.id);
+            query_builder.push("))");
         }
     }
 
+    if top {
+        query_builder.push(" ORDER BY c.votes DESC, c.created_at DESC, c.id DESC");
+    } else {
+        query_builder.push(" ORDER BY c.created_at DESC, c.id DESC");
+    }
+
     query_builder.push(" LIMIT ");
     query_builder.push_bind(limit);
-    query_builder.push(" OFFSET ");
-    query_builder.push_bind(offset);
+    if after.is_none() {
+        query_builder.push(" OFFSET ");
+        query_builder.push_bind(offset);
+    }
 
     query_builder
-        .build_query_as::<Comment>()
+        .build_query_as::<ListedComment>()
         .fetch_all(pool)
         .await
 }
 
-pub async fn list_post_comments(
+/// Counts the published top-level comments (threads) on a tutorial or post,
+/// the unit the list endpoints page by.
+pub async fn count_threads(pool: &DbPool, scope: CommentScope<'_>) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar(&format!(
+        "SELECT COUNT(*) FROM comments WHERE {} = ? AND parent_id IS NULL \
+         AND status = 'published'",
+        scope.column()
+    ))
+    .bind(scope.id())
+    .fetch_one(pool)
+    .await
+}
+
+/// Counts every published comment on a tutorial or post, replies included.
+pub async fn count_comments(pool: &DbPool, scope: CommentScope<'_>) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar(&format!(
+        "SELECT COUNT(*) FROM comments WHERE {} = ? AND status = 'published'",
+        scope.column()
+    ))
+    .bind(scope.id())
+    .fetch_one(pool)
+    .await
+}
+
+/// Counts every comment on a tutorial, replies included.
+pub async fn count_comments_for_tutorial(
     pool: &DbPool,
-    post_id: &str,
-    limit: i64,
-    offset: i64,
-    sort: Option<&str>,
-) -> Result<Vec<Comment>, sqlx::Error> {
-    let mut query_builder = sqlx::QueryBuilder::new(concat!(
-        "SELECT id, tutorial_id, post_id, author, content, created_at, votes, is_admin, ",
-        "author_username, is_guest FROM comments WHERE post_id = "
-    ));
-    query_builder.push_bind(post_id);
+    tutorial_id: &str,
+) -> Result<i64, sqlx::Error> {
+    count_comments(pool, CommentScope::Tutorial(tutorial_id)).await
+}
 
-    match sort {
-        Some("top") => {
-            query_builder.push(" ORDER BY votes DESC, created_at DESC");
-        }
-        _ => {
-            query_builder.push(" ORDER BY created_at DESC");
-        }
+/// Counts every comment on a post, replies included.
+pub async fn count_comments_for_post(pool: &DbPool, post_id: &str) -> Result<i64, sqlx::Error> {
+    count_comments(pool, CommentScope::Post(post_id)).await
+}
+
+/// Fetches every reply below the given top-level comments, at any depth,
+/// oldest first, with `voter`'s vote state like [`list_scoped_comments`].
+pub async fn list_replies(
+    pool: &DbPool,
+    root_ids: &[String],
+    voter: Option<&str>,
+) -> Result<Vec<ListedComment>, sqlx::Error> {
+    if root_ids.is_empty() {
+        return Ok(Vec::new());
     }
 
-    query_builder.push(" LIMIT ");
-    query_builder.push_bind(limit);
-    query_builder.push(" OFFSET ");
-    query_builder.push_bind(offset);
+    let mut query_builder = sqlx::QueryBuilder::new(
+        "WITH RECURSIVE thread(id) AS (SELECT id FROM comments WHERE status = 'published' \
+         AND parent_id IN (",
+    );
+    let mut separated = query_builder.separated(", ");
+    for id in root_ids {
+        separated.push_bind(id);
+    }
+    query_builder.push(format!(
+        ") UNION SELECT c.id FROM comments c INNER JOIN thread t ON c.parent_id = t.id \
+         WHERE c.status = 'published') \
+         SELECT {LISTED_COLUMNS} FROM comments c LEFT JOIN comment_votes v \
+         ON v.comment_id = c.id AND v.voter_id = "
+    ));
+    query_builder.push_bind(voter);
+    query_builder
+        .push(" WHERE c.id IN (SELECT id FROM thread) ORDER BY c.created_at ASC, c.id ASC");
 
     query_builder
-        .build_query_as::<Comment>()
+        .build_query_as::<ListedComment>()
         .fetch_all(pool)
         .await
 }
 
+/// Nesting level of a comment: 1 for a top-level comment, 2 for a reply to
+/// it, and so on. `None` when the comment doesn't exist.
+pub async fn comment_depth(pool: &DbPool, id: &str) -> Result<Option<i64>, sqlx::Error> {
+    sqlx::query_scalar(concat!(
+        "WITH RECURSIVE ancestors(id, parent_id, depth) AS (",
+        "SELECT id, parent_id, 1 FROM comments WHERE id = ? ",
+        "UNION ALL SELECT c.id, c.parent_id, a.depth + 1 FROM comments c ",
+        "INNER JOIN ancestors a ON c.id = a.parent_id) ",
+        "SELECT MAX(depth) FROM ancestors"
+    ))
+    .bind(id)
+    .fetch_one(pool)
+    .await
+}
+
+/// Fetches every comment on a tutorial, oldest first, for exports.
+pub async fn list_all_tutorial_comments(
+    pool: &DbPool,
+    tutorial_id: &str,
+) -> Result<Vec<Comment>, sqlx::Error> {
+    sqlx::query_as::<_, Comment>(concat!(
+        "SELECT id, tutorial_id, post_id, parent_id, author, content, created_at, votes, is_admin, ",
+        "author_username, is_guest, user_id, edited_at, avatar_hash, status FROM comments ",
+        "WHERE tutorial_id = ? ",
+        "ORDER BY created_at ASC, id ASC"
+    ))
+    .bind(tutorial_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Inserts previously exported comments verbatim (including votes and
+/// timestamps) in a single transaction.
+///
+/// Comments whose ID already exists are left untouched, so re-importing the
+/// same document is idempotent. Parents must come before their replies; a
+/// reply whose parent is missing becomes a top-level comment. Returns the
+/// number of rows inserted.
+pub async fn insert_imported_comments(
+    pool: &DbPool,
+    comments: &[Comment],
+) -> Result<usize, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let inserted = insert_imported_comments_tx(&mut tx, comments).await?;
+    tx.commit().await?;
+
+    Ok(inserted)
+}
+
+/// [`insert_imported_comments`] within an existing transaction.
+pub(crate) async fn insert_imported_comments_tx(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    comments: &[Comment],
+) -> Result<usize, sqlx::Error> {
+    let mut inserted = 0;
+
+    for comment in comments {
+        // Imported rows must never throttle a real author's next comment, so
+        // they get a rate-limit key of their own.
+        let rate_limit_key = format!("import:{}", comment.id);
+        let result = sqlx::query(concat!(
+            "INSERT OR IGNORE INTO comments (id, tutorial_id, post_id, parent_id, author, ",
+            "rate_limit_key, content, created_at, votes, is_admin, author_username, is_guest, ",
+            "user_id, edited_at, avatar_hash, status) ",
+            "VALUES (?, ?, ?, (SELECT id FROM comments WHERE id = ?), ?, ?, ?, ?, ?, ?, ?, ?, ",
+            "(SELECT id FROM users WHERE id = ?), ?, ?, ?)"
+        ))
+        .bind(&comment.id)
+        .bind(&comment.tutorial_id)
+        .bind(&comment.post_id)
+        .bind(&comment.parent_id)
+        .bind(&comment.author)
+        .bind(&rate_limit_key)
+        .bind(&comment.content)
+        .bind(&comment.created_at)
+        .bind(comment.votes)
+        .bind(comment.is_admin)
+        .bind(&comment.author_username)
+        .bind(comment.is_guest)
+        .bind(comment.user_id)
+        .bind(&comment.edited_at)
+        .bind(&comment.avatar_hash)
+        .bind(&comment.status)
+        .execute(&mut **tx)
+        .await?;
+        inserted += result.rows_affected() as usize;
+    }
+
+    Ok(inserted)
+}
+
 #[allow(clippy::too_many_arguments)]
 pub async fn create_comment(
     pool: &DbPool,
     id: &str,
     tutorial_id: Option<String>,
     post_id: Option<String>,
+    parent_id: Option<String>,
     author: &str,
     rate_limit_key: &str,
     content: &str,
@@ -83,15 +325,19 @@ pub async fn create_comment(
     is_admin: bool,
     author_username: Option<String>,
     is_guest: Option<bool>,
+    user_id: Option<i64>,
+    avatar_hash: Option<String>,
+    status: &str,
 ) -> Result<Comment, sqlx::Error> {
     sqlx::query(concat!(
-        "INSERT INTO comments (id, tutorial_id, post_id, author, rate_limit_key, content, ",
-        "created_at, votes, is_admin, author_username, is_guest) ",
-        "VALUES (?, ?, ?, ?, ?, ?, ?, 0, ?, ?, ?)"
+        "INSERT INTO comments (id, tutorial_id, post_id, parent_id, author, rate_limit_key, ",
+        "content, created_at, votes, is_admin, author_username, is_guest, user_id, avatar_hash, ",
+        "status) VALUES (?, ?, ?, ?, ?, ?, ?, ?, 0, ?, ?, ?, ?, ?, ?)"
     ))
     .bind(id)
     .bind(&tutorial_id)
     .bind(&post_id)
+    .bind(&parent_id)
     .bind(author)
     .bind(rate_limit_key)
     .bind(content)
@@ -99,6 +345,9 @@ pub async fn create_comment(
     .bind(is_admin)
     .bind(&author_username)
     .bind(is_guest)
+    .bind(user_id)
+    .bind(&avatar_hash)
+    .bind(status)
     .execute(pool)
     .await?;
 
@@ -106,33 +355,204 @@ pub async fn create_comment(
         id: id.to_string(),
         tutorial_id,
         post_id,
+        parent_id,
         author: author.to_string(),
         content: content.to_string(),
         created_at: created_at.to_string(),
+        edited_at: None,
         votes: 0,
         is_admin,
         author_username,
         is_guest,
+        user_id,
+        avatar_hash,
+        status: status.to_string(),
     })
 }
 
 pub async fn get_comment(pool: &DbPool, id: &str) -> Result<Option<Comment>, sqlx::Error> {
     sqlx::query_as::<_, Comment>(concat!(
-        "SELECT id, tutorial_id, post_id, author, content, created_at, votes, is_admin, ",
-        "author_username, is_guest FROM comments WHERE id = ?"
+        "SELECT id, tutorial_id, post_id, parent_id, author, content, created_at, votes, is_admin, ",
+        "author_username, is_guest, user_id, edited_at, avatar_hash, status FROM comments WHERE id = ?"
     ))
     .bind(id)
     .fetch_optional(pool)
     .await
 }
 
-pub async fn delete_comment(pool: &DbPool, id: &str) -> Result<bool, sqlx::Error> {
-    let result = sqlx::query("DELETE FROM comments WHERE id = ?")
+/// Replaces a comment's content and stamps `edited_at`. Returns the updated
+/// comment, or `None` if it no longer exists.
+pub async fn update_comment_content(
+    pool: &DbPool,
+    id: &str,
+    content: &str,
+    edited_at: &str,
+) -> Result<Option<Comment>, sqlx::Error> {
+    let result = sqlx::query("UPDATE comments SET content = ?, edited_at = ? WHERE id = ?")
+        .bind(content)
+        .bind(edited_at)
         .bind(id)
         .execute(pool)
         .await?;
+    if result.rows_affected() == 0 {
+        return Ok(None);
+    }
+    get_comment(pool, id).await
+}
+
+/// Deletes a comment (votes cascade) after recording it in the deletion log.
+pub async fn delete_comment(
+    pool: &DbPool,
+    id: &str,
+    deleted_by: &str,
+) -> Result<bool, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let Some(snapshot) = deletion_log::comment_snapshot_tx(&mut tx, id).await? else {
+        return Ok(false);
+    };
+    deletion_log::record_tx(&mut tx, &snapshot, deleted_by).await?;
+
+    sqlx::query("DELETE FROM comments WHERE id = ?")
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    Ok(true)
+}
+
+/// Deletes several comments in one transaction, recording each in the
+/// deletion log like [`delete_comment`]. Returns the IDs that existed; a
+/// listed reply whose thread was deleted earlier in the batch counts as
+/// deleted with it.
+pub async fn delete_comments(
+    pool: &DbPool,
+    ids: &[String],
+    deleted_by: &str,
+) -> Result<Vec<String>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let mut removed = std::collections::HashSet::new();
+    let mut deleted = Vec::new();
+
+    for id in ids {
+        if removed.contains(id) {
+            deleted.push(id.clone());
+            continue;
+        }
+        let Some(snapshot) = deletion_log::comment_snapshot_tx(&mut tx, id).await? else {
+            continue;
+        };
+        if let crate::models::DeletionSnapshot::Comment { replies, .. } = &snapshot {
+            removed.extend(replies.iter().map(|reply| reply.id.clone()));
+        }
+        deletion_log::record_tx(&mut tx, &snapshot, deleted_by).await?;
+
+        sqlx::query("DELETE FROM comments WHERE id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        removed.insert(id.clone());
+        deleted.push(id.clone());
+    }
+    tx.commit().await?;
+
+    Ok(deleted)
+}
+
+/// Filters for [`list_moderated_comments`] and [`count_moderated_comments`];
+/// `None` matches all.
+#[derive(Debug, Default)]
+pub struct ModerationFilter<'a> {
+    /// Display name or account name of the author, case-insensitive.
+    pub author: Option<&'a str>,
+    /// `tutorial` or `post`.
+    pub parent_type: Option<&'a str>,
+    /// First day (`YYYY-MM-DD`, UTC) to include.
+    pub from: Option<&'a str>,
+    /// Last day (`YYYY-MM-DD`, UTC) to include.
+    pub to: Option<&'a str>,
+    /// `LIKE` pattern (escaped with `\`) the content must match.
+    pub content_pattern: Option<&'a str>,
+    /// `published` or `pending`.
+    pub status: Option<&'a str>,
+}
+
+/// A comment with the titles needed to show where it was posted.
+#[derive(Debug, sqlx::FromRow)]
+pub struct ModeratedComment {
+    #[sqlx(flatten)]
+    pub comment: Comment,
+    pub tutorial_title: Option<String>,
+    pub post_title: Option<String>,
+    /// Slug of the page the post belongs to.
+    pub page_slug: Option<String>,
+}
+
+const MODERATION_FROM: &str = concat!(
+    "FROM comments c ",
+    "LEFT JOIN tutorials t ON t.id = c.tutorial_id ",
+    "LEFT JOIN site_posts p ON p.id = c.post_id ",
+    "LEFT JOIN site_pages pg ON pg.id = p.page_id ",
+    "WHERE (? IS NULL OR c.author = ? COLLATE NOCASE OR c.author_username = ? COLLATE NOCASE) ",
+    "AND (? IS NULL OR (? = 'tutorial' AND c.tutorial_id IS NOT NULL) ",
+    "OR (? = 'post' AND c.post_id IS NOT NULL)) ",
+    "AND (? IS NULL OR date(c.created_at) >= ?) ",
+    "AND (? IS NULL OR date(c.created_at) <= ?) ",
+    "AND (? IS NULL OR c.content LIKE ? ESCAPE '\\') ",
+    "AND (? IS NULL OR c.status = ?) "
+);
+
+fn bind_moderation_filter<'q, O>(
+    query: sqlx::query::QueryAs<'q, sqlx::Sqlite, O, sqlx::sqlite::SqliteArguments<'q>>,
+    filter: &ModerationFilter<'q>,
+) -> sqlx::query::QueryAs<'q, sqlx::Sqlite, O, sqlx::sqlite::SqliteArguments<'q>> {
+    query
+        .bind(filter.author)
+        .bind(filter.author)
+        .bind(filter.author)
+        .bind(filter.parent_type)
+        .bind(filter.parent_type)
+        .bind(filter.parent_type)
+        .bind(filter.from)
+        .bind(filter.from)
+        .bind(filter.to)
+        .bind(filter.to)
+        .bind(filter.content_pattern)
+        .bind(filter.content_pattern)
+        .bind(filter.status)
+        .bind(filter.status)
+}
 
-    Ok(result.rows_affected() > 0)
+/// Comments on all tutorials and posts, replies included, newest first.
+pub async fn list_moderated_comments(
+    pool: &DbPool,
+    filter: &ModerationFilter<'_>,
+    limit: i64,
+    offset: i64,
+) -> Result<Vec<ModeratedComment>, sqlx::Error> {
+    let sql = format!(
+        "SELECT c.id, c.tutorial_id, c.post_id, c.parent_id, c.author, c.content, \
+         c.created_at, c.votes, c.is_admin, c.author_username, c.is_guest, c.user_id, \
+         c.edited_at, c.avatar_hash, c.status, t.title AS tutorial_title, p.title AS post_title, \
+         pg.slug AS page_slug {MODERATION_FROM}\
+         ORDER BY c.created_at DESC, c.id DESC LIMIT ? OFFSET ?"
+    );
+    bind_moderation_filter(sqlx::query_as::<_, ModeratedComment>(&sql), filter)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
+        .await
+}
+
+pub async fn count_moderated_comments(
+    pool: &DbPool,
+    filter: &ModerationFilter<'_>,
+) -> Result<i64, sqlx::Error> {
+    let sql = format!("SELECT COUNT(*) {MODERATION_FROM}");
+    let (count,): (i64,) = bind_moderation_filter(sqlx::query_as(&sql), filter)
+        .fetch_one(pool)
+        .await?;
+    Ok(count)
 }
 
 pub async fn check_comment_exists(pool: &DbPool, id: &str) -> Result<bool, sqlx::Error> {
@@ -157,6 +577,35 @@ pub async fn check_vote_exists(
     Ok(exists.is_some())
 }
 
+/// Removes a user's vote and decrements the comment's total in one
+/// transaction. Returns false when there was no vote to remove.
+pub async fn remove_vote(
+    pool: &DbPool,
+    comment_id: &str,
+    voter_id: &str,
+) -> Result<bool, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let removed = sqlx::query("DELETE FROM comment_votes WHERE comment_id = ? AND voter_id = ?")
+        .bind(comment_id)
+        .bind(voter_id)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+    if removed == 0 {
+        return Ok(false);
+    }
+
+    sqlx::query("UPDATE comments SET votes = MAX(votes - 1, 0) WHERE id = ?")
+        .bind(comment_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    Ok(true)
+}
+
 /// Records a vote for a comment and increments the total vote count in a transaction.
 pub async fn add_vote(pool: &DbPool, comment_id: &str, voter_id: &str) -> Result<(), sqlx::Error> {
     // Audit vote within a transaction to ensure consistency between vote count and records
@@ -193,3 +642,39 @@ pub async fn get_last_comment_time(
 
     Ok(last_comment.map(|(t,)| t))
 }
+
+/// What a comment was posted on: the tutorial or post title, and for posts
+/// the slugs the public link is built from.
+#[derive(Debug, sqlx::FromRow)]
+pub struct CommentParent {
+    pub title: String,
+    pub page_slug: Option<String>,
+    pub post_slug: Option<String>,
+}
+
+pub async fn get_comment_parent(
+    pool: &DbPool,
+    tutorial_id: Option<&str>,
+    post_id: Option<&str>,
+) -> Result<Option<CommentParent>, sqlx::Error> {
+    match (tutorial_id, post_id) {
+        (Some(tutorial_id), _) => {
+            sqlx::query_as::<_, CommentParent>(
+                "SELECT title, NULL AS page_slug, NULL AS post_slug FROM tutorials WHERE id = ?",
+            )
+            .bind(tutorial_id)
+            .fetch_optional(pool)
+            .await
+        }
+        (None, Some(post_id)) => {
+            sqlx::query_as::<_, CommentParent>(
+                "SELECT p.title, pg.slug AS page_slug, p.slug AS post_slug FROM site_posts p \
+                 JOIN site_pages pg ON pg.id = p.page_id WHERE p.id = ?",
+            )
+            .bind(post_id)
+            .fetch_optional(pool)
+            .await
+        }
+        (None, None) => Ok(None),
+    }
+}
@@ -1,8 +1,19 @@
+use crate::cache::{CacheStatus, TtlCache, PUBLIC_CACHE_TTL};
 use crate::db::DbPool;
-use crate::models::SiteContent;
+use crate::models::{SiteContent, SiteContentRevision, SiteContentRevisionSummary};
 use crate::repositories::common::serialize_json_value;
 use serde_json::Value;
 use sqlx;
+use std::sync::{Arc, LazyLock};
+
+/// Sections by name. Missing sections are cached too, so an unset section
+/// doesn't cost a query per request either.
+static SITE_CONTENT_CACHE: LazyLock<TtlCache<String, Option<SiteContent>>> =
+    LazyLock::new(|| TtlCache::new(PUBLIC_CACHE_TTL));
+
+/// Every section, as listed by `GET /api/content`.
+static SITE_CONTENT_LIST_CACHE: LazyLock<TtlCache<(), Arc<Vec<SiteContent>>>> =
+    LazyLock::new(|| TtlCache::new(PUBLIC_CACHE_TTL));
 
 /// Fetches all semi-static site content sections (headers, footers, etc.).
 pub async fn fetch_all_site_content(pool: &DbPool) -> Result<Vec<SiteContent>, sqlx::Error> {
@@ -13,6 +24,19 @@ pub async fn fetch_all_site_content(pool: &DbPool) -> Result<Vec<SiteContent>, s
     .await
 }
 
+/// Cached variant of [`fetch_all_site_content`] for the public listing.
+pub async fn fetch_all_site_content_cached(
+    pool: &DbPool,
+) -> Result<(Arc<Vec<SiteContent>>, CacheStatus), sqlx::Error> {
+    if let Some(records) = SITE_CONTENT_LIST_CACHE.get(&()) {
+        return Ok((records, CacheStatus::Hit));
+    }
+
+    let records = Arc::new(fetch_all_site_content(pool).await?);
+    SITE_CONTENT_LIST_CACHE.insert((), Arc::clone(&records));
+    Ok((records, CacheStatus::Miss))
+}
+
 pub async fn fetch_site_content_by_section(
     pool: &DbPool,
     section: &str,
@@ -25,27 +49,116 @@ pub async fn fetch_site_content_by_section(
     .await
 }
 
+/// Cached variant of [`fetch_site_content_by_section`] for hot read paths.
+pub async fn fetch_site_content_by_section_cached(
+    pool: &DbPool,
+    section: &str,
+) -> Result<Option<SiteContent>, sqlx::Error> {
+    if let Some(record) = SITE_CONTENT_CACHE.get(section) {
+        return Ok(record);
+    }
+
+    let record = fetch_site_content_by_section(pool, section).await?;
+    SITE_CONTENT_CACHE.insert(section.to_string(), record.clone());
+    Ok(record)
+}
+
+/// Drops a section, and the listing containing it, from the read caches so
+/// the next read sees the database.
+pub fn invalidate_site_content_cache(section: &str) {
+    SITE_CONTENT_CACHE.remove(section);
+    SITE_CONTENT_LIST_CACHE.clear();
+}
+
 /// Persists or updates content for a specific section.
 ///
 /// Handles serialization of a generic `serde_json::Value` into a persistence string.
+/// Every write is also appended to `site_content_revisions` under
+/// `updated_by`, and revisions beyond `SITE_CONTENT_REVISION_LIMIT` are
+/// dropped in the same transaction. Content saved before revisions were
+/// tracked (seeded or imported) is snapshotted first, so the first update
+/// can still be rolled back.
 pub async fn upsert_site_content(
     pool: &DbPool,
     section: &str,
     content: &Value,
+    updated_by: &str,
 ) -> Result<SiteContent, sqlx::Error> {
     let serialized = serialize_json_value(content)?;
 
+    let mut tx = pool.begin().await?;
+    sqlx::query(concat!(
+        "INSERT INTO site_content_revisions (section, content_json, updated_by) ",
+        "SELECT section, content_json, NULL FROM site_content WHERE section = ? ",
+        "AND NOT EXISTS (SELECT 1 FROM site_content_revisions WHERE section = ?)"
+    ))
+    .bind(section)
+    .bind(section)
+    .execute(&mut *tx)
+    .await?;
+
     // Atomic UPSERT using SQLite pattern
     sqlx::query(
         "INSERT INTO site_content (section, content_json, updated_at) VALUES (?, ?, CURRENT_TIMESTAMP) \
          ON CONFLICT(section) DO UPDATE SET content_json = excluded.content_json, updated_at = CURRENT_TIMESTAMP",
     )
     .bind(section)
-    .bind(serialized)
-    .execute(pool)
+    .bind(&serialized)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        "INSERT INTO site_content_revisions (section, content_json, updated_by) VALUES (?, ?, ?)",
+    )
+    .bind(section)
+    .bind(&serialized)
+    .bind(updated_by)
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query(concat!(
+        "DELETE FROM site_content_revisions WHERE section = ? AND id NOT IN ",
+        "(SELECT id FROM site_content_revisions WHERE section = ? ",
+        "ORDER BY id DESC LIMIT ?)"
+    ))
+    .bind(section)
+    .bind(section)
+    .bind(i64::from(crate::config::get().site_content_revision_limit))
+    .execute(&mut *tx)
     .await?;
+    tx.commit().await?;
+    invalidate_site_content_cache(section);
 
     fetch_site_content_by_section(pool, section)
         .await?
         .ok_or_else(|| sqlx::Error::RowNotFound)
 }
+
+/// Saved versions of a section, newest first.
+pub async fn list_revisions(
+    pool: &DbPool,
+    section: &str,
+) -> Result<Vec<SiteContentRevisionSummary>, sqlx::Error> {
+    sqlx::query_as::<_, SiteContentRevisionSummary>(
+        "SELECT id, updated_by, created_at FROM site_content_revisions \
+         WHERE section = ? ORDER BY id DESC",
+    )
+    .bind(section)
+    .fetch_all(pool)
+    .await
+}
+
+/// One saved version of a section.
+pub async fn get_revision(
+    pool: &DbPool,
+    section: &str,
+    id: i64,
+) -> Result<Option<SiteContentRevision>, sqlx::Error> {
+    sqlx::query_as::<_, SiteContentRevision>(
+        "SELECT id, section, content_json, updated_by, created_at FROM site_content_revisions \
+         WHERE section = ? AND id = ?",
+    )
+    .bind(section)
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+}
@@ -0,0 +1,50 @@
+//! Persistence for the registry of site content sections.
+
+use crate::db::DbPool;
+use crate::models::ContentSection;
+
+pub async fn list_sections(pool: &DbPool) -> Result<Vec<ContentSection>, sqlx::Error> {
+    sqlx::query_as::<_, ContentSection>(
+        "SELECT name, created_at FROM content_sections ORDER BY name",
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Registers `name`. Returns `None` if it is already registered.
+pub async fn create_section(
+    pool: &DbPool,
+    name: &str,
+) -> Result<Option<ContentSection>, sqlx::Error> {
+    sqlx::query_as::<_, ContentSection>(
+        "INSERT INTO content_sections (name) VALUES (?) ON CONFLICT(name) DO NOTHING \
+         RETURNING name, created_at",
+    )
+    .bind(name)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Unregisters `name` together with its stored content and revisions.
+/// Returns `false` if it was not registered.
+pub async fn delete_section(pool: &DbPool, name: &str) -> Result<bool, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let result = sqlx::query("DELETE FROM content_sections WHERE name = ?")
+        .bind(name)
+        .execute(&mut *tx)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Ok(false);
+    }
+    sqlx::query("DELETE FROM site_content WHERE section = ?")
+        .bind(name)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("DELETE FROM site_content_revisions WHERE section = ?")
+        .bind(name)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    super::content::invalidate_site_content_cache(name);
+    Ok(true)
+}
@@ -0,0 +1,267 @@
+//! Append-only log of hard deletes.
+//!
+//! Every repository delete captures the rows it is about to remove (parent
+//! plus cascaded children) and writes them here in the same transaction, so
+//! a deletion can never happen without its snapshot. Entries are purged
+//! once they are older than `DELETION_LOG_RETENTION_DAYS`.
+
+use crate::db::DbPool;
+use crate::models::{
+    Comment, CommentVote, DeletionLogRecord, DeletionSnapshot, SitePage, SitePost, Tutorial,
+};
+use sqlx::{Sqlite, Transaction};
+
+const COMMENT_COLUMNS: &str = concat!(
+    "id, tutorial_id, post_id, parent_id, author, content, created_at, votes, is_admin, ",
+    "author_username, is_guest, user_id, edited_at, avatar_hash, status"
+);
+const POST_COLUMNS: &str = concat!(
+    "id, page_id, title, slug, excerpt, content_markdown, is_published, ",
+    "allow_comments, published_at, order_index, cover_image_url, meta_description, ",
+    "canonical_url, created_at, updated_at"
+);
+
+/// Captures a tutorial with its sections, comments and their votes.
+pub(crate) async fn tutorial_snapshot_tx(
+    tx: &mut Transaction<'_, Sqlite>,
+    id: &str,
+) -> Result<Option<DeletionSnapshot>, sqlx::Error> {
+    let Some(tutorial) = sqlx::query_as::<_, Tutorial>(concat!(
+        "SELECT id, title, description, icon, color, topics, content, version, status, ",
+        "published_at, order_index, allow_comments, created_by, updated_by, ",
+        "created_at, updated_at ",
+        "FROM tutorials WHERE id = ?"
+    ))
+    .bind(id)
+    .fetch_optional(&mut **tx)
+    .await?
+    else {
+        return Ok(None);
+    };
+
+    let comments = sqlx::query_as::<_, Comment>(&format!(
+        "SELECT {COMMENT_COLUMNS} FROM comments WHERE tutorial_id = ? ORDER BY created_at, id"
+    ))
+    .bind(id)
+    .fetch_all(&mut **tx)
+    .await?;
+    let votes = sqlx::query_as::<_, CommentVote>(concat!(
+        "SELECT v.comment_id, v.voter_id, v.created_at FROM comment_votes v ",
+        "INNER JOIN comments c ON c.id = v.comment_id WHERE c.tutorial_id = ? ",
+        "ORDER BY v.comment_id, v.voter_id"
+    ))
+    .bind(id)
+    .fetch_all(&mut **tx)
+    .await?;
+
+    let sections = super::tutorial_sections::list_sections_tx(tx, id).await?;
+
+    Ok(Some(DeletionSnapshot::Tutorial {
+        tutorial,
+        sections,
+        comments,
+        votes,
+    }))
+}
+
+/// Captures a page with all of its posts.
+pub(crate) async fn page_snapshot_tx(
+    tx: &mut Transaction<'_, Sqlite>,
+    id: &str,
+) -> Result<Option<DeletionSnapshot>, sqlx::Error> {
+    let Some(page) = sqlx::query_as::<_, SitePage>(concat!(
+        "SELECT id, slug, title, description, nav_label, show_in_nav, order_index, ",
+        "is_published, hero_json, layout_json, meta_robots, custom_headers_json, ",
+        "created_at, updated_at FROM site_pages WHERE id = ?"
+    ))
+    .bind(id)
+    .fetch_optional(&mut **tx)
+    .await?
+    else {
+        return Ok(None);
+    };
+
+    let mut posts = sqlx::query_as::<_, SitePost>(&format!(
+        "SELECT {POST_COLUMNS} FROM site_posts WHERE page_id = ? ORDER BY order_index, id"
+    ))
+    .bind(id)
+    .fetch_all(&mut **tx)
+    .await?;
+    super::posts::attach_tags(&mut **tx, &mut posts).await?;
+
+    Ok(Some(DeletionSnapshot::Page { page, posts }))
+}
+
+/// Captures a single post.
+pub(crate) async fn post_snapshot_tx(
+    tx: &mut Transaction<'_, Sqlite>,
+    id: &str,
+) -> Result<Option<DeletionSnapshot>, sqlx::Error> {
+    let mut post = sqlx::query_as::<_, SitePost>(&format!(
+        "SELECT {POST_COLUMNS} FROM site_posts WHERE id = ?"
+    ))
+    .bind(id)
+    .fetch_optional(&mut **tx)
+    .await?;
+    if let Some(post) = post.as_mut() {
+        super::posts::attach_tags(&mut **tx, std::slice::from_mut(post)).await?;
+    }
+
+    Ok(post.map(|post| DeletionSnapshot::Post { post }))
+}
+
+/// Captures a comment with its replies (at any depth) and their votes.
+pub(crate) async fn comment_snapshot_tx(
+    tx: &mut Transaction<'_, Sqlite>,
+    id: &str,
+) -> Result<Option<DeletionSnapshot>, sqlx::Error> {
+    let Some(comment) = sqlx::query_as::<_, Comment>(&format!(
+        "SELECT {COMMENT_COLUMNS} FROM comments WHERE id = ?"
+    ))
+    .bind(id)
+    .fetch_optional(&mut **tx)
+    .await?
+    else {
+        return Ok(None);
+    };
+
+    const THREAD: &str = concat!(
+        "WITH RECURSIVE thread(id) AS (SELECT ? UNION ",
+        "SELECT c.id FROM comments c INNER JOIN thread t ON c.parent_id = t.id) "
+    );
+    let replies = sqlx::query_as::<_, Comment>(&format!(
+        "{THREAD}SELECT {COMMENT_COLUMNS} FROM comments \
+         WHERE id IN (SELECT id FROM thread) AND id != ? ORDER BY created_at, id"
+    ))
+    .bind(id)
+    .bind(id)
+    .fetch_all(&mut **tx)
+    .await?;
+
+    let votes = sqlx::query_as::<_, CommentVote>(&format!(
+        "{THREAD}SELECT comment_id, voter_id, created_at FROM comment_votes \
+         WHERE comment_id IN (SELECT id FROM thread) ORDER BY comment_id, voter_id"
+    ))
+    .bind(id)
+    .fetch_all(&mut **tx)
+    .await?;
+
+    Ok(Some(DeletionSnapshot::Comment {
+        comment,
+        replies,
+        votes,
+    }))
+}
+
+/// Writes `snapshot` to the log inside the deleting transaction.
+pub(crate) async fn record_tx(
+    tx: &mut Transaction<'_, Sqlite>,
+    snapshot: &DeletionSnapshot,
+    deleted_by: &str,
+) -> Result<(), sqlx::Error> {
+    let snapshot_json = serde_json::to_string(snapshot).map_err(|e| {
+        sqlx::Error::Protocol(format!("Failed to serialize deletion snapshot: {e}"))
+    })?;
+
+    sqlx::query(concat!(
+        "INSERT INTO deletion_log (entity_type, entity_id, snapshot_json, deleted_by) ",
+        "VALUES (?, ?, ?, ?)"
+    ))
+    .bind(snapshot.entity_type())
+    .bind(snapshot.entity_id())
+    .bind(snapshot_json)
+    .bind(deleted_by)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Lists entries, newest first.
+pub async fn list_entries(
+    pool: &DbPool,
+    limit: i64,
+) -> Result<Vec<DeletionLogRecord>, sqlx::Error> {
+    sqlx::query_as::<_, DeletionLogRecord>(concat!(
+        "SELECT id, entity_type, entity_id, snapshot_json, deleted_by, deleted_at ",
+        "FROM deletion_log ORDER BY deleted_at DESC, id DESC LIMIT ?"
+    ))
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn get_entry(pool: &DbPool, id: i64) -> Result<Option<DeletionLogRecord>, sqlx::Error> {
+    sqlx::query_as::<_, DeletionLogRecord>(concat!(
+        "SELECT id, entity_type, entity_id, snapshot_json, deleted_by, deleted_at ",
+        "FROM deletion_log WHERE id = ?"
+    ))
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Deletes entries older than `days` days.
+pub async fn purge_older_than(pool: &DbPool, days: i64) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        "DELETE FROM deletion_log WHERE deleted_at <= datetime('now', '-' || ? || ' days')",
+    )
+    .bind(days)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// Puts back the original timestamps of a row re-created by a restore;
+/// the creation paths stamp rows with the current time.
+pub async fn restore_timestamps(
+    pool: &DbPool,
+    entity_type: &str,
+    id: &str,
+    created_at: &str,
+    updated_at: &str,
+) -> Result<(), sqlx::Error> {
+    let table = match entity_type {
+        "tutorial" => "tutorials",
+        "page" => "site_pages",
+        "post" => "site_posts",
+        other => {
+            return Err(sqlx::Error::Protocol(format!(
+                "No timestamps to restore for {other}"
+            )))
+        }
+    };
+
+    sqlx::query(&format!(
+        "UPDATE {table} SET created_at = ?, updated_at = ? WHERE id = ?"
+    ))
+    .bind(created_at)
+    .bind(updated_at)
+    .bind(id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Re-inserts votes without touching the comments' cached totals (the
+/// restored comment rows already carry them). Returns the number inserted.
+pub async fn insert_votes(pool: &DbPool, votes: &[CommentVote]) -> Result<usize, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let mut inserted = 0;
+
+    for vote in votes {
+        let result = sqlx::query(concat!(
+            "INSERT OR IGNORE INTO comment_votes (comment_id, voter_id, created_at) ",
+            "VALUES (?, ?, COALESCE(?, CURRENT_TIMESTAMP))"
+        ))
+        .bind(&vote.comment_id)
+        .bind(&vote.voter_id)
+        .bind(&vote.created_at)
+        .execute(&mut *tx)
+        .await?;
+        inserted += result.rows_affected() as usize;
+    }
+
+    tx.commit().await?;
+    Ok(inserted)
+}
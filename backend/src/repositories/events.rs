@@ -0,0 +1,101 @@
+//! Content publication events backing the public changelog.
+
+use crate::db::DbPool;
+use crate::models::{ChangelogEntry, ContentEventKind};
+
+/// Minimum change in content length (bytes) for an edit of a published
+/// document to be announced. Typo fixes and small rewording stay quiet.
+pub const SUBSTANTIAL_UPDATE_BYTES: usize = 500;
+
+/// Decides whether a save produces a changelog event.
+///
+/// A document becoming visible is always announced; an edit to an already
+/// visible document only when its content grew or shrank substantially.
+pub fn classify_change(
+    was_published: bool,
+    is_published: bool,
+    old_len: usize,
+    new_len: usize,
+) -> Option<ContentEventKind> {
+    match (was_published, is_published) {
+        (false, true) => Some(ContentEventKind::Published),
+        (true, true) if old_len.abs_diff(new_len) >= SUBSTANTIAL_UPDATE_BYTES => {
+            Some(ContentEventKind::Updated)
+        }
+        _ => None,
+    }
+}
+
+/// Appends one event. `entity_type` is `tutorial` or `post`.
+pub async fn record_content_event(
+    pool: &DbPool,
+    entity_type: &str,
+    entity_id: &str,
+    slug: &str,
+    title: &str,
+    kind: ContentEventKind,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO content_events (entity_type, entity_id, slug, title, kind) VALUES (?, ?, ?, ?, ?)",
+    )
+    .bind(entity_type)
+    .bind(entity_id)
+    .bind(slug)
+    .bind(title)
+    .bind(kind.as_str())
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Most recent events whose document is still publicly visible, newest
+/// first (drafts and trashed documents are left out). Links are built from the document's current slugs so renamed
+/// posts don't produce dead links. Posts on pages marked `noindex` (or
+/// `none`) are left out of the feed.
+pub async fn list_public_events(
+    pool: &DbPool,
+    limit: i64,
+) -> Result<Vec<ChangelogEntry>, sqlx::Error> {
+    sqlx::query_as::<_, ChangelogEntry>(concat!(
+        "SELECT e.id, e.entity_type, e.entity_id, e.title, e.kind, e.created_at, ",
+        "CASE e.entity_type WHEN 'tutorial' THEN '/tutorials/' || t.id ",
+        "ELSE '/posts/' || pg.slug || '/' || sp.slug END AS link ",
+        "FROM content_events e ",
+        "LEFT JOIN tutorials t ON e.entity_type = 'tutorial' AND t.id = e.entity_id ",
+        "LEFT JOIN site_posts sp ON e.entity_type = 'post' AND sp.id = e.entity_id ",
+        "LEFT JOIN site_pages pg ON pg.id = sp.page_id ",
+        "WHERE (t.status = 'published' AND t.deleted_at IS NULL) OR (sp.is_published = 1 AND pg.is_published = 1 ",
+        "AND sp.deleted_at IS NULL AND pg.deleted_at IS NULL ",
+        "AND ',' || REPLACE(COALESCE(pg.meta_robots, ''), ' ', '') || ',' NOT LIKE '%,noindex,%' ",
+        "AND ',' || REPLACE(COALESCE(pg.meta_robots, ''), ' ', '') || ',' NOT LIKE '%,none,%') ",
+        "ORDER BY e.created_at DESC, e.id DESC LIMIT ?"
+    ))
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classify_change_announces_publication_and_large_edits_only() {
+        assert_eq!(
+            classify_change(false, true, 0, 10),
+            Some(ContentEventKind::Published)
+        );
+        assert_eq!(
+            classify_change(true, true, 1000, 1000 + SUBSTANTIAL_UPDATE_BYTES),
+            Some(ContentEventKind::Updated)
+        );
+        assert_eq!(
+            classify_change(true, true, 2000, 2000 - SUBSTANTIAL_UPDATE_BYTES),
+            Some(ContentEventKind::Updated)
+        );
+        assert_eq!(classify_change(true, true, 1000, 1010), None);
+        assert_eq!(classify_change(true, false, 1000, 5000), None);
+        assert_eq!(classify_change(false, false, 0, 5000), None);
+    }
+}
@@ -0,0 +1,38 @@
+//! Persistence for the icon registry tutorials are validated against.
+
+use crate::db::DbPool;
+use crate::models::AllowedIcon;
+
+pub async fn list_icons(pool: &DbPool) -> Result<Vec<AllowedIcon>, sqlx::Error> {
+    sqlx::query_as::<_, AllowedIcon>("SELECT name, created_at FROM allowed_icons ORDER BY name")
+        .fetch_all(pool)
+        .await
+}
+
+/// Adds `name`. Returns `None` if it is already registered.
+pub async fn create_icon(pool: &DbPool, name: &str) -> Result<Option<AllowedIcon>, sqlx::Error> {
+    sqlx::query_as::<_, AllowedIcon>(
+        "INSERT INTO allowed_icons (name) VALUES (?) ON CONFLICT(name) DO NOTHING \
+         RETURNING name, created_at",
+    )
+    .bind(name)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Removes `name`. Returns `false` if it was not registered.
+pub async fn delete_icon(pool: &DbPool, name: &str) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM allowed_icons WHERE name = ?")
+        .bind(name)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Number of tutorials, trashed ones included, that use `name`.
+pub async fn count_tutorials_using(pool: &DbPool, name: &str) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar("SELECT COUNT(*) FROM tutorials WHERE icon = ?")
+        .bind(name)
+        .fetch_one(pool)
+        .await
+}
@@ -5,13 +5,31 @@
 //! SQL structure using `sqlx`. They handle connections, transactions,
 //! and map database rows to application models.
 
+pub mod api_keys; // Hashed API keys and their owners
 pub mod app_metadata; // Generic key-value storage
+pub mod audit; // Persistent log of admin content changes
+pub mod comment_blocklist; // Words, IPs and names comments are checked against
 pub mod comments; // Comment and voting persistence
 pub mod common; // Shared validation and serialization utilities
 pub mod content; // Dynamic landing page sections
+pub mod content_sections; // Site content sections the API manages
+pub mod deletion_log; // Restorable snapshots of hard deletes
+pub mod events; // Publication history for the changelog
+pub mod icons; // Icons tutorials may use
 pub mod newsletter; // Newsletter subscription persistence
 pub mod pages; // Site page structure
 pub mod posts; // Detailed blog post content
+pub mod previews; // Nonces behind revocable preview links
+pub mod search_index; // Rebuilding and checking the full-text search index
+pub mod search_log; // Logged search queries and their analytics
+pub mod security_counters; // Daily CSRF/auth rejection counts
+pub mod sessions; // Issued login sessions and their revocation
+pub mod sitemap; // Public documents listed in the sitemap
+pub mod stats; // Content volume statistics
 pub mod token_blacklist; // Authentication revocation state
+pub mod tutorial_sections; // Optional chapters of a tutorial
 pub mod tutorials; // Course material and topic indexing
+pub mod two_factor; // TOTP state, recovery codes and login challenges
+pub mod uploads; // Records of uploaded files
 pub mod users; // User identity and brute-force tracking
+pub mod views; // Daily read counts of tutorials and posts
@@ -1,14 +1,24 @@
+use crate::cache::{CacheStatus, TtlCache, PAGE_BUNDLES, PUBLIC_CACHE_TTL};
 use crate::db::DbPool;
-use crate::models::{CreateSitePageRequest, SitePage, UpdateSitePageRequest};
+use crate::models::{CreateSitePageRequest, SitePage, TrashedPage, UpdateSitePageRequest};
 use crate::repositories::common::{serialize_json_value, validate_slug};
+use crate::repositories::deletion_log;
 use sqlx;
+use std::collections::BTreeMap;
+use std::sync::{Arc, LazyLock};
 
-/// Fetches all site pages, ordered by their custom navigation index and title.
+/// The published page list. Page writes through this module invalidate it.
+static PUBLISHED_PAGES_CACHE: LazyLock<TtlCache<(), Arc<Vec<SitePage>>>> =
+    LazyLock::new(|| TtlCache::new(PUBLIC_CACHE_TTL));
+
+/// Fetches all site pages outside the trash, ordered by their custom
+/// navigation index and title.
 pub async fn list_site_pages(pool: &DbPool) -> Result<Vec<SitePage>, sqlx::Error> {
     sqlx::query_as::<_, SitePage>(concat!(
         "SELECT id, slug, title, description, nav_label, show_in_nav, order_index, ",
-        "is_published, hero_json, layout_json, created_at, updated_at ",
-        "FROM site_pages ORDER BY order_index, title"
+        "is_published, hero_json, layout_json, meta_robots, custom_headers_json, ",
+        "created_at, updated_at ",
+        "FROM site_pages WHERE deleted_at IS NULL ORDER BY order_index, title"
     ))
     .fetch_all(pool)
     .await
@@ -18,8 +28,9 @@ pub async fn list_site_pages(pool: &DbPool) -> Result<Vec<SitePage>, sqlx::Error
 pub async fn list_nav_pages(pool: &DbPool) -> Result<Vec<SitePage>, sqlx::Error> {
     sqlx::query_as::<_, SitePage>(concat!(
         "SELECT id, slug, title, description, nav_label, show_in_nav, order_index, ",
-        "is_published, hero_json, layout_json, created_at, updated_at ",
-        "FROM site_pages WHERE show_in_nav = 1 AND is_published = 1 ",
+        "is_published, hero_json, layout_json, meta_robots, custom_headers_json, ",
+        "created_at, updated_at ",
+        "FROM site_pages WHERE show_in_nav = 1 AND is_published = 1 AND deleted_at IS NULL ",
         "ORDER BY order_index, title"
     ))
     .fetch_all(pool)
@@ -29,33 +40,77 @@ pub async fn list_nav_pages(pool: &DbPool) -> Result<Vec<SitePage>, sqlx::Error>
 pub async fn list_published_pages(pool: &DbPool) -> Result<Vec<SitePage>, sqlx::Error> {
     sqlx::query_as::<_, SitePage>(concat!(
         "SELECT id, slug, title, description, nav_label, show_in_nav, order_index, ",
-        "is_published, hero_json, layout_json, created_at, updated_at ",
-        "FROM site_pages WHERE is_published = 1 ORDER BY order_index, title"
+        "is_published, hero_json, layout_json, meta_robots, custom_headers_json, ",
+        "created_at, updated_at ",
+        "FROM site_pages WHERE is_published = 1 AND deleted_at IS NULL ",
+        "ORDER BY order_index, title"
     ))
     .fetch_all(pool)
     .await
 }
 
+/// Cached variant of [`list_published_pages`] backing the public navigation
+/// and page-slug endpoints, which every page view hits.
+pub async fn list_published_pages_cached(pool: &DbPool) -> Result<Arc<Vec<SitePage>>, sqlx::Error> {
+    Ok(published_pages_with_status(pool).await?.0)
+}
+
+async fn published_pages_with_status(
+    pool: &DbPool,
+) -> Result<(Arc<Vec<SitePage>>, CacheStatus), sqlx::Error> {
+    if let Some(pages) = PUBLISHED_PAGES_CACHE.get(&()) {
+        return Ok((pages, CacheStatus::Hit));
+    }
+
+    let pages = Arc::new(list_published_pages(pool).await?);
+    PUBLISHED_PAGES_CACHE.insert((), Arc::clone(&pages));
+    Ok((pages, CacheStatus::Miss))
+}
+
+/// Navigation entries derived from [`list_published_pages_cached`]; same
+/// rows and order as [`list_nav_pages`].
+pub async fn list_nav_pages_cached(
+    pool: &DbPool,
+) -> Result<(Vec<SitePage>, CacheStatus), sqlx::Error> {
+    let (pages, status) = published_pages_with_status(pool).await?;
+    let nav = pages
+        .iter()
+        .filter(|page| page.show_in_nav)
+        .cloned()
+        .collect();
+    Ok((nav, status))
+}
+
+pub(crate) fn invalidate_published_pages_cache() {
+    PUBLISHED_PAGES_CACHE.clear();
+    PAGE_BUNDLES.clear();
+    // The sitemap lists the same pages and the posts below them
+    crate::sitemap::invalidate();
+}
+
+/// Fetches a site page by ID, unless it is in the trash.
 pub async fn get_site_page_by_id(pool: &DbPool, id: &str) -> Result<Option<SitePage>, sqlx::Error> {
     sqlx::query_as::<_, SitePage>(concat!(
         "SELECT id, slug, title, description, nav_label, show_in_nav, order_index, ",
-        "is_published, hero_json, layout_json, created_at, updated_at ",
-        "FROM site_pages WHERE id = ?"
+        "is_published, hero_json, layout_json, meta_robots, custom_headers_json, ",
+        "created_at, updated_at ",
+        "FROM site_pages WHERE id = ? AND deleted_at IS NULL"
     ))
     .bind(id)
     .fetch_optional(pool)
     .await
 }
 
-/// Fetches a single site page by its URL slug.
+/// Fetches a single site page by its URL slug, unless it is in the trash.
 pub async fn get_site_page_by_slug(
     pool: &DbPool,
     slug: &str,
 ) -> Result<Option<SitePage>, sqlx::Error> {
     sqlx::query_as::<_, SitePage>(concat!(
         "SELECT id, slug, title, description, nav_label, show_in_nav, order_index, ",
-        "is_published, hero_json, layout_json, created_at, updated_at ",
-        "FROM site_pages WHERE slug = ?"
+        "is_published, hero_json, layout_json, meta_robots, custom_headers_json, ",
+        "created_at, updated_at ",
+        "FROM site_pages WHERE slug = ? AND deleted_at IS NULL"
     ))
     .bind(slug)
     .fetch_optional(pool)
@@ -66,23 +121,32 @@ pub async fn get_site_page_by_slug(
 pub async fn create_site_page(
     pool: &DbPool,
     page: CreateSitePageRequest,
+) -> Result<SitePage, sqlx::Error> {
+    create_site_page_with_id(pool, &uuid::Uuid::new_v4().to_string(), page).await
+}
+
+/// Creates a site page under a caller-chosen ID (used when restoring).
+pub async fn create_site_page_with_id(
+    pool: &DbPool,
+    id: &str,
+    page: CreateSitePageRequest,
 ) -> Result<SitePage, sqlx::Error> {
     // Validate slug hygiene
     validate_slug(&page.slug)?;
 
-    let id = uuid::Uuid::new_v4().to_string();
     let hero_json = serialize_json_value(&page.hero)?;
     let layout_json = serialize_json_value(&page.layout)?;
+    let custom_headers_json = serialize_headers(&page.custom_headers)?;
     let description = page.description.unwrap_or_default();
     let order_index = page.order_index.unwrap_or(0);
 
     // Insert record
     sqlx::query(concat!(
         "INSERT INTO site_pages (id, slug, title, description, nav_label, show_in_nav, ",
-        "order_index, is_published, hero_json, layout_json) ",
-        "VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
+        "order_index, is_published, hero_json, layout_json, meta_robots, ",
+        "custom_headers_json) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
     ))
-    .bind(&id)
+    .bind(id)
     .bind(&page.slug)
     .bind(&page.title)
     .bind(description)
@@ -92,15 +156,114 @@ pub async fn create_site_page(
     .bind(if page.is_published { 1 } else { 0 })
     .bind(hero_json)
     .bind(layout_json)
+    .bind(page.meta_robots)
+    .bind(custom_headers_json)
     .execute(pool)
     .await?;
+    invalidate_published_pages_cache();
 
     // Return the inserted state
-    get_site_page_by_id(pool, &id)
+    get_site_page_by_id(pool, id)
         .await?
         .ok_or_else(|| sqlx::Error::RowNotFound)
 }
 
+/// Copies a page under a new ID and the first free slug of `{slug}-copy`,
+/// `{slug}-copy-2`, ... The copy starts unpublished. With `include_posts`
+/// every post of the page outside the trash is copied too, keeping its slug and tags under a
+/// new ID. Runs in one transaction; a missing page is `RowNotFound`.
+pub async fn duplicate_site_page(
+    pool: &DbPool,
+    id: &str,
+    include_posts: bool,
+) -> Result<SitePage, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let source_slug: String =
+        sqlx::query_scalar("SELECT slug FROM site_pages WHERE id = ? AND deleted_at IS NULL")
+            .bind(id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)?;
+
+    let mut copy = 1;
+    let slug = loop {
+        let candidate = copy_slug(&source_slug, copy);
+        let taken: bool =
+            sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM site_pages WHERE slug = ?)")
+                .bind(&candidate)
+                .fetch_one(&mut *tx)
+                .await?;
+        if !taken {
+            break candidate;
+        }
+        copy += 1;
+    };
+    validate_slug(&slug)?;
+
+    let new_id = uuid::Uuid::new_v4().to_string();
+    sqlx::query(concat!(
+        "INSERT INTO site_pages (id, slug, title, description, nav_label, show_in_nav, ",
+        "order_index, is_published, hero_json, layout_json, meta_robots, ",
+        "custom_headers_json) SELECT ?, ?, title, description, nav_label, show_in_nav, ",
+        "order_index, 0, hero_json, layout_json, meta_robots, custom_headers_json ",
+        "FROM site_pages WHERE id = ?"
+    ))
+    .bind(&new_id)
+    .bind(&slug)
+    .bind(id)
+    .execute(&mut *tx)
+    .await?;
+
+    if include_posts {
+        let post_ids: Vec<String> = sqlx::query_scalar(
+            "SELECT id FROM site_posts WHERE page_id = ? AND deleted_at IS NULL",
+        )
+        .bind(id)
+        .fetch_all(&mut *tx)
+        .await?;
+        for post_id in post_ids {
+            let new_post_id = uuid::Uuid::new_v4().to_string();
+            sqlx::query(concat!(
+                "INSERT INTO site_posts (id, page_id, title, slug, excerpt, content_markdown, ",
+                "is_published, allow_comments, published_at, order_index, content_stats, ",
+                "cover_image_url, meta_description, canonical_url) ",
+                "SELECT ?, ?, title, slug, excerpt, content_markdown, is_published, ",
+                "allow_comments, published_at, order_index, content_stats, ",
+                "cover_image_url, meta_description, canonical_url ",
+                "FROM site_posts WHERE id = ?"
+            ))
+            .bind(&new_post_id)
+            .bind(&new_id)
+            .bind(&post_id)
+            .execute(&mut *tx)
+            .await?;
+            sqlx::query("INSERT INTO post_tags (post_id, tag) SELECT ?, tag FROM post_tags WHERE post_id = ?")
+                .bind(&new_post_id)
+                .bind(&post_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+    }
+    tx.commit().await?;
+
+    get_site_page_by_id(pool, &new_id)
+        .await?
+        .ok_or_else(|| sqlx::Error::RowNotFound)
+}
+
+/// The `copy`-th copy slug of `slug`, shortening `slug` so the result stays
+/// within the 100 characters a slug may have.
+fn copy_slug(slug: &str, copy: u32) -> String {
+    let suffix = if copy <= 1 {
+        "-copy".to_string()
+    } else {
+        format!("-copy-{copy}")
+    };
+    let mut base = &slug[..slug.len().min(100 - suffix.len())];
+    base = base.trim_end_matches('-');
+    format!("{base}{suffix}")
+}
+
 /// Updates an existing site page using selective field merging.
 pub async fn update_site_page(
     pool: &DbPool,
@@ -112,11 +275,42 @@ pub async fn update_site_page(
     }
 
     // Load existing to allow partial updates
-    let mut existing = get_site_page_by_id(pool, id)
+    let existing = get_site_page_by_id(pool, id)
         .await?
         .ok_or(sqlx::Error::RowNotFound)?;
 
-    // Apply updates
+    write_site_page(pool, &merge_page_update(existing, payload)?, None).await?;
+
+    get_site_page_by_id(pool, id)
+        .await?
+        .ok_or_else(|| sqlx::Error::RowNotFound)
+}
+
+/// Applies `payload` on top of `expected` only if the stored row still
+/// matches `expected` column for column. Returns `Ok(None)` when another
+/// write got there first, so callers can answer 409 instead of silently
+/// overwriting it.
+pub async fn update_site_page_if_unmodified(
+    pool: &DbPool,
+    expected: &SitePage,
+    payload: UpdateSitePageRequest,
+) -> Result<Option<SitePage>, sqlx::Error> {
+    if let Some(slug) = payload.slug.as_deref() {
+        validate_slug(slug)?;
+    }
+
+    let updated = merge_page_update(expected.clone(), payload)?;
+    if !write_site_page(pool, &updated, Some(expected)).await? {
+        return Ok(None);
+    }
+
+    get_site_page_by_id(pool, &expected.id).await
+}
+
+fn merge_page_update(
+    mut existing: SitePage,
+    payload: UpdateSitePageRequest,
+) -> Result<SitePage, sqlx::Error> {
     if let Some(slug) = payload.slug {
         existing.slug = slug;
     }
@@ -144,40 +338,257 @@ pub async fn update_site_page(
     if let Some(layout) = payload.layout {
         existing.layout_json = serialize_json_value(&layout)?;
     }
+    if let Some(meta_robots) = payload.meta_robots {
+        existing.meta_robots = meta_robots;
+    }
+    if let Some(custom_headers) = payload.custom_headers {
+        existing.custom_headers_json = serialize_headers(&custom_headers)?;
+    }
+    Ok(existing)
+}
 
-    // Execute UPDATE
-    sqlx::query(concat!(
-        "UPDATE site_pages SET slug = ?, title = ?, description = ?, nav_label = ?, ",
-        "show_in_nav = ?, order_index = ?, is_published = ?, hero_json = ?, ",
-        "layout_json = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?"
-    ))
-    .bind(&existing.slug)
-    .bind(&existing.title)
-    .bind(&existing.description)
-    .bind(&existing.nav_label)
-    .bind(if existing.show_in_nav { 1 } else { 0 })
-    .bind(existing.order_index)
-    .bind(if existing.is_published { 1 } else { 0 })
-    .bind(&existing.hero_json)
-    .bind(&existing.layout_json)
+fn serialize_headers(headers: &BTreeMap<String, String>) -> Result<String, sqlx::Error> {
+    serde_json::to_string(headers)
+        .map_err(|e| sqlx::Error::Protocol(format!("Failed to serialize headers: {e}")))
+}
+
+/// Writes every editable column of `page`. With `fence`, the UPDATE only
+/// matches while the row still holds the fenced values; returns whether a
+/// row was written.
+async fn write_site_page(
+    pool: &DbPool,
+    page: &SitePage,
+    fence: Option<&SitePage>,
+) -> Result<bool, sqlx::Error> {
+    let sql = if fence.is_some() {
+        concat!(
+            "UPDATE site_pages SET slug = ?, title = ?, description = ?, nav_label = ?, ",
+            "show_in_nav = ?, order_index = ?, is_published = ?, hero_json = ?, ",
+            "layout_json = ?, meta_robots = ?, custom_headers_json = ?, ",
+            "updated_at = CURRENT_TIMESTAMP WHERE id = ? AND deleted_at IS NULL ",
+            "AND slug = ? AND title = ? AND description = ? AND nav_label IS ? ",
+            "AND show_in_nav = ? AND order_index = ? AND is_published = ? ",
+            "AND hero_json = ? AND layout_json = ? AND meta_robots IS ? ",
+            "AND custom_headers_json = ? AND updated_at = ?"
+        )
+    } else {
+        concat!(
+            "UPDATE site_pages SET slug = ?, title = ?, description = ?, nav_label = ?, ",
+            "show_in_nav = ?, order_index = ?, is_published = ?, hero_json = ?, ",
+            "layout_json = ?, meta_robots = ?, custom_headers_json = ?, ",
+            "updated_at = CURRENT_TIMESTAMP WHERE id = ? AND deleted_at IS NULL"
+        )
+    };
+
+    let mut query = sqlx::query(sql)
+        .bind(&page.slug)
+        .bind(&page.title)
+        .bind(&page.description)
+        .bind(&page.nav_label)
+        .bind(if page.show_in_nav { 1 } else { 0 })
+        .bind(page.order_index)
+        .bind(if page.is_published { 1 } else { 0 })
+        .bind(&page.hero_json)
+        .bind(&page.layout_json)
+        .bind(&page.meta_robots)
+        .bind(&page.custom_headers_json)
+        .bind(&page.id);
+    if let Some(expected) = fence {
+        query = query
+            .bind(&expected.slug)
+            .bind(&expected.title)
+            .bind(&expected.description)
+            .bind(&expected.nav_label)
+            .bind(if expected.show_in_nav { 1 } else { 0 })
+            .bind(expected.order_index)
+            .bind(if expected.is_published { 1 } else { 0 })
+            .bind(&expected.hero_json)
+            .bind(&expected.layout_json)
+            .bind(&expected.meta_robots)
+            .bind(&expected.custom_headers_json)
+            .bind(&expected.updated_at);
+    }
+
+    let written = query.execute(pool).await?.rows_affected() > 0;
+    if written {
+        invalidate_published_pages_cache();
+    }
+    Ok(written)
+}
+
+/// Deletes a page and, through the cascade, its posts. Both are recorded in
+/// the deletion log in the same transaction.
+pub async fn delete_site_page(
+    pool: &DbPool,
+    id: &str,
+    deleted_by: &str,
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let snapshot = deletion_log::page_snapshot_tx(&mut tx, id)
+        .await?
+        .ok_or(sqlx::Error::RowNotFound)?;
+    deletion_log::record_tx(&mut tx, &snapshot, deleted_by).await?;
+
+    sqlx::query("DELETE FROM site_pages WHERE id = ?")
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    invalidate_published_pages_cache();
+    Ok(())
+}
+
+/// Moves a page to the trash, hiding its posts along with it. Returns
+/// `false` if no page outside the trash has this ID.
+pub async fn trash_site_page(pool: &DbPool, id: &str) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE site_pages SET deleted_at = datetime('now') WHERE id = ? AND deleted_at IS NULL",
+    )
     .bind(id)
     .execute(pool)
     .await?;
+    let trashed = result.rows_affected() > 0;
+    if trashed {
+        invalidate_published_pages_cache();
+    }
+    Ok(trashed)
+}
 
-    get_site_page_by_id(pool, id)
-        .await?
-        .ok_or_else(|| sqlx::Error::RowNotFound)
+/// Takes a page out of the trash. Returns `false` if it was not there.
+pub async fn restore_site_page(pool: &DbPool, id: &str) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE site_pages SET deleted_at = NULL WHERE id = ? AND deleted_at IS NOT NULL",
+    )
+    .bind(id)
+    .execute(pool)
+    .await?;
+    let restored = result.rows_affected() > 0;
+    if restored {
+        invalidate_published_pages_cache();
+    }
+    Ok(restored)
 }
 
-pub async fn delete_site_page(pool: &DbPool, id: &str) -> Result<(), sqlx::Error> {
-    let result = sqlx::query("DELETE FROM site_pages WHERE id = ?")
+/// Whether any page, trashed or not, has this ID. A trashed page still
+/// holds its ID and slug until it is restored or permanently deleted.
+pub async fn check_page_id_taken(pool: &DbPool, id: &str) -> Result<bool, sqlx::Error> {
+    let exists: Option<(i64,)> = sqlx::query_as("SELECT 1 FROM site_pages WHERE id = ?")
         .bind(id)
-        .execute(pool)
+        .fetch_optional(pool)
         .await?;
+    Ok(exists.is_some())
+}
 
-    if result.rows_affected() == 0 {
-        Err(sqlx::Error::RowNotFound)
-    } else {
-        Ok(())
+/// Pages in the trash, most recently deleted first.
+pub async fn list_trashed_pages(pool: &DbPool) -> Result<Vec<TrashedPage>, sqlx::Error> {
+    sqlx::query_as::<_, TrashedPage>(concat!(
+        "SELECT id, slug, title, ",
+        "(SELECT COUNT(*) FROM site_posts WHERE page_id = site_pages.id) AS post_count, ",
+        "deleted_at, updated_at FROM site_pages ",
+        "WHERE deleted_at IS NOT NULL ORDER BY deleted_at DESC, id"
+    ))
+    .fetch_all(pool)
+    .await
+}
+
+/// Permanently deletes the pages that have been in the trash for `days`
+/// days or longer, through [`delete_site_page`]. Returns how many went.
+pub async fn purge_trash_older_than(
+    pool: &DbPool,
+    days: i64,
+    deleted_by: &str,
+) -> Result<u64, sqlx::Error> {
+    let expired: Vec<String> = sqlx::query_scalar(
+        "SELECT id FROM site_pages WHERE deleted_at <= datetime('now', '-' || ? || ' days')",
+    )
+    .bind(days)
+    .fetch_all(pool)
+    .await?;
+
+    let mut purged = 0;
+    for id in expired {
+        match delete_site_page(pool, &id, deleted_by).await {
+            Ok(()) => purged += 1,
+            // Deleted in the meantime
+            Err(sqlx::Error::RowNotFound) => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(purged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn retitle(title: &str) -> UpdateSitePageRequest {
+        UpdateSitePageRequest {
+            slug: None,
+            title: Some(title.to_string()),
+            description: None,
+            nav_label: None,
+            show_in_nav: None,
+            order_index: None,
+            is_published: None,
+            hero: None,
+            layout: None,
+            meta_robots: None,
+            custom_headers: None,
+        }
+    }
+
+    #[test]
+    fn copy_slugs_count_up_and_stay_within_the_length_limit() {
+        assert_eq!(copy_slug("about", 1), "about-copy");
+        assert_eq!(copy_slug("about", 3), "about-copy-3");
+
+        // Cut right after a hyphen, which is dropped as well
+        let long = format!("{}-{}", "a".repeat(91), "b".repeat(8));
+        let copied = copy_slug(&long, 12);
+        assert_eq!(copied, format!("{}-copy-12", "a".repeat(91)));
+        validate_slug(&copied).expect("copy slug is valid");
+    }
+
+    #[tokio::test]
+    async fn fenced_update_refuses_to_overwrite_a_concurrent_change() {
+        let pool = crate::test_support::migrated_pool().await;
+
+        let page = create_site_page(
+            &pool,
+            CreateSitePageRequest {
+                slug: "fenced".to_string(),
+                title: "Original".to_string(),
+                description: None,
+                nav_label: None,
+                show_in_nav: false,
+                order_index: None,
+                is_published: false,
+                hero: json!({}),
+                layout: json!({ "blocks": [] }),
+                meta_robots: None,
+                custom_headers: Default::default(),
+            },
+        )
+        .await
+        .expect("create page");
+
+        // Someone else saves first; the snapshot `page` is now stale.
+        update_site_page(&pool, &page.id, retitle("Theirs"))
+            .await
+            .expect("concurrent update");
+        let stale = update_site_page_if_unmodified(&pool, &page, retitle("Mine"))
+            .await
+            .expect("fenced update");
+        assert!(stale.is_none());
+
+        let fresh = get_site_page_by_id(&pool, &page.id).await.unwrap().unwrap();
+        assert_eq!(fresh.title, "Theirs");
+        let applied = update_site_page_if_unmodified(&pool, &fresh, retitle("Mine"))
+            .await
+            .expect("fenced update")
+            .expect("snapshot is current");
+        assert_eq!(applied.title, "Mine");
     }
 }
@@ -1,33 +1,116 @@
 use crate::db::DbPool;
-use crate::models::{CreateSitePostRequest, SitePost, UpdateSitePostRequest};
+use crate::models::{
+    AdjacentPost, ArchiveMonth, CreateSitePostRequest, SitePost, SitePostRevision,
+    SitePostRevisionSummary, SitePostSummary, TagCount, TrashedPost, UpdateSitePostRequest,
+};
 use crate::repositories::common::validate_slug;
-use sqlx;
+use crate::repositories::deletion_log;
+use crate::repositories::stats::content_stats_json;
+use sqlx::{self, Sqlite};
+use std::collections::HashMap;
 
-/// Lists all posts belonging to a specific page (admin view).
+/// Lists all posts belonging to a specific page (admin view), leaving out
+/// those in the trash.
 pub async fn list_site_posts_for_page(
     pool: &DbPool,
     page_id: &str,
 ) -> Result<Vec<SitePost>, sqlx::Error> {
-    sqlx::query_as::<_, SitePost>(concat!(
+    let mut posts = sqlx::query_as::<_, SitePost>(concat!(
         "SELECT id, page_id, title, slug, excerpt, content_markdown, is_published, ",
-        "allow_comments, published_at, order_index, created_at, updated_at ",
-        "FROM site_posts WHERE page_id = ? ORDER BY order_index, created_at"
+        "allow_comments, published_at, order_index, cover_image_url, meta_description, ",
+        "canonical_url, created_at, updated_at ",
+        "FROM site_posts WHERE page_id = ? AND deleted_at IS NULL ",
+        "ORDER BY order_index, created_at"
     ))
     .bind(page_id)
     .fetch_all(pool)
-    .await
+    .await?;
+    attach_tags(pool, &mut posts).await?;
+    Ok(posts)
+}
+
+/// Narrows the published posts of a page in [`list_published_post_summaries`]
+/// and [`count_published_posts`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PostFilter<'a> {
+    /// Only posts carrying this tag (lowercase).
+    pub tag: Option<&'a str>,
+    /// Only posts of this archive month, as `YYYY-MM`; see [`list_archive`].
+    pub month: Option<&'a str>,
 }
 
-/// Lists all published posts for a specific page, sorted by order index and publication date.
-pub async fn list_published_posts_for_page(
+/// Conditions of [`PostFilter`], binding `tag` twice and then `month` twice.
+const POST_FILTER_SQL: &str = concat!(
+    "AND (? IS NULL OR id IN (SELECT post_id FROM post_tags WHERE tag = ?)) ",
+    "AND (? IS NULL OR strftime('%Y-%m', COALESCE(published_at, created_at)) = ?)"
+);
+
+/// One page of the published posts of a page matching `filter`. `order_by`
+/// must be one of the handlers' fixed `ORDER BY` clauses over `site_posts`,
+/// never user input.
+pub async fn list_published_post_summaries(
     pool: &DbPool,
     page_id: &str,
-) -> Result<Vec<SitePost>, sqlx::Error> {
-    sqlx::query_as::<_, SitePost>(concat!(
-        "SELECT id, page_id, title, slug, excerpt, content_markdown, is_published, ",
-        "allow_comments, published_at, order_index, created_at, updated_at ",
-        "FROM site_posts WHERE page_id = ? AND is_published = 1 ",
-        "ORDER BY order_index, COALESCE(published_at, created_at)"
+    filter: PostFilter<'_>,
+    order_by: &'static str,
+    limit: i64,
+    offset: i64,
+) -> Result<Vec<SitePostSummary>, sqlx::Error> {
+    let mut posts = sqlx::query_as::<_, SitePostSummary>(&format!(
+        "SELECT id, page_id, title, slug, excerpt, published_at, order_index, created_at, \
+         updated_at, COALESCE(json_extract(content_stats, '$.words'), 0) AS word_count \
+         FROM site_posts WHERE page_id = ? AND is_published = 1 AND deleted_at IS NULL \
+         {POST_FILTER_SQL} \
+         ORDER BY {order_by} LIMIT ? OFFSET ?"
+    ))
+    .bind(page_id)
+    .bind(filter.tag)
+    .bind(filter.tag)
+    .bind(filter.month)
+    .bind(filter.month)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(pool)
+    .await?;
+
+    let ids: Vec<&str> = posts.iter().map(|post| post.id.as_str()).collect();
+    let mut tags = load_tags(pool, &ids).await?;
+    for post in &mut posts {
+        post.tags = tags.remove(&post.id).unwrap_or_default();
+    }
+    Ok(posts)
+}
+
+/// Number of published posts of a page matching `filter`.
+pub async fn count_published_posts(
+    pool: &DbPool,
+    page_id: &str,
+    filter: PostFilter<'_>,
+) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar(&format!(
+        "SELECT COUNT(*) FROM site_posts WHERE page_id = ? AND is_published = 1 \
+         AND deleted_at IS NULL {POST_FILTER_SQL}"
+    ))
+    .bind(page_id)
+    .bind(filter.tag)
+    .bind(filter.tag)
+    .bind(filter.month)
+    .bind(filter.month)
+    .fetch_one(pool)
+    .await
+}
+
+/// Published posts of a page counted per month of publication, newest
+/// first. Posts without `published_at` count from `created_at`; months
+/// without posts are left out.
+pub async fn list_archive(pool: &DbPool, page_id: &str) -> Result<Vec<ArchiveMonth>, sqlx::Error> {
+    sqlx::query_as::<_, ArchiveMonth>(concat!(
+        "SELECT CAST(strftime('%Y', posted) AS INTEGER) AS year, ",
+        "CAST(strftime('%m', posted) AS INTEGER) AS month, COUNT(*) AS count ",
+        "FROM (SELECT COALESCE(published_at, created_at) AS posted FROM site_posts ",
+        "WHERE page_id = ? AND is_published = 1 AND deleted_at IS NULL) ",
+        "WHERE strftime('%Y-%m', posted) IS NOT NULL ",
+        "GROUP BY year, month ORDER BY year DESC, month DESC"
     ))
     .bind(page_id)
     .fetch_all(pool)
@@ -39,26 +122,134 @@ pub async fn get_published_post_by_slug(
     page_id: &str,
     post_slug: &str,
 ) -> Result<Option<SitePost>, sqlx::Error> {
-    sqlx::query_as::<_, SitePost>(concat!(
+    let mut post = sqlx::query_as::<_, SitePost>(concat!(
         "SELECT id, page_id, title, slug, excerpt, content_markdown, is_published, ",
-        "allow_comments, published_at, order_index, created_at, updated_at ",
-        "FROM site_posts WHERE page_id = ? AND slug = ? AND is_published = 1"
+        "allow_comments, published_at, order_index, cover_image_url, meta_description, ",
+        "canonical_url, created_at, updated_at ",
+        "FROM site_posts WHERE page_id = ? AND slug = ? AND is_published = 1 ",
+        "AND deleted_at IS NULL"
     ))
     .bind(page_id)
     .bind(post_slug)
     .fetch_optional(pool)
-    .await
+    .await?;
+    if let Some(post) = post.as_mut() {
+        attach_tags(pool, std::slice::from_mut(post)).await?;
+    }
+    Ok(post)
+}
+
+/// The published posts right before and after `post_id` on its page, in
+/// the page's manual order (`order_index`, then `published_at`). A draft
+/// being previewed is placed among the published posts the same way.
+pub async fn get_adjacent_posts(
+    pool: &DbPool,
+    page_id: &str,
+    post_id: &str,
+) -> Result<(Option<AdjacentPost>, Option<AdjacentPost>), sqlx::Error> {
+    type Neighbors = (
+        Option<String>,
+        Option<String>,
+        Option<String>,
+        Option<String>,
+        Option<String>,
+        Option<String>,
+    );
+    let row: Option<Neighbors> = sqlx::query_as(concat!(
+        "SELECT prev_slug, prev_title, prev_excerpt, next_slug, next_title, next_excerpt ",
+        "FROM (SELECT id, ",
+        "LAG(slug) OVER w AS prev_slug, LAG(title) OVER w AS prev_title, ",
+        "LAG(COALESCE(excerpt, '')) OVER w AS prev_excerpt, ",
+        "LEAD(slug) OVER w AS next_slug, LEAD(title) OVER w AS next_title, ",
+        "LEAD(COALESCE(excerpt, '')) OVER w AS next_excerpt ",
+        "FROM site_posts WHERE page_id = ? AND (is_published = 1 OR id = ?) ",
+        "AND deleted_at IS NULL ",
+        "WINDOW w AS (ORDER BY order_index, published_at, id)) WHERE id = ?"
+    ))
+    .bind(page_id)
+    .bind(post_id)
+    .bind(post_id)
+    .fetch_optional(pool)
+    .await?;
+
+    let Some((prev_slug, prev_title, prev_excerpt, next_slug, next_title, next_excerpt)) = row
+    else {
+        return Ok((None, None));
+    };
+    let adjacent = |slug: Option<String>, title: Option<String>, excerpt: Option<String>| {
+        Some(AdjacentPost {
+            slug: slug?,
+            title: title?,
+            excerpt: excerpt.unwrap_or_default(),
+        })
+    };
+    Ok((
+        adjacent(prev_slug, prev_title, prev_excerpt),
+        adjacent(next_slug, next_title, next_excerpt),
+    ))
+}
+
+/// Fetches the published post of a page that was reachable under
+/// `former_slug` before being renamed.
+pub async fn get_published_post_by_former_slug(
+    pool: &DbPool,
+    page_id: &str,
+    former_slug: &str,
+) -> Result<Option<SitePost>, sqlx::Error> {
+    let mut post = sqlx::query_as::<_, SitePost>(concat!(
+        "SELECT p.id, p.page_id, p.title, p.slug, p.excerpt, p.content_markdown, ",
+        "p.is_published, p.allow_comments, p.published_at, p.order_index, ",
+        "p.cover_image_url, p.meta_description, p.canonical_url, p.created_at, ",
+        "p.updated_at FROM post_slug_history h JOIN site_posts p ON p.id = h.post_id ",
+        "WHERE h.page_id = ? AND h.slug = ? AND p.is_published = 1 AND p.deleted_at IS NULL"
+    ))
+    .bind(page_id)
+    .bind(former_slug)
+    .fetch_optional(pool)
+    .await?;
+    if let Some(post) = post.as_mut() {
+        attach_tags(pool, std::slice::from_mut(post)).await?;
+    }
+    Ok(post)
+}
+
+/// Fetches the post `post_slug` of a page whether published or not, for
+/// preview links.
+pub async fn get_post_by_slug(
+    pool: &DbPool,
+    page_id: &str,
+    post_slug: &str,
+) -> Result<Option<SitePost>, sqlx::Error> {
+    let mut post = sqlx::query_as::<_, SitePost>(concat!(
+        "SELECT id, page_id, title, slug, excerpt, content_markdown, is_published, ",
+        "allow_comments, published_at, order_index, cover_image_url, meta_description, ",
+        "canonical_url, created_at, updated_at ",
+        "FROM site_posts WHERE page_id = ? AND slug = ? AND deleted_at IS NULL"
+    ))
+    .bind(page_id)
+    .bind(post_slug)
+    .fetch_optional(pool)
+    .await?;
+    if let Some(post) = post.as_mut() {
+        attach_tags(pool, std::slice::from_mut(post)).await?;
+    }
+    Ok(post)
 }
 
 pub async fn get_site_post_by_id(pool: &DbPool, id: &str) -> Result<Option<SitePost>, sqlx::Error> {
-    sqlx::query_as::<_, SitePost>(concat!(
+    let mut post = sqlx::query_as::<_, SitePost>(concat!(
         "SELECT id, page_id, title, slug, excerpt, content_markdown, is_published, ",
-        "allow_comments, published_at, order_index, created_at, updated_at ",
-        "FROM site_posts WHERE id = ?"
+        "allow_comments, published_at, order_index, cover_image_url, meta_description, ",
+        "canonical_url, created_at, updated_at ",
+        "FROM site_posts WHERE id = ? AND deleted_at IS NULL"
     ))
     .bind(id)
     .fetch_optional(pool)
-    .await
+    .await?;
+    if let Some(post) = post.as_mut() {
+        attach_tags(pool, std::slice::from_mut(post)).await?;
+    }
+    Ok(post)
 }
 
 /// Creates a new blog post for a parent page.
@@ -66,21 +257,33 @@ pub async fn create_site_post(
     pool: &DbPool,
     page_id: &str,
     payload: CreateSitePostRequest,
+) -> Result<SitePost, sqlx::Error> {
+    create_site_post_with_id(pool, &uuid::Uuid::new_v4().to_string(), page_id, payload).await
+}
+
+/// Creates a blog post under a caller-chosen ID (used when restoring).
+/// Drops any history entry for the slug, which now belongs to this post.
+pub async fn create_site_post_with_id(
+    pool: &DbPool,
+    id: &str,
+    page_id: &str,
+    payload: CreateSitePostRequest,
 ) -> Result<SitePost, sqlx::Error> {
     // Validate slug hygiene
     validate_slug(&payload.slug)?;
 
-    let id = uuid::Uuid::new_v4().to_string();
     let excerpt = payload.excerpt.unwrap_or_default();
     let order_index = payload.order_index.unwrap_or(0);
 
     // Insert record
+    let mut tx = pool.begin().await?;
     sqlx::query(concat!(
         "INSERT INTO site_posts (id, page_id, title, slug, excerpt, content_markdown, ",
-        "is_published, allow_comments, published_at, order_index) ",
-        "VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
+        "is_published, allow_comments, published_at, order_index, content_stats, ",
+        "cover_image_url, meta_description, canonical_url) ",
+        "VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
     ))
-    .bind(&id)
+    .bind(id)
     .bind(page_id)
     .bind(&payload.title)
     .bind(&payload.slug)
@@ -90,29 +293,53 @@ pub async fn create_site_post(
     .bind(if payload.allow_comments { 1 } else { 0 })
     .bind(payload.published_at)
     .bind(order_index)
-    .execute(pool)
+    .bind(content_stats_json(&payload.content_markdown))
+    .bind(&payload.cover_image_url)
+    .bind(&payload.meta_description)
+    .bind(&payload.canonical_url)
+    .execute(&mut *tx)
     .await?;
+    // The new post owns this slug now; an old link must not lead elsewhere
+    sqlx::query("DELETE FROM post_slug_history WHERE page_id = ? AND slug = ?")
+        .bind(page_id)
+        .bind(&payload.slug)
+        .execute(&mut *tx)
+        .await?;
+    replace_post_tags_tx(&mut tx, id, &payload.tags).await?;
+    tx.commit().await?;
+    invalidate_public_caches();
 
     // Return created state
-    get_site_post_by_id(pool, &id)
+    get_site_post_by_id(pool, id)
         .await?
         .ok_or_else(|| sqlx::Error::RowNotFound)
 }
 
 /// Updates an existing blog post using field merging.
+///
+/// Returns the post as it was before the update alongside the stored
+/// result, so callers can react to transitions (e.g. first publication)
+/// without a second lookup.
+///
+/// The replaced state is kept in `site_post_revisions`, attributed to
+/// `editor`, and the oldest revisions beyond `POST_REVISION_LIMIT` are
+/// dropped, all in the same transaction. A replaced slug goes into
+/// `post_slug_history` so links using it keep resolving.
 pub async fn update_site_post(
     pool: &DbPool,
+    editor: &str,
     id: &str,
     payload: UpdateSitePostRequest,
-) -> Result<SitePost, sqlx::Error> {
+) -> Result<(SitePost, SitePost), sqlx::Error> {
     if let Some(slug) = payload.slug.as_deref() {
         validate_slug(slug)?;
     }
 
     // Load existing
-    let mut existing = get_site_post_by_id(pool, id)
+    let previous = get_site_post_by_id(pool, id)
         .await?
         .ok_or(sqlx::Error::RowNotFound)?;
+    let mut existing = previous.clone();
 
     // Merge changes
     if let Some(title) = payload.title {
@@ -139,11 +366,36 @@ pub async fn update_site_post(
     if let Some(order_index) = payload.order_index {
         existing.order_index = order_index;
     }
+    if let Some(cover_image_url) = payload.cover_image_url {
+        existing.cover_image_url = cover_image_url;
+    }
+    if let Some(meta_description) = payload.meta_description {
+        existing.meta_description = meta_description;
+    }
+    if let Some(canonical_url) = payload.canonical_url {
+        existing.canonical_url = canonical_url;
+    }
+
+    // Keep the state about to be replaced as the post's next revision
+    let mut tx = pool.begin().await?;
+    sqlx::query(concat!(
+        "INSERT INTO site_post_revisions (post_id, revision, title, slug, excerpt, ",
+        "content_markdown, is_published, published_at, editor) ",
+        "SELECT id, (SELECT COALESCE(MAX(revision), 0) + 1 FROM site_post_revisions ",
+        "WHERE post_id = ?), title, slug, excerpt, content_markdown, is_published, ",
+        "published_at, ? FROM site_posts WHERE id = ?"
+    ))
+    .bind(id)
+    .bind(editor)
+    .bind(id)
+    .execute(&mut *tx)
+    .await?;
 
     // Save back to DB
     sqlx::query(concat!(
         "UPDATE site_posts SET title = ?, slug = ?, excerpt = ?, content_markdown = ?, ",
         "is_published = ?, allow_comments = ?, published_at = ?, order_index = ?, ",
+        "content_stats = ?, cover_image_url = ?, meta_description = ?, canonical_url = ?, ",
         "updated_at = CURRENT_TIMESTAMP WHERE id = ?"
     ))
     .bind(&existing.title)
@@ -154,32 +406,277 @@ pub async fn update_site_post(
     .bind(if existing.allow_comments { 1 } else { 0 })
     .bind(&existing.published_at)
     .bind(existing.order_index)
+    .bind(content_stats_json(&existing.content_markdown))
+    .bind(&existing.cover_image_url)
+    .bind(&existing.meta_description)
+    .bind(&existing.canonical_url)
     .bind(id)
-    .execute(pool)
+    .execute(&mut *tx)
+    .await?;
+    if let Some(tags) = payload.tags.as_deref() {
+        replace_post_tags_tx(&mut tx, id, tags).await?;
+    }
+    if existing.slug != previous.slug {
+        sqlx::query("DELETE FROM post_slug_history WHERE page_id = ? AND slug = ?")
+            .bind(&existing.page_id)
+            .bind(&existing.slug)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query(
+            "INSERT OR REPLACE INTO post_slug_history (page_id, slug, post_id) VALUES (?, ?, ?)",
+        )
+        .bind(&previous.page_id)
+        .bind(&previous.slug)
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+    }
+    sqlx::query(concat!(
+        "DELETE FROM site_post_revisions WHERE post_id = ? AND revision NOT IN ",
+        "(SELECT revision FROM site_post_revisions WHERE post_id = ? ",
+        "ORDER BY revision DESC LIMIT ?)"
+    ))
+    .bind(id)
+    .bind(id)
+    .bind(i64::from(crate::config::get().post_revision_limit))
+    .execute(&mut *tx)
     .await?;
+    tx.commit().await?;
+    invalidate_public_caches();
 
-    get_site_post_by_id(pool, id)
+    let updated = get_site_post_by_id(pool, id)
         .await?
-        .ok_or_else(|| sqlx::Error::RowNotFound)
+        .ok_or_else(|| sqlx::Error::RowNotFound)?;
+    Ok((previous, updated))
+}
+
+/// Stored revisions of a post, newest first.
+pub async fn list_revisions(
+    pool: &DbPool,
+    post_id: &str,
+) -> Result<Vec<SitePostRevisionSummary>, sqlx::Error> {
+    sqlx::query_as::<_, SitePostRevisionSummary>(concat!(
+        "SELECT revision, title, editor, created_at FROM site_post_revisions ",
+        "WHERE post_id = ? ORDER BY revision DESC"
+    ))
+    .bind(post_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// One stored revision of a post.
+pub async fn get_revision(
+    pool: &DbPool,
+    post_id: &str,
+    revision: i64,
+) -> Result<Option<SitePostRevision>, sqlx::Error> {
+    sqlx::query_as::<_, SitePostRevision>(concat!(
+        "SELECT revision, title, slug, excerpt, content_markdown, is_published, ",
+        "published_at, editor, created_at FROM site_post_revisions ",
+        "WHERE post_id = ? AND revision = ?"
+    ))
+    .bind(post_id)
+    .bind(revision)
+    .fetch_optional(pool)
+    .await
 }
 
-pub async fn delete_site_post(pool: &DbPool, id: &str) -> Result<(), sqlx::Error> {
-    let result = sqlx::query("DELETE FROM site_posts WHERE id = ?")
+/// Deletes a post after recording it in the deletion log. Its comments are
+/// not cascaded and stay in place.
+pub async fn delete_site_post(
+    pool: &DbPool,
+    id: &str,
+    deleted_by: &str,
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let snapshot = deletion_log::post_snapshot_tx(&mut tx, id)
+        .await?
+        .ok_or(sqlx::Error::RowNotFound)?;
+    deletion_log::record_tx(&mut tx, &snapshot, deleted_by).await?;
+
+    sqlx::query("DELETE FROM site_posts WHERE id = ?")
         .bind(id)
-        .execute(pool)
+        .execute(&mut *tx)
         .await?;
+    tx.commit().await?;
+    invalidate_public_caches();
+    Ok(())
+}
 
-    if result.rows_affected() == 0 {
-        Err(sqlx::Error::RowNotFound)
-    } else {
-        Ok(())
+/// Fills in the `tags` of `posts` from `post_tags`, alphabetically.
+pub(crate) async fn attach_tags<'e, E>(
+    executor: E,
+    posts: &mut [SitePost],
+) -> Result<(), sqlx::Error>
+where
+    E: sqlx::Executor<'e, Database = Sqlite>,
+{
+    let ids: Vec<&str> = posts.iter().map(|post| post.id.as_str()).collect();
+    let mut tags = load_tags(executor, &ids).await?;
+    for post in posts {
+        post.tags = tags.remove(&post.id).unwrap_or_default();
     }
+    Ok(())
 }
 
-pub async fn check_post_exists(pool: &DbPool, id: &str) -> Result<bool, sqlx::Error> {
+/// Tags of the posts `ids`, alphabetically per post.
+async fn load_tags<'e, E>(
+    executor: E,
+    ids: &[&str],
+) -> Result<HashMap<String, Vec<String>>, sqlx::Error>
+where
+    E: sqlx::Executor<'e, Database = Sqlite>,
+{
+    let mut tags: HashMap<String, Vec<String>> = HashMap::new();
+    if ids.is_empty() {
+        return Ok(tags);
+    }
+
+    let mut query_builder =
+        sqlx::QueryBuilder::new("SELECT post_id, tag FROM post_tags WHERE post_id IN (");
+    let mut separated = query_builder.separated(", ");
+    for id in ids {
+        separated.push_bind(*id);
+    }
+    query_builder.push(") ORDER BY tag");
+
+    let rows: Vec<(String, String)> = query_builder.build_query_as().fetch_all(executor).await?;
+    for (post_id, tag) in rows {
+        tags.entry(post_id).or_default().push(tag);
+    }
+    Ok(tags)
+}
+
+/// Replaces the tags of a post. `tags` must already be sanitized.
+pub(crate) async fn replace_post_tags_tx(
+    tx: &mut sqlx::Transaction<'_, Sqlite>,
+    post_id: &str,
+    tags: &[String],
+) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM post_tags WHERE post_id = ?")
+        .bind(post_id)
+        .execute(&mut **tx)
+        .await?;
+
+    for tag in tags {
+        sqlx::query("INSERT INTO post_tags (post_id, tag) VALUES (?, ?)")
+            .bind(post_id)
+            .bind(tag)
+            .execute(&mut **tx)
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Moves a post to the trash. Returns `false` if no post outside the trash
+/// has this ID.
+pub async fn trash_site_post(pool: &DbPool, id: &str) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE site_posts SET deleted_at = datetime('now') WHERE id = ? AND deleted_at IS NULL",
+    )
+    .bind(id)
+    .execute(pool)
+    .await?;
+    let trashed = result.rows_affected() > 0;
+    if trashed {
+        invalidate_public_caches();
+    }
+    Ok(trashed)
+}
+
+/// Takes a post out of the trash. Returns `false` if it was not there. A
+/// post of a trashed page stays hidden until the page is restored too.
+pub async fn restore_site_post(pool: &DbPool, id: &str) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE site_posts SET deleted_at = NULL WHERE id = ? AND deleted_at IS NOT NULL",
+    )
+    .bind(id)
+    .execute(pool)
+    .await?;
+    let restored = result.rows_affected() > 0;
+    if restored {
+        invalidate_public_caches();
+    }
+    Ok(restored)
+}
+
+/// Whether any post, trashed or not, has this ID.
+pub async fn check_post_id_taken(pool: &DbPool, id: &str) -> Result<bool, sqlx::Error> {
     let exists: Option<(i64,)> = sqlx::query_as("SELECT 1 FROM site_posts WHERE id = ?")
         .bind(id)
         .fetch_optional(pool)
         .await?;
     Ok(exists.is_some())
 }
+
+/// Posts in the trash with the title of their page, most recently deleted
+/// first. Posts of a trashed page are only listed if they were trashed on
+/// their own.
+pub async fn list_trashed_posts(pool: &DbPool) -> Result<Vec<TrashedPost>, sqlx::Error> {
+    sqlx::query_as::<_, TrashedPost>(concat!(
+        "SELECT sp.id, sp.page_id, pg.title AS page_title, sp.title, sp.slug, ",
+        "sp.deleted_at, sp.updated_at FROM site_posts sp ",
+        "INNER JOIN site_pages pg ON pg.id = sp.page_id ",
+        "WHERE sp.deleted_at IS NOT NULL ORDER BY sp.deleted_at DESC, sp.id"
+    ))
+    .fetch_all(pool)
+    .await
+}
+
+/// Permanently deletes the posts that have been in the trash for `days`
+/// days or longer, through [`delete_site_post`]. Returns how many went.
+pub async fn purge_trash_older_than(
+    pool: &DbPool,
+    days: i64,
+    deleted_by: &str,
+) -> Result<u64, sqlx::Error> {
+    let expired: Vec<String> = sqlx::query_scalar(
+        "SELECT id FROM site_posts WHERE deleted_at <= datetime('now', '-' || ? || ' days')",
+    )
+    .bind(days)
+    .fetch_all(pool)
+    .await?;
+
+    let mut purged = 0;
+    for id in expired {
+        match delete_site_post(pool, &id, deleted_by).await {
+            Ok(()) => purged += 1,
+            // Deleted in the meantime, possibly along with its page
+            Err(sqlx::Error::RowNotFound) => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(purged)
+}
+
+/// Tags of published posts on published pages, with how many such posts
+/// carry each, alphabetically.
+pub async fn list_tag_counts(pool: &DbPool) -> Result<Vec<TagCount>, sqlx::Error> {
+    sqlx::query_as::<_, TagCount>(concat!(
+        "SELECT pt.tag, COUNT(*) AS count FROM post_tags pt ",
+        "INNER JOIN site_posts sp ON sp.id = pt.post_id ",
+        "INNER JOIN site_pages pg ON pg.id = sp.page_id ",
+        "WHERE sp.is_published = 1 AND pg.is_published = 1 ",
+        "AND sp.deleted_at IS NULL AND pg.deleted_at IS NULL ",
+        "GROUP BY pt.tag ORDER BY pt.tag"
+    ))
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn check_post_exists(pool: &DbPool, id: &str) -> Result<bool, sqlx::Error> {
+    let exists: Option<(i64,)> =
+        sqlx::query_as("SELECT 1 FROM site_posts WHERE id = ? AND deleted_at IS NULL")
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+    Ok(exists.is_some())
+}
+
+/// Drops the cached views a post write can change: the sitemap and the
+/// published page bundles listing the first posts of each page.
+fn invalidate_public_caches() {
+    crate::cache::PAGE_BUNDLES.clear();
+    crate::sitemap::invalidate();
+}
@@ -0,0 +1,64 @@
+//! Per-page and per-post nonces behind preview links, see
+//! [`crate::security::preview_token`].
+
+use crate::db::DbPool;
+use crate::security::preview_token::{new_nonce, PreviewKind};
+
+fn table(kind: PreviewKind) -> &'static str {
+    match kind {
+        PreviewKind::Page => "site_pages",
+        PreviewKind::Post => "site_posts",
+    }
+}
+
+/// The nonce of page or post `id`; `None` if it does not exist or no
+/// preview link is active.
+pub async fn preview_nonce(
+    pool: &DbPool,
+    kind: PreviewKind,
+    id: &str,
+) -> Result<Option<String>, sqlx::Error> {
+    let nonce: Option<Option<String>> = sqlx::query_scalar(&format!(
+        "SELECT preview_nonce FROM {} WHERE id = ?",
+        table(kind)
+    ))
+    .bind(id)
+    .fetch_optional(pool)
+    .await?;
+    Ok(nonce.flatten())
+}
+
+/// The nonce of page or post `id`, generating one if no preview link is
+/// active yet. `None` if it does not exist. Leaves `updated_at` alone.
+pub async fn ensure_preview_nonce(
+    pool: &DbPool,
+    kind: PreviewKind,
+    id: &str,
+) -> Result<Option<String>, sqlx::Error> {
+    sqlx::query_scalar(&format!(
+        "UPDATE {} SET preview_nonce = COALESCE(preview_nonce, ?) WHERE id = ? \
+         RETURNING preview_nonce",
+        table(kind)
+    ))
+    .bind(new_nonce())
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Clears the nonce of page or post `id`, revoking all of its preview
+/// links. Returns `false` if it does not exist.
+pub async fn clear_preview_nonce(
+    pool: &DbPool,
+    kind: PreviewKind,
+    id: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(&format!(
+        "UPDATE {} SET preview_nonce = NULL WHERE id = ?",
+        table(kind)
+    ))
+    .bind(id)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
@@ -0,0 +1,75 @@
+//! Maintenance of the full-text search index. Triggers keep `tutorials_fts`
+//! in step with `tutorials` and `tutorial_sections`; these functions detect
+//! and repair drift when that failed.
+
+use crate::db::migrations::{create_tutorials_fts_sql, fts_tokenizer_in, indexed_tutorial_content};
+use crate::db::DbPool;
+
+/// Row counts of one search index and the table it is built from.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct IndexStatus {
+    /// Name of the FTS table.
+    pub index: &'static str,
+    /// Rows that should be indexed.
+    pub source_rows: i64,
+    /// Rows in the index.
+    pub indexed_rows: i64,
+    /// Source rows the index has no row for.
+    pub missing: i64,
+    /// Index rows with no source row, or more than one for the same source.
+    pub stale: i64,
+    /// FTS5 tokenizer the index was created with.
+    pub tokenizer: String,
+}
+
+/// Recreates `tutorials_fts` with `tokenizer` and indexes every tutorial not
+/// in the trash, in one transaction. Returns the number of rows indexed.
+///
+/// The triggers that maintain the index refer to it by name, so they keep
+/// working on the new table.
+pub async fn rebuild_tutorials(pool: &DbPool, tokenizer: &str) -> Result<u64, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("DROP TABLE IF EXISTS tutorials_fts")
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query(&create_tutorials_fts_sql(tokenizer))
+        .execute(&mut *tx)
+        .await?;
+    let indexed = sqlx::query(&format!(
+        "INSERT INTO tutorials_fts(tutorial_id, title, description, content, topics) \
+         SELECT t.id, t.title, t.description, {}, t.topics FROM tutorials AS t \
+         WHERE t.deleted_at IS NULL",
+        indexed_tutorial_content("t.content", "t.id")
+    ))
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+    tx.commit().await?;
+    Ok(indexed)
+}
+
+pub async fn tutorials_status(pool: &DbPool) -> Result<IndexStatus, sqlx::Error> {
+    let (source_rows, indexed_rows, missing): (i64, i64, i64) = sqlx::query_as(
+        "SELECT \
+         (SELECT COUNT(*) FROM tutorials WHERE deleted_at IS NULL), \
+         (SELECT COUNT(*) FROM tutorials_fts), \
+         (SELECT COUNT(*) FROM tutorials t WHERE t.deleted_at IS NULL AND NOT EXISTS \
+            (SELECT 1 FROM tutorials_fts f WHERE f.tutorial_id = t.id))",
+    )
+    .fetch_one(pool)
+    .await?;
+    let create_sql: String = sqlx::query_scalar(
+        "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = 'tutorials_fts'",
+    )
+    .fetch_one(pool)
+    .await?;
+    Ok(IndexStatus {
+        index: "tutorials_fts",
+        source_rows,
+        indexed_rows,
+        missing,
+        // Every index row beyond one per live tutorial is stale
+        stale: indexed_rows - (source_rows - missing),
+        tokenizer: fts_tokenizer_in(&create_sql),
+    })
+}
@@ -0,0 +1,70 @@
+//! Logged public search queries, see [`crate::search_log`].
+
+use crate::db::DbPool;
+use crate::models::{SearchDailyVolume, SearchQueryCount};
+
+/// Stores one search of the already normalized `query`.
+pub async fn insert(
+    pool: &DbPool,
+    query: &str,
+    result_count: i64,
+    ip_hash: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("INSERT INTO search_log (query, result_count, ip_hash) VALUES (?, ?, ?)")
+        .bind(query)
+        .bind(result_count)
+        .bind(ip_hash)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// The `limit` most searched queries of the last `days` days (today
+/// included), most frequent first. With `zero_results_only`, only searches
+/// that found nothing are counted.
+pub async fn top_queries(
+    pool: &DbPool,
+    days: i64,
+    zero_results_only: bool,
+    limit: i64,
+) -> Result<Vec<SearchQueryCount>, sqlx::Error> {
+    sqlx::query_as::<_, SearchQueryCount>(concat!(
+        "SELECT query, COUNT(*) AS searches, COUNT(DISTINCT ip_hash) AS visitors, ",
+        "MAX(searched_at) AS last_searched_at FROM search_log ",
+        "WHERE date(searched_at) > date('now', '-' || ? || ' days') AND (NOT ? OR result_count = 0) ",
+        "GROUP BY query ORDER BY searches DESC, last_searched_at DESC, query LIMIT ?"
+    ))
+    .bind(days)
+    .bind(zero_results_only)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
+
+/// Searches per day of the last `days` days (today included), oldest first.
+pub async fn daily_volumes(
+    pool: &DbPool,
+    days: i64,
+) -> Result<Vec<SearchDailyVolume>, sqlx::Error> {
+    sqlx::query_as::<_, SearchDailyVolume>(concat!(
+        "SELECT date(searched_at) AS day, COUNT(*) AS searches, ",
+        "SUM(result_count = 0) AS zero_results FROM search_log ",
+        "WHERE date(searched_at) > date('now', '-' || ? || ' days') ",
+        "GROUP BY day ORDER BY day"
+    ))
+    .bind(days)
+    .fetch_all(pool)
+    .await
+}
+
+/// Deletes searches older than `days` days.
+pub async fn purge_older_than(pool: &DbPool, days: i64) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        "DELETE FROM search_log WHERE searched_at <= datetime('now', '-' || ? || ' days')",
+    )
+    .bind(days)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,71 @@
+//! Daily counters of rejected CSRF checks, tokens and logins.
+
+use crate::db::DbPool;
+use crate::models::SecurityCounterRow;
+
+/// Adds one rejection to today's row for `(source, reason)`.
+pub async fn increment(pool: &DbPool, source: &str, reason: &str) -> Result<(), sqlx::Error> {
+    sqlx::query(concat!(
+        "INSERT INTO security_counters (day, source, reason, count) ",
+        "VALUES (date('now'), ?, ?, 1) ",
+        "ON CONFLICT(day, source, reason) DO UPDATE SET count = count + 1"
+    ))
+    .bind(source)
+    .bind(reason)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Rows of the last `days` days (today included), newest day first.
+pub async fn list_recent(pool: &DbPool, days: i64) -> Result<Vec<SecurityCounterRow>, sqlx::Error> {
+    sqlx::query_as::<_, SecurityCounterRow>(concat!(
+        "SELECT day, source, reason, count FROM security_counters ",
+        "WHERE day > date('now', '-' || ? || ' days') ",
+        "ORDER BY day DESC, source, reason"
+    ))
+    .bind(days)
+    .fetch_all(pool)
+    .await
+}
+
+/// Deletes rows older than `days` days.
+pub async fn prune_older_than(pool: &DbPool, days: i64) -> Result<u64, sqlx::Error> {
+    let result =
+        sqlx::query("DELETE FROM security_counters WHERE day <= date('now', '-' || ? || ' days')")
+            .bind(days)
+            .execute(pool)
+            .await?;
+    Ok(result.rows_affected())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn increments_accumulate_per_day_and_reason() {
+        let pool = crate::test_support::migrated_pool().await;
+
+        for _ in 0..3 {
+            increment(&pool, "csrf", "mismatch").await.unwrap();
+        }
+        increment(&pool, "token", "expired").await.unwrap();
+        sqlx::query(
+            "INSERT INTO security_counters (day, source, reason, count) VALUES (date('now', '-40 days'), 'token', 'expired', 9)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let rows = list_recent(&pool, 30).await.unwrap();
+        let counts: Vec<_> = rows
+            .iter()
+            .map(|row| (row.source.as_str(), row.reason.as_str(), row.count))
+            .collect();
+        assert_eq!(counts, [("csrf", "mismatch", 3), ("token", "expired", 1)]);
+
+        assert_eq!(prune_older_than(&pool, 30).await.unwrap(), 1);
+    }
+}
@@ -0,0 +1,109 @@
+//! Login sessions, one row per `jti`. A session stays listed until its
+//! last token expires or it is revoked.
+
+use crate::db::DbPool;
+use crate::models::Session;
+
+pub async fn create_session(
+    pool: &DbPool,
+    jti: &str,
+    username: &str,
+    expires_at: i64,
+    user_agent: Option<&str>,
+    ip_hash: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(concat!(
+        "INSERT INTO sessions (jti, username, expires_at, user_agent, ip_hash) ",
+        "VALUES (?, ?, ?, ?, ?)"
+    ))
+    .bind(jti)
+    .bind(username)
+    .bind(expires_at)
+    .bind(user_agent)
+    .bind(ip_hash)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Moves the expiry of a refreshed session.
+pub async fn extend_session(pool: &DbPool, jti: &str, expires_at: i64) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE sessions SET expires_at = ? WHERE jti = ?")
+        .bind(expires_at)
+        .bind(jti)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Whether the session was revoked. A token without a session row counts
+/// as revoked: every login records one, so a missing row means the session
+/// was deleted or never existed.
+pub async fn is_session_revoked(pool: &DbPool, jti: &str) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM sessions WHERE jti = ? AND revoked_at IS NULL",
+    )
+    .bind(jti)
+    .fetch_one(pool)
+    .await
+    .map(|count| count == 0)
+}
+
+pub async fn list_active_sessions(
+    pool: &DbPool,
+    username: &str,
+) -> Result<Vec<Session>, sqlx::Error> {
+    let now = chrono::Utc::now().timestamp();
+    sqlx::query_as::<_, Session>(concat!(
+        "SELECT jti, issued_at, expires_at, user_agent FROM sessions ",
+        "WHERE username = ? AND revoked_at IS NULL AND expires_at > ? ",
+        "ORDER BY issued_at DESC, jti"
+    ))
+    .bind(username)
+    .bind(now)
+    .fetch_all(pool)
+    .await
+}
+
+/// Revokes one of `username`'s sessions. Returns `false` if there is no
+/// such unrevoked session.
+pub async fn revoke_session(pool: &DbPool, username: &str, jti: &str) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(concat!(
+        "UPDATE sessions SET revoked_at = datetime('now') ",
+        "WHERE jti = ? AND username = ? AND revoked_at IS NULL"
+    ))
+    .bind(jti)
+    .bind(username)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Revokes every session of `username` and returns how many were active.
+///
+/// Takes any executor so an account change can revoke inside its own
+/// transaction.
+pub async fn revoke_all_sessions<'e, E>(executor: E, username: &str) -> Result<u64, sqlx::Error>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Sqlite>,
+{
+    let now = chrono::Utc::now().timestamp();
+    let result = sqlx::query(concat!(
+        "UPDATE sessions SET revoked_at = datetime('now') ",
+        "WHERE username = ? AND revoked_at IS NULL AND expires_at > ?"
+    ))
+    .bind(username)
+    .bind(now)
+    .execute(executor)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+pub async fn cleanup_expired_sessions(pool: &DbPool) -> Result<u64, sqlx::Error> {
+    let now = chrono::Utc::now().timestamp();
+    let result = sqlx::query("DELETE FROM sessions WHERE expires_at < ?")
+        .bind(now)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
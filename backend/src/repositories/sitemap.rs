@@ -0,0 +1,26 @@
+//! Public documents listed in the sitemap, see [`crate::sitemap`].
+
+use crate::db::DbPool;
+use crate::models::{SitemapDocument, TUTORIAL_STATUS_PUBLISHED};
+
+/// Published pages, published posts on published pages and published
+/// tutorials, all outside the trash, with the paths the frontend serves them at.
+/// Robots directives are left for the caller to apply.
+pub async fn list_public_documents(pool: &DbPool) -> Result<Vec<SitemapDocument>, sqlx::Error> {
+    sqlx::query_as::<_, SitemapDocument>(concat!(
+        "SELECT 'page' AS kind, '/pages/' || slug AS path, updated_at, meta_robots ",
+        "FROM site_pages WHERE is_published = 1 AND deleted_at IS NULL ",
+        "UNION ALL ",
+        "SELECT 'post', '/posts/' || pg.slug || '/' || sp.slug, sp.updated_at, pg.meta_robots ",
+        "FROM site_posts sp INNER JOIN site_pages pg ON pg.id = sp.page_id ",
+        "WHERE sp.is_published = 1 AND pg.is_published = 1 ",
+        "AND sp.deleted_at IS NULL AND pg.deleted_at IS NULL ",
+        "UNION ALL ",
+        "SELECT 'tutorial', '/tutorials/' || id, updated_at, NULL ",
+        "FROM tutorials WHERE status = ? AND deleted_at IS NULL ",
+        "ORDER BY kind, path"
+    ))
+    .bind(TUTORIAL_STATUS_PUBLISHED)
+    .fetch_all(pool)
+    .await
+}
@@ -0,0 +1,264 @@
+//! Content volume statistics.
+//!
+//! Word counts are computed once when a tutorial or post is saved and cached
+//! in the `content_stats` JSON column (`{"words": n}`), so the dashboard
+//! overview is a handful of aggregate queries instead of a scan over every
+//! document body.
+
+use crate::db::DbPool;
+use crate::models::{
+    ContentAreaStats, ContentStatsResponse, DocumentWordCount, PageContentStats, StaleDocument,
+};
+use sqlx::FromRow;
+use std::collections::HashMap;
+
+/// Rows backfilled per round trip; keeps memory flat on large databases.
+const BACKFILL_BATCH_SIZE: i64 = 100;
+
+/// Counts the words of a markdown document in a single pass.
+///
+/// A word is any whitespace-separated token containing at least one
+/// alphanumeric character, so markdown syntax such as `#`, `-`, `>` or code
+/// fences doesn't inflate the count.
+pub fn count_words(text: &str) -> i64 {
+    let mut words = 0;
+    let mut in_token = false;
+    let mut token_has_alnum = false;
+
+    for c in text.chars() {
+        if c.is_whitespace() {
+            if in_token && token_has_alnum {
+                words += 1;
+            }
+            in_token = false;
+            token_has_alnum = false;
+        } else {
+            in_token = true;
+            token_has_alnum |= c.is_alphanumeric();
+        }
+    }
+    if in_token && token_has_alnum {
+        words += 1;
+    }
+
+    words
+}
+
+/// Serialized value stored in the `content_stats` column for `text`.
+pub fn content_stats_json(text: &str) -> String {
+    serde_json::json!({ "words": count_words(text) }).to_string()
+}
+
+/// Fills in `content_stats` for rows written without it (seeded content,
+/// the import binary, or rows that predate the column). Returns the number
+/// of rows updated.
+pub async fn backfill_content_stats(pool: &DbPool) -> Result<u64, sqlx::Error> {
+    let mut updated = 0;
+    for (table, body_column) in [("tutorials", "content"), ("site_posts", "content_markdown")] {
+        loop {
+            let rows: Vec<(String, String)> = sqlx::query_as(&format!(
+                "SELECT id, {body_column} FROM {table} WHERE content_stats IS NULL LIMIT ?"
+            ))
+            .bind(BACKFILL_BATCH_SIZE)
+            .fetch_all(pool)
+            .await?;
+
+            if rows.is_empty() {
+                break;
+            }
+
+            let mut tx = pool.begin().await?;
+            for (id, body) in &rows {
+                sqlx::query(&format!(
+                    "UPDATE {table} SET content_stats = ? WHERE id = ?"
+                ))
+                .bind(content_stats_json(body))
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+            }
+            tx.commit().await?;
+            updated += rows.len() as u64;
+        }
+    }
+
+    Ok(updated)
+}
+
+#[derive(FromRow)]
+struct AreaTotalsRow {
+    documents: i64,
+    published: i64,
+    total_words: i64,
+    average_words: Option<f64>,
+}
+
+#[derive(FromRow)]
+struct PageTotalsRow {
+    page_id: String,
+    slug: String,
+    title: String,
+    is_published: bool,
+    documents: i64,
+    published: i64,
+    total_words: i64,
+    average_words: Option<f64>,
+}
+
+#[derive(FromRow)]
+struct PostExtremeRow {
+    page_id: String,
+    id: String,
+    title: String,
+    words: i64,
+    longest_rank: i64,
+    shortest_rank: i64,
+}
+
+impl From<AreaTotalsRow> for ContentAreaStats {
+    fn from(row: AreaTotalsRow) -> Self {
+        ContentAreaStats {
+            documents: row.documents,
+            published: row.published,
+            drafts: row.documents - row.published,
+            total_words: row.total_words,
+            average_words: row.average_words.unwrap_or(0.0),
+            longest: None,
+            shortest: None,
+        }
+    }
+}
+
+/// Builds the dashboard overview from the cached word counts.
+pub async fn content_overview(
+    pool: &DbPool,
+    stale_after_days: i64,
+) -> Result<ContentStatsResponse, sqlx::Error> {
+    let mut tutorials: ContentAreaStats = sqlx::query_as::<_, AreaTotalsRow>(concat!(
+        "SELECT COUNT(*) AS documents, COUNT(*) AS published, ",
+        "COALESCE(SUM(json_extract(content_stats, '$.words')), 0) AS total_words, ",
+        "AVG(json_extract(content_stats, '$.words')) AS average_words FROM tutorials"
+    ))
+    .fetch_one(pool)
+    .await?
+    .into();
+
+    tutorials.longest = tutorial_extreme(pool, "DESC").await?;
+    tutorials.shortest = tutorial_extreme(pool, "ASC").await?;
+
+    let page_rows = sqlx::query_as::<_, PageTotalsRow>(concat!(
+        "SELECT p.id AS page_id, p.slug, p.title, p.is_published, ",
+        "COUNT(s.id) AS documents, ",
+        "COALESCE(SUM(CASE WHEN s.is_published = 1 THEN 1 ELSE 0 END), 0) AS published, ",
+        "COALESCE(SUM(json_extract(s.content_stats, '$.words')), 0) AS total_words, ",
+        "AVG(json_extract(s.content_stats, '$.words')) AS average_words ",
+        "FROM site_pages p LEFT JOIN site_posts s ON s.page_id = p.id ",
+        "GROUP BY p.id ORDER BY p.order_index, p.title"
+    ))
+    .fetch_all(pool)
+    .await?;
+
+    // Longest and shortest post of every page in one query.
+    let extremes = sqlx::query_as::<_, PostExtremeRow>(concat!(
+        "SELECT page_id, id, title, words, longest_rank, shortest_rank FROM (",
+        "SELECT page_id, id, title, words, ",
+        "ROW_NUMBER() OVER (PARTITION BY page_id ORDER BY words DESC, id) AS longest_rank, ",
+        "ROW_NUMBER() OVER (PARTITION BY page_id ORDER BY words ASC, id) AS shortest_rank ",
+        "FROM (SELECT page_id, id, title, ",
+        "COALESCE(json_extract(content_stats, '$.words'), 0) AS words FROM site_posts)",
+        ") WHERE longest_rank = 1 OR shortest_rank = 1"
+    ))
+    .fetch_all(pool)
+    .await?;
+
+    let mut extremes_by_page: HashMap<
+        String,
+        (Option<DocumentWordCount>, Option<DocumentWordCount>),
+    > = HashMap::new();
+    for row in extremes {
+        let entry = extremes_by_page.entry(row.page_id).or_default();
+        let doc = || DocumentWordCount {
+            id: row.id.clone(),
+            title: row.title.clone(),
+            words: row.words,
+        };
+        if row.longest_rank == 1 {
+            entry.0 = Some(doc());
+        }
+        if row.shortest_rank == 1 {
+            entry.1 = Some(doc());
+        }
+    }
+
+    let pages = page_rows
+        .into_iter()
+        .map(|row| {
+            let (longest, shortest) = extremes_by_page.remove(&row.page_id).unwrap_or_default();
+            let mut posts = ContentAreaStats::from(AreaTotalsRow {
+                documents: row.documents,
+                published: row.published,
+                total_words: row.total_words,
+                average_words: row.average_words,
+            });
+            posts.longest = longest;
+            posts.shortest = shortest;
+            PageContentStats {
+                page_id: row.page_id,
+                slug: row.slug,
+                title: row.title,
+                is_published: row.is_published,
+                posts,
+            }
+        })
+        .collect();
+
+    // datetime() normalizes both SQLite's "YYYY-MM-DD HH:MM:SS" and RFC 3339
+    // timestamps so imported rows compare correctly.
+    let cutoff = format!("-{stale_after_days} days");
+    let stale_documents = sqlx::query_as::<_, StaleDocument>(concat!(
+        "SELECT kind, id, title, updated_at FROM (",
+        "SELECT 'tutorial' AS kind, id, title, updated_at FROM tutorials ",
+        "WHERE datetime(updated_at) < datetime('now', ?) ",
+        "UNION ALL ",
+        "SELECT 'post' AS kind, id, title, updated_at FROM site_posts ",
+        "WHERE datetime(updated_at) < datetime('now', ?)",
+        ") ORDER BY datetime(updated_at) ASC, id LIMIT 200"
+    ))
+    .bind(&cutoff)
+    .bind(&cutoff)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(ContentStatsResponse {
+        generated_at: chrono::Utc::now().to_rfc3339(),
+        stale_after_days,
+        tutorials,
+        pages,
+        stale_documents,
+    })
+}
+
+async fn tutorial_extreme(
+    pool: &DbPool,
+    direction: &'static str,
+) -> Result<Option<DocumentWordCount>, sqlx::Error> {
+    sqlx::query_as::<_, DocumentWordCount>(&format!(
+        "SELECT id, title, COALESCE(json_extract(content_stats, '$.words'), 0) AS words \
+         FROM tutorials ORDER BY words {direction}, id LIMIT 1"
+    ))
+    .fetch_optional(pool)
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn count_words_ignores_markdown_punctuation() {
+        assert_eq!(count_words(""), 0);
+        assert_eq!(count_words("# Title\n\n- one two\n> three"), 4);
+        assert_eq!(count_words("```rust\nlet x = 1;\n```"), 4);
+        assert_eq!(count_words("  Grüße,   Welt!  "), 2);
+    }
+}
@@ -44,8 +44,14 @@ pub async fn is_token_blacklisted(pool: &DbPool, token: &str) -> Result<bool, sq
 }
 
 /// Deletes expired tokens from the blacklist to prevent unbounded table growth.
+///
+/// `expires_at` is stored as RFC3339, so the cutoff is formatted the same
+/// way; SQLite's `datetime('now')` uses a space instead of the `T` and would
+/// keep same-day entries forever.
 pub async fn cleanup_expired(pool: &DbPool) -> Result<u64, sqlx::Error> {
-    let result = sqlx::query("DELETE FROM token_blacklist WHERE expires_at < datetime('now')")
+    let now = chrono::Utc::now().to_rfc3339();
+    let result = sqlx::query("DELETE FROM token_blacklist WHERE expires_at < ?")
+        .bind(now)
         .execute(pool)
         .await?;
     Ok(result.rows_affected())
@@ -54,19 +60,10 @@ pub async fn cleanup_expired(pool: &DbPool) -> Result<u64, sqlx::Error> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use sqlx::SqlitePool;
-
-    async fn setup_test_db() -> DbPool {
-        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
-        crate::db::migrations::run_migrations(&pool)
-            .await
-            .expect("Failed to run migrations");
-        pool
-    }
 
     #[tokio::test]
     async fn blacklisted_token_is_detected_after_hashing() {
-        let pool = setup_test_db().await;
+        let pool = crate::test_support::migrated_pool().await;
         let token = "some.raw.jwt.value";
 
         assert!(!is_token_blacklisted(&pool, token).await.unwrap());
@@ -79,7 +76,7 @@ mod tests {
 
     #[tokio::test]
     async fn stored_value_is_hashed_not_plaintext() {
-        let pool = setup_test_db().await;
+        let pool = crate::test_support::migrated_pool().await;
         let token = "another.raw.jwt.value";
         let expires_at = chrono::Utc::now().timestamp() + 3600;
 
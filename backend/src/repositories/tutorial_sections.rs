@@ -0,0 +1,235 @@
+//! Persistence for tutorial sections (chapters).
+//!
+//! Every change to a section also stamps the owning tutorial's
+//! `updated_at` and `updated_by` in the same transaction, so conditional
+//! GETs and the "last edited by" attribution see it. Search indexing
+//! follows through the triggers on `tutorial_sections`.
+
+use crate::db::DbPool;
+use crate::models::TutorialSection;
+use sqlx::{Sqlite, Transaction};
+
+const SECTION_COLUMNS: &str =
+    "tutorial_id, section_id, title, content, order_index, created_at, updated_at";
+
+/// Sections of a tutorial in reading order.
+pub async fn list_sections(
+    pool: &DbPool,
+    tutorial_id: &str,
+) -> Result<Vec<TutorialSection>, sqlx::Error> {
+    sqlx::query_as::<_, TutorialSection>(&format!(
+        "SELECT {SECTION_COLUMNS} FROM tutorial_sections WHERE tutorial_id = ? \
+         ORDER BY order_index, section_id"
+    ))
+    .bind(tutorial_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Appends a section to the end of the tutorial. Returns `None` if the
+/// tutorial already has a section with this ID.
+pub async fn create_section(
+    pool: &DbPool,
+    editor: &str,
+    tutorial_id: &str,
+    section_id: &str,
+    title: &str,
+    content: &str,
+) -> Result<Option<TutorialSection>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let section =
+        create_section_tx(&mut tx, editor, tutorial_id, section_id, title, content).await?;
+    if section.is_some() {
+        tx.commit().await?;
+    }
+    Ok(section)
+}
+
+/// [`create_section`] within an existing transaction. The caller commits.
+pub(crate) async fn create_section_tx(
+    tx: &mut Transaction<'_, Sqlite>,
+    editor: &str,
+    tutorial_id: &str,
+    section_id: &str,
+    title: &str,
+    content: &str,
+) -> Result<Option<TutorialSection>, sqlx::Error> {
+    let section = sqlx::query_as::<_, TutorialSection>(&format!(
+        "INSERT INTO tutorial_sections (tutorial_id, section_id, title, content, order_index) \
+         VALUES (?, ?, ?, ?, (SELECT COALESCE(MAX(order_index) + 1, 0) \
+                              FROM tutorial_sections WHERE tutorial_id = ?)) \
+         ON CONFLICT(tutorial_id, section_id) DO NOTHING \
+         RETURNING {SECTION_COLUMNS}"
+    ))
+    .bind(tutorial_id)
+    .bind(section_id)
+    .bind(title)
+    .bind(content)
+    .bind(tutorial_id)
+    .fetch_optional(&mut **tx)
+    .await?;
+    if section.is_some() {
+        touch_tutorial_tx(tx, tutorial_id, editor).await?;
+    }
+    Ok(section)
+}
+
+/// Replaces a section's title and content. Returns `None` if the tutorial
+/// has no such section.
+pub async fn update_section(
+    pool: &DbPool,
+    editor: &str,
+    tutorial_id: &str,
+    section_id: &str,
+    title: &str,
+    content: &str,
+) -> Result<Option<TutorialSection>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let section = sqlx::query_as::<_, TutorialSection>(&format!(
+        "UPDATE tutorial_sections SET title = ?, content = ?, updated_at = datetime('now') \
+         WHERE tutorial_id = ? AND section_id = ? \
+         RETURNING {SECTION_COLUMNS}"
+    ))
+    .bind(title)
+    .bind(content)
+    .bind(tutorial_id)
+    .bind(section_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+    if section.is_none() {
+        return Ok(None);
+    }
+
+    touch_tutorial_tx(&mut tx, tutorial_id, editor).await?;
+    tx.commit().await?;
+    Ok(section)
+}
+
+/// Removes a section. Returns `false` if the tutorial has no such section.
+pub async fn delete_section(
+    pool: &DbPool,
+    editor: &str,
+    tutorial_id: &str,
+    section_id: &str,
+) -> Result<bool, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let result =
+        sqlx::query("DELETE FROM tutorial_sections WHERE tutorial_id = ? AND section_id = ?")
+            .bind(tutorial_id)
+            .bind(section_id)
+            .execute(&mut *tx)
+            .await?;
+    if result.rows_affected() == 0 {
+        return Ok(false);
+    }
+
+    touch_tutorial_tx(&mut tx, tutorial_id, editor).await?;
+    tx.commit().await?;
+    Ok(true)
+}
+
+/// Gives the listed sections positions `0..ids.len()` in that order; the
+/// remaining sections follow in their current order. Returns `None`,
+/// changing nothing, if an ID is not a section of the tutorial.
+pub async fn reorder_sections(
+    pool: &DbPool,
+    editor: &str,
+    tutorial_id: &str,
+    ids: &[String],
+) -> Result<Option<Vec<TutorialSection>>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let current: Vec<String> = sqlx::query_scalar(
+        "SELECT section_id FROM tutorial_sections WHERE tutorial_id = ? \
+         ORDER BY order_index, section_id",
+    )
+    .bind(tutorial_id)
+    .fetch_all(&mut *tx)
+    .await?;
+    if ids.iter().any(|id| !current.contains(id)) {
+        return Ok(None);
+    }
+
+    let rest = current.iter().filter(|id| !ids.contains(id));
+    for (order_index, id) in (0i64..).zip(ids.iter().chain(rest)) {
+        sqlx::query(
+            "UPDATE tutorial_sections SET order_index = ? \
+             WHERE tutorial_id = ? AND section_id = ?",
+        )
+        .bind(order_index)
+        .bind(tutorial_id)
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+    }
+    touch_tutorial_tx(&mut tx, tutorial_id, editor).await?;
+
+    let sections = sqlx::query_as::<_, TutorialSection>(&format!(
+        "SELECT {SECTION_COLUMNS} FROM tutorial_sections WHERE tutorial_id = ? \
+         ORDER BY order_index, section_id"
+    ))
+    .bind(tutorial_id)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(Some(sections))
+}
+
+/// Re-inserts sections captured in a deletion snapshot, keeping their
+/// order and timestamps.
+pub async fn insert_sections(
+    pool: &DbPool,
+    sections: &[TutorialSection],
+) -> Result<usize, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let mut inserted = 0;
+    for section in sections {
+        let result = sqlx::query(
+            "INSERT OR IGNORE INTO tutorial_sections \
+             (tutorial_id, section_id, title, content, order_index, created_at, updated_at) \
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&section.tutorial_id)
+        .bind(&section.section_id)
+        .bind(&section.title)
+        .bind(&section.content)
+        .bind(section.order_index)
+        .bind(&section.created_at)
+        .bind(&section.updated_at)
+        .execute(&mut *tx)
+        .await?;
+        inserted += result.rows_affected() as usize;
+    }
+    tx.commit().await?;
+    Ok(inserted)
+}
+
+/// Sections of a tutorial inside a transaction, for deletion snapshots.
+pub(crate) async fn list_sections_tx(
+    tx: &mut Transaction<'_, Sqlite>,
+    tutorial_id: &str,
+) -> Result<Vec<TutorialSection>, sqlx::Error> {
+    sqlx::query_as::<_, TutorialSection>(&format!(
+        "SELECT {SECTION_COLUMNS} FROM tutorial_sections WHERE tutorial_id = ? \
+         ORDER BY order_index, section_id"
+    ))
+    .bind(tutorial_id)
+    .fetch_all(&mut **tx)
+    .await
+}
+
+async fn touch_tutorial_tx(
+    tx: &mut Transaction<'_, Sqlite>,
+    tutorial_id: &str,
+    editor: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE tutorials SET updated_by = ?, updated_at = datetime('now') WHERE id = ?")
+        .bind(editor)
+        .bind(tutorial_id)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
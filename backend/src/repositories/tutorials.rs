@@ -1,32 +1,138 @@
 use crate::db::DbPool;
-use crate::models::Tutorial;
+use crate::models::{
+    TrashedTutorial, Tutorial, TutorialOrderEntry, TutorialRevision, TutorialRevisionSummary,
+    TUTORIAL_STATUS_PUBLISHED,
+};
+use crate::repositories::deletion_log;
+use crate::repositories::stats::content_stats_json;
 use sqlx;
 
 /// Fetches a paginated list of tutorials, excluding full content to save bandwidth.
+/// Drafts are left out unless `include_drafts` is set; trashed tutorials always are.
 pub async fn list_tutorials(
     pool: &DbPool,
     limit: i64,
     offset: i64,
+    include_drafts: bool,
 ) -> Result<Vec<Tutorial>, sqlx::Error> {
     sqlx::query_as::<_, Tutorial>(
-        "SELECT id, title, description, icon, color, topics, '' as content, version, created_at, updated_at \
-         FROM tutorials ORDER BY created_at ASC LIMIT ? OFFSET ?"
+        "SELECT id, title, description, icon, color, topics, '' as content, version, status, \
+         published_at, order_index, allow_comments, created_by, updated_by, \
+         created_at, updated_at \
+         FROM tutorials WHERE deleted_at IS NULL AND (? OR status = ?) \
+         ORDER BY order_index ASC, created_at ASC LIMIT ? OFFSET ?",
     )
+    .bind(include_drafts)
+    .bind(TUTORIAL_STATUS_PUBLISHED)
     .bind(limit)
     .bind(offset)
     .fetch_all(pool)
     .await
 }
 
-/// Fetches a single tutorial by its unique ID.
+/// Like [`list_tutorials`], limited to tutorials that have every one of
+/// `topics`. Topics are matched ASCII case-insensitively and must be given
+/// lowercased and without duplicates.
+pub async fn list_tutorials_by_topics(
+    pool: &DbPool,
+    topics: &[String],
+    limit: i64,
+    offset: i64,
+    include_drafts: bool,
+) -> Result<Vec<Tutorial>, sqlx::Error> {
+    let sql = format!(
+        "SELECT id, title, description, icon, color, topics, '' as content, version, status, \
+         published_at, order_index, allow_comments, created_by, updated_by, \
+         created_at, updated_at \
+         FROM tutorials WHERE deleted_at IS NULL AND (? OR status = ?) AND {} \
+         ORDER BY order_index ASC, created_at ASC LIMIT ? OFFSET ?",
+        topic_filter(topics)
+    );
+
+    let mut query = sqlx::query_as::<_, Tutorial>(&sql)
+        .bind(include_drafts)
+        .bind(TUTORIAL_STATUS_PUBLISHED);
+    for topic in topics {
+        query = query.bind(topic);
+    }
+    query
+        .bind(topics.len() as i64)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
+        .await
+}
+
+/// Counts the tutorials [`list_tutorials`] (or, with `topics`,
+/// [`list_tutorials_by_topics`]) pages through.
+pub async fn count_tutorials(
+    pool: &DbPool,
+    topics: &[String],
+    include_drafts: bool,
+) -> Result<i64, sqlx::Error> {
+    if topics.is_empty() {
+        return sqlx::query_scalar(
+            "SELECT COUNT(*) FROM tutorials WHERE deleted_at IS NULL AND (? OR status = ?)",
+        )
+        .bind(include_drafts)
+        .bind(TUTORIAL_STATUS_PUBLISHED)
+        .fetch_one(pool)
+        .await;
+    }
+
+    let sql = format!(
+        "SELECT COUNT(*) FROM tutorials WHERE deleted_at IS NULL AND (? OR status = ?) AND {}",
+        topic_filter(topics)
+    );
+    let mut query = sqlx::query_scalar(&sql)
+        .bind(include_drafts)
+        .bind(TUTORIAL_STATUS_PUBLISHED);
+    for topic in topics {
+        query = query.bind(topic);
+    }
+    query.bind(topics.len() as i64).fetch_one(pool).await
+}
+
+/// Condition matching tutorials that have all `topics`; binds each topic,
+/// then their count.
+fn topic_filter(topics: &[String]) -> String {
+    let placeholders = vec!["?"; topics.len()].join(", ");
+    format!(
+        "id IN (SELECT tutorial_id FROM tutorial_topics WHERE lower(topic) IN ({placeholders}) \
+         GROUP BY tutorial_id HAVING COUNT(DISTINCT lower(topic)) = ?)"
+    )
+}
+
+/// Fetches a single tutorial by its unique ID, unless it is in the trash.
 pub async fn get_tutorial(pool: &DbPool, id: &str) -> Result<Option<Tutorial>, sqlx::Error> {
-    sqlx::query_as::<_, Tutorial>("SELECT * FROM tutorials WHERE id = ?")
+    sqlx::query_as::<_, Tutorial>("SELECT * FROM tutorials WHERE id = ? AND deleted_at IS NULL")
         .bind(id)
         .fetch_optional(pool)
         .await
 }
 
+/// Whether a tutorial that is not in the trash has this ID.
 pub async fn check_tutorial_exists(pool: &DbPool, id: &str) -> Result<bool, sqlx::Error> {
+    let exists: Option<(i64,)> =
+        sqlx::query_as("SELECT 1 FROM tutorials WHERE id = ? AND deleted_at IS NULL")
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+    Ok(exists.is_some())
+}
+
+/// Whether the tutorial accepts new comments, or `None` if no tutorial
+/// outside the trash has this ID.
+pub async fn comments_allowed(pool: &DbPool, id: &str) -> Result<Option<bool>, sqlx::Error> {
+    sqlx::query_scalar("SELECT allow_comments FROM tutorials WHERE id = ? AND deleted_at IS NULL")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Whether any tutorial, trashed or not, has this ID. A trashed tutorial
+/// still holds its ID until it is restored or permanently deleted.
+pub async fn check_tutorial_id_taken(pool: &DbPool, id: &str) -> Result<bool, sqlx::Error> {
     let exists: Option<(i64,)> = sqlx::query_as("SELECT 1 FROM tutorials WHERE id = ?")
         .bind(id)
         .fetch_optional(pool)
@@ -35,6 +141,8 @@ pub async fn check_tutorial_exists(pool: &DbPool, id: &str) -> Result<bool, sqlx
 }
 
 /// Creates a new tutorial and its associated topics within a single transaction.
+/// A tutorial created as published gets the current time as `published_at`.
+/// `created_by` is recorded as both author and last editor.
 #[allow(clippy::too_many_arguments)]
 pub async fn create_tutorial(
     pool: &DbPool,
@@ -46,15 +154,58 @@ pub async fn create_tutorial(
     color: &str,
     topics_json: &str,
     topics_vec: &[String],
+    status: &str,
+    allow_comments: bool,
+    created_by: Option<&str>,
 ) -> Result<Tutorial, sqlx::Error> {
-    // Start ACID transaction
     let mut tx = pool.begin().await?;
+    let tutorial = create_tutorial_tx(
+        &mut tx,
+        id,
+        title,
+        description,
+        content,
+        icon,
+        color,
+        topics_json,
+        topics_vec,
+        status,
+        allow_comments,
+        created_by,
+    )
+    .await?;
+    tx.commit().await?;
+    crate::sitemap::invalidate();
 
+    Ok(tutorial)
+}
+
+/// [`create_tutorial`] within an existing transaction. The caller commits
+/// and invalidates the sitemap.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn create_tutorial_tx(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    id: &str,
+    title: &str,
+    description: &str,
+    content: &str,
+    icon: &str,
+    color: &str,
+    topics_json: &str,
+    topics_vec: &[String],
+    status: &str,
+    allow_comments: bool,
+    created_by: Option<&str>,
+) -> Result<Tutorial, sqlx::Error> {
     // Step 1: Insert core tutorial record
     sqlx::query(
         r#"
-        INSERT INTO tutorials (id, title, description, icon, color, topics, content, version)
-        VALUES (?, ?, ?, ?, ?, ?, ?, 1)
+        INSERT INTO tutorials (id, title, description, icon, color, topics, content, version,
+                               content_stats, status, published_at, order_index, allow_comments,
+                               created_by, updated_by)
+        VALUES (?, ?, ?, ?, ?, ?, ?, 1, ?, ?,
+                CASE WHEN ? = 'published' THEN datetime('now') END,
+                (SELECT COALESCE(MAX(order_index) + 1, 0) FROM tutorials), ?, ?, ?)
         "#,
     )
     .bind(id)
@@ -64,33 +215,44 @@ pub async fn create_tutorial(
     .bind(color)
     .bind(topics_json)
     .bind(content)
-    .execute(&mut *tx)
+    .bind(content_stats_json(content))
+    .bind(status)
+    .bind(status)
+    .bind(allow_comments)
+    .bind(created_by)
+    .bind(created_by)
+    .execute(&mut **tx)
     .await?;
 
     // Step 2: Sync relational topics table for indexed searching
-    replace_tutorial_topics_tx(&mut tx, id, topics_vec).await?;
+    replace_tutorial_topics_tx(tx, id, topics_vec).await?;
 
     // Step 3: Fetch the finalized record (including timestamps)
-    let tutorial = sqlx::query_as::<_, Tutorial>(concat!(
-        "SELECT id, title, description, icon, color, topics, content, version, ",
-        "created_at, updated_at FROM tutorials WHERE id = ?"
+    sqlx::query_as::<_, Tutorial>(concat!(
+        "SELECT id, title, description, icon, color, topics, content, version, status, ",
+        "published_at, order_index, allow_comments, created_by, updated_by, ",
+        "created_at, updated_at ",
+        "FROM tutorials WHERE id = ?"
     ))
     .bind(id)
-    .fetch_one(&mut *tx)
-    .await?;
-
-    tx.commit().await?;
-
-    Ok(tutorial)
+    .fetch_one(&mut **tx)
+    .await
 }
 
 /// Updates an existing tutorial using optimistic concurrency control.
 ///
 /// Returns `Ok(None)` if a conflict occurred (version mismatch), otherwise
-/// returns the updated record.
+/// returns the updated record. Moving a draft to `published` stamps
+/// `published_at`; moving it back to `draft` clears it.
+///
+/// `editor` becomes the tutorial's `updated_by`. The replaced version is
+/// kept in `tutorial_revisions`, attributed to `editor`, and the oldest
+/// revisions beyond `TUTORIAL_REVISION_LIMIT` are dropped, all in the same
+/// transaction.
 #[allow(clippy::too_many_arguments)]
 pub async fn update_tutorial(
     pool: &DbPool,
+    editor: &str,
     id: &str,
     title: &str,
     description: &str,
@@ -99,19 +261,80 @@ pub async fn update_tutorial(
     color: &str,
     topics_json: &str,
     topics_vec: &[String],
+    status: &str,
+    allow_comments: bool,
     current_version: i32,
 ) -> Result<Option<Tutorial>, sqlx::Error> {
     // Start transaction for atomic update of main table and relational topics
     let mut tx = pool.begin().await?;
+    let tutorial = update_tutorial_tx(
+        &mut tx,
+        editor,
+        id,
+        title,
+        description,
+        content,
+        icon,
+        color,
+        topics_json,
+        topics_vec,
+        status,
+        allow_comments,
+        current_version,
+    )
+    .await?;
+    if tutorial.is_some() {
+        tx.commit().await?;
+        crate::sitemap::invalidate();
+    }
 
+    Ok(tutorial)
+}
+
+/// [`update_tutorial`] within an existing transaction. On `Ok(None)` the
+/// caller should roll back; otherwise it commits and invalidates the
+/// sitemap.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn update_tutorial_tx(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    editor: &str,
+    id: &str,
+    title: &str,
+    description: &str,
+    content: &str,
+    icon: &str,
+    color: &str,
+    topics_json: &str,
+    topics_vec: &[String],
+    status: &str,
+    allow_comments: bool,
+    current_version: i32,
+) -> Result<Option<Tutorial>, sqlx::Error> {
     let new_version = current_version + 1;
 
-    // Step 1: Perform UPDATE with version-based fence
+    // Step 1: Keep the version about to be replaced. If the fence below
+    // fails, dropping the transaction discards this row again.
+    sqlx::query(concat!(
+        "INSERT OR REPLACE INTO tutorial_revisions ",
+        "(tutorial_id, version, title, description, content, topics, editor) ",
+        "SELECT id, version, title, description, content, topics, ? ",
+        "FROM tutorials WHERE id = ? AND version = ?"
+    ))
+    .bind(editor)
+    .bind(id)
+    .bind(current_version)
+    .execute(&mut **tx)
+    .await?;
+
+    // Step 2: Perform UPDATE with version-based fence
     let result = sqlx::query(
         r#"
         UPDATE tutorials
         SET title = ?, description = ?, icon = ?, color = ?, topics = ?,
-            content = ?, version = ?, updated_at = datetime('now')
+            content = ?, content_stats = ?, status = ?,
+            published_at = CASE WHEN ? = 'published'
+                                THEN COALESCE(published_at, datetime('now')) END,
+            allow_comments = ?, version = ?, updated_by = ?, updated_at = datetime('now')
         WHERE id = ? AND version = ?
         "#,
     )
@@ -121,10 +344,15 @@ pub async fn update_tutorial(
     .bind(color)
     .bind(topics_json)
     .bind(content)
+    .bind(content_stats_json(content))
+    .bind(status)
+    .bind(status)
+    .bind(allow_comments)
     .bind(new_version)
+    .bind(editor)
     .bind(id)
     .bind(current_version)
-    .execute(&mut *tx)
+    .execute(&mut **tx)
     .await?;
 
     // Check for concurrency conflict: if 0 rows affected, someone else updated first
@@ -132,30 +360,258 @@ pub async fn update_tutorial(
         return Ok(None);
     }
 
-    // Step 2: Sync topics
-    replace_tutorial_topics_tx(&mut tx, id, topics_vec).await?;
+    // Step 3: Sync topics and prune old revisions
+    replace_tutorial_topics_tx(tx, id, topics_vec).await?;
+    sqlx::query(concat!(
+        "DELETE FROM tutorial_revisions WHERE tutorial_id = ? AND version NOT IN ",
+        "(SELECT version FROM tutorial_revisions WHERE tutorial_id = ? ",
+        "ORDER BY version DESC LIMIT ?)"
+    ))
+    .bind(id)
+    .bind(id)
+    .bind(i64::from(crate::config::get().tutorial_revision_limit))
+    .execute(&mut **tx)
+    .await?;
 
-    // Step 3: Fetch updated state
-    let tutorial = sqlx::query_as::<_, Tutorial>(concat!(
-        "SELECT id, title, description, icon, color, topics, content, version, ",
-        "created_at, updated_at FROM tutorials WHERE id = ?"
+    // Step 4: Fetch updated state
+    sqlx::query_as::<_, Tutorial>(concat!(
+        "SELECT id, title, description, icon, color, topics, content, version, status, ",
+        "published_at, order_index, allow_comments, created_by, updated_by, ",
+        "created_at, updated_at ",
+        "FROM tutorials WHERE id = ?"
     ))
     .bind(id)
-    .fetch_one(&mut *tx)
+    .fetch_one(&mut **tx)
+    .await
+    .map(Some)
+}
+
+/// Stored revisions of a tutorial, newest first.
+pub async fn list_revisions(
+    pool: &DbPool,
+    tutorial_id: &str,
+) -> Result<Vec<TutorialRevisionSummary>, sqlx::Error> {
+    sqlx::query_as::<_, TutorialRevisionSummary>(concat!(
+        "SELECT version, title, editor, created_at FROM tutorial_revisions ",
+        "WHERE tutorial_id = ? ORDER BY version DESC"
+    ))
+    .bind(tutorial_id)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn get_revision(
+    pool: &DbPool,
+    tutorial_id: &str,
+    version: i64,
+) -> Result<Option<TutorialRevision>, sqlx::Error> {
+    sqlx::query_as::<_, TutorialRevision>(concat!(
+        "SELECT version, title, description, content, topics, editor, created_at ",
+        "FROM tutorial_revisions WHERE tutorial_id = ? AND version = ?"
+    ))
+    .bind(tutorial_id)
+    .bind(version)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Publishes a draft and stamps `published_at`. Returns `false` when no
+/// draft with that ID exists, including when it is already published.
+pub async fn publish_tutorial(pool: &DbPool, id: &str) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(concat!(
+        "UPDATE tutorials SET status = ?, published_at = datetime('now') ",
+        "WHERE id = ? AND status <> ? AND deleted_at IS NULL"
+    ))
+    .bind(TUTORIAL_STATUS_PUBLISHED)
+    .bind(id)
+    .bind(TUTORIAL_STATUS_PUBLISHED)
+    .execute(pool)
+    .await?;
+    crate::sitemap::invalidate();
+    Ok(result.rows_affected() > 0)
+}
+
+/// Gives the listed tutorials positions `0..ids.len()` in that order. The
+/// remaining tutorials outside the trash follow in their current order.
+/// Returns `None`, changing nothing, if an ID is unknown or trashed.
+pub async fn reorder_tutorials(
+    pool: &DbPool,
+    ids: &[String],
+) -> Result<Option<Vec<TutorialOrderEntry>>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let current: Vec<String> = sqlx::query_scalar(
+        "SELECT id FROM tutorials WHERE deleted_at IS NULL ORDER BY order_index, created_at",
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+    if ids.iter().any(|id| !current.contains(id)) {
+        return Ok(None);
+    }
+
+    let rest = current.iter().filter(|id| !ids.contains(id));
+    let mut ordering = Vec::with_capacity(current.len());
+    for (order_index, id) in (0i64..).zip(ids.iter().chain(rest)) {
+        sqlx::query("UPDATE tutorials SET order_index = ? WHERE id = ?")
+            .bind(order_index)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        ordering.push(TutorialOrderEntry {
+            id: id.clone(),
+            order_index,
+        });
+    }
+
+    tx.commit().await?;
+    Ok(Some(ordering))
+}
+
+/// Moves a tutorial to the trash. Returns `false` if no tutorial outside
+/// the trash has this ID.
+pub async fn trash_tutorial(pool: &DbPool, id: &str) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE tutorials SET deleted_at = datetime('now') WHERE id = ? AND deleted_at IS NULL",
+    )
+    .bind(id)
+    .execute(pool)
+    .await?;
+    crate::sitemap::invalidate();
+    Ok(result.rows_affected() > 0)
+}
+
+/// Takes a tutorial out of the trash. Returns `false` if it was not there.
+pub async fn restore_tutorial(pool: &DbPool, id: &str) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE tutorials SET deleted_at = NULL WHERE id = ? AND deleted_at IS NOT NULL",
+    )
+    .bind(id)
+    .execute(pool)
+    .await?;
+    crate::sitemap::invalidate();
+    Ok(result.rows_affected() > 0)
+}
+
+/// Moves a tutorial outside the trash to a new ID, along with everything
+/// keyed by it, and leaves a redirect from the old ID behind. Redirects that
+/// pointed at the old ID follow to the new one; a redirect from the new ID
+/// itself is dropped. Returns `false` if no such tutorial exists.
+///
+/// Not every table referencing the ID does so with `ON UPDATE CASCADE`, so
+/// foreign key checks are deferred to the commit while the rows are moved
+/// one table at a time.
+pub async fn rename_tutorial(
+    pool: &DbPool,
+    old_id: &str,
+    new_id: &str,
+) -> Result<bool, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("PRAGMA defer_foreign_keys = ON")
+        .execute(&mut *tx)
+        .await?;
+
+    let result = sqlx::query("UPDATE tutorials SET id = ? WHERE id = ? AND deleted_at IS NULL")
+        .bind(new_id)
+        .bind(old_id)
+        .execute(&mut *tx)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Ok(false);
+    }
+
+    for table in [
+        "tutorial_topics",
+        "tutorial_sections",
+        "comments",
+        "tutorial_revisions",
+        "tutorial_redirects",
+    ] {
+        sqlx::query(&format!(
+            "UPDATE {table} SET tutorial_id = ? WHERE tutorial_id = ?"
+        ))
+        .bind(new_id)
+        .bind(old_id)
+        .execute(&mut *tx)
+        .await?;
+    }
+    sqlx::query(concat!(
+        "UPDATE content_events SET entity_id = ?, slug = ? ",
+        "WHERE entity_type = 'tutorial' AND entity_id = ?"
+    ))
+    .bind(new_id)
+    .bind(new_id)
+    .bind(old_id)
+    .execute(&mut *tx)
+    .await?;
+    // View counts are never purged, so rows left by an earlier tutorial
+    // under the new ID would collide with the moved ones
+    sqlx::query("DELETE FROM content_views WHERE entity_type = 'tutorial' AND entity_id = ?")
+        .bind(new_id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query(
+        "UPDATE content_views SET entity_id = ? WHERE entity_type = 'tutorial' AND entity_id = ?",
+    )
+    .bind(new_id)
+    .bind(old_id)
+    .execute(&mut *tx)
     .await?;
 
+    sqlx::query("DELETE FROM tutorial_redirects WHERE old_id = ?")
+        .bind(new_id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("INSERT OR REPLACE INTO tutorial_redirects (old_id, tutorial_id) VALUES (?, ?)")
+        .bind(old_id)
+        .bind(new_id)
+        .execute(&mut *tx)
+        .await?;
+
     tx.commit().await?;
+    crate::sitemap::invalidate();
+    Ok(true)
+}
 
-    Ok(Some(tutorial))
+/// The current ID of a tutorial that was renamed away from `old_id`.
+pub async fn resolve_redirect(pool: &DbPool, old_id: &str) -> Result<Option<String>, sqlx::Error> {
+    sqlx::query_scalar("SELECT tutorial_id FROM tutorial_redirects WHERE old_id = ?")
+        .bind(old_id)
+        .fetch_optional(pool)
+        .await
 }
 
-pub async fn delete_tutorial(pool: &DbPool, id: &str) -> Result<bool, sqlx::Error> {
-    let result = sqlx::query("DELETE FROM tutorials WHERE id = ?")
+/// Tutorials in the trash, most recently deleted first.
+pub async fn list_trashed_tutorials(pool: &DbPool) -> Result<Vec<TrashedTutorial>, sqlx::Error> {
+    sqlx::query_as::<_, TrashedTutorial>(concat!(
+        "SELECT id, title, description, status, deleted_at, updated_at FROM tutorials ",
+        "WHERE deleted_at IS NOT NULL ORDER BY deleted_at DESC, id"
+    ))
+    .fetch_all(pool)
+    .await
+}
+
+/// Permanently deletes a tutorial, trashed or not; its topics, comments and
+/// votes cascade. The removed rows are recorded in the deletion log in the
+/// same transaction.
+pub async fn delete_tutorial(
+    pool: &DbPool,
+    id: &str,
+    deleted_by: &str,
+) -> Result<bool, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let Some(snapshot) = deletion_log::tutorial_snapshot_tx(&mut tx, id).await? else {
+        return Ok(false);
+    };
+    deletion_log::record_tx(&mut tx, &snapshot, deleted_by).await?;
+
+    sqlx::query("DELETE FROM tutorials WHERE id = ?")
         .bind(id)
-        .execute(pool)
+        .execute(&mut *tx)
         .await?;
+    tx.commit().await?;
+    crate::sitemap::invalidate();
 
-    Ok(result.rows_affected() > 0)
+    Ok(true)
 }
 
 /// Helper to replace all topics for a tutorial within an existing transaction.
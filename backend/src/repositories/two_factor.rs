@@ -0,0 +1,219 @@
+//! Persistence for two-factor login: TOTP state on `users`, recovery codes
+//! and pending login challenges.
+
+use crate::db::DbPool;
+use crate::models::{TwoFactorChallenge, TwoFactorState};
+
+const STATE_COLUMNS: &str =
+    "id AS user_id, username, role, totp_secret, totp_enabled, totp_last_step";
+
+pub async fn get_state(
+    pool: &DbPool,
+    username: &str,
+) -> Result<Option<TwoFactorState>, sqlx::Error> {
+    sqlx::query_as::<_, TwoFactorState>(&format!(
+        "SELECT {STATE_COLUMNS} FROM users WHERE username = ?"
+    ))
+    .bind(username)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn get_state_by_id(
+    pool: &DbPool,
+    user_id: i64,
+) -> Result<Option<TwoFactorState>, sqlx::Error> {
+    sqlx::query_as::<_, TwoFactorState>(&format!("SELECT {STATE_COLUMNS} FROM users WHERE id = ?"))
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Starts (or restarts) enrollment with a new encrypted secret. Returns
+/// `false` when two-factor login is already enabled.
+pub async fn store_pending_secret(
+    pool: &DbPool,
+    user_id: i64,
+    encrypted_secret: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(concat!(
+        "UPDATE users SET totp_secret = ?, totp_last_step = NULL ",
+        "WHERE id = ? AND totp_enabled = FALSE"
+    ))
+    .bind(encrypted_secret)
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Completes enrollment and replaces any previous recovery codes. Returns
+/// `false` if enrollment was not pending anymore.
+pub async fn enable(
+    pool: &DbPool,
+    user_id: i64,
+    verified_step: i64,
+    recovery_code_hashes: &[String],
+) -> Result<bool, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let result = sqlx::query(concat!(
+        "UPDATE users SET totp_enabled = TRUE, totp_last_step = ? ",
+        "WHERE id = ? AND totp_enabled = FALSE AND totp_secret IS NOT NULL"
+    ))
+    .bind(verified_step)
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+    if result.rows_affected() == 0 {
+        return Ok(false);
+    }
+
+    sqlx::query("DELETE FROM user_recovery_codes WHERE user_id = ?")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+    for hash in recovery_code_hashes {
+        sqlx::query("INSERT INTO user_recovery_codes (user_id, code_hash) VALUES (?, ?)")
+            .bind(user_id)
+            .bind(hash)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    tx.commit().await?;
+    Ok(true)
+}
+
+/// Turns two-factor login off and forgets the secret, recovery codes and
+/// pending challenges.
+pub async fn disable(pool: &DbPool, user_id: i64) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    sqlx::query(concat!(
+        "UPDATE users SET totp_secret = NULL, totp_enabled = FALSE, totp_last_step = NULL ",
+        "WHERE id = ?"
+    ))
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query("DELETE FROM user_recovery_codes WHERE user_id = ?")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("DELETE FROM two_factor_challenges WHERE user_id = ?")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Records `step` as used. Returns `false` if an equal or later step was
+/// already recorded, which means the code was replayed.
+pub async fn advance_last_step(
+    pool: &DbPool,
+    user_id: i64,
+    step: i64,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(concat!(
+        "UPDATE users SET totp_last_step = ? ",
+        "WHERE id = ? AND (totp_last_step IS NULL OR totp_last_step < ?)"
+    ))
+    .bind(step)
+    .bind(user_id)
+    .bind(step)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Marks an unused recovery code as used. Returns `false` when no unused
+/// code with that hash exists.
+pub async fn consume_recovery_code(
+    pool: &DbPool,
+    user_id: i64,
+    code_hash: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(concat!(
+        "UPDATE user_recovery_codes SET used_at = datetime('now') ",
+        "WHERE user_id = ? AND code_hash = ? AND used_at IS NULL"
+    ))
+    .bind(user_id)
+    .bind(code_hash)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn create_challenge(
+    pool: &DbPool,
+    token_hash: &str,
+    user_id: i64,
+    expires_at: &str,
+    remember_me: bool,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(concat!(
+        "INSERT INTO two_factor_challenges (token_hash, user_id, expires_at, remember_me) ",
+        "VALUES (?, ?, ?, ?)"
+    ))
+    .bind(token_hash)
+    .bind(user_id)
+    .bind(expires_at)
+    .bind(remember_me)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn get_challenge(
+    pool: &DbPool,
+    token_hash: &str,
+) -> Result<Option<TwoFactorChallenge>, sqlx::Error> {
+    sqlx::query_as::<_, TwoFactorChallenge>(concat!(
+        "SELECT user_id, expires_at, attempts, remember_me ",
+        "FROM two_factor_challenges WHERE token_hash = ?"
+    ))
+    .bind(token_hash)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Counts a wrong code against a challenge and discards the challenge once
+/// `max_attempts` is reached.
+pub async fn record_challenge_failure(
+    pool: &DbPool,
+    token_hash: &str,
+    max_attempts: i64,
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("UPDATE two_factor_challenges SET attempts = attempts + 1 WHERE token_hash = ?")
+        .bind(token_hash)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("DELETE FROM two_factor_challenges WHERE token_hash = ? AND attempts >= ?")
+        .bind(token_hash)
+        .bind(max_attempts)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Removes a challenge. Returns `false` if it was already gone, so two
+/// concurrent redemptions cannot both succeed.
+pub async fn delete_challenge(pool: &DbPool, token_hash: &str) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM two_factor_challenges WHERE token_hash = ?")
+        .bind(token_hash)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn cleanup_expired_challenges(pool: &DbPool) -> Result<u64, sqlx::Error> {
+    let now = chrono::Utc::now().to_rfc3339();
+    let result = sqlx::query("DELETE FROM two_factor_challenges WHERE expires_at < ?")
+        .bind(now)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,278 @@
+//! Persistence for the records of uploaded files.
+
+use crate::db::DbPool;
+use crate::media::GeneratedVariant;
+use crate::models::{Upload, UploadReference, UploadVariant};
+use sqlx::{Sqlite, Transaction};
+use std::collections::{HashMap, HashSet};
+
+const UPLOAD_COLUMNS: &str = "id, filename, original_name, mime, size_bytes, width, height, \
+                              alt, caption, uploaded_by, created_at";
+
+/// Metadata of a file about to be stored.
+#[derive(Debug)]
+pub struct NewUpload<'a> {
+    pub id: &'a str,
+    pub filename: &'a str,
+    pub original_name: &'a str,
+    pub mime: &'a str,
+    pub size_bytes: i64,
+    pub width: Option<i64>,
+    pub height: Option<i64>,
+    pub alt: Option<&'a str>,
+    pub caption: Option<&'a str>,
+    pub uploaded_by: &'a str,
+    pub variants: &'a [GeneratedVariant],
+}
+
+/// Records an upload and its variants within `tx`, so the caller can roll
+/// it back if the file never makes it to its final place.
+pub async fn insert_upload(
+    tx: &mut Transaction<'_, Sqlite>,
+    upload: &NewUpload<'_>,
+) -> Result<Upload, sqlx::Error> {
+    let mut record = sqlx::query_as::<_, Upload>(&format!(
+        "INSERT INTO uploads (id, filename, original_name, mime, size_bytes, width, height, \
+         alt, caption, uploaded_by) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?) \
+         RETURNING {UPLOAD_COLUMNS}"
+    ))
+    .bind(upload.id)
+    .bind(upload.filename)
+    .bind(upload.original_name)
+    .bind(upload.mime)
+    .bind(upload.size_bytes)
+    .bind(upload.width)
+    .bind(upload.height)
+    .bind(upload.alt)
+    .bind(upload.caption)
+    .bind(upload.uploaded_by)
+    .fetch_one(&mut **tx)
+    .await?;
+
+    for variant in upload.variants {
+        sqlx::query(
+            "INSERT INTO upload_variants (upload_id, width, height, filename, size_bytes) \
+             VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind(upload.id)
+        .bind(variant.width)
+        .bind(variant.height)
+        .bind(&variant.filename)
+        .bind(variant.size_bytes as i64)
+        .execute(&mut **tx)
+        .await?;
+    }
+    attach_variants(&mut **tx, std::slice::from_mut(&mut record)).await?;
+    Ok(record)
+}
+
+pub async fn get_upload(pool: &DbPool, id: &str) -> Result<Option<Upload>, sqlx::Error> {
+    let upload = sqlx::query_as::<_, Upload>(&format!(
+        "SELECT {UPLOAD_COLUMNS} FROM uploads WHERE id = ?"
+    ))
+    .bind(id)
+    .fetch_optional(pool)
+    .await?;
+
+    let Some(mut upload) = upload else {
+        return Ok(None);
+    };
+    attach_variants(pool, std::slice::from_mut(&mut upload)).await?;
+    Ok(Some(upload))
+}
+
+/// Sets the alt text and caption of `id`; returns the updated upload, or
+/// `None` if there is no such upload.
+pub async fn update_upload_description(
+    pool: &DbPool,
+    id: &str,
+    alt: Option<&str>,
+    caption: Option<&str>,
+) -> Result<Option<Upload>, sqlx::Error> {
+    let upload = sqlx::query_as::<_, Upload>(&format!(
+        "UPDATE uploads SET alt = ?, caption = ? WHERE id = ? RETURNING {UPLOAD_COLUMNS}"
+    ))
+    .bind(alt)
+    .bind(caption)
+    .bind(id)
+    .fetch_optional(pool)
+    .await?;
+
+    let Some(mut upload) = upload else {
+        return Ok(None);
+    };
+    attach_variants(pool, std::slice::from_mut(&mut upload)).await?;
+    Ok(Some(upload))
+}
+
+/// Uploads newest first. `pattern` is a LIKE pattern (escaped with `\\`)
+/// matched against the stored and the original file name.
+pub async fn list_uploads(
+    pool: &DbPool,
+    pattern: Option<&str>,
+    limit: i64,
+    offset: i64,
+) -> Result<Vec<Upload>, sqlx::Error> {
+    let mut uploads = sqlx::query_as::<_, Upload>(&format!(
+        "SELECT {UPLOAD_COLUMNS} FROM uploads \
+         WHERE (? IS NULL OR filename LIKE ? ESCAPE '\\' OR original_name LIKE ? ESCAPE '\\') \
+         ORDER BY created_at DESC, id LIMIT ? OFFSET ?"
+    ))
+    .bind(pattern)
+    .bind(pattern)
+    .bind(pattern)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(pool)
+    .await?;
+
+    attach_variants(pool, &mut uploads).await?;
+    Ok(uploads)
+}
+
+pub async fn count_uploads(pool: &DbPool, pattern: Option<&str>) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT COUNT(*) FROM uploads \
+         WHERE (? IS NULL OR filename LIKE ? ESCAPE '\\' OR original_name LIKE ? ESCAPE '\\')",
+    )
+    .bind(pattern)
+    .bind(pattern)
+    .bind(pattern)
+    .fetch_one(pool)
+    .await
+}
+
+/// Uploads recorded at least `hours` ago, oldest first.
+pub async fn list_uploads_older_than(
+    pool: &DbPool,
+    hours: i64,
+) -> Result<Vec<Upload>, sqlx::Error> {
+    let mut uploads = sqlx::query_as::<_, Upload>(&format!(
+        "SELECT {UPLOAD_COLUMNS} FROM uploads \
+         WHERE created_at <= datetime('now', '-' || ? || ' hours') ORDER BY created_at, id"
+    ))
+    .bind(hours)
+    .fetch_all(pool)
+    .await?;
+
+    attach_variants(pool, &mut uploads).await?;
+    Ok(uploads)
+}
+
+/// Names of all recorded files, originals and variants.
+pub async fn list_recorded_filenames(pool: &DbPool) -> Result<HashSet<String>, sqlx::Error> {
+    let filenames: Vec<String> = sqlx::query_scalar(
+        "SELECT filename FROM uploads UNION ALL SELECT filename FROM upload_variants",
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(filenames.into_iter().collect())
+}
+
+/// MIME type and size of the stored file `filename`, an upload or one of
+/// its variants.
+pub async fn find_stored_file(
+    pool: &DbPool,
+    filename: &str,
+) -> Result<Option<(String, i64)>, sqlx::Error> {
+    sqlx::query_as::<_, (String, i64)>(
+        "SELECT mime, size_bytes FROM uploads WHERE filename = ?1 \
+         UNION ALL SELECT 'image/webp', size_bytes FROM upload_variants WHERE filename = ?1 \
+         LIMIT 1",
+    )
+    .bind(filename)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Fills in the `variants` of `uploads` from `upload_variants`, narrowest
+/// first.
+async fn attach_variants<'e, E>(executor: E, uploads: &mut [Upload]) -> Result<(), sqlx::Error>
+where
+    E: sqlx::Executor<'e, Database = Sqlite>,
+{
+    if uploads.is_empty() {
+        return Ok(());
+    }
+
+    let mut query_builder = sqlx::QueryBuilder::new(
+        "SELECT upload_id, width, height, filename, size_bytes FROM upload_variants \
+         WHERE upload_id IN (",
+    );
+    let mut separated = query_builder.separated(", ");
+    for upload in uploads.iter() {
+        separated.push_bind(upload.id.as_str());
+    }
+    query_builder.push(") ORDER BY width");
+
+    #[derive(sqlx::FromRow)]
+    struct VariantRow {
+        upload_id: String,
+        #[sqlx(flatten)]
+        variant: UploadVariant,
+    }
+    let rows: Vec<VariantRow> = query_builder.build_query_as().fetch_all(executor).await?;
+    let mut variants: HashMap<String, Vec<UploadVariant>> = HashMap::new();
+    for row in rows {
+        variants.entry(row.upload_id).or_default().push(row.variant);
+    }
+    for upload in uploads {
+        upload.variants = variants.remove(&upload.id).unwrap_or_default();
+    }
+    Ok(())
+}
+
+/// Content that mentions the upload `id`, through the original or any of
+/// its variants (their file names all start with the ID): tutorials and
+/// their sections, post markdown and cover images, page hero/layout blobs
+/// and site content sections, trashed items included since they can be
+/// restored. A plain substring match, so absolute and relative URLs are
+/// both found.
+pub async fn find_references(pool: &DbPool, id: &str) -> Result<Vec<UploadReference>, sqlx::Error> {
+    let escaped = id
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    let pattern = format!("%{escaped}%");
+
+    sqlx::query_as::<_, UploadReference>(concat!(
+        "SELECT 'tutorial' AS kind, id, title FROM tutorials ",
+        "WHERE content LIKE ?1 ESCAPE '\\' ",
+        "UNION ALL SELECT 'tutorial_section', tutorial_id, title FROM tutorial_sections ",
+        "WHERE content LIKE ?1 ESCAPE '\\' ",
+        "UNION ALL SELECT 'post', id, title FROM site_posts ",
+        "WHERE content_markdown LIKE ?1 ESCAPE '\\' OR cover_image_url LIKE ?1 ESCAPE '\\' ",
+        "UNION ALL SELECT 'page', id, title FROM site_pages ",
+        "WHERE hero_json LIKE ?1 ESCAPE '\\' OR layout_json LIKE ?1 ESCAPE '\\' ",
+        "UNION ALL SELECT 'site_content', section, section FROM site_content ",
+        "WHERE content_json LIKE ?1 ESCAPE '\\'"
+    ))
+    .bind(pattern)
+    .fetch_all(pool)
+    .await
+}
+
+/// Removes the record of `id` and its variants within `tx`, returning
+/// them. The caller deletes the files before committing.
+pub async fn delete_upload(
+    tx: &mut Transaction<'_, Sqlite>,
+    id: &str,
+) -> Result<Option<Upload>, sqlx::Error> {
+    let upload = sqlx::query_as::<_, Upload>(&format!(
+        "SELECT {UPLOAD_COLUMNS} FROM uploads WHERE id = ?"
+    ))
+    .bind(id)
+    .fetch_optional(&mut **tx)
+    .await?;
+
+    let Some(mut upload) = upload else {
+        return Ok(None);
+    };
+    attach_variants(&mut **tx, std::slice::from_mut(&mut upload)).await?;
+    // `upload_variants` rows go with it (ON DELETE CASCADE)
+    sqlx::query("DELETE FROM uploads WHERE id = ?")
+        .bind(id)
+        .execute(&mut **tx)
+        .await?;
+    Ok(Some(upload))
+}
@@ -1,5 +1,5 @@
 use crate::db::DbPool;
-use crate::models::User;
+use crate::models::{BlockedLoginAttempt, User};
 use sqlx::{self, FromRow};
 
 /// Represents a snapshot of failed login attempts for a specific user.
@@ -12,6 +12,23 @@ pub struct LoginAttempt {
     pub blocked_until: Option<String>,
 }
 
+/// How failures recorded under one kind of `login_attempts` key escalate
+/// into lockouts.
+#[derive(Debug, Clone, Copy)]
+pub struct LockoutPolicy {
+    /// Stored in `login_attempts.key_type`.
+    pub key_type: &'static str,
+    /// Failures that trigger the short block.
+    pub short_threshold: i64,
+    /// Failures that trigger the long block.
+    pub long_threshold: i64,
+    pub short_block_seconds: i64,
+    pub long_block_seconds: i64,
+    /// A failure arriving more than this long after the previous one starts
+    /// the count over. `None` keeps counting until a successful login.
+    pub window_seconds: Option<i64>,
+}
+
 /// Retrieves a full user record by username, including the secure password hash.
 pub async fn get_user_by_username(
     pool: &DbPool,
@@ -35,41 +52,84 @@ pub async fn get_login_attempt(
     .await
 }
 
+/// Lockouts that are still in force, longest first.
+pub async fn list_blocked_login_attempts(
+    pool: &DbPool,
+) -> Result<Vec<BlockedLoginAttempt>, sqlx::Error> {
+    let now = chrono::Utc::now().to_rfc3339();
+    sqlx::query_as::<_, BlockedLoginAttempt>(concat!(
+        "SELECT username AS key, key_type, fail_count, blocked_until, last_attempt_at ",
+        "FROM login_attempts WHERE blocked_until > ? ORDER BY blocked_until DESC"
+    ))
+    .bind(now)
+    .fetch_all(pool)
+    .await
+}
+
+/// Forgets the attempts recorded under one key. Returns `false` if there
+/// were none.
+pub async fn delete_login_attempt(pool: &DbPool, key: &str) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM login_attempts WHERE username = ?")
+        .bind(key)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Forgets every recorded attempt, lifting all lockouts.
+pub async fn delete_all_login_attempts(pool: &DbPool) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM login_attempts")
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
+
 /// Atomically increments the failure count and applies tiered blocking logic.
 ///
 /// Blocking Strategy:
-/// - `short_threshold`..`long_threshold - 1` failures: applies `short_block`.
-/// - `long_threshold`+ failures: applies `long_block`.
+/// - `short_threshold`..`long_threshold - 1` failures: applies the short block.
+/// - `long_threshold`+ failures: applies the long block.
+/// - A failure outside the policy's window restarts the count at 1.
 /// - Uses SQLite's UPSERT pattern for thread-safe counters.
 ///
-/// Thresholds are parameters because the same table backs two differently
+/// The policy is a parameter because the same table backs two differently
 /// tuned limiters: the tight per-(IP+username) lockout and the looser
 /// IP-wide lockout that catches username rotation.
 pub async fn record_failed_login(
     pool: &DbPool,
-    username_hash: &str,
-    long_block: &str,
-    short_block: &str,
-    long_threshold: i64,
-    short_threshold: i64,
+    key_hash: &str,
+    policy: &LockoutPolicy,
 ) -> Result<(), sqlx::Error> {
-    let now = chrono::Utc::now().to_rfc3339();
-    sqlx::query(
-        "INSERT INTO login_attempts (username, fail_count, blocked_until, last_attempt_at) \
-         VALUES (?, 1, NULL, ?) \
-         ON CONFLICT(username) DO UPDATE SET fail_count = login_attempts.fail_count + 1, \
-         blocked_until = CASE \
-             WHEN login_attempts.fail_count + 1 >= ? THEN ? \
-             WHEN login_attempts.fail_count + 1 >= ? THEN ? \
-             ELSE NULL \
-         END, \
-         last_attempt_at = excluded.last_attempt_at",
-    )
-    .bind(username_hash)
-    .bind(&now)
-    .bind(long_threshold)
+    let now = chrono::Utc::now();
+    let long_block = (now + chrono::Duration::seconds(policy.long_block_seconds)).to_rfc3339();
+    let short_block = (now + chrono::Duration::seconds(policy.short_block_seconds)).to_rfc3339();
+    // Without a window the comparison is against NULL and never restarts.
+    let window_start = policy
+        .window_seconds
+        .map(|seconds| (now - chrono::Duration::seconds(seconds)).to_rfc3339());
+
+    sqlx::query(concat!(
+        "INSERT INTO login_attempts (username, key_type, fail_count, blocked_until, last_attempt_at) ",
+        "VALUES (?1, ?2, 1, NULL, ?3) ",
+        "ON CONFLICT(username) DO UPDATE SET ",
+        "fail_count = CASE WHEN login_attempts.last_attempt_at < ?4 THEN 1 ",
+        "    ELSE login_attempts.fail_count + 1 END, ",
+        "blocked_until = CASE ",
+        "    WHEN login_attempts.last_attempt_at < ?4 THEN NULL ",
+        "    WHEN login_attempts.fail_count + 1 >= ?5 THEN ?6 ",
+        "    WHEN login_attempts.fail_count + 1 >= ?7 THEN ?8 ",
+        "    ELSE NULL ",
+        "END, ",
+        "key_type = excluded.key_type, ",
+        "last_attempt_at = excluded.last_attempt_at"
+    ))
+    .bind(key_hash)
+    .bind(policy.key_type)
+    .bind(now.to_rfc3339())
+    .bind(window_start)
+    .bind(policy.long_threshold)
     .bind(long_block)
-    .bind(short_threshold)
+    .bind(policy.short_threshold)
     .bind(short_block)
     .execute(pool)
     .await?;
@@ -78,9 +138,9 @@ pub async fn record_failed_login(
 
 /// Deletes login attempt rows whose lockout state can no longer matter.
 ///
-/// A row is stale once its last failure is older than the cutoff: the longest
-/// lockout is 60 seconds, so anything inactive for hours carries no security
-/// state worth keeping. Without this, the table grows unbounded under attack
+/// A row is stale once its last failure and any lockout ended more than 24
+/// hours ago: the longest lockout is a few minutes, so anything inactive for
+/// that long carries no security state worth keeping. Without this, the table grows unbounded under attack
 /// traffic from rotating IPs (each IP+username combination is its own row,
 /// and rows are otherwise only deleted on a successful login for that key).
 ///
@@ -89,10 +149,14 @@ pub async fn record_failed_login(
 /// make same-day lexicographic comparisons unreliable).
 pub async fn cleanup_stale_login_attempts(pool: &DbPool) -> Result<u64, sqlx::Error> {
     let cutoff = (chrono::Utc::now() - chrono::Duration::hours(24)).to_rfc3339();
-    let result = sqlx::query("DELETE FROM login_attempts WHERE last_attempt_at < ?")
-        .bind(cutoff)
-        .execute(pool)
-        .await?;
+    let result = sqlx::query(concat!(
+        "DELETE FROM login_attempts WHERE last_attempt_at < ? ",
+        "AND (blocked_until IS NULL OR blocked_until < ?)"
+    ))
+    .bind(&cutoff)
+    .bind(&cutoff)
+    .execute(pool)
+    .await?;
     Ok(result.rows_affected())
 }
 
@@ -104,6 +168,40 @@ pub async fn clear_login_attempts(pool: &DbPool, username_hash: &str) -> Result<
     Ok(())
 }
 
+/// Replaces an account's password hash. Returns `false` if the account no
+/// longer exists.
+pub async fn update_password_hash(
+    pool: &DbPool,
+    username: &str,
+    password_hash: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("UPDATE users SET password_hash = ? WHERE username = ?")
+        .bind(password_hash)
+        .bind(username)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Swaps in a rehash of the account's password, but only while the stored
+/// hash is still `previous_hash`. A password change that lands in between
+/// wins; returns `false` in that case or if the account is gone.
+pub async fn replace_password_hash(
+    pool: &DbPool,
+    username: &str,
+    previous_hash: &str,
+    password_hash: &str,
+) -> Result<bool, sqlx::Error> {
+    let result =
+        sqlx::query("UPDATE users SET password_hash = ? WHERE username = ? AND password_hash = ?")
+            .bind(password_hash)
+            .bind(username)
+            .bind(previous_hash)
+            .execute(pool)
+            .await?;
+    Ok(result.rows_affected() > 0)
+}
+
 pub async fn check_user_exists_by_name(pool: &DbPool, username: &str) -> Result<bool, sqlx::Error> {
     let exists: Option<(i64,)> = sqlx::query_as("SELECT 1 FROM users WHERE username = ?")
         .bind(username)
@@ -111,3 +209,127 @@ pub async fn check_user_exists_by_name(pool: &DbPool, username: &str) -> Result<
         .await?;
     Ok(exists.is_some())
 }
+
+/// Outcome of a change that must leave at least one admin account.
+#[derive(Debug)]
+pub enum AdminGuarded<T> {
+    /// The change was applied.
+    Applied(T),
+    /// No user with that ID.
+    NotFound,
+    /// The change would have removed the last admin; nothing was written.
+    LastAdmin,
+}
+
+/// Lists every account, oldest first.
+pub async fn list_users(pool: &DbPool) -> Result<Vec<User>, sqlx::Error> {
+    sqlx::query_as::<_, User>("SELECT * FROM users ORDER BY id")
+        .fetch_all(pool)
+        .await
+}
+
+/// Email address of a user. Accounts have no email column yet; until one
+/// is added this returns `None` for everyone.
+pub async fn get_user_email(pool: &DbPool, id: i64) -> Result<Option<String>, sqlx::Error> {
+    let has_email: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM pragma_table_info('users') WHERE name = 'email'")
+            .fetch_one(pool)
+            .await?;
+    if has_email == 0 {
+        return Ok(None);
+    }
+
+    let email: Option<Option<String>> = sqlx::query_scalar("SELECT email FROM users WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+    Ok(email.flatten())
+}
+
+pub async fn get_user_by_id(pool: &DbPool, id: i64) -> Result<Option<User>, sqlx::Error> {
+    sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Inserts an account. A taken username surfaces as a unique violation.
+pub async fn create_user(
+    pool: &DbPool,
+    username: &str,
+    password_hash: &str,
+    role: &str,
+) -> Result<User, sqlx::Error> {
+    sqlx::query_as::<_, User>(
+        "INSERT INTO users (username, password_hash, role) VALUES (?, ?, ?) RETURNING *",
+    )
+    .bind(username)
+    .bind(password_hash)
+    .bind(role)
+    .fetch_one(pool)
+    .await
+}
+
+/// Changes an account's role and/or password hash and revokes every session
+/// of the account in the same transaction, so no token outlives the change.
+///
+/// The last-admin check is part of the `UPDATE` itself, so two concurrent
+/// demotions cannot both succeed and leave the site without an admin.
+pub async fn update_user(
+    pool: &DbPool,
+    id: i64,
+    role: Option<&str>,
+    password_hash: Option<&str>,
+) -> Result<AdminGuarded<User>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let updated = sqlx::query_as::<_, User>(concat!(
+        "UPDATE users SET role = COALESCE(?, role), password_hash = COALESCE(?, password_hash) ",
+        "WHERE id = ? AND (COALESCE(?, role) = 'admin' OR role <> 'admin' ",
+        "OR (SELECT COUNT(*) FROM users WHERE role = 'admin') > 1) ",
+        "RETURNING *"
+    ))
+    .bind(role)
+    .bind(password_hash)
+    .bind(id)
+    .bind(role)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let Some(user) = updated else {
+        drop(tx);
+        return missing_or_last_admin(pool, id).await;
+    };
+    crate::repositories::sessions::revoke_all_sessions(&mut *tx, &user.username).await?;
+    tx.commit().await?;
+    Ok(AdminGuarded::Applied(user))
+}
+
+/// Deletes an account unless it is the last admin, revoking its sessions in
+/// the same transaction.
+pub async fn delete_user(pool: &DbPool, id: i64) -> Result<AdminGuarded<()>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let deleted: Option<String> = sqlx::query_scalar(concat!(
+        "DELETE FROM users WHERE id = ? AND (role <> 'admin' ",
+        "OR (SELECT COUNT(*) FROM users WHERE role = 'admin') > 1) ",
+        "RETURNING username"
+    ))
+    .bind(id)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let Some(username) = deleted else {
+        drop(tx);
+        return missing_or_last_admin(pool, id).await;
+    };
+    crate::repositories::sessions::revoke_all_sessions(&mut *tx, &username).await?;
+    tx.commit().await?;
+    Ok(AdminGuarded::Applied(()))
+}
+
+/// Explains why a guarded write to user `id` matched no row.
+async fn missing_or_last_admin<T>(pool: &DbPool, id: i64) -> Result<AdminGuarded<T>, sqlx::Error> {
+    Ok(match get_user_by_id(pool, id).await? {
+        Some(_) => AdminGuarded::LastAdmin,
+        None => AdminGuarded::NotFound,
+    })
+}
@@ -0,0 +1,79 @@
+//! Daily read counts of tutorials and posts.
+
+use crate::db::DbPool;
+use crate::models::ContentViewTotal;
+use std::collections::HashMap;
+
+/// Adds one view to the row of `day` (`YYYY-MM-DD`).
+pub async fn increment(
+    pool: &DbPool,
+    entity_type: &str,
+    entity_id: &str,
+    day: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(concat!(
+        "INSERT INTO content_views (entity_type, entity_id, day, count) VALUES (?, ?, ?, 1) ",
+        "ON CONFLICT(entity_type, entity_id, day) DO UPDATE SET count = count + 1"
+    ))
+    .bind(entity_type)
+    .bind(entity_id)
+    .bind(day)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// All-time views of each of `ids`. IDs never viewed are left out.
+pub async fn view_counts(
+    pool: &DbPool,
+    entity_type: &str,
+    ids: &[String],
+) -> Result<HashMap<String, i64>, sqlx::Error> {
+    if ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let mut query_builder = sqlx::QueryBuilder::new(
+        "SELECT entity_id, SUM(count) FROM content_views WHERE entity_type = ",
+    );
+    query_builder.push_bind(entity_type);
+    query_builder.push(" AND entity_id IN (");
+    let mut separated = query_builder.separated(", ");
+    for id in ids {
+        separated.push_bind(id);
+    }
+    query_builder.push(") GROUP BY entity_id");
+
+    let rows: Vec<(String, i64)> = query_builder.build_query_as().fetch_all(pool).await?;
+    Ok(rows.into_iter().collect())
+}
+
+/// Views per entity between `from` and `to` (inclusive `YYYY-MM-DD` days,
+/// either may be open), most viewed first. The title is that of the current
+/// tutorial or post, or `None` once it is gone.
+pub async fn totals(
+    pool: &DbPool,
+    entity_type: &str,
+    from: Option<&str>,
+    to: Option<&str>,
+) -> Result<Vec<ContentViewTotal>, sqlx::Error> {
+    let titles = if entity_type == "post" {
+        "site_posts"
+    } else {
+        "tutorials"
+    };
+    sqlx::query_as::<_, ContentViewTotal>(&format!(
+        "SELECT v.entity_id, t.title, SUM(v.count) AS views FROM content_views v \
+         LEFT JOIN {titles} t ON t.id = v.entity_id \
+         WHERE v.entity_type = ? AND (? IS NULL OR v.day >= ?) AND (? IS NULL OR v.day <= ?) \
+         GROUP BY v.entity_id ORDER BY views DESC, v.entity_id"
+    ))
+    .bind(entity_type)
+    .bind(from)
+    .bind(from)
+    .bind(to)
+    .bind(to)
+    .fetch_all(pool)
+    .await
+}
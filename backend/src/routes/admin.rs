@@ -1,8 +1,13 @@
-use crate::handlers::{comments, site_content, site_pages, site_posts, tutorials, upload};
+use crate::handlers::{
+    api_keys, audit_log, comment_blocklist, comments, content_sections, content_transfer,
+    deletion_log, icons, login_attempts, maintenance, notifications, previews, search,
+    site_content, site_pages, site_posts, stats, tutorials, upload, users,
+};
 use crate::middleware::auth::auth_middleware;
 use crate::security::csrf::enforce_csrf;
-use crate::{db::DbPool, middleware::security::TrustedClientIpKeyExtractor};
+use crate::{db::DbPool, middleware::security::TrustedClientIpKeyExtractor, state::AppState};
 use axum::{
+    extract::DefaultBodyLimit,
     routing::{delete, get, post, put},
     Router,
 };
@@ -11,7 +16,14 @@ use std::sync::Arc;
 use tower_governor::{governor::GovernorConfig, GovernorLayer};
 use tower_http::limit::RequestBodyLimitLayer;
 
-const ADMIN_BODY_LIMIT: usize = 11 * 1024 * 1024;
+/// Room for the multipart framing and the text fields next to an uploaded
+/// file of `MAX_UPLOAD_SIZE_MB`.
+const MULTIPART_OVERHEAD: usize = 64 * 1024;
+/// Content bundles and tutorial imports are parsed in full before anything
+/// is validated, so they get a tighter cap than uploads.
+const CONTENT_IMPORT_BODY_LIMIT: usize = 5 * 1024 * 1024;
+/// Site content sections may hold up to 5 MB of JSON.
+const SITE_CONTENT_BODY_LIMIT: usize = 5 * 1024 * 1024;
 
 /// Admin Route Module
 ///
@@ -20,13 +32,21 @@ const ADMIN_BODY_LIMIT: usize = 11 * 1024 * 1024;
 /// # Middleware Stacking (Critical)
 /// Layers are applied from bottom to top:
 /// 1. `GovernorLayer`: Prevents brute force on admin actions.
-/// 2. `RequestBodyLimitLayer`: Prevents DoS while allowing 10MB uploads plus multipart overhead.
-/// 3. `auth_middleware`: Ensures a valid JWT is present.
-/// 4. `enforce_csrf`: Validates session integrity (Double-Submit Cookie).
+/// 2. `RequestBodyLimitLayer`: Prevents DoS while allowing the largest body any route takes.
+///    Below that, extractors are held to [`super::DEFAULT_BODY_LIMIT`]; only the upload,
+///    import and site content routes raise it with their own `DefaultBodyLimit`.
+/// 3. `auth_middleware`: Ensures a valid JWT or API key is present.
+/// 4. `enforce_csrf`: Validates session integrity (Double-Submit Cookie);
+///    API-key requests are exempt.
 pub fn routes(
     pool: DbPool,
     rate_limit_config: Arc<GovernorConfig<TrustedClientIpKeyExtractor, NoOpMiddleware>>,
-) -> Router<DbPool> {
+) -> Router<AppState> {
+    let upload_body_limit = crate::config::get().max_upload_bytes() + MULTIPART_OVERHEAD;
+    let admin_body_limit = upload_body_limit
+        .max(CONTENT_IMPORT_BODY_LIMIT)
+        .max(SITE_CONTENT_BODY_LIMIT);
+
     // Dashboard initialization loads several independent resources in
     // parallel. Rate-limiting those reads by client IP makes a normal refresh
     // exhaust the small write burst and returns a misleading 429 response.
@@ -38,34 +58,252 @@ pub fn routes(
             "/api/pages/{page_id}/posts",
             get(site_posts::list_posts_for_page),
         )
-        .route("/api/posts/{id}", get(site_posts::get_post));
+        .route("/api/posts/{id}", get(site_posts::get_post))
+        .route(
+            "/api/posts/{id}/revisions",
+            get(site_posts::list_post_revisions),
+        )
+        .route(
+            "/api/posts/{id}/revisions/{revision}",
+            get(site_posts::get_post_revision),
+        )
+        .route(
+            "/api/tutorials/{id}/export.json",
+            get(tutorials::export_tutorial),
+        )
+        .route(
+            "/api/tutorials/{id}/export",
+            get(tutorials::export_tutorial_markdown),
+        )
+        .route(
+            "/api/admin/tutorials/trash",
+            get(tutorials::list_trashed_tutorials),
+        )
+        .route("/api/admin/trash", get(site_pages::list_trash))
+        .route(
+            "/api/content/{section}/revisions",
+            get(site_content::list_site_content_revisions),
+        )
+        .route(
+            "/api/content/{section}/revisions/{id}",
+            get(site_content::get_site_content_revision),
+        )
+        .route("/api/admin/search/status", get(search::search_index_status))
+        .route("/api/admin/search/analytics", get(search::search_analytics))
+        .route(
+            "/api/tutorials/{id}/revisions",
+            get(tutorials::list_revisions),
+        )
+        .route(
+            "/api/tutorials/{id}/revisions/{version}",
+            get(tutorials::get_revision),
+        )
+        .route("/api/admin/stats/content", get(stats::content_stats))
+        .route("/api/admin/stats/views", get(stats::view_stats))
+        .route("/api/admin/security/summary", get(stats::security_summary))
+        .route("/api/admin/users", get(users::list_users))
+        .route("/api/admin/api-keys", get(api_keys::list_api_keys))
+        .route("/api/admin/icons", get(icons::list_icons))
+        .route(
+            "/api/admin/content-sections",
+            get(content_sections::list_content_sections),
+        )
+        .route("/api/admin/uploads", get(upload::list_uploads))
+        .route(
+            "/api/admin/content/export",
+            get(content_transfer::export_content),
+        )
+        .route("/api/admin/audit-log", get(audit_log::list_audit_log))
+        .route(
+            "/api/admin/comments",
+            get(comments::list_moderated_comments),
+        )
+        .route(
+            "/api/admin/comment-blocklist",
+            get(comment_blocklist::list_blocklist),
+        )
+        .route(
+            "/api/admin/login-attempts",
+            get(login_attempts::list_blocked_login_attempts),
+        )
+        .route(
+            "/api/admin/deletion-log",
+            get(deletion_log::list_deletion_log),
+        )
+        .route("/api/admin/backups", get(maintenance::list_backups))
+        .route(
+            "/api/admin/backups/{name}",
+            get(maintenance::download_backup),
+        );
 
     let write_routes = Router::new()
         .route("/api/tutorials", post(tutorials::create_tutorial))
+        .route("/api/tutorials/order", put(tutorials::reorder_tutorials))
+        .route(
+            "/api/tutorials/{id}/duplicate",
+            post(tutorials::duplicate_tutorial),
+        )
+        .route(
+            "/api/tutorials/{id}/rename",
+            post(tutorials::rename_tutorial),
+        )
+        .route(
+            "/api/admin/tutorials/import-one",
+            post(tutorials::import_tutorial)
+                .layer(DefaultBodyLimit::max(CONTENT_IMPORT_BODY_LIMIT)),
+        )
+        .route(
+            "/api/admin/tutorials/import-markdown",
+            post(tutorials::import_tutorial_markdown)
+                .layer(DefaultBodyLimit::max(CONTENT_IMPORT_BODY_LIMIT)),
+        )
         .route(
             "/api/tutorials/{id}",
-            put(tutorials::update_tutorial).delete(tutorials::delete_tutorial),
+            put(tutorials::update_tutorial)
+                .patch(tutorials::patch_tutorial)
+                .delete(tutorials::delete_tutorial),
+        )
+        .route(
+            "/api/tutorials/{id}/publish",
+            post(tutorials::publish_tutorial),
+        )
+        .route(
+            "/api/tutorials/{id}/restore",
+            post(tutorials::restore_tutorial),
+        )
+        .route(
+            "/api/tutorials/{id}/revisions/{version}/restore",
+            post(tutorials::restore_revision),
+        )
+        .route(
+            "/api/tutorials/{id}/sections",
+            post(tutorials::create_section),
+        )
+        .route(
+            "/api/tutorials/{id}/sections/order",
+            put(tutorials::reorder_sections),
+        )
+        .route(
+            "/api/tutorials/{id}/sections/{section_id}",
+            put(tutorials::update_section).delete(tutorials::delete_section),
         )
         .route("/api/pages", post(site_pages::create_site_page))
         .route(
             "/api/pages/{id}",
-            put(site_pages::update_site_page).delete(site_pages::delete_site_page),
+            put(site_pages::update_site_page)
+                .patch(site_pages::patch_site_page)
+                .delete(site_pages::delete_site_page),
+        )
+        .route(
+            "/api/pages/{id}/duplicate",
+            post(site_pages::duplicate_site_page),
+        )
+        .route(
+            "/api/pages/{id}/restore",
+            post(site_pages::restore_site_page),
         )
         .route("/api/pages/{page_id}/posts", post(site_posts::create_post))
         .route(
             "/api/content/{section}",
-            put(site_content::update_site_content),
+            put(site_content::update_site_content)
+                .layer(DefaultBodyLimit::max(SITE_CONTENT_BODY_LIMIT)),
+        )
+        .route(
+            "/api/content/{section}/revisions/{id}/restore",
+            post(site_content::restore_site_content_revision),
         )
         .route(
             "/api/posts/{id}",
             put(site_posts::update_post).delete(site_posts::delete_post),
         )
+        .route("/api/posts/{id}/restore", post(site_posts::restore_post))
+        .route(
+            "/api/posts/{id}/revisions/{revision}/restore",
+            post(site_posts::restore_post_revision),
+        )
+        .route(
+            "/api/admin/pages/{id}/preview-token",
+            post(previews::issue_page_preview_token).delete(previews::revoke_page_preview_tokens),
+        )
+        .route(
+            "/api/admin/posts/{id}/preview-token",
+            post(previews::issue_post_preview_token).delete(previews::revoke_post_preview_tokens),
+        )
         .route(
             "/api/tutorials/{id}/comments",
             post(comments::create_comment),
         )
-        .route("/api/comments/{id}", delete(comments::delete_comment))
-        .route("/api/upload", post(upload::upload_image))
+        .route(
+            "/api/comments/{id}",
+            put(comments::update_comment).delete(comments::delete_comment),
+        )
+        .route(
+            "/api/admin/comments",
+            delete(comments::delete_comments_bulk),
+        )
+        .route(
+            "/api/admin/comment-blocklist",
+            post(comment_blocklist::create_blocklist_entry),
+        )
+        .route(
+            "/api/admin/comment-blocklist/{id}",
+            delete(comment_blocklist::delete_blocklist_entry),
+        )
+        .route(
+            "/api/upload",
+            post(upload::upload_image).layer(DefaultBodyLimit::max(upload_body_limit)),
+        )
+        .route("/api/admin/users", post(users::create_user))
+        .route(
+            "/api/admin/users/{id}",
+            put(users::update_user).delete(users::delete_user),
+        )
+        .route(
+            "/api/admin/deletion-log/{id}/restore",
+            post(deletion_log::restore_deletion),
+        )
+        .route("/api/admin/maintenance/prune", post(maintenance::prune_now))
+        .route("/api/admin/backup", post(maintenance::create_backup))
+        .route(
+            "/api/admin/maintenance",
+            post(maintenance::set_maintenance_mode),
+        )
+        .route("/api/admin/search/reindex", post(search::reindex_search))
+        .route(
+            "/api/admin/notifications/test",
+            post(notifications::send_test_notification),
+        )
+        .route(
+            "/api/admin/login-attempts",
+            delete(login_attempts::clear_all_login_attempts),
+        )
+        .route(
+            "/api/admin/login-attempts/{key}",
+            delete(login_attempts::clear_login_attempt),
+        )
+        .route("/api/admin/api-keys", post(api_keys::create_api_key))
+        .route("/api/admin/api-keys/{id}", delete(api_keys::revoke_api_key))
+        .route("/api/admin/icons", post(icons::create_icon))
+        .route("/api/admin/icons/{name}", delete(icons::delete_icon))
+        .route(
+            "/api/admin/content-sections",
+            post(content_sections::create_content_section),
+        )
+        .route(
+            "/api/admin/content-sections/{name}",
+            delete(content_sections::delete_content_section),
+        )
+        .route("/api/admin/uploads/fetch", post(upload::fetch_upload))
+        .route("/api/admin/uploads/cleanup", post(upload::clean_up_uploads))
+        .route(
+            "/api/admin/uploads/{id}",
+            put(upload::update_upload).delete(upload::delete_upload),
+        )
+        .route(
+            "/api/admin/content/import",
+            post(content_transfer::import_content)
+                .layer(DefaultBodyLimit::max(CONTENT_IMPORT_BODY_LIMIT)),
+        )
         .layer(GovernorLayer::new(rate_limit_config));
 
     Router::new()
@@ -79,7 +317,7 @@ pub fn routes(
             pool.clone(),
             auth_middleware,
         ))
-        .layer(RequestBodyLimitLayer::new(ADMIN_BODY_LIMIT))
+        .layer(RequestBodyLimitLayer::new(admin_body_limit))
 }
 
 #[cfg(test)]
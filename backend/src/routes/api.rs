@@ -1,13 +1,17 @@
-use crate::handlers::{auth, comments, newsletter, search, site_content, site_pages, tutorials};
-use crate::{db::DbPool, middleware::security::TrustedClientIpKeyExtractor};
+use crate::handlers::{
+    auth, changelog, comments, newsletter, search, site_content, site_pages, tutorials, upload,
+    well_known,
+};
+use crate::security::csrf::CsrfGuard;
+use crate::{db::DbPool, middleware::security::TrustedClientIpKeyExtractor, state::AppState};
 use axum::{
+    middleware::from_extractor_with_state,
     routing::{get, post},
     Router,
 };
 use governor::middleware::NoOpMiddleware;
 use std::sync::Arc;
 use tower_governor::{governor::GovernorConfig, GovernorLayer};
-use tower_http::services::ServeDir;
 
 /// Public API Route Module
 ///
@@ -17,12 +21,17 @@ use tower_http::services::ServeDir;
 /// - **Read Access**: Generally open, but rate-limited.
 /// - **Write Access**: Restricted to specific public actions (like voting/commenting)
 ///   which are protected by stricter rate limits.
-/// - **Static Assets**: Serves the `uploads` directory safely via `tower-http`.
+/// - **CSRF**: Every group containing a mutating route carries a router-level
+///   `CsrfGuard` layer, so enforcement doesn't depend on each handler
+///   remembering the extractor. New mutating routes must also be listed in
+///   [`super::MUTATING_ROUTES`].
+/// - **Uploads**: `/uploads/{key}` streams stored files with year-long cache
+///   headers, ETags and range support.
 pub fn routes(
-    upload_dir: String,
+    pool: DbPool,
     _admin_rate_limit_config: Arc<GovernorConfig<TrustedClientIpKeyExtractor, NoOpMiddleware>>,
     public_rate_limit_config: Arc<GovernorConfig<TrustedClientIpKeyExtractor, NoOpMiddleware>>,
-) -> Router<DbPool> {
+) -> Router<AppState> {
     // Grouped so both endpoints share the same rate limit: voting has no
     // dedicated limit of its own and would otherwise be callable at
     // unlimited frequency by any authenticated client.
@@ -31,23 +40,52 @@ pub fn routes(
             "/api/posts/{id}/comments",
             get(comments::list_post_comments).post(comments::create_post_comment),
         )
-        .route("/api/comments/{id}/vote", post(comments::vote_comment))
-        .route_layer(GovernorLayer::new(public_rate_limit_config.clone()));
+        .route(
+            "/api/comments/{id}/vote",
+            post(comments::vote_comment).delete(comments::unvote_comment),
+        )
+        .route("/api/comments/token", get(comments::issue_form_token))
+        .route_layer(GovernorLayer::new(public_rate_limit_config.clone()))
+        .route_layer(from_extractor_with_state::<CsrfGuard, _>(pool.clone()));
 
     let rate_limited_newsletter_route = Router::new()
         .route(
             "/api/public/newsletter",
             post(newsletter::subscribe_to_newsletter),
         )
+        .route_layer(GovernorLayer::new(public_rate_limit_config.clone()))
+        .route_layer(from_extractor_with_state::<CsrfGuard, _>(pool));
+
+    // Called on every keystroke of the search box
+    let rate_limited_search_routes = Router::new()
+        .route("/api/search/suggest", get(search::suggest))
         .route_layer(GovernorLayer::new(public_rate_limit_config));
 
     Router::new()
         .route("/api/auth/me", get(auth::me))
+        .route("/api/auth/csrf", get(auth::csrf_token))
+        .route("/api/auth/jwks", get(auth::jwks))
         .route("/api/tutorials", get(tutorials::list_tutorials))
         .route("/api/tutorials/{id}", get(tutorials::get_tutorial))
+        .route(
+            "/api/tutorials/{id}/sections",
+            get(tutorials::list_sections),
+        )
         .route("/api/search/tutorials", get(search::search_tutorials))
         .route("/api/search/topics", get(search::get_all_topics))
+        .route(
+            "/api/topics/{topic}/tutorials",
+            get(tutorials::list_topic_tutorials),
+        )
         .route("/api/tutorials/{id}/comments", get(comments::list_comments))
+        .route(
+            "/api/tutorials/{id}/comments/count",
+            get(comments::count_tutorial_comments),
+        )
+        .route(
+            "/api/posts/{id}/comments/count",
+            get(comments::count_post_comments),
+        )
         .route("/api/content", get(site_content::list_site_content))
         .route(
             "/api/content/{section}",
@@ -55,18 +93,46 @@ pub fn routes(
         )
         .merge(rate_limited_comment_routes)
         .merge(rate_limited_newsletter_route)
+        .merge(rate_limited_search_routes)
         .route(
             "/api/public/pages/{slug}",
             get(site_pages::get_published_page_by_slug),
         )
+        .route(
+            "/api/public/pages/{slug}/posts",
+            get(site_pages::list_published_posts),
+        )
         .route(
             "/api/public/pages/{slug}/posts/{post_slug}",
             get(site_pages::get_published_post_by_slug),
         )
+        .route(
+            "/api/public/pages/{slug}/archive",
+            get(site_pages::get_archive),
+        )
+        .route(
+            "/api/public/pages/{slug}/archive/{year}/{month}",
+            get(site_pages::list_archive_posts),
+        )
+        .route("/api/public/tags", get(site_pages::list_tags))
         .route("/api/public/navigation", get(site_pages::get_navigation))
+        .route("/api/public/changelog", get(changelog::get_changelog))
+        .route(
+            "/api/public/changelog.rss",
+            get(changelog::get_changelog_rss),
+        )
+        .route(
+            "/api/public/settings",
+            get(site_content::get_public_settings),
+        )
         .route(
             "/api/public/published-pages",
             get(site_pages::list_published_page_slugs),
         )
-        .nest_service("/uploads", ServeDir::new(upload_dir))
+        // Static-path routes win over main.rs's `/{*path}` SPA fallback.
+        .route("/robots.txt", get(well_known::robots_txt))
+        .route("/sitemap.xml", get(well_known::sitemap_xml))
+        .route("/sitemaps/{file}", get(well_known::sitemap_part))
+        .route("/.well-known/security.txt", get(well_known::security_txt))
+        .route("/uploads/{key}", get(upload::serve_upload))
 }
@@ -1,13 +1,16 @@
 use crate::handlers::auth;
-use crate::{db::DbPool, middleware::security::TrustedClientIpKeyExtractor};
-use axum::{routing::post, Router};
+use crate::{middleware::security::TrustedClientIpKeyExtractor, state::AppState};
+use axum::{
+    routing::{delete, get, post},
+    Router,
+};
 use std::sync::Arc;
 use tower_governor::{governor::GovernorConfigBuilder, GovernorLayer};
 use tower_http::limit::RequestBodyLimitLayer;
 
 const LOGIN_BODY_LIMIT: usize = 64 * 1024;
 
-pub fn routes() -> Router<DbPool> {
+pub fn routes() -> Router<AppState> {
     let rate_limit_config = Arc::new(
         GovernorConfigBuilder::default()
             .per_second(1)
@@ -20,7 +23,19 @@ pub fn routes() -> Router<DbPool> {
     Router::new()
         // Core Identity Endpoints
         .route("/api/auth/login", post(auth::login))
+        .route("/api/auth/refresh", post(auth::refresh))
         .route("/api/auth/logout", post(auth::logout))
+        .route("/api/auth/change-password", post(auth::change_password))
+        .route("/api/auth/sudo", post(auth::enter_sudo_mode))
+        // Sessions
+        .route("/api/auth/sessions", get(auth::list_sessions))
+        .route("/api/auth/sessions/{jti}", delete(auth::revoke_session))
+        .route("/api/auth/logout-all", post(auth::logout_all))
+        // Two-Factor Authentication
+        .route("/api/auth/login/2fa", post(auth::login_two_factor))
+        .route("/api/auth/2fa/setup", post(auth::setup_two_factor))
+        .route("/api/auth/2fa/verify", post(auth::verify_two_factor))
+        .route("/api/auth/2fa/disable", post(auth::disable_two_factor))
         // System-wide Protections
         .layer(RequestBodyLimitLayer::new(LOGIN_BODY_LIMIT))
         .layer(GovernorLayer::new(rate_limit_config))
@@ -2,12 +2,95 @@ pub mod admin;
 pub mod api;
 pub mod auth;
 
-use crate::{db::DbPool, middleware::security::TrustedClientIpKeyExtractor};
+use crate::{db::DbPool, middleware::security::TrustedClientIpKeyExtractor, state::AppState};
 use axum::Router;
 use std::sync::Arc;
 use tower_governor::governor::GovernorConfigBuilder;
 
-pub fn create_routes(pool: DbPool, upload_dir: String) -> Router<DbPool> {
+/// Cap on the request bodies extractors (`Json`, `Multipart`, ...) read,
+/// applied to the whole app in `main.rs`. Routes that take more, like the
+/// upload route, raise it with their own `DefaultBodyLimit`.
+pub const DEFAULT_BODY_LIMIT: usize = 1024 * 1024;
+
+/// Every state-changing route registered in [`admin`] and [`api`], as
+/// `(method, path)`.
+///
+/// The route tests send an authenticated request without a CSRF token to
+/// each entry and expect a 403, and they cross-check this list against the
+/// `.route(...)` registrations in both modules, so a mutating route that is
+/// missing here (and therefore unverified) fails the test suite.
+pub const MUTATING_ROUTES: &[(&str, &str)] = &[
+    // admin.rs
+    ("POST", "/api/tutorials"),
+    ("PUT", "/api/tutorials/order"),
+    ("POST", "/api/tutorials/{id}/duplicate"),
+    ("POST", "/api/tutorials/{id}/rename"),
+    ("POST", "/api/admin/tutorials/import-one"),
+    ("POST", "/api/admin/tutorials/import-markdown"),
+    ("PUT", "/api/tutorials/{id}"),
+    ("PATCH", "/api/tutorials/{id}"),
+    ("DELETE", "/api/tutorials/{id}"),
+    ("POST", "/api/tutorials/{id}/publish"),
+    ("POST", "/api/tutorials/{id}/restore"),
+    ("POST", "/api/tutorials/{id}/revisions/{version}/restore"),
+    ("POST", "/api/tutorials/{id}/sections"),
+    ("PUT", "/api/tutorials/{id}/sections/order"),
+    ("PUT", "/api/tutorials/{id}/sections/{section_id}"),
+    ("DELETE", "/api/tutorials/{id}/sections/{section_id}"),
+    ("POST", "/api/pages"),
+    ("PUT", "/api/pages/{id}"),
+    ("PATCH", "/api/pages/{id}"),
+    ("DELETE", "/api/pages/{id}"),
+    ("POST", "/api/pages/{id}/duplicate"),
+    ("POST", "/api/pages/{id}/restore"),
+    ("POST", "/api/pages/{page_id}/posts"),
+    ("PUT", "/api/content/{section}"),
+    ("POST", "/api/content/{section}/revisions/{id}/restore"),
+    ("PUT", "/api/posts/{id}"),
+    ("DELETE", "/api/posts/{id}"),
+    ("POST", "/api/posts/{id}/restore"),
+    ("POST", "/api/posts/{id}/revisions/{revision}/restore"),
+    ("POST", "/api/admin/pages/{id}/preview-token"),
+    ("DELETE", "/api/admin/pages/{id}/preview-token"),
+    ("POST", "/api/admin/posts/{id}/preview-token"),
+    ("DELETE", "/api/admin/posts/{id}/preview-token"),
+    ("POST", "/api/tutorials/{id}/comments"),
+    ("PUT", "/api/comments/{id}"),
+    ("DELETE", "/api/comments/{id}"),
+    ("DELETE", "/api/admin/comments"),
+    ("POST", "/api/admin/comment-blocklist"),
+    ("DELETE", "/api/admin/comment-blocklist/{id}"),
+    ("POST", "/api/upload"),
+    ("POST", "/api/admin/users"),
+    ("PUT", "/api/admin/users/{id}"),
+    ("DELETE", "/api/admin/users/{id}"),
+    ("POST", "/api/admin/deletion-log/{id}/restore"),
+    ("POST", "/api/admin/maintenance/prune"),
+    ("POST", "/api/admin/maintenance"),
+    ("POST", "/api/admin/backup"),
+    ("POST", "/api/admin/search/reindex"),
+    ("POST", "/api/admin/notifications/test"),
+    ("DELETE", "/api/admin/login-attempts"),
+    ("DELETE", "/api/admin/login-attempts/{key}"),
+    ("POST", "/api/admin/api-keys"),
+    ("DELETE", "/api/admin/api-keys/{id}"),
+    ("POST", "/api/admin/icons"),
+    ("DELETE", "/api/admin/icons/{name}"),
+    ("POST", "/api/admin/content-sections"),
+    ("DELETE", "/api/admin/content-sections/{name}"),
+    ("POST", "/api/admin/content/import"),
+    ("POST", "/api/admin/uploads/fetch"),
+    ("POST", "/api/admin/uploads/cleanup"),
+    ("PUT", "/api/admin/uploads/{id}"),
+    ("DELETE", "/api/admin/uploads/{id}"),
+    // api.rs
+    ("POST", "/api/posts/{id}/comments"),
+    ("POST", "/api/comments/{id}/vote"),
+    ("DELETE", "/api/comments/{id}/vote"),
+    ("POST", "/api/public/newsletter"),
+];
+
+pub fn create_routes(pool: DbPool) -> Router<AppState> {
     let admin_rate_limit_config = Arc::new(
         GovernorConfigBuilder::default()
             .per_second(1)
@@ -28,14 +111,13 @@ pub fn create_routes(pool: DbPool, upload_dir: String) -> Router<DbPool> {
 
     let login_router = auth::routes();
     let admin_router = admin::routes(pool.clone(), admin_rate_limit_config.clone());
-    let api_router = api::routes(
-        upload_dir,
-        admin_rate_limit_config,
-        public_rate_limit_config,
-    );
+    let api_router = api::routes(pool, admin_rate_limit_config, public_rate_limit_config);
 
     Router::new()
         .merge(login_router)
         .merge(admin_router)
         .merge(api_router)
 }
+
+#[cfg(test)]
+mod tests;
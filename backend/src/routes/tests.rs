@@ -0,0 +1,305 @@
+//! Route tests: requests go through the whole API router, middleware
+//! included, over a fresh in-memory database per test. The helpers here
+//! set up the database, the accounts and the requests; the tests live in
+//! one module per feature.
+
+mod access;
+mod content;
+mod pages;
+mod posts;
+mod search;
+mod tutorials;
+mod uploads;
+mod users;
+
+use super::*;
+use crate::models::{CreateSitePageRequest, CreateSitePostRequest, SitePage, SitePost, Tutorial};
+use crate::security::{auth, csrf};
+use crate::storage::Store;
+use axum::{
+    body::{Body, Bytes},
+    extract::{ConnectInfo, DefaultBodyLimit},
+    http::{header, request, HeaderMap, Method, Request, StatusCode},
+};
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU32, Ordering};
+use tower::ServiceExt;
+
+fn init_secrets() {
+    if auth::JWT_SECRET.get().is_none() {
+        let _ =
+            auth::init_jwt_secret("this_is_a_test_jwt_secret_with_adequate_entropy_123_ABC_!!!");
+    }
+    let _ = csrf::init_csrf_secret(
+        "this_is_a_very_long_secret_key_for_testing_purposes_only_at_least_32_bytes",
+    );
+    let _ = crate::handlers::auth::init_login_attempt_salt(
+        "this_is_a_test_salt_for_login_attempts_at_least_32_chars",
+    );
+}
+
+/// A signed-in account: its bearer token and a matching CSRF token.
+struct TestUser {
+    token: String,
+    csrf_token: String,
+}
+
+/// Signs in as an account named after `role`, creating it on first use.
+/// The session is recorded the way a password login records it.
+async fn seed_user(pool: &DbPool, role: &str) -> TestUser {
+    sign_in(pool, auth::Claims::new(role.to_string(), role.to_string())).await
+}
+
+/// Like [`seed_user`], in sudo mode.
+async fn seed_sudo_user(pool: &DbPool, role: &str) -> TestUser {
+    let claims = auth::Claims::new(role.to_string(), role.to_string());
+    sign_in(pool, claims.with_sudo(chrono::Utc::now())).await
+}
+
+async fn sign_in(pool: &DbPool, claims: auth::Claims) -> TestUser {
+    sqlx::query("INSERT OR IGNORE INTO users (username, password_hash, role) VALUES (?, '', ?)")
+        .bind(&claims.sub)
+        .bind(&claims.role)
+        .execute(pool)
+        .await
+        .expect("seed user");
+    crate::repositories::sessions::create_session(
+        pool,
+        &claims.jti,
+        &claims.sub,
+        claims.exp as i64,
+        None,
+        "test",
+    )
+    .await
+    .expect("record session");
+    TestUser {
+        token: auth::encode_claims(&claims).expect("issue jwt"),
+        csrf_token: csrf::issue_csrf_token(&claims.sub, chrono::Duration::hours(1))
+            .expect("issue csrf token"),
+    }
+}
+
+/// A response with its body read to the end.
+struct TestResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Bytes,
+}
+
+impl TestResponse {
+    /// The body as JSON, or `Null` if it is not JSON.
+    fn json(&self) -> serde_json::Value {
+        serde_json::from_slice(&self.body).unwrap_or_default()
+    }
+}
+
+/// The API router over a fresh database, with the body limit `main.rs`
+/// applies and uploads kept in the configured upload directory.
+struct TestApp {
+    pool: DbPool,
+    router: Router,
+    clients: AtomicU32,
+}
+
+impl TestApp {
+    async fn new() -> Self {
+        init_secrets();
+        let pool = crate::test_support::migrated_pool().await;
+        let store: Arc<dyn Store> = Arc::new(crate::storage::LocalDiskStore::new(
+            crate::config::get().upload_dir.clone(),
+        ));
+        let router = create_routes(pool.clone())
+            .layer(DefaultBodyLimit::max(DEFAULT_BODY_LIMIT))
+            .with_state(AppState {
+                pool: pool.clone(),
+                store,
+            });
+        TestApp {
+            pool,
+            router,
+            clients: AtomicU32::new(1),
+        }
+    }
+
+    /// See [`seed_user`].
+    async fn seed_user(&self, role: &str) -> TestUser {
+        seed_user(&self.pool, role).await
+    }
+
+    /// A request to `uri`, signed in as `role` with a CSRF token, or
+    /// anonymous without one.
+    async fn request(&self, role: Option<&str>, method: Method, uri: &str) -> request::Builder {
+        let builder = Request::builder().method(method).uri(uri);
+        match role {
+            Some(role) => with_user(builder, &self.seed_user(role).await),
+            None => builder,
+        }
+    }
+
+    /// Sends `body` as JSON, signed in as `role`, and returns the status and
+    /// the JSON body of the response (`Null` if it has none).
+    async fn send_as(
+        &self,
+        role: &str,
+        method: Method,
+        uri: impl AsRef<str>,
+        body: Option<serde_json::Value>,
+    ) -> (StatusCode, serde_json::Value) {
+        self.send_json(Some(role), method, uri.as_ref(), body).await
+    }
+
+    /// Like [`TestApp::send_as`], without signing in.
+    async fn send_anonymous(
+        &self,
+        method: Method,
+        uri: impl AsRef<str>,
+        body: Option<serde_json::Value>,
+    ) -> (StatusCode, serde_json::Value) {
+        self.send_json(None, method, uri.as_ref(), body).await
+    }
+
+    /// An anonymous `GET`.
+    async fn get(&self, uri: impl AsRef<str>) -> (StatusCode, serde_json::Value) {
+        self.send_anonymous(Method::GET, uri, None).await
+    }
+
+    async fn send_json(
+        &self,
+        role: Option<&str>,
+        method: Method,
+        uri: &str,
+        body: Option<serde_json::Value>,
+    ) -> (StatusCode, serde_json::Value) {
+        let builder = self.request(role, method, uri).await;
+        let request = match body {
+            Some(body) => builder
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string())),
+            None => builder.body(Body::empty()),
+        };
+        let response = self.send(request.unwrap()).await;
+        (response.status, response.json())
+    }
+
+    /// Sends `request` from the client set with [`from_client`], or else
+    /// from one not seen before, so the rate limiters stay out of it.
+    async fn send(&self, mut request: Request<Body>) -> TestResponse {
+        if request
+            .extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .is_none()
+        {
+            let [_, a, b, c] = self.clients.fetch_add(1, Ordering::Relaxed).to_be_bytes();
+            request
+                .extensions_mut()
+                .insert(ConnectInfo(SocketAddr::from(([10, a, b, c], 4000))));
+        }
+        let response = self.router.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        TestResponse {
+            status,
+            headers,
+            body,
+        }
+    }
+}
+
+/// Adds the bearer token and the CSRF header and cookie of `user`.
+fn with_user(builder: request::Builder, user: &TestUser) -> request::Builder {
+    builder
+        .header(header::AUTHORIZATION, format!("Bearer {}", user.token))
+        .header(csrf::csrf_header_name(), &user.csrf_token)
+        .header(
+            header::COOKIE,
+            format!("{}={}", csrf::csrf_cookie_name(), user.csrf_token),
+        )
+}
+
+/// Makes `request` come from `127.0.0.<client>`, for tests that count
+/// requests per client.
+fn from_client(mut request: Request<Body>, client: u8) -> Request<Body> {
+    request
+        .extensions_mut()
+        .insert(ConnectInfo(SocketAddr::from(([127, 0, 0, client], 4000))));
+    request
+}
+
+/// A published page with nothing else set.
+fn page_request(slug: &str) -> CreateSitePageRequest {
+    CreateSitePageRequest {
+        slug: slug.to_string(),
+        title: slug.to_string(),
+        description: None,
+        nav_label: None,
+        show_in_nav: false,
+        order_index: None,
+        is_published: true,
+        hero: serde_json::json!({}),
+        layout: serde_json::json!({}),
+        meta_robots: None,
+        custom_headers: Default::default(),
+    }
+}
+
+async fn seed_page(pool: &DbPool, page: CreateSitePageRequest) -> SitePage {
+    crate::repositories::pages::create_site_page(pool, page)
+        .await
+        .expect("seed page")
+}
+
+/// A published post with the body "Body" and nothing else set.
+fn post_request(slug: &str) -> CreateSitePostRequest {
+    CreateSitePostRequest {
+        title: slug.to_string(),
+        slug: slug.to_string(),
+        excerpt: None,
+        content_markdown: "Body".to_string(),
+        is_published: true,
+        allow_comments: true,
+        published_at: None,
+        order_index: None,
+        tags: Vec::new(),
+        cover_image_url: None,
+        meta_description: None,
+        canonical_url: None,
+    }
+}
+
+async fn seed_post(pool: &DbPool, page_id: &str, post: CreateSitePostRequest) -> SitePost {
+    crate::repositories::posts::create_site_post(pool, page_id, post)
+        .await
+        .expect("seed post")
+}
+
+/// A tutorial with the given title, topics and status, open for comments,
+/// with placeholder text everywhere else.
+async fn seed_tutorial(
+    pool: &DbPool,
+    id: &str,
+    title: &str,
+    topics: &[&str],
+    status: &str,
+) -> Tutorial {
+    let topics: Vec<String> = topics.iter().map(|topic| topic.to_string()).collect();
+    crate::repositories::tutorials::create_tutorial(
+        pool,
+        id,
+        title,
+        "Description",
+        "Body",
+        "Terminal",
+        "from-blue-500 to-indigo-600",
+        &serde_json::to_string(&topics).unwrap(),
+        &topics,
+        status,
+        true,
+        None,
+    )
+    .await
+    .expect("seed tutorial")
+}
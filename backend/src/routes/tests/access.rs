@@ -0,0 +1,246 @@
+use super::*;
+use crate::models::TUTORIAL_STATUS_PUBLISHED;
+use regex::Regex;
+use std::collections::BTreeSet;
+
+/// Extracts every `(METHOD, path)` registered through `.route(...)` with a
+/// state-changing method router in the given source file.
+fn mutating_registrations(source: &str) -> BTreeSet<(String, String)> {
+    let method_re = Regex::new(r"\b(post|put|patch|delete)\(").expect("valid method regex");
+    let path_re = Regex::new(r#"^\s*"([^"]+)""#).expect("valid path regex");
+
+    let mut found = BTreeSet::new();
+    for (start, _) in source.match_indices(".route(") {
+        // Walk to the matching close paren of this `.route(` call.
+        let call = &source[start + ".route(".len()..];
+        let mut depth = 1;
+        let end = call
+            .char_indices()
+            .find_map(|(i, c)| {
+                match c {
+                    '(' => depth += 1,
+                    ')' => depth -= 1,
+                    _ => {}
+                }
+                (depth == 0).then_some(i)
+            })
+            .expect("balanced .route( call");
+        let call = &call[..end];
+
+        let Some(path) = path_re.captures(call) else {
+            continue;
+        };
+        for method in method_re.captures_iter(call) {
+            found.insert((method[1].to_ascii_uppercase(), path[1].to_string()));
+        }
+    }
+    found
+}
+
+#[test]
+fn manifest_lists_every_mutating_route() {
+    let mut registered = mutating_registrations(include_str!("../admin.rs"));
+    registered.extend(mutating_registrations(include_str!("../api.rs")));
+
+    let manifest: BTreeSet<(String, String)> = MUTATING_ROUTES
+        .iter()
+        .map(|(method, path)| (method.to_string(), path.to_string()))
+        .collect();
+
+    assert_eq!(
+        registered, manifest,
+        "routes::MUTATING_ROUTES must match the mutating routes registered in admin.rs and api.rs"
+    );
+}
+
+#[tokio::test]
+async fn mutating_routes_reject_authenticated_requests_without_csrf() {
+    let app = TestApp::new().await;
+    let admin = app.seed_user("admin").await;
+
+    for (method, path) in MUTATING_ROUTES {
+        let uri = path
+            .replace("{id}", "missing")
+            .replace("{page_id}", "missing")
+            .replace("{section}", "hero")
+            .replace("{version}", "1");
+        let request = Request::builder()
+            .method(Method::from_bytes(method.as_bytes()).unwrap())
+            .uri(&uri)
+            .header(header::AUTHORIZATION, format!("Bearer {}", admin.token))
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from("{}"))
+            .unwrap();
+
+        let response = app.send(request).await;
+        assert_eq!(
+            response.status,
+            StatusCode::FORBIDDEN,
+            "{method} {path} accepted a request without a CSRF token"
+        );
+        let body = response.json();
+        assert!(
+            body["error"].as_str().unwrap_or_default().contains("CSRF"),
+            "{method} {path} was rejected for a reason other than CSRF: {body}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn editor_can_update_tutorials_but_not_delete_pages() {
+    let app = TestApp::new().await;
+    seed_tutorial(
+        &app.pool,
+        "editable",
+        "Editable",
+        &["Linux"],
+        TUTORIAL_STATUS_PUBLISHED,
+    )
+    .await;
+    let page = seed_page(&app.pool, page_request("guarded")).await;
+
+    let (status, _) = app
+        .send_as(
+            "editor",
+            Method::PUT,
+            "/api/tutorials/editable",
+            Some(serde_json::json!({ "description": "After" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = app
+        .send_as(
+            "editor",
+            Method::DELETE,
+            format!("/api/pages/{}", page.id),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn api_keys_skip_csrf_but_not_the_rate_limiter() {
+    let app = TestApp::new().await;
+    seed_tutorial(
+        &app.pool,
+        "scripted",
+        "Scripted",
+        &["Linux"],
+        TUTORIAL_STATUS_PUBLISHED,
+    )
+    .await;
+    let owner = crate::repositories::users::create_user(&app.pool, "ci-bot", "unused", "editor")
+        .await
+        .expect("seed key owner");
+    let with_key = |key: &str, client: u8| {
+        let request = Request::builder()
+            .method(Method::PUT)
+            .uri("/api/tutorials/scripted")
+            .header(auth::API_KEY_HEADER, key)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(r#"{"description":"After"}"#))
+            .unwrap();
+        app.send(from_client(request, client))
+    };
+
+    let (status, created) = app
+        .send_as(
+            "admin",
+            Method::POST,
+            "/api/admin/api-keys",
+            Some(serde_json::json!({ "name": "CI", "user_id": owner.id })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let key = created["key"].as_str().expect("plaintext key").to_string();
+    assert_eq!(created["username"], "ci-bot");
+    assert!(key.starts_with(created["prefix"].as_str().unwrap()));
+
+    let stored: String = sqlx::query_scalar("SELECT key_hash FROM api_keys")
+        .fetch_one(&app.pool)
+        .await
+        .unwrap();
+    assert_ne!(stored, key);
+
+    // No CSRF token, yet writes go through; the admin limiter still counts them.
+    for _ in 0..3 {
+        assert_eq!(with_key(&key, 2).await.status, StatusCode::OK);
+    }
+    assert_eq!(
+        with_key(&key, 2).await.status,
+        StatusCode::TOO_MANY_REQUESTS
+    );
+
+    let (status, _) = app
+        .send_as(
+            "admin",
+            Method::DELETE,
+            format!("/api/admin/api-keys/{}", created["id"]),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    assert_eq!(with_key(&key, 3).await.status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn database_backups_need_sudo_mode() {
+    let app = TestApp::new().await;
+
+    let (status, body) = app
+        .send_as("admin", Method::POST, "/api/admin/backup", None)
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["code"], "sudo_required");
+
+    let (status, body) = app
+        .send_as(
+            "admin",
+            Method::GET,
+            "/api/admin/backups/backup-20260101-000000.db",
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["code"], "sudo_required");
+
+    let (status, body) = app
+        .send_as("admin", Method::GET, "/api/admin/backups", None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body["items"].is_array());
+    assert_eq!(
+        body["retention_days"],
+        crate::config::get().backup_retention_days
+    );
+}
+
+#[tokio::test]
+async fn auth_and_csrf_rejections_are_json_with_a_code() {
+    let app = TestApp::new().await;
+    let admin = app.seed_user("admin").await;
+    let send = |method: Method, uri: &str, bearer: Option<&str>| {
+        let mut builder = Request::builder().method(method).uri(uri);
+        if let Some(bearer) = bearer {
+            builder = builder.header(header::AUTHORIZATION, format!("Bearer {bearer}"));
+        }
+        app.send(builder.body(Body::empty()).unwrap())
+    };
+
+    let response = send(Method::GET, "/api/admin/backups", None).await;
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+    assert_eq!(response.json()["code"], "unauthorized");
+    assert_eq!(response.json()["error"], "Missing authentication token");
+
+    let response = send(Method::GET, "/api/admin/backups", Some("not-a-jwt")).await;
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+    assert_eq!(response.json()["code"], "unauthorized");
+
+    // No CSRF token on a mutating request
+    let response = send(Method::POST, "/api/admin/backup", Some(&admin.token)).await;
+    assert_eq!(response.status, StatusCode::FORBIDDEN);
+    assert_eq!(response.json()["code"], "forbidden");
+}
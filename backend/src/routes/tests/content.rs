@@ -0,0 +1,457 @@
+use super::*;
+use crate::models::{TUTORIAL_STATUS_DRAFT, TUTORIAL_STATUS_PUBLISHED};
+
+#[tokio::test]
+async fn sitemap_lists_only_public_documents() {
+    let app = TestApp::new().await;
+    let mut page_ids = Vec::new();
+    for (slug, is_published, meta_robots) in [
+        ("journal", true, None),
+        ("drafts", false, None),
+        ("private", true, Some("noindex, follow".to_string())),
+    ] {
+        let page = CreateSitePageRequest {
+            is_published,
+            meta_robots,
+            ..page_request(slug)
+        };
+        page_ids.push(seed_page(&app.pool, page).await.id);
+    }
+    for (page_id, slug, is_published) in [
+        (&page_ids[0], "hello", true),
+        (&page_ids[0], "unfinished", false),
+        (&page_ids[1], "orphaned", true),
+        (&page_ids[2], "secret", true),
+    ] {
+        let post = CreateSitePostRequest {
+            is_published,
+            ..post_request(slug)
+        };
+        seed_post(&app.pool, page_id, post).await;
+    }
+    for (id, status) in [
+        ("shell-basics", TUTORIAL_STATUS_PUBLISHED),
+        ("unreleased", TUTORIAL_STATUS_DRAFT),
+    ] {
+        seed_tutorial(&app.pool, id, id, &["Linux"], status).await;
+    }
+
+    let sitemap = crate::sitemap::build(&app.pool, "https://blog.example.com")
+        .await
+        .expect("build sitemap");
+    assert!(sitemap.parts.is_empty());
+    let locs: Vec<&str> = sitemap
+        .root
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("<loc>"))
+        .filter_map(|line| line.strip_suffix("</loc>"))
+        .filter(|loc| !loc.contains("/tutorials/") || !loc.ends_with(char::is_numeric))
+        .collect();
+    // The migrations' sample tutorials (numeric ids) are published too
+    assert_eq!(
+        locs,
+        [
+            "https://blog.example.com/",
+            "https://blog.example.com/pages/journal",
+            "https://blog.example.com/posts/journal/hello",
+            "https://blog.example.com/tutorials/shell-basics",
+        ]
+    );
+
+    // Without PUBLIC_BASE_URL there is no origin to link to
+    let response = app
+        .send(Request::get("/sitemap.xml").body(Body::empty()).unwrap())
+        .await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+    assert_eq!(
+        response.headers[header::CONTENT_TYPE],
+        "application/xml; charset=utf-8"
+    );
+}
+
+#[tokio::test]
+async fn site_content_revisions_can_be_listed_and_restored() {
+    let app = TestApp::new().await;
+    let send = |method: Method, uri: String, body: Option<serde_json::Value>| {
+        app.send_as("admin", method, uri, body)
+    };
+    let revisions_uri = || "/api/content/cta_section/revisions".to_string();
+
+    let (_, seeded) = send(Method::GET, "/api/content/cta_section".to_string(), None).await;
+    for title in ["Second", "Third"] {
+        let (status, _) = send(
+            Method::PUT,
+            "/api/content/cta_section".to_string(),
+            Some(serde_json::json!({ "content": { "title": title } })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    // The seeded content was kept as the first revision, without an author
+    let (status, revisions) = send(Method::GET, revisions_uri(), None).await;
+    assert_eq!(status, StatusCode::OK);
+    let authors: Vec<_> = revisions
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|rev| rev["updated_by"].clone())
+        .collect();
+    assert_eq!(
+        authors,
+        [
+            serde_json::json!("admin"),
+            "admin".into(),
+            serde_json::Value::Null
+        ]
+    );
+    let first = revisions[2]["id"].as_i64().unwrap();
+
+    let (status, snapshot) = send(
+        Method::GET,
+        format!("/api/content/cta_section/revisions/{first}"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(snapshot["content"], seeded["content"]);
+    let (status, _) = send(
+        Method::GET,
+        "/api/content/cta_section/revisions/999".to_string(),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = send(
+        Method::GET,
+        "/api/content/unknown/revisions".to_string(),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, restored) = send(
+        Method::POST,
+        format!("/api/content/cta_section/revisions/{first}/restore"),
+        Some(serde_json::json!({})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(restored["content"], seeded["content"]);
+
+    // The restore is a revision of its own
+    let (_, revisions) = send(Method::GET, revisions_uri(), None).await;
+    assert_eq!(revisions.as_array().unwrap().len(), 4);
+    let (_, latest) = send(
+        Method::GET,
+        format!("/api/content/cta_section/revisions/{}", revisions[0]["id"]),
+        None,
+    )
+    .await;
+    assert_eq!(latest["content"], seeded["content"]);
+
+    // Older revisions beyond the limit are dropped
+    let limit = crate::config::get().site_content_revision_limit as usize;
+    for n in 0..limit {
+        crate::repositories::content::upsert_site_content(
+            &app.pool,
+            "cta_section",
+            &serde_json::json!({ "title": format!("Bulk {n}") }),
+            "admin",
+        )
+        .await
+        .expect("update section");
+    }
+    let (_, revisions) = send(Method::GET, revisions_uri(), None).await;
+    assert_eq!(revisions.as_array().unwrap().len(), limit);
+    let (status, _) = send(
+        Method::GET,
+        format!("/api/content/cta_section/revisions/{first}"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn public_reads_are_cached_until_a_write_invalidates_them() {
+    let app = TestApp::new().await;
+    let page = CreateSitePageRequest {
+        title: "Almanac".to_string(),
+        show_in_nav: true,
+        ..page_request("almanac")
+    };
+    let page = seed_page(&app.pool, page).await;
+    let write = |method: Method, uri: String, body: serde_json::Value| {
+        app.send_as("admin", method, uri, Some(body))
+    };
+    let read = |uri: &'static str| {
+        let app = &app;
+        async move {
+            let request = Request::get(uri).body(Body::empty()).unwrap();
+            let response = app.send(request).await;
+            let cache_status = response
+                .headers
+                .get("cache-status")
+                .map(|value| value.to_str().unwrap().to_string());
+            (response.status, cache_status, response.json())
+        }
+    };
+
+    let (status, cache_status, bundle) = read("/api/public/pages/almanac").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(cache_status.as_deref(), Some("miss"));
+    assert_eq!(bundle["page"]["title"], "Almanac");
+    // Other tests share the process-wide caches and may clear them at any
+    // time, so a hit is only expected within a few tries
+    let mut hit = false;
+    for _ in 0..3 {
+        let (_, cache_status, cached) = read("/api/public/pages/almanac").await;
+        assert_eq!(cached, bundle);
+        if cache_status.as_deref() == Some("hit") {
+            hit = true;
+            break;
+        }
+    }
+    assert!(hit, "the page bundle was never served from the cache");
+    let (_, cache_status, _) = read("/api/public/navigation").await;
+    assert!(matches!(cache_status.as_deref(), Some("hit" | "miss")));
+
+    // Page and post writes are visible on the next read
+    let (status, _) = write(
+        Method::PUT,
+        format!("/api/pages/{}", page.id),
+        serde_json::json!({ "title": "Almanac 2026", "nav_label": "Year" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, _, bundle) = read("/api/public/pages/almanac").await;
+    assert_eq!(bundle["page"]["title"], "Almanac 2026");
+    let (_, _, navigation) = read("/api/public/navigation").await;
+    let labels: Vec<_> = navigation["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|item| item["slug"] == "almanac")
+        .map(|item| item["label"].clone())
+        .collect();
+    assert_eq!(labels, ["Year"]);
+
+    let (status, _) = write(
+        Method::POST,
+        format!("/api/pages/{}/posts", page.id),
+        serde_json::json!({
+            "title": "January",
+            "slug": "january",
+            "content_markdown": "Cold",
+            "is_published": true,
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, _, bundle) = read("/api/public/pages/almanac").await;
+    assert_eq!(bundle["posts_total"], 1);
+    assert_eq!(bundle["posts"][0]["slug"], "january");
+
+    // So are site content writes
+    let (_, cache_status, _) = read("/api/content").await;
+    assert!(cache_status.is_some());
+    let (status, _) = write(
+        Method::PUT,
+        "/api/content/cta_section".to_string(),
+        serde_json::json!({ "content": { "title": "Fresh" } }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, _, content) = read("/api/content").await;
+    let cta: Vec<_> = content["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|item| item["section"] == "cta_section")
+        .map(|item| item["content"]["title"].clone())
+        .collect();
+    assert_eq!(cta, ["Fresh"]);
+}
+
+#[tokio::test]
+async fn content_blobs_are_checked_against_their_schemas() {
+    let app = TestApp::new().await;
+    let page = CreateSitePageRequest {
+        title: "Gazette".to_string(),
+        hero: serde_json::json!({ "title": "Welcome" }),
+        ..page_request("gazette")
+    };
+    let page = seed_page(&app.pool, page).await;
+    let send = |method: Method, uri: String, body: Option<serde_json::Value>| {
+        app.send_as("admin", method, uri, body)
+    };
+
+    // The shipped content of every section passes its own schema
+    let (_, listed) = send(Method::GET, "/api/content".to_string(), None).await;
+    for item in listed["items"].as_array().unwrap() {
+        let section = item["section"].as_str().unwrap();
+        let (status, body) = send(
+            Method::PUT,
+            format!("/api/content/{section}?dry_run=true"),
+            Some(serde_json::json!({ "content": item["content"] })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::NO_CONTENT, "{section}: {body}");
+    }
+
+    // A rejection names the offending fields; a dry run saves nothing
+    let header = serde_json::json!({
+        "content": { "brand": {}, "navItems": [{ "id": "1", "label": "Blog" }, "home"] }
+    });
+    let (status, body) = send(
+        Method::PUT,
+        "/api/content/header?dry_run=true".to_string(),
+        Some(header),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let message = body["error"].as_str().unwrap();
+    assert!(message.contains("/navItems/0: "), "{message}");
+    assert!(message.contains("/navItems/1: "), "{message}");
+
+    let (status, _) = send(
+        Method::PUT,
+        "/api/content/login?dry_run=true".to_string(),
+        Some(serde_json::json!({ "content": { "title": "Changed" } })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (_, login) = send(Method::GET, "/api/content/login".to_string(), None).await;
+    assert_ne!(login["content"]["title"], "Changed");
+
+    let (status, body) = send(
+        Method::PUT,
+        format!("/api/pages/{}?dry_run=true", page.id),
+        Some(serde_json::json!({ "hero": { "title": 7 }, "layout": { "postsSection": [] } })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(
+        body["error"].as_str().unwrap().contains("/title: "),
+        "{body}"
+    );
+
+    let (status, _) = send(
+        Method::PUT,
+        format!("/api/pages/{}?dry_run=true", page.id),
+        Some(serde_json::json!({ "title": "Renamed", "hero": { "title": "Hi" } })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, body) = send(
+        Method::PATCH,
+        format!("/api/pages/{}?dry_run=true", page.id),
+        Some(serde_json::json!([
+            { "op": "replace", "path": "/layout", "value": { "aboutSection": "About" } }
+        ])),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(
+        body["error"].as_str().unwrap().contains("/aboutSection: "),
+        "{body}"
+    );
+
+    let (_, stored) = send(Method::GET, format!("/api/pages/{}", page.id), None).await;
+    assert_eq!(stored["title"], "Gazette");
+    assert_eq!(stored["hero"], serde_json::json!({ "title": "Welcome" }));
+}
+
+#[tokio::test]
+async fn content_bundles_can_be_exported_and_imported() {
+    let source = TestApp::new().await;
+    let target = TestApp::new().await;
+    let page = CreateSitePageRequest {
+        title: "Ledger".to_string(),
+        hero: serde_json::json!({ "title": "Ledger" }),
+        ..page_request("ledger")
+    };
+    seed_page(&source.pool, page).await;
+
+    let (status, bundle) = source
+        .send_as(
+            "admin",
+            Method::GET,
+            "/api/admin/content/export?sections=hero,footer",
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let sections: Vec<_> = bundle["site_content"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["section"].clone())
+        .collect();
+    assert_eq!(sections, ["footer", "hero"]);
+    assert_eq!(bundle["pages"], serde_json::json!([]));
+
+    let (status, mut bundle) = source
+        .send_as(
+            "admin",
+            Method::GET,
+            "/api/admin/content/export?sections=hero&include_pages=true",
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(bundle["pages"][0]["slug"], "ledger");
+    bundle["site_content"][0]["content"]["title"] = "Imported".into();
+
+    // The target already has a hero section, so only the page is new
+    let import = |mode: &str, bundle: &serde_json::Value| {
+        target.send_as(
+            "admin",
+            Method::POST,
+            format!("/api/admin/content/import?mode={mode}"),
+            Some(bundle.clone()),
+        )
+    };
+    let (status, report) = import("skip_existing", &bundle).await;
+    assert_eq!(status, StatusCode::OK, "{report}");
+    assert_eq!(
+        (report["created"].clone(), report["skipped"].clone()),
+        (1.into(), 1.into())
+    );
+    assert_eq!(report["results"][0]["kind"], "site_content");
+    assert_eq!(report["results"][0]["outcome"], "skipped");
+    let (_, hero) = target.get("/api/content/hero").await;
+    assert_ne!(hero["content"]["title"], "Imported");
+
+    let (status, report) = import("overwrite", &bundle).await;
+    assert_eq!(status, StatusCode::OK, "{report}");
+    assert_eq!(report["overwritten"], 2);
+    let (_, hero) = target.get("/api/content/hero").await;
+    assert_eq!(hero["content"]["title"], "Imported");
+
+    // One bad item rejects the whole bundle
+    let mut broken = bundle.clone();
+    broken["pages"][0]["id"] = "fresh-page".into();
+    broken["pages"][0]["slug"] = "fresh".into();
+    broken["posts"] = serde_json::json!([{
+        "id": "orphan", "page_id": "missing", "title": "Orphan", "slug": "orphan",
+        "excerpt": "", "content_markdown": "Body", "is_published": true,
+        "published_at": null, "order_index": 0
+    }]);
+    let (status, _) = import("overwrite", &broken).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = target
+        .send_as("admin", Method::GET, "/api/pages/fresh-page", None)
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let mut unknown = bundle.clone();
+    unknown["site_content"][0]["section"] = "not_registered".into();
+    let (status, _) = import("overwrite", &unknown).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let oversized = serde_json::json!({ "padding": "a".repeat(6 * 1024 * 1024) });
+    let (status, _) = import("overwrite", &oversized).await;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+}
@@ -0,0 +1,160 @@
+use super::*;
+
+#[tokio::test]
+async fn pages_can_be_duplicated_with_their_posts() {
+    let app = TestApp::new().await;
+    let page = seed_page(
+        &app.pool,
+        CreateSitePageRequest {
+            description: Some("Start here".to_string()),
+            hero: serde_json::json!({ "title": "Welcome" }),
+            layout: serde_json::json!({ "blocks": ["posts"] }),
+            ..page_request("landing")
+        },
+    )
+    .await;
+    let post = CreateSitePostRequest {
+        tags: vec!["intro".to_string()],
+        ..post_request("hello")
+    };
+    let post = seed_post(&app.pool, &page.id, post).await;
+    let duplicate = |uri: String| app.send_as("admin", Method::POST, uri, None);
+
+    let (status, copy) = duplicate(format!(
+        "/api/pages/{}/duplicate?include_posts=true",
+        page.id
+    ))
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_ne!(copy["id"], page.id.as_str());
+    assert_eq!(copy["slug"], "landing-copy");
+    assert_eq!(copy["is_published"], false);
+    assert_eq!(copy["hero"], serde_json::json!({ "title": "Welcome" }));
+    assert_eq!(copy["layout"], serde_json::json!({ "blocks": ["posts"] }));
+
+    let copied_posts = crate::repositories::posts::list_site_posts_for_page(
+        &app.pool,
+        copy["id"].as_str().unwrap(),
+    )
+    .await
+    .expect("list copied posts");
+    assert_eq!(copied_posts.len(), 1);
+    assert_ne!(copied_posts[0].id, post.id);
+    assert_eq!(copied_posts[0].slug, "hello");
+    assert_eq!(copied_posts[0].tags, ["intro"]);
+
+    let (status, second) = duplicate(format!("/api/pages/{}/duplicate", page.id)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(second["slug"], "landing-copy-2");
+    let second_posts = crate::repositories::posts::list_site_posts_for_page(
+        &app.pool,
+        second["id"].as_str().unwrap(),
+    )
+    .await
+    .expect("list posts");
+    assert!(second_posts.is_empty());
+
+    let (status, _) = duplicate("/api/pages/missing/duplicate".to_string()).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn deleted_pages_and_posts_go_to_the_trash_until_restored() {
+    let app = TestApp::new().await;
+    let page = seed_page(
+        &app.pool,
+        CreateSitePageRequest {
+            title: "Journal".to_string(),
+            show_in_nav: true,
+            ..page_request("logbook")
+        },
+    )
+    .await;
+    let mut post_ids = Vec::new();
+    for slug in ["kept", "binned"] {
+        post_ids.push(seed_post(&app.pool, &page.id, post_request(slug)).await.id);
+    }
+    let send = |method: Method, uri: String| app.send_as("admin", method, uri, None);
+    let public_slugs = || async {
+        let (_, page) = app.get("/api/public/pages/logbook").await;
+        page["posts"]
+            .as_array()
+            .map(|posts| {
+                let mut slugs: Vec<_> = posts
+                    .iter()
+                    .map(|post| post["slug"].as_str().unwrap().to_string())
+                    .collect();
+                slugs.sort();
+                slugs
+            })
+            .unwrap_or_default()
+    };
+    assert_eq!(public_slugs().await, ["binned", "kept"]);
+
+    // A trashed post disappears from the page and its own address
+    let (status, _) = send(Method::DELETE, format!("/api/posts/{}", post_ids[1])).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    assert_eq!(public_slugs().await, ["kept"]);
+    let (status, _) = app.get("/api/public/pages/logbook/posts/binned").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = send(Method::GET, format!("/api/posts/{}", post_ids[1])).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // Trashing the page hides it along with its remaining post
+    let (status, _) = send(Method::DELETE, format!("/api/pages/{}", page.id)).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = app.get("/api/public/pages/logbook").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (_, navigation) = app.get("/api/public/navigation").await;
+    assert_eq!(navigation["items"], serde_json::json!([]));
+
+    let (_, trash) = send(Method::GET, "/api/admin/trash".to_string()).await;
+    assert_eq!(trash["pages"][0]["id"], page.id.as_str());
+    assert_eq!(trash["pages"][0]["post_count"], 2);
+    assert_eq!(trash["posts"].as_array().unwrap().len(), 1);
+    assert_eq!(trash["posts"][0]["id"], post_ids[1].as_str());
+    assert_eq!(trash["posts"][0]["page_title"], "Journal");
+
+    // Removing it for good needs sudo mode
+    let (status, body) = send(
+        Method::DELETE,
+        format!("/api/pages/{}?permanent=true", page.id),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["code"], "sudo_required");
+
+    let restore_page = format!("/api/pages/{}/restore", page.id);
+    let (status, _) = send(Method::POST, restore_page.clone()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(public_slugs().await, ["kept"]);
+    let (status, _) = send(Method::POST, restore_page).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = send(Method::POST, format!("/api/posts/{}/restore", post_ids[1])).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(public_slugs().await, ["binned", "kept"]);
+
+    // Trash older than TRASH_RETENTION_DAYS is deleted for good, into the
+    // deletion log
+    let (status, _) = send(Method::DELETE, format!("/api/posts/{}", post_ids[1])).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    sqlx::query("UPDATE site_posts SET deleted_at = datetime('now', '-400 days') WHERE id = ?")
+        .bind(&post_ids[1])
+        .execute(&app.pool)
+        .await
+        .unwrap();
+    let report = crate::maintenance::prune(&app.pool).await;
+    assert_eq!(report.trashed_posts, 1);
+    assert_eq!(report.trashed_pages, 0);
+    assert!(
+        !crate::repositories::posts::check_post_id_taken(&app.pool, &post_ids[1])
+            .await
+            .unwrap()
+    );
+    let logged: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM deletion_log WHERE entity_id = ?")
+        .bind(&post_ids[1])
+        .fetch_one(&app.pool)
+        .await
+        .unwrap();
+    assert_eq!(logged, 1);
+}
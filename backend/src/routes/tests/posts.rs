@@ -0,0 +1,513 @@
+use super::*;
+
+#[tokio::test]
+async fn preview_links_open_only_their_own_draft_until_revoked() {
+    let app = TestApp::new().await;
+    let journal = seed_page(&app.pool, page_request("journal")).await;
+    let hidden = seed_page(
+        &app.pool,
+        CreateSitePageRequest {
+            is_published: false,
+            ..page_request("hidden")
+        },
+    )
+    .await;
+    let mut post_ids = Vec::new();
+    for (page_id, slug, is_published) in [
+        (&journal.id, "draft-one", false),
+        (&journal.id, "draft-two", false),
+        (&hidden.id, "hidden-draft", false),
+        (&hidden.id, "hidden-live", true),
+    ] {
+        let post = CreateSitePostRequest {
+            is_published,
+            ..post_request(slug)
+        };
+        post_ids.push(seed_post(&app.pool, page_id, post).await.id);
+    }
+
+    let (status, _) = app.get("/api/public/pages/journal/posts/draft-one").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let preview_token = format!("/api/admin/posts/{}/preview-token", post_ids[0]);
+    let (status, issued) = app
+        .send_as("editor", Method::POST, &preview_token, None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let post_token = issued["token"].as_str().unwrap().to_string();
+    assert!(issued["expires_at"].as_str().is_some());
+
+    let request = app
+        .request(
+            None,
+            Method::GET,
+            &format!("/api/public/pages/journal/posts/draft-one?preview={post_token}"),
+        )
+        .await;
+    let response = app.send(request.body(Body::empty()).unwrap()).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.json()["post"]["slug"], "draft-one");
+    assert_eq!(response.headers["x-robots-tag"], "noindex, nofollow");
+
+    // The token opens nothing but its own post
+    for uri in [
+        format!("/api/public/pages/journal/posts/draft-two?preview={post_token}"),
+        format!("/api/public/pages/hidden?preview={post_token}"),
+        format!("/api/public/pages/hidden/posts/hidden-draft?preview={post_token}"),
+    ] {
+        let (status, _) = app.get(&uri).await;
+        assert_eq!(status, StatusCode::NOT_FOUND, "{uri}");
+    }
+
+    let page_preview_token = format!("/api/admin/pages/{}/preview-token", hidden.id);
+    let (status, _) = app
+        .send_as("editor", Method::POST, &page_preview_token, None)
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, issued) = app
+        .send_as("admin", Method::POST, &page_preview_token, None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let page_token = issued["token"].as_str().unwrap();
+    let (status, body) = app
+        .get(format!("/api/public/pages/hidden?preview={page_token}"))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let slugs: Vec<&str> = body["posts"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|post| post["slug"].as_str().unwrap())
+        .collect();
+    assert_eq!(slugs, ["hidden-live"]);
+
+    // Revoking invalidates links issued before; a new one works again
+    let (status, _) = app
+        .send_as("editor", Method::DELETE, &preview_token, None)
+        .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = app
+        .get(format!(
+            "/api/public/pages/journal/posts/draft-one?preview={post_token}"
+        ))
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (_, issued) = app
+        .send_as("editor", Method::POST, &preview_token, None)
+        .await;
+    let (status, _) = app
+        .get(format!(
+            "/api/public/pages/journal/posts/draft-one?preview={}",
+            issued["token"].as_str().unwrap()
+        ))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = app
+        .send_as(
+            "editor",
+            Method::POST,
+            "/api/admin/posts/missing/preview-token",
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn posts_can_be_tagged_and_listed_by_tag() {
+    let app = TestApp::new().await;
+    let page = seed_page(&app.pool, page_request("journal")).await;
+    let create = |slug: &str, tags: serde_json::Value, is_published: bool| {
+        app.send_as(
+            "editor",
+            Method::POST,
+            format!("/api/pages/{}/posts", page.id),
+            Some(serde_json::json!({
+                "title": slug,
+                "slug": slug,
+                "content_markdown": "Body",
+                "is_published": is_published,
+                "tags": tags,
+            })),
+        )
+    };
+
+    let (status, _) = create("twice", serde_json::json!(["Linux", " linux "]), true).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, kernel) =
+        create("kernel", serde_json::json!([" Linux", "Kernel", ""]), true).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(kernel["tags"], serde_json::json!(["kernel", "linux"]));
+    let (status, _) = create("shells", serde_json::json!(["linux", "shell"]), true).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = create("draft", serde_json::json!(["linux", "secret"]), false).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_, listed) = app.get("/api/public/pages/journal/posts?tag=LINUX").await;
+    let slugs = |listed: &serde_json::Value| -> Vec<String> {
+        listed["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|post| post["slug"].as_str().unwrap().to_string())
+            .collect()
+    };
+    let mut linux = slugs(&listed);
+    linux.sort();
+    assert_eq!(linux, ["kernel", "shells"]);
+
+    // Retagging replaces the tags
+    let (status, retagged) = app
+        .send_as(
+            "editor",
+            Method::PUT,
+            format!("/api/posts/{}", kernel["id"].as_str().unwrap()),
+            Some(serde_json::json!({ "tags": ["Drivers"] })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(retagged["tags"], serde_json::json!(["drivers"]));
+    let (_, listed) = app.get("/api/public/pages/journal/posts?tag=linux").await;
+    assert_eq!(slugs(&listed), ["shells"]);
+
+    let (status, tags) = app.get("/api/public/tags").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        tags,
+        serde_json::json!([
+            { "tag": "drivers", "count": 1 },
+            { "tag": "linux", "count": 1 },
+            { "tag": "shell", "count": 1 },
+        ])
+    );
+
+    let (status, _) = app.get("/api/public/pages/missing/posts").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn post_revisions_can_be_listed_and_restored() {
+    let app = TestApp::new().await;
+    let page = seed_page(&app.pool, page_request("notes")).await;
+    let send = |method: Method, uri: String, body: Option<serde_json::Value>| {
+        app.send_as("editor", method, uri, body)
+    };
+
+    let (status, post) = send(
+        Method::POST,
+        format!("/api/pages/{}/posts", page.id),
+        Some(serde_json::json!({
+            "title": "First draft",
+            "slug": "essay",
+            "content_markdown": "One",
+            "is_published": false,
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let id = post["id"].as_str().unwrap().to_string();
+
+    for (title, content) in [("Second draft", "Two"), ("Final", "Three")] {
+        let (status, _) = send(
+            Method::PUT,
+            format!("/api/posts/{id}"),
+            Some(serde_json::json!({ "title": title, "content_markdown": content })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    let (status, revisions) = send(Method::GET, format!("/api/posts/{id}/revisions"), None).await;
+    assert_eq!(status, StatusCode::OK);
+    let listed: Vec<(i64, &str)> = revisions
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|rev| {
+            (
+                rev["revision"].as_i64().unwrap(),
+                rev["title"].as_str().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(listed, [(2, "Second draft"), (1, "First draft")]);
+    assert_eq!(revisions[0]["editor"], "editor");
+
+    let (status, first) = send(Method::GET, format!("/api/posts/{id}/revisions/1"), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(first["content_markdown"], "One");
+    let (status, _) = send(Method::GET, format!("/api/posts/{id}/revisions/9"), None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, restored) = send(
+        Method::POST,
+        format!("/api/posts/{id}/revisions/1/restore"),
+        Some(serde_json::json!({})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(restored["title"], "First draft");
+    assert_eq!(restored["content_markdown"], "One");
+
+    // The state the restore replaced was kept as a new revision
+    let (_, revisions) = send(Method::GET, format!("/api/posts/{id}/revisions"), None).await;
+    assert_eq!(revisions.as_array().unwrap().len(), 3);
+    assert_eq!(revisions[0]["revision"], 3);
+    assert_eq!(revisions[0]["title"], "Final");
+
+    let (status, _) = send(
+        Method::GET,
+        "/api/posts/missing/revisions".to_string(),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    // Cover and SEO fields are validated, and an empty value clears them
+    let (status, _) = send(
+        Method::PUT,
+        format!("/api/posts/{id}"),
+        Some(serde_json::json!({ "cover_image_url": "http://example.com/a.png" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, updated) = send(
+        Method::PUT,
+        format!("/api/posts/{id}"),
+        Some(serde_json::json!({
+            "cover_image_url": "/uploads/cover.webp",
+            "meta_description": " A short summary ",
+            "canonical_url": "https://elsewhere.example.com/essay",
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(updated["cover_image_url"], "/uploads/cover.webp");
+    assert_eq!(updated["meta_description"], "A short summary");
+    assert_eq!(
+        updated["canonical_url"],
+        "https://elsewhere.example.com/essay"
+    );
+    let (_, cleared) = send(
+        Method::PUT,
+        format!("/api/posts/{id}"),
+        Some(serde_json::json!({ "canonical_url": "" })),
+    )
+    .await;
+    assert!(cleared["canonical_url"].is_null());
+    assert_eq!(cleared["cover_image_url"], "/uploads/cover.webp");
+}
+
+#[tokio::test]
+async fn renamed_posts_resolve_under_their_former_slugs() {
+    let app = TestApp::new().await;
+    let page = seed_page(&app.pool, page_request("blog")).await;
+    let create = |slug: &str| {
+        app.send_as(
+            "editor",
+            Method::POST,
+            format!("/api/pages/{}/posts", page.id),
+            Some(serde_json::json!({
+                "title": slug,
+                "slug": slug,
+                "content_markdown": "Body",
+                "is_published": true,
+            })),
+        )
+    };
+    let read = |slug: &str| app.get(format!("/api/public/pages/blog/posts/{slug}"));
+
+    let (_, post) = create("first-name").await;
+    let id = post["id"].as_str().unwrap().to_string();
+    for slug in ["second-name", "third-name"] {
+        let (status, _) = app
+            .send_as(
+                "editor",
+                Method::PUT,
+                format!("/api/posts/{id}"),
+                Some(serde_json::json!({ "slug": slug })),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    for former in ["first-name", "second-name"] {
+        let (status, detail) = read(former).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(detail["post"]["id"], id.as_str());
+        assert_eq!(detail["canonical_slug"], "third-name");
+    }
+    let (status, detail) = read("third-name").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(detail.get("canonical_slug").is_none());
+
+    // A new post taking a former slug is no longer shadowed by the history
+    let (status, newcomer) = create("first-name").await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, detail) = read("first-name").await;
+    assert_eq!(detail["post"]["id"], newcomer["id"]);
+    assert!(detail.get("canonical_slug").is_none());
+
+    let (status, _) = read("never-existed").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn public_post_listing_is_paginated_and_sortable() {
+    let app = TestApp::new().await;
+    let page = seed_page(&app.pool, page_request("journal")).await;
+    for n in 1..=13 {
+        let post = CreateSitePostRequest {
+            title: format!("Entry {n:02}"),
+            content_markdown: "Three little words".to_string(),
+            // The last one stays a draft
+            is_published: n < 13,
+            published_at: Some(format!("2026-01-{n:02} 08:00:00")),
+            order_index: Some(n),
+            ..post_request(&format!("entry-{n:02}"))
+        };
+        seed_post(&app.pool, &page.id, post).await;
+    }
+    let slugs = |posts: &serde_json::Value| -> Vec<String> {
+        posts
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|post| post["slug"].as_str().unwrap().to_string())
+            .collect()
+    };
+
+    // The page bundle embeds only the first page of summaries
+    let (status, bundle) = app.get("/api/public/pages/journal").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(bundle["posts_total"], 12);
+    assert_eq!(bundle["posts"].as_array().unwrap().len(), 10);
+    assert_eq!(bundle["posts"][0]["slug"], "entry-01");
+    assert_eq!(bundle["posts"][0]["word_count"], 3);
+    assert!(bundle["posts"][0].get("content_markdown").is_none());
+
+    let (status, listed) = app
+        .get("/api/public/pages/journal/posts?limit=5&offset=10")
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(slugs(&listed["items"]), ["entry-11", "entry-12"]);
+    assert_eq!(listed["total"], 12);
+    assert_eq!(listed["hasMore"], false);
+
+    let (_, listed) = app
+        .get("/api/public/pages/journal/posts?limit=3&sort=published_at:desc")
+        .await;
+    assert_eq!(
+        slugs(&listed["items"]),
+        ["entry-12", "entry-11", "entry-10"]
+    );
+    assert_eq!(listed["hasMore"], true);
+
+    let (status, _) = app
+        .get("/api/public/pages/journal/posts?sort=content:asc")
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn archive_groups_published_posts_by_month() {
+    let app = TestApp::new().await;
+    let page = seed_page(&app.pool, page_request("diary")).await;
+    for (slug, published_at, is_published) in [
+        ("new-year", Some("2025-01-01 09:00:00"), true),
+        ("winter", Some("2025-01-20T18:30:00Z"), true),
+        ("spring", Some("2025-03-10 12:00:00"), true),
+        ("undated", None, true),
+        ("unfinished", Some("2025-03-11 12:00:00"), false),
+    ] {
+        let post = CreateSitePostRequest {
+            is_published,
+            published_at: published_at.map(str::to_string),
+            ..post_request(slug)
+        };
+        seed_post(&app.pool, &page.id, post).await;
+    }
+    // Without a publication date the creation date counts
+    sqlx::query("UPDATE site_posts SET created_at = '2024-12-24 20:00:00' WHERE slug = 'undated'")
+        .execute(&app.pool)
+        .await
+        .unwrap();
+
+    let (status, archive) = app.get("/api/public/pages/diary/archive").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        archive,
+        serde_json::json!([
+            { "year": 2025, "month": 3, "count": 1 },
+            { "year": 2025, "month": 1, "count": 2 },
+            { "year": 2024, "month": 12, "count": 1 },
+        ])
+    );
+
+    let (status, january) = app
+        .get("/api/public/pages/diary/archive/2025/1?limit=1")
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(january["total"], 2);
+    assert_eq!(january["hasMore"], true);
+    assert_eq!(january["items"][0]["slug"], "new-year");
+    let (_, january) = app
+        .get("/api/public/pages/diary/archive/2025/01?offset=1")
+        .await;
+    assert_eq!(january["items"][0]["slug"], "winter");
+
+    let (_, december) = app.get("/api/public/pages/diary/archive/2024/12").await;
+    assert_eq!(december["items"][0]["slug"], "undated");
+    let (_, empty) = app.get("/api/public/pages/diary/archive/2025/2").await;
+    assert_eq!(empty["total"], 0);
+
+    let (status, _) = app.get("/api/public/pages/diary/archive/2025/13").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = app.get("/api/public/pages/missing/archive").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn post_detail_links_to_neighboring_published_posts() {
+    let app = TestApp::new().await;
+    let page = seed_page(&app.pool, page_request("series")).await;
+    // Same order_index throughout, so publication dates decide; the draft
+    // sits between part one and part two
+    for (slug, day, is_published) in [
+        ("part-two", 3, true),
+        ("part-one", 1, true),
+        ("draft", 2, false),
+        ("part-three", 4, true),
+    ] {
+        let post = CreateSitePostRequest {
+            excerpt: Some(format!("About {slug}")),
+            is_published,
+            published_at: Some(format!("2026-02-0{day} 08:00:00")),
+            order_index: Some(1),
+            cover_image_url: Some(format!("/uploads/{slug}.webp")),
+            ..post_request(slug)
+        };
+        seed_post(&app.pool, &page.id, post).await;
+    }
+
+    let (_, first) = app.get("/api/public/pages/series/posts/part-one").await;
+    assert_eq!(first["post"]["cover_image_url"], "/uploads/part-one.webp");
+    assert!(first["previous"].is_null());
+    assert_eq!(
+        first["next"],
+        serde_json::json!({
+            "slug": "part-two",
+            "title": "part-two",
+            "excerpt": "About part-two",
+        })
+    );
+
+    let (_, middle) = app.get("/api/public/pages/series/posts/part-two").await;
+    assert_eq!(middle["previous"]["slug"], "part-one");
+    assert_eq!(middle["next"]["slug"], "part-three");
+
+    let (_, last) = app.get("/api/public/pages/series/posts/part-three").await;
+    assert_eq!(last["previous"]["slug"], "part-two");
+    assert!(last["next"].is_null());
+}
@@ -0,0 +1,256 @@
+use super::*;
+use crate::models::{TUTORIAL_STATUS_DRAFT, TUTORIAL_STATUS_PUBLISHED};
+
+#[tokio::test]
+async fn search_results_are_paginated_with_a_total() {
+    let app = TestApp::new().await;
+    for (id, topic) in [
+        ("quux-a", "Linux"),
+        ("quux-b", "Linux"),
+        ("quux-c", "Windows"),
+    ] {
+        let title = format!("Quuxify {id}");
+        seed_tutorial(&app.pool, id, &title, &[topic], TUTORIAL_STATUS_PUBLISHED).await;
+    }
+    let search = |uri: &'static str| {
+        let app = &app;
+        async move {
+            let (status, body) = app.get(uri).await;
+            assert_eq!(status, StatusCode::OK);
+            body
+        }
+    };
+
+    let first = search("/api/search/tutorials?q=quuxify&limit=2").await;
+    assert_eq!(first["items"].as_array().unwrap().len(), 2);
+    assert_eq!(first["total"], 3);
+    assert_eq!(first["hasMore"], true);
+
+    let second = search("/api/search/tutorials?q=quuxify&limit=2&offset=2").await;
+    assert_eq!(second["items"].as_array().unwrap().len(), 1);
+    assert_eq!(second["offset"], 2);
+    assert_eq!(second["hasMore"], false);
+
+    let beyond = search("/api/search/tutorials?q=quuxify&limit=2&offset=10").await;
+    assert_eq!(beyond["items"], serde_json::json!([]));
+    assert_eq!(beyond["total"], 3);
+
+    // The count honors the topic filter, too
+    let filtered = search("/api/search/tutorials?q=quuxify&topic=linux&offset=-5").await;
+    assert_eq!(filtered["items"].as_array().unwrap().len(), 2);
+    assert_eq!(filtered["total"], 2);
+    assert_eq!(filtered["offset"], 0);
+}
+
+#[tokio::test]
+async fn search_can_be_limited_to_a_creation_range_and_sorted() {
+    let app = TestApp::new().await;
+    for (id, topic, created_at, updated_at) in [
+        (
+            "blorp-old",
+            "Linux",
+            "2023-01-10 08:00:00",
+            "2024-06-01 00:00:00",
+        ),
+        (
+            "blorp-mid",
+            "Linux",
+            "2023-06-15T12:00:00+02:00",
+            "2023-06-15 10:00:00",
+        ),
+        (
+            "blorp-new",
+            "Windows",
+            "2024-02-01 09:30:00",
+            "2024-02-01 09:30:00",
+        ),
+    ] {
+        let title = format!("Blorp {id}");
+        seed_tutorial(&app.pool, id, &title, &[topic], TUTORIAL_STATUS_PUBLISHED).await;
+        sqlx::query("UPDATE tutorials SET created_at = ?, updated_at = ? WHERE id = ?")
+            .bind(created_at)
+            .bind(updated_at)
+            .bind(id)
+            .execute(&app.pool)
+            .await
+            .unwrap();
+    }
+    let ids = |page: &serde_json::Value| -> Vec<String> {
+        page["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| item["id"].as_str().unwrap().to_string())
+            .collect()
+    };
+
+    let (_, page) = app.get("/api/search/tutorials?q=blorp&sort=newest").await;
+    assert_eq!(ids(&page), ["blorp-new", "blorp-mid", "blorp-old"]);
+    let (_, page) = app.get("/api/search/tutorials?q=blorp&sort=updated").await;
+    assert_eq!(ids(&page), ["blorp-old", "blorp-new", "blorp-mid"]);
+
+    // Offsets are honored: 12:00+02:00 is 10:00 UTC
+    let (_, page) = app
+        .get("/api/search/tutorials?q=blorp&sort=newest&created_after=2023-06-15T10:00:00Z")
+        .await;
+    assert_eq!(ids(&page), ["blorp-new", "blorp-mid"]);
+    assert_eq!(page["total"], 2);
+
+    let (_, page) = app
+        .get("/api/search/tutorials?q=blorp&topic=linux&created_before=2023-06-15T09:59:59Z")
+        .await;
+    assert_eq!(ids(&page), ["blorp-old"]);
+    assert_eq!(page["total"], 1);
+
+    let (status, body) = app
+        .get("/api/search/tutorials?q=blorp&created_after=2023-06-15")
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["error"].as_str().unwrap().contains("created_after"));
+    let (status, _) = app.get("/api/search/tutorials?q=blorp&sort=oldest").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn visitor_searches_are_logged_for_the_search_analytics() {
+    let app = TestApp::new().await;
+    seed_tutorial(
+        &app.pool,
+        "zorblax-basics",
+        "Zorblax basics",
+        &["Linux"],
+        TUTORIAL_STATUS_PUBLISHED,
+    )
+    .await;
+    sqlx::query(concat!(
+        "INSERT INTO search_log (query, result_count, ip_hash, searched_at) ",
+        "VALUES ('ancient', 0, 'old', datetime('now', '-10 days'))"
+    ))
+    .execute(&app.pool)
+    .await
+    .unwrap();
+
+    for (uri, client, role) in [
+        ("/api/search/tutorials?q=zorblax", 1, None),
+        ("/api/search/tutorials?q=%20%20ZORBLAX%20%20", 2, None),
+        ("/api/search/tutorials?q=zorblax&offset=20", 1, None),
+        ("/api/search/tutorials?q=zorblax", 3, Some("admin")),
+        ("/api/search/tutorials?q=quuxnothing", 1, None),
+    ] {
+        let request = app.request(role, Method::GET, uri).await;
+        let request = from_client(request.body(Body::empty()).unwrap(), client);
+        let response = app.send(request).await;
+        assert_eq!(response.status, StatusCode::OK, "{uri}");
+    }
+
+    // Searches are logged in the background; the later page and the
+    // editor's search are not logged at all.
+    let mut logged = 0;
+    for _ in 0..100 {
+        logged = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM search_log")
+            .fetch_one(&app.pool)
+            .await
+            .unwrap();
+        if logged >= 4 {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    assert_eq!(logged, 4);
+
+    let analytics = |uri: &'static str| app.send_as("admin", Method::GET, uri, None);
+
+    let (status, report) = analytics("/api/admin/search/analytics?days=7").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(report["total_searches"], 3);
+    assert_eq!(report["top_queries"][0]["query"], "zorblax");
+    assert_eq!(report["top_queries"][0]["searches"], 2);
+    assert_eq!(report["top_queries"][0]["visitors"], 2);
+    let zero: Vec<&str> = report["zero_result_queries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| entry["query"].as_str().unwrap())
+        .collect();
+    assert_eq!(zero, ["quuxnothing"]);
+    assert_eq!(report["daily"].as_array().unwrap().len(), 1);
+    assert_eq!(report["daily"][0]["zero_results"], 1);
+
+    let (_, report) = analytics("/api/admin/search/analytics").await;
+    assert_eq!(report["days"], 30);
+    assert_eq!(report["total_searches"], 4);
+    let (status, _) = analytics("/api/admin/search/analytics?days=0").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    assert_eq!(
+        crate::repositories::search_log::purge_older_than(&app.pool, 7)
+            .await
+            .unwrap(),
+        1
+    );
+}
+
+#[tokio::test]
+async fn topics_are_listed_with_counts_and_their_tutorials() {
+    let app = TestApp::new().await;
+    for (id, topics, status) in [
+        ("zork-1", &["Zorkology"][..], TUTORIAL_STATUS_PUBLISHED),
+        ("zork-2", &["Zorkology", "Grues"], TUTORIAL_STATUS_PUBLISHED),
+        ("zork-3", &["Zorkology", "Grues"], TUTORIAL_STATUS_DRAFT),
+    ] {
+        seed_tutorial(&app.pool, id, id, topics, status).await;
+    }
+
+    // Drafts are not counted
+    let (_, counts) = app.get("/api/search/topics?with_counts=true").await;
+    let count_of = |topic: &str| {
+        counts
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|entry| entry["topic"] == topic)
+            .map(|entry| entry["count"].clone())
+    };
+    assert_eq!(count_of("Zorkology"), Some(serde_json::json!(2)));
+    assert_eq!(count_of("Grues"), Some(serde_json::json!(1)));
+    let (_, names) = app.get("/api/search/topics").await;
+    assert!(names
+        .as_array()
+        .unwrap()
+        .contains(&serde_json::json!("Grues")));
+
+    let (status, page) = app.get("/api/topics/zorkology/tutorials?limit=1").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(page["total"], 2);
+    assert_eq!(page["items"][0]["id"], "zork-1");
+    assert!(page["items"][0].get("content").is_none());
+
+    let (status, _) = app.get("/api/topics/nonexistent-topic/tutorials").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn search_suggestions_match_title_and_topic_prefixes() {
+    let app = TestApp::new().await;
+    seed_tutorial(
+        &app.pool,
+        "tour",
+        "Guided quasar tour",
+        &["Quartz"],
+        TUTORIAL_STATUS_PUBLISHED,
+    )
+    .await;
+
+    let (status, _) = app.get("/api/search/suggest?q=q").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, suggestions) = app.get("/api/search/suggest?q=qua").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        suggestions,
+        serde_json::json!([
+            { "type": "topic", "label": "Quartz" },
+            { "type": "tutorial", "label": "Guided quasar tour", "id": "tour" },
+        ])
+    );
+}
@@ -0,0 +1,879 @@
+use super::*;
+use crate::models::{TUTORIAL_STATUS_DRAFT, TUTORIAL_STATUS_PUBLISHED};
+
+#[tokio::test]
+async fn draft_tutorials_stay_hidden_until_published() {
+    let app = TestApp::new().await;
+    seed_tutorial(
+        &app.pool,
+        "unreleased",
+        "Unreleased walkthrough",
+        &["Linux"],
+        TUTORIAL_STATUS_DRAFT,
+    )
+    .await;
+    let listed = |list: &serde_json::Value| {
+        list.as_array()
+            .unwrap()
+            .iter()
+            .any(|tutorial| tutorial["id"] == "unreleased")
+    };
+
+    let (status, _) = app.get("/api/tutorials/unreleased").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (_, list) = app.get("/api/tutorials").await;
+    assert!(!listed(&list));
+    let (_, hits) = app.get("/api/search/tutorials?q=unreleased").await;
+    assert!(!listed(&hits["items"]));
+
+    let (_, list) = app
+        .send_as("editor", Method::GET, "/api/tutorials", None)
+        .await;
+    assert!(listed(&list));
+    let (_, draft) = app
+        .send_as("editor", Method::GET, "/api/tutorials/unreleased", None)
+        .await;
+    assert_eq!(draft["status"], "draft");
+    assert!(draft["published_at"].is_null());
+
+    let (status, published) = app
+        .send_as(
+            "editor",
+            Method::POST,
+            "/api/tutorials/unreleased/publish",
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(published["status"], "published");
+    assert!(published["published_at"].is_string());
+
+    let (status, _) = app.get("/api/tutorials/unreleased").await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, hits) = app.get("/api/search/tutorials?q=unreleased").await;
+    assert!(listed(&hits["items"]));
+}
+
+#[tokio::test]
+async fn deleted_tutorials_go_to_the_trash_until_restored() {
+    let app = TestApp::new().await;
+    seed_tutorial(
+        &app.pool,
+        "binned",
+        "Binned walkthrough",
+        &["Linux"],
+        TUTORIAL_STATUS_PUBLISHED,
+    )
+    .await;
+    let search_hits = || async {
+        let (_, hits) = app.get("/api/search/tutorials?q=binned").await;
+        hits["items"].as_array().unwrap().len()
+    };
+    assert_eq!(search_hits().await, 1);
+
+    let (status, _) = app
+        .send_as("admin", Method::DELETE, "/api/tutorials/binned", None)
+        .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = app.get("/api/tutorials/binned").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(search_hits().await, 0);
+
+    let (_, trash) = app
+        .send_as("admin", Method::GET, "/api/admin/tutorials/trash", None)
+        .await;
+    assert_eq!(trash[0]["id"], "binned");
+    assert!(trash[0]["deleted_at"].is_string());
+
+    // Removing it for good needs sudo mode.
+    let (status, body) = app
+        .send_as(
+            "admin",
+            Method::DELETE,
+            "/api/tutorials/binned?permanent=true",
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["code"], "sudo_required");
+
+    let (status, _) = app
+        .send_as("admin", Method::POST, "/api/tutorials/binned/restore", None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = app.get("/api/tutorials/binned").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(search_hits().await, 1);
+    let (_, trash) = app
+        .send_as("admin", Method::GET, "/api/admin/tutorials/trash", None)
+        .await;
+    assert_eq!(trash.as_array().unwrap().len(), 0);
+}
+
+#[tokio::test]
+async fn tutorial_updates_keep_revisions_that_can_be_restored() {
+    let app = TestApp::new().await;
+    seed_tutorial(
+        &app.pool,
+        "versioned",
+        "First title",
+        &["Linux"],
+        TUTORIAL_STATUS_PUBLISHED,
+    )
+    .await;
+    let send = |method: Method, uri: &'static str, body: Option<serde_json::Value>| {
+        app.send_as("admin", method, uri, body)
+    };
+
+    for (title, content) in [
+        ("Second title", "Second body"),
+        ("Third title", "Third body"),
+    ] {
+        let (status, _) = send(
+            Method::PUT,
+            "/api/tutorials/versioned",
+            Some(serde_json::json!({ "title": title, "content": content })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    let (_, revisions) = send(Method::GET, "/api/tutorials/versioned/revisions", None).await;
+    let versions: Vec<_> = revisions
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|revision| revision["version"].as_i64().unwrap())
+        .collect();
+    assert_eq!(versions, [2, 1]);
+    assert_eq!(revisions[1]["editor"], "admin");
+
+    let (_, first) = send(Method::GET, "/api/tutorials/versioned/revisions/1", None).await;
+    assert_eq!(first["title"], "First title");
+    assert_eq!(first["content"], "Body");
+    assert_eq!(first["topics"], serde_json::json!(["Linux"]));
+
+    let (status, _) = send(Method::GET, "/api/tutorials/versioned/revisions/9", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, restored) = send(
+        Method::POST,
+        "/api/tutorials/versioned/revisions/1/restore",
+        Some(serde_json::Value::Null),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(restored["title"], "First title");
+    assert_eq!(restored["content"], "Body");
+    assert_eq!(restored["version"], 4);
+
+    // The version that was current before the restore is kept as well.
+    let (_, third) = send(Method::GET, "/api/tutorials/versioned/revisions/3", None).await;
+    assert_eq!(third["title"], "Third title");
+}
+
+#[tokio::test]
+async fn concurrent_tutorial_editors_cannot_overwrite_each_other() {
+    let app = TestApp::new().await;
+    seed_tutorial(
+        &app.pool,
+        "contended",
+        "Shared title",
+        &["Linux"],
+        TUTORIAL_STATUS_PUBLISHED,
+    )
+    .await;
+    let send = |method: Method, if_match: Option<&'static str>, body: serde_json::Value| {
+        let app = &app;
+        async move {
+            let mut builder = app
+                .request(Some("admin"), method, "/api/tutorials/contended")
+                .await
+                .header(header::CONTENT_TYPE, "application/json");
+            if let Some(if_match) = if_match {
+                builder = builder.header(header::IF_MATCH, if_match);
+            }
+            app.send(builder.body(Body::from(body.to_string())).unwrap())
+                .await
+        }
+    };
+
+    // Both editors loaded version 1; the first to save wins.
+    let response = send(
+        Method::PUT,
+        None,
+        serde_json::json!({ "content": "First editor", "expected_version": 1 }),
+    )
+    .await;
+    assert_eq!(response.status, StatusCode::OK);
+
+    let response = send(
+        Method::PUT,
+        None,
+        serde_json::json!({ "content": "Second editor", "expected_version": 1 }),
+    )
+    .await;
+    assert_eq!(response.status, StatusCode::CONFLICT);
+    let response = send(
+        Method::PUT,
+        Some("\"1\""),
+        serde_json::json!({ "content": "Second editor" }),
+    )
+    .await;
+    assert_eq!(response.status, StatusCode::CONFLICT);
+    let response = send(
+        Method::PATCH,
+        Some("1"),
+        serde_json::json!([{ "op": "replace", "path": "/content", "value": "Second editor" }]),
+    )
+    .await;
+    assert_eq!(response.status, StatusCode::CONFLICT);
+
+    // After reloading version 2 the second editor can save.
+    let response = send(
+        Method::PUT,
+        Some("W/\"2\""),
+        serde_json::json!({ "content": "Second editor" }),
+    )
+    .await;
+    assert_eq!(response.status, StatusCode::OK);
+    let tutorial = response.json();
+    assert_eq!(tutorial["content"], "Second editor");
+    assert_eq!(tutorial["version"], 3);
+
+    let response = send(
+        Method::PUT,
+        Some("three"),
+        serde_json::json!({ "content": "Third editor" }),
+    )
+    .await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn tutorials_can_be_reordered() {
+    let app = TestApp::new().await;
+    sqlx::query("DELETE FROM tutorials")
+        .execute(&app.pool)
+        .await
+        .expect("clear default tutorials");
+    for id in ["alpha", "bravo", "charlie"] {
+        seed_tutorial(
+            &app.pool,
+            id,
+            "Ordered walkthrough",
+            &["Linux"],
+            TUTORIAL_STATUS_PUBLISHED,
+        )
+        .await;
+    }
+    let ids = |value: &serde_json::Value| -> Vec<String> {
+        value
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|entry| entry["id"].as_str().unwrap().to_string())
+            .collect()
+    };
+    let reorder = |body: serde_json::Value| {
+        app.send_as("admin", Method::PUT, "/api/tutorials/order", Some(body))
+    };
+
+    let (status, ordering) = reorder(serde_json::json!(["charlie", "alpha"])).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(ids(&ordering), ["charlie", "alpha", "bravo"]);
+    assert_eq!(ordering[2]["order_index"], 2);
+
+    let (_, listed) = app
+        .send_as("admin", Method::GET, "/api/tutorials", None)
+        .await;
+    assert_eq!(ids(&listed), ["charlie", "alpha", "bravo"]);
+
+    for body in [
+        serde_json::json!(["alpha", "alpha"]),
+        serde_json::json!(["alpha", "missing"]),
+        serde_json::json!([]),
+    ] {
+        let (status, _) = reorder(body).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+}
+
+#[tokio::test]
+async fn tutorials_can_be_duplicated_as_drafts() {
+    let app = TestApp::new().await;
+    let topics = vec!["Linux".to_string(), "Shell".to_string()];
+    crate::repositories::tutorials::create_tutorial(
+        &app.pool,
+        "template",
+        "Boilerplate walkthrough",
+        "Start here",
+        "Reusable body",
+        "Terminal",
+        "from-blue-500 to-indigo-600",
+        &serde_json::to_string(&topics).unwrap(),
+        &topics,
+        TUTORIAL_STATUS_PUBLISHED,
+        true,
+        None,
+    )
+    .await
+    .expect("seed tutorial");
+    let duplicate = |role: &'static str, body: Option<serde_json::Value>| {
+        app.send_as(
+            role,
+            Method::POST,
+            "/api/tutorials/template/duplicate",
+            body,
+        )
+    };
+
+    let (status, copy) = duplicate("admin", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_ne!(copy["id"], "template");
+    assert_eq!(copy["title"], "Copy of Boilerplate walkthrough");
+    assert_eq!(copy["content"], "Reusable body");
+    assert_eq!(copy["topics"], serde_json::json!(["Linux", "Shell"]));
+    assert_eq!(copy["version"], 1);
+    assert_eq!(copy["status"], TUTORIAL_STATUS_DRAFT);
+
+    let copy_id = copy["id"].as_str().unwrap();
+    let topic_rows: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM tutorial_topics WHERE tutorial_id = ?")
+            .bind(copy_id)
+            .fetch_one(&app.pool)
+            .await
+            .unwrap();
+    assert_eq!(topic_rows, 2);
+    let indexed: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM tutorials_fts WHERE tutorial_id = ?")
+            .bind(copy_id)
+            .fetch_one(&app.pool)
+            .await
+            .unwrap();
+    assert_eq!(indexed, 1);
+
+    let (status, _) = duplicate("admin", Some(serde_json::json!({ "id": "template-copy" }))).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = duplicate("admin", Some(serde_json::json!({ "id": "template-copy" }))).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let (status, _) = duplicate("editor", None).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn renamed_tutorials_keep_their_comments_and_old_links() {
+    let app = TestApp::new().await;
+    seed_tutorial(
+        &app.pool,
+        "old-slug",
+        "Renamed walkthrough",
+        &["Linux"],
+        TUTORIAL_STATUS_PUBLISHED,
+    )
+    .await;
+    sqlx::query(
+        "INSERT INTO comments (id, tutorial_id, author, content) \
+         VALUES ('kept', 'old-slug', 'Reader', 'Still here')",
+    )
+    .execute(&app.pool)
+    .await
+    .expect("seed comment");
+    crate::repositories::views::increment(&app.pool, "tutorial", "old-slug", "2024-01-01")
+        .await
+        .expect("seed view");
+    let rename = |uri: &'static str, new_id: &'static str| {
+        app.send_as(
+            "admin",
+            Method::POST,
+            uri,
+            Some(serde_json::json!({ "new_id": new_id })),
+        )
+    };
+
+    let (status, renamed) = rename("/api/tutorials/old-slug/rename", "new-slug").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(renamed["id"], "new-slug");
+
+    let (_, current) = app.get("/api/tutorials/new-slug").await;
+    assert_eq!(current["id"], "new-slug");
+    assert!(current.get("canonical_id").is_none());
+
+    let (_, via_old_link) = app.get("/api/tutorials/old-slug").await;
+    assert_eq!(via_old_link["id"], "new-slug");
+    assert_eq!(via_old_link["canonical_id"], "new-slug");
+
+    let comment_owner: String =
+        sqlx::query_scalar("SELECT tutorial_id FROM comments WHERE id = 'kept'")
+            .fetch_one(&app.pool)
+            .await
+            .unwrap();
+    assert_eq!(comment_owner, "new-slug");
+    let moved_views: Option<String> = sqlx::query_scalar(
+        "SELECT entity_id FROM content_views WHERE entity_type = 'tutorial' AND day = '2024-01-01'",
+    )
+    .fetch_optional(&app.pool)
+    .await
+    .unwrap();
+    assert_eq!(moved_views.as_deref(), Some("new-slug"));
+
+    // A second rename keeps the first old link pointing at the tutorial.
+    let (status, _) = rename("/api/tutorials/new-slug/rename", "newest-slug").await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, via_old_link) = app.get("/api/tutorials/old-slug").await;
+    assert_eq!(via_old_link["canonical_id"], "newest-slug");
+
+    seed_tutorial(
+        &app.pool,
+        "taken",
+        "Another walkthrough",
+        &["Linux"],
+        TUTORIAL_STATUS_PUBLISHED,
+    )
+    .await;
+    let (status, _) = rename("/api/tutorials/newest-slug/rename", "taken").await;
+    assert_eq!(status, StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn tutorial_list_filters_by_topics() {
+    let app = TestApp::new().await;
+    sqlx::query("DELETE FROM tutorials")
+        .execute(&app.pool)
+        .await
+        .expect("clear default tutorials");
+    for (id, topics) in [
+        ("files", ["Linux", "Filesystem"]),
+        ("network", ["Linux", "Networking"]),
+        ("docker", ["Containers", "Networking"]),
+    ] {
+        seed_tutorial(
+            &app.pool,
+            id,
+            "Filtered walkthrough",
+            &topics,
+            TUTORIAL_STATUS_PUBLISHED,
+        )
+        .await;
+    }
+    let list = |query: String| {
+        let app = &app;
+        async move {
+            let (status, body) = app.get(format!("/api/tutorials?{query}")).await;
+            let ids: Vec<String> = body
+                .as_array()
+                .map(|tutorials| {
+                    tutorials
+                        .iter()
+                        .map(|tutorial| tutorial["id"].as_str().unwrap().to_string())
+                        .collect()
+                })
+                .unwrap_or_default();
+            (status, ids)
+        }
+    };
+
+    assert_eq!(
+        list("topic=linux".to_string()).await,
+        (
+            StatusCode::OK,
+            vec!["files".to_string(), "network".to_string()]
+        )
+    );
+    assert_eq!(
+        list("topics=NETWORKING,linux".to_string()).await,
+        (StatusCode::OK, vec!["network".to_string()])
+    );
+    assert_eq!(
+        list("topic=Containers&topics=Networking".to_string()).await,
+        (StatusCode::OK, vec!["docker".to_string()])
+    );
+    assert_eq!(
+        list("topic=Unknown".to_string()).await,
+        (StatusCode::OK, Vec::new())
+    );
+    let (status, _) = list(format!("topic={}", "a".repeat(101))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn tutorial_list_envelope_reports_total_and_has_more() {
+    let app = TestApp::new().await;
+    sqlx::query("DELETE FROM tutorials")
+        .execute(&app.pool)
+        .await
+        .expect("clear default tutorials");
+    for id in ["one", "two", "three", "four"] {
+        seed_tutorial(
+            &app.pool,
+            id,
+            "Paged walkthrough",
+            &["Linux"],
+            TUTORIAL_STATUS_PUBLISHED,
+        )
+        .await;
+    }
+    let list = |query: &'static str| {
+        let app = &app;
+        async move {
+            let (status, body) = app.get(format!("/api/tutorials?{query}")).await;
+            assert_eq!(status, StatusCode::OK);
+            body
+        }
+    };
+
+    // Without the envelope the bare array stays.
+    assert_eq!(list("limit=2").await.as_array().unwrap().len(), 2);
+
+    let first = list("limit=2&envelope=true").await;
+    assert_eq!(first["items"].as_array().unwrap().len(), 2);
+    assert_eq!(first["total"], 4);
+    assert_eq!(first["hasMore"], true);
+
+    let last = list("limit=2&offset=2&envelope=true").await;
+    assert_eq!(last["items"].as_array().unwrap().len(), 2);
+    assert_eq!(
+        (last["limit"].as_i64(), last["offset"].as_i64()),
+        (Some(2), Some(2))
+    );
+    assert_eq!(last["hasMore"], false);
+
+    let filtered = list("limit=2&topic=unknown&envelope=true").await;
+    assert_eq!(filtered["total"], 0);
+    assert_eq!(filtered["hasMore"], false);
+}
+
+#[tokio::test]
+async fn tutorial_reads_are_counted_once_per_client_and_hour() {
+    let app = TestApp::new().await;
+    seed_tutorial(
+        &app.pool,
+        "counted-walkthrough",
+        "Counted walkthrough",
+        &[],
+        TUTORIAL_STATUS_PUBLISHED,
+    )
+    .await;
+    let admin_get = |uri: &'static str| app.send_as("admin", Method::GET, uri, None);
+
+    for client in [1, 1, 2] {
+        let request = Request::builder()
+            .uri("/api/tutorials/counted-walkthrough")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.send(from_client(request, client)).await;
+        assert_eq!(response.status, StatusCode::OK);
+        assert!(response.json().get("view_count").is_none());
+    }
+
+    // The counter is written in the background
+    let mut stats = serde_json::Value::Null;
+    for _ in 0..50 {
+        (_, stats) = admin_get("/api/admin/stats/views?entity_type=tutorial").await;
+        if stats["totals"][0]["views"] == 2 {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    assert_eq!(stats["totals"][0]["entity_id"], "counted-walkthrough");
+    assert_eq!(stats["totals"][0]["title"], "Counted walkthrough");
+    assert_eq!(stats["totals"][0]["views"], 2);
+
+    let (_, admin_view) = admin_get("/api/tutorials/counted-walkthrough").await;
+    assert!(admin_view["view_count"].as_i64().unwrap() >= 2);
+
+    let (status, _) = admin_get("/api/admin/stats/views?from=2020-13-01").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = admin_get("/api/admin/stats/views?entity_type=page").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = app.get("/api/admin/stats/views").await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn tutorials_with_comments_disabled_reject_new_comments() {
+    let app = TestApp::new().await;
+    crate::repositories::tutorials::create_tutorial(
+        &app.pool,
+        "closed-thread",
+        "Closed thread",
+        "No new comments",
+        "Body",
+        "Terminal",
+        "from-blue-500 to-indigo-600",
+        "[]",
+        &[],
+        TUTORIAL_STATUS_PUBLISHED,
+        false,
+        None,
+    )
+    .await
+    .expect("seed tutorial");
+    sqlx::query(
+        "INSERT INTO comments (id, tutorial_id, author, content) \
+         VALUES ('earlier', 'closed-thread', 'Reader', 'Written before closing')",
+    )
+    .execute(&app.pool)
+    .await
+    .expect("seed comment");
+    let send = |method: Method, uri: &'static str, body: Option<serde_json::Value>| {
+        app.send_as("admin", method, uri, body)
+    };
+    let comment = || Some(serde_json::json!({ "content": "Another thought" }));
+
+    let (status, _) = send(
+        Method::POST,
+        "/api/tutorials/closed-thread/comments",
+        comment(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, comments) = send(Method::GET, "/api/tutorials/closed-thread/comments", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(comments.as_array().map(Vec::len), Some(1));
+
+    let (status, tutorial) = send(
+        Method::PUT,
+        "/api/tutorials/closed-thread",
+        Some(serde_json::json!({ "allow_comments": true })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(tutorial["allow_comments"], true);
+
+    let (status, _) = send(
+        Method::POST,
+        "/api/tutorials/closed-thread/comments",
+        comment(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, count) = send(
+        Method::GET,
+        "/api/tutorials/closed-thread/comments/count",
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(count, serde_json::json!({ "count": 2 }));
+}
+
+#[tokio::test]
+async fn tutorials_record_their_author_and_last_editor() {
+    let app = TestApp::new().await;
+
+    let (status, created) = app
+        .send_as(
+            "admin",
+            Method::POST,
+            "/api/tutorials",
+            Some(serde_json::json!({
+                "id": "attributed",
+                "title": "Attributed",
+                "description": "Has an author",
+                "icon": "Terminal",
+                "color": "from-blue-500 to-indigo-600",
+                "topics": ["Linux"],
+                "content": "Body",
+            })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(created["created_by"], "admin");
+    assert_eq!(created["updated_by"], "admin");
+
+    let (status, updated) = app
+        .send_as(
+            "editor",
+            Method::PUT,
+            "/api/tutorials/attributed",
+            Some(serde_json::json!({ "description": "Edited" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(updated["created_by"], "admin");
+    assert_eq!(updated["updated_by"], "editor");
+
+    let (_, listed) = app
+        .send_as("admin", Method::GET, "/api/tutorials", None)
+        .await;
+    let entry = listed
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|tutorial| tutorial["id"] == "attributed")
+        .expect("listed");
+    assert_eq!(entry["updated_by"], "editor");
+
+    // Visitors see PUBLIC_AUTHOR_NAME, which the tests leave unset
+    let (status, public) = app.get("/api/tutorials/attributed").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(public.get("created_by").is_none());
+    assert!(public.get("updated_by").is_none());
+}
+
+#[tokio::test]
+async fn tutorials_can_be_fetched_with_sanitized_html() {
+    let app = TestApp::new().await;
+    let long_content = format!("## Long\n\n{}", "word ".repeat(20_000));
+    for (id, content) in [
+        (
+            "rendered",
+            "# Setup\n\n## Install\n\n### Install\n\n<script>alert(1)</script>\n\n[bad](javascript:alert(1))",
+        ),
+        ("long", long_content.as_str()),
+    ] {
+        crate::repositories::tutorials::create_tutorial(
+            &app.pool,
+            id,
+            id,
+            "Served as HTML",
+            content,
+            "Terminal",
+            "from-blue-500 to-indigo-600",
+            "[]",
+            &[],
+            TUTORIAL_STATUS_PUBLISHED,
+            true,
+            None,
+        )
+        .await
+        .expect("seed tutorial");
+    }
+
+    let (_, plain) = app.get("/api/tutorials/rendered").await;
+    assert!(plain.get("content_html").is_none());
+    let toc = serde_json::json!([
+        {"level": 2, "text": "Install", "anchor": "install"},
+        {"level": 3, "text": "Install", "anchor": "install-1"}
+    ]);
+    assert_eq!(plain["toc"], toc);
+
+    let (status, rendered) = app.get("/api/tutorials/rendered?format=html").await;
+    assert_eq!(status, StatusCode::OK);
+    let html = rendered["content_html"].as_str().unwrap();
+    assert!(html.contains(r#"<h1 id="setup">Setup</h1>"#));
+    assert!(html.contains(r#"<h3 id="install-1">Install</h3>"#));
+    assert_eq!(rendered["toc"], toc);
+    assert!(!html.contains("<script"));
+    assert!(!html.contains("javascript:"));
+    assert!(rendered["content"].as_str().unwrap().contains("<script>"));
+
+    let (status, _) = app.get("/api/tutorials/rendered?format=pdf").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    // Long documents only get a table of contents on request
+    let (_, long) = app.get("/api/tutorials/long").await;
+    assert_eq!(long["toc"], serde_json::json!([]));
+    let (_, long) = app.get("/api/tutorials/long?toc=true").await;
+    assert_eq!(long["toc"][0]["anchor"], "long");
+}
+
+#[tokio::test]
+async fn tutorials_can_be_split_into_searchable_sections() {
+    let app = TestApp::new().await;
+    seed_tutorial(
+        &app.pool,
+        "chaptered",
+        "Chaptered",
+        &[],
+        TUTORIAL_STATUS_PUBLISHED,
+    )
+    .await;
+    let send = |method: Method, uri: &'static str, body: Option<serde_json::Value>| {
+        app.send_as("admin", method, uri, body)
+    };
+    let section_ids = |value: &serde_json::Value| -> Vec<String> {
+        value
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|section| section["id"].as_str().unwrap().to_string())
+            .collect()
+    };
+
+    // Legacy tutorials serve `content` with no sections
+    let (_, tutorial) = send(Method::GET, "/api/tutorials/chaptered", None).await;
+    assert_eq!(tutorial["sections"], serde_json::json!([]));
+
+    let (status, section) = send(
+        Method::POST,
+        "/api/tutorials/chaptered/sections",
+        Some(serde_json::json!({
+            "title": "Install & Configure",
+            "content": "Run the zyxwvutsrq installer",
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(section["id"], "install-configure");
+
+    let (status, _) = send(
+        Method::POST,
+        "/api/tutorials/chaptered/sections",
+        Some(serde_json::json!({ "id": "basics", "title": "Basics", "content": "Start here" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let (status, _) = send(
+        Method::POST,
+        "/api/tutorials/chaptered/sections",
+        Some(serde_json::json!({ "id": "basics", "title": "Again", "content": "Taken" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let (status, ordering) = send(
+        Method::PUT,
+        "/api/tutorials/chaptered/sections/order",
+        Some(serde_json::json!(["basics"])),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(section_ids(&ordering), ["basics", "install-configure"]);
+
+    let (_, tutorial) = send(Method::GET, "/api/tutorials/chaptered", None).await;
+    assert_eq!(
+        section_ids(&tutorial["sections"]),
+        ["basics", "install-configure"]
+    );
+    assert_eq!(tutorial["updated_by"], "admin");
+
+    // Section text is searchable as part of the tutorial
+    let (_, results) = app.get("/api/search/tutorials?q=zyxwvutsrq").await;
+    assert_eq!(results["items"][0]["id"], "chaptered");
+
+    let (status, updated) = send(
+        Method::PUT,
+        "/api/tutorials/chaptered/sections/install-configure",
+        Some(serde_json::json!({ "content": "Run the setup script" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(updated["title"], "Install & Configure");
+    assert_eq!(updated["content"], "Run the setup script");
+
+    let (_, results) = app.get("/api/search/tutorials?q=zyxwvutsrq").await;
+    assert_eq!(results["items"], serde_json::json!([]));
+
+    let (status, _) = send(
+        Method::DELETE,
+        "/api/tutorials/chaptered/sections/basics",
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = send(
+        Method::DELETE,
+        "/api/tutorials/chaptered/sections/basics",
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (_, sections) = send(Method::GET, "/api/tutorials/chaptered/sections", None).await;
+    assert_eq!(section_ids(&sections), ["install-configure"]);
+}
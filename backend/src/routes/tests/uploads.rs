@@ -0,0 +1,613 @@
+use super::*;
+
+const BOUNDARY: &str = "test-boundary";
+
+/// One part of a multipart form.
+enum Part<'a> {
+    Field(&'a str, &'a str),
+    File(&'a str, &'a [u8]),
+}
+
+/// A multipart form with the given parts, the file ones under the name
+/// "file" as `image/png`.
+fn multipart(parts: &[Part]) -> Vec<u8> {
+    let mut body = Vec::new();
+    for part in parts {
+        match part {
+            Part::Field(name, value) => body.extend_from_slice(
+                format!(
+                    "--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"{name}\"\r\n\r\n\
+                     {value}\r\n"
+                )
+                .as_bytes(),
+            ),
+            Part::File(filename, bytes) => {
+                body.extend_from_slice(
+                    format!(
+                        "--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"file\"; \
+                         filename=\"{filename}\"\r\nContent-Type: image/png\r\n\r\n"
+                    )
+                    .as_bytes(),
+                );
+                body.extend_from_slice(bytes);
+                body.extend_from_slice(b"\r\n");
+            }
+        }
+    }
+    body.extend_from_slice(format!("--{BOUNDARY}--\r\n").as_bytes());
+    body
+}
+
+/// A PNG of the given size in a single color.
+fn png(width: u32, height: u32, color: [u8; 3]) -> Vec<u8> {
+    let mut png = Vec::new();
+    image::DynamicImage::ImageRgb8(image::RgbImage::from_pixel(
+        width,
+        height,
+        image::Rgb(color),
+    ))
+    .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+    .unwrap();
+    png
+}
+
+/// Posts `form` to the upload route as the admin.
+async fn upload(app: &TestApp, form: Vec<u8>) -> TestResponse {
+    let request = app
+        .request(Some("admin"), Method::POST, "/api/upload")
+        .await
+        .header(
+            header::CONTENT_TYPE,
+            format!("multipart/form-data; boundary={BOUNDARY}"),
+        )
+        .body(Body::from(form))
+        .unwrap();
+    app.send(request).await
+}
+
+async fn upload_count(app: &TestApp) -> i64 {
+    sqlx::query_scalar("SELECT COUNT(*) FROM uploads")
+        .fetch_one(&app.pool)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn uploads_are_recorded_with_their_metadata() {
+    let app = TestApp::new().await;
+    let png = png(3, 2, [1, 2, 3]);
+
+    let response = upload(&app, multipart(&[Part::File("photos/holiday.png", &png)])).await;
+    let uploaded: crate::models::UploadResponse =
+        serde_json::from_slice(&response.body).expect("upload response");
+    assert_eq!(response.status, StatusCode::OK);
+
+    let stored = crate::config::get()
+        .upload_dir
+        .join(&uploaded.upload.filename);
+    let on_disk = std::fs::read(&stored);
+    let _ = std::fs::remove_file(&stored);
+    // Only succeeds if the test created the directory
+    let _ = std::fs::remove_dir(&crate::config::get().upload_dir);
+
+    let on_disk = on_disk.expect("file written");
+    assert_eq!(
+        image::load_from_memory(&on_disk).unwrap().to_rgb8(),
+        image::load_from_memory(&png).unwrap().to_rgb8()
+    );
+    assert_eq!(
+        uploaded.url,
+        format!("/uploads/{}", uploaded.upload.filename)
+    );
+    assert_eq!(uploaded.upload.original_name, "holiday.png");
+    assert_eq!(uploaded.upload.mime, "image/png");
+    assert_eq!(uploaded.upload.size_bytes, on_disk.len() as i64);
+    assert_eq!(
+        (uploaded.upload.width, uploaded.upload.height),
+        (Some(3), Some(2))
+    );
+    assert_eq!(uploaded.upload.uploaded_by.as_deref(), Some("admin"));
+    // Narrower than every variant width
+    assert!(uploaded.upload.variants.is_empty());
+
+    let record = crate::repositories::uploads::get_upload(&app.pool, &uploaded.upload.id)
+        .await
+        .expect("load upload")
+        .expect("upload recorded");
+    assert_eq!(record.filename, uploaded.upload.filename);
+}
+
+#[tokio::test]
+async fn uploads_can_be_listed_and_deleted_once_unreferenced() {
+    let app = TestApp::new().await;
+    let mut tx = app.pool.begin().await.unwrap();
+    for (id, filename, original_name) in [
+        ("library-1", "library-1.png", "beach_day.png"),
+        ("library-2", "library-2.png", "mountains.png"),
+    ] {
+        crate::repositories::uploads::insert_upload(
+            &mut tx,
+            &crate::repositories::uploads::NewUpload {
+                id,
+                filename,
+                original_name,
+                mime: "image/png",
+                size_bytes: 10,
+                width: None,
+                height: None,
+                alt: None,
+                caption: None,
+                uploaded_by: "admin",
+                variants: &[],
+            },
+        )
+        .await
+        .expect("seed upload");
+    }
+    tx.commit().await.unwrap();
+    let send = |method: Method, uri: &'static str| app.send_as("admin", method, uri, None);
+
+    // `_` matches itself only, not any character
+    let (status, page) = send(Method::GET, "/api/admin/uploads?q=beach_").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(page["total"], 1);
+    assert_eq!(page["items"][0]["id"], "library-1");
+
+    let (_, page) = send(Method::GET, "/api/admin/uploads?limit=1").await;
+    assert_eq!(page["total"], 2);
+    assert_eq!(page["items"].as_array().unwrap().len(), 1);
+    assert_eq!(page["hasMore"], true);
+
+    sqlx::query(
+        "UPDATE site_content SET content_json = json_set(content_json, '$.image', \
+         '/uploads/library-1.png') WHERE section = 'hero'",
+    )
+    .execute(&app.pool)
+    .await
+    .expect("reference upload");
+
+    let (status, conflict) = send(Method::DELETE, "/api/admin/uploads/library-1").await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(conflict["code"], "upload_in_use");
+    assert_eq!(
+        conflict["references"],
+        serde_json::json!([{ "kind": "site_content", "id": "hero", "title": "hero" }])
+    );
+
+    sqlx::query(
+        "UPDATE site_content SET content_json = json_remove(content_json, '$.image') \
+         WHERE section = 'hero'",
+    )
+    .execute(&app.pool)
+    .await
+    .unwrap();
+
+    let upload_dir = crate::config::get().upload_dir.clone();
+    std::fs::create_dir_all(&upload_dir).unwrap();
+    let stored = upload_dir.join("library-1.png");
+    std::fs::write(&stored, b"png").unwrap();
+
+    let (status, _) = send(Method::DELETE, "/api/admin/uploads/library-1").await;
+    let file_left = stored.exists();
+    let _ = std::fs::remove_file(&stored);
+    // Only succeeds if no other test left files behind
+    let _ = std::fs::remove_dir(&upload_dir);
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    assert!(!file_left);
+    assert!(
+        crate::repositories::uploads::get_upload(&app.pool, "library-1")
+            .await
+            .unwrap()
+            .is_none()
+    );
+
+    // A record whose file is already gone can still be deleted
+    let (status, _) = send(Method::DELETE, "/api/admin/uploads/library-2").await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = send(Method::DELETE, "/api/admin/uploads/library-2").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn uploaded_images_get_resized_variants() {
+    let app = TestApp::new().await;
+    let png = png(640, 100, [9, 90, 200]);
+
+    let response = upload(&app, multipart(&[Part::File("banner.png", &png)])).await;
+    let uploaded: crate::models::UploadResponse =
+        serde_json::from_slice(&response.body).expect("upload response");
+    assert_eq!(response.status, StatusCode::OK);
+
+    let upload_dir = crate::config::get().upload_dir.clone();
+    let variant_path = upload_dir.join(format!("{}_320.webp", uploaded.upload.id));
+    let variant_written = variant_path.exists();
+
+    let (status, _) = app
+        .send_as(
+            "admin",
+            Method::DELETE,
+            format!("/api/admin/uploads/{}", uploaded.upload.id),
+            None,
+        )
+        .await;
+    let variant_left = variant_path.exists();
+    let _ = std::fs::remove_file(upload_dir.join(&uploaded.upload.filename));
+    let _ = std::fs::remove_file(&variant_path);
+    // Only succeeds if no other test left files behind
+    let _ = std::fs::remove_dir(&upload_dir);
+
+    // 960 is wider than the image itself
+    let variants = &uploaded.upload.variants;
+    assert_eq!(variants.len(), 1, "{variants:?}");
+    assert_eq!((variants[0].width, variants[0].height), (320, 50));
+    assert_eq!(
+        variants[0].url,
+        format!("/uploads/{}_320.webp", uploaded.upload.id)
+    );
+    assert!(variant_written);
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    assert!(!variant_left);
+}
+
+#[tokio::test]
+async fn uploads_are_served_with_cache_headers_etags_and_ranges() {
+    let mut app = TestApp::new().await;
+    app.router = app.router.layer(axum::middleware::from_fn(
+        crate::middleware::security::security_headers,
+    ));
+    let png = png(40, 30, [200, 40, 90]);
+
+    let response = upload(&app, multipart(&[Part::File("tile.png", &png)])).await;
+    assert_eq!(response.status, StatusCode::OK);
+    let uploaded: crate::models::UploadResponse =
+        serde_json::from_slice(&response.body).expect("upload response");
+    let size = uploaded.upload.size_bytes;
+
+    let get = |uri: String, headers: &[(header::HeaderName, &str)]| {
+        let mut builder = Request::get(uri);
+        for (name, value) in headers {
+            builder = builder.header(name, *value);
+        }
+        app.send(builder.body(Body::empty()).unwrap())
+    };
+    let file_uri = format!("/uploads/{}", uploaded.upload.filename);
+
+    let whole = get(file_uri.clone(), &[]).await;
+    let etag = whole.headers[header::ETAG].to_str().unwrap().to_string();
+    let cached = get(file_uri.clone(), &[(header::IF_NONE_MATCH, &etag)]).await;
+    let range = get(file_uri.clone(), &[(header::RANGE, "bytes=1-3")]).await;
+    let stale_range = get(
+        file_uri.clone(),
+        &[
+            (header::RANGE, "bytes=1-3"),
+            (header::IF_RANGE, "\"other\""),
+        ],
+    )
+    .await;
+    let beyond = get(file_uri.clone(), &[(header::RANGE, "bytes=100000-")]).await;
+    let missing = get(
+        "/uploads/3f0c0a64-0000-4000-8000-000000000000.png".to_string(),
+        &[],
+    )
+    .await;
+    let hidden = get("/uploads/.env".to_string(), &[]).await;
+
+    let _ = std::fs::remove_file(
+        crate::config::get()
+            .upload_dir
+            .join(&uploaded.upload.filename),
+    );
+    // Only succeeds if no other test left files behind
+    let _ = std::fs::remove_dir(&crate::config::get().upload_dir);
+
+    assert_eq!(whole.status, StatusCode::OK);
+    assert_eq!(whole.headers[header::CONTENT_TYPE], "image/png");
+    assert_eq!(
+        whole.headers[header::CACHE_CONTROL],
+        "public, max-age=31536000, immutable"
+    );
+    assert_eq!(whole.headers[header::ACCEPT_RANGES], "bytes");
+    assert_eq!(whole.body.len() as i64, size);
+    assert!(whole.body.starts_with(b"\x89PNG"));
+
+    assert_eq!(cached.status, StatusCode::NOT_MODIFIED);
+    assert_eq!(cached.headers[header::ETAG], etag.as_str());
+    assert_eq!(
+        cached.headers[header::CACHE_CONTROL],
+        "public, max-age=31536000, immutable"
+    );
+    assert!(cached.body.is_empty());
+
+    assert_eq!(range.status, StatusCode::PARTIAL_CONTENT);
+    assert_eq!(
+        range.headers[header::CONTENT_RANGE],
+        format!("bytes 1-3/{size}").as_str()
+    );
+    assert_eq!(&range.body[..], b"PNG");
+
+    assert_eq!(stale_range.status, StatusCode::OK);
+    assert_eq!(stale_range.body.len() as i64, size);
+
+    assert_eq!(beyond.status, StatusCode::RANGE_NOT_SATISFIABLE);
+    assert_eq!(
+        beyond.headers[header::CONTENT_RANGE],
+        format!("bytes */{size}").as_str()
+    );
+
+    assert_eq!(missing.status, StatusCode::NOT_FOUND);
+    assert!(missing.headers[header::CACHE_CONTROL]
+        .to_str()
+        .unwrap()
+        .starts_with("no-store"));
+    assert_eq!(missing.json()["error"], "Upload not found");
+    assert_eq!(hidden.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn fetching_uploads_from_internal_urls_is_refused() {
+    let app = TestApp::new().await;
+
+    for url in [
+        "http://127.0.0.1:8489/uploads/cat.png",
+        "http://localhost/cat.png",
+        "http://169.254.169.254/latest/meta-data/",
+        "http://[::ffff:10.0.0.1]/cat.png",
+        "file:///etc/passwd",
+        "not a url",
+    ] {
+        let (status, body) = app
+            .send_as(
+                "admin",
+                Method::POST,
+                "/api/admin/uploads/fetch",
+                Some(serde_json::json!({ "url": url })),
+            )
+            .await;
+        let error = body["error"].as_str().unwrap_or_default();
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{url}: {error}");
+        assert!(
+            error.starts_with("URL not allowed") || error == "Invalid URL",
+            "{url}: {error}"
+        );
+    }
+
+    assert_eq!(upload_count(&app).await, 0);
+}
+
+#[tokio::test]
+async fn unreferenced_uploads_are_cleaned_up_after_a_grace_period() {
+    let app = TestApp::new().await;
+    let upload_dir = crate::config::get().upload_dir.clone();
+    std::fs::create_dir_all(&upload_dir).unwrap();
+    let two_days_ago = std::time::SystemTime::now() - std::time::Duration::from_secs(2 * 86_400);
+    let write_file = |name: &str, old: bool| {
+        let file = std::fs::File::create(upload_dir.join(name)).unwrap();
+        std::io::Write::write_all(&mut &file, b"image bytes").unwrap();
+        if old {
+            file.set_modified(two_days_ago).unwrap();
+        }
+    };
+    let orphan = uuid::Uuid::new_v4().to_string();
+    let referenced = uuid::Uuid::new_v4().to_string();
+    let fresh = uuid::Uuid::new_v4().to_string();
+    let legacy_orphan = uuid::Uuid::new_v4().to_string();
+    let legacy_fresh = uuid::Uuid::new_v4().to_string();
+    for (id, age) in [
+        (&orphan, "-3 days"),
+        (&referenced, "-3 days"),
+        (&fresh, "-1 hours"),
+    ] {
+        sqlx::query(
+            "INSERT INTO uploads (id, filename, original_name, mime, size_bytes, created_at) \
+             VALUES (?, ? || '.png', 'photo.png', 'image/png', 11, datetime('now', ?))",
+        )
+        .bind(id)
+        .bind(id)
+        .bind(age)
+        .execute(&app.pool)
+        .await
+        .unwrap();
+        write_file(&format!("{id}.png"), true);
+    }
+    sqlx::query(
+        "INSERT INTO upload_variants (upload_id, width, height, filename, size_bytes) \
+         VALUES (?, 320, 200, ? || '_320.webp', 11)",
+    )
+    .bind(&orphan)
+    .bind(&orphan)
+    .execute(&app.pool)
+    .await
+    .unwrap();
+    write_file(&format!("{orphan}_320.webp"), true);
+    write_file(&format!("{legacy_orphan}.jpg"), true);
+    write_file(&format!("{legacy_fresh}.jpg"), false);
+    sqlx::query("INSERT INTO site_content (section, content_json) VALUES ('cleanup_probe', ?)")
+        .bind(format!(r#"{{"image":"/uploads/{referenced}.png"}}"#))
+        .execute(&app.pool)
+        .await
+        .unwrap();
+
+    let clean_up = |query: &str| {
+        app.send_as(
+            "admin",
+            Method::POST,
+            format!("/api/admin/uploads/cleanup{query}"),
+            None,
+        )
+    };
+    let names = |report: &serde_json::Value, list: &str, field: &str| -> Vec<String> {
+        report[list]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|entry| entry[field].as_str().unwrap().to_string())
+            .collect()
+    };
+
+    let (dry_status, dry_report) = clean_up("").await;
+    let orphan_kept_by_dry_run = upload_dir.join(format!("{orphan}.png")).exists();
+    let (status, report) = clean_up("?dry_run=false").await;
+    let exists = |name: String| upload_dir.join(name).exists();
+    let orphan_left = exists(format!("{orphan}.png")) || exists(format!("{orphan}_320.webp"));
+    let legacy_orphan_left = exists(format!("{legacy_orphan}.jpg"));
+    let others_kept = exists(format!("{referenced}.png"))
+        && exists(format!("{fresh}.png"))
+        && exists(format!("{legacy_fresh}.jpg"));
+    let remaining: Vec<String> = sqlx::query_scalar("SELECT id FROM uploads ORDER BY id")
+        .fetch_all(&app.pool)
+        .await
+        .unwrap();
+
+    for name in [
+        format!("{orphan}.png"),
+        format!("{orphan}_320.webp"),
+        format!("{referenced}.png"),
+        format!("{fresh}.png"),
+        format!("{legacy_orphan}.jpg"),
+        format!("{legacy_fresh}.jpg"),
+    ] {
+        let _ = std::fs::remove_file(upload_dir.join(name));
+    }
+    // Only succeeds if no other test left files behind
+    let _ = std::fs::remove_dir(&upload_dir);
+
+    assert_eq!(dry_status, StatusCode::OK);
+    assert_eq!(dry_report["dry_run"], true);
+    assert_eq!(names(&dry_report, "uploads", "id"), vec![orphan.clone()]);
+    assert_eq!(dry_report["uploads"][0]["size_bytes"], 22);
+    assert!(names(&dry_report, "files", "key").contains(&format!("{legacy_orphan}.jpg")));
+    assert!(!names(&dry_report, "files", "key").contains(&format!("{legacy_fresh}.jpg")));
+    assert!(orphan_kept_by_dry_run);
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(report["dry_run"], false);
+    assert_eq!(names(&report, "uploads", "id"), vec![orphan.clone()]);
+    assert!(!orphan_left);
+    assert!(!legacy_orphan_left);
+    assert!(others_kept);
+    let mut expected = vec![referenced, fresh];
+    expected.sort();
+    assert_eq!(remaining, expected);
+}
+
+#[tokio::test]
+async fn undecodable_images_are_rejected_and_removed() {
+    let app = TestApp::new().await;
+    // PNG signature and an IHDR header for a 3x2 image, but no image data
+    let mut png = b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0dIHDR".to_vec();
+    png.extend_from_slice(&[0, 0, 0, 3, 0, 0, 0, 2, 8, 2, 0, 0, 0, 0, 0, 0, 0]);
+
+    let response = upload(&app, multipart(&[Part::File("broken.png", &png)])).await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+
+    assert_eq!(upload_count(&app).await, 0);
+    // Only succeeds if the test created the directory and left it empty
+    let _ = std::fs::remove_dir(&crate::config::get().upload_dir);
+}
+
+#[tokio::test]
+async fn uploads_carry_alt_text_and_captions() {
+    let app = TestApp::new().await;
+    let png = png(3, 2, [1, 2, 3]);
+    // The text fields may come before and after the file
+    let form = |caption: &str| {
+        multipart(&[
+            Part::Field("alt", "  A red <b>fox</b>  "),
+            Part::File("fox.png", &png),
+            Part::Field("caption", caption),
+        ])
+    };
+
+    let response = upload(&app, form(&"c".repeat(1001))).await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+
+    let response = upload(&app, form("Seen at dawn")).await;
+    let uploaded = response.json();
+    let filename = uploaded["filename"].as_str().unwrap_or_default();
+    let _ = std::fs::remove_file(crate::config::get().upload_dir.join(filename));
+    // Only succeeds if the test created the directory and left it empty
+    let _ = std::fs::remove_dir(&crate::config::get().upload_dir);
+    assert_eq!(response.status, StatusCode::OK);
+    // Stored as plain text; escaping is up to whoever renders it
+    assert_eq!(uploaded["alt"], "A red <b>fox</b>");
+    assert_eq!(uploaded["caption"], "Seen at dawn");
+    assert_eq!(upload_count(&app).await, 1);
+
+    let uri = format!("/api/admin/uploads/{}", uploaded["id"].as_str().unwrap());
+    let update = |body: serde_json::Value| app.send_as("admin", Method::PUT, &uri, Some(body));
+
+    let (status, _) = update(serde_json::json!({ "alt": "a".repeat(301) })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // An empty caption clears it; the alt text is left as it is
+    let (status, updated) = update(serde_json::json!({ "caption": "   " })).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(updated["alt"], "A red <b>fox</b>");
+    assert!(updated["caption"].is_null());
+
+    let (status, _) = update(serde_json::json!({ "alt": " A fox ", "caption": "Dawn" })).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, page) = app
+        .send_as("admin", Method::GET, "/api/admin/uploads", None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(page["items"][0]["alt"], "A fox");
+    assert_eq!(page["items"][0]["caption"], "Dawn");
+
+    let (status, _) = app
+        .send_as(
+            "admin",
+            Method::PUT,
+            "/api/admin/uploads/missing",
+            Some(serde_json::json!({})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn only_the_upload_route_takes_large_bodies() {
+    let app = TestApp::new().await;
+
+    // JSON endpoints stay at the small default
+    let page = serde_json::json!({
+        "title": "Big", "slug": "big", "description": "a".repeat(DEFAULT_BODY_LIMIT),
+    });
+    let (status, _) = app
+        .send_as("admin", Method::POST, "/api/pages", Some(page))
+        .await;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+
+    // An upload over the default but within MAX_UPLOAD_SIZE_MB gets to the
+    // handler; one over the configured size is refused by it
+    let max_upload_bytes = crate::config::get().max_upload_bytes();
+    let form = |file_size: usize| {
+        let mut file = b"\x89PNG\r\n\x1a\n".to_vec();
+        file.resize(file_size, 0);
+        multipart(&[Part::File("big.png", &file)])
+    };
+
+    let response = upload(&app, form(2 * DEFAULT_BODY_LIMIT)).await;
+    // Not a decodable PNG
+    assert_eq!(
+        response.status,
+        StatusCode::BAD_REQUEST,
+        "{:?}",
+        response.body
+    );
+
+    let response = upload(&app, form(max_upload_bytes + 1)).await;
+    assert_eq!(response.status, StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(
+        response.json()["error"],
+        format!(
+            "File too large. Max size: {} MB",
+            crate::config::get().max_upload_size_mb
+        )
+    );
+
+    assert_eq!(upload_count(&app).await, 0);
+    // Only succeeds if the test created the directory and left it empty
+    let _ = std::fs::remove_dir(&crate::config::get().upload_dir);
+}
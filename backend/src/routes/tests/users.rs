@@ -0,0 +1,60 @@
+use super::*;
+
+#[tokio::test]
+async fn account_changes_end_the_sessions_it_holds() {
+    let app = TestApp::new().await;
+    let admin = seed_sudo_user(&app.pool, "admin").await;
+    let as_admin = |method: Method, username: &str, body: serde_json::Value| {
+        let app = &app;
+        let admin = &admin;
+        let username = username.to_string();
+        async move {
+            let id = crate::repositories::users::get_user_by_username(&app.pool, &username)
+                .await
+                .unwrap()
+                .expect("user exists")
+                .id;
+            let request = Request::builder()
+                .method(method)
+                .uri(format!("/api/admin/users/{id}"))
+                .header(header::CONTENT_TYPE, "application/json");
+            let request = with_user(request, admin)
+                .body(Body::from(body.to_string()))
+                .unwrap();
+            app.send(request).await.status
+        }
+    };
+    let sessions_of = |user: &TestUser| {
+        let request = Request::get("/api/auth/sessions");
+        app.send(with_user(request, user).body(Body::empty()).unwrap())
+    };
+
+    let editor = app.seed_user("editor").await;
+    let user = app.seed_user("user").await;
+    assert_eq!(sessions_of(&editor).await.status, StatusCode::OK);
+    assert_eq!(sessions_of(&user).await.status, StatusCode::OK);
+
+    // The old token still says "editor"; it must not outlive the demotion
+    let status = as_admin(Method::PUT, "editor", serde_json::json!({ "role": "user" })).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(sessions_of(&editor).await.status, StatusCode::UNAUTHORIZED);
+    assert_eq!(sessions_of(&user).await.status, StatusCode::OK);
+
+    let status = as_admin(
+        Method::PUT,
+        "user",
+        serde_json::json!({ "password": "a sufficiently long password" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(sessions_of(&user).await.status, StatusCode::UNAUTHORIZED);
+
+    let user = app.seed_user("user").await;
+    assert_eq!(sessions_of(&user).await.status, StatusCode::OK);
+    let status = as_admin(Method::DELETE, "user", serde_json::Value::Null).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    assert_eq!(sessions_of(&user).await.status, StatusCode::UNAUTHORIZED);
+
+    // The admin's own session is untouched
+    assert_eq!(sessions_of(&admin).await.status, StatusCode::OK);
+}
@@ -0,0 +1,142 @@
+//! JSON Schemas for Free-Form Content Blobs
+//!
+//! Page `hero`/`layout` blobs and the structured site content sections are
+//! stored as opaque JSON, but the frontend expects a particular shape. The
+//! schemas next to this file describe that shape; they are compiled once and
+//! checked on every write, so a malformed blob is rejected with the JSON
+//! pointer of each offending field instead of breaking a page at render time.
+
+use jsonschema::Validator;
+use serde_json::Value;
+use std::sync::LazyLock;
+
+/// How many violations a rejection reports.
+const MAX_REPORTED_VIOLATIONS: usize = 5;
+
+/// A schema shipped with the backend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Schema {
+    PageHero,
+    PageLayout,
+    Hero,
+    TutorialSection,
+    Header,
+    Footer,
+    Settings,
+    SiteMeta,
+    Login,
+}
+
+impl Schema {
+    pub const ALL: [Schema; 9] = [
+        Schema::PageHero,
+        Schema::PageLayout,
+        Schema::Hero,
+        Schema::TutorialSection,
+        Schema::Header,
+        Schema::Footer,
+        Schema::Settings,
+        Schema::SiteMeta,
+        Schema::Login,
+    ];
+
+    /// The schema of a site content section, if it has one.
+    pub fn for_section(section: &str) -> Option<Schema> {
+        match section {
+            "hero" => Some(Schema::Hero),
+            "tutorial_section" => Some(Schema::TutorialSection),
+            "header" => Some(Schema::Header),
+            "footer" => Some(Schema::Footer),
+            "settings" => Some(Schema::Settings),
+            "site_meta" => Some(Schema::SiteMeta),
+            "login" => Some(Schema::Login),
+            _ => None,
+        }
+    }
+
+    fn source(self) -> &'static str {
+        match self {
+            Schema::PageHero => include_str!("page_hero.json"),
+            Schema::PageLayout => include_str!("page_layout.json"),
+            Schema::Hero => include_str!("hero.json"),
+            Schema::TutorialSection => include_str!("tutorial_section.json"),
+            Schema::Header => include_str!("header.json"),
+            Schema::Footer => include_str!("footer.json"),
+            Schema::Settings => include_str!("settings.json"),
+            Schema::SiteMeta => include_str!("site_meta.json"),
+            Schema::Login => include_str!("login.json"),
+        }
+    }
+
+    fn validator(self) -> &'static Validator {
+        static VALIDATORS: LazyLock<Vec<Validator>> =
+            LazyLock::new(|| Schema::ALL.iter().map(|schema| schema.compile()).collect());
+        // `ALL` lists the variants in declaration order
+        &VALIDATORS[self as usize]
+    }
+
+    fn compile(self) -> Validator {
+        let schema: Value =
+            serde_json::from_str(self.source()).expect("bundled schema is valid JSON");
+        jsonschema::validator_for(&schema)
+            .unwrap_or_else(|err| panic!("bundled schema {self:?} does not compile: {err}"))
+    }
+
+    /// Checks `value` against the schema. On failure, returns the first few
+    /// violations as `"<json pointer>: <message>"`, with the root as `/`.
+    /// Messages never echo the offending value.
+    pub fn validate(self, value: &Value) -> Result<(), Vec<String>> {
+        let violations: Vec<String> = self
+            .validator()
+            .iter_errors(value)
+            .take(MAX_REPORTED_VIOLATIONS)
+            .map(|err| {
+                let pointer = err.instance_path().as_str();
+                let pointer = if pointer.is_empty() { "/" } else { pointer };
+                format!("{pointer}: {}", err.masked())
+            })
+            .collect();
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn bundled_schemas_compile() {
+        for (index, schema) in Schema::ALL.into_iter().enumerate() {
+            assert_eq!(schema as usize, index);
+            let _ = schema.validator();
+        }
+    }
+
+    #[test]
+    fn violations_are_reported_by_json_pointer() {
+        let violations = Schema::PageLayout
+            .validate(&json!({ "postsSection": { "title": 42, "emptyTitle": [] } }))
+            .unwrap_err();
+        assert_eq!(violations.len(), 2);
+        assert!(violations
+            .iter()
+            .any(|v| v.starts_with("/postsSection/title: ")));
+        assert!(violations
+            .iter()
+            .any(|v| v.starts_with("/postsSection/emptyTitle: ")));
+        assert!(!violations.iter().any(|v| v.contains("42")));
+
+        let violations = Schema::PageHero.validate(&json!("hero")).unwrap_err();
+        assert!(violations[0].starts_with("/: "), "{violations:?}");
+
+        assert!(Schema::PageHero
+            .validate(&json!({ "title": "Welcome", "extra": true }))
+            .is_ok());
+    }
+}
@@ -0,0 +1,75 @@
+//! Search Logging
+//!
+//! Every first page of a public tutorial search is logged to `search_log`
+//! with its normalized query, how many tutorials it found and a keyed hash
+//! of the client IP, behind `GET /api/admin/search/analytics`. Later pages
+//! of the same search are not logged again.
+//!
+//! Logging never delays the search: the database write runs on a spawned
+//! task and its failure is only logged. Rows older than
+//! `SEARCH_LOG_RETENTION_DAYS` are purged by the maintenance task.
+
+use crate::db::DbPool;
+use crate::handlers::auth::hash_search_ip;
+use std::net::IpAddr;
+
+/// Longest query that is stored; longer ones are cut off.
+pub const MAX_LOGGED_QUERY_CHARS: usize = 100;
+
+/// Lowercases `raw`, collapses runs of whitespace into single spaces and
+/// cuts the result to [`MAX_LOGGED_QUERY_CHARS`], so the same search typed
+/// slightly differently is counted together. `None` for a blank query.
+pub fn normalize_query(raw: &str) -> Option<String> {
+    let collapsed = raw.split_whitespace().collect::<Vec<_>>().join(" ");
+    if collapsed.is_empty() {
+        return None;
+    }
+    let truncated: String = collapsed
+        .to_lowercase()
+        .chars()
+        .take(MAX_LOGGED_QUERY_CHARS)
+        .collect();
+    Some(truncated.trim_end().to_string())
+}
+
+/// Logs one search for `raw_query` by `client_ip` that found `result_count`
+/// tutorials.
+pub fn record(pool: &DbPool, raw_query: &str, result_count: i64, client_ip: IpAddr) {
+    let Some(query) = normalize_query(raw_query) else {
+        return;
+    };
+    let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+        return;
+    };
+    let pool = pool.clone();
+    runtime.spawn(async move {
+        let ip_hash = hash_search_ip(&client_ip.to_string());
+        if let Err(err) =
+            crate::repositories::search_log::insert(&pool, &query, result_count, &ip_hash).await
+        {
+            tracing::warn!("Failed to log search query: {}", err);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn queries_are_collapsed_lowercased_and_truncated() {
+        assert_eq!(
+            normalize_query("  Rust \t  Ownership\n").as_deref(),
+            Some("rust ownership")
+        );
+        assert_eq!(normalize_query(" \n\t "), None);
+
+        let long = format!("{} tail", "x".repeat(MAX_LOGGED_QUERY_CHARS - 1));
+        let normalized = normalize_query(&long).unwrap();
+        assert_eq!(normalized, "x".repeat(MAX_LOGGED_QUERY_CHARS - 1));
+        assert_eq!(
+            normalize_query(&"Ü".repeat(150)).unwrap().chars().count(),
+            MAX_LOGGED_QUERY_CHARS
+        );
+    }
+}
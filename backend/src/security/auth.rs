@@ -5,22 +5,28 @@
 //! protecting routes.
 //!
 //! # Security Features
-//! - HS256 JWT tokens with configurable expiration
+//! - HS256 JWT tokens with configurable expiration, or RS256/EdDSA when a
+//!   keypair is configured (public key served at `GET /api/auth/jwks`)
 //! - Secure, HttpOnly session cookies
 //! - High-entropy secret validation
 //! - Bearer token and cookie-based authentication
+//! - Hashed API keys (`X-Api-Key`) for programmatic clients
+//! - Sudo mode ([`RequireSudo`]) guarding destructive admin actions
 //! - Automatic token expiration handling
 //!
 //! # Usage
 //! Before using any authentication functions, initialize the JWT secret:
 //! ```rust,no_run
 //! use minos_backend::security::auth;
-//! auth::init_jwt_secret().expect("Failed to initialize JWT secret");
+//! use minos_backend::config;
+//! auth::init_jwt_secret(&config::get().jwt_secret).expect("Failed to initialize JWT secret");
 //! ```
+//! or, for asymmetric signing, [`init_jwt_keypair`] with the configured PEM keys.
 
 use axum::{
     extract::FromRef,
     extract::FromRequestParts,
+    http::Extensions,
     http::{
         header::{AUTHORIZATION, SET_COOKIE},
         request::Parts,
@@ -28,16 +34,20 @@ use axum::{
     },
 };
 use axum_extra::extract::cookie::{Cookie, CookieJar, SameSite};
-use chrono::{Duration, Utc};
-use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
-use std::env;
 use std::sync::LazyLock;
 use std::sync::OnceLock;
 use time::{Duration as TimeDuration, OffsetDateTime};
 
 use crate::db::DbPool;
+use crate::models::{
+    internal_error_plain, unauthorized, AppError, RejectionReason, RejectionSource,
+};
+use crate::security::rejections;
 
 /// Global storage for the JWT secret key.
 /// Initialized once at application startup via init_jwt_secret().
@@ -48,6 +58,10 @@ pub static JWT_SECRET: OnceLock<String> = OnceLock::new();
 pub static DECODING_KEY: LazyLock<DecodingKey> =
     LazyLock::new(|| DecodingKey::from_secret(get_jwt_secret().as_bytes()));
 
+/// Asymmetric signing keys. When set, tokens are signed and verified with
+/// them instead of `JWT_SECRET`.
+static JWT_KEYPAIR: OnceLock<JwtKeyPair> = OnceLock::new();
+
 /// List of known placeholder secrets that must not be used in production.
 /// These are common defaults found in example configurations.
 const SECRET_BLACKLIST: &[&str] = &[
@@ -71,43 +85,29 @@ const MIN_CHAR_CLASSES: usize = 3;
 /// Name of the HTTP-only authentication cookie.
 pub const AUTH_COOKIE_NAME: &str = "ltcms_session";
 
-/// Authentication cookie time-to-live in seconds (24 hours).
-const AUTH_COOKIE_TTL_SECONDS: i64 = 24 * 60 * 60;
+/// Lifetime of a newly issued token and its cookie:
+/// `AUTH_SESSION_TTL_HOURS`, or `AUTH_REMEMBER_ME_TTL_HOURS` for a
+/// "remember me" login.
+pub fn session_ttl(remember: bool) -> Duration {
+    let config = crate::config::get();
+    let hours = if remember {
+        config.auth_remember_me_ttl_hours
+    } else {
+        config.auth_session_ttl_hours
+    };
+    Duration::hours(i64::from(hours))
+}
 
-/// Initializes the JWT secret from the environment variable.
-///
-/// This function must be called once at application startup before any
-/// authentication operations. It validates the secret for security and
-/// stores it in global state.
+/// Validates a candidate JWT secret without installing it.
 ///
 /// # Security Validation
 /// The secret is checked for:
-/// - Presence (not missing or empty)
+/// - Presence (not empty or whitespace only)
 /// - Blacklisted placeholder values
 /// - Minimum length (43 characters for ~256 bits of entropy)
 /// - Character diversity (at least 3 character classes)
 /// - Uniqueness (at least 10 unique characters)
-///
-/// # Returns
-/// - `Ok(())` if the secret was successfully initialized
-/// - `Err(String)` with a descriptive error message if validation fails
-///
-/// # Errors
-/// - JWT_SECRET environment variable not set
-/// - Secret is empty or whitespace only
-/// - Secret uses a known placeholder value
-/// - Secret has insufficient entropy
-/// - Secret was already initialized (can only be called once)
-///
-/// # Example
-/// ```rust,no_run
-/// use minos_backend::security::auth;
-/// auth::init_jwt_secret().expect("Failed to initialize JWT secret");
-/// ```
-pub fn init_jwt_secret() -> Result<(), String> {
-    // Load secret from environment
-    let secret = env::var("JWT_SECRET")
-        .map_err(|_| "JWT_SECRET environment variable not set".to_string())?;
+pub fn validate_jwt_secret(secret: &str) -> Result<(), String> {
     let trimmed = secret.trim();
 
     // Check for empty secret
@@ -136,14 +136,72 @@ pub fn init_jwt_secret() -> Result<(), String> {
         .to_string());
     }
 
+    Ok(())
+}
+
+/// Initializes the JWT secret.
+///
+/// This function must be called once at application startup before any
+/// authentication operations. The secret comes from the validated process
+/// configuration ([`crate::config::Config::jwt_secret`]) and is checked
+/// again with [`validate_jwt_secret`] before it is stored.
+///
+/// # Errors
+/// - Secret fails [`validate_jwt_secret`]
+/// - Secret was already initialized (can only be called once)
+///
+/// # Example
+/// ```rust,no_run
+/// use minos_backend::{config, security::auth};
+/// auth::init_jwt_secret(&config::get().jwt_secret).expect("Failed to initialize JWT secret");
+/// ```
+pub fn init_jwt_secret(secret: &str) -> Result<(), String> {
+    validate_jwt_secret(secret)?;
+    if JWT_KEYPAIR.get().is_some() {
+        return Err("JWT keypair already initialized".to_string());
+    }
+
     // Store secret in global state (can only be done once)
     JWT_SECRET
-        .set(trimmed.to_string())
+        .set(secret.trim().to_string())
         .map_err(|_| "JWT_SECRET already initialized".to_string())?;
 
     Ok(())
 }
 
+/// Switches token signing to an RSA (RS256) or Ed25519 (EdDSA) keypair.
+///
+/// Called instead of [`init_jwt_secret`] when `JWT_PRIVATE_KEY_PEM` and
+/// `JWT_PUBLIC_KEY_PEM` are configured. Tokens signed with the other scheme
+/// stop verifying, so switching logs everyone out.
+///
+/// # Errors
+/// - The keys fail [`JwtKeyPair::from_pem`]
+/// - Signing keys were already initialized
+pub fn init_jwt_keypair(private_pem: &str, public_pem: &str) -> Result<Algorithm, String> {
+    if JWT_SECRET.get().is_some() {
+        return Err("JWT_SECRET already initialized".to_string());
+    }
+    let keys = JwtKeyPair::from_pem(private_pem, public_pem)?;
+    let algorithm = keys.algorithm();
+    JWT_KEYPAIR
+        .set(keys)
+        .map_err(|_| "JWT keypair already initialized".to_string())?;
+    Ok(algorithm)
+}
+
+/// The public keys external verifiers need, in JWKS form. Empty while
+/// tokens are signed with the shared `JWT_SECRET`.
+pub fn jwks() -> JwkSet {
+    JwkSet {
+        keys: JWT_KEYPAIR
+            .get()
+            .map(|keys| keys.jwk().clone())
+            .into_iter()
+            .collect(),
+    }
+}
+
 /// Retrieves the JWT secret from global state.
 ///
 /// # Panics
@@ -158,6 +216,34 @@ fn get_jwt_secret() -> &'static str {
         .as_str()
 }
 
+/// Actions on content and accounts that are granted per role.
+///
+/// Admins hold every permission. Editors may write tutorials and blog posts
+/// (and upload the images they use), and keep working while the site is in
+/// maintenance mode, but cannot delete content or touch
+/// pages, site content or accounts. Everything else the admin area offers
+/// (statistics, imports, the deletion log) stays behind
+/// [`ensure_admin`](crate::handlers::common::ensure_admin).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permission {
+    /// Create, update and patch tutorials.
+    EditTutorials,
+    /// Create and update blog posts, and browse the pages they belong to.
+    EditPosts,
+    /// Upload images.
+    UploadMedia,
+    /// Delete tutorials and posts.
+    DeleteContent,
+    /// Create, update and delete site pages.
+    ManagePages,
+    /// Update site content sections.
+    ManageSiteContent,
+    /// Create, update and delete accounts.
+    ManageUsers,
+    /// Keep reading the site while it is in maintenance mode.
+    BypassMaintenance,
+}
+
 /// JWT claims structure containing user identity and authorization information.
 ///
 /// These claims are encoded into the JWT token and can be extracted when
@@ -165,8 +251,13 @@ fn get_jwt_secret() -> &'static str {
 ///
 /// # Fields
 /// - `sub`: Subject (username) - identifies the user
-/// - `role`: User role (e.g., "admin", "user") - for authorization
+/// - `role`: User role ("admin", "editor" or "user") - see [`Claims::can`]
 /// - `exp`: Expiration timestamp (Unix epoch) - prevents token reuse
+/// - `auth_time`: When the session started (Unix epoch) - bounds refreshes
+/// - `jti`: Session ID - keys the `sessions` row that makes it revocable
+/// - `uid`: Account ID (`users.id`) - stable across username changes
+/// - `remember`: Issued for a "remember me" login - selects the token lifetime
+/// - `sudo_until`: End of sudo mode (Unix epoch) - see [`RequireSudo`]
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Claims {
     /// Subject: the username of the authenticated user
@@ -177,24 +268,75 @@ pub struct Claims {
 
     /// Expiration time as Unix timestamp (seconds since epoch)
     pub exp: usize,
+
+    /// Time of the password login this session started with, carried over
+    /// unchanged by refreshes. Zero for tokens issued before the claim existed.
+    #[serde(default)]
+    pub auth_time: usize,
+
+    /// Identifies the login session; kept across refreshes. Empty for
+    /// tokens issued before the claim existed and for API-key requests.
+    #[serde(default)]
+    pub jti: String,
+
+    /// `users.id` of the account. Unlike `sub` it survives renames, so
+    /// anything the account owns is keyed by it. `None` for tokens issued
+    /// before the claim existed.
+    #[serde(default)]
+    pub uid: Option<i64>,
+
+    /// Whether the login asked to be remembered; refreshes keep issuing
+    /// tokens with the longer lifetime. See [`session_ttl`].
+    #[serde(default)]
+    pub remember: bool,
+
+    /// Until when the session may perform destructive actions, set by
+    /// re-entering the password at `POST /api/auth/sudo`. `None` outside
+    /// sudo mode.
+    #[serde(default)]
+    pub sudo_until: Option<usize>,
 }
 
 impl Claims {
-    /// Creates new JWT claims with a 24-hour expiration.
+    /// Whether the token's role grants `permission`. Unknown roles grant
+    /// nothing.
+    pub fn can(&self, permission: Permission) -> bool {
+        match self.role.as_str() {
+            "admin" => true,
+            "editor" => matches!(
+                permission,
+                Permission::EditTutorials
+                    | Permission::EditPosts
+                    | Permission::UploadMedia
+                    | Permission::BypassMaintenance
+            ),
+            _ => false,
+        }
+    }
+
+    /// Creates new JWT claims expiring after the configured session
+    /// lifetime (`AUTH_SESSION_TTL_HOURS`).
     ///
     /// # Arguments
     /// * `username` - The username to include in the token
     /// * `role` - The user's role for authorization
     ///
-    /// # Returns
-    /// A new Claims instance with expiration set to 24 hours from now
-    ///
     /// # Panics
     /// Panics if the system time is severely misconfigured
     pub fn new(username: String, role: String) -> Self {
-        // Calculate expiration time (24 hours from now)
-        let expiration = Utc::now()
-            .checked_add_signed(Duration::hours(24))
+        Self::for_login(username, role, false)
+    }
+
+    /// Creates claims for a new login, with the "remember me" lifetime if
+    /// `remember` is set.
+    ///
+    /// # Panics
+    /// Panics if the system time is severely misconfigured
+    pub fn for_login(username: String, role: String, remember: bool) -> Self {
+        let now = Utc::now();
+
+        let expiration = now
+            .checked_add_signed(session_ttl(remember))
             .and_then(|dt| usize::try_from(dt.timestamp()).ok())
             .expect(
                 "Failed to calculate JWT expiration timestamp. System time may be misconfigured.",
@@ -204,8 +346,54 @@ impl Claims {
             sub: username,
             role,
             exp: expiration,
+            auth_time: usize::try_from(now.timestamp()).unwrap_or_default(),
+            jti: uuid::Uuid::new_v4().simple().to_string(),
+            uid: None,
+            remember,
+            sudo_until: None,
         }
     }
+
+    /// Time left until the token expires, zero once it has.
+    pub fn remaining_lifetime(&self, now: DateTime<Utc>) -> Duration {
+        Duration::seconds((self.exp as i64 - now.timestamp()).max(0))
+    }
+
+    /// Claims for a refreshed token of the same session.
+    ///
+    /// The new expiry is `ttl` from `now`, capped at `auth_time +
+    /// max_session` so a session cannot be extended forever. Returns `None`
+    /// once that cap has been reached. Tokens without `auth_time` are treated
+    /// as having started `ttl` before their expiry.
+    pub fn refreshed(
+        &self,
+        now: DateTime<Utc>,
+        ttl: Duration,
+        max_session: Duration,
+    ) -> Option<Claims> {
+        let session_start = if self.auth_time > 0 {
+            self.auth_time as i64
+        } else {
+            self.exp as i64 - ttl.num_seconds()
+        };
+        let session_end = session_start.saturating_add(max_session.num_seconds());
+        let expiration = (now.timestamp() + ttl.num_seconds()).min(session_end);
+
+        if expiration <= now.timestamp() {
+            return None;
+        }
+
+        Some(Claims {
+            sub: self.sub.clone(),
+            role: self.role.clone(),
+            exp: usize::try_from(expiration).ok()?,
+            auth_time: usize::try_from(session_start).ok()?,
+            jti: self.jti.clone(),
+            uid: self.uid,
+            remember: self.remember,
+            sudo_until: self.sudo_until,
+        })
+    }
 }
 
 /// Creates a signed JWT token for a user.
@@ -222,8 +410,13 @@ impl Claims {
 /// - `Err(jsonwebtoken::errors::Error)` - If token generation fails
 ///
 /// # Security
-/// The token is signed using HS256 with the JWT secret, ensuring it
-/// cannot be forged without knowledge of the secret key.
+/// The token is signed using HS256 with the JWT secret (or with the
+/// configured keypair, see [`init_jwt_keypair`]), ensuring it cannot be
+/// forged without knowledge of the signing key.
+///
+/// The token has no session, so requests made with it are rejected. Logins
+/// build the [`Claims`] themselves to record the session under its `jti`
+/// before signing with [`encode_claims`].
 ///
 /// # Example
 /// ```rust,no_run
@@ -232,8 +425,14 @@ impl Claims {
 /// # Ok::<(), jsonwebtoken::errors::Error>(())
 /// ```
 pub fn create_jwt(username: String, role: String) -> Result<String, jsonwebtoken::errors::Error> {
-    // Create claims with 24-hour expiration
-    let claims = Claims::new(username, role);
+    encode_claims(&Claims::new(username, role))
+}
+
+/// Signs an already assembled set of claims, e.g. from [`Claims::refreshed`].
+pub fn encode_claims(claims: &Claims) -> Result<String, jsonwebtoken::errors::Error> {
+    if let Some(keys) = JWT_KEYPAIR.get() {
+        return encode_with_keypair(keys, claims);
+    }
 
     // Get the initialized JWT secret
     let secret = get_jwt_secret();
@@ -241,11 +440,22 @@ pub fn create_jwt(username: String, role: String) -> Result<String, jsonwebtoken
     // Encode and sign the token
     encode(
         &Header::default(),
-        &claims,
+        claims,
         &EncodingKey::from_secret(secret.as_bytes()),
     )
 }
 
+/// Signs with an asymmetric key, naming it in the `kid` header so
+/// verifiers can pick it from the JWKS.
+fn encode_with_keypair(
+    keys: &JwtKeyPair,
+    claims: &Claims,
+) -> Result<String, jsonwebtoken::errors::Error> {
+    let mut header = Header::new(keys.algorithm());
+    header.kid = Some(keys.key_id().to_string());
+    encode(&header, claims, keys.encoding_key())
+}
+
 /// Verifies a JWT token and extracts its claims.
 ///
 /// This validates the token's signature, checks expiration, and returns
@@ -262,6 +472,8 @@ pub fn create_jwt(username: String, role: String) -> Result<String, jsonwebtoken
 /// - Signature must match (prevents tampering)
 /// - Token must not be expired (with 60-second leeway for clock skew)
 /// - Token must be well-formed
+/// - Token must be signed with the configured algorithm only (HS256, or
+///   the keypair's RS256/EdDSA), so one scheme cannot stand in for the other
 ///
 /// # Security
 /// This function prevents:
@@ -269,19 +481,70 @@ pub fn create_jwt(username: String, role: String) -> Result<String, jsonwebtoken
 /// - Token replay after expiration (expiration check)
 /// - Malformed tokens (parsing validation)
 pub fn verify_jwt(token: &str) -> Result<Claims, jsonwebtoken::errors::Error> {
+    match JWT_KEYPAIR.get() {
+        Some(keys) => decode_claims(token, keys.decoding_key(), keys.algorithm()),
+        // Decode and validate the token using the cached decoding key
+        None => decode_claims(token, &DECODING_KEY, Algorithm::HS256),
+    }
+}
+
+fn decode_claims(
+    token: &str,
+    key: &DecodingKey,
+    algorithm: Algorithm,
+) -> Result<Claims, jsonwebtoken::errors::Error> {
     // Configure validation rules
-    let mut validation = Validation::default();
+    let mut validation = Validation::new(algorithm);
     validation.leeway = 60; // Allow 60 seconds of clock skew
     validation.validate_exp = true; // Ensure token hasn't expired
 
-    // Decode and validate the token using the cached decoding key
-    let token_data = decode::<Claims>(token, &DECODING_KEY, &validation)?;
+    let token_data = decode::<Claims>(token, key, &validation)?;
 
     Ok(token_data.claims)
 }
 
+/// Marker left in the request extensions once a token rejection has been
+/// counted. `CsrfGuard` runs the `Claims` extractor a second time for the
+/// same request; the marker keeps that from counting the rejection twice.
+#[derive(Clone, Copy)]
+struct TokenRejectionRecorded;
+
+/// Counts a rejected token at most once per request.
+pub(crate) fn record_token_rejection(
+    extensions: &mut Extensions,
+    pool: &DbPool,
+    reason: RejectionReason,
+) {
+    if extensions.insert(TokenRejectionRecorded).is_none() {
+        rejections::record(pool, RejectionSource::Token, reason);
+    }
+}
+
+/// Whether a verified token was revoked, on its own (blacklist) or together
+/// with its whole session. Tokens without a session row, including ones
+/// without a `jti`, count as revoked.
+pub(crate) async fn is_token_revoked(
+    pool: &DbPool,
+    token: &str,
+    claims: &Claims,
+) -> Result<bool, sqlx::Error> {
+    if crate::repositories::token_blacklist::is_token_blacklisted(pool, token).await? {
+        return Ok(true);
+    }
+    crate::repositories::sessions::is_session_revoked(pool, &claims.jti).await
+}
+
+mod api_key;
 mod cookies;
+mod keys;
+mod sudo;
+pub use api_key::{
+    api_key_display_prefix, authenticate_api_key, extract_api_key, generate_api_key, hash_api_key,
+    ApiKeyAuth, API_KEY_HEADER,
+};
 pub use cookies::{append_auth_cookie, build_auth_cookie, build_cookie_removal};
+pub use keys::JwtKeyPair;
+pub use sudo::{RequireSudo, SUDO_MODE_TTL_SECONDS, SUDO_REQUIRED_CODE};
 
 /// Validates that a secret has minimum entropy requirements.
 ///
@@ -341,27 +604,14 @@ fn secret_has_min_entropy(secret: &str) -> bool {
 ///
 /// # Returns
 /// - `true` by default (cookies only sent over HTTPS)
-/// - `false` if AUTH_COOKIE_SECURE is explicitly set to "false"
+/// - `false` if AUTH_COOKIE_SECURE is explicitly set to false
 ///
 /// # Security Warning
 /// Setting this to false allows cookies over HTTP, which exposes tokens
-/// to network sniffing. Only use this in trusted development environments.
-///
-/// # Environment Variable
-/// Set AUTH_COOKIE_SECURE=false to disable (logs a warning)
+/// to network sniffing. Only use this in trusted development environments;
+/// the server logs a warning at startup when it is disabled.
 pub fn cookies_should_be_secure() -> bool {
-    match env::var("AUTH_COOKIE_SECURE") {
-        // Only disable if explicitly set to false
-        Ok(value) if value.trim().eq_ignore_ascii_case("false") => {
-            tracing::warn!(concat!(
-                "AUTH_COOKIE_SECURE explicitly set to false. Cookies will be sent over HTTP; ",
-                "only use this in trusted development environments."
-            ));
-            false
-        }
-        // Default to secure cookies
-        _ => true,
-    }
+    crate::config::get().auth_cookie_secure
 }
 
 /// Extracts the JWT token from request headers.
@@ -379,6 +629,9 @@ pub fn cookies_should_be_secure() -> bool {
 ///
 /// # Priority
 /// Authorization header is checked first, falling back to cookies
+///
+/// API keys are not tokens and are read separately by [`extract_api_key`];
+/// the extractors try them before calling this.
 pub fn extract_token(headers: &HeaderMap) -> Option<String> {
     // First check Authorization header
     if let Some(header_value) = headers.get(AUTHORIZATION) {
@@ -432,7 +685,7 @@ where
     S: Send + Sync,
     DbPool: FromRef<S>,
 {
-    type Rejection = (StatusCode, String);
+    type Rejection = AppError;
 
     async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
         // Step 1: Try cache (middleware/previous extractor)
@@ -440,35 +693,43 @@ where
             return Ok(OptionalClaims(Some(claims.clone())));
         }
 
-        // Step 2: Try extraction. If missing, this matches the 'Anonymous' state.
+        // Step 2: An API key, when presented, must resolve.
+        if let Some(key) = extract_api_key(&parts.headers) {
+            let key = key.to_owned();
+            let pool = DbPool::from_ref(state);
+            let claims = authenticate_api_key(&mut parts.extensions, &pool, &key).await?;
+            return Ok(OptionalClaims(Some(claims)));
+        }
+
+        // Step 3: Try extraction. If missing, this matches the 'Anonymous' state.
         let token = match extract_token(&parts.headers) {
             Some(token) => token,
             None => return Ok(OptionalClaims(None)),
         };
 
-        // Step 3: If token exists, it MUST be valid. Invalid tokens for optional endpoints
+        // Step 4: If token exists, it MUST be valid. Invalid tokens for optional endpoints
         // still result in 401 to prevent deceptive client state.
-        let claims = verify_jwt(&token)
-            .map_err(|e| (StatusCode::UNAUTHORIZED, format!("Invalid token: {}", e)))?;
-
-        // Step 4: Revocation check
         let pool = DbPool::from_ref(state);
-        let is_blacklisted =
-            crate::repositories::token_blacklist::is_token_blacklisted(&pool, &token)
-                .await
-                .map_err(|e| {
-                    tracing::error!("Database error checking token blacklist: {}", e);
-                    (
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        "Internal server error".to_string(),
-                    )
-                })?;
-
-        if is_blacklisted {
-            return Err((
-                StatusCode::UNAUTHORIZED,
-                "Token has been revoked".to_string(),
-            ));
+        let claims = verify_jwt(&token).map_err(|e| {
+            record_token_rejection(
+                &mut parts.extensions,
+                &pool,
+                rejections::token_error_reason(&e),
+            );
+            unauthorized(format!("Invalid token: {}", e))
+        })?;
+
+        // Step 5: Revocation check (token blacklist and session)
+        let is_revoked = is_token_revoked(&pool, &token, &claims)
+            .await
+            .map_err(|e| {
+                tracing::error!("Database error checking token blacklist: {}", e);
+                internal_error_plain("Internal server error")
+            })?;
+
+        if is_revoked {
+            record_token_rejection(&mut parts.extensions, &pool, RejectionReason::Blacklisted);
+            return Err(unauthorized("Token has been revoked"));
         }
 
         // Cache result
@@ -0,0 +1,86 @@
+use super::*;
+use base64ct::{Base64UrlUnpadded, Encoding};
+
+/// Header carrying an API key. It is never set by the browser on its own
+/// and is not in the CORS allowlist, so API-key requests need no CSRF token.
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Marks generated keys so they are recognisable in logs and secret scanners.
+const API_KEY_MARKER: &str = "mk_";
+
+/// Random bytes per key (256 bits).
+const API_KEY_BYTES: usize = 32;
+
+/// Characters of the key kept in plaintext for the admin key list.
+const DISPLAY_PREFIX_LEN: usize = 10;
+
+/// Request extension present when the request's [`Claims`] were resolved
+/// from an API key rather than a session token.
+#[derive(Debug, Clone, Copy)]
+pub struct ApiKeyAuth {
+    pub key_id: i64,
+}
+
+/// Returns the trimmed `X-Api-Key` header value, if any.
+pub fn extract_api_key(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(API_KEY_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+}
+
+/// Generates a new plaintext key.
+pub fn generate_api_key() -> String {
+    let mut raw = [0u8; API_KEY_BYTES];
+    rand::fill(&mut raw[..]);
+    format!("{API_KEY_MARKER}{}", Base64UrlUnpadded::encode_string(&raw))
+}
+
+/// Keys carry 256 random bits, so an unsalted SHA-256 digest is a safe
+/// lookup value.
+pub fn hash_api_key(key: &str) -> String {
+    crate::security::sha256_hex(key.as_bytes())
+}
+
+/// The part of a key that stays visible in the key list.
+pub fn api_key_display_prefix(key: &str) -> String {
+    key.chars().take(DISPLAY_PREFIX_LEN).collect()
+}
+
+/// Resolves an API key to synthetic [`Claims`] carrying the owner's current
+/// role, and caches both the claims and an [`ApiKeyAuth`] marker in
+/// `extensions`.
+///
+/// The claims expire like a fresh session but have no `auth_time` or
+/// `jti`, and `/api/auth/refresh` only accepts real tokens, so a key cannot
+/// be traded for a session.
+pub async fn authenticate_api_key(
+    extensions: &mut Extensions,
+    pool: &DbPool,
+    key: &str,
+) -> Result<Claims, AppError> {
+    let owner = crate::repositories::api_keys::find_owner_by_hash(pool, &hash_api_key(key))
+        .await
+        .map_err(|e| {
+            tracing::error!("Database error resolving API key: {}", e);
+            internal_error_plain("Internal server error")
+        })?;
+
+    let Some(owner) = owner else {
+        record_token_rejection(extensions, pool, RejectionReason::Malformed);
+        return Err(unauthorized("Invalid API key"));
+    };
+
+    let claims = Claims {
+        auth_time: 0,
+        jti: String::new(),
+        uid: Some(owner.user_id),
+        ..Claims::new(owner.username, owner.role)
+    };
+    extensions.insert(claims.clone());
+    extensions.insert(ApiKeyAuth {
+        key_id: owner.key_id,
+    });
+    Ok(claims)
+}
@@ -7,6 +7,7 @@ use super::*;
 ///
 /// # Arguments
 /// * `token` - The JWT token to store in the cookie
+/// * `ttl` - Cookie lifetime; should match the token's expiry
 ///
 /// # Returns
 /// A Cookie configured for secure authentication token storage
@@ -15,15 +16,15 @@ use super::*;
 /// - HttpOnly: Prevents JavaScript access (XSS protection)
 /// - SameSite=Lax: CSRF protection while allowing navigation
 /// - Secure flag: HTTPS-only (when AUTH_COOKIE_SECURE is not false)
-/// - 24-hour expiration: Matches JWT expiration
+/// - Max-Age: `ttl`
 /// - Path=/: Available to all routes
-pub fn build_auth_cookie(token: &str) -> Cookie<'static> {
+pub fn build_auth_cookie(token: &str, ttl: Duration) -> Cookie<'static> {
     // Build cookie with security flags
     let mut builder = Cookie::build((AUTH_COOKIE_NAME, token.to_owned()))
         .path("/")
         .http_only(true)
         .same_site(SameSite::Lax)
-        .max_age(TimeDuration::seconds(AUTH_COOKIE_TTL_SECONDS));
+        .max_age(TimeDuration::seconds(ttl.num_seconds()));
 
     // Add Secure flag in production (HTTPS only)
     if cookies_should_be_secure() {
@@ -70,19 +71,21 @@ pub fn build_cookie_removal() -> Cookie<'static> {
 ///
 /// # Extraction order
 /// 1. Check if claims already in request extensions (from middleware)
-/// 2. Extract token from Authorization header or cookie
-/// 3. Validate token and decode claims
+/// 2. Resolve an `X-Api-Key` header, if present, to its owner's claims
+/// 3. Extract token from Authorization header or cookie
+/// 4. Validate token and decode claims
 ///
 /// # Errors
 /// Returns 401 Unauthorized if:
-/// - No token found in headers or cookies
+/// - No API key or token found in headers or cookies
+/// - The API key is unknown or revoked
 /// - Token is invalid or expired
 impl<S> FromRequestParts<S> for Claims
 where
     S: Send + Sync,
     DbPool: FromRef<S>,
 {
-    type Rejection = (StatusCode, String);
+    type Rejection = AppError;
 
     async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
         // Step 1: Check cache. If auth middleware already ran, claims are in extensions.
@@ -90,36 +93,39 @@ where
             return Ok(claims.clone());
         }
 
-        // Step 2: Extract raw token from standard locations (Header/Cookie).
-        let token = extract_token(&parts.headers).ok_or_else(|| {
-            (
-                StatusCode::UNAUTHORIZED,
-                "Missing authentication token".to_string(),
-            )
-        })?;
+        // Step 2: API keys take precedence over any token sent alongside.
+        if let Some(key) = extract_api_key(&parts.headers) {
+            let key = key.to_owned();
+            let pool = DbPool::from_ref(state);
+            return authenticate_api_key(&mut parts.extensions, &pool, &key).await;
+        }
 
-        // Step 3: Verify cryptographic signature and expiration.
-        let claims = verify_jwt(&token)
-            .map_err(|e| (StatusCode::UNAUTHORIZED, format!("Invalid token: {}", e)))?;
+        // Step 3: Extract raw token from standard locations (Header/Cookie).
+        let token = extract_token(&parts.headers)
+            .ok_or_else(|| unauthorized("Missing authentication token"))?;
 
-        // Step 4: Check if token has been revoked (Logout/Blacklist).
+        // Step 4: Verify cryptographic signature and expiration.
         let pool = DbPool::from_ref(state);
-        let is_blacklisted =
-            crate::repositories::token_blacklist::is_token_blacklisted(&pool, &token)
-                .await
-                .map_err(|e| {
-                    tracing::error!("Database error checking token blacklist: {}", e);
-                    (
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        "Internal server error".to_string(),
-                    )
-                })?;
+        let claims = verify_jwt(&token).map_err(|e| {
+            record_token_rejection(
+                &mut parts.extensions,
+                &pool,
+                rejections::token_error_reason(&e),
+            );
+            unauthorized(format!("Invalid token: {}", e))
+        })?;
+
+        // Step 5: Check if token or its session has been revoked (Logout/Blacklist).
+        let is_revoked = is_token_revoked(&pool, &token, &claims)
+            .await
+            .map_err(|e| {
+                tracing::error!("Database error checking token blacklist: {}", e);
+                internal_error_plain("Internal server error")
+            })?;
 
-        if is_blacklisted {
-            return Err((
-                StatusCode::UNAUTHORIZED,
-                "Token has been revoked".to_string(),
-            ));
+        if is_revoked {
+            record_token_rejection(&mut parts.extensions, &pool, RejectionReason::Blacklisted);
+            return Err(unauthorized("Token has been revoked"));
         }
 
         // Cache result for downstream handlers
@@ -0,0 +1,285 @@
+//! Asymmetric token signing keys (RS256 and EdDSA).
+//!
+//! `jsonwebtoken` is built without its PEM feature, so the PEM armour and
+//! the handful of DER structures involved (PKCS#8, PKCS#1 and
+//! SubjectPublicKeyInfo) are unpacked here. Only what those formats need is
+//! understood; anything else is rejected with a message naming the setting.
+
+use base64ct::{Base64, Base64UrlUnpadded, Encoding};
+use jsonwebtoken::jwk::{
+    AlgorithmParameters, CommonParameters, EllipticCurve, Jwk, KeyAlgorithm,
+    OctetKeyPairParameters, OctetKeyPairType, PublicKeyUse, RSAKeyParameters, RSAKeyType,
+};
+use jsonwebtoken::{crypto, Algorithm, DecodingKey, EncodingKey};
+use sha2::{Digest, Sha256};
+
+const TAG_INTEGER: u8 = 0x02;
+const TAG_BIT_STRING: u8 = 0x03;
+const TAG_OCTET_STRING: u8 = 0x04;
+const TAG_OID: u8 = 0x06;
+const TAG_SEQUENCE: u8 = 0x30;
+
+/// 1.2.840.113549.1.1.1
+const OID_RSA_ENCRYPTION: &[u8] = &[0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x01, 0x01];
+/// 1.3.101.112
+const OID_ED25519: &[u8] = &[0x2B, 0x65, 0x70];
+
+/// Message signed at startup to prove the configured keys belong together.
+const PAIR_CHECK_MESSAGE: &[u8] = b"minos-jwt-keypair-check";
+
+/// A validated signing keypair with its public JWK.
+pub struct JwtKeyPair {
+    algorithm: Algorithm,
+    encoding: EncodingKey,
+    decoding: DecodingKey,
+    jwk: Jwk,
+}
+
+impl JwtKeyPair {
+    /// Loads a keypair from PEM text.
+    ///
+    /// The private key may be PKCS#8 (`PRIVATE KEY`, RSA or Ed25519) or
+    /// PKCS#1 (`RSA PRIVATE KEY`); the public key SubjectPublicKeyInfo
+    /// (`PUBLIC KEY`) or PKCS#1 (`RSA PUBLIC KEY`). RSA keys sign with
+    /// RS256, Ed25519 keys with EdDSA. A probe signature checks that the
+    /// two halves match.
+    pub fn from_pem(private_pem: &str, public_pem: &str) -> Result<Self, String> {
+        let private = parse_private_key(private_pem)?;
+        let public = parse_public_key(public_pem)?;
+
+        let (algorithm, encoding, decoding, params) = match (private, public) {
+            (PrivateKey::Rsa(pkcs1), PublicKey::Rsa { der, n, e }) => (
+                Algorithm::RS256,
+                EncodingKey::from_rsa_der(&pkcs1),
+                DecodingKey::from_rsa_der(&der),
+                AlgorithmParameters::RSA(RSAKeyParameters {
+                    key_type: RSAKeyType::RSA,
+                    n: Base64UrlUnpadded::encode_string(&n),
+                    e: Base64UrlUnpadded::encode_string(&e),
+                }),
+            ),
+            (PrivateKey::Ed25519(pkcs8), PublicKey::Ed25519(raw)) => (
+                Algorithm::EdDSA,
+                EncodingKey::from_ed_der(&pkcs8),
+                DecodingKey::from_ed_der(&raw),
+                AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+                    key_type: OctetKeyPairType::OctetKeyPair,
+                    curve: EllipticCurve::Ed25519,
+                    x: Base64UrlUnpadded::encode_string(&raw),
+                }),
+            ),
+            _ => {
+                return Err(
+                    "JWT_PRIVATE_KEY_PEM and JWT_PUBLIC_KEY_PEM hold different key types"
+                        .to_string(),
+                )
+            }
+        };
+
+        let signature = crypto::sign(PAIR_CHECK_MESSAGE, &encoding, algorithm)
+            .map_err(|err| format!("JWT_PRIVATE_KEY_PEM cannot sign: {err}"))?;
+        let matches = crypto::verify(&signature, PAIR_CHECK_MESSAGE, &decoding, algorithm)
+            .map_err(|err| format!("JWT_PUBLIC_KEY_PEM cannot verify: {err}"))?;
+        if !matches {
+            return Err("JWT_PUBLIC_KEY_PEM does not belong to JWT_PRIVATE_KEY_PEM".to_string());
+        }
+
+        let key_algorithm = match algorithm {
+            Algorithm::RS256 => KeyAlgorithm::RS256,
+            _ => KeyAlgorithm::EdDSA,
+        };
+        let jwk = Jwk {
+            common: CommonParameters {
+                public_key_use: Some(PublicKeyUse::Signature),
+                key_algorithm: Some(key_algorithm),
+                key_id: Some(thumbprint(&params)),
+                ..Default::default()
+            },
+            algorithm: params,
+        };
+
+        Ok(Self {
+            algorithm,
+            encoding,
+            decoding,
+            jwk,
+        })
+    }
+
+    /// `RS256` or `EdDSA`, depending on the key type.
+    pub fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    /// The RFC 7638 thumbprint of the public key, used as `kid`.
+    pub fn key_id(&self) -> &str {
+        self.jwk.common.key_id.as_deref().unwrap_or_default()
+    }
+
+    /// The public key as published at `GET /api/auth/jwks`.
+    pub fn jwk(&self) -> &Jwk {
+        &self.jwk
+    }
+
+    pub(super) fn encoding_key(&self) -> &EncodingKey {
+        &self.encoding
+    }
+
+    pub(super) fn decoding_key(&self) -> &DecodingKey {
+        &self.decoding
+    }
+}
+
+enum PrivateKey {
+    /// PKCS#1 `RSAPrivateKey`, as `ring` expects it.
+    Rsa(Vec<u8>),
+    /// The whole PKCS#8 document, as `ring` expects it.
+    Ed25519(Vec<u8>),
+}
+
+enum PublicKey {
+    /// PKCS#1 `RSAPublicKey` plus its unsigned big-endian components.
+    Rsa {
+        der: Vec<u8>,
+        n: Vec<u8>,
+        e: Vec<u8>,
+    },
+    /// The raw 32-byte point.
+    Ed25519(Vec<u8>),
+}
+
+fn parse_private_key(pem: &str) -> Result<PrivateKey, String> {
+    let malformed = || "JWT_PRIVATE_KEY_PEM is not a valid private key".to_string();
+    let (label, der) = decode_pem(pem).ok_or_else(malformed)?;
+    match label.as_str() {
+        "RSA PRIVATE KEY" => Ok(PrivateKey::Rsa(der)),
+        "PRIVATE KEY" => {
+            let mut outer = Der(&der);
+            let mut info = Der(outer.next(TAG_SEQUENCE).ok_or_else(malformed)?);
+            info.next(TAG_INTEGER).ok_or_else(malformed)?;
+            let mut algorithm = Der(info.next(TAG_SEQUENCE).ok_or_else(malformed)?);
+            let oid = algorithm.next(TAG_OID).ok_or_else(malformed)?;
+            let key = info.next(TAG_OCTET_STRING).ok_or_else(malformed)?;
+            match oid {
+                OID_RSA_ENCRYPTION => Ok(PrivateKey::Rsa(key.to_vec())),
+                OID_ED25519 => Ok(PrivateKey::Ed25519(der.clone())),
+                _ => Err(unsupported("JWT_PRIVATE_KEY_PEM")),
+            }
+        }
+        "ENCRYPTED PRIVATE KEY" => {
+            Err("JWT_PRIVATE_KEY_PEM is encrypted; export it without a passphrase".to_string())
+        }
+        _ => Err(malformed()),
+    }
+}
+
+fn parse_public_key(pem: &str) -> Result<PublicKey, String> {
+    let malformed = || "JWT_PUBLIC_KEY_PEM is not a valid public key".to_string();
+    let (label, der) = decode_pem(pem).ok_or_else(malformed)?;
+    match label.as_str() {
+        "RSA PUBLIC KEY" => rsa_public_key(der).ok_or_else(malformed),
+        "PUBLIC KEY" => {
+            let mut outer = Der(&der);
+            let mut info = Der(outer.next(TAG_SEQUENCE).ok_or_else(malformed)?);
+            let mut algorithm = Der(info.next(TAG_SEQUENCE).ok_or_else(malformed)?);
+            let oid = algorithm.next(TAG_OID).ok_or_else(malformed)?;
+            // A BIT STRING starts with its count of unused bits, always 0 here.
+            let key = match info.next(TAG_BIT_STRING).ok_or_else(malformed)? {
+                [0, key @ ..] => key,
+                _ => return Err(malformed()),
+            };
+            match oid {
+                OID_RSA_ENCRYPTION => rsa_public_key(key.to_vec()).ok_or_else(malformed),
+                OID_ED25519 if key.len() == 32 => Ok(PublicKey::Ed25519(key.to_vec())),
+                OID_ED25519 => Err(malformed()),
+                _ => Err(unsupported("JWT_PUBLIC_KEY_PEM")),
+            }
+        }
+        _ => Err(malformed()),
+    }
+}
+
+fn unsupported(setting: &str) -> String {
+    format!("{setting} must be an RSA or Ed25519 key")
+}
+
+/// Splits a PKCS#1 `RSAPublicKey` into modulus and exponent.
+fn rsa_public_key(der: Vec<u8>) -> Option<PublicKey> {
+    let mut outer = Der(&der);
+    let mut key = Der(outer.next(TAG_SEQUENCE)?);
+    let n = unsigned(key.next(TAG_INTEGER)?).to_vec();
+    let e = unsigned(key.next(TAG_INTEGER)?).to_vec();
+    if n.is_empty() || e.is_empty() {
+        return None;
+    }
+    Some(PublicKey::Rsa { der, n, e })
+}
+
+/// Drops the sign padding DER puts in front of positive integers.
+fn unsigned(integer: &[u8]) -> &[u8] {
+    let leading_zeros = integer.iter().take_while(|&&b| b == 0).count();
+    &integer[leading_zeros..]
+}
+
+/// Returns the label and decoded body of the first PEM block.
+fn decode_pem(pem: &str) -> Option<(String, Vec<u8>)> {
+    let start = pem.find("-----BEGIN ")?;
+    let rest = &pem[start + "-----BEGIN ".len()..];
+    let label_end = rest.find("-----")?;
+    let label = &rest[..label_end];
+    let body = &rest[label_end + "-----".len()..];
+    let body = &body[..body.find(&format!("-----END {label}-----"))?];
+
+    let base64: String = body.chars().filter(|c| !c.is_whitespace()).collect();
+    let der = Base64::decode_vec(&base64).ok()?;
+    Some((label.to_string(), der))
+}
+
+/// Cursor over a run of DER elements.
+struct Der<'a>(&'a [u8]);
+
+impl<'a> Der<'a> {
+    /// Consumes the next element, which must carry `tag`, and returns its
+    /// contents.
+    fn next(&mut self, tag: u8) -> Option<&'a [u8]> {
+        let (&found, rest) = self.0.split_first()?;
+        if found != tag {
+            return None;
+        }
+        let (&first, rest) = rest.split_first()?;
+        let (len, rest) = if first < 0x80 {
+            (usize::from(first), rest)
+        } else {
+            let count = usize::from(first & 0x7F);
+            if count == 0 || count > 4 || rest.len() < count {
+                return None;
+            }
+            let (bytes, rest) = rest.split_at(count);
+            let len = bytes
+                .iter()
+                .fold(0usize, |len, &byte| (len << 8) | usize::from(byte));
+            (len, rest)
+        };
+        if rest.len() < len {
+            return None;
+        }
+        let (contents, rest) = rest.split_at(len);
+        self.0 = rest;
+        Some(contents)
+    }
+}
+
+/// RFC 7638 JWK thumbprint: SHA-256 over the required members in
+/// lexicographic order.
+fn thumbprint(params: &AlgorithmParameters) -> String {
+    let canonical = match params {
+        AlgorithmParameters::RSA(rsa) => {
+            format!(r#"{{"e":"{}","kty":"RSA","n":"{}"}}"#, rsa.e, rsa.n)
+        }
+        AlgorithmParameters::OctetKeyPair(okp) => {
+            format!(r#"{{"crv":"Ed25519","kty":"OKP","x":"{}"}}"#, okp.x)
+        }
+        _ => String::new(),
+    };
+    Base64UrlUnpadded::encode_string(&Sha256::digest(canonical.as_bytes()))
+}
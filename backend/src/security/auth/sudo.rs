@@ -0,0 +1,68 @@
+//! Sudo mode: a short window, opened by re-entering the password, in which
+//! a session may perform destructive admin actions. A stolen session token
+//! alone is not enough to delete content or manage accounts.
+
+use super::*;
+use crate::models::{api_error_with_code, AppError};
+
+/// How long sudo mode lasts after the password was confirmed.
+pub const SUDO_MODE_TTL_SECONDS: i64 = 10 * 60;
+
+/// `code` of the 403 returned when an action needs sudo mode.
+pub const SUDO_REQUIRED_CODE: &str = "sudo_required";
+
+impl Claims {
+    /// Whether the token is in sudo mode at `now`.
+    pub fn in_sudo_mode(&self, now: DateTime<Utc>) -> bool {
+        self.sudo_until
+            .is_some_and(|until| until as i64 > now.timestamp())
+    }
+
+    /// Fails with the `sudo_required` 403 unless the token is in sudo mode.
+    /// For handlers where only some requests need it; otherwise use
+    /// [`RequireSudo`].
+    pub fn require_sudo(&self) -> Result<(), AppError> {
+        if self.in_sudo_mode(Utc::now()) {
+            Ok(())
+        } else {
+            Err(api_error_with_code(
+                StatusCode::FORBIDDEN,
+                SUDO_REQUIRED_CODE,
+                "Confirm your password to continue",
+            ))
+        }
+    }
+
+    /// The same claims in sudo mode for [`SUDO_MODE_TTL_SECONDS`] from
+    /// `now`, but never past the token's own expiry.
+    pub fn with_sudo(&self, now: DateTime<Utc>) -> Claims {
+        let until = (now.timestamp() + SUDO_MODE_TTL_SECONDS).min(self.exp as i64);
+        Claims {
+            sudo_until: usize::try_from(until).ok(),
+            ..self.clone()
+        }
+    }
+}
+
+/// Extractor for destructive admin actions: the caller's [`Claims`],
+/// provided the session is in sudo mode.
+///
+/// Otherwise rejects with 403 and `code: "sudo_required"`, telling the
+/// client to ask for the password, call `POST /api/auth/sudo` and retry.
+/// API keys never carry sudo mode, so these actions need a login session.
+pub struct RequireSudo(pub Claims);
+
+impl<S> FromRequestParts<S> for RequireSudo
+where
+    S: Send + Sync,
+    DbPool: FromRef<S>,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let claims = Claims::from_request_parts(parts, state).await?;
+
+        claims.require_sudo()?;
+        Ok(RequireSudo(claims))
+    }
+}
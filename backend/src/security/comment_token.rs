@@ -0,0 +1,145 @@
+//! Anti-spam tokens for guest comment forms.
+//!
+//! Most spam bots post a comment the moment they have loaded the page. The
+//! comment form therefore fetches a token recording when it was shown, and a
+//! guest submission must send it back: one that is younger than
+//! `COMMENT_MIN_FORM_SECONDS` or older than [`MAX_TOKEN_AGE`] is refused.
+//!
+//! Tokens are stateless and signed like CSRF tokens (see
+//! [`crate::security::csrf`]).
+//!
+//! # Token Format
+//! `c1|issued_at|nonce|base64url(signature)`
+
+use chrono::{DateTime, Duration, Utc};
+use uuid::Uuid;
+
+use crate::security::csrf;
+
+/// Current token format version; also keeps CSRF tokens from verifying.
+const TOKEN_VERSION: &str = "c1";
+
+/// How long a form may stay open before its token runs out.
+pub const MAX_TOKEN_AGE: Duration = Duration::hours(1);
+
+/// Why a token was refused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenError {
+    /// Missing, malformed or not signed by this server.
+    Invalid,
+    /// Sent back sooner than the minimum form time.
+    TooFast,
+    /// Older than [`MAX_TOKEN_AGE`].
+    Expired,
+}
+
+/// Issues a token for a form shown now.
+pub fn issue_comment_token() -> Result<String, String> {
+    issue_comment_token_at(Utc::now())
+}
+
+/// Issues a token for a form shown at `issued_at`.
+pub fn issue_comment_token_at(issued_at: DateTime<Utc>) -> Result<String, String> {
+    let nonce = Uuid::new_v4().simple().to_string();
+    let payload = format!("{TOKEN_VERSION}|{}|{nonce}", issued_at.timestamp());
+    let signature = csrf::sign_payload(&payload)?;
+    Ok(format!("{payload}|{signature}"))
+}
+
+/// Checks a token sent back at `now`, which must be at least `min_age` and
+/// at most [`MAX_TOKEN_AGE`] after it was issued.
+pub fn verify_comment_token(
+    token: &str,
+    min_age: Duration,
+    now: DateTime<Utc>,
+) -> Result<(), TokenError> {
+    let mut parts = token.trim().split('|');
+    let (Some(version), Some(issued_at), Some(nonce), Some(signature), None) = (
+        parts.next(),
+        parts.next(),
+        parts.next(),
+        parts.next(),
+        parts.next(),
+    ) else {
+        return Err(TokenError::Invalid);
+    };
+    if version != TOKEN_VERSION || nonce.len() < 16 {
+        return Err(TokenError::Invalid);
+    }
+
+    let payload = format!("{version}|{issued_at}|{nonce}");
+    if !csrf::signature_matches(&payload, signature).unwrap_or(false) {
+        return Err(TokenError::Invalid);
+    }
+
+    let issued_at = issued_at
+        .parse::<i64>()
+        .ok()
+        .and_then(|seconds| DateTime::from_timestamp(seconds, 0))
+        .ok_or(TokenError::Invalid)?;
+    let age = now.signed_duration_since(issued_at);
+    if age < min_age {
+        return Err(TokenError::TooFast);
+    }
+    if age > MAX_TOKEN_AGE {
+        return Err(TokenError::Expired);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn init_secret() {
+        let _ = csrf::init_csrf_secret(
+            "this_is_a_very_long_secret_key_for_testing_purposes_only_at_least_32_bytes",
+        );
+    }
+
+    #[test]
+    fn accepts_tokens_between_the_minimum_form_time_and_an_hour() {
+        init_secret();
+        let issued = Utc::now();
+        let token = issue_comment_token_at(issued).unwrap();
+        let min_age = Duration::seconds(3);
+
+        assert_eq!(
+            verify_comment_token(&token, min_age, issued + Duration::seconds(1)),
+            Err(TokenError::TooFast)
+        );
+        assert_eq!(
+            verify_comment_token(&token, min_age, issued + Duration::seconds(3)),
+            Ok(())
+        );
+        assert_eq!(
+            verify_comment_token(&token, min_age, issued + Duration::minutes(61)),
+            Err(TokenError::Expired)
+        );
+    }
+
+    #[test]
+    fn rejects_tampered_and_foreign_tokens() {
+        init_secret();
+        let issued = Utc::now() - Duration::seconds(30);
+        let token = issue_comment_token_at(issued).unwrap();
+
+        // Moving the timestamp back invalidates the signature.
+        let parts: Vec<&str> = token.split('|').collect();
+        let backdated = format!(
+            "{}|{}|{}|{}",
+            parts[0],
+            issued.timestamp() - 600,
+            parts[2],
+            parts[3]
+        );
+        let csrf_token = csrf::issue_csrf_token("guest", Duration::hours(1)).unwrap();
+        for token in [backdated.as_str(), csrf_token.as_str(), "", "c1|1|2|3"] {
+            assert_eq!(
+                verify_comment_token(token, Duration::zero(), Utc::now()),
+                Err(TokenError::Invalid),
+                "{token}"
+            );
+        }
+    }
+}
@@ -6,7 +6,8 @@
 //! # Security Features
 //! - HMAC-SHA256 signed tokens (prevents forgery)
 //! - Per-user token binding (prevents token theft across accounts)
-//! - Time-based expiration (6-hour TTL)
+//! - Time-based expiration (tracks the session lifetime, optionally capped by
+//!   `CSRF_TOKEN_TTL_HOURS`)
 //! - Random nonce for uniqueness
 //! - Version support for token format evolution
 //! - Constant-time signature comparison (prevents timing attacks)
@@ -21,8 +22,8 @@
 //!
 //! ## Initialization
 //! ```rust,no_run
-//! use minos_backend::security::csrf;
-//! csrf::init_csrf_secret().expect("Failed to initialize CSRF secret");
+//! use minos_backend::{config, security::csrf};
+//! csrf::init_csrf_secret(&config::get().csrf_secret).expect("Failed to initialize CSRF secret");
 //! ```
 //!
 //! ## Protection
@@ -41,36 +42,29 @@ use axum::{
     http::{
         header::{HeaderName, SET_COOKIE},
         request::Parts,
-        HeaderMap, HeaderValue, Method, StatusCode,
+        HeaderMap, HeaderValue, Method,
     },
-    Json,
 };
 use axum_extra::extract::cookie::{Cookie, CookieJar, SameSite};
 use base64ct::{Base64UrlUnpadded, Encoding};
 use chrono::{Duration, Utc};
 use hmac::{digest::KeyInit, Hmac, Mac};
 use sha2::Sha256;
-use std::{collections::HashSet, env, sync::OnceLock};
+use std::{collections::HashSet, sync::OnceLock};
 use time::{Duration as TimeDuration, OffsetDateTime};
 use uuid::Uuid;
 
-use crate::{models::ErrorResponse, security::auth};
+use crate::security::auth;
 
 /// HMAC-SHA256 type alias for token signing
 type HmacSha256 = Hmac<Sha256>;
 
-/// Environment variable name for the CSRF secret
-const CSRF_SECRET_ENV: &str = "CSRF_SECRET";
-
 /// Name of the CSRF cookie
 const CSRF_COOKIE_NAME: &str = "ltcms_csrf";
 
 /// Name of the CSRF HTTP header
 const CSRF_HEADER_NAME: &str = "x-csrf-token";
 
-/// CSRF token time-to-live in seconds (6 hours)
-const CSRF_TOKEN_TTL_SECONDS: i64 = 6 * 60 * 60;
-
 /// Minimum length for CSRF secret (256 bits recommended)
 const CSRF_MIN_SECRET_LENGTH: usize = 32;
 
@@ -80,57 +74,57 @@ const CSRF_VERSION: &str = "v1";
 /// Global storage for the CSRF secret key
 static CSRF_SECRET: OnceLock<Vec<u8>> = OnceLock::new();
 
-/// Initializes the CSRF secret from the environment variable.
-///
-/// This function must be called once at application startup before any
-/// CSRF operations. It validates the secret for security and stores it
-/// in global state.
+/// Validates a candidate CSRF secret without installing it.
 ///
 /// # Security Validation
 /// The secret is checked for:
-/// - Presence (not missing)
 /// - Minimum length (32 bytes for adequate entropy)
 /// - Character diversity (at least 10 unique characters)
+pub fn validate_csrf_secret(secret: &str) -> Result<(), String> {
+    let trimmed = secret.trim();
+
+    // Validate minimum length requirement
+    if trimmed.len() < CSRF_MIN_SECRET_LENGTH {
+        return Err(format!(
+            "CSRF_SECRET must be at least {CSRF_MIN_SECRET_LENGTH} characters long"
+        ));
+    }
+
+    // Validate entropy requirement (unique characters)
+    let unique_chars = trimmed.chars().collect::<HashSet<_>>().len();
+    if unique_chars < 10 {
+        return Err("CSRF_SECRET must contain at least 10 unique characters".to_string());
+    }
+
+    Ok(())
+}
+
+/// Initializes the CSRF secret.
+///
+/// This function must be called once at application startup before any
+/// CSRF operations. It re-checks the secret with [`validate_csrf_secret`]
+/// and stores it in global state.
 ///
 /// # Returns
 /// - `Ok(())` if the secret was successfully initialized
 /// - `Err(String)` with a descriptive error message if validation fails
 ///
 /// # Errors
-/// - CSRF_SECRET environment variable not set
 /// - Secret is too short (< 32 characters)
 /// - Secret has insufficient entropy (< 10 unique characters)
 /// - Secret was already initialized (can only be called once)
 ///
 /// # Example
 /// ```rust,no_run
-/// use minos_backend::security::csrf;
-/// csrf::init_csrf_secret().expect("Failed to initialize CSRF secret");
+/// use minos_backend::{config, security::csrf};
+/// csrf::init_csrf_secret(&config::get().csrf_secret).expect("Failed to initialize CSRF secret");
 /// ```
-pub fn init_csrf_secret() -> Result<(), String> {
-    // Load secret from environment variable
-    let secret = env::var(CSRF_SECRET_ENV)
-        .map_err(|_| format!("{CSRF_SECRET_ENV} environment variable not set"))?;
-    let trimmed = secret.trim();
-
-    // Validate minimum length requirement
-    if trimmed.len() < CSRF_MIN_SECRET_LENGTH {
-        return Err(format!(
-            "{CSRF_SECRET_ENV} must be at least {CSRF_MIN_SECRET_LENGTH} characters long"
-        ));
-    }
-
-    // Validate entropy requirement (unique characters)
-    let unique_chars = trimmed.chars().collect::<HashSet<_>>().len();
-    if unique_chars < 10 {
-        return Err(format!(
-            "{CSRF_SECRET_ENV} must contain at least 10 unique characters"
-        ));
-    }
+pub fn init_csrf_secret(secret: &str) -> Result<(), String> {
+    validate_csrf_secret(secret)?;
 
     // Store secret in thread-safe static storage
     CSRF_SECRET
-        .set(trimmed.as_bytes().to_vec())
+        .set(secret.trim().as_bytes().to_vec())
         .map_err(|_| "CSRF secret already initialized".to_string())?;
 
     Ok(())
@@ -150,13 +144,24 @@ fn get_secret() -> &'static [u8] {
         .as_slice()
 }
 
+/// Lifetime of a CSRF token issued alongside a session with `session_ttl`
+/// left: the session's remaining lifetime, capped by `CSRF_TOKEN_TTL_HOURS`
+/// when that is set.
+pub fn token_ttl(session_ttl: Duration) -> Duration {
+    match crate::config::get().csrf_token_ttl_hours {
+        Some(hours) => session_ttl.min(Duration::hours(i64::from(hours))),
+        None => session_ttl,
+    }
+}
+
 /// Issues a new CSRF token for a user.
 ///
 /// Creates a cryptographically signed token bound to the user's identity.
-/// The token is valid for 6 hours and includes a random nonce for uniqueness.
+/// The token is valid for `ttl` and includes a random nonce for uniqueness.
 ///
 /// # Arguments
 /// * `username` - The username to bind the token to
+/// * `ttl` - Token lifetime; callers pass the remaining session lifetime
 ///
 /// # Returns
 /// - `Ok(String)` - The complete CSRF token (v1 format)
@@ -183,7 +188,7 @@ fn get_secret() -> &'static [u8] {
 /// - Username is empty
 /// - Failed to compute expiration timestamp
 /// - HMAC initialization fails
-pub fn issue_csrf_token(username: &str) -> Result<String, String> {
+pub fn issue_csrf_token(username: &str, ttl: Duration) -> Result<String, String> {
     // Validate input
     if username.is_empty() {
         return Err("Username required for CSRF token".to_string());
@@ -191,7 +196,7 @@ pub fn issue_csrf_token(username: &str) -> Result<String, String> {
 
     // Calculate token expiration
     let expiry = Utc::now()
-        .checked_add_signed(Duration::seconds(CSRF_TOKEN_TTL_SECONDS))
+        .checked_add_signed(token_ttl(ttl))
         .ok_or_else(|| "Failed to compute CSRF expiry".to_string())?
         .timestamp();
 
@@ -206,10 +211,7 @@ pub fn issue_csrf_token(username: &str) -> Result<String, String> {
     let versioned_payload = format!("{CSRF_VERSION}|{payload}");
 
     // Create HMAC signature
-    let mut mac = HmacSha256::new_from_slice(get_secret())
-        .map_err(|_| "Failed to initialize CSRF HMAC".to_string())?;
-    mac.update(versioned_payload.as_bytes());
-    let signature = Base64UrlUnpadded::encode_string(&mac.finalize().into_bytes());
+    let signature = sign_payload(&versioned_payload)?;
 
     // Return complete token
     Ok(format!("{versioned_payload}|{signature}"))
@@ -303,22 +305,42 @@ fn validate_csrf_token(token: &str, expected_username: &str) -> Result<(), Strin
     // Verify HMAC signature
     let versioned_payload = format!("{version}|{username_b64}|{expiry}|{nonce}");
 
+    if !signature_matches(&versioned_payload, signature)? {
+        return Err("CSRF signature mismatch".to_string());
+    }
+
+    Ok(())
+}
+
+/// Base64URL-encoded HMAC-SHA256 of `payload` under the CSRF secret.
+///
+/// Also signs the other stateless tokens the server hands out (see
+/// [`crate::security::comment_token`] and [`crate::security::preview_token`]);
+/// their payloads start with their own version tag, so one kind of token
+/// never verifies as another.
+pub(crate) fn sign_payload(payload: &str) -> Result<String, String> {
+    let mut mac = HmacSha256::new_from_slice(get_secret())
+        .map_err(|_| "Failed to initialize CSRF HMAC".to_string())?;
+    mac.update(payload.as_bytes());
+    Ok(Base64UrlUnpadded::encode_string(
+        &mac.finalize().into_bytes(),
+    ))
+}
+
+/// Whether `signature` is [`sign_payload`]'s signature of `payload`,
+/// compared in constant time.
+pub(crate) fn signature_matches(payload: &str, signature: &str) -> Result<bool, String> {
     let mut mac = HmacSha256::new_from_slice(get_secret())
         .map_err(|_| "Failed to initialize CSRF HMAC".to_string())?;
-    mac.update(versioned_payload.as_bytes());
+    mac.update(payload.as_bytes());
     let expected_signature = mac.finalize().into_bytes();
 
     let provided_signature = Base64UrlUnpadded::decode_vec(signature)
         .map_err(|_| "Invalid CSRF signature".to_string())?;
 
     // Constant-time signature comparison
-    if expected_signature.len() != provided_signature.len()
-        || !subtle_equals(&expected_signature, &provided_signature)
-    {
-        return Err("CSRF signature mismatch".to_string());
-    }
-
-    Ok(())
+    Ok(expected_signature.len() == provided_signature.len()
+        && subtle_equals(&expected_signature, &provided_signature))
 }
 
 /// Performs constant-time equality comparison on byte slices.
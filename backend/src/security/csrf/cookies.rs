@@ -5,12 +5,13 @@ use super::*;
 /// # Arguments
 /// * `headers` - Mutable reference to the response HeaderMap
 /// * `token` - The CSRF token to include in the cookie
+/// * `ttl` - Cookie lifetime, the same one the token was issued with
 ///
 /// # Error Handling
 /// Logs an error if cookie serialization fails (should never happen)
-pub fn append_csrf_cookie(headers: &mut HeaderMap, token: &str) {
+pub fn append_csrf_cookie(headers: &mut HeaderMap, token: &str, ttl: Duration) {
     // Build cookie with security flags
-    let cookie = build_csrf_cookie(token);
+    let cookie = build_csrf_cookie(token, ttl);
 
     // Append to Set-Cookie header
     if let Ok(value) = HeaderValue::from_str(&cookie.to_string()) {
@@ -48,21 +49,22 @@ pub fn append_csrf_removal(headers: &mut HeaderMap) {
 /// A Cookie configured for CSRF protection
 ///
 /// # Security Flags
-/// - SameSite=Strict: Prevents cross-site cookie sending (strict CSRF protection)
+/// - SameSite: `CSRF_COOKIE_SAMESITE`, Strict unless configured otherwise
 /// - HttpOnly=false: Allows JavaScript read access (needed for header submission)
-/// - Secure: HTTPS-only (when AUTH_COOKIE_SECURE is not false)
+/// - Secure: HTTPS-only (when AUTH_COOKIE_SECURE is not false, and always
+///   for SameSite=None)
 /// - Path=/: Available to all routes
-/// - Max-Age: 6 hours (matches token expiration)
-pub(super) fn build_csrf_cookie(token: &str) -> Cookie<'static> {
+/// - Max-Age: `ttl`, capped like the token itself by [`token_ttl`]
+pub(super) fn build_csrf_cookie(token: &str, ttl: Duration) -> Cookie<'static> {
     // Build cookie with security settings
     let mut builder = Cookie::build((CSRF_COOKIE_NAME, token.to_owned()))
         .path("/")
-        .same_site(SameSite::Strict)
-        .max_age(TimeDuration::seconds(CSRF_TOKEN_TTL_SECONDS))
+        .same_site(cookie_same_site())
+        .max_age(TimeDuration::seconds(token_ttl(ttl).num_seconds()))
         .http_only(false); // Must be false for JavaScript to read and submit in header
 
     // Add Secure flag in production (HTTPS only)
-    if auth::cookies_should_be_secure() {
+    if cookie_must_be_secure() {
         builder = builder.secure(true);
     }
 
@@ -83,15 +85,25 @@ pub(super) fn build_csrf_removal() -> Cookie<'static> {
     // Build cookie with expiration in the past to trigger removal
     let mut builder = Cookie::build((CSRF_COOKIE_NAME, ""))
         .path("/")
-        .same_site(SameSite::Strict)
+        .same_site(cookie_same_site())
         .expires(OffsetDateTime::UNIX_EPOCH)
         .max_age(TimeDuration::seconds(0))
         .http_only(false);
 
     // Match security settings of CSRF cookie
-    if auth::cookies_should_be_secure() {
+    if cookie_must_be_secure() {
         builder = builder.secure(true);
     }
 
     builder.build()
 }
+
+fn cookie_same_site() -> SameSite {
+    crate::config::get().csrf_cookie_samesite
+}
+
+/// Browsers ignore `SameSite=None` cookies without `Secure`, so that
+/// policy always sets it.
+fn cookie_must_be_secure() -> bool {
+    auth::cookies_should_be_secure() || cookie_same_site() == SameSite::None
+}
@@ -1,4 +1,7 @@
 use super::*;
+use crate::models::{forbidden, AppError, RejectionReason, RejectionSource};
+use crate::security::rejections;
+use axum::extract::FromRef;
 
 /// AXUM extractor for CSRF protection.
 ///
@@ -7,7 +10,7 @@ use super::*;
 ///
 /// # Validation Process
 /// 1. Skip validation for safe HTTP methods
-/// 2. Ensure user is authenticated (extract Claims)
+/// 2. Ensure user is authenticated (extract Claims); API-key requests pass
 /// 3. Extract token from x-csrf-token header
 /// 4. Extract token from cookie
 /// 5. Verify header and cookie tokens match (double-submit pattern)
@@ -108,14 +111,27 @@ pub(super) fn validate_browser_origin(headers: &HeaderMap) -> Result<(), String>
     Err("Cross-origin request blocked".to_string())
 }
 
+/// Classifies a [`validate_csrf_token`] failure for the rejection counters.
+pub(super) fn token_failure_reason(message: &str) -> RejectionReason {
+    if message.contains("expired") {
+        RejectionReason::Expired
+    } else if message.contains("signature") {
+        RejectionReason::Signature
+    } else if message.contains("not issued for this account") {
+        RejectionReason::Mismatch
+    } else {
+        RejectionReason::Malformed
+    }
+}
+
 impl<S> FromRequestParts<S> for CsrfGuard
 where
     S: Send + Sync,
     crate::db::DbPool: axum::extract::FromRef<S>,
 {
-    type Rejection = (StatusCode, Json<ErrorResponse>);
+    type Rejection = AppError;
 
-    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
         // Step 1: Method Filter. CSRF is only required for state-changing operations.
         if matches!(
             parts.method,
@@ -128,10 +144,18 @@ where
         let claims_result = if let Some(existing) = parts.extensions.get::<auth::Claims>() {
             Ok(existing.clone())
         } else {
-            auth::Claims::from_request_parts(parts, _state).await
+            auth::Claims::from_request_parts(parts, state).await
+        };
+        let pool = crate::db::DbPool::from_ref(state);
+        let reject = |reason: RejectionReason, error: String| {
+            rejections::record(&pool, RejectionSource::Csrf, reason);
+            forbidden(error)
         };
 
         let claims = match claims_result {
+            // API keys travel in a header a cross-site page cannot set, and
+            // there is no session cookie to ride.
+            Ok(_) if parts.extensions.get::<auth::ApiKeyAuth>().is_some() => return Ok(Self),
             Ok(claims) => {
                 // User is logged in -> Enforce strict CSRF checks.
                 parts.extensions.insert(claims.clone());
@@ -145,7 +169,7 @@ where
                 // browser-origin check: if the request carries an Origin (or
                 // Referer), it must be same-host or a configured frontend.
                 if let Err(reason) = validate_browser_origin(&parts.headers) {
-                    return Err((StatusCode::FORBIDDEN, Json(ErrorResponse { error: reason })));
+                    return Err(reject(RejectionReason::Origin, reason));
                 }
                 return Ok(Self);
             }
@@ -157,37 +181,28 @@ where
             .get(HeaderName::from_static(CSRF_HEADER_NAME))
             .and_then(|value| value.to_str().ok())
             .ok_or_else(|| {
-                (
-                    StatusCode::FORBIDDEN,
-                    Json(ErrorResponse {
-                        error: "Missing CSRF token header".to_string(),
-                    }),
+                reject(
+                    RejectionReason::Missing,
+                    "Missing CSRF token header".to_string(),
                 )
             })?;
 
         let jar = CookieJar::from_headers(&parts.headers);
-        let cookie = jar.get(CSRF_COOKIE_NAME).ok_or_else(|| {
-            (
-                StatusCode::FORBIDDEN,
-                Json(ErrorResponse {
-                    error: "Missing CSRF cookie".to_string(),
-                }),
-            )
-        })?;
+        let cookie = jar
+            .get(CSRF_COOKIE_NAME)
+            .ok_or_else(|| reject(RejectionReason::Missing, "Missing CSRF cookie".to_string()))?;
 
         // Step 4: Double-Submit Validation. Ensure the tokens match.
         if cookie.value() != header_value {
-            return Err((
-                StatusCode::FORBIDDEN,
-                Json(ErrorResponse {
-                    error: "CSRF token mismatch".to_string(),
-                }),
+            return Err(reject(
+                RejectionReason::Mismatch,
+                "CSRF token mismatch".to_string(),
             ));
         }
 
         // Step 5: Master Validation. Verify signature, expiration, and user binding.
         validate_csrf_token(header_value, &claims.sub)
-            .map_err(|err| (StatusCode::FORBIDDEN, Json(ErrorResponse { error: err })))?;
+            .map_err(|err| reject(token_failure_reason(&err), err))?;
 
         Ok(Self)
     }
@@ -4,11 +4,9 @@ use super::*;
 fn test_csrf_secret_initialization() {
     // Ensure secret is initialized for tests
     if CSRF_SECRET.get().is_none() {
-        env::set_var(
-            "CSRF_SECRET",
+        let _ = init_csrf_secret(
             "this_is_a_very_long_secret_key_for_testing_purposes_only_at_least_32_bytes",
         );
-        let _ = init_csrf_secret();
     }
 
     assert!(CSRF_SECRET.get().is_some());
@@ -17,15 +15,13 @@ fn test_csrf_secret_initialization() {
 #[test]
 fn test_issue_csrf_token() {
     if CSRF_SECRET.get().is_none() {
-        env::set_var(
-            "CSRF_SECRET",
+        let _ = init_csrf_secret(
             "this_is_a_very_long_secret_key_for_testing_purposes_only_at_least_32_bytes",
         );
-        let _ = init_csrf_secret();
     }
 
     let username = "testuser";
-    let token = issue_csrf_token(username).expect("Failed to issue token");
+    let token = issue_csrf_token(username, Duration::hours(6)).expect("Failed to issue token");
 
     let parts: Vec<&str> = token.split('|').collect();
     // v1|base64(username)|expiry|nonce|signature
@@ -40,15 +36,13 @@ fn test_issue_csrf_token() {
 #[test]
 fn test_validate_csrf_token_valid() {
     if CSRF_SECRET.get().is_none() {
-        env::set_var(
-            "CSRF_SECRET",
+        let _ = init_csrf_secret(
             "this_is_a_very_long_secret_key_for_testing_purposes_only_at_least_32_bytes",
         );
-        let _ = init_csrf_secret();
     }
 
     let username = "valid_user";
-    let token = issue_csrf_token(username).unwrap();
+    let token = issue_csrf_token(username, Duration::hours(6)).unwrap();
 
     assert!(validate_csrf_token(&token, username).is_ok());
 }
@@ -56,34 +50,32 @@ fn test_validate_csrf_token_valid() {
 #[test]
 fn test_validate_csrf_token_wrong_user() {
     if CSRF_SECRET.get().is_none() {
-        env::set_var(
-            "CSRF_SECRET",
+        let _ = init_csrf_secret(
             "this_is_a_very_long_secret_key_for_testing_purposes_only_at_least_32_bytes",
         );
-        let _ = init_csrf_secret();
     }
 
-    let token = issue_csrf_token("user_a").unwrap();
+    let token = issue_csrf_token("user_a", Duration::hours(6)).unwrap();
     let result = validate_csrf_token(&token, "user_b");
 
     assert!(result.is_err());
+    let err = result.unwrap_err();
+    assert_eq!(err, "CSRF token not issued for this account");
     assert_eq!(
-        result.unwrap_err(),
-        "CSRF token not issued for this account"
+        guard::token_failure_reason(&err),
+        crate::models::RejectionReason::Mismatch
     );
 }
 
 #[test]
 fn test_validate_csrf_token_tampered() {
     if CSRF_SECRET.get().is_none() {
-        env::set_var(
-            "CSRF_SECRET",
+        let _ = init_csrf_secret(
             "this_is_a_very_long_secret_key_for_testing_purposes_only_at_least_32_bytes",
         );
-        let _ = init_csrf_secret();
     }
 
-    let token = issue_csrf_token("test_tamper").unwrap();
+    let token = issue_csrf_token("test_tamper", Duration::hours(6)).unwrap();
     let mut parts: Vec<&str> = token.split('|').collect();
 
     // Tamper with the nonce (part 3)
@@ -100,17 +92,20 @@ fn test_validate_csrf_token_tampered() {
 
     let result = validate_csrf_token(&tampered_token, "test_tamper");
     assert!(result.is_err());
-    assert_eq!(result.unwrap_err(), "CSRF signature mismatch");
+    let err = result.unwrap_err();
+    assert_eq!(err, "CSRF signature mismatch");
+    assert_eq!(
+        guard::token_failure_reason(&err),
+        crate::models::RejectionReason::Signature
+    );
 }
 
 #[test]
 fn test_validate_csrf_token_expired() {
     if CSRF_SECRET.get().is_none() {
-        env::set_var(
-            "CSRF_SECRET",
+        let _ = init_csrf_secret(
             "this_is_a_very_long_secret_key_for_testing_purposes_only_at_least_32_bytes",
         );
-        let _ = init_csrf_secret();
     }
 
     // Manually construct an expired token
@@ -131,7 +126,12 @@ fn test_validate_csrf_token_expired() {
 
     let result = validate_csrf_token(&token, username);
     assert!(result.is_err());
-    assert_eq!(result.unwrap_err(), "CSRF token expired");
+    let err = result.unwrap_err();
+    assert_eq!(err, "CSRF token expired");
+    assert_eq!(
+        guard::token_failure_reason(&err),
+        crate::models::RejectionReason::Expired
+    );
 }
 
 #[test]
@@ -202,12 +202,12 @@ fn browser_origin_check_falls_back_to_referer() {
 #[test]
 fn test_build_csrf_cookie() {
     let token = "test_token_value";
-    let cookie = build_csrf_cookie(token);
+    let cookie = build_csrf_cookie(token, Duration::hours(30));
 
     assert_eq!(cookie.name(), CSRF_COOKIE_NAME);
     assert_eq!(cookie.value(), token);
     assert_eq!(cookie.path(), Some("/"));
     assert_eq!(cookie.same_site(), Some(SameSite::Strict));
     assert_eq!(cookie.http_only(), Some(false));
-    assert!(cookie.max_age().is_some());
+    assert_eq!(cookie.max_age(), Some(TimeDuration::hours(30)));
 }
@@ -6,7 +6,12 @@
 use sha2::{Digest, Sha256};
 
 pub mod auth; // JWT token lifecycle and verification
+pub mod comment_token; // Timed anti-spam tokens for guest comment forms
 pub mod csrf; // Double-submit cookie CSRF protection
+pub mod outbound; // SSRF guard for fetching user-supplied URLs
+pub mod preview_token; // Signed preview links for unpublished pages and posts
+pub mod rejections; // Counters of rejected CSRF checks, tokens and logins
+pub mod totp; // One-time codes and recovery codes for two-factor login
 
 /// Returns the lowercase hex-encoded SHA-256 digest of `data`.
 ///
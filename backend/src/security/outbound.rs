@@ -0,0 +1,194 @@
+//! Outbound Request Guard
+//!
+//! Requests the server makes to URLs supplied by users (importing an image
+//! from a URL) must not reach the server's own network: loopback services,
+//! cloud metadata endpoints or hosts on the private LAN. [`resolve_public`]
+//! vets a URL and resolves it up front; the caller then connects only to the
+//! addresses it returned, so a DNS answer can't change between the check and
+//! the connection.
+
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use url::Url;
+
+/// Why a URL may not be fetched.
+#[derive(Debug, PartialEq)]
+pub enum OutboundError {
+    /// Not an `http` or `https` URL.
+    UnsupportedScheme(String),
+    /// No host, or user info in the URL.
+    InvalidUrl,
+    /// The host name did not resolve.
+    Unresolvable(String),
+    /// The host is, or resolves to, a non-public address.
+    Forbidden(IpAddr),
+}
+
+impl fmt::Display for OutboundError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OutboundError::UnsupportedScheme(scheme) => {
+                write!(f, "only http and https URLs can be fetched, not {scheme}")
+            }
+            OutboundError::InvalidUrl => write!(f, "the URL needs a host and no credentials"),
+            OutboundError::Unresolvable(host) => write!(f, "host {host} could not be resolved"),
+            OutboundError::Forbidden(ip) => {
+                write!(f, "{ip} is not a public address")
+            }
+        }
+    }
+}
+
+impl std::error::Error for OutboundError {}
+
+/// Checks that `url` is an http(s) URL whose host only resolves to public
+/// addresses, and returns those addresses.
+pub async fn resolve_public(url: &Url) -> Result<Vec<SocketAddr>, OutboundError> {
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(OutboundError::UnsupportedScheme(url.scheme().to_string()));
+    }
+    if !url.username().is_empty() || url.password().is_some() {
+        return Err(OutboundError::InvalidUrl);
+    }
+    let host = url.host_str().ok_or(OutboundError::InvalidUrl)?;
+    let port = url
+        .port_or_known_default()
+        .ok_or(OutboundError::InvalidUrl)?;
+
+    // IPv6 literals keep their brackets in `host_str`
+    let addrs: Vec<SocketAddr> = match host.trim_matches(['[', ']']).parse::<IpAddr>() {
+        Ok(ip) => vec![SocketAddr::new(ip, port)],
+        Err(_) => tokio::net::lookup_host((host, port))
+            .await
+            .map_err(|_| OutboundError::Unresolvable(host.to_string()))?
+            .collect(),
+    };
+    if addrs.is_empty() {
+        return Err(OutboundError::Unresolvable(host.to_string()));
+    }
+    // Every address must be public: the connection may use any of them
+    if let Some(addr) = addrs.iter().find(|addr| !is_public_ip(addr.ip())) {
+        return Err(OutboundError::Forbidden(addr.ip()));
+    }
+    Ok(addrs)
+}
+
+/// Whether `ip` is a globally routable unicast address, i.e. none of
+/// loopback, private, link-local, shared (CGNAT), documentation,
+/// benchmarking, multicast or reserved space. IPv6 addresses that embed an
+/// IPv4 address (mapped, NAT64, 6to4) are judged by that address.
+pub fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_ipv4(ip),
+        IpAddr::V6(ip) => is_public_ipv6(ip),
+    }
+}
+
+fn is_public_ipv4(ip: Ipv4Addr) -> bool {
+    let [a, b, c, _] = ip.octets();
+    !(a == 0 // "this network"
+        || a == 10
+        || a == 127
+        || (a == 100 && (64..128).contains(&b)) // shared address space
+        || (a == 169 && b == 254)
+        || (a == 172 && (16..32).contains(&b))
+        || (a == 192 && b == 0 && c == 0) // IETF protocol assignments
+        || (a == 192 && b == 0 && c == 2)
+        || (a == 192 && b == 168)
+        || (a == 198 && (18..20).contains(&b)) // benchmarking
+        || (a == 198 && b == 51 && c == 100)
+        || (a == 203 && b == 0 && c == 113)
+        || a >= 224) // multicast, reserved and broadcast
+}
+
+fn is_public_ipv6(ip: Ipv6Addr) -> bool {
+    if let Some(mapped) = ip.to_ipv4_mapped() {
+        return is_public_ipv4(mapped);
+    }
+    let segments = ip.segments();
+    // NAT64 (64:ff9b::/96) and 6to4 (2002::/16) reach IPv4 hosts
+    if segments[..6] == [0x64, 0xff9b, 0, 0, 0, 0] {
+        return is_public_ipv4(Ipv4Addr::from(
+            (u32::from(segments[6]) << 16) | u32::from(segments[7]),
+        ));
+    }
+    if segments[0] == 0x2002 {
+        return is_public_ipv4(Ipv4Addr::from(
+            (u32::from(segments[1]) << 16) | u32::from(segments[2]),
+        ));
+    }
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_multicast()
+        || segments[0] & 0xfe00 == 0xfc00 // unique local
+        || segments[0] & 0xffc0 == 0xfe80 // link-local
+        || segments[0] & 0xffc0 == 0xfec0 // site-local
+        || (segments[0] == 0x2001 && segments[1] == 0x0db8) // documentation
+        || segments[..6] == [0, 0, 0, 0, 0, 0]) // IPv4-compatible
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_globally_routable_addresses_are_public() {
+        for public in [
+            "93.184.215.14",
+            "1.1.1.1",
+            "2606:4700::1111",
+            "::ffff:8.8.8.8",
+        ] {
+            assert!(is_public_ip(public.parse().unwrap()), "{public}");
+        }
+        for internal in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "172.31.255.255",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "255.255.255.255",
+            "224.0.0.1",
+            "::1",
+            "::",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+            "64:ff9b::a9fe:a9fe",
+            "2002:c0a8:0101::1",
+        ] {
+            assert!(!is_public_ip(internal.parse().unwrap()), "{internal}");
+        }
+    }
+
+    #[tokio::test]
+    async fn internal_and_non_http_urls_are_refused() {
+        let check = |url: &str| {
+            let url = Url::parse(url).unwrap();
+            async move { resolve_public(&url).await }
+        };
+        assert_eq!(
+            check("ftp://example.com/cat.png").await,
+            Err(OutboundError::UnsupportedScheme("ftp".to_string()))
+        );
+        assert_eq!(
+            check("http://user:pw@example.com/cat.png").await,
+            Err(OutboundError::InvalidUrl)
+        );
+        assert_eq!(
+            check("http://169.254.169.254/latest/meta-data").await,
+            Err(OutboundError::Forbidden("169.254.169.254".parse().unwrap()))
+        );
+        assert_eq!(
+            check("http://[::1]:8489/").await,
+            Err(OutboundError::Forbidden("::1".parse().unwrap()))
+        );
+        assert!(matches!(
+            check("http://localhost/cat.png").await,
+            Err(OutboundError::Forbidden(_))
+        ));
+    }
+}
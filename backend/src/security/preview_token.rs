@@ -0,0 +1,180 @@
+//! Preview links for unpublished pages and posts.
+//!
+//! An editor can hand a reviewer without an account a link that shows one
+//! draft page or post. The token in the link is stateless and signed like
+//! CSRF tokens (see [`crate::security::csrf`]), over the kind and ID of the
+//! page or post, its expiry and the `preview_nonce` stored with it. The
+//! token itself names neither: it only verifies against the one entity it
+//! was issued for, and clearing that entity's nonce revokes every link
+//! issued so far.
+//!
+//! # Token Format
+//! `p1.expires_at.base64url(signature)`, with `.` as the separator so the
+//! token needs no escaping in a query string.
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::security::csrf;
+
+/// Current token format version; also keeps other signed tokens from
+/// verifying.
+const TOKEN_VERSION: &str = "p1";
+
+/// What a preview token was issued for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PreviewKind {
+    Page,
+    Post,
+}
+
+impl PreviewKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PreviewKind::Page => "page",
+            PreviewKind::Post => "post",
+        }
+    }
+}
+
+/// Generates a fresh per-entity nonce.
+pub fn new_nonce() -> String {
+    Uuid::new_v4().simple().to_string()
+}
+
+fn payload(kind: PreviewKind, id: &str, nonce: &str, expires_at: i64) -> String {
+    format!(
+        "{TOKEN_VERSION}|{}|{id}|{nonce}|{expires_at}",
+        kind.as_str()
+    )
+}
+
+/// Issues a token for the page or post `id`, valid until `expires_at`.
+pub fn issue_preview_token(
+    kind: PreviewKind,
+    id: &str,
+    nonce: &str,
+    expires_at: DateTime<Utc>,
+) -> Result<String, String> {
+    let expires_at = expires_at.timestamp();
+    let signature = csrf::sign_payload(&payload(kind, id, nonce, expires_at))?;
+    Ok(format!("{TOKEN_VERSION}.{expires_at}.{signature}"))
+}
+
+/// Whether `token` was issued for the page or post `id` while it had
+/// `nonce`, and has not expired at `now`.
+pub fn verify_preview_token(
+    token: &str,
+    kind: PreviewKind,
+    id: &str,
+    nonce: &str,
+    now: DateTime<Utc>,
+) -> bool {
+    let mut parts = token.trim().split('.');
+    let (Some(TOKEN_VERSION), Some(expires_at), Some(signature), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return false;
+    };
+    let Ok(expires_at) = expires_at.parse::<i64>() else {
+        return false;
+    };
+    if expires_at <= now.timestamp() {
+        return false;
+    }
+    csrf::signature_matches(&payload(kind, id, nonce, expires_at), signature).unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn init_secret() {
+        let _ = csrf::init_csrf_secret(
+            "this_is_a_very_long_secret_key_for_testing_purposes_only_at_least_32_bytes",
+        );
+    }
+
+    #[test]
+    fn tokens_only_verify_for_their_entity_and_nonce_until_they_expire() {
+        init_secret();
+        let now = Utc::now();
+        let nonce = new_nonce();
+        let token = issue_preview_token(
+            PreviewKind::Post,
+            "post-1",
+            &nonce,
+            now + Duration::hours(1),
+        )
+        .unwrap();
+
+        assert!(verify_preview_token(
+            &token,
+            PreviewKind::Post,
+            "post-1",
+            &nonce,
+            now
+        ));
+        assert!(!verify_preview_token(
+            &token,
+            PreviewKind::Post,
+            "post-2",
+            &nonce,
+            now
+        ));
+        assert!(!verify_preview_token(
+            &token,
+            PreviewKind::Page,
+            "post-1",
+            &nonce,
+            now
+        ));
+        assert!(!verify_preview_token(
+            &token,
+            PreviewKind::Post,
+            "post-1",
+            &new_nonce(),
+            now
+        ));
+        assert!(!verify_preview_token(
+            &token,
+            PreviewKind::Post,
+            "post-1",
+            &nonce,
+            now + Duration::hours(2)
+        ));
+    }
+
+    #[test]
+    fn rejects_extended_and_malformed_tokens() {
+        init_secret();
+        let now = Utc::now();
+        let nonce = new_nonce();
+        let token =
+            issue_preview_token(PreviewKind::Page, "about", &nonce, now + Duration::hours(1))
+                .unwrap();
+
+        // Pushing the expiry out invalidates the signature.
+        let parts: Vec<&str> = token.split('.').collect();
+        let extended = format!(
+            "{}.{}.{}",
+            parts[0],
+            (now + Duration::days(30)).timestamp(),
+            parts[2]
+        );
+        let csrf_token = csrf::issue_csrf_token("guest", Duration::hours(1)).unwrap();
+        for token in [
+            extended.as_str(),
+            csrf_token.as_str(),
+            "",
+            "p1.1.2",
+            "p1..x",
+        ] {
+            assert!(
+                !verify_preview_token(token, PreviewKind::Page, "about", &nonce, now),
+                "{token}"
+            );
+        }
+    }
+}
@@ -0,0 +1,67 @@
+//! Rejection Counters
+//!
+//! Every CSRF, token and login rejection is counted twice: as a `metrics`
+//! counter (`security_rejections_total`, labeled by `source` and `reason`)
+//! for whatever recorder the deployment installs, and as a daily row in
+//! `security_counters` behind `GET /api/admin/security/summary`.
+//!
+//! Recording never delays the rejection itself: the database write runs on
+//! a spawned task and its failure is only logged.
+
+use crate::db::DbPool;
+use crate::models::{RejectionReason, RejectionSource};
+use jsonwebtoken::errors::{Error as JwtError, ErrorKind as JwtErrorKind};
+
+/// Days covered by the security summary; older rows are pruned.
+pub const SUMMARY_DAYS: i64 = 30;
+
+/// Counts one rejection.
+pub fn record(pool: &DbPool, source: RejectionSource, reason: RejectionReason) {
+    metrics::counter!(
+        "security_rejections_total",
+        "source" => source.as_str(),
+        "reason" => reason.as_str()
+    )
+    .increment(1);
+
+    let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+        return;
+    };
+    let pool = pool.clone();
+    runtime.spawn(async move {
+        if let Err(err) = crate::repositories::security_counters::increment(
+            &pool,
+            source.as_str(),
+            reason.as_str(),
+        )
+        .await
+        {
+            tracing::warn!("Failed to record security rejection: {}", err);
+        }
+    });
+}
+
+/// Maps a JWT verification failure to a rejection reason.
+pub fn token_error_reason(err: &JwtError) -> RejectionReason {
+    match err.kind() {
+        JwtErrorKind::ExpiredSignature => RejectionReason::Expired,
+        JwtErrorKind::InvalidSignature => RejectionReason::Signature,
+        _ => RejectionReason::Malformed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn jwt_errors_map_to_reasons() {
+        let expired = JwtError::from(JwtErrorKind::ExpiredSignature);
+        let forged = JwtError::from(JwtErrorKind::InvalidSignature);
+        let garbage = JwtError::from(JwtErrorKind::InvalidToken);
+
+        assert_eq!(token_error_reason(&expired), RejectionReason::Expired);
+        assert_eq!(token_error_reason(&forged), RejectionReason::Signature);
+        assert_eq!(token_error_reason(&garbage), RejectionReason::Malformed);
+    }
+}
@@ -0,0 +1,319 @@
+//! Time-based one-time passwords (RFC 6238) for two-factor login.
+//!
+//! Codes use the parameters every authenticator app defaults to: HMAC-SHA1,
+//! 30-second steps and six digits. Secrets are stored encrypted with
+//! AES-256-GCM under a key derived from `TOTP_ENCRYPTION_KEY`; without that
+//! setting, two-factor enrollment is unavailable.
+//!
+//! Recovery codes are random, so a plain SHA-256 digest is enough to store
+//! them; unlike passwords they cannot be guessed from a dictionary.
+
+use super::sha256_hex;
+use base64ct::{Base64, Encoding};
+use ring::{aead, hmac};
+use std::collections::HashSet;
+use std::sync::OnceLock;
+use subtle::ConstantTimeEq;
+
+/// Length of a TOTP step in seconds.
+pub const STEP_SECONDS: u64 = 30;
+/// Digits in a code.
+pub const CODE_DIGITS: usize = 6;
+/// Steps accepted on either side of the current one, for clock drift.
+pub const ALLOWED_DRIFT_STEPS: u64 = 1;
+/// Recovery codes issued when two-factor login is enabled.
+pub const RECOVERY_CODE_COUNT: usize = 10;
+/// Issuer shown by authenticator apps.
+pub const ISSUER: &str = "minos";
+
+const SECRET_BYTES: usize = 20;
+const MIN_KEY_LENGTH: usize = 32;
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+/// Lowercase letters and digits without the easily confused `0 1 l o`.
+const RECOVERY_ALPHABET: &[u8] = b"abcdefghijkmnpqrstuvwxyz23456789";
+const RECOVERY_GROUP_LEN: usize = 5;
+
+static ENCRYPTION_KEY: OnceLock<aead::LessSafeKey> = OnceLock::new();
+
+/// Validates a candidate `TOTP_ENCRYPTION_KEY` without installing it.
+pub fn validate_totp_encryption_key(secret: &str) -> Result<(), String> {
+    let trimmed = secret.trim();
+    if trimmed.len() < MIN_KEY_LENGTH {
+        return Err(format!(
+            "TOTP_ENCRYPTION_KEY must be at least {MIN_KEY_LENGTH} characters long"
+        ));
+    }
+    if trimmed.chars().collect::<HashSet<_>>().len() < 10 {
+        return Err("TOTP_ENCRYPTION_KEY must contain at least 10 unique characters".to_string());
+    }
+    Ok(())
+}
+
+/// Installs the key TOTP secrets are encrypted with. The AES key is the
+/// SHA-256 digest of the configured string.
+pub fn init_totp_encryption_key(secret: &str) -> Result<(), String> {
+    validate_totp_encryption_key(secret)?;
+
+    let digest = ring::digest::digest(&ring::digest::SHA256, secret.trim().as_bytes());
+    let key = aead::UnboundKey::new(&aead::AES_256_GCM, digest.as_ref())
+        .map_err(|_| "Failed to derive TOTP encryption key".to_string())?;
+    ENCRYPTION_KEY
+        .set(aead::LessSafeKey::new(key))
+        .map_err(|_| "TOTP encryption key already initialized".to_string())
+}
+
+/// Whether two-factor enrollment is available.
+pub fn is_configured() -> bool {
+    ENCRYPTION_KEY.get().is_some()
+}
+
+fn encryption_key() -> Result<&'static aead::LessSafeKey, String> {
+    ENCRYPTION_KEY
+        .get()
+        .ok_or_else(|| "TOTP_ENCRYPTION_KEY is not configured".to_string())
+}
+
+/// Generates a new random shared secret.
+pub fn generate_secret() -> Vec<u8> {
+    let mut secret = vec![0u8; SECRET_BYTES];
+    rand::fill(&mut secret[..]);
+    secret
+}
+
+/// Encrypts a secret for storage as `base64(nonce || ciphertext || tag)`.
+pub fn encrypt_secret(secret: &[u8]) -> Result<String, String> {
+    let key = encryption_key()?;
+    let mut nonce = [0u8; aead::NONCE_LEN];
+    rand::fill(&mut nonce[..]);
+
+    let mut sealed = secret.to_vec();
+    key.seal_in_place_append_tag(
+        aead::Nonce::assume_unique_for_key(nonce),
+        aead::Aad::empty(),
+        &mut sealed,
+    )
+    .map_err(|_| "Failed to encrypt TOTP secret".to_string())?;
+
+    let mut stored = nonce.to_vec();
+    stored.extend_from_slice(&sealed);
+    Ok(Base64::encode_string(&stored))
+}
+
+/// Reverses [`encrypt_secret`]. Fails when the key has changed or the value
+/// was tampered with.
+pub fn decrypt_secret(stored: &str) -> Result<Vec<u8>, String> {
+    let key = encryption_key()?;
+    let mut bytes =
+        Base64::decode_vec(stored).map_err(|_| "Stored TOTP secret is not base64".to_string())?;
+    if bytes.len() < aead::NONCE_LEN {
+        return Err("Stored TOTP secret is truncated".to_string());
+    }
+
+    let mut sealed = bytes.split_off(aead::NONCE_LEN);
+    let nonce: [u8; aead::NONCE_LEN] = bytes
+        .try_into()
+        .map_err(|_| "Stored TOTP secret is truncated".to_string())?;
+    let plain = key
+        .open_in_place(
+            aead::Nonce::assume_unique_for_key(nonce),
+            aead::Aad::empty(),
+            &mut sealed,
+        )
+        .map_err(|_| "Failed to decrypt TOTP secret".to_string())?;
+    Ok(plain.to_vec())
+}
+
+/// RFC 4648 base32 without padding, the encoding authenticator apps expect.
+pub fn base32_encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(5) * 8);
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for &byte in data {
+        buffer = (buffer << 8) | u32::from(byte);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(BASE32_ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        out.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+    }
+    out
+}
+
+/// The `otpauth://` URI authenticator apps import, usually via a QR code.
+pub fn otpauth_uri(username: &str, secret: &[u8]) -> String {
+    let mut label = url::form_urlencoded::byte_serialize(format!("{ISSUER}:{username}").as_bytes())
+        .collect::<String>();
+    // Labels are path segments; `+` would not be read back as a space.
+    label = label.replace('+', "%20");
+    format!(
+        "otpauth://totp/{label}?secret={}&issuer={ISSUER}&algorithm=SHA1&digits={CODE_DIGITS}&period={STEP_SECONDS}",
+        base32_encode(secret)
+    )
+}
+
+/// The step a Unix timestamp falls in.
+pub fn step_at(unix_seconds: u64) -> u64 {
+    unix_seconds / STEP_SECONDS
+}
+
+/// The code for `step`, as RFC 4226 dynamic truncation of HMAC-SHA1.
+pub fn code_at(secret: &[u8], step: u64) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY, secret);
+    let tag = hmac::sign(&key, &step.to_be_bytes());
+    let digest = tag.as_ref();
+
+    let offset = usize::from(digest[digest.len() - 1] & 0x0f);
+    let binary = u32::from_be_bytes([
+        digest[offset] & 0x7f,
+        digest[offset + 1],
+        digest[offset + 2],
+        digest[offset + 3],
+    ]);
+    format!(
+        "{:0width$}",
+        binary % 10u32.pow(CODE_DIGITS as u32),
+        width = CODE_DIGITS
+    )
+}
+
+/// Checks `code` against the steps around `now`.
+///
+/// Returns the matching step so the caller can store it: a step at or below
+/// `last_used_step` is rejected, which makes every code single-use.
+pub fn verify_code(
+    secret: &[u8],
+    code: &str,
+    unix_seconds: u64,
+    last_used_step: Option<u64>,
+) -> Option<u64> {
+    let code: String = code.chars().filter(|c| !c.is_whitespace()).collect();
+    if code.len() != CODE_DIGITS || !code.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let current = step_at(unix_seconds);
+    let first = current.saturating_sub(ALLOWED_DRIFT_STEPS);
+    (first..=current + ALLOWED_DRIFT_STEPS)
+        .filter(|step| last_used_step.is_none_or(|last| *step > last))
+        .find(|step| {
+            let expected = code_at(secret, *step);
+            bool::from(expected.as_bytes().ct_eq(code.as_bytes()))
+        })
+}
+
+/// Generates a fresh set of recovery codes (`xxxxx-xxxxx`).
+pub fn generate_recovery_codes() -> Vec<String> {
+    (0..RECOVERY_CODE_COUNT)
+        .map(|_| {
+            let mut raw = [0u8; RECOVERY_GROUP_LEN * 2];
+            rand::fill(&mut raw[..]);
+            let chars: String = raw
+                .iter()
+                .map(|byte| RECOVERY_ALPHABET[usize::from(*byte) % RECOVERY_ALPHABET.len()] as char)
+                .collect();
+            format!(
+                "{}-{}",
+                &chars[..RECOVERY_GROUP_LEN],
+                &chars[RECOVERY_GROUP_LEN..]
+            )
+        })
+        .collect()
+}
+
+/// Digest a recovery code is stored and looked up by. Case, spaces and
+/// dashes are ignored so codes can be typed loosely.
+pub fn hash_recovery_code(code: &str) -> String {
+    let normalized: String = code
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    sha256_hex(normalized.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RFC_SECRET: &[u8] = b"12345678901234567890";
+
+    #[test]
+    fn codes_match_the_rfc_6238_sha1_vectors() {
+        // The RFC lists eight-digit codes; six-digit codes are their suffix.
+        for (time, expected) in [
+            (59, "287082"),
+            (1_111_111_109, "081804"),
+            (1_234_567_890, "005924"),
+            (2_000_000_000, "279037"),
+        ] {
+            assert_eq!(code_at(RFC_SECRET, step_at(time)), expected, "t={time}");
+        }
+    }
+
+    #[test]
+    fn verification_allows_drift_but_not_reuse() {
+        let now = 1_111_111_109;
+        let previous = code_at(RFC_SECRET, step_at(now) - 1);
+
+        let step = verify_code(RFC_SECRET, &previous, now, None).expect("drift accepted");
+        assert_eq!(step, step_at(now) - 1);
+        assert_eq!(verify_code(RFC_SECRET, &previous, now, Some(step)), None);
+        assert_eq!(
+            verify_code(
+                RFC_SECRET,
+                &code_at(RFC_SECRET, step_at(now) + 2),
+                now,
+                None
+            ),
+            None
+        );
+        assert_eq!(verify_code(RFC_SECRET, "08180", now, None), None);
+        assert_eq!(
+            verify_code(RFC_SECRET, "081 804", now, None),
+            Some(step_at(now))
+        );
+    }
+
+    #[test]
+    fn base32_and_uri_follow_the_key_uri_format() {
+        assert_eq!(base32_encode(b"foobar"), "MZXW6YTBOI");
+        assert_eq!(
+            base32_encode(RFC_SECRET),
+            "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ"
+        );
+
+        let uri = otpauth_uri("jane doe", RFC_SECRET);
+        assert!(uri.starts_with("otpauth://totp/minos%3Ajane%20doe?secret=GEZDGNBV"));
+        assert!(uri.ends_with("&issuer=minos&algorithm=SHA1&digits=6&period=30"));
+    }
+
+    #[test]
+    fn secrets_round_trip_through_encryption() {
+        let _ = init_totp_encryption_key("this_is_a_test_totp_encryption_key_0123456789");
+        let secret = generate_secret();
+
+        let stored = encrypt_secret(&secret).unwrap();
+        assert_ne!(encrypt_secret(&secret).unwrap(), stored, "nonces differ");
+        assert_eq!(decrypt_secret(&stored).unwrap(), secret);
+
+        let mut tampered = Base64::decode_vec(&stored).unwrap();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(decrypt_secret(&Base64::encode_string(&tampered)).is_err());
+    }
+
+    #[test]
+    fn recovery_codes_are_unique_and_hash_loosely() {
+        let codes = generate_recovery_codes();
+        assert_eq!(codes.len(), RECOVERY_CODE_COUNT);
+        assert_eq!(codes.iter().collect::<HashSet<_>>().len(), codes.len());
+
+        let code = &codes[0];
+        assert_eq!(
+            hash_recovery_code(code),
+            hash_recovery_code(&code.to_uppercase().replace('-', " "))
+        );
+    }
+}
@@ -0,0 +1,270 @@
+//! Sitemap
+//!
+//! `GET /sitemap.xml` lists the home page, every published page that does
+//! not opt out of indexing via `meta_robots`, the published posts on those
+//! pages and every published tutorial, with absolute links built from
+//! `PUBLIC_BASE_URL`. Without that setting there is no trustworthy origin to
+//! link to, so the sitemap answers 404.
+//!
+//! A sitemap file may hold at most 50,000 URLs. Beyond that `/sitemap.xml`
+//! becomes a sitemap index pointing at `/sitemaps/1.xml`, `/sitemaps/2.xml`
+//! and so on.
+//!
+//! Rendering walks every public document, so the result is cached for
+//! [`SITEMAP_CACHE_TTL`]. Repository writes to tutorials, pages and posts
+//! call [`invalidate`] so the next request sees the change.
+
+use crate::db::DbPool;
+use crate::handlers::common::parse_stored_timestamp;
+use crate::models::{robots_excludes_indexing, SitemapDocument};
+use crate::repositories;
+use chrono::{DateTime, SecondsFormat, Utc};
+use html_escape::encode_text as esc;
+use std::sync::{Arc, LazyLock, RwLock};
+use std::time::{Duration, Instant};
+
+/// Most URLs a single sitemap file may list (sitemaps.org protocol).
+pub const MAX_URLS_PER_SITEMAP: usize = 50_000;
+
+/// How long a rendered sitemap is served before it is rebuilt, unless a
+/// content change invalidates it first.
+pub const SITEMAP_CACHE_TTL: Duration = Duration::from_secs(600);
+
+const XML_HEADER: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n";
+const SITEMAP_NS: &str = "http://www.sitemaps.org/schemas/sitemap/0.9";
+
+type CachedSitemap = (Instant, Arc<Sitemap>);
+
+static SITEMAP_CACHE: LazyLock<RwLock<Option<CachedSitemap>>> = LazyLock::new(|| RwLock::new(None));
+
+/// One `<url>` entry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SitemapEntry {
+    /// Site-relative path, starting with `/`.
+    pub path: String,
+    pub lastmod: Option<DateTime<Utc>>,
+    pub changefreq: &'static str,
+}
+
+/// A rendered sitemap: either a single `<urlset>` in `root` and no `parts`,
+/// or a `<sitemapindex>` in `root` whose n-th child is `parts[n - 1]`.
+#[derive(Debug)]
+pub struct Sitemap {
+    pub root: String,
+    pub parts: Vec<String>,
+}
+
+impl Sitemap {
+    /// Body of `/sitemaps/{number}.xml`, numbered from 1.
+    pub fn part(&self, number: usize) -> Option<&str> {
+        number
+            .checked_sub(1)
+            .and_then(|index| self.parts.get(index))
+            .map(String::as_str)
+    }
+}
+
+/// Drops the cached sitemap so the next request rebuilds it.
+pub fn invalidate() {
+    if let Ok(mut cache) = SITEMAP_CACHE.write() {
+        *cache = None;
+    }
+}
+
+/// The sitemap for `base_url`, from the cache while it is fresh.
+pub async fn cached(pool: &DbPool, base_url: &str) -> Result<Arc<Sitemap>, sqlx::Error> {
+    if let Ok(cache) = SITEMAP_CACHE.read() {
+        if let Some((cached_at, sitemap)) = cache.as_ref() {
+            if cached_at.elapsed() < SITEMAP_CACHE_TTL {
+                return Ok(Arc::clone(sitemap));
+            }
+        }
+    }
+
+    let sitemap = Arc::new(build(pool, base_url).await?);
+    if let Ok(mut cache) = SITEMAP_CACHE.write() {
+        *cache = Some((Instant::now(), Arc::clone(&sitemap)));
+    }
+    Ok(sitemap)
+}
+
+/// Loads the public documents and renders them, bypassing the cache.
+pub async fn build(pool: &DbPool, base_url: &str) -> Result<Sitemap, sqlx::Error> {
+    let documents = repositories::sitemap::list_public_documents(pool).await?;
+    Ok(render(base_url, &entries(documents), MAX_URLS_PER_SITEMAP))
+}
+
+/// Turns documents into entries, dropping those whose robots directives
+/// exclude indexing and prepending the home page. Pages are expected to
+/// change more often than the posts and tutorials they link to.
+pub fn entries(documents: Vec<SitemapDocument>) -> Vec<SitemapEntry> {
+    let mut entries: Vec<SitemapEntry> = documents
+        .into_iter()
+        .filter(|doc| !robots_excludes_indexing(doc.meta_robots.as_deref()))
+        .map(|doc| SitemapEntry {
+            changefreq: if doc.kind == "page" {
+                "weekly"
+            } else {
+                "monthly"
+            },
+            lastmod: parse_stored_timestamp(&doc.updated_at),
+            path: doc.path,
+        })
+        .collect();
+
+    let home = SitemapEntry {
+        path: "/".to_string(),
+        lastmod: entries.iter().filter_map(|entry| entry.lastmod).max(),
+        changefreq: "daily",
+    };
+    entries.insert(0, home);
+    entries
+}
+
+/// Renders `entries` as a single `<urlset>`, or as a `<sitemapindex>` plus
+/// parts of at most `max_per_file` URLs when there are more.
+pub fn render(base_url: &str, entries: &[SitemapEntry], max_per_file: usize) -> Sitemap {
+    let max_per_file = max_per_file.max(1);
+    if entries.len() <= max_per_file {
+        return Sitemap {
+            root: render_urlset(base_url, entries),
+            parts: Vec::new(),
+        };
+    }
+
+    let chunks: Vec<&[SitemapEntry]> = entries.chunks(max_per_file).collect();
+    let mut root = format!("{XML_HEADER}<sitemapindex xmlns=\"{SITEMAP_NS}\">\n");
+    for (index, chunk) in chunks.iter().enumerate() {
+        root.push_str("  <sitemap>\n");
+        root.push_str(&format!(
+            "    <loc>{}/sitemaps/{}.xml</loc>\n",
+            esc(base_url),
+            index + 1
+        ));
+        if let Some(newest) = chunk.iter().filter_map(|entry| entry.lastmod).max() {
+            root.push_str(&format!(
+                "    <lastmod>{}</lastmod>\n",
+                format_lastmod(newest)
+            ));
+        }
+        root.push_str("  </sitemap>\n");
+    }
+    root.push_str("</sitemapindex>\n");
+
+    Sitemap {
+        root,
+        parts: chunks
+            .into_iter()
+            .map(|chunk| render_urlset(base_url, chunk))
+            .collect(),
+    }
+}
+
+fn render_urlset(base_url: &str, entries: &[SitemapEntry]) -> String {
+    let mut out = format!("{XML_HEADER}<urlset xmlns=\"{SITEMAP_NS}\">\n");
+    for entry in entries {
+        out.push_str("  <url>\n");
+        out.push_str(&format!(
+            "    <loc>{}{}</loc>\n",
+            esc(base_url),
+            esc(&entry.path)
+        ));
+        if let Some(lastmod) = entry.lastmod {
+            out.push_str(&format!(
+                "    <lastmod>{}</lastmod>\n",
+                format_lastmod(lastmod)
+            ));
+        }
+        out.push_str(&format!(
+            "    <changefreq>{}</changefreq>\n",
+            entry.changefreq
+        ));
+        out.push_str("  </url>\n");
+    }
+    out.push_str("</urlset>\n");
+    out
+}
+
+fn format_lastmod(timestamp: DateTime<Utc>) -> String {
+    timestamp.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document(kind: &str, path: &str, updated_at: &str, robots: Option<&str>) -> SitemapDocument {
+        SitemapDocument {
+            kind: kind.to_string(),
+            path: path.to_string(),
+            updated_at: updated_at.to_string(),
+            meta_robots: robots.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn entries_skip_noindex_documents_and_lead_with_the_home_page() {
+        let entries = entries(vec![
+            document("page", "/pages/about", "2026-01-02 10:00:00", None),
+            document(
+                "page",
+                "/pages/hidden",
+                "2026-03-01 10:00:00",
+                Some("noindex"),
+            ),
+            document("tutorial", "/tutorials/t1", "2026-02-03T04:05:06Z", None),
+        ]);
+
+        let paths: Vec<&str> = entries.iter().map(|entry| entry.path.as_str()).collect();
+        assert_eq!(paths, ["/", "/pages/about", "/tutorials/t1"]);
+        assert_eq!(entries[0].changefreq, "daily");
+        assert_eq!(entries[1].changefreq, "weekly");
+        assert_eq!(entries[2].changefreq, "monthly");
+        assert_eq!(entries[0].lastmod, entries[2].lastmod);
+    }
+
+    #[test]
+    fn render_writes_a_single_urlset_with_escaped_links() {
+        let entries = entries(vec![document(
+            "post",
+            "/posts/news/a&b",
+            "2026-01-02 10:00:00",
+            None,
+        )]);
+        let sitemap = render("https://blog.example.com", &entries, 10);
+
+        assert!(sitemap.parts.is_empty());
+        assert!(sitemap.root.contains("<urlset"));
+        assert!(sitemap
+            .root
+            .contains("<loc>https://blog.example.com/posts/news/a&amp;b</loc>"));
+        assert!(sitemap
+            .root
+            .contains("<lastmod>2026-01-02T10:00:00Z</lastmod>"));
+    }
+
+    #[test]
+    fn render_splits_into_an_index_above_the_limit() {
+        let entries: Vec<SitemapEntry> = (0..5)
+            .map(|n| SitemapEntry {
+                path: format!("/tutorials/{n}"),
+                lastmod: parse_stored_timestamp(&format!("2026-01-0{} 00:00:00", n + 1)),
+                changefreq: "monthly",
+            })
+            .collect();
+        let sitemap = render("https://blog.example.com", &entries, 2);
+
+        assert_eq!(sitemap.parts.len(), 3);
+        assert!(sitemap.root.contains("<sitemapindex"));
+        assert!(sitemap
+            .root
+            .contains("<loc>https://blog.example.com/sitemaps/3.xml</loc>"));
+        assert!(sitemap
+            .root
+            .contains("<lastmod>2026-01-04T00:00:00Z</lastmod>"));
+        assert!(sitemap.part(1).unwrap().contains("/tutorials/1</loc>"));
+        assert!(sitemap.part(3).unwrap().contains("/tutorials/4</loc>"));
+        assert!(sitemap.part(0).is_none());
+        assert!(sitemap.part(4).is_none());
+    }
+}
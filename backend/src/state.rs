@@ -0,0 +1,29 @@
+//! Shared Application State
+//!
+//! The state every route is served with. Handlers extract just the part
+//! they need (`State<DbPool>`, `State<Arc<dyn Store>>`) through
+//! [`FromRef`].
+
+use crate::db::DbPool;
+use crate::storage::Store;
+use axum::extract::FromRef;
+use std::sync::Arc;
+
+#[derive(Clone)]
+pub struct AppState {
+    pub pool: DbPool,
+    /// Where uploaded files are kept.
+    pub store: Arc<dyn Store>,
+}
+
+impl FromRef<AppState> for DbPool {
+    fn from_ref(state: &AppState) -> Self {
+        state.pool.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<dyn Store> {
+    fn from_ref(state: &AppState) -> Self {
+        state.store.clone()
+    }
+}
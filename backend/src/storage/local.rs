@@ -0,0 +1,101 @@
+use super::{ObjectInfo, Store, StoreError, StoredObject};
+use async_trait::async_trait;
+use axum::body::Body;
+use std::io::{ErrorKind, SeekFrom};
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tokio_util::io::ReaderStream;
+
+/// Files in a directory on local disk.
+#[derive(Debug, Clone)]
+pub struct LocalDiskStore {
+    root: PathBuf,
+}
+
+impl LocalDiskStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+#[async_trait]
+impl Store for LocalDiskStore {
+    /// Uploads are staged in the same directory, so this is an atomic
+    /// rename.
+    async fn put(&self, key: &str, source: &Path, _content_type: &str) -> Result<(), StoreError> {
+        let target = self.root.join(key);
+        if let Err(err) = fs::rename(source, &target).await {
+            let _ = fs::remove_file(source).await;
+            return Err(err.into());
+        }
+        Ok(())
+    }
+
+    async fn get(
+        &self,
+        key: &str,
+        range: Option<RangeInclusive<u64>>,
+    ) -> Result<Option<StoredObject>, StoreError> {
+        let mut file = match fs::File::open(self.root.join(key)).await {
+            Ok(file) => file,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        let Some(range) = range else {
+            let content_length = file.metadata().await?.len();
+            return Ok(Some(StoredObject {
+                body: Body::from_stream(ReaderStream::new(file)),
+                content_length,
+            }));
+        };
+        file.seek(SeekFrom::Start(*range.start())).await?;
+        let content_length = range.end() - range.start() + 1;
+        Ok(Some(StoredObject {
+            body: Body::from_stream(ReaderStream::new(file.take(content_length))),
+            content_length,
+        }))
+    }
+
+    async fn delete(&self, key: &str) -> Result<bool, StoreError> {
+        match fs::remove_file(self.root.join(key)).await {
+            Ok(()) => Ok(true),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(false),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool, StoreError> {
+        Ok(fs::try_exists(self.root.join(key)).await?)
+    }
+
+    async fn list(&self) -> Result<Vec<ObjectInfo>, StoreError> {
+        let mut entries = match fs::read_dir(&self.root).await {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err.into()),
+        };
+        let mut objects = Vec::new();
+        while let Some(entry) = entries.next_entry().await? {
+            // Symlinks and directories are not ours
+            let metadata = fs::symlink_metadata(entry.path()).await?;
+            if !metadata.is_file() {
+                continue;
+            }
+            let Ok(key) = entry.file_name().into_string() else {
+                continue;
+            };
+            objects.push(ObjectInfo {
+                key,
+                size_bytes: metadata.len(),
+                modified: metadata.modified()?.into(),
+            });
+        }
+        Ok(objects)
+    }
+
+    fn public_url(&self, key: &str) -> String {
+        format!("/uploads/{key}")
+    }
+}
@@ -0,0 +1,144 @@
+//! Upload Storage
+//!
+//! Uploaded files go through a [`Store`], selected at startup from
+//! `STORAGE_BACKEND` and carried in the application state:
+//! - [`LocalDiskStore`] keeps them in `UPLOAD_DIR`.
+//! - [`S3Store`] (cargo feature `s3`) keeps them in an S3-compatible bucket,
+//!   so several replicas can share them.
+//!
+//! Uploads are always written to and processed in `UPLOAD_DIR` first; a
+//! store then takes the finished file over with [`Store::put`]. Files are
+//! streamed in both directions, never held in memory whole. Whatever the
+//! store, they are served under `/uploads/{key}` by
+//! [`crate::handlers::upload::serve_upload`].
+
+mod local;
+#[cfg(feature = "s3")]
+mod s3;
+
+pub use local::LocalDiskStore;
+#[cfg(feature = "s3")]
+pub use s3::S3Store;
+
+use crate::config::{Config, StorageBackend};
+use async_trait::async_trait;
+use axum::body::Body;
+use chrono::{DateTime, Utc};
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::path::Path;
+use std::sync::Arc;
+
+/// A stored file, or the requested range of it, streamed.
+pub struct StoredObject {
+    pub body: Body,
+    /// Length of `body`.
+    pub content_length: u64,
+}
+
+/// A stored file as listed by [`Store::list`].
+#[derive(Debug, Clone)]
+pub struct ObjectInfo {
+    pub key: String,
+    pub size_bytes: u64,
+    pub modified: DateTime<Utc>,
+}
+
+/// Why a store operation failed.
+#[derive(Debug)]
+pub enum StoreError {
+    Io(std::io::Error),
+    /// The remote service refused or could not be reached.
+    Backend(String),
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StoreError::Io(err) => write!(f, "storage I/O error: {err}"),
+            StoreError::Backend(message) => write!(f, "storage backend error: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for StoreError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            StoreError::Io(err) => Some(err),
+            StoreError::Backend(_) => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for StoreError {
+    fn from(err: std::io::Error) -> Self {
+        StoreError::Io(err)
+    }
+}
+
+/// Where uploaded files live. Keys are plain file names such as
+/// `<id>.png`.
+#[async_trait]
+pub trait Store: Send + Sync {
+    /// Takes over the finished file at `source` as `key`; `source` is gone
+    /// afterwards, also on failure.
+    async fn put(&self, key: &str, source: &Path, content_type: &str) -> Result<(), StoreError>;
+
+    /// The file stored as `key`, if there is one; only the bytes in `range`
+    /// when one is given, which must lie within the file.
+    async fn get(
+        &self,
+        key: &str,
+        range: Option<RangeInclusive<u64>>,
+    ) -> Result<Option<StoredObject>, StoreError>;
+
+    /// Removes `key`. Returns whether there was such a file.
+    async fn delete(&self, key: &str) -> Result<bool, StoreError>;
+
+    async fn exists(&self, key: &str) -> Result<bool, StoreError>;
+
+    /// Every stored file, in no particular order.
+    async fn list(&self) -> Result<Vec<ObjectInfo>, StoreError>;
+
+    /// URL the file `key` is publicly served at.
+    fn public_url(&self, key: &str) -> String;
+}
+
+/// The store configured by `STORAGE_BACKEND`.
+pub fn from_config(config: &Config) -> Arc<dyn Store> {
+    match &config.storage {
+        StorageBackend::Local => Arc::new(LocalDiskStore::new(config.upload_dir.clone())),
+        #[cfg(feature = "s3")]
+        StorageBackend::S3(settings) => Arc::new(S3Store::new(settings.clone())),
+        // Config validation rejects this combination
+        #[cfg(not(feature = "s3"))]
+        StorageBackend::S3(_) => unreachable!("STORAGE_BACKEND=s3 needs the s3 feature"),
+    }
+}
+
+/// Whether `key` can name a stored file: a single path segment of
+/// letters, digits, `-`, `_` and `.`, not starting with a dot.
+pub fn is_valid_key(key: &str) -> bool {
+    !key.is_empty()
+        && !key.starts_with('.')
+        && key
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_are_single_plain_segments() {
+        assert!(is_valid_key(
+            "0b6f7c1e-1f55-4c0e-9d68-7f9b8d2f3a10_320.webp"
+        ));
+        assert!(!is_valid_key(""));
+        assert!(!is_valid_key(".env"));
+        assert!(!is_valid_key("../database.db"));
+        assert!(!is_valid_key("nested/file.png"));
+        assert!(!is_valid_key("file name.png"));
+    }
+}
@@ -0,0 +1,36 @@
+//! Fixtures shared by the unit tests.
+
+use crate::db::DbPool;
+use crate::security::auth;
+use sqlx::sqlite::SqlitePoolOptions;
+
+/// A fresh in-memory database with every migration applied. It holds a
+/// single connection, since each in-memory connection is a database of
+/// its own.
+pub async fn migrated_pool() -> DbPool {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .expect("create sqlite pool");
+    crate::db::migrations::run_migrations(&pool)
+        .await
+        .expect("run migrations");
+    pool
+}
+
+/// Claims for `username` with `role` that never expire, for calling
+/// handlers directly. They belong to no session, so the extractor would
+/// reject them as a bearer token.
+pub fn claims_for(username: &str, role: &str) -> auth::Claims {
+    auth::Claims {
+        sub: username.to_string(),
+        role: role.to_string(),
+        exp: usize::MAX,
+        auth_time: 0,
+        jti: String::new(),
+        uid: None,
+        remember: false,
+        sudo_until: None,
+    }
+}
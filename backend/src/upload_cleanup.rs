@@ -0,0 +1,193 @@
+//! Orphaned Upload Cleanup
+//!
+//! Deleting a post or tutorial leaves its images behind. [`clean_up`] finds
+//! recorded uploads and stored files that no content references any more
+//! (see [`repositories::uploads::find_references`]) and reports them, or
+//! deletes them when it is not a dry run. Anything younger than
+//! [`GRACE_PERIOD_HOURS`] is left alone: it may belong to an edit that
+//! hasn't been saved yet.
+//!
+//! Runs through `POST /api/admin/uploads/cleanup` and the `cleanup_uploads`
+//! binary.
+
+use crate::{
+    db::DbPool,
+    models::Upload,
+    repositories,
+    storage::{self, Store, StoreError},
+};
+use chrono::{Duration, Utc};
+use serde::Serialize;
+use std::fmt;
+
+/// Uploads and files at most this old are never cleaned up.
+pub const GRACE_PERIOD_HOURS: i64 = 24;
+
+/// A recorded upload nothing references.
+#[derive(Debug, Clone, Serialize)]
+pub struct OrphanedUpload {
+    pub id: String,
+    pub filename: String,
+    pub original_name: String,
+    /// The original and its variants.
+    pub size_bytes: u64,
+    pub created_at: String,
+}
+
+/// A stored file without a record that nothing references, e.g. one
+/// uploaded before uploads were recorded.
+#[derive(Debug, Clone, Serialize)]
+pub struct OrphanedFile {
+    pub key: String,
+    pub size_bytes: u64,
+    pub modified: String,
+}
+
+/// Outcome of one [`clean_up`] run.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CleanupReport {
+    /// Whether the orphans were only reported.
+    pub dry_run: bool,
+    pub uploads: Vec<OrphanedUpload>,
+    pub files: Vec<OrphanedFile>,
+    /// Bytes freed, or that a real run would free.
+    pub bytes: u64,
+    /// Uploads and unrecorded files inside the grace period, not checked.
+    pub skipped_recent: u64,
+}
+
+/// Why a cleanup or an upload deletion failed.
+#[derive(Debug)]
+pub enum CleanupError {
+    Database(sqlx::Error),
+    Store(StoreError),
+}
+
+impl fmt::Display for CleanupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CleanupError::Database(err) => write!(f, "database error: {err}"),
+            CleanupError::Store(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for CleanupError {}
+
+impl From<sqlx::Error> for CleanupError {
+    fn from(err: sqlx::Error) -> Self {
+        CleanupError::Database(err)
+    }
+}
+
+impl From<StoreError> for CleanupError {
+    fn from(err: StoreError) -> Self {
+        CleanupError::Store(err)
+    }
+}
+
+/// Finds unreferenced uploads and files outside the grace period and, unless
+/// `dry_run`, deletes them.
+pub async fn clean_up(
+    pool: &DbPool,
+    store: &dyn Store,
+    dry_run: bool,
+) -> Result<CleanupReport, CleanupError> {
+    let mut report = CleanupReport {
+        dry_run,
+        ..CleanupReport::default()
+    };
+
+    let candidates =
+        repositories::uploads::list_uploads_older_than(pool, GRACE_PERIOD_HOURS).await?;
+    let total = repositories::uploads::count_uploads(pool, None).await?;
+    report.skipped_recent += u64::try_from(total)
+        .unwrap_or(0)
+        .saturating_sub(candidates.len() as u64);
+    for upload in candidates {
+        if !repositories::uploads::find_references(pool, &upload.id)
+            .await?
+            .is_empty()
+        {
+            continue;
+        }
+        let orphan = OrphanedUpload {
+            size_bytes: recorded_size(&upload),
+            id: upload.id,
+            filename: upload.filename,
+            original_name: upload.original_name,
+            created_at: upload.created_at,
+        };
+        if !dry_run && remove_upload(pool, store, &orphan.id).await?.is_none() {
+            // Deleted by someone else in the meantime
+            continue;
+        }
+        report.bytes += orphan.size_bytes;
+        report.uploads.push(orphan);
+    }
+
+    // Recorded files are covered above, with their records
+    let recorded = repositories::uploads::list_recorded_filenames(pool).await?;
+    let cutoff = Utc::now() - Duration::hours(GRACE_PERIOD_HOURS);
+    for object in store.list().await? {
+        if !storage::is_valid_key(&object.key) || recorded.contains(&object.key) {
+            continue;
+        }
+        if object.modified > cutoff {
+            report.skipped_recent += 1;
+            continue;
+        }
+        // Uploads are named `<id>.<ext>` and `<id>_<width>.webp`; content
+        // refers to them by that ID
+        let id = object.key.split(['.', '_']).next().unwrap_or_default();
+        if !repositories::uploads::find_references(pool, id)
+            .await?
+            .is_empty()
+        {
+            continue;
+        }
+        if !dry_run && !store.delete(&object.key).await? {
+            continue;
+        }
+        report.bytes += object.size_bytes;
+        report.files.push(OrphanedFile {
+            key: object.key,
+            size_bytes: object.size_bytes,
+            modified: object.modified.to_rfc3339(),
+        });
+    }
+
+    Ok(report)
+}
+
+/// Deletes the upload `id`: its record and, before that is committed, its
+/// files. A file already gone is fine. Returns the deleted upload, or
+/// `None` if there is no such upload.
+pub async fn remove_upload(
+    pool: &DbPool,
+    store: &dyn Store,
+    id: &str,
+) -> Result<Option<Upload>, CleanupError> {
+    let mut tx = pool.begin().await?;
+    let Some(upload) = repositories::uploads::delete_upload(&mut tx, id).await? else {
+        return Ok(None);
+    };
+    let filenames = std::iter::once(&upload.filename)
+        .chain(upload.variants.iter().map(|variant| &variant.filename));
+    for filename in filenames {
+        if !store.delete(filename).await? {
+            tracing::warn!("Deleted upload file {} was already gone", filename);
+        }
+    }
+    tx.commit().await?;
+    Ok(Some(upload))
+}
+
+fn recorded_size(upload: &Upload) -> u64 {
+    let variants: i64 = upload
+        .variants
+        .iter()
+        .map(|variant| variant.size_bytes)
+        .sum();
+    u64::try_from(upload.size_bytes + variants).unwrap_or(0)
+}
@@ -0,0 +1,104 @@
+//! View Counting
+//!
+//! Public reads of tutorials and posts add to a daily row in `content_views`
+//! behind `GET /api/admin/stats/views`. A client is counted at most once per
+//! document and hour: the server remembers a keyed hash of
+//! (document, client IP) for the current hour and forgets all of them when
+//! the hour turns, so raw addresses are never kept.
+//!
+//! Counting never delays the read: the database write runs on a spawned
+//! task and its failure is only logged.
+
+use crate::db::DbPool;
+use chrono::Utc;
+use std::collections::hash_map::RandomState;
+use std::collections::HashSet;
+use std::hash::BuildHasher;
+use std::net::IpAddr;
+use std::sync::{Mutex, OnceLock};
+
+/// Upper bound on remembered clients per hour. Once reached, further views
+/// in that hour are counted without deduplication rather than growing the
+/// set without limit.
+const MAX_SEEN_PER_HOUR: usize = 100_000;
+
+static HASHER: OnceLock<RandomState> = OnceLock::new();
+static SEEN: Mutex<SeenViews> = Mutex::new(SeenViews::new());
+
+/// Hashes of the (document, client) pairs already counted in one hour.
+struct SeenViews {
+    hour: i64,
+    keys: Option<HashSet<u64>>,
+}
+
+impl SeenViews {
+    const fn new() -> Self {
+        Self {
+            hour: i64::MIN,
+            keys: None,
+        }
+    }
+
+    /// Returns `true` if `key` was not seen yet in `hour`.
+    fn first_in_hour(&mut self, hour: i64, key: u64) -> bool {
+        if self.hour != hour {
+            self.hour = hour;
+            self.keys = None;
+        }
+        let keys = self.keys.get_or_insert_with(HashSet::new);
+        if keys.len() >= MAX_SEEN_PER_HOUR {
+            return !keys.contains(&key);
+        }
+        keys.insert(key)
+    }
+}
+
+/// Counts one view of `entity_id` unless `client_ip` already viewed it this hour.
+pub fn record(pool: &DbPool, entity_type: &'static str, entity_id: &str, client_ip: IpAddr) {
+    let now = Utc::now();
+    let key = HASHER
+        .get_or_init(RandomState::new)
+        .hash_one((entity_type, entity_id, client_ip));
+    let first = SEEN
+        .lock()
+        .map(|mut seen| seen.first_in_hour(now.timestamp().div_euclid(3600), key))
+        .unwrap_or(true);
+    if !first {
+        return;
+    }
+
+    let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+        return;
+    };
+    let pool = pool.clone();
+    let entity_id = entity_id.to_string();
+    let day = now.format("%Y-%m-%d").to_string();
+    runtime.spawn(async move {
+        if let Err(err) =
+            crate::repositories::views::increment(&pool, entity_type, &entity_id, &day).await
+        {
+            tracing::warn!(
+                "Failed to record view of {} {}: {}",
+                entity_type,
+                entity_id,
+                err
+            );
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clients_are_counted_once_per_hour() {
+        let mut seen = SeenViews::new();
+
+        assert!(seen.first_in_hour(10, 1));
+        assert!(!seen.first_in_hour(10, 1));
+        assert!(seen.first_in_hour(10, 2));
+        assert!(seen.first_in_hour(11, 1));
+        assert!(!seen.first_in_hour(11, 1));
+    }
+}
@@ -0,0 +1,185 @@
+//! Boot-time Cache Warmup
+//!
+//! After a deploy the first visitors otherwise pay for cold caches: empty
+//! in-process caches and an SQLite page cache that has not read the FTS
+//! index yet. With `WARMUP=true`, [`run`] executes a few representative reads
+//! after migrations and before the listener starts, timing each step.
+//!
+//! Warmup is best effort. A failing step is logged and skipped, and the whole
+//! run is bounded by [`WARMUP_TIMEOUT`] so a broken index cannot stall boot.
+
+use crate::{db::DbPool, handlers::content_sections, repositories};
+use serde::Serialize;
+use std::future::Future;
+use std::sync::OnceLock;
+use tokio::time::{timeout_at, Instant};
+
+/// Upper bound on the whole warmup run.
+pub const WARMUP_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Term used for the representative full-text query.
+const WARMUP_SEARCH_TERM: &str = "linux";
+
+static REPORT: OnceLock<WarmupReport> = OnceLock::new();
+
+/// Timing of a single warmup step.
+#[derive(Debug, Clone, Serialize)]
+pub struct WarmupStep {
+    pub name: &'static str,
+    pub duration_ms: u64,
+    pub ok: bool,
+}
+
+/// Outcome of a warmup run, logged at startup and served by
+/// `/api/health/ready`.
+#[derive(Debug, Clone, Serialize)]
+pub struct WarmupReport {
+    pub total_ms: u64,
+    pub timed_out: bool,
+    pub steps: Vec<WarmupStep>,
+}
+
+/// The report of this process's warmup, if one ran.
+pub fn report() -> Option<&'static WarmupReport> {
+    REPORT.get()
+}
+
+/// Runs every warmup step, logs the report and stores it for [`report`].
+pub async fn run(pool: &DbPool) -> &'static WarmupReport {
+    let started = Instant::now();
+    let deadline = started + WARMUP_TIMEOUT;
+    let mut report = WarmupReport {
+        total_ms: 0,
+        timed_out: false,
+        steps: Vec::new(),
+    };
+
+    let steps_completed = step(&mut report, deadline, "fts_query", search(pool)).await
+        && step(
+            &mut report,
+            deadline,
+            "published_pages",
+            repositories::pages::list_published_pages_cached(pool),
+        )
+        .await
+        && step(&mut report, deadline, "site_content", site_content(pool)).await;
+
+    report.timed_out = !steps_completed;
+    report.total_ms = started.elapsed().as_millis() as u64;
+
+    for entry in &report.steps {
+        tracing::info!(
+            step = entry.name,
+            duration_ms = entry.duration_ms,
+            ok = entry.ok,
+            "Warmup step finished"
+        );
+    }
+    tracing::info!(
+        total_ms = report.total_ms,
+        timed_out = report.timed_out,
+        "Warmup finished"
+    );
+
+    REPORT.get_or_init(|| report)
+}
+
+/// Runs one step against the shared deadline. Returns `false` once the
+/// deadline has passed so the remaining steps are skipped.
+async fn step<T>(
+    report: &mut WarmupReport,
+    deadline: Instant,
+    name: &'static str,
+    work: impl Future<Output = Result<T, sqlx::Error>>,
+) -> bool {
+    let started = Instant::now();
+    let outcome = timeout_at(deadline, work).await;
+    let duration_ms = started.elapsed().as_millis() as u64;
+
+    let (ok, in_time) = match outcome {
+        Ok(Ok(_)) => (true, true),
+        Ok(Err(err)) => {
+            tracing::warn!(step = name, "Warmup step failed: {}", err);
+            (false, true)
+        }
+        Err(_) => {
+            tracing::warn!(step = name, "Warmup timed out after {:?}", WARMUP_TIMEOUT);
+            (false, false)
+        }
+    };
+
+    report.steps.push(WarmupStep {
+        name,
+        duration_ms,
+        ok,
+    });
+    in_time
+}
+
+/// Same shape as the public search query, so SQLite reads the index pages
+/// real searches will need.
+async fn search(pool: &DbPool) -> Result<usize, sqlx::Error> {
+    let rows: Vec<(String,)> = sqlx::query_as(concat!(
+        "SELECT t.id FROM tutorials t ",
+        "INNER JOIN tutorials_fts ON t.id = tutorials_fts.tutorial_id ",
+        "WHERE tutorials_fts MATCH ? ORDER BY bm25(tutorials_fts) LIMIT 20"
+    ))
+    .bind(format!("{WARMUP_SEARCH_TERM}*"))
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.len())
+}
+
+async fn site_content(pool: &DbPool) -> Result<(), sqlx::Error> {
+    for section in content_sections::registered_sections(pool).await?.iter() {
+        repositories::content::fetch_site_content_by_section_cached(pool, section).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    #[tokio::test]
+    async fn warmup_runs_every_step_against_a_fresh_database() {
+        let pool = crate::test_support::migrated_pool().await;
+
+        let report = run(&pool).await;
+        assert!(!report.timed_out);
+        let names: Vec<_> = report.steps.iter().map(|step| step.name).collect();
+        assert_eq!(names, ["fts_query", "published_pages", "site_content"]);
+        assert!(report.steps.iter().all(|step| step.ok), "{report:?}");
+    }
+
+    #[tokio::test]
+    async fn failing_step_does_not_abort_warmup() {
+        // No migrations, so the query fails; the step is still recorded.
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .expect("create sqlite pool");
+
+        let mut report = WarmupReport {
+            total_ms: 0,
+            timed_out: false,
+            steps: Vec::new(),
+        };
+        let deadline = Instant::now() + WARMUP_TIMEOUT;
+        assert!(step(&mut report, deadline, "fts_query", search(&pool)).await);
+        assert!(!report.steps[0].ok);
+
+        let expired = Instant::now();
+        assert!(
+            !step(
+                &mut report,
+                expired,
+                "late",
+                std::future::pending::<Result<(), sqlx::Error>>()
+            )
+            .await
+        );
+    }
+}
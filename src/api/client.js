@@ -212,6 +212,7 @@ class ApiClient {
           payload?.error || payload?.message || response.statusText || 'Request failed',
         )
         error.status = response.status
+        error.code = payload?.code
         throw error
       }
       cleanup()
@@ -296,11 +297,16 @@ class ApiClient {
     return this.request(endpoint, options)
   }
 
+  async getCommentFormToken(options = {}) {
+    return this.request('/comments/token', options)
+  }
+
   async createPostComment(postId, content, author = null, options = {}) {
+    const { formToken, ...requestOptions } = options
     return this.request(`/posts/${encodeURIComponent(postId)}/comments`, {
       method: 'POST',
-      body: { content, author },
-      ...options,
+      body: { content, author, form_token: formToken },
+      ...requestOptions,
     })
   }
 
@@ -337,6 +343,36 @@ class ApiClient {
       ...options,
     })
   }
+  async validateSiteContentSection(section, content, options = {}) {
+    return this.request(`/content/${encodeURIComponent(section)}?dry_run=true`, {
+      method: 'PUT',
+      body: { content },
+      ...options,
+    })
+  }
+  async exportContentBundle(params = {}, options = {}) {
+    const query = new URLSearchParams(params).toString()
+    return this.request(`/admin/content/export${query ? `?${query}` : ''}`, options)
+  }
+  async importContentBundle(bundle, mode = 'skip_existing', options = {}) {
+    return this.request(`/admin/content/import?mode=${encodeURIComponent(mode)}`, {
+      method: 'POST',
+      body: bundle,
+      ...options,
+    })
+  }
+  async listSiteContentRevisions(section, options = {}) {
+    return this.request(`/content/${encodeURIComponent(section)}/revisions`, options)
+  }
+  async getSiteContentRevision(section, id, options = {}) {
+    return this.request(`/content/${encodeURIComponent(section)}/revisions/${id}`, options)
+  }
+  async restoreSiteContentRevision(section, id, options = {}) {
+    return this.request(`/content/${encodeURIComponent(section)}/revisions/${id}/restore`, {
+      method: 'POST',
+      ...options,
+    })
+  }
   async listPages(options = {}) {
     return this.request('/pages', options)
   }
@@ -357,6 +393,13 @@ class ApiClient {
       ...options,
     })
   }
+  async validatePageUpdate(id, payload, options = {}) {
+    return this.request(`/pages/${encodeURIComponent(id)}?dry_run=true`, {
+      method: 'PUT',
+      body: payload,
+      ...options,
+    })
+  }
   async deletePage(id, options = {}) {
     return this.request(`/pages/${encodeURIComponent(id)}`, {
       method: 'DELETE',
@@ -389,6 +432,21 @@ class ApiClient {
       ...options,
     })
   }
+  async restorePage(id, options = {}) {
+    return this.request(`/pages/${encodeURIComponent(id)}/restore`, {
+      method: 'POST',
+      ...options,
+    })
+  }
+  async restorePost(id, options = {}) {
+    return this.request(`/posts/${encodeURIComponent(id)}/restore`, {
+      method: 'POST',
+      ...options,
+    })
+  }
+  async listTrash(options = {}) {
+    return this.request('/admin/trash', options)
+  }
   async getPublishedPage(slug, options = {}) {
     return this.request(`/public/pages/${encodeURIComponent(slug)}`, options)
   }
@@ -405,20 +463,81 @@ class ApiClient {
       ...options,
     })
   }
+  async listPublishedPosts(pageSlug, params = {}, options = {}) {
+    const query = new URLSearchParams(params).toString()
+    const endpoint = `/public/pages/${encodeURIComponent(pageSlug)}/posts${query ? `?${query}` : ''}`
+    return this.request(endpoint, options)
+  }
+  async getPostArchive(pageSlug, options = {}) {
+    return this.request(`/public/pages/${encodeURIComponent(pageSlug)}/archive`, options)
+  }
+  async listArchivePosts(pageSlug, year, month, params = {}, options = {}) {
+    const query = new URLSearchParams(params).toString()
+    const endpoint = `/public/pages/${encodeURIComponent(pageSlug)}/archive/${year}/${month}`
+    return this.request(`${endpoint}${query ? `?${query}` : ''}`, options)
+  }
   async getPublishedPost(pageSlug, postSlug, options = {}) {
     return this.request(
       `/public/pages/${encodeURIComponent(pageSlug)}/posts/${encodeURIComponent(postSlug)}`,
       options,
     )
   }
-  async uploadImage(file, options = {}) {
+  async uploadImage(file, { alt, caption } = {}, options = {}) {
     const formData = new FormData()
     formData.append('file', file)
+    if (alt) formData.append('alt', alt)
+    if (caption) formData.append('caption', caption)
     return this.request('/upload', {
       method: 'POST',
       body: formData,
       ...options,
     })
   }
+  async fetchUpload(url, options = {}) {
+    return this.request('/admin/uploads/fetch', {
+      method: 'POST',
+      body: { url },
+      ...options,
+    })
+  }
+  async listUploads({ q, limit, offset } = {}, options = {}) {
+    const params = new URLSearchParams()
+    if (q) params.set('q', q)
+    if (limit != null) params.set('limit', String(limit))
+    if (offset != null) params.set('offset', String(offset))
+    const query = params.toString()
+    return this.request(`/admin/uploads${query ? `?${query}` : ''}`, options)
+  }
+  async cleanupUploads({ dryRun = true } = {}, options = {}) {
+    return this.request(`/admin/uploads/cleanup?dry_run=${dryRun ? 'true' : 'false'}`, {
+      method: 'POST',
+      ...options,
+    })
+  }
+  async updateUpload(id, { alt, caption }, options = {}) {
+    return this.request(`/admin/uploads/${encodeURIComponent(id)}`, {
+      method: 'PUT',
+      body: { alt, caption },
+      ...options,
+    })
+  }
+  async deleteUpload(id, options = {}) {
+    return this.request(`/admin/uploads/${encodeURIComponent(id)}`, {
+      method: 'DELETE',
+      ...options,
+    })
+  }
+  async createBackup(options = {}) {
+    return this.request('/admin/backup', {
+      method: 'POST',
+      ...options,
+    })
+  }
+  async listBackups(options = {}) {
+    return this.request('/admin/backups', options)
+  }
+  backupDownloadUrl(name) {
+    return `${API_BASE_URL}/admin/backups/${encodeURIComponent(name)}`
+  }
 }
 export const api = new ApiClient()
@@ -35,6 +35,24 @@ const Comments = ({ tutorialId, postId }) => {
 
   const isPost = Boolean(postId)
 
+  // Guests must send back a token fetched when the form was shown
+  const formTokenRef = useRef(null)
+  const refreshFormToken = useCallback(async () => {
+    try {
+      const data = await api.getCommentFormToken()
+      formTokenRef.current = data?.token ?? null
+    } catch (error) {
+      console.error('Failed to fetch comment form token:', error)
+      formTokenRef.current = null
+    }
+  }, [])
+
+  useEffect(() => {
+    if (isPost && !isAuthenticated) {
+      refreshFormToken()
+    }
+  }, [isPost, isAuthenticated, contextId, refreshFormToken])
+
   // Close emoji picker when clicking outside
   useEffect(() => {
     const handleClickOutside = (event) => {
@@ -121,7 +139,10 @@ const Comments = ({ tutorialId, postId }) => {
     setIsLoading(true)
     try {
       if (isPost) {
-        await api.createPostComment(contextId, newComment, isAuthenticated ? null : guestName)
+        await api.createPostComment(contextId, newComment, isAuthenticated ? null : guestName, {
+          formToken: isAuthenticated ? undefined : formTokenRef.current,
+        })
+        if (!isAuthenticated) refreshFormToken()
       } else {
         await api.createComment(contextId, newComment)
       }
@@ -3,11 +3,13 @@ import { ArrowUpRight, CalendarDays, Clock3 } from 'lucide-react'
 import PropTypes from 'prop-types'
 import { formatDate, normalizeSlug, buildPreviewText } from '../../utils/postUtils'
 
-const estimateReadingTime = (text) => {
-  const wordCount = String(text || '')
-    .trim()
-    .split(/\s+/)
-    .filter(Boolean).length
+const estimateReadingTime = (text, knownWordCount) => {
+  const wordCount = Number.isFinite(knownWordCount)
+    ? knownWordCount
+    : String(text || '')
+        .trim()
+        .split(/\s+/)
+        .filter(Boolean).length
   return Math.max(2, Math.ceil(wordCount / 200))
 }
 
@@ -65,7 +67,7 @@ tracking-[0.16em] text-[#171713]/50`}
 tracking-[0.14em] text-[#171713]/45`}
         >
           <Clock3 className="h-3.5 w-3.5" />{' '}
-          {estimateReadingTime(post.content_markdown || previewText)} Min. Lesezeit
+          {estimateReadingTime(post.content_markdown || previewText, post.word_count)} Min. Lesezeit
         </span>
         {href && (
           <span
@@ -9,6 +9,7 @@ import 'katex/dist/katex.min.css'
 import CodeBlock from '../ui/CodeBlock'
 import remarkMergeInlineParagraphs from '../../utils/remarkMergeInlineParagraphs'
 import remarkGithubAlerts from '../../utils/remarkGithubAlerts'
+import remarkHeadingIds from '../../utils/remarkHeadingIds'
 import { AlertCircle, AlertTriangle, Info, Lightbulb, MessageCircle } from 'lucide-react'
 const mergeClassNames = (...classes) => classes.filter(Boolean).join(' ')
 const headingClasses = {
@@ -21,8 +22,15 @@ const headingClasses = {
 }
 const MarkdownRenderer = ({ content, className = '', withBreaks = false }) => {
   const remarkPlugins = withBreaks
-    ? [remarkMath, remarkGfm, remarkMergeInlineParagraphs, remarkGithubAlerts, remarkBreaks]
-    : [remarkMath, remarkGfm, remarkMergeInlineParagraphs, remarkGithubAlerts]
+    ? [
+        remarkMath,
+        remarkGfm,
+        remarkMergeInlineParagraphs,
+        remarkGithubAlerts,
+        remarkHeadingIds,
+        remarkBreaks,
+      ]
+    : [remarkMath, remarkGfm, remarkMergeInlineParagraphs, remarkGithubAlerts, remarkHeadingIds]
   return (
     <div
       className={mergeClassNames('markdown-renderer text-gray-700 dark:text-slate-200', className)}
@@ -2,7 +2,7 @@ import { useMemo, useState, useRef } from 'react'
 import PropTypes from 'prop-types'
 import { AlertCircle, FileText, RefreshCw, X, Image as ImageIcon, Loader2 } from 'lucide-react'
 import { sanitizeSlug, isValidSlug } from '../../utils/slug'
-import { sanitizeInteger } from './formUtils'
+import { markdownImage as toMarkdownImage, sanitizeInteger } from './formUtils'
 import { api } from '../../api/client'
 
 const formatDateTimeLocal = (value) => {
@@ -93,7 +93,7 @@ const PostForm = ({ mode, initialData, onSubmit, onCancel, submitting }) => {
     try {
       const data = await api.uploadImage(file)
       const imageUrl = data.url
-      const markdownImage = `\n![${file.name}](${imageUrl})\n`
+      const markdownImage = `\n${toMarkdownImage(imageUrl, data.alt || file.name, data.caption)}\n`
 
       const textarea = textareaRef.current
       if (textarea) {
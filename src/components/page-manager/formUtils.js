@@ -18,3 +18,12 @@ export const sanitizeInteger = (value, fallback = 0) => {
   if (Number.isNaN(parsed)) return fallback
   return parsed
 }
+
+// Markdown image for an upload; its alt text and caption are plain text, so
+// characters Markdown or HTML would interpret are escaped.
+export const markdownImage = (url, alt, caption) => {
+  const escape = (text) =>
+    text.replace(/[\\[\]()"<>&*_`]/g, (char) => `\\${char}`).replace(/\s+/g, ' ')
+  const title = caption ? ` "${escape(caption)}"` : ''
+  return `![${escape(alt)}](${url}${title})`
+}
@@ -89,7 +89,7 @@ const SearchBar = ({ onClose }) => {
           cacheBust: false,
           signal: controller.signal,
         })
-        setResults(Array.isArray(data) ? data : [])
+        setResults(Array.isArray(data?.items) ? data.items : [])
       } catch (error) {
         if (error.name !== 'AbortError') {
           console.error('Search failed:', error)
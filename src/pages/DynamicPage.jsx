@@ -158,7 +158,7 @@ dark:from-slate-950 dark:via-slate-900 dark:to-slate-950 pb-16`}
               title={postsTitle}
               emptyTitle={postsEmptyTitle}
               emptyMessage={postsEmptyMessage}
-              countLabel={formatPostsCount(posts.length)}
+              countLabel={formatPostsCount(pageData?.posts_total ?? posts.length)}
               pageSlug={normalizedSlug}
             />
           </div>
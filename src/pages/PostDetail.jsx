@@ -1,7 +1,7 @@
 import { useEffect, useMemo, useState } from 'react'
 import { Helmet } from 'react-helmet-async'
 import { ArrowLeft, Asterisk, CalendarDays, Clock3, Loader2, Share2 } from 'lucide-react'
-import { Link, useParams } from 'react-router-dom'
+import { Link, useNavigate, useParams } from 'react-router-dom'
 import { api } from '../api/client'
 import MarkdownRenderer from '../components/markdown/MarkdownRenderer'
 import { formatDate } from '../utils/postUtils'
@@ -17,6 +17,7 @@ const readingTime = (content) => {
 /** Editorial article view matching the public one-page blog design. */
 const PostDetail = () => {
   const { pageSlug, postSlug } = useParams()
+  const navigate = useNavigate()
   const [post, setPost] = useState(null)
   const [error, setError] = useState(null)
   const [shareLabel, setShareLabel] = useState('Teilen')
@@ -30,7 +31,12 @@ const PostDetail = () => {
         const data = await api.getPublishedPost(pageSlug, postSlug, {
           signal: controller.signal,
         })
-        if (!controller.signal.aborted) setPost(data?.post || data)
+        if (controller.signal.aborted) return
+        // Old link to a renamed post: move to its current address
+        if (data?.canonical_slug && data.canonical_slug !== postSlug) {
+          navigate(`/posts/${pageSlug}/${data.canonical_slug}`, { replace: true })
+        }
+        setPost(data?.post || data)
       } catch (loadError) {
         if (!controller.signal.aborted) setError(loadError)
       }
@@ -38,7 +44,7 @@ const PostDetail = () => {
 
     loadPost()
     return () => controller.abort()
-  }, [pageSlug, postSlug])
+  }, [navigate, pageSlug, postSlug])
 
   const minutes = useMemo(() => readingTime(post?.content_markdown), [post?.content_markdown])
 
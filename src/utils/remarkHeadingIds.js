@@ -0,0 +1,38 @@
+import { visit } from 'unist-util-visit'
+
+// Mirrors the backend's heading anchors (backend/src/markdown/toc.rs) so the
+// `toc` entries served with tutorials and posts link to these ids.
+const RE_STRIP = /[^\p{Alphabetic}\p{N}_\- ]/gu
+
+export const createHeadingSlugger = () => {
+  const occurrences = new Map()
+  return (text) => {
+    const original = text.toLowerCase().replace(RE_STRIP, '').replace(/ /g, '-')
+    let slug = original
+    while (occurrences.has(slug)) {
+      const count = occurrences.get(original) + 1
+      occurrences.set(original, count)
+      slug = `${original}-${count}`
+    }
+    occurrences.set(slug, 0)
+    return slug
+  }
+}
+
+const plainText = (node) => {
+  if (node.type === 'text' || node.type === 'inlineCode') return node.value
+  if (node.type === 'image') return node.alt || ''
+  return (node.children || []).map(plainText).join('')
+}
+
+export default function remarkHeadingIds() {
+  return (tree) => {
+    const slug = createHeadingSlugger()
+    visit(tree, 'heading', (node) => {
+      if (!node.data) node.data = {}
+      if (!node.data.hProperties) node.data.hProperties = {}
+
+      node.data.hProperties.id = slug(plainText(node))
+    })
+  }
+}